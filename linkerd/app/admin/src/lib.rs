@@ -6,5 +6,5 @@ mod pprof;
 mod server;
 mod stack;
 
-pub use self::server::{Admin, Latch, Readiness};
+pub use self::server::{Admin, Latch, Policies, Readiness};
 pub use self::stack::{Config, Task};
@@ -8,6 +8,14 @@
 //! * `PUT /proxy-log-level` -- sets a new tracing filter.
 //! * `GET /tasks` -- returns a dump of spawned Tokio tasks (when enabled by the
 //!   tracing configuration).
+//! * `GET /policies/inbound.json` -- returns the server policies for each inbound
+//!   port.
+//! * `GET /policies/outbound.json` -- returns the client policies for each
+//!   discovered outbound target.
+//! * `GET /balancers.json` -- returns the endpoints of each outbound HTTP
+//!   balancer with their load and circuit breaker state.
+//! * `GET /connections.json` -- returns the connections open on the inbound and
+//!   outbound listeners.
 //! * `POST /shutdown` -- shuts down the proxy.
 
 use futures::future::{self, TryFutureExt};
@@ -15,8 +23,11 @@ use http::StatusCode;
 use linkerd_app_core::{
     metrics::{self as metrics, legacy::FmtMetrics},
     proxy::http::{Body, BoxBody, ClientHandle, Request, Response},
-    trace, Error, Result,
+    trace,
+    transport::Connections,
+    Error, Result,
 };
+use linkerd_app_outbound::http::concrete::Balancers;
 use std::{
     future::Future,
     pin::Pin,
@@ -24,11 +35,17 @@ use std::{
 };
 use tokio::sync::mpsc;
 
+mod balancers;
+mod connections;
 mod json;
 mod log;
+mod policy;
 mod readiness;
 
-pub use self::readiness::{Latch, Readiness};
+pub use self::{
+    policy::Policies,
+    readiness::{Latch, Readiness},
+};
 
 #[derive(Clone)]
 pub struct Admin<M> {
@@ -37,6 +54,9 @@ pub struct Admin<M> {
     ready: Readiness,
     shutdown_tx: mpsc::UnboundedSender<()>,
     enable_shutdown: bool,
+    policies: Policies,
+    balancers: Balancers,
+    connections: Connections,
     #[cfg(feature = "pprof")]
     pprof: Option<crate::pprof::Pprof>,
 }
@@ -57,12 +77,30 @@ impl<M> Admin<M> {
             shutdown_tx,
             enable_shutdown,
             tracing,
+            policies: Policies::default(),
+            balancers: Balancers::default(),
+            connections: Connections::default(),
 
             #[cfg(feature = "pprof")]
             pprof: None,
         }
     }
 
+    pub fn with_policies(mut self, policies: Policies) -> Self {
+        self.policies = policies;
+        self
+    }
+
+    pub fn with_balancers(mut self, balancers: Balancers) -> Self {
+        self.balancers = balancers;
+        self
+    }
+
+    pub fn with_connections(mut self, connections: Connections) -> Self {
+        self.connections = connections;
+        self
+    }
+
     #[cfg(feature = "pprof")]
     pub fn with_profiling(mut self, enabled: bool) -> Self {
         self.pprof = enabled.then_some(crate::pprof::Pprof);
@@ -269,6 +307,53 @@ where
 
             "/env.json" => Box::pin(future::ok(Self::env_rsp(req))),
 
+            "/policies/inbound.json" | "/policies/outbound.json" => {
+                if !Self::client_is_localhost(&req) {
+                    return Box::pin(future::ok(Self::forbidden_not_localhost()));
+                }
+                if req.method() != http::Method::GET {
+                    return Box::pin(future::ok(Self::method_not_allowed()));
+                }
+                if let Err(not_acceptable) = json::accepts_json(&req) {
+                    return Box::pin(future::ok(not_acceptable));
+                }
+
+                let rsp = if req.uri().path() == "/policies/inbound.json" {
+                    self.policies.inbound_rsp()
+                } else {
+                    self.policies.outbound_rsp()
+                };
+                Box::pin(future::ok(rsp))
+            }
+
+            "/balancers.json" => {
+                if !Self::client_is_localhost(&req) {
+                    return Box::pin(future::ok(Self::forbidden_not_localhost()));
+                }
+                if req.method() != http::Method::GET {
+                    return Box::pin(future::ok(Self::method_not_allowed()));
+                }
+                if let Err(not_acceptable) = json::accepts_json(&req) {
+                    return Box::pin(future::ok(not_acceptable));
+                }
+
+                Box::pin(future::ok(balancers::rsp(&self.balancers)))
+            }
+
+            "/connections.json" => {
+                if !Self::client_is_localhost(&req) {
+                    return Box::pin(future::ok(Self::forbidden_not_localhost()));
+                }
+                if req.method() != http::Method::GET {
+                    return Box::pin(future::ok(Self::method_not_allowed()));
+                }
+                if let Err(not_acceptable) = json::accepts_json(&req) {
+                    return Box::pin(future::ok(not_acceptable));
+                }
+
+                Box::pin(future::ok(connections::rsp(&self.connections, &req)))
+            }
+
             "/shutdown" => {
                 if req.method() == http::Method::POST {
                     if Self::client_is_localhost(&req) {
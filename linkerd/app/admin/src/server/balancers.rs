@@ -0,0 +1,112 @@
+//! Dumps the endpoints of each outbound HTTP balancer along with their load
+//! and circuit breaker state.
+
+use super::{
+    json,
+    policy::{client_meta, millis},
+};
+use linkerd_app_core::proxy::{
+    balance::registry::{EndpointSnapshot, GateState, LoadSample, Trip},
+    http::BoxBody,
+};
+use linkerd_app_outbound::http::concrete::Balancers;
+use serde_json::{json, Value};
+use std::time::UNIX_EPOCH;
+
+pub(super) fn rsp(balancers: &Balancers) -> http::Response<BoxBody> {
+    json::json_rsp(&json!({ "balancers": dump(balancers) }))
+}
+
+fn dump(balancers: &Balancers) -> Vec<Value> {
+    let mut balancers = balancers.snapshot();
+    // Order balancers by parent and backend so that dumps are stable.
+    balancers.sort_by_cached_key(|b| ((*b.key.0).to_string(), (*b.key.1).to_string()));
+    balancers
+        .iter()
+        .map(|b| {
+            json!({
+                "parent": client_meta(&b.key.0),
+                "backend": client_meta(&b.key.1),
+                "endpoints": b.endpoints.iter().map(endpoint).collect::<Vec<_>>(),
+            })
+        })
+        .collect()
+}
+
+fn endpoint(ep: &EndpointSnapshot) -> Value {
+    json!({
+        "addr": ep.addr.to_string(),
+        "labels": &*ep.labels,
+        "load": ep.load.as_ref().map(load),
+        "gate": ep.gate.map(gate),
+        "last_trip": ep.last_trip.as_ref().map(trip),
+    })
+}
+
+fn load(sample: &LoadSample) -> Value {
+    json!({
+        "rtt_ms": sample.rtt.map(|rtt| rtt.as_nanos() as f64 / 1_000_000.0),
+        "in_flight": sample.in_flight,
+        "load": sample.load,
+    })
+}
+
+fn gate(state: GateState) -> &'static str {
+    match state {
+        GateState::Open => "open",
+        GateState::Probing => "probing",
+        GateState::Shut => "shut",
+    }
+}
+
+fn trip(trip: &Trip) -> Value {
+    let at = trip.at.duration_since(UNIX_EPOCH).unwrap_or_default();
+    json!({
+        "reason": trip.reason,
+        "at_unix_ms": millis(at),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{sync::Arc, time::Duration};
+
+    #[test]
+    fn dumps_endpoint_state() {
+        let ep = EndpointSnapshot {
+            addr: ([192, 0, 2, 1], 8080).into(),
+            labels: Arc::new([("pod".to_string(), "web-0".to_string())].into()),
+            load: Some(LoadSample {
+                load: 0.04,
+                rtt: Some(Duration::from_millis(20)),
+                in_flight: Some(1),
+            }),
+            gate: Some(GateState::Shut),
+            last_trip: Some(Trip {
+                reason: "consecutive_failures",
+                at: UNIX_EPOCH + Duration::from_secs(1),
+            }),
+        };
+
+        let dump = endpoint(&ep);
+        assert_eq!(dump["addr"], "192.0.2.1:8080");
+        assert_eq!(dump["labels"]["pod"], "web-0");
+        assert_eq!(dump["load"]["rtt_ms"], 20.0);
+        assert_eq!(dump["load"]["in_flight"], 1);
+        assert_eq!(dump["load"]["load"], 0.04);
+        assert_eq!(dump["gate"], "shut");
+        assert_eq!(dump["last_trip"]["reason"], "consecutive_failures");
+        assert_eq!(dump["last_trip"]["at_unix_ms"], 1_000);
+
+        let dump = endpoint(&EndpointSnapshot {
+            load: None,
+            gate: None,
+            last_trip: None,
+            ..ep
+        });
+        assert!(dump["load"].is_null());
+        assert!(dump["gate"].is_null());
+        assert!(dump["last_trip"].is_null());
+    }
+}
@@ -0,0 +1,245 @@
+//! Dumps the connections currently open on the proxy's inbound and outbound
+//! listeners.
+//!
+//! The dump may be filtered with query parameters:
+//!
+//! * `direction` -- `inbound` or `outbound`.
+//! * `protocol` -- `http1`, `http2`, or `opaque`.
+//! * `peer` -- the peer's IP address, optionally with a port.
+//! * `identity` -- the peer's mTLS identity.
+
+use super::{json, policy::millis};
+use http::StatusCode;
+use linkerd_app_core::{
+    proxy::http::BoxBody,
+    tls,
+    transport::{
+        connections::{ConnectionSnapshot, Connections, Direction, Protocol},
+        ClientAddr, Remote,
+    },
+};
+use serde_json::{json, Value};
+use std::net::{IpAddr, SocketAddr};
+
+#[derive(Debug, Default)]
+struct Filter {
+    direction: Option<Direction>,
+    protocol: Option<Protocol>,
+    peer: Option<Peer>,
+    identity: Option<String>,
+}
+
+#[derive(Debug)]
+enum Peer {
+    Ip(IpAddr),
+    Addr(SocketAddr),
+}
+
+pub(super) fn rsp<B>(connections: &Connections, req: &http::Request<B>) -> http::Response<BoxBody> {
+    let filter = match req.uri().query().map(Filter::parse).transpose() {
+        Ok(filter) => filter.unwrap_or_default(),
+        Err(error) => return json::json_error_rsp(error, StatusCode::BAD_REQUEST),
+    };
+    json::json_rsp(&json!({ "connections": dump(connections, &filter) }))
+}
+
+fn dump(connections: &Connections, filter: &Filter) -> Vec<Value> {
+    let mut connections = connections
+        .snapshot()
+        .into_iter()
+        .filter(|c| filter.matches(c))
+        .collect::<Vec<_>>();
+    // List the oldest connections first so that dumps are stable.
+    connections.sort_by(|a, b| b.age.cmp(&a.age));
+    connections.iter().map(connection).collect()
+}
+
+fn connection(conn: &ConnectionSnapshot) -> Value {
+    json!({
+        "direction": direction(conn.direction),
+        "peer": conn.client_addr.to_string(),
+        "orig_dst": conn.orig_dst_addr.to_string(),
+        "tls": conn.tls.as_ref().map(tls),
+        "protocol": conn.protocol.map(protocol),
+        "age_ms": millis(conn.age),
+        "bytes_read": conn.bytes_read,
+        "bytes_written": conn.bytes_written,
+        "streams": conn.streams,
+    })
+}
+
+fn tls(tls: &tls::ConditionalServerTlsLabels) -> Value {
+    match tls {
+        tls::ConditionalServerTlsLabels::Some(tls::ServerTlsLabels::Established { client_id }) => {
+            json!({
+                "status": "established",
+                "client_id": client_id.as_ref().map(ToString::to_string),
+            })
+        }
+        tls::ConditionalServerTlsLabels::Some(tls::ServerTlsLabels::Passthru { sni }) => json!({
+            "status": "passthru",
+            "sni": sni.to_string(),
+        }),
+        tls::ConditionalServerTlsLabels::None(reason) => json!({
+            "status": "none",
+            "reason": reason.to_string(),
+        }),
+    }
+}
+
+fn direction(direction: Direction) -> &'static str {
+    match direction {
+        Direction::In => "inbound",
+        Direction::Out => "outbound",
+    }
+}
+
+fn protocol(protocol: Protocol) -> &'static str {
+    match protocol {
+        Protocol::Http1 => "http1",
+        Protocol::Http2 => "http2",
+        Protocol::Opaque => "opaque",
+    }
+}
+
+fn client_id(tls: &tls::ConditionalServerTlsLabels) -> Option<String> {
+    match tls {
+        tls::ConditionalServerTlsLabels::Some(tls::ServerTlsLabels::Established {
+            client_id: Some(id),
+        }) => Some(id.to_string()),
+        _ => None,
+    }
+}
+
+// === impl Filter ===
+
+impl Filter {
+    fn parse(query: &str) -> Result<Self, String> {
+        let mut filter = Self::default();
+        for param in query.split('&').filter(|p| !p.is_empty()) {
+            let (key, value) = param.split_once('=').unwrap_or((param, ""));
+            match key {
+                "direction" => {
+                    filter.direction = Some(match value {
+                        "inbound" => Direction::In,
+                        "outbound" => Direction::Out,
+                        _ => return Err(format!("invalid direction: {value}")),
+                    })
+                }
+                "protocol" => {
+                    filter.protocol = Some(match value {
+                        "http1" => Protocol::Http1,
+                        "http2" => Protocol::Http2,
+                        "opaque" => Protocol::Opaque,
+                        _ => return Err(format!("invalid protocol: {value}")),
+                    })
+                }
+                "peer" => {
+                    filter.peer = Some(if let Ok(addr) = value.parse() {
+                        Peer::Addr(addr)
+                    } else if let Ok(ip) = value.parse() {
+                        Peer::Ip(ip)
+                    } else {
+                        return Err(format!("invalid peer address: {value}"));
+                    })
+                }
+                "identity" => filter.identity = Some(value.to_string()),
+                _ => return Err(format!("unsupported query parameter: {key}")),
+            }
+        }
+        Ok(filter)
+    }
+
+    fn matches(&self, conn: &ConnectionSnapshot) -> bool {
+        if self.direction.is_some_and(|d| d != conn.direction) {
+            return false;
+        }
+        if self.protocol.is_some() && self.protocol != conn.protocol {
+            return false;
+        }
+        let Remote(ClientAddr(peer)) = conn.client_addr;
+        match self.peer {
+            Some(Peer::Ip(ip)) if ip != peer.ip() => return false,
+            Some(Peer::Addr(addr)) if addr != peer => return false,
+            _ => {}
+        }
+        if let Some(identity) = self.identity.as_ref() {
+            if conn.tls.as_ref().and_then(client_id).as_ref() != Some(identity) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use linkerd_app_core::transport::OrigDstAddr;
+    use std::time::Duration;
+
+    #[test]
+    fn filters_connections() {
+        let conn = ConnectionSnapshot {
+            direction: Direction::In,
+            client_addr: Remote(ClientAddr(([192, 0, 2, 2], 40000).into())),
+            orig_dst_addr: OrigDstAddr(([192, 0, 2, 1], 8080).into()),
+            tls: Some(tls::ConditionalServerTlsLabels::Some(
+                tls::ServerTlsLabels::Established {
+                    client_id: Some(
+                        "foo.ns1.serviceaccount.identity.linkerd.cluster.local"
+                            .parse()
+                            .unwrap(),
+                    ),
+                },
+            )),
+            protocol: Some(Protocol::Http2),
+            age: Duration::from_secs(3),
+            bytes_read: 10,
+            bytes_written: 20,
+            streams: 1,
+        };
+
+        let dump = connection(&conn);
+        assert_eq!(dump["direction"], "inbound");
+        assert_eq!(dump["peer"], "192.0.2.2:40000");
+        assert_eq!(dump["orig_dst"], "192.0.2.1:8080");
+        assert_eq!(dump["tls"]["status"], "established");
+        assert_eq!(
+            dump["tls"]["client_id"],
+            "foo.ns1.serviceaccount.identity.linkerd.cluster.local"
+        );
+        assert_eq!(dump["protocol"], "http2");
+        assert_eq!(dump["age_ms"], 3_000);
+        assert_eq!(dump["bytes_read"], 10);
+        assert_eq!(dump["bytes_written"], 20);
+        assert_eq!(dump["streams"], 1);
+
+        for query in [
+            "",
+            "direction=inbound",
+            "protocol=http2",
+            "peer=192.0.2.2",
+            "peer=192.0.2.2:40000",
+            "identity=foo.ns1.serviceaccount.identity.linkerd.cluster.local",
+            "direction=inbound&protocol=http2&peer=192.0.2.2",
+        ] {
+            let filter = Filter::parse(query).unwrap();
+            assert!(filter.matches(&conn), "{query} must match");
+        }
+
+        for query in [
+            "direction=outbound",
+            "protocol=opaque",
+            "peer=192.0.2.3",
+            "peer=192.0.2.2:40001",
+            "identity=bar.ns1.serviceaccount.identity.linkerd.cluster.local",
+        ] {
+            let filter = Filter::parse(query).unwrap();
+            assert!(!filter.matches(&conn), "{query} must not match");
+        }
+
+        assert!(Filter::parse("direction=sideways").is_err());
+        assert!(Filter::parse("port=8080").is_err());
+    }
+}
@@ -0,0 +1,816 @@
+//! Dumps the client and server policies currently held by the proxy.
+
+use super::json;
+use linkerd_app_core::{exp_backoff::ExponentialBackoff, proxy::http::BoxBody, Addr};
+use linkerd_proxy_client_policy::{
+    self as client,
+    route::{grpc as grpc_route, http as http_route},
+    ClientPolicy,
+};
+use linkerd_proxy_server_policy::{self as server, ServerPolicy};
+use linkerd_tonic_watch::{Snapshot, Snapshots};
+use serde_json::{json, Value};
+use std::time::{Duration, UNIX_EPOCH};
+
+/// The policies discovered by the proxy.
+#[derive(Clone, Debug, Default)]
+pub struct Policies {
+    /// Inbound server policies, by port.
+    pub inbound: Snapshots<u16, ServerPolicy>,
+    /// Outbound client policies, by discovery target.
+    pub outbound: Snapshots<Addr, ClientPolicy>,
+}
+
+// === impl Policies ===
+
+impl Policies {
+    pub(super) fn inbound_rsp(&self) -> http::Response<BoxBody> {
+        let mut ports = self.inbound.snapshot();
+        ports.sort_by_key(|(port, _)| *port);
+        let ports = ports
+            .iter()
+            .map(|(port, snap)| {
+                let mut dump = snapshot(snap, server_policy);
+                dump["port"] = json!(port);
+                dump
+            })
+            .collect::<Vec<_>>();
+        json::json_rsp(&json!({ "ports": ports }))
+    }
+
+    pub(super) fn outbound_rsp(&self) -> http::Response<BoxBody> {
+        let mut targets = self
+            .outbound
+            .snapshot()
+            .into_iter()
+            .map(|(addr, snap)| (addr.to_string(), snap))
+            .collect::<Vec<_>>();
+        targets.sort_by(|(a, _), (b, _)| a.cmp(b));
+        let targets = targets
+            .iter()
+            .map(|(target, snap)| {
+                let mut dump = snapshot(snap, client_policy);
+                dump["target"] = json!(target);
+                dump
+            })
+            .collect::<Vec<_>>();
+        json::json_rsp(&json!({ "targets": targets }))
+    }
+}
+
+fn snapshot<P>(snap: &Snapshot<P>, policy: impl Fn(&P) -> Value) -> Value {
+    let last_update = snap
+        .last_update
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    json!({
+        "generation": snap.generation,
+        "last_update_unix_ms": millis(last_update),
+        "policy": policy(&snap.value),
+    })
+}
+
+// === server policies ===
+
+fn server_policy(policy: &ServerPolicy) -> Value {
+    json!({
+        "meta": server_meta(&policy.meta),
+        "protocol": server_protocol(&policy.protocol),
+    })
+}
+
+fn server_protocol(protocol: &server::Protocol) -> Value {
+    match protocol {
+        server::Protocol::Detect {
+            http,
+            timeout,
+            tcp_authorizations,
+        } => json!({
+            "kind": "detect",
+            "timeout_ms": millis(*timeout),
+            "http_routes": http.iter().map(server_route).collect::<Vec<_>>(),
+            "tcp_authorizations": authorizations(tcp_authorizations),
+        }),
+        server::Protocol::Http1(routes) => json!({
+            "kind": "http1",
+            "routes": routes.iter().map(server_route).collect::<Vec<_>>(),
+        }),
+        server::Protocol::Http2(routes) => json!({
+            "kind": "http2",
+            "routes": routes.iter().map(server_route).collect::<Vec<_>>(),
+        }),
+        server::Protocol::Grpc { routes, grpc_web } => json!({
+            "kind": "grpc",
+            "routes": routes.iter().map(server_route).collect::<Vec<_>>(),
+            "grpc_web": grpc_web,
+        }),
+        server::Protocol::Tls(authzs) => json!({
+            "kind": "tls",
+            "authorizations": authorizations(authzs),
+        }),
+        server::Protocol::Opaque(authzs) => json!({
+            "kind": "opaque",
+            "authorizations": authorizations(authzs),
+        }),
+    }
+}
+
+fn server_route<M: Dump, F: Dump>(
+    route: &server::route::Route<M, server::RoutePolicy<F>>,
+) -> Value {
+    let rules = route
+        .rules
+        .iter()
+        .map(|rule| {
+            json!({
+                "matches": dumps(&rule.matches),
+                "meta": server_meta(&rule.policy.meta),
+                "authorizations": authorizations(&rule.policy.authorizations),
+                "filters": dumps(&rule.policy.filters),
+            })
+        })
+        .collect::<Vec<_>>();
+    json!({
+        "hosts": dumps(&route.hosts),
+        "rules": rules,
+    })
+}
+
+fn authorizations(authzs: &[server::Authorization]) -> Vec<Value> {
+    authzs
+        .iter()
+        .map(|authz| {
+            json!({
+                "meta": server_meta(&authz.meta),
+                "networks": dumps(&authz.networks),
+                "authentication": authz.authentication.dump(),
+            })
+        })
+        .collect()
+}
+
+fn server_meta(meta: &server::Meta) -> Value {
+    json!({
+        "group": meta.group(),
+        "kind": meta.kind(),
+        "name": meta.name(),
+    })
+}
+
+// === client policies ===
+
+fn client_policy(policy: &ClientPolicy) -> Value {
+    json!({
+        "parent": client_meta(&policy.parent),
+        "protocol": client_protocol(&policy.protocol),
+        "backends": policy.backends.iter().map(backend).collect::<Vec<_>>(),
+    })
+}
+
+fn client_protocol(protocol: &client::Protocol) -> Value {
+    match protocol {
+        client::Protocol::Detect {
+            timeout,
+            http1,
+            http2,
+            opaque,
+        } => json!({
+            "kind": "detect",
+            "timeout_ms": millis(*timeout),
+            "http1": http_routes(&http1.routes, &http1.failure_accrual),
+            "http2": http_routes(&http2.routes, &http2.failure_accrual),
+            "opaque": opaque_routes(opaque),
+        }),
+        client::Protocol::Http1(http1) => json!({
+            "kind": "http1",
+            "http1": http_routes(&http1.routes, &http1.failure_accrual),
+        }),
+        client::Protocol::Http2(http2) => json!({
+            "kind": "http2",
+            "http2": http_routes(&http2.routes, &http2.failure_accrual),
+        }),
+        client::Protocol::Grpc(grpc) => json!({
+            "kind": "grpc",
+            "grpc": http_routes(&grpc.routes, &grpc.failure_accrual),
+        }),
+        client::Protocol::Opaque(opaque) => json!({
+            "kind": "opaque",
+            "opaque": opaque_routes(opaque),
+        }),
+        client::Protocol::Tls(tls) => {
+            let routes = tls
+                .routes
+                .iter()
+                .map(|route| {
+                    let mut dump = route_policy(&route.policy);
+                    dump["snis"] = json!(dumps(&route.snis));
+                    dump
+                })
+                .collect::<Vec<_>>();
+            json!({
+                "kind": "tls",
+                "tls": { "routes": routes },
+            })
+        }
+    }
+}
+
+fn http_routes<M: Dump, F: Dump, P: Dump>(
+    routes: &[client::route::Route<M, client::RoutePolicy<F, P>>],
+    failure_accrual: &Option<client::FailureAccrual>,
+) -> Value {
+    let routes = routes
+        .iter()
+        .map(|route| {
+            let rules = route
+                .rules
+                .iter()
+                .map(|rule| {
+                    let mut dump = route_policy(&rule.policy);
+                    dump["matches"] = json!(dumps(&rule.matches));
+                    dump
+                })
+                .collect::<Vec<_>>();
+            json!({
+                "hosts": dumps(&route.hosts),
+                "rules": rules,
+            })
+        })
+        .collect::<Vec<_>>();
+    json!({
+        "routes": routes,
+        "failure_accrual": failure_accrual.as_ref().map(self::failure_accrual),
+    })
+}
+
+fn opaque_routes(opaque: &client::opaq::Opaque) -> Value {
+    json!({
+        "routes": opaque.routes.iter().map(|r| route_policy(&r.policy)).collect::<Vec<_>>(),
+    })
+}
+
+fn route_policy<F: Dump, P: Dump>(policy: &client::RoutePolicy<F, P>) -> Value {
+    json!({
+        "meta": client_meta(&policy.meta),
+        "filters": dumps(&policy.filters),
+        "distribution": distribution(&policy.distribution),
+        "params": policy.params.dump(),
+    })
+}
+
+fn distribution<F: Dump>(dist: &client::RouteDistribution<F>) -> Value {
+    let route_backend = |rb: &client::RouteBackend<F>, weight: Option<u32>| {
+        json!({
+            "backend": client_meta(&rb.backend.meta),
+            "filters": dumps(&rb.filters),
+            "weight": weight,
+        })
+    };
+    match dist {
+        client::RouteDistribution::Empty => json!({ "kind": "empty" }),
+        client::RouteDistribution::FirstAvailable(backends) => json!({
+            "kind": "first_available",
+            "backends": backends
+                .iter()
+                .map(|rb| route_backend(rb, None))
+                .collect::<Vec<_>>(),
+        }),
+        client::RouteDistribution::RandomAvailable(backends) => json!({
+            "kind": "random_available",
+            "backends": backends
+                .iter()
+                .map(|(rb, weight)| route_backend(rb, Some(*weight)))
+                .collect::<Vec<_>>(),
+        }),
+    }
+}
+
+fn backend(backend: &client::Backend) -> Value {
+    let dispatcher = match &backend.dispatcher {
+        client::BackendDispatcher::Forward(addr, _) => json!({
+            "kind": "forward",
+            "addr": addr.to_string(),
+        }),
+        client::BackendDispatcher::BalanceP2c(load, discovery) => json!({
+            "kind": "balance_p2c",
+            "load": self::load(load),
+            "discovery": self::discovery(discovery),
+        }),
+        client::BackendDispatcher::Fail { message } => json!({
+            "kind": "fail",
+            "message": &**message,
+        }),
+    };
+    json!({
+        "meta": client_meta(&backend.meta),
+        "queue": {
+            "capacity": backend.queue.capacity,
+            "failfast_timeout_ms": millis(backend.queue.failfast_timeout),
+        },
+        "dispatcher": dispatcher,
+    })
+}
+
+fn failure_accrual(fa: &client::FailureAccrual) -> Value {
+    match fa {
+        client::FailureAccrual::Consecutive(c) => json!({
+            "kind": "consecutive",
+            "max_failures": c.max_failures,
+            "backoff": backoff(&c.backoff),
+        }),
+        client::FailureAccrual::Unified(u) => json!({
+            "kind": "unified",
+            "threshold": u.threshold.as_fraction(),
+            "window_ms": millis(u.window),
+            "min_requests": u.min_requests,
+            "max_consecutive_failures": u.max_consecutive_failures,
+            "backoff": backoff(&u.backoff),
+        }),
+    }
+}
+
+fn load(load: &client::Load) -> Value {
+    match load {
+        client::Load::PeakEwma(ewma) => json!({
+            "kind": "peak_ewma",
+            "decay_ms": millis(ewma.decay),
+            "default_rtt_ms": millis(ewma.default_rtt),
+        }),
+        client::Load::PenaltyPeakEwma(ewma) => json!({
+            "kind": "penalty_peak_ewma",
+            "decay_ms": millis(ewma.decay),
+            "default_rtt_ms": millis(ewma.default_rtt),
+            "penalty_ms": millis(ewma.penalty),
+            "penalty_decay_ms": millis(ewma.penalty_decay),
+            "max_retry_after_ms": millis(ewma.max_retry_after),
+        }),
+    }
+}
+
+fn discovery(discovery: &client::EndpointDiscovery) -> Value {
+    match discovery {
+        client::EndpointDiscovery::DestinationGet { path } => json!({
+            "kind": "destination_get",
+            "path": path,
+        }),
+        client::EndpointDiscovery::Dns(dns) => json!({
+            "kind": "dns",
+            "authority": dns.authority.to_string(),
+            "records": match dns.records {
+                client::DnsRecords::Addrs => "addrs",
+                client::DnsRecords::Srv => "srv",
+            },
+        }),
+    }
+}
+
+fn backoff(backoff: &ExponentialBackoff) -> Value {
+    json!({
+        "min_ms": millis(backoff.min()),
+        "max_ms": millis(backoff.max()),
+        "jitter": backoff.jitter_ratio(),
+    })
+}
+
+pub(super) fn client_meta(meta: &client::Meta) -> Value {
+    json!({
+        "group": meta.group(),
+        "kind": meta.kind(),
+        "name": meta.name(),
+        "namespace": meta.namespace(),
+        "section": meta.section(),
+        "port": meta.port(),
+    })
+}
+
+// === impl Dump ===
+
+/// Describes the route matches, filters, and parameters that are shared by
+/// policies as JSON.
+trait Dump {
+    fn dump(&self) -> Value;
+}
+
+impl Dump for () {
+    fn dump(&self) -> Value {
+        Value::Null
+    }
+}
+
+impl Dump for server::authz::Network {
+    fn dump(&self) -> Value {
+        json!({
+            "net": self.net.to_string(),
+            "except": self.except.iter().map(ToString::to_string).collect::<Vec<_>>(),
+        })
+    }
+}
+
+impl Dump for server::Authentication {
+    fn dump(&self) -> Value {
+        match self {
+            Self::Unauthenticated => json!({ "kind": "unauthenticated" }),
+            Self::TlsUnauthenticated => json!({ "kind": "tls_unauthenticated" }),
+            Self::TlsAuthenticated {
+                identities,
+                suffixes,
+            } => json!({
+                "kind": "tls_authenticated",
+                "identities": identities,
+                "suffixes": suffixes.iter().map(|s| s.as_str()).collect::<Vec<_>>(),
+            }),
+        }
+    }
+}
+
+impl Dump for http_route::MatchHost {
+    fn dump(&self) -> Value {
+        match self {
+            Self::Exact(host) => json!({ "kind": "exact", "value": host }),
+            Self::Suffix(labels) => json!({ "kind": "suffix", "value": suffix(labels) }),
+        }
+    }
+}
+
+impl Dump for client::tls::sni::MatchSni {
+    fn dump(&self) -> Value {
+        match self {
+            Self::Exact(sni) => json!({ "kind": "exact", "value": sni }),
+            Self::Suffix(labels) => json!({ "kind": "suffix", "value": suffix(labels) }),
+        }
+    }
+}
+
+impl Dump for http_route::MatchRequest {
+    fn dump(&self) -> Value {
+        use http_route::r#match::{MatchPath, MatchQueryParam};
+
+        let path = self.path.as_ref().map(|path| match path {
+            MatchPath::Exact(p) => json!({ "kind": "exact", "value": p }),
+            MatchPath::Prefix(p) => json!({ "kind": "prefix", "value": p }),
+            MatchPath::Regex(re) => json!({ "kind": "regex", "value": re.as_str() }),
+        });
+        let query_params = self
+            .query_params
+            .iter()
+            .map(|qp| match qp {
+                MatchQueryParam::Exact(name, value) => {
+                    json!({ "kind": "exact", "name": name, "value": value })
+                }
+                MatchQueryParam::Regex(name, re) => {
+                    json!({ "kind": "regex", "name": name, "value": re.as_str() })
+                }
+            })
+            .collect::<Vec<_>>();
+        json!({
+            "path": path,
+            "headers": dumps(&self.headers),
+            "query_params": query_params,
+            "method": self.method.as_ref().map(http::Method::as_str),
+        })
+    }
+}
+
+impl Dump for grpc_route::MatchRoute {
+    fn dump(&self) -> Value {
+        json!({
+            "service": self.rpc.service,
+            "method": self.rpc.method,
+            "headers": dumps(&self.headers),
+        })
+    }
+}
+
+impl Dump for http_route::MatchHeader {
+    fn dump(&self) -> Value {
+        match self {
+            Self::Exact(name, value) => json!({
+                "kind": "exact",
+                "name": name.as_str(),
+                "value": header_value(value),
+            }),
+            Self::Regex(name, re) => json!({
+                "kind": "regex",
+                "name": name.as_str(),
+                "value": re.as_str(),
+            }),
+        }
+    }
+}
+
+impl Dump for server::http::Filter {
+    fn dump(&self) -> Value {
+        match self {
+            Self::InjectFailure(f) => f.dump(),
+            Self::Redirect(r) => r.dump(),
+            Self::RequestHeaders(h) => modify_headers("request_headers", h),
+            Self::Compression(c) => c.dump(),
+            Self::Deadline(d) => d.dump(),
+            Self::InternalError(message) => internal_error(message),
+        }
+    }
+}
+
+impl Dump for server::grpc::Filter {
+    fn dump(&self) -> Value {
+        match self {
+            Self::InjectFailure(f) => f.dump(),
+            Self::RequestHeaders(h) => modify_headers("request_headers", h),
+            Self::Deadline(d) => d.dump(),
+            Self::InternalError(message) => internal_error(message),
+        }
+    }
+}
+
+impl Dump for client::http::Filter {
+    fn dump(&self) -> Value {
+        match self {
+            Self::InjectFailure(f) => f.dump(),
+            Self::Redirect(r) => r.dump(),
+            Self::RequestHeaders(h) => modify_headers("request_headers", h),
+            Self::ResponseHeaders(h) => modify_headers("response_headers", h),
+            Self::Compression(c) => c.dump(),
+            Self::InternalError(message) => internal_error(message),
+        }
+    }
+}
+
+impl Dump for client::grpc::Filter {
+    fn dump(&self) -> Value {
+        match self {
+            Self::InjectFailure(f) => f.dump(),
+            Self::RequestHeaders(h) => modify_headers("request_headers", h),
+            Self::InternalError(message) => internal_error(message),
+        }
+    }
+}
+
+impl Dump for client::opaq::Filter {
+    fn dump(&self) -> Value {
+        match self {
+            Self::Forbidden => json!({ "kind": "forbidden" }),
+            Self::Invalid(message) => json!({ "kind": "invalid", "message": &**message }),
+            Self::InternalError(message) => internal_error(message),
+        }
+    }
+}
+
+impl Dump for client::tls::Filter {
+    fn dump(&self) -> Value {
+        match self {
+            Self::Forbidden => json!({ "kind": "forbidden" }),
+            Self::Invalid(message) => json!({ "kind": "invalid", "message": &**message }),
+            Self::InternalError(message) => internal_error(message),
+        }
+    }
+}
+
+impl<T: Dump> Dump for http_route::filter::InjectFailure<T> {
+    fn dump(&self) -> Value {
+        let (numerator, denominator) = self.distribution.ratio();
+        json!({
+            "kind": "inject_failure",
+            "response": self.response.dump(),
+            "numerator": numerator,
+            "denominator": denominator,
+        })
+    }
+}
+
+impl Dump for http_route::filter::FailureResponse {
+    fn dump(&self) -> Value {
+        json!({ "status": self.status.as_u16(), "message": &*self.message })
+    }
+}
+
+impl Dump for grpc_route::filter::FailureResponse {
+    fn dump(&self) -> Value {
+        json!({ "code": self.code, "message": &*self.message })
+    }
+}
+
+impl Dump for http_route::filter::RedirectRequest {
+    fn dump(&self) -> Value {
+        use http_route::filter::{redirect::AuthorityOverride, ModifyPath};
+
+        let authority = self.authority.as_ref().map(|authority| match authority {
+            AuthorityOverride::Exact(a) => json!({ "kind": "exact", "value": a.as_str() }),
+            AuthorityOverride::Host(h) => json!({ "kind": "host", "value": h.as_str() }),
+            AuthorityOverride::Port(p) => json!({ "kind": "port", "value": p.get() }),
+        });
+        let path = self.path.as_ref().map(|path| match path {
+            ModifyPath::ReplaceFullPath(p) => json!({ "kind": "replace_full_path", "value": p }),
+            ModifyPath::ReplacePrefixMatch(p) => {
+                json!({ "kind": "replace_prefix_match", "value": p })
+            }
+        });
+        json!({
+            "kind": "redirect",
+            "scheme": self.scheme.as_ref().map(|s| s.as_str()),
+            "authority": authority,
+            "path": path,
+            "status": self.status.map(|s| s.as_u16()),
+        })
+    }
+}
+
+impl Dump for http_route::filter::Compression {
+    fn dump(&self) -> Value {
+        json!({
+            "kind": "compression",
+            "encodings": self.encodings.iter().map(|e| e.as_str()).collect::<Vec<_>>(),
+            "content_types": self.content_types,
+            "min_size": self.min_size,
+            "decompress_requests": self.decompress_requests,
+            "max_decompressed_request_size": self.max_decompressed_request_size,
+        })
+    }
+}
+
+impl Dump for http_route::filter::Deadline {
+    fn dump(&self) -> Value {
+        json!({
+            "kind": "deadline",
+            "http_header": self.http_header.as_ref().map(|h| h.as_str()),
+            "max_ms": opt_millis(self.max),
+        })
+    }
+}
+
+impl Dump for client::http::RouteParams {
+    fn dump(&self) -> Value {
+        let retry = self.retry.as_ref().map(|retry| {
+            let status_ranges = retry
+                .status_ranges
+                .0
+                .iter()
+                .map(|r| json!({ "start": r.start(), "end": r.end() }))
+                .collect::<Vec<_>>();
+            json!({
+                "max_retries": retry.max_retries,
+                "max_request_bytes": retry.max_request_bytes,
+                "status_ranges": status_ranges,
+                "timeout_ms": opt_millis(retry.timeout),
+                "backoff": retry.backoff.as_ref().map(backoff),
+            })
+        });
+        json!({
+            "timeouts": timeouts(&self.timeouts),
+            "retry": retry,
+            "export_hostname_labels": self.export_hostname_labels,
+            "upgrade_timeouts": {
+                "idle_ms": opt_millis(self.upgrade_timeouts.idle),
+                "lifetime_ms": opt_millis(self.upgrade_timeouts.lifetime),
+            },
+        })
+    }
+}
+
+impl Dump for client::grpc::RouteParams {
+    fn dump(&self) -> Value {
+        let retry = self.retry.as_ref().map(|retry| {
+            json!({
+                "max_retries": retry.max_retries,
+                "max_request_bytes": retry.max_request_bytes,
+                "codes": &*retry.codes.0,
+                "timeout_ms": opt_millis(retry.timeout),
+                "backoff": retry.backoff.as_ref().map(backoff),
+            })
+        });
+        json!({
+            "timeouts": timeouts(&self.timeouts),
+            "retry": retry,
+            "export_hostname_labels": self.export_hostname_labels,
+        })
+    }
+}
+
+impl Dump for client::tls::RouteParams {
+    fn dump(&self) -> Value {
+        json!({ "export_hostname_labels": self.export_hostname_labels })
+    }
+}
+
+fn modify_headers(kind: &'static str, modify: &http_route::filter::ModifyHeader) -> Value {
+    let pairs = |headers: &[(http::HeaderName, http::HeaderValue)]| {
+        headers
+            .iter()
+            .map(|(name, value)| json!({ "name": name.as_str(), "value": header_value(value) }))
+            .collect::<Vec<_>>()
+    };
+    json!({
+        "kind": kind,
+        "add": pairs(&modify.add),
+        "set": pairs(&modify.set),
+        "remove": modify.remove.iter().map(|h| h.as_str()).collect::<Vec<_>>(),
+    })
+}
+
+fn internal_error(message: &'static str) -> Value {
+    json!({ "kind": "internal_error", "message": message })
+}
+
+fn timeouts(timeouts: &client::http::Timeouts) -> Value {
+    json!({
+        "response_ms": opt_millis(timeouts.response),
+        "idle_ms": opt_millis(timeouts.idle),
+        "request_ms": opt_millis(timeouts.request),
+    })
+}
+
+// === helpers ===
+
+pub(super) fn millis(d: Duration) -> u64 {
+    d.as_millis().try_into().unwrap_or(u64::MAX)
+}
+
+fn dumps<T: Dump>(values: &[T]) -> Vec<Value> {
+    values.iter().map(Dump::dump).collect()
+}
+
+fn opt_millis(d: Option<Duration>) -> Option<u64> {
+    d.map(millis)
+}
+
+fn header_value(value: &http::HeaderValue) -> String {
+    String::from_utf8_lossy(value.as_bytes()).into_owned()
+}
+
+/// Reverses the tokenized labels of a suffix match into a domain name.
+fn suffix(labels: &[String]) -> String {
+    labels
+        .iter()
+        .rev()
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::SocketAddr;
+
+    #[test]
+    fn dumps_invalid_policies() {
+        let policies = Policies::default();
+        policies
+            .inbound
+            .record(4143, ServerPolicy::invalid(Duration::from_secs(10)));
+        policies.outbound.record(
+            Addr::from(SocketAddr::from(([192, 0, 2, 1], 8080))),
+            ClientPolicy::invalid(Duration::from_secs(10)),
+        );
+
+        let inbound = server_policy(&policies.inbound.get(&4143).unwrap().value);
+        assert_eq!(inbound["meta"]["name"], "invalid");
+        assert_eq!(inbound["protocol"]["kind"], "detect");
+        assert_eq!(inbound["protocol"]["timeout_ms"], 10_000);
+
+        let snap = policies
+            .outbound
+            .get(&Addr::from(SocketAddr::from(([192, 0, 2, 1], 8080))))
+            .unwrap();
+        let outbound = snapshot(&snap, client_policy);
+        assert_eq!(outbound["generation"], 1);
+        assert_eq!(outbound["policy"]["parent"]["name"], "invalid");
+        let http1 = &outbound["policy"]["protocol"]["http1"];
+        assert_eq!(
+            http1["routes"][0]["rules"][0]["distribution"]["kind"],
+            "empty"
+        );
+        assert!(http1["failure_accrual"].is_null());
+    }
+
+    #[test]
+    fn dumps_matches_and_filters() {
+        use http_route::{filter::ModifyHeader, r#match::MatchPath};
+
+        let matches = http_route::MatchRequest {
+            path: Some(MatchPath::Prefix("/api".to_string())),
+            method: Some(http::Method::GET),
+            ..Default::default()
+        };
+        let dump = matches.dump();
+        assert_eq!(dump["path"], json!({ "kind": "prefix", "value": "/api" }));
+        assert_eq!(dump["method"], "GET");
+
+        let host = http_route::MatchHost::Suffix(vec!["com".into(), "example".into()]);
+        assert_eq!(host.dump()["value"], "example.com");
+
+        let filter = client::http::Filter::RequestHeaders(ModifyHeader {
+            add: vec![("x-env".parse().unwrap(), "test".parse().unwrap())],
+            set: vec![],
+            remove: vec!["x-debug".parse().unwrap()],
+        });
+        assert_eq!(
+            filter.dump(),
+            json!({
+                "kind": "request_headers",
+                "add": [{ "name": "x-env", "value": "test" }],
+                "set": [],
+                "remove": ["x-debug"],
+            })
+        );
+    }
+}
@@ -13,6 +13,7 @@ use linkerd_app_core::{
     Error, Result,
 };
 use linkerd_app_inbound as inbound;
+use linkerd_app_outbound as outbound;
 use std::{pin::Pin, time::Duration};
 use thiserror::Error;
 use tokio::sync::mpsc;
@@ -79,6 +80,9 @@ impl Config {
         self,
         bind: B,
         policy: impl inbound::policy::GetPolicy,
+        policies: crate::Policies,
+        balancers: outbound::http::concrete::Balancers,
+        connections: transport::Connections,
         identity: identity::Server,
         report: R,
         metrics: inbound::InboundMetrics,
@@ -101,7 +105,10 @@ impl Config {
         let (ready, latch) = crate::server::Readiness::new();
 
         #[cfg_attr(not(feature = "pprof"), allow(unused_mut))]
-        let admin = crate::server::Admin::new(report, ready, shutdown, self.enable_shutdown, trace);
+        let admin = crate::server::Admin::new(report, ready, shutdown, self.enable_shutdown, trace)
+            .with_policies(policies)
+            .with_balancers(balancers)
+            .with_connections(connections);
 
         #[cfg(feature = "pprof")]
         let admin = admin.with_profiling(self.enable_profiling);
@@ -123,6 +130,7 @@ impl Config {
             .arc_new_clone_http();
 
         let inbound::DetectMetrics(detect_metrics) = metrics.detect.clone();
+        let sniffers = http::Sniffers::default();
         let tcp = http
             .unlift_new()
             .push(http::NewServeHttp::layer({
@@ -167,7 +175,7 @@ impl Config {
                         }
                         // If the connection failed HTTP detection, check if we detected TLS for
                         // another target. This might indicate that the client is confused/stale.
-                        http::Detection::NotHttp => match tcp.tls {
+                        http::Detection::NotHttp | http::Detection::Opaque(_) => match tcp.tls {
                             tls::ConditionalServerTls::Some(tls::ServerTls::Passthru { sni }) => {
                                 Err(UnexpectedSni(sni, tcp.client).into())
                             }
@@ -181,7 +189,9 @@ impl Config {
             .push(http::NewDetect::layer(move |tcp: &Tcp| {
                 http::DetectParams {
                     read_timeout: DETECT_TIMEOUT,
-                    metrics: detect_metrics.metrics(tcp.policy.server_label())
+                    metrics: detect_metrics.metrics(tcp.policy.server_label()),
+                    sniffers: sniffers.clone(),
+                    server_first_timeout: None,
                 }
             }))
             .push(transport::metrics::NewServer::layer(metrics.proxy.transport))
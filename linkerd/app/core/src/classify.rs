@@ -42,6 +42,8 @@ pub enum Class {
     Http(Result<http::StatusCode>),
     Grpc(Result<grpc::Code>),
     Error(Cow<'static, str>),
+    /// The outcome of an opaque connection to an endpoint.
+    Opaque(Result<()>),
 }
 
 // === impl Request ===
@@ -239,7 +241,7 @@ impl Class {
     pub fn is_failure(&self) -> bool {
         matches!(
             self,
-            Class::Http(Err(_)) | Class::Grpc(Err(_)) | Class::Error(_),
+            Class::Http(Err(_)) | Class::Grpc(Err(_)) | Class::Error(_) | Class::Opaque(Err(_)),
         )
     }
 }
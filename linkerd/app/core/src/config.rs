@@ -1,8 +1,9 @@
 pub use crate::exp_backoff::ExponentialBackoff;
 use crate::{
+    metrics::prom::HistogramEncoding,
     proxy::http::{h1, h2},
     svc::{queue, ExtractParam, Param},
-    transport::{Backlog, DualListenAddr, Keepalive, ListenAddr, UserTimeout},
+    transport::{proxy_protocol, Backlog, DualListenAddr, Keepalive, ListenAddr, UserTimeout},
 };
 use std::time::Duration;
 
@@ -13,6 +14,7 @@ pub struct ServerConfig {
     pub user_timeout: UserTimeout,
     pub backlog: Backlog,
     pub http2: h2::ServerParams,
+    pub proxy_protocol: proxy_protocol::Config,
 }
 
 #[derive(Clone, Debug)]
@@ -31,6 +33,7 @@ pub struct ProxyConfig {
     pub connect: ConnectConfig,
     pub max_in_flight_requests: usize,
     pub detect_protocol_timeout: Duration,
+    pub histogram_encoding: HistogramEncoding,
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
@@ -91,3 +94,9 @@ impl Param<Backlog> for ServerConfig {
         self.backlog
     }
 }
+
+impl Param<proxy_protocol::Config> for ServerConfig {
+    fn param(&self) -> proxy_protocol::Config {
+        self.proxy_protocol.clone()
+    }
+}
@@ -1,6 +1,9 @@
 use crate::{
-    classify, config, dns, identity, metrics, proxy::http, svc, tls, transport::ConnectTcp, Addr,
-    Error,
+    classify, config, dns, identity, metrics,
+    proxy::http,
+    svc, tls,
+    transport::{self, happy_eyeballs, ConnectTcp, HappyEyeballs},
+    Addr, Error,
 };
 use linkerd_metrics::prom;
 use std::fmt;
@@ -75,6 +78,7 @@ pub type RspBody = linkerd_http_metrics::requests::ResponseBody<
 #[derive(Clone, Debug, Default)]
 pub struct Metrics {
     balance: balance::Metrics,
+    family: transport::metrics::family::AddrFamilyMetrics,
 }
 
 const EWMA_CONFIG: http::balance::EwmaConfig = http::balance::EwmaConfig {
@@ -86,6 +90,9 @@ impl Metrics {
     pub fn register(registry: &mut prom::Registry) -> Self {
         Metrics {
             balance: balance::Metrics::register(registry.sub_registry_with_prefix("balancer")),
+            family: transport::metrics::family::AddrFamilyMetrics::register(
+                registry.sub_registry_with_prefix("happy_eyeballs"),
+            ),
         }
     }
 }
@@ -104,12 +111,15 @@ impl Config {
         let addr = self.addr;
         tracing::trace!(%addr, "Building");
 
-        let client = svc::stack(ConnectTcp::new(
-            self.connect.keepalive,
-            self.connect.user_timeout,
+        let client = svc::stack(HappyEyeballs::new(
+            ConnectTcp::new(self.connect.keepalive, self.connect.user_timeout),
+            happy_eyeballs::DEFAULT_ATTEMPT_DELAY,
+        ))
+        .push(transport::metrics::family::AddrFamilyClient::layer(
+            metrics.family,
         ))
         .push(tls::Client::layer(identity))
-        .push_connect_timeout(self.connect.timeout) // Client<NewClient, ConnectTcp>
+        .push_connect_timeout(self.connect.timeout)
         .push_map_target(|(_version, target)| target)
         .push(self::client::layer::<_, _>(self.connect.http2))
         .push_on_service(svc::MapErr::layer_boxed())
@@ -230,10 +240,17 @@ mod balance {
     use crate::{
         dns,
         metrics::prom::encoding::EncodeLabelSet,
-        proxy::{dns_resolve::DnsResolve, http, resolve::recover},
+        proxy::{
+            core::Update,
+            dns_resolve::{self, DnsResolve},
+            http,
+            resolve::recover,
+        },
         svc, tls,
+        transport::ServerAddrs,
+        Error,
     };
-    use futures::Stream;
+    use futures::prelude::*;
     use linkerd_error::Recover;
     use linkerd_exp_backoff::{ExponentialBackoff, ExponentialBackoffStream};
     use linkerd_stack::ExtractParam;
@@ -242,7 +259,7 @@ mod balance {
 
     pub(super) type Metrics = http::balance::MetricFamilies<Labels>;
 
-    pub(super) type Resolve = recover::Resolve<ResolveRecover, DnsResolve>;
+    pub(super) type Resolve = DualStack<recover::Resolve<ResolveRecover, DnsResolve>>;
 
     /// Control-plane clients balance over discovered endpoints using round-trip
     /// time, since the response-rate penalties depend on HTTP classification
@@ -262,7 +279,10 @@ mod balance {
         dns: dns::Resolver,
         backoff: ExponentialBackoff,
     ) -> impl svc::Layer<N, Service = NewBalance<B, N>> {
-        let resolve = recover::Resolve::new(ResolveRecover::new(backoff), DnsResolve::new(dns));
+        let resolve = DualStack(recover::Resolve::new(
+            ResolveRecover::new(backoff),
+            DnsResolve::new(dns),
+        ));
 
         svc::layer::mk(move |inner| {
             NewBalance::new(
@@ -302,6 +322,18 @@ mod balance {
         backoff: ExponentialBackoff,
     }
 
+    /// Pairs resolved IPv6 and IPv4 addresses that share a port into a single
+    /// endpoint, so that a dual-stack controller (e.g. a Service with both IPv4
+    /// and IPv6 cluster IPs) is connected to by racing both addresses rather
+    /// than being balanced over as two endpoints.
+    ///
+    /// See [`dns_resolve::pair_updates`].
+    #[derive(Clone, Debug)]
+    pub(super) struct DualStack<R>(R);
+
+    type DualStackResolution =
+        Pin<Box<dyn Stream<Item = Result<Update<ServerAddrs>, Error>> + Send + 'static>>;
+
     /// A [`Stream`] used for control-plane client's error recovery.
     #[pin_project::pin_project(project = ResolveBackoffProj)]
     pub(super) enum ResolveBackoff {
@@ -352,6 +384,47 @@ mod balance {
         }
     }
 
+    // === impl DualStack ===
+
+    impl<R> tower::Service<ControlAddr> for DualStack<R>
+    where
+        R: crate::proxy::core::Resolve<ControlAddr, Endpoint = ()>,
+    {
+        type Response = DualStackResolution;
+        type Error = Error;
+        type Future =
+            Pin<Box<dyn Future<Output = Result<DualStackResolution, Error>> + Send + 'static>>;
+
+        fn poll_ready(&mut self, _: &mut task::Context<'_>) -> task::Poll<Result<(), Error>> {
+            task::Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, target: ControlAddr) -> Self::Future {
+            let resolve = self.0.resolve(target).map_err(Into::<Error>::into);
+            Box::pin(resolve.map_ok(|updates| {
+                let updates = dns_resolve::pair_updates(updates.map_err(Into::<Error>::into))
+                    .map_ok(|update| match update {
+                        Update::Reset(eps) => Update::Reset(into_server_addrs(eps)),
+                        Update::Add(eps) => Update::Add(into_server_addrs(eps)),
+                        Update::Remove(addrs) => Update::Remove(addrs),
+                        Update::DoesNotExist => Update::DoesNotExist,
+                    });
+                Box::pin(updates) as DualStackResolution
+            }))
+        }
+    }
+
+    fn into_server_addrs(
+        eps: Vec<(SocketAddr, dns_resolve::DualStack<()>)>,
+    ) -> Vec<(SocketAddr, ServerAddrs)> {
+        eps.into_iter()
+            .map(|(addr, ep)| {
+                let addrs = std::iter::once(addr).chain(ep.other_family).collect();
+                (addr, ServerAddrs(addrs))
+            })
+            .collect()
+    }
+
     // === impl ResolveBackoff ===
 
     impl Stream for ResolveBackoff {
@@ -399,12 +472,12 @@ mod balance {
 
     // === impl IntoTarget ===
 
-    impl<N: svc::NewService<Target>> svc::NewService<(SocketAddr, ())> for IntoTarget<N> {
+    impl<N: svc::NewService<Target>> svc::NewService<(SocketAddr, ServerAddrs)> for IntoTarget<N> {
         type Service = N::Service;
 
-        fn new_service(&self, (addr, ()): (SocketAddr, ())) -> Self::Service {
+        fn new_service(&self, (addr, addrs): (SocketAddr, ServerAddrs)) -> Self::Service {
             self.inner
-                .new_service(Target::new(addr, self.server_id.clone()))
+                .new_service(Target::new(addr, addrs, self.server_id.clone()))
         }
     }
 
@@ -424,27 +497,36 @@ mod client {
     use crate::{
         proxy::http,
         svc, tls,
-        transport::{Remote, ServerAddr},
+        transport::{Remote, ServerAddrs},
     };
     use std::net::SocketAddr;
 
     #[derive(Clone, Hash, Debug, Eq, PartialEq)]
     pub struct Target {
         pub(super) addr: SocketAddr,
+        addrs: ServerAddrs,
         server_id: tls::ConditionalClientTls,
     }
 
     impl Target {
-        pub(super) fn new(addr: SocketAddr, server_id: tls::ConditionalClientTls) -> Self {
-            Self { addr, server_id }
+        pub(super) fn new(
+            addr: SocketAddr,
+            addrs: ServerAddrs,
+            server_id: tls::ConditionalClientTls,
+        ) -> Self {
+            Self {
+                addr,
+                addrs,
+                server_id,
+            }
         }
     }
 
     // === impl Target ===
 
-    impl svc::Param<Remote<ServerAddr>> for Target {
-        fn param(&self) -> Remote<ServerAddr> {
-            Remote(ServerAddr(self.addr))
+    impl svc::Param<Remote<ServerAddrs>> for Target {
+        fn param(&self) -> Remote<ServerAddrs> {
+            Remote(self.addrs.clone())
         }
     }
 
@@ -5,10 +5,17 @@ use linkerd_trace_context::{
     export::{ExportSpan, SpanKind, SpanLabels},
     Span, TraceContext,
 };
+pub use linkerd_trace_context::{sample_route, BaggageConfig, Sampler, SamplerConfig, TailConfig};
 use std::sync::Arc;
 use tokio::sync::mpsc;
 
-pub type SpanSink = mpsc::Sender<ExportSpan>;
+/// Sends completed spans to the trace collector.
+#[derive(Clone, Debug)]
+pub struct SpanSink {
+    tx: mpsc::Sender<ExportSpan>,
+    sampler: Sampler,
+    baggage: Arc<BaggageConfig>,
+}
 
 pub fn server<S>(
     sink: Option<SpanSink>,
@@ -32,6 +39,20 @@ pub fn client<S>(
     }))
 }
 
+// === impl SpanSink ===
+
+impl SpanSink {
+    pub fn new(tx: mpsc::Sender<ExportSpan>, sampler: Sampler, baggage: BaggageConfig) -> Self {
+        Self {
+            tx,
+            sampler,
+            baggage: Arc::new(baggage),
+        }
+    }
+}
+
+// === impl SpanConverter ===
+
 #[derive(Clone)]
 pub struct SpanConverter {
     kind: SpanKind,
@@ -44,8 +65,16 @@ impl trace_context::SpanSink for SpanConverter {
         true
     }
 
+    fn sampler(&self) -> Option<&Sampler> {
+        Some(&self.sink.sampler)
+    }
+
+    fn baggage(&self) -> Option<&BaggageConfig> {
+        Some(&self.sink.baggage)
+    }
+
     fn try_send(&mut self, span: Span) -> Result<(), Error> {
-        self.sink.try_send(ExportSpan {
+        self.sink.tx.try_send(ExportSpan {
             span,
             kind: self.kind,
             labels: Arc::clone(&self.labels),
@@ -67,6 +67,7 @@ pub struct ProxyRuntime {
     pub tap: proxy::tap::Registry,
     pub span_sink: Option<http_tracing::SpanSink>,
     pub drain: drain::Watch,
+    pub connections: transport::Connections,
 }
 
 pub fn http_request_authority_addr<B>(req: &http::Request<B>) -> Result<Addr, addr::Error> {
@@ -506,6 +506,12 @@ impl legacy::FmtLabels for Class {
                 f,
                 "classification=\"failure\",grpc_status=\"\",error=\"{msg}\""
             ),
+
+            Class::Opaque(res) => write!(
+                f,
+                "classification=\"{}\",grpc_status=\"\",error=\"\"",
+                class(res.is_ok())
+            ),
         }
     }
 }
@@ -4,8 +4,9 @@ pub use linkerd_transport_metrics as metrics;
 use std::sync::Arc;
 
 pub mod allow_ips;
+pub mod connections;
 pub mod labels;
-pub use self::allow_ips::AllowIps;
+pub use self::{allow_ips::AllowIps, connections::Connections};
 
 #[derive(Clone, Debug)]
 pub struct Metrics(metrics::Registry<labels::Key>);
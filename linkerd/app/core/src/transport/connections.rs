@@ -0,0 +1,507 @@
+//! A registry of the connections accepted by the proxy's listeners.
+//!
+//! Connections are registered as they are accepted and are unregistered when
+//! their I/O is dropped. As the connection's stack is built, it records the TLS
+//! status and protocol negotiated for the connection. Connections are
+//! identified by their direction and the address of the socket's peer, which is
+//! available to every layer that handles the connection's I/O.
+
+pub use crate::metrics::Direction;
+use crate::{io, proxy::http, svc, tls};
+use futures::prelude::*;
+use linkerd_error::Result;
+use linkerd_proxy_transport::{
+    addrs::{ClientAddr, OrigDstAddr, Remote},
+    AddrPair,
+};
+use parking_lot::Mutex;
+use pin_project::pin_project;
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc, Weak,
+    },
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::time::Instant;
+
+/// Tracks the connections accepted by the proxy.
+#[derive(Clone, Debug, Default)]
+pub struct Connections(Arc<Registry>);
+
+pub type SensorIo<I> = io::SensorIo<I, Sensor>;
+
+/// Holds a connection's registration for the lifetime of its I/O and counts
+/// the bytes transferred on it.
+#[derive(Debug)]
+pub struct Sensor(Arc<Connection>);
+
+/// The protocol a connection was determined to carry.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Protocol {
+    Http1,
+    Http2,
+    Opaque,
+}
+
+/// Describes what was negotiated for a connection as its stack was built.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Negotiated {
+    pub direction: Direction,
+    pub tls: tls::ConditionalServerTlsLabels,
+    pub protocol: Protocol,
+}
+
+/// Records the TLS status and protocol negotiated for each connection served
+/// by the inner stack.
+#[derive(Clone, Debug)]
+pub struct NewRecord<X, N> {
+    connections: Connections,
+    extract: X,
+    inner: N,
+}
+
+#[derive(Clone, Debug)]
+pub struct Record<S> {
+    connections: Connections,
+    negotiated: Negotiated,
+    inner: S,
+}
+
+/// Counts the in-flight requests on each HTTP connection served by the inner
+/// stack.
+#[derive(Clone, Debug)]
+pub struct NewCountStreams<N> {
+    connections: Connections,
+    direction: Direction,
+    inner: N,
+}
+
+#[derive(Clone, Debug)]
+pub struct CountStreams<S> {
+    connection: Option<Arc<Connection>>,
+    inner: S,
+}
+
+#[pin_project]
+#[derive(Debug)]
+pub struct StreamFuture<F> {
+    #[pin]
+    inner: F,
+    _stream: Option<StreamGuard>,
+}
+
+/// Describes a connection at the time of a snapshot.
+#[derive(Clone, Debug)]
+pub struct ConnectionSnapshot {
+    pub direction: Direction,
+    pub client_addr: Remote<ClientAddr>,
+    pub orig_dst_addr: OrigDstAddr,
+    /// Unset until the connection's TLS status is known.
+    pub tls: Option<tls::ConditionalServerTlsLabels>,
+    /// Unset until the connection's protocol is known.
+    pub protocol: Option<Protocol>,
+    pub age: Duration,
+    pub bytes_read: u64,
+    pub bytes_written: u64,
+    /// The number of requests awaiting a response on an HTTP connection.
+    pub streams: usize,
+}
+
+type Key = (Direction, SocketAddr);
+
+#[derive(Debug, Default)]
+struct Registry {
+    connections: Mutex<HashMap<Key, Weak<Connection>>>,
+}
+
+#[derive(Debug)]
+struct Connection {
+    key: Key,
+    client_addr: Remote<ClientAddr>,
+    orig_dst_addr: OrigDstAddr,
+    opened_at: Instant,
+    bytes_read: AtomicU64,
+    bytes_written: AtomicU64,
+    streams: AtomicUsize,
+    negotiated: Mutex<Option<Negotiated>>,
+    registry: Weak<Registry>,
+}
+
+#[derive(Debug)]
+struct StreamGuard(Arc<Connection>);
+
+// === impl Connections ===
+
+impl Connections {
+    /// Registers each connection accepted by `listen`.
+    pub fn track<A, I>(
+        &self,
+        direction: Direction,
+        listen: impl Stream<Item = Result<(A, I)>>,
+    ) -> impl Stream<Item = Result<(A, SensorIo<I>)>>
+    where
+        A: svc::Param<AddrPair> + svc::Param<OrigDstAddr>,
+        I: io::PeerAddr,
+    {
+        let connections = self.clone();
+        listen.map_ok(move |(addrs, io)| {
+            let sensor = connections.register(direction, &addrs, &io);
+            (addrs, io::SensorIo::new(io, sensor))
+        })
+    }
+
+    /// Describes the connections that are currently open.
+    pub fn snapshot(&self) -> Vec<ConnectionSnapshot> {
+        // The connection handles must be dropped after the lock is released,
+        // since dropping the last handle unregisters the connection.
+        let connections = self
+            .0
+            .connections
+            .lock()
+            .values()
+            .filter_map(Weak::upgrade)
+            .collect::<Vec<_>>();
+        let now = Instant::now();
+        connections.iter().map(|c| c.snapshot(now)).collect()
+    }
+
+    fn register<A, I>(&self, direction: Direction, addrs: &A, io: &I) -> Sensor
+    where
+        A: svc::Param<AddrPair> + svc::Param<OrigDstAddr>,
+        I: io::PeerAddr,
+    {
+        let AddrPair(client_addr, _) = addrs.param();
+        let peer = io.peer_addr().unwrap_or(*client_addr);
+        let connection = Arc::new(Connection {
+            key: (direction, peer),
+            client_addr: Remote(client_addr),
+            orig_dst_addr: addrs.param(),
+            opened_at: Instant::now(),
+            bytes_read: AtomicU64::new(0),
+            bytes_written: AtomicU64::new(0),
+            streams: AtomicUsize::new(0),
+            negotiated: Mutex::new(None),
+            registry: Arc::downgrade(&self.0),
+        });
+        self.0
+            .connections
+            .lock()
+            .insert(connection.key, Arc::downgrade(&connection));
+        Sensor(connection)
+    }
+
+    fn get(&self, direction: Direction, peer: SocketAddr) -> Option<Arc<Connection>> {
+        self.0
+            .connections
+            .lock()
+            .get(&(direction, peer))
+            .and_then(Weak::upgrade)
+    }
+}
+
+// === impl Connection ===
+
+impl Connection {
+    fn snapshot(&self, now: Instant) -> ConnectionSnapshot {
+        let negotiated = self.negotiated.lock().clone();
+        ConnectionSnapshot {
+            direction: self.key.0,
+            client_addr: self.client_addr,
+            orig_dst_addr: self.orig_dst_addr,
+            tls: negotiated.as_ref().map(|n| n.tls.clone()),
+            protocol: negotiated.map(|n| n.protocol),
+            age: now.saturating_duration_since(self.opened_at),
+            bytes_read: self.bytes_read.load(Ordering::Relaxed),
+            bytes_written: self.bytes_written.load(Ordering::Relaxed),
+            streams: self.streams.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        let Some(registry) = self.registry.upgrade() else {
+            return;
+        };
+        let mut connections = registry.connections.lock();
+        // The peer's address may have been reused by a newer connection, so
+        // only remove the entry if it refers to this connection.
+        if let Some(entry) = connections.get(&self.key) {
+            if std::ptr::eq(entry.as_ptr(), self) {
+                connections.remove(&self.key);
+            }
+        }
+    }
+}
+
+// === impl Sensor ===
+
+impl io::Sensor for Sensor {
+    fn record_read(&mut self, sz: usize) {
+        self.0.bytes_read.fetch_add(sz as u64, Ordering::Relaxed);
+    }
+
+    fn record_write(&mut self, sz: usize) {
+        self.0.bytes_written.fetch_add(sz as u64, Ordering::Relaxed);
+    }
+
+    fn record_close(&mut self, _: Option<linkerd_errno::Errno>) {}
+
+    fn record_error<T>(&mut self, op: io::Poll<T>) -> io::Poll<T> {
+        op
+    }
+}
+
+// === impl Negotiated ===
+
+impl Negotiated {
+    /// Describes a connection accepted from the local application.
+    ///
+    /// The proxy never terminates TLS on these connections.
+    pub fn outbound(protocol: Protocol) -> Self {
+        Self {
+            direction: Direction::Out,
+            tls: tls::ConditionalServerTlsLabels::None(tls::NoServerTls::Loopback),
+            protocol,
+        }
+    }
+}
+
+// === impl Protocol ===
+
+impl From<http::Variant> for Protocol {
+    fn from(version: http::Variant) -> Self {
+        match version {
+            http::Variant::Http1 => Self::Http1,
+            http::Variant::H2 => Self::Http2,
+        }
+    }
+}
+
+// === impl NewRecord ===
+
+impl<N> NewRecord<(), N> {
+    pub fn layer(connections: Connections) -> impl svc::layer::Layer<N, Service = Self> + Clone {
+        Self::layer_via(connections, ())
+    }
+}
+
+impl<X: Clone, N> NewRecord<X, N> {
+    pub fn layer_via(
+        connections: Connections,
+        extract: X,
+    ) -> impl svc::layer::Layer<N, Service = Self> + Clone {
+        svc::layer::mk(move |inner| Self {
+            connections: connections.clone(),
+            extract: extract.clone(),
+            inner,
+        })
+    }
+}
+
+impl<T, X, N> svc::NewService<T> for NewRecord<X, N>
+where
+    X: svc::ExtractParam<Negotiated, T>,
+    N: svc::NewService<T>,
+{
+    type Service = Record<N::Service>;
+
+    fn new_service(&self, target: T) -> Self::Service {
+        let negotiated = self.extract.extract_param(&target);
+        let inner = self.inner.new_service(target);
+        Record {
+            connections: self.connections.clone(),
+            negotiated,
+            inner,
+        }
+    }
+}
+
+impl<I, S> svc::Service<I> for Record<S>
+where
+    I: io::PeerAddr,
+    S: svc::Service<I>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), S::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, io: I) -> Self::Future {
+        if let Ok(peer) = io.peer_addr() {
+            if let Some(conn) = self.connections.get(self.negotiated.direction, peer) {
+                *conn.negotiated.lock() = Some(self.negotiated.clone());
+            }
+        }
+        self.inner.call(io)
+    }
+}
+
+// === impl NewCountStreams ===
+
+impl<N> NewCountStreams<N> {
+    pub fn layer(
+        connections: Connections,
+        direction: Direction,
+    ) -> impl svc::layer::Layer<N, Service = Self> + Clone {
+        svc::layer::mk(move |inner| Self {
+            connections: connections.clone(),
+            direction,
+            inner,
+        })
+    }
+}
+
+impl<N> svc::NewService<http::ClientHandle> for NewCountStreams<N>
+where
+    N: svc::NewService<http::ClientHandle>,
+{
+    type Service = CountStreams<N::Service>;
+
+    fn new_service(&self, client: http::ClientHandle) -> Self::Service {
+        // The client handle describes the socket's peer, so the connection is
+        // looked up once as it is served rather than for each request.
+        let connection = self.connections.get(self.direction, client.addr);
+        let inner = self.inner.new_service(client);
+        CountStreams { connection, inner }
+    }
+}
+
+// === impl CountStreams ===
+
+impl<Req, S> svc::Service<Req> for CountStreams<S>
+where
+    S: svc::Service<Req>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = StreamFuture<S::Future>;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), S::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Req) -> Self::Future {
+        let stream = self.connection.clone().map(StreamGuard::new);
+        StreamFuture {
+            inner: self.inner.call(req),
+            _stream: stream,
+        }
+    }
+}
+
+// === impl StreamFuture ===
+
+impl<F: Future> Future for StreamFuture<F> {
+    type Output = F::Output;
+
+    #[inline]
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<F::Output> {
+        self.project().inner.poll(cx)
+    }
+}
+
+// === impl StreamGuard ===
+
+impl StreamGuard {
+    fn new(connection: Arc<Connection>) -> Self {
+        connection.streams.fetch_add(1, Ordering::Relaxed);
+        Self(connection)
+    }
+}
+
+impl Drop for StreamGuard {
+    fn drop(&mut self) {
+        self.0.streams.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use linkerd_proxy_transport::addrs::ServerAddr;
+
+    #[derive(Clone, Debug)]
+    struct Addrs {
+        client: SocketAddr,
+        orig_dst: SocketAddr,
+    }
+
+    #[derive(Debug)]
+    struct Io(SocketAddr);
+
+    impl svc::Param<AddrPair> for Addrs {
+        fn param(&self) -> AddrPair {
+            AddrPair(ClientAddr(self.client), ServerAddr(self.orig_dst))
+        }
+    }
+
+    impl svc::Param<OrigDstAddr> for Addrs {
+        fn param(&self) -> OrigDstAddr {
+            OrigDstAddr(self.orig_dst)
+        }
+    }
+
+    impl io::PeerAddr for Io {
+        fn peer_addr(&self) -> std::io::Result<SocketAddr> {
+            Ok(self.0)
+        }
+    }
+
+    #[tokio::test]
+    async fn tracks_open_connections() {
+        let connections = Connections::default();
+        let addrs = Addrs {
+            client: ([192, 0, 2, 2], 40000).into(),
+            orig_dst: ([192, 0, 2, 1], 8080).into(),
+        };
+        let mut sensor = connections.register(Direction::In, &addrs, &Io(addrs.client));
+        io::Sensor::record_read(&mut sensor, 10);
+        io::Sensor::record_write(&mut sensor, 20);
+
+        let mut record = Record {
+            connections: connections.clone(),
+            negotiated: Negotiated {
+                direction: Direction::In,
+                tls: tls::ConditionalServerTlsLabels::None(tls::NoServerTls::NoClientHello),
+                protocol: Protocol::Http2,
+            },
+            inner: svc::mk(|_: Io| future::ok::<_, ()>(())),
+        };
+        svc::Service::call(&mut record, Io(addrs.client))
+            .await
+            .unwrap();
+
+        let stream = StreamGuard::new(connections.get(Direction::In, addrs.client).unwrap());
+
+        let [conn] = &connections.snapshot()[..] else {
+            panic!("expected a single connection");
+        };
+        assert_eq!(conn.direction, Direction::In);
+        assert_eq!(conn.client_addr, Remote(ClientAddr(addrs.client)));
+        assert_eq!(conn.orig_dst_addr, OrigDstAddr(addrs.orig_dst));
+        assert_eq!(conn.protocol, Some(Protocol::Http2));
+        assert_eq!(conn.bytes_read, 10);
+        assert_eq!(conn.bytes_written, 20);
+        assert_eq!(conn.streams, 1);
+
+        drop(stream);
+        assert_eq!(connections.snapshot()[0].streams, 0);
+
+        // Outbound connections are tracked separately.
+        assert!(connections.get(Direction::Out, addrs.client).is_none());
+
+        drop(sensor);
+        assert!(connections.snapshot().is_empty());
+    }
+}
@@ -77,6 +77,7 @@ async fn upgraded_request_remains_relative_form() {
             tls::ConditionalServerTls::Some(tls::ServerTls::Established {
                 client_id: Some(self.param()),
                 negotiated_protocol: None,
+                server_id: None,
             })
         }
     }
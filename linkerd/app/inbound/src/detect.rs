@@ -5,7 +5,7 @@ use crate::{
 use linkerd_app_core::{
     identity, io,
     metrics::{prom, ServerLabel},
-    proxy::http,
+    proxy::{http, tap},
     svc, tls,
     transport::{
         self,
@@ -14,8 +14,8 @@ use linkerd_app_core::{
     },
     Error, Infallible,
 };
-use std::{fmt::Debug, time};
-use tracing::info;
+use std::{fmt::Debug, sync::Arc, time};
+use tracing::{debug, info};
 
 #[cfg(test)]
 mod tests;
@@ -24,6 +24,17 @@ mod tests;
 pub struct MetricsFamilies(pub HttpDetectMetrics);
 pub type HttpDetectMetrics = http::DetectMetricsFamilies<ServerLabel>;
 
+/// Counts connections that are forwarded as opaque streams after protocol
+/// detection, by the protocol that was identified, if any.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct DetectedMetrics(prom::Family<DetectedLabels, prom::Counter>);
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct DetectedLabels {
+    server: ServerLabel,
+    protocol: Option<http::OpaqueProtocol>,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub(crate) struct Forward {
     client_addr: Remote<ClientAddr>,
@@ -103,27 +114,46 @@ impl Inbound<svc::ArcNewTcp<Http, io::BoxedIo>> {
         self.map_stack(|cfg, rt, http| {
             let forward = svc::stack(forward)
                 .push_on_service(svc::MapTargetLayer::new(io::BoxedIo::new))
+                .push(tap::NewTapTcp::layer(rt.tap.clone()))
                 .push(transport::metrics::NewServer::layer(
                     rt.metrics.proxy.transport.clone(),
                 ))
+                .push(transport::connections::NewRecord::layer(
+                    rt.connections.clone(),
+                ))
                 .push_map_target(Forward::from)
                 .push(policy::NewTcpPolicy::layer(rt.metrics.tcp_authz.clone()))
                 .arc_new_tcp();
 
             let detect_timeout = cfg.proxy.detect_protocol_timeout;
+            let sniffers = http::Sniffers::default();
+            let server_first_ports = Arc::new(cfg.server_first_ports.clone());
+            let server_first_timeout = cfg.server_first_detect_timeout;
+            let detected = rt.metrics.tcp_detected.clone();
             let detect = http
                 .clone()
                 .push_on_service(svc::MapTargetLayer::new(io::BoxedIo::new))
                 .push(transport::metrics::NewServer::layer(
                     rt.metrics.proxy.transport.clone(),
                 ))
+                .push(transport::connections::NewRecord::layer(
+                    rt.connections.clone(),
+                ))
                 .push_switch(
-                    |(detected, Detect { tls, .. })| -> Result<_, Infallible> {
-                        match detected {
+                    move |(detection, Detect { tls, .. })| -> Result<_, Infallible> {
+                        match detection {
                             http::Detection::Http(http) => {
                                 Ok(svc::Either::Left(Http { http, tls }))
                             }
-                            http::Detection::NotHttp => Ok(svc::Either::Right(tls)),
+                            http::Detection::NotHttp => {
+                                detected.record(tls.policy.server_label(), None);
+                                Ok(svc::Either::Right(tls))
+                            }
+                            http::Detection::Opaque(protocol) => {
+                                debug!(%protocol, "Handling connection as opaque");
+                                detected.record(tls.policy.server_label(), Some(protocol));
+                                Ok(svc::Either::Right(tls))
+                            }
                             // When HTTP detection fails, forward the connection to the application as
                             // an opaque TCP stream.
                             http::Detection::ReadTimeout(timeout) => {
@@ -158,6 +188,7 @@ impl Inbound<svc::ArcNewTcp<Http, io::BoxedIo>> {
                                             ?timeout,
                                             "Handling connection as opaque due to policy"
                                         );
+                                        detected.record(tls.policy.server_label(), None);
                                         Ok(svc::Either::Right(tls))
                                     }
                                 }
@@ -171,6 +202,10 @@ impl Inbound<svc::ArcNewTcp<Http, io::BoxedIo>> {
                     move |Detect { timeout, tls }: &Detect| http::DetectParams {
                         read_timeout: *timeout,
                         metrics: metrics.metrics(tls.policy.server_label()),
+                        sniffers: sniffers.clone(),
+                        server_first_timeout: server_first_ports
+                            .contains(&tls.orig_dst_addr.port())
+                            .then_some(server_first_timeout),
                     },
                 ))
                 .arc_new_tcp();
@@ -179,6 +214,9 @@ impl Inbound<svc::ArcNewTcp<Http, io::BoxedIo>> {
                 .push(transport::metrics::NewServer::layer(
                     rt.metrics.proxy.transport.clone(),
                 ))
+                .push(transport::connections::NewRecord::layer(
+                    rt.connections.clone(),
+                ))
                 .push_switch(
                     // If we have a protocol hint, skip detection and just used the hinted HTTP
                     // version.
@@ -198,6 +236,15 @@ impl Inbound<svc::ArcNewTcp<Http, io::BoxedIo>> {
                                     tls,
                                 }));
                             }
+                            // gRPC servers that accept gRPC-Web may receive requests from
+                            // browsers, which are commonly sent over HTTP/1.1 and translated to
+                            // gRPC by the HTTP stack.
+                            Protocol::Grpc { grpc_web: true, .. } => {
+                                return Ok(svc::Either::Right(Detect {
+                                    timeout: detect_timeout,
+                                    tls,
+                                }));
+                            }
                             // Unmeshed services don't use protocol upgrading, so we can use the
                             // hint without further detection.
                             Protocol::Http1 { .. } => http::Variant::Http1,
@@ -231,9 +278,13 @@ impl<I> Inbound<svc::ArcNewTcp<Tls, TlsIo<I>>> {
         self.map_stack(|cfg, rt, detect| {
             let forward = svc::stack(forward)
                 .push_on_service(svc::MapTargetLayer::new(io::BoxedIo::new))
+                .push(tap::NewTapTcp::layer(rt.tap.clone()))
                 .push(transport::metrics::NewServer::layer(
                     rt.metrics.proxy.transport.clone(),
                 ))
+                .push(transport::connections::NewRecord::layer(
+                    rt.connections.clone(),
+                ))
                 .push_map_target(Forward::from)
                 .push(policy::NewTcpPolicy::layer(rt.metrics.tcp_authz.clone()))
                 .arc_new_tcp();
@@ -322,6 +373,38 @@ impl svc::Param<Remote<ServerAddr>> for Forward {
     }
 }
 
+impl tap::InspectTcp for Forward {
+    fn src_addr(&self) -> Option<std::net::SocketAddr> {
+        Some(self.client_addr.into())
+    }
+
+    fn src_tls(&self) -> tls::ConditionalServerTls {
+        self.tls.clone()
+    }
+
+    fn dst_addr(&self) -> Option<std::net::SocketAddr> {
+        Some(self.orig_dst_addr.into())
+    }
+
+    fn dst_labels(&self) -> Option<tap::Labels> {
+        None
+    }
+
+    fn is_outbound(&self) -> bool {
+        false
+    }
+}
+
+impl svc::Param<transport::connections::Negotiated> for Forward {
+    fn param(&self) -> transport::connections::Negotiated {
+        transport::connections::Negotiated {
+            direction: transport::connections::Direction::In,
+            tls: self.tls.as_ref().map(|t| t.labels()),
+            protocol: transport::connections::Protocol::Opaque,
+        }
+    }
+}
+
 impl svc::Param<transport::labels::Key> for Forward {
     fn param(&self) -> transport::labels::Key {
         transport::labels::Key::inbound_server(
@@ -426,6 +509,16 @@ impl svc::Param<ServerLabel> for Http {
     }
 }
 
+impl svc::Param<transport::connections::Negotiated> for Http {
+    fn param(&self) -> transport::connections::Negotiated {
+        transport::connections::Negotiated {
+            direction: transport::connections::Direction::In,
+            tls: self.tls.status.as_ref().map(|t| t.labels()),
+            protocol: self.http.into(),
+        }
+    }
+}
+
 impl svc::Param<transport::labels::Key> for Http {
     fn param(&self) -> transport::labels::Key {
         transport::labels::Key::inbound_server(
@@ -470,3 +563,36 @@ impl MetricsFamilies {
         ))
     }
 }
+
+// === impl DetectedMetrics ===
+
+impl DetectedMetrics {
+    pub(crate) fn register(reg: &mut prom::Registry) -> Self {
+        let detected = prom::Family::default();
+        reg.register(
+            "detected_connections",
+            "Connections that failed HTTP detection by detected protocol",
+            detected.clone(),
+        );
+        Self(detected)
+    }
+
+    fn record(&self, server: ServerLabel, protocol: Option<http::OpaqueProtocol>) {
+        self.0
+            .get_or_create(&DetectedLabels { server, protocol })
+            .inc();
+    }
+}
+
+// === impl DetectedLabels ===
+
+impl prom::encoding::EncodeLabelSet for DetectedLabels {
+    fn encode(&self, enc: &mut prom::encoding::LabelSetEncoder<'_>) -> std::fmt::Result {
+        use prom::encoding::*;
+
+        self.server.encode(enc)?;
+        ("protocol", self.protocol.map_or("unknown", |p| p.as_str())).encode(enc.encode_label())?;
+
+        Ok(())
+    }
+}
@@ -18,6 +18,7 @@ const RESULTS_HTTP1: &str = "results_total{result=\"http/1\",srv_group=\"policy.
 const RESULTS_HTTP2: &str = "results_total{result=\"http/2\",srv_group=\"policy.linkerd.io\",srv_kind=\"server\",srv_name=\"testsrv\",srv_port=\"1000\"}";
 const RESULTS_READ_TIMEOUT: &str = "results_total{result=\"read_timeout\",srv_group=\"policy.linkerd.io\",srv_kind=\"server\",srv_name=\"testsrv\",srv_port=\"1000\"}";
 const RESULTS_ERROR: &str = "results_total{result=\"error\",srv_group=\"policy.linkerd.io\",srv_kind=\"server\",srv_name=\"testsrv\",srv_port=\"1000\"}";
+const DETECTED_UNKNOWN: &str = "tcp_detected_connections_total{srv_group=\"policy.linkerd.io\",srv_kind=\"server\",srv_name=\"testsrv\",srv_port=\"1000\",protocol=\"unknown\"}";
 
 fn authzs() -> Arc<[Authorization]> {
     Arc::new([Authorization {
@@ -101,6 +102,7 @@ async fn detect_http_non_http() {
         status: tls::ConditionalServerTls::Some(tls::ServerTls::Established {
             client_id: Some(client_id()),
             negotiated_protocol: None,
+            server_id: None,
         }),
         policy: allow(Protocol::Detect {
             timeout: std::time::Duration::from_secs(10),
@@ -113,15 +115,21 @@ async fn detect_http_non_http() {
     iow.write_all(NOT_HTTP).await.unwrap();
 
     let mut registry = prom::Registry::default();
-    inbound()
-        .with_stack(new_panic("http stack must not be used"))
-        .push_detect_http(super::HttpDetectMetrics::register(&mut registry), new_ok())
-        .into_inner()
-        .new_service(target)
-        .oneshot(ior)
-        .await
-        .expect("should succeed");
-
+    let mut inbound_registry = prom::Registry::default();
+    Inbound::new(
+        test_util::default_config(),
+        test_util::runtime().0,
+        &mut inbound_registry,
+    )
+    .with_stack(new_panic("http stack must not be used"))
+    .push_detect_http(super::HttpDetectMetrics::register(&mut registry), new_ok())
+    .into_inner()
+    .new_service(target)
+    .oneshot(ior)
+    .await
+    .expect("should succeed");
+
+    assert_contains_metric!(&inbound_registry, DETECTED_UNKNOWN, 1);
     assert_contains_metric!(&registry, RESULTS_NOT_HTTP, 1);
     assert_contains_metric!(&registry, RESULTS_HTTP1, 0);
     assert_contains_metric!(&registry, RESULTS_HTTP2, 0);
@@ -139,6 +147,7 @@ async fn detect_http() {
         status: tls::ConditionalServerTls::Some(tls::ServerTls::Established {
             client_id: Some(client_id()),
             negotiated_protocol: None,
+            server_id: None,
         }),
         policy: allow(Protocol::Detect {
             timeout: std::time::Duration::from_secs(10),
@@ -179,6 +188,7 @@ async fn hinted_http1() {
         status: tls::ConditionalServerTls::Some(tls::ServerTls::Established {
             client_id: Some(client_id()),
             negotiated_protocol: None,
+            server_id: None,
         }),
         policy: allow(Protocol::Http1(vec![].into())),
     };
@@ -215,6 +225,7 @@ async fn hinted_http1_supports_http2() {
         status: tls::ConditionalServerTls::Some(tls::ServerTls::Established {
             client_id: Some(client_id()),
             negotiated_protocol: None,
+            server_id: None,
         }),
         policy: allow(Protocol::Http1(vec![].into())),
     };
@@ -251,6 +262,7 @@ async fn hinted_http2() {
         status: tls::ConditionalServerTls::Some(tls::ServerTls::Established {
             client_id: Some(client_id()),
             negotiated_protocol: None,
+            server_id: None,
         }),
         policy: allow(Protocol::Http2(vec![].into())),
     };
@@ -278,6 +290,79 @@ async fn hinted_http2() {
     assert_not_contains_metric!(&registry, RESULTS_ERROR);
 }
 
+#[tokio::test(flavor = "current_thread")]
+async fn hinted_grpc() {
+    let _trace = trace::test::trace_init();
+    let target = Tls {
+        client_addr: client_addr(),
+        orig_dst_addr: orig_dst_addr(),
+        status: tls::ConditionalServerTls::None(tls::NoServerTls::NoClientHello),
+        policy: allow(Protocol::Grpc {
+            routes: vec![].into(),
+            grpc_web: false,
+        }),
+    };
+
+    let (ior, _) = io::duplex(100);
+
+    let mut registry = prom::Registry::default();
+    inbound()
+        .with_stack(new_ok())
+        .push_detect_http(
+            super::HttpDetectMetrics::register(&mut registry),
+            new_panic("tcp stack must not be used"),
+        )
+        .into_inner()
+        .new_service(target)
+        .oneshot(ior)
+        .await
+        .expect("should succeed");
+
+    // No detection is performed when gRPC-Web is disabled, so no metrics are recorded.
+    assert_not_contains_metric!(&registry, RESULTS_NOT_HTTP);
+    assert_not_contains_metric!(&registry, RESULTS_HTTP1);
+    assert_not_contains_metric!(&registry, RESULTS_HTTP2);
+    assert_not_contains_metric!(&registry, RESULTS_READ_TIMEOUT);
+    assert_not_contains_metric!(&registry, RESULTS_ERROR);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn hinted_grpc_web_supports_http1() {
+    let _trace = trace::test::trace_init();
+    let target = Tls {
+        client_addr: client_addr(),
+        orig_dst_addr: orig_dst_addr(),
+        status: tls::ConditionalServerTls::None(tls::NoServerTls::NoClientHello),
+        policy: allow(Protocol::Grpc {
+            routes: vec![].into(),
+            grpc_web: true,
+        }),
+    };
+
+    let (ior, mut iow) = io::duplex(100);
+    iow.write_all(HTTP1).await.unwrap();
+
+    let mut registry = prom::Registry::default();
+    inbound()
+        .with_stack(new_ok())
+        .push_detect_http(
+            super::HttpDetectMetrics::register(&mut registry),
+            new_panic("tcp stack must not be used"),
+        )
+        .into_inner()
+        .new_service(target)
+        .oneshot(ior)
+        .await
+        .expect("should succeed");
+
+    // gRPC-Web clients may use HTTP/1.1, so servers that accept gRPC-Web are detected.
+    assert_contains_metric!(&registry, RESULTS_NOT_HTTP, 0);
+    assert_contains_metric!(&registry, RESULTS_HTTP1, 1);
+    assert_contains_metric!(&registry, RESULTS_HTTP2, 0);
+    assert_contains_metric!(&registry, RESULTS_READ_TIMEOUT, 0);
+    assert_contains_metric!(&registry, RESULTS_ERROR, 0);
+}
+
 fn client_id() -> tls::ClientId {
     "testsa.testns.serviceaccount.identity.linkerd.cluster.local"
         .parse()
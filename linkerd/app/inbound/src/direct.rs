@@ -11,7 +11,7 @@ use linkerd_app_core::{
     transport_header::{self, NewTransportHeaderServer, SessionProtocol, TransportHeader},
     Conditional, Error, Infallible, NameAddr, Result,
 };
-use std::fmt::Debug;
+use std::{fmt::Debug, sync::Arc};
 use thiserror::Error;
 use tracing::{debug_span, info_span};
 
@@ -32,6 +32,7 @@ pub(crate) struct LocalTcp {
     client_addr: Remote<ClientAddr>,
     server_addr: Remote<ServerAddr>,
     client_id: tls::ClientId,
+    server_id: Option<Arc<tls::ServerId>>,
     policy: policy::AllowPolicy,
 }
 
@@ -64,6 +65,7 @@ pub struct GatewayTransportHeader {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ClientInfo {
     pub client_id: tls::ClientId,
+    pub server_id: Option<Arc<tls::ServerId>>,
     pub alpn: Option<tls::NegotiatedProtocol>,
     pub client_addr: Remote<ClientAddr>,
     pub local_addr: OrigDstAddr,
@@ -119,6 +121,9 @@ impl<N> Inbound<N> {
                 .push(transport::metrics::NewServer::layer(
                     rt.metrics.proxy.transport.clone(),
                 ))
+                .push(transport::connections::NewRecord::layer(
+                    rt.connections.clone(),
+                ))
                 .check_new_service::<AuthorizedLocalTcp, _>()
                 .push_map_target(|(permit, tcp): (policy::ServerPermit, LocalTcp)| {
                     AuthorizedLocalTcp {
@@ -141,6 +146,9 @@ impl<N> Inbound<N> {
                         .push(transport::metrics::NewServer::layer(
                             rt.metrics.proxy.transport.clone(),
                         ))
+                        .push(transport::connections::NewRecord::layer(
+                            rt.connections.clone(),
+                        ))
                         .into_inner(),
                 )
                 .push_switch(
@@ -163,6 +171,7 @@ impl<N> Inbound<N> {
                                             server_addr: Remote(ServerAddr(addr)),
                                             client_addr: client.client_addr,
                                             client_id: client.client_id,
+                                            server_id: client.server_id,
                                             policy,
                                         }),
                                         Some(protocol) => {
@@ -204,6 +213,9 @@ impl<N> Inbound<N> {
                         .push(transport::metrics::NewServer::layer(
                             rt.metrics.proxy.transport.clone(),
                         ))
+                        .push(transport::connections::NewRecord::layer(
+                            rt.connections.clone(),
+                        ))
                         .instrument(
                             |g: &GatewayTransportHeader| info_span!("gateway", dst = %g.target),
                         )
@@ -252,8 +264,10 @@ where
             Conditional::Some(tls::ServerTls::Established {
                 client_id: Some(client_id),
                 negotiated_protocol,
+                server_id,
             }) => Ok(Self {
                 client_id,
+                server_id,
                 alpn: negotiated_protocol,
                 client_addr: addrs.param(),
                 local_addr: addrs.param(),
@@ -291,6 +305,7 @@ impl Param<tls::ConditionalServerTls> for LocalTcp {
         tls::ConditionalServerTls::Some(tls::ServerTls::Established {
             client_id: Some(self.client_id.clone()),
             negotiated_protocol: None,
+            server_id: self.server_id.clone(),
         })
     }
 }
@@ -303,6 +318,18 @@ impl Param<Remote<ServerAddr>> for AuthorizedLocalTcp {
     }
 }
 
+impl Param<transport::connections::Negotiated> for AuthorizedLocalTcp {
+    fn param(&self) -> transport::connections::Negotiated {
+        transport::connections::Negotiated {
+            direction: transport::connections::Direction::In,
+            tls: tls::ConditionalServerTlsLabels::Some(tls::ServerTlsLabels::Established {
+                client_id: Some(self.client_id.clone()),
+            }),
+            protocol: transport::connections::Protocol::Opaque,
+        }
+    }
+}
+
 impl Param<transport::labels::Key> for AuthorizedLocalTcp {
     fn param(&self) -> transport::labels::Key {
         transport::labels::Key::inbound_server(
@@ -335,6 +362,18 @@ impl Param<Remote<ClientAddr>> for LocalHttp {
     }
 }
 
+impl Param<transport::connections::Negotiated> for LocalHttp {
+    fn param(&self) -> transport::connections::Negotiated {
+        transport::connections::Negotiated {
+            direction: transport::connections::Direction::In,
+            tls: tls::ConditionalServerTlsLabels::Some(tls::ServerTlsLabels::Established {
+                client_id: Some(self.client.client_id.clone()),
+            }),
+            protocol: Param::<http::Variant>::param(self).into(),
+        }
+    }
+}
+
 impl Param<transport::labels::Key> for LocalHttp {
     fn param(&self) -> transport::labels::Key {
         transport::labels::Key::inbound_server(
@@ -379,6 +418,7 @@ impl svc::Param<tls::ConditionalServerTls> for LocalHttp {
         tls::ConditionalServerTls::Some(tls::ServerTls::Established {
             client_id: Some(self.client.client_id.clone()),
             negotiated_protocol: self.client.alpn.clone(),
+            server_id: self.client.server_id.clone(),
         })
     }
 }
@@ -391,6 +431,20 @@ impl Param<GatewayAddr> for GatewayTransportHeader {
     }
 }
 
+impl Param<transport::connections::Negotiated> for GatewayTransportHeader {
+    fn param(&self) -> transport::connections::Negotiated {
+        transport::connections::Negotiated {
+            direction: transport::connections::Direction::In,
+            tls: self.param(),
+            protocol: match self.protocol {
+                Some(SessionProtocol::Http1) => transport::connections::Protocol::Http1,
+                Some(SessionProtocol::Http2) => transport::connections::Protocol::Http2,
+                None => transport::connections::Protocol::Opaque,
+            },
+        }
+    }
+}
+
 impl Param<transport::labels::Key> for GatewayTransportHeader {
     fn param(&self) -> transport::labels::Key {
         transport::labels::Key::inbound_server(
@@ -424,6 +478,7 @@ impl Param<tls::ConditionalServerTls> for GatewayTransportHeader {
         tls::ConditionalServerTls::Some(tls::ServerTls::Established {
             client_id: Some(self.client.client_id.clone()),
             negotiated_protocol: self.client.alpn.clone(),
+            server_id: self.client.server_id.clone(),
         })
     }
 }
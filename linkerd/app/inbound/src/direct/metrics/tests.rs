@@ -40,6 +40,7 @@ fn run_metric_test(header: TransportHeader) -> prom::Registry {
     let local_addr = OrigDstAddr(([127, 0, 0, 1], 4143).into());
     let client_info = ClientInfo {
         client_id: client_id.clone(),
+        server_id: None,
         alpn: Some(tls::NegotiatedProtocol("transport.l5d.io/v1".into())),
         client_addr,
         local_addr,
@@ -101,7 +101,9 @@ impl<C> Inbound<C> {
                 .push(transport::metrics::Client::layer(rt.metrics.proxy.transport.clone()))
                 .check_service::<Http>()
                 .push_map_target(|(_version, target)| target)
-                .push(http::client::layer())
+                .push(http::client::layer_with_metrics(
+                    rt.metrics.h1_pool.clone(),
+                ))
                 .check_service::<Http>()
                 .push_on_service(svc::MapErr::layer_boxed())
                 .into_new_service()
@@ -217,7 +219,7 @@ impl<C> Inbound<C> {
                 .instrument(|_: &Logical| debug_span!("profile"))
                 .arc_new_http();
 
-            discover
+            let route = discover
                 // Skip the profile stack if it takes too long to become ready.
                 .push_when_unready(config.profile_skip_timeout, http.into_inner())
                 .push_on_service(
@@ -245,11 +247,37 @@ impl<C> Inbound<C> {
                 .push(svc::ArcNewService::layer())
                 .push(svc::NewOneshotRoute::layer_via(|t: &policy::Permitted<T>| {
                     LogicalPerRequest::from(t)
-                }))
+                }));
+
+            route
+                .clone()
+                // Enforce deadlines set by route policy.
+                .push_on_service(http::BoxRequest::layer())
+                .push_on_service(http::EnforceTimeouts::layer())
+                .push_on_service(http::BoxResponse::layer())
+                .push_switch(
+                    // Only routes with a deadline filter need to enforce timeouts.
+                    |permitted: policy::Permitted<T>| -> Result<_, Infallible> {
+                        if permitted.has_deadline() {
+                            return Ok(svc::Either::Left(permitted));
+                        }
+                        Ok(svc::Either::Right(permitted))
+                    },
+                    route.into_inner(),
+                )
+                .push(linkerd_http_compress::NewCompress::layer(
+                    rt.metrics.compression.clone(),
+                ))
+                .push(linkerd_http_grpc_web::NewRecordGrpcWeb::layer(
+                    rt.metrics.grpc_web.clone(),
+                ))
                 .push(self::metrics::layer(&rt.metrics))
                 .check_new_service::<policy::Permitted<T>, http::Request<http::BoxBody>>()
                 .push(svc::ArcNewService::layer())
                 .push(policy::NewHttpPolicy::layer(rt.metrics.http_authz.clone()))
+                // Translate gRPC-Web requests before policy is enforced so that gRPC
+                // routes match the translated request.
+                .push(linkerd_http_grpc_web::NewGrpcWeb::<policy::AllowPolicy, _>::layer())
                 // Used by tap.
                 .push_http_insert_target::<tls::ConditionalServerTls>()
                 .push_http_insert_target::<Remote<ClientAddr>>()
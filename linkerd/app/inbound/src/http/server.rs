@@ -8,7 +8,7 @@ use linkerd_app_core::{
     proxy::http,
     svc::{self, ExtractParam, Param},
     tls,
-    transport::{ClientAddr, OrigDstAddr, Remote},
+    transport::{connections, ClientAddr, OrigDstAddr, Remote},
     Error, Result,
 };
 use linkerd_http_access_log::NewAccessLog;
@@ -117,6 +117,10 @@ impl<H> Inbound<H> {
 
             http.check_new_service::<T, http::Request<http::BoxBody>>()
                 .unlift_new()
+                .push_on_service(connections::NewCountStreams::layer(
+                    rt.connections.clone(),
+                    connections::Direction::In,
+                ))
                 .check_new_new_service::<T, http::ClientHandle, http::Request<_>>()
                 .push(http::NewServeHttp::layer(move |t: &T| http::ServerParams {
                     version: t.param(),
@@ -220,6 +224,13 @@ impl errors::HttpRescue<Error> for ServerRescue {
             return Ok(errors::SyntheticHttpResponse::unavailable(error));
         }
 
+        // A deadline propagated by the client's proxy was exceeded.
+        if errors::is_caused_by::<http::stream_timeouts::ResponseTimeoutError>(&*error) {
+            return Ok(errors::SyntheticHttpResponse::gateway_timeout_nonfatal(
+                error,
+            ));
+        }
+
         if errors::is_caused_by::<errors::H2Error>(&*error) {
             return Err(error);
         }
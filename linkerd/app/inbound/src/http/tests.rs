@@ -433,6 +433,62 @@ async fn http1_connect_timeout_unmeshed_response_error_header() {
         .expect("background task failed");
 }
 
+#[tokio::test(flavor = "current_thread")]
+async fn http1_propagated_deadline() {
+    let _trace = trace_init();
+    tokio::time::pause();
+
+    // Build a mock connect that sleeps longer than the default inbound
+    // connect timeout.
+    let connect = support::connect().endpoint(Target::addr(), connect_timeout());
+
+    // The port's overrides enforce deadlines propagated in a header, so the
+    // request times out before the connect timeout is reached.
+    let target = Target::meshed_http1().with_overrides(policy::PortOverrides {
+        deadline_ports: [80..=80].into_iter().collect(),
+        deadline: policy::Deadline {
+            http_header: Some(::http::HeaderName::from_static("l5d-deadline")),
+            max: None,
+        },
+        ..Default::default()
+    });
+    let mut client = hyper::client::conn::http1::Builder::new();
+    let profiles = profile::resolver();
+    let profile_tx =
+        profiles.profile_tx(NameAddr::from_str_and_port("foo.svc.cluster.local", 5550).unwrap());
+    profile_tx.send(profile::Profile::default()).unwrap();
+    let cfg = default_config();
+    let (rt, _shutdown) = runtime();
+    let server = build_server(cfg, rt, profiles, connect).new_service(target);
+    let (mut client, bg) = http_util::connect_and_accept_http1(&mut client, server).await;
+
+    let req = Request::builder()
+        .method(http::Method::GET)
+        .uri("http://foo.svc.cluster.local:5550")
+        .header("l5d-deadline", "100ms")
+        .body(BoxBody::default())
+        .unwrap();
+    let start = time::Instant::now();
+    let rsp = client
+        .send_request(req)
+        .await
+        .expect("HTTP client request failed");
+    tracing::info!(?rsp);
+    assert_eq!(rsp.status(), http::StatusCode::GATEWAY_TIMEOUT);
+    assert!(
+        start.elapsed() < time::Duration::from_secs(1),
+        "the propagated deadline must be enforced before the connect timeout"
+    );
+
+    // Wait for all of the background tasks to complete, panicking if any returned an error.
+    drop(client);
+    bg.join_all()
+        .await
+        .into_iter()
+        .collect::<Result<Vec<()>, Error>>()
+        .expect("background task failed");
+}
+
 #[tokio::test(flavor = "current_thread")]
 async fn h2_response_meshed_error_header() {
     let _trace = trace_init();
@@ -695,6 +751,64 @@ async fn grpc_response_class() {
     drop(bg);
 }
 
+#[tokio::test(flavor = "current_thread")]
+async fn grpc_web_http1_to_h2() {
+    let _trace = trace_init();
+
+    // Build a mock connector that serves a gRPC server over HTTP/2.
+    let connect = {
+        let mut server = hyper::server::conn::http2::Builder::new(TokioExecutor::new());
+        server.timer(hyper_util::rt::TokioTimer::new());
+        support::connect().endpoint_fn_boxed(
+            Target::addr(),
+            grpc_status_server(server, tonic::Code::Unknown),
+        )
+    };
+
+    // Browsers send gRPC-Web requests over HTTP/1.1.
+    let mut client = hyper::client::conn::http1::Builder::new();
+    let profiles = profile::resolver();
+    let profile_tx =
+        profiles.profile_tx(NameAddr::from_str_and_port("foo.svc.cluster.local", 5550).unwrap());
+    profile_tx.send(profile::Profile::default()).unwrap();
+    let cfg = default_config();
+    let (rt, _shutdown) = runtime();
+    let server = build_server(cfg, rt, profiles, connect).new_service(Target::UNMESHED_GRPC_WEB);
+    let (mut client, bg) = http_util::connect_and_accept_http1(&mut client, server).await;
+
+    let req = Request::builder()
+        .method(http::Method::POST)
+        .uri("http://foo.svc.cluster.local:5550/foo.Bar/Baz")
+        .header(http::header::CONTENT_TYPE, "application/grpc-web")
+        .body(BoxBody::default())
+        .unwrap();
+    let rsp = client
+        .send_request(req)
+        .await
+        .expect("HTTP client request failed");
+    tracing::info!(?rsp);
+    assert_eq!(rsp.status(), http::StatusCode::OK);
+    assert_eq!(rsp.version(), ::http::Version::HTTP_11);
+    assert_eq!(
+        rsp.headers().get(http::header::CONTENT_TYPE).unwrap(),
+        "application/grpc-web"
+    );
+
+    // The backend's trailers are encoded in the response body.
+    use http_body_util::BodyExt;
+    let body = rsp
+        .into_body()
+        .collect()
+        .await
+        .expect("response body must succeed")
+        .to_bytes();
+    assert_eq!(body[0], 0x80, "body must end with a trailers frame");
+    assert_eq!(&body[5..], b"grpc-status:2\r\n");
+
+    drop(client);
+    let _ = bg.join_all().await;
+}
+
 #[tokio::test(flavor = "current_thread")]
 async fn unsafe_authority_labels_true() {
     let _trace = trace_init();
@@ -882,7 +996,15 @@ fn connect_timeout() -> Box<dyn FnMut(Remote<ServerAddr>) -> ConnectFuture + Sen
 }
 
 #[derive(Clone, Debug)]
-struct Target(http::Variant, tls::ConditionalServerTls);
+/// A server target with its HTTP version, TLS status, whether the server's
+/// policy accepts gRPC-Web requests, and the port overrides applied to its
+/// policy.
+struct Target(
+    http::Variant,
+    tls::ConditionalServerTls,
+    bool,
+    Option<policy::PortOverrides>,
+);
 
 #[track_caller]
 fn check_error_header(hdrs: &::http::HeaderMap, expected: &str) {
@@ -903,10 +1025,20 @@ impl Target {
     const UNMESHED_HTTP1: Self = Self(
         http::Variant::Http1,
         tls::ConditionalServerTls::None(tls::NoServerTls::NoClientHello),
+        false,
+        None,
     );
     const UNMESHED_H2: Self = Self(
         http::Variant::H2,
         tls::ConditionalServerTls::None(tls::NoServerTls::NoClientHello),
+        false,
+        None,
+    );
+    const UNMESHED_GRPC_WEB: Self = Self(
+        http::Variant::Http1,
+        tls::ConditionalServerTls::None(tls::NoServerTls::NoClientHello),
+        true,
+        None,
     );
 
     fn meshed_http1() -> Self {
@@ -919,7 +1051,10 @@ impl Target {
                         .unwrap(),
                 )),
                 negotiated_protocol: None,
+                server_id: None,
             }),
+            false,
+            None,
         )
     }
 
@@ -933,10 +1068,17 @@ impl Target {
                         .unwrap(),
                 )),
                 negotiated_protocol: None,
+                server_id: None,
             }),
+            false,
+            None,
         )
     }
 
+    fn with_overrides(self, overrides: policy::PortOverrides) -> Self {
+        Self(self.0, self.1, self.2, Some(overrides))
+    }
+
     fn addr() -> SocketAddr {
         ([127, 0, 0, 1], 80).into()
     }
@@ -983,20 +1125,30 @@ impl svc::Param<policy::AllowPolicy> for Target {
                 name: "testsaz".into(),
             }),
         }]);
-        let (policy, _) = policy::AllowPolicy::for_test(
-            self.param(),
-            policy::ServerPolicy {
-                protocol: policy::Protocol::Http1(Arc::new([
-                    linkerd_proxy_server_policy::http::default(authorizations),
-                ])),
-                meta: Arc::new(policy::Meta::Resource {
-                    group: "policy.linkerd.io".into(),
-                    kind: "server".into(),
-                    name: "testsrv".into(),
-                }),
-                local_rate_limit: Default::default(),
+        let server = policy::ServerPolicy {
+            protocol: if self.2 {
+                policy::Protocol::Grpc {
+                    routes: Arc::new([linkerd_proxy_server_policy::grpc::default(authorizations)]),
+                    grpc_web: true,
+                }
+            } else {
+                policy::Protocol::Http1(Arc::new([linkerd_proxy_server_policy::http::default(
+                    authorizations,
+                )]))
             },
-        );
+            meta: Arc::new(policy::Meta::Resource {
+                group: "policy.linkerd.io".into(),
+                kind: "server".into(),
+                name: "testsrv".into(),
+            }),
+            local_rate_limit: Default::default(),
+        };
+        let dst: OrigDstAddr = self.param();
+        let server = match self.3 {
+            Some(ref overrides) => overrides.apply(dst.port(), server),
+            None => server,
+        };
+        let (policy, _) = policy::AllowPolicy::for_test(dst, server);
         policy
     }
 }
@@ -34,6 +34,7 @@ use linkerd_app_core::{
     transport::{self, Remote, ServerAddr},
     Error, NameAddr, NameMatch, ProxyRuntime,
 };
+use rangemap::RangeInclusiveSet;
 use std::{fmt::Debug, time::Duration};
 use thiserror::Error;
 
@@ -57,6 +58,12 @@ pub struct Config {
 
     /// Enables unsafe authority labels.
     pub unsafe_authority_labels: bool,
+
+    /// Ports on which servers are expected to speak first (e.g. MySQL).
+    /// Protocol detection on these ports stops waiting for the client after
+    /// `server_first_detect_timeout`.
+    pub server_first_ports: RangeInclusiveSet<u16>,
+    pub server_first_detect_timeout: Duration,
 }
 
 #[derive(Clone)]
@@ -73,6 +80,7 @@ struct Runtime {
     tap: tap::Registry,
     span_sink: Option<SpanSink>,
     drain: drain::Watch,
+    connections: transport::Connections,
 }
 
 /// Indicates the name to be used to route gateway connections.
@@ -147,11 +155,12 @@ impl<S> Inbound<S> {
 impl Inbound<()> {
     pub fn new(config: Config, runtime: ProxyRuntime, prom: &mut prom::Registry) -> Self {
         let runtime = Runtime {
-            metrics: InboundMetrics::new(runtime.metrics, prom),
+            metrics: InboundMetrics::new(runtime.metrics, prom, config.proxy.histogram_encoding),
             identity: runtime.identity,
             tap: runtime.tap,
             span_sink: runtime.span_sink,
             drain: runtime.drain,
+            connections: runtime.connections,
         };
         Self {
             config,
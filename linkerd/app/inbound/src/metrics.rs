@@ -12,7 +12,7 @@ pub(crate) mod authz;
 pub(crate) mod error;
 
 use crate::http::router::metrics::{
-    count_reqs::RequestCountFamilies, req_body::RequestBodyFamilies,
+    count_reqs::RequestCountFamilies, labels::RouteLabels, req_body::RequestBodyFamilies,
     req_duration::RequestDurationFamilies, rsp_body::ResponseBodyFamilies,
     rsp_duration::ResponseDurationFamilies, status::StatusCodeFamilies,
 };
@@ -33,6 +33,7 @@ pub struct InboundMetrics {
     pub proxy: Proxy,
 
     pub detect: crate::detect::MetricsFamilies,
+    pub(crate) tcp_detected: crate::detect::DetectedMetrics,
     pub direct: crate::direct::MetricsFamilies,
     pub request_count: RequestCountFamilies,
     pub request_body_data: RequestBodyFamilies,
@@ -40,23 +41,41 @@ pub struct InboundMetrics {
     pub response_body_data: ResponseBodyFamilies,
     pub response_duration: ResponseDurationFamilies,
     pub status_codes: StatusCodeFamilies,
+    pub compression: linkerd_http_compress::CompressMetrics,
+    pub grpc_web: linkerd_http_grpc_web::GrpcWebMetrics<RouteLabels>,
+    pub h1_pool: linkerd_app_core::proxy::http::h1::PoolMetrics,
 }
 
 impl InboundMetrics {
-    pub(crate) fn new(proxy: Proxy, reg: &mut prom::Registry) -> Self {
+    pub(crate) fn new(
+        proxy: Proxy,
+        reg: &mut prom::Registry,
+        histograms: prom::HistogramEncoding,
+    ) -> Self {
         let detect =
             crate::detect::MetricsFamilies::register(reg.sub_registry_with_prefix("tcp_detect"));
+        let tcp_detected =
+            crate::detect::DetectedMetrics::register(reg.sub_registry_with_prefix("tcp"));
         let direct = crate::direct::MetricsFamilies::register(
             reg.sub_registry_with_prefix("tcp_transport_header"),
         );
         let request_count = RequestCountFamilies::register(reg);
         let request_body_data = RequestBodyFamilies::register(reg);
         let request_duration =
-            RequestDurationFamilies::register(reg, Self::REQUEST_BUCKETS.iter().copied());
+            RequestDurationFamilies::register(reg, histograms.buckets(Self::REQUEST_BUCKETS));
         let response_body_data = ResponseBodyFamilies::register(reg);
         let response_duration =
-            ResponseDurationFamilies::register(reg, Self::RESPONSE_BUCKETS.iter().copied());
+            ResponseDurationFamilies::register(reg, histograms.buckets(Self::RESPONSE_BUCKETS));
         let status_codes = StatusCodeFamilies::register(reg);
+        let compression = linkerd_http_compress::CompressMetrics::register(
+            reg.sub_registry_with_prefix("http_route_compression"),
+        );
+        let grpc_web = linkerd_http_grpc_web::GrpcWebMetrics::register(
+            reg.sub_registry_with_prefix("http_route_grpc_web"),
+        );
+        let h1_pool = linkerd_app_core::proxy::http::h1::PoolMetrics::register(
+            reg.sub_registry_with_prefix("http1_pool"),
+        );
 
         Self {
             http_authz: authz::HttpAuthzMetrics::default(),
@@ -65,6 +84,7 @@ impl InboundMetrics {
             tcp_errors: error::TcpErrorMetrics::default(),
             proxy,
             detect,
+            tcp_detected,
             direct,
             request_count,
             request_body_data,
@@ -72,6 +92,9 @@ impl InboundMetrics {
             response_body_data,
             response_duration,
             status_codes,
+            compression,
+            grpc_web,
+            h1_pool,
         }
     }
 
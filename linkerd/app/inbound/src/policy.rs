@@ -9,7 +9,7 @@ use crate::metrics::authz::HTTPLocalRateLimitLabels;
 
 pub(crate) use self::store::Store;
 pub use self::{
-    config::Config,
+    config::{Config, PortOverrides},
     http::{
         HttpInvalidPolicy, HttpRouteInvalidRedirect, HttpRouteNotFound, HttpRouteRedirect,
         HttpRouteUnauthorized, NewHttpPolicy, PermitVariant, Permitted,
@@ -19,16 +19,18 @@ pub use self::{
 
 pub use linkerd_app_core::metrics::ServerLabel;
 use linkerd_app_core::{
-    identity as id,
     metrics::{RouteAuthzLabels, ServerAuthzLabels},
-    tls,
+    svc, tls,
     transport::{ClientAddr, OrigDstAddr, Remote},
 };
 use linkerd_idle_cache::Cached;
 pub use linkerd_proxy_server_policy::{
     authz::Suffix,
     grpc::Route as GrpcRoute,
-    http::{filter::Redirection, Route as HttpRoute},
+    http::{
+        filter::{Deadline, Redirection},
+        Route as HttpRoute,
+    },
     route, Authentication, Authorization, Meta, Protocol, RateLimitError, RoutePolicy,
     ServerPolicy,
 };
@@ -163,12 +165,23 @@ impl AllowPolicy {
             Protocol::Detect { http, .. } | Protocol::Http1(http) | Protocol::Http2(http) => {
                 Some(Routes::Http(http.clone()))
             }
-            Protocol::Grpc(grpc) => Some(Routes::Grpc(grpc.clone())),
+            Protocol::Grpc { routes, .. } => Some(Routes::Grpc(routes.clone())),
             _ => None,
         }
     }
 }
 
+/// gRPC-Web requests are translated to gRPC for gRPC servers that enable it.
+impl svc::Param<linkerd_http_grpc_web::Enabled> for AllowPolicy {
+    fn param(&self) -> linkerd_http_grpc_web::Enabled {
+        let enabled = matches!(
+            self.server.borrow().protocol,
+            Protocol::Grpc { grpc_web: true, .. }
+        );
+        linkerd_http_grpc_web::Enabled(enabled)
+    }
+}
+
 fn is_tls_authorized(tls: &tls::ConditionalServerTls, authz: &Authorization) -> bool {
     match authz.authentication {
         Authentication::Unauthenticated => true,
@@ -180,20 +193,11 @@ fn is_tls_authorized(tls: &tls::ConditionalServerTls, authz: &Authorization) ->
             )
         }
 
-        Authentication::TlsAuthenticated {
-            ref identities,
-            ref suffixes,
-        } => match tls {
+        Authentication::TlsAuthenticated { .. } => match tls {
             tls::ConditionalServerTls::Some(tls::ServerTls::Established {
                 client_id: Some(tls::server::ClientId(ref id)),
                 ..
-            }) => match id {
-                id::Id::Uri(_) => identities.contains(&*id.to_str()),
-                id::Id::Dns(_) => {
-                    identities.contains(&*id.to_str())
-                        || suffixes.iter().any(|s| s.contains(&id.to_str()))
-                }
-            },
+            }) => authz.authentication.permits_client_id(id),
             _ => false,
         },
     }
@@ -255,6 +259,7 @@ mod tests {
         tls::ConditionalServerTls::Some(tls::ServerTls::Established {
             client_id: Some(client_id),
             negotiated_protocol: None,
+            server_id: None,
         })
     }
 
@@ -340,4 +345,41 @@ mod tests {
         );
         assert!(is_tls_authorized(&tls, &authz))
     }
+
+    #[test]
+    fn is_authorized_for_trust_domain_wildcard() {
+        let tls = server_tls("spiffe://other-root/ns/web/sa/default");
+        let authz = authorization(BTreeSet::from(["spiffe://other-root/*".into()]), vec![]);
+        assert!(is_tls_authorized(&tls, &authz))
+    }
+
+    #[test]
+    fn is_not_authorized_for_other_trust_domain_wildcard() {
+        let tls = server_tls("spiffe://other-root/ns/web/sa/default");
+        let authz = authorization(BTreeSet::from(["spiffe://some-root/*".into()]), vec![]);
+        assert!(!is_tls_authorized(&tls, &authz))
+    }
+
+    #[test]
+    fn is_authorized_for_spiffe_path_wildcard() {
+        let authz = authorization(
+            BTreeSet::from(["spiffe://other-root/ns/web/*".into()]),
+            vec![],
+        );
+        assert!(is_tls_authorized(
+            &server_tls("spiffe://other-root/ns/web/sa/default"),
+            &authz
+        ));
+        assert!(!is_tls_authorized(
+            &server_tls("spiffe://other-root/ns/webapp/sa/default"),
+            &authz
+        ));
+    }
+
+    #[test]
+    fn is_not_authorized_for_dns_id_and_spiffe_wildcard() {
+        let tls = server_tls("some.id.local");
+        let authz = authorization(BTreeSet::from(["spiffe://some-root/*".into()]), vec![]);
+        assert!(!is_tls_authorized(&tls, &authz))
+    }
 }
@@ -1,3 +1,4 @@
+use super::PortOverrides;
 use futures::prelude::*;
 use linkerd2_proxy_api::inbound::{
     self as api, inbound_server_policies_client::InboundServerPoliciesClient as Client,
@@ -10,7 +11,7 @@ use linkerd_app_core::{
 };
 use linkerd_proxy_server_policy::ServerPolicy;
 use linkerd_tonic_stream::{LimitReceiveFuture, ReceiveLimits};
-use linkerd_tonic_watch::StreamWatch;
+use linkerd_tonic_watch::{Snapshots, StreamWatch};
 use std::sync::Arc;
 use tokio::time;
 
@@ -19,6 +20,8 @@ pub(super) struct Api<S> {
     workload: Arc<str>,
     limits: ReceiveLimits,
     default_detect_timeout: time::Duration,
+    overrides: Arc<PortOverrides>,
+    snapshots: Snapshots<u16, ServerPolicy>,
     client: Client<S>,
 }
 
@@ -40,12 +43,16 @@ where
         workload: Arc<str>,
         limits: ReceiveLimits,
         default_detect_timeout: time::Duration,
+        overrides: PortOverrides,
+        snapshots: Snapshots<u16, ServerPolicy>,
         client: S,
     ) -> Self {
         Self {
             workload,
             limits,
             default_detect_timeout,
+            overrides: Arc::new(overrides),
+            snapshots,
             client: Client::new(client),
         }
     }
@@ -82,12 +89,14 @@ where
 
         let detect_timeout = self.default_detect_timeout;
         let limits = self.limits;
+        let overrides = self.overrides.clone();
+        let snapshots = self.snapshots.clone();
         let mut client = self.client.clone();
         Box::pin(async move {
             let rsp = LimitReceiveFuture::new(limits, client.watch_port(tonic::Request::new(req)))
                 .await?;
             Ok(rsp.map(move |s| {
-                s.map_ok(move |up| {
+                let policies = s.map_ok(move |up| {
                     // If the server returned an invalid server policy, we
                     // default to using an invalid policy that causes all
                     // requests to report an internal error.
@@ -97,10 +106,11 @@ where
                             .get_or_init(|| ServerPolicy::invalid(detect_timeout))
                             .clone()
                     });
+                    let policy = overrides.apply(port, policy);
                     tracing::debug!(?policy);
                     policy
-                })
-                .boxed()
+                });
+                snapshots.observe(port, policies.boxed())
             }))
         })
     }
@@ -1,6 +1,13 @@
-use super::{api::Api, DefaultPolicy, GetPolicy, Protocol, ServerPolicy, Store};
+use super::{
+    api::Api, route, DefaultPolicy, GetPolicy, Protocol, RoutePolicy, ServerPolicy, Store,
+};
 use linkerd_app_core::{exp_backoff::ExponentialBackoff, proxy::http, Error};
+use linkerd_proxy_server_policy::{
+    grpc,
+    http::filter::{Compression, Deadline},
+};
 use linkerd_tonic_stream::ReceiveLimits;
+use linkerd_tonic_watch::Snapshots;
 use rangemap::RangeInclusiveSet;
 use std::{
     collections::{HashMap, HashSet},
@@ -20,15 +27,41 @@ pub enum Config {
         cache_max_idle_age: Duration,
         ports: HashSet<u16>,
         opaque_ports: RangeInclusiveSet<u16>,
+        overrides: PortOverrides,
     },
     Fixed {
         default: DefaultPolicy,
         cache_max_idle_age: Duration,
         ports: HashMap<u16, ServerPolicy>,
         opaque_ports: RangeInclusiveSet<u16>,
+        overrides: PortOverrides,
     },
 }
 
+/// Configures per-port behavior that the policy API does not describe.
+///
+/// Overrides are applied to each port's server policy as it is discovered (or,
+/// for fixed policies, at startup).
+#[derive(Clone, Debug, Default)]
+pub struct PortOverrides {
+    /// Ports on which gRPC servers also accept gRPC-Web requests.
+    pub grpc_web_ports: RangeInclusiveSet<u16>,
+
+    /// Ports on which every route limits requests to the deadline propagated
+    /// by the client's proxy.
+    pub deadline_ports: RangeInclusiveSet<u16>,
+
+    /// The deadline applied to requests on `deadline_ports`.
+    pub deadline: Deadline,
+
+    /// Ports on which HTTP routes compress responses (and, optionally,
+    /// decompress requests).
+    pub compression_ports: RangeInclusiveSet<u16>,
+
+    /// The compression applied to requests on `compression_ports`.
+    pub compression: Arc<Compression>,
+}
+
 // === impl Config ===
 
 impl Config {
@@ -38,6 +71,7 @@ impl Config {
         client: C,
         backoff: ExponentialBackoff,
         limits: ReceiveLimits,
+        snapshots: Snapshots<u16, ServerPolicy>,
     ) -> impl GetPolicy + Clone + Send + Sync + 'static
     where
         C: tonic::client::GrpcService<tonic::body::Body, Error = Error>,
@@ -52,13 +86,24 @@ impl Config {
                 ports,
                 cache_max_idle_age,
                 opaque_ports,
-            } => Store::spawn_fixed(default, cache_max_idle_age, ports, opaque_ports),
+                overrides,
+            } => {
+                let ports = ports
+                    .into_iter()
+                    .map(|(port, policy)| (port, overrides.apply(port, policy)))
+                    .collect::<HashMap<_, _>>();
+                for (port, policy) in &ports {
+                    snapshots.record(*port, policy.clone());
+                }
+                Store::spawn_fixed(default, cache_max_idle_age, ports, opaque_ports)
+            }
 
             Self::Discover {
                 default,
                 ports,
                 cache_max_idle_age,
                 opaque_ports,
+                overrides,
             } => {
                 let watch = {
                     let detect_timeout = match default {
@@ -68,10 +113,201 @@ impl Config {
                         }) => timeout,
                         _ => Duration::from_secs(10),
                     };
-                    Api::new(workload, limits, detect_timeout, client).into_watch(backoff)
+                    Api::new(
+                        workload,
+                        limits,
+                        detect_timeout,
+                        overrides,
+                        snapshots,
+                        client,
+                    )
+                    .into_watch(backoff)
                 };
                 Store::spawn_discover(default, cache_max_idle_age, watch, ports, opaque_ports)
             }
         }
     }
 }
+
+// === impl PortOverrides ===
+
+impl PortOverrides {
+    pub(crate) fn apply(&self, port: u16, mut policy: ServerPolicy) -> ServerPolicy {
+        use linkerd_proxy_server_policy::http::Filter;
+
+        if let Protocol::Grpc {
+            ref mut grpc_web, ..
+        } = policy.protocol
+        {
+            *grpc_web |= self.grpc_web_ports.contains(&port);
+        }
+
+        let mut http_filters = vec![];
+        let mut grpc_filters = vec![];
+        if self.deadline_ports.contains(&port) {
+            http_filters.push(Filter::Deadline(self.deadline.clone()));
+            grpc_filters.push(grpc::Filter::Deadline(self.deadline.clone()));
+        }
+        // gRPC negotiates message compression itself.
+        if self.compression_ports.contains(&port) {
+            http_filters.push(Filter::Compression(self.compression.clone()));
+        }
+
+        policy.protocol = match policy.protocol {
+            Protocol::Detect {
+                http,
+                timeout,
+                tcp_authorizations,
+            } => Protocol::Detect {
+                http: push_filters(http, &http_filters),
+                timeout,
+                tcp_authorizations,
+            },
+            Protocol::Http1(http) => Protocol::Http1(push_filters(http, &http_filters)),
+            Protocol::Http2(http) => Protocol::Http2(push_filters(http, &http_filters)),
+            Protocol::Grpc { routes, grpc_web } => Protocol::Grpc {
+                routes: push_filters(routes, &grpc_filters),
+                grpc_web,
+            },
+            protocol => protocol,
+        };
+
+        policy
+    }
+}
+
+/// Adds filters to every rule of the given routes.
+fn push_filters<M: Clone, F: Clone>(
+    routes: Arc<[route::Route<M, RoutePolicy<F>>]>,
+    filters: &[F],
+) -> Arc<[route::Route<M, RoutePolicy<F>>]> {
+    if filters.is_empty() {
+        return routes;
+    }
+    routes
+        .iter()
+        .cloned()
+        .map(|mut route| {
+            for rule in &mut route.rules {
+                rule.policy.filters.extend_from_slice(filters);
+            }
+            route
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policy::Meta;
+    use linkerd_app_core::{svc, transport::OrigDstAddr};
+    use linkerd_proxy_server_policy::{Authentication, Authorization};
+
+    fn authorizations() -> Arc<[Authorization]> {
+        Arc::new([Authorization {
+            authentication: Authentication::Unauthenticated,
+            networks: vec![Default::default()],
+            meta: Meta::new_default("authz"),
+        }])
+    }
+
+    fn grpc_server() -> ServerPolicy {
+        ServerPolicy {
+            protocol: Protocol::Grpc {
+                routes: Arc::new([grpc::default(authorizations())]),
+                grpc_web: false,
+            },
+            meta: Meta::new_default("grpc"),
+            local_rate_limit: Default::default(),
+        }
+    }
+
+    fn http_server() -> ServerPolicy {
+        ServerPolicy {
+            protocol: Protocol::Http1(Arc::new([linkerd_proxy_server_policy::http::default(
+                authorizations(),
+            )])),
+            meta: Meta::new_default("http"),
+            local_rate_limit: Default::default(),
+        }
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn grpc_web_ports_enable_grpc_web() {
+        let config = Config::Fixed {
+            default: DefaultPolicy::Deny,
+            cache_max_idle_age: Duration::from_secs(20),
+            ports: [(8080, grpc_server()), (8081, grpc_server())]
+                .into_iter()
+                .collect(),
+            opaque_ports: Default::default(),
+            overrides: PortOverrides {
+                grpc_web_ports: [8080..=8080].into_iter().collect(),
+                ..Default::default()
+            },
+        };
+        // Fixed policies never use the control plane client.
+        let client = svc::mk(|_: http::Request<tonic::body::Body>| {
+            futures::future::pending::<Result<http::Response<http::BoxBody>, Error>>()
+        });
+        let snapshots = Snapshots::default();
+        let policies = config.build(
+            "test".into(),
+            client,
+            ExponentialBackoff::try_new(Duration::from_secs(1), Duration::from_secs(1), 0.0)
+                .unwrap(),
+            Default::default(),
+            snapshots.clone(),
+        );
+
+        let enabled = |port: u16| {
+            let policy = policies.get_policy(OrigDstAddr(([192, 0, 2, 2], port).into()));
+            svc::Param::<linkerd_http_grpc_web::Enabled>::param(&policy).0
+        };
+        assert!(
+            enabled(8080),
+            "gRPC-Web must be enabled on configured ports"
+        );
+        assert!(
+            !enabled(8081),
+            "gRPC-Web must not be enabled on other ports"
+        );
+
+        let snapshot = snapshots.get(&8080).expect("fixed policies are recorded");
+        assert!(matches!(
+            snapshot.value.protocol,
+            Protocol::Grpc { grpc_web: true, .. }
+        ));
+    }
+
+    #[test]
+    fn compression_ports_compress_http_routes() {
+        use linkerd_proxy_server_policy::http::Filter;
+
+        let compression = Arc::new(Compression {
+            min_size: 0,
+            ..Default::default()
+        });
+        let overrides = PortOverrides {
+            compression_ports: [8080..=8080].into_iter().collect(),
+            compression: compression.clone(),
+            ..Default::default()
+        };
+
+        let filters = |policy: ServerPolicy| match policy.protocol {
+            Protocol::Http1(routes) => routes[0].rules[0].policy.filters.clone(),
+            protocol => panic!("unexpected protocol: {protocol:?}"),
+        };
+        assert_eq!(
+            filters(overrides.apply(8080, http_server())),
+            vec![Filter::Compression(compression)]
+        );
+        assert_eq!(filters(overrides.apply(8081, http_server())), vec![]);
+
+        // gRPC negotiates message compression itself.
+        assert_eq!(
+            overrides.apply(8080, grpc_server()).protocol,
+            grpc_server().protocol
+        );
+    }
+}
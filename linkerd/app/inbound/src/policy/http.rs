@@ -6,13 +6,16 @@ use crate::{
 };
 use futures::{future, TryFutureExt};
 use linkerd_app_core::{
+    http_tracing,
     metrics::RouteAuthzLabels,
+    proxy::http::{stream_timeouts, StreamTimeouts},
     svc::{self, ServiceExt},
     tls::{self, ConditionalServerTls},
     transport::{ClientAddr, OrigDstAddr, Remote, ServerAddr},
     Conditional, Error, Result,
 };
-use linkerd_proxy_server_policy::{grpc, http, route::RouteMatch};
+use linkerd_http_access_log::AccessLog;
+use linkerd_proxy_server_policy::{grpc, http, route::RouteMatch, Meta};
 use std::{sync::Arc, task};
 
 #[cfg(test)]
@@ -52,6 +55,8 @@ struct ConnectionMeta {
 pub struct Permitted<T> {
     permit: HttpRoutePermit,
     protocol: PermitVariant,
+    compression: Option<Arc<http::filter::Compression>>,
+    deadline: bool,
     target: T,
 }
 
@@ -173,29 +178,58 @@ where
         // Find an appropriate route for the request and ensure that it's
         // authorized.
         let target = self.target.clone();
+        if let Some(log) = req.extensions().get::<AccessLog>() {
+            let OrigDstAddr(dst) = self.connection.dst;
+            log.record_server(access_log_name(&self.policy.server_label().0));
+            log.record_endpoint(dst);
+        }
         let permit = match self.policy.routes() {
-            None => err!(self.mk_route_not_found()),
+            None => {
+                add_response_flag(&req, "route_not_found");
+                err!(self.mk_route_not_found())
+            }
             Some(Routes::Http(routes)) => {
                 let (permit, mtch, route) = try_fut!(self.authorize(&routes, &req));
+                http_tracing::sample_route(&mut req, route.meta.kind(), route.meta.name());
                 try_fut!(apply_http_filters(mtch, route, &mut req));
+                let compression = route.filters.iter().find_map(|f| match f {
+                    http::Filter::Compression(c) => Some(c.clone()),
+                    _ => None,
+                });
+                let deadline = route
+                    .filters
+                    .iter()
+                    .any(|f| matches!(f, http::Filter::Deadline(_)));
                 Permitted {
                     permit,
                     target,
                     protocol: PermitVariant::Http,
+                    compression,
+                    deadline,
                 }
             }
             Some(Routes::Grpc(routes)) => {
                 let (permit, _, route) = try_fut!(self.authorize(&routes, &req));
+                http_tracing::sample_route(&mut req, route.meta.kind(), route.meta.name());
                 try_fut!(apply_grpc_filters(route, &mut req));
+                let deadline = route
+                    .filters
+                    .iter()
+                    .any(|f| matches!(f, grpc::Filter::Deadline(_)));
                 Permitted {
                     permit,
                     target,
                     protocol: PermitVariant::Grpc,
+                    compression: None,
+                    deadline,
                 }
             }
         };
 
-        try_fut!(self.check_rate_limit());
+        if let Err(error) = self.check_rate_limit() {
+            add_response_flag(&req, "rate_limited");
+            err!(error)
+        }
 
         future::Either::Left(
             self.inner
@@ -215,8 +249,13 @@ impl<T, N> HttpPolicyService<T, N> {
         routes: &'m [super::route::Route<M, RoutePolicy<P>>],
         req: &::http::Request<B>,
     ) -> Result<(HttpRoutePermit, RouteMatch<M::Summary>, &'m RoutePolicy<P>)> {
-        let (r#match, route) =
-            super::route::find(routes, req).ok_or_else(|| self.mk_route_not_found())?;
+        let (r#match, route) = super::route::find(routes, req).ok_or_else(|| {
+            add_response_flag(req, "route_not_found");
+            self.mk_route_not_found()
+        })?;
+        if let Some(log) = req.extensions().get::<AccessLog>() {
+            log.record_route(access_log_name(&route.meta));
+        }
 
         let labels = linkerd_app_core::metrics::RouteLabels {
             route: route.meta.clone(),
@@ -272,6 +311,7 @@ impl<T, N> HttpPolicyService<T, N> {
                         );
                     }
                 }
+                add_response_flag(req, "unauthorized");
                 self.metrics.deny(
                     labels,
                     self.connection.dst,
@@ -345,6 +385,17 @@ impl<T, N> HttpPolicyService<T, N> {
     }
 }
 
+/// Describes a policy resource in access logs as `<kind>/<name>`.
+fn access_log_name(meta: &Meta) -> String {
+    format!("{}/{}", meta.kind().to_ascii_lowercase(), meta.name())
+}
+
+fn add_response_flag<B>(req: &::http::Request<B>, flag: &'static str) {
+    if let Some(log) = req.extensions().get::<AccessLog>() {
+        log.add_response_flag(flag);
+    }
+}
+
 fn apply_http_filters<B>(
     r#match: http::RouteMatch,
     route: &http::Policy,
@@ -377,6 +428,12 @@ fn apply_http_filters<B>(
                 rh.apply(req.headers_mut());
             }
 
+            // Compression transforms bodies, so it is applied by a
+            // `linkerd_http_compress` layer configured from the permit.
+            http::Filter::Compression(_) => {}
+
+            http::Filter::Deadline(deadline) => apply_deadline(deadline, req),
+
             http::Filter::InternalError(msg) => {
                 return Err(HttpInvalidPolicy(msg).into());
             }
@@ -399,6 +456,8 @@ fn apply_grpc_filters<B>(route: &grpc::Policy, req: &mut ::http::Request<B>) ->
                 rh.apply(req.headers_mut());
             }
 
+            grpc::Filter::Deadline(deadline) => apply_deadline(deadline, req),
+
             grpc::Filter::InternalError(msg) => {
                 return Err(HttpInvalidPolicy(msg).into());
             }
@@ -408,6 +467,25 @@ fn apply_grpc_filters<B>(route: &grpc::Policy, req: &mut ::http::Request<B>) ->
     Ok(())
 }
 
+/// Limits the request to the deadline propagated by the client's proxy.
+///
+/// The limit is enforced by the router's `EnforceTimeouts` layer.
+fn apply_deadline<B>(deadline: &http::filter::Deadline, req: &mut ::http::Request<B>) {
+    let propagated =
+        stream_timeouts::propagated_deadline(req.headers(), deadline.http_header.as_ref());
+    let limit = match (propagated, deadline.max) {
+        (Some(propagated), Some(max)) => Some(propagated.min(max)),
+        (propagated, max) => propagated.or(max),
+    };
+    if let Some(limit) = limit {
+        tracing::debug!(?limit, "Applying request deadline");
+        req.extensions_mut().insert(StreamTimeouts {
+            limit: Some(limit.into()),
+            ..Default::default()
+        });
+    }
+}
+
 // === impl Permitted ===
 
 impl<T> svc::Param<Remote<ServerAddr>> for Permitted<T>
@@ -440,6 +518,12 @@ impl<T> svc::Param<PermitVariant> for Permitted<T> {
     }
 }
 
+impl<T> svc::Param<Option<Arc<http::filter::Compression>>> for Permitted<T> {
+    fn param(&self) -> Option<Arc<http::filter::Compression>> {
+        self.compression.clone()
+    }
+}
+
 impl<T> svc::Param<RouteLabels> for Permitted<T> {
     fn param(&self) -> RouteLabels {
         self.route_labels()
@@ -457,6 +541,11 @@ impl<T> Permitted<T> {
         self.protocol
     }
 
+    /// Returns true if the permitting route applies a deadline filter.
+    pub fn has_deadline(&self) -> bool {
+        self.deadline
+    }
+
     /// Returns a reference to the underlying `T`-typed target.
     pub fn target_ref(&self) -> &T {
         &self.target
@@ -472,6 +561,8 @@ impl<T> Permitted<T> {
         let Self {
             permit,
             protocol: _,
+            compression: _,
+            deadline: _,
             target,
         } = self;
 
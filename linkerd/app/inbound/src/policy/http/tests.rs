@@ -12,6 +12,7 @@ macro_rules! conn {
             tls: tls::ConditionalServerTls::Some(tls::ServerTls::Established {
                 client_id: Some("foo.bar.bah".parse().unwrap()),
                 negotiated_protocol: None,
+                server_id: None,
             }),
         }
     }};
@@ -449,47 +450,50 @@ async fn grpc_route() {
         kind: "grpcproute".into(),
         name: "testrt".into(),
     });
-    let (mut svc, _tx) = new_svc!(Protocol::Grpc(Arc::new([Route {
-        hosts: vec![],
-        rules: vec![
-            Rule {
-                matches: vec![MatchRoute {
-                    rpc: MatchRpc {
-                        service: Some("foo.bar.bah".to_string()),
-                        method: Some("baz".to_string()),
+    let (mut svc, _tx) = new_svc!(Protocol::Grpc {
+        routes: Arc::new([Route {
+            hosts: vec![],
+            rules: vec![
+                Rule {
+                    matches: vec![MatchRoute {
+                        rpc: MatchRpc {
+                            service: Some("foo.bar.bah".to_string()),
+                            method: Some("baz".to_string()),
+                        },
+                        ..MatchRoute::default()
+                    }],
+                    policy: Policy {
+                        authorizations: Arc::new([Authorization {
+                            authentication: Authentication::Unauthenticated,
+                            networks: vec![std::net::IpAddr::from([192, 168, 3, 3]).into()],
+                            meta: Arc::new(Meta::Resource {
+                                group: "policy.linkerd.io".into(),
+                                kind: "AuthorizationPolicy".into(),
+                                name: "test".into(),
+                            }),
+                        }]),
+                        filters: vec![],
+                        meta: rmeta.clone(),
                     },
-                    ..MatchRoute::default()
-                }],
-                policy: Policy {
-                    authorizations: Arc::new([Authorization {
-                        authentication: Authentication::Unauthenticated,
-                        networks: vec![std::net::IpAddr::from([192, 168, 3, 3]).into()],
-                        meta: Arc::new(Meta::Resource {
-                            group: "policy.linkerd.io".into(),
-                            kind: "AuthorizationPolicy".into(),
-                            name: "test".into(),
-                        }),
-                    }]),
-                    filters: vec![],
-                    meta: rmeta.clone(),
                 },
-            },
-            Rule {
-                matches: vec![MatchRoute {
-                    rpc: MatchRpc {
-                        service: Some("foo.bar.bah".to_string()),
-                        method: Some("qux".to_string()),
+                Rule {
+                    matches: vec![MatchRoute {
+                        rpc: MatchRpc {
+                            service: Some("foo.bar.bah".to_string()),
+                            method: Some("qux".to_string()),
+                        },
+                        ..MatchRoute::default()
+                    }],
+                    policy: Policy {
+                        authorizations: Arc::new([]),
+                        filters: vec![],
+                        meta: rmeta.clone(),
                     },
-                    ..MatchRoute::default()
-                }],
-                policy: Policy {
-                    authorizations: Arc::new([]),
-                    filters: vec![],
-                    meta: rmeta.clone(),
-                },
-            }
-        ],
-    }])));
+                }
+            ],
+        }]),
+        grpc_web: false
+    });
 
     let rsp = svc
         .call(
@@ -547,35 +551,38 @@ async fn grpc_filter_header() {
         kind: "httproute".into(),
         name: "testrt".into(),
     });
-    let proto = Protocol::Grpc(Arc::new([Route {
-        hosts: vec![],
-        rules: vec![Rule {
-            matches: vec![MatchRoute {
-                rpc: MatchRpc {
-                    service: Some("foo.bar.bah".to_string()),
-                    method: Some("baz".to_string()),
+    let proto = Protocol::Grpc {
+        routes: Arc::new([Route {
+            hosts: vec![],
+            rules: vec![Rule {
+                matches: vec![MatchRoute {
+                    rpc: MatchRpc {
+                        service: Some("foo.bar.bah".to_string()),
+                        method: Some("baz".to_string()),
+                    },
+                    ..MatchRoute::default()
+                }],
+
+                policy: Policy {
+                    authorizations: Arc::new([Authorization {
+                        authentication: Authentication::Unauthenticated,
+                        networks: vec![std::net::IpAddr::from([192, 168, 3, 3]).into()],
+                        meta: Arc::new(Meta::Resource {
+                            group: "policy.linkerd.io".into(),
+                            kind: "AuthorizatoinPolicy".into(),
+                            name: "test".into(),
+                        }),
+                    }]),
+                    filters: vec![Filter::RequestHeaders(http::filter::ModifyHeader {
+                        add: vec![("testkey".parse().unwrap(), "testval".parse().unwrap())],
+                        ..http::filter::ModifyHeader::default()
+                    })],
+                    meta: rmeta.clone(),
                 },
-                ..MatchRoute::default()
             }],
-
-            policy: Policy {
-                authorizations: Arc::new([Authorization {
-                    authentication: Authentication::Unauthenticated,
-                    networks: vec![std::net::IpAddr::from([192, 168, 3, 3]).into()],
-                    meta: Arc::new(Meta::Resource {
-                        group: "policy.linkerd.io".into(),
-                        kind: "AuthorizatoinPolicy".into(),
-                        name: "test".into(),
-                    }),
-                }]),
-                filters: vec![Filter::RequestHeaders(http::filter::ModifyHeader {
-                    add: vec![("testkey".parse().unwrap(), "testval".parse().unwrap())],
-                    ..http::filter::ModifyHeader::default()
-                })],
-                meta: rmeta.clone(),
-            },
-        }],
-    }]));
+        }]),
+        grpc_web: false,
+    };
     let inner = |permit: HttpRoutePermit, req: ::http::Request<BoxBody>| -> Result<_> {
         assert_eq!(req.headers().len(), 1);
         assert_eq!(
@@ -620,38 +627,41 @@ async fn grpc_filter_inject_failure() {
         kind: "grpcroute".into(),
         name: "testrt".into(),
     });
-    let proto = Protocol::Grpc(Arc::new([Route {
-        hosts: vec![],
-        rules: vec![Rule {
-            matches: vec![MatchRoute {
-                rpc: MatchRpc {
-                    service: Some("foo.bar.bah".to_string()),
-                    method: Some("baz".to_string()),
+    let proto = Protocol::Grpc {
+        routes: Arc::new([Route {
+            hosts: vec![],
+            rules: vec![Rule {
+                matches: vec![MatchRoute {
+                    rpc: MatchRpc {
+                        service: Some("foo.bar.bah".to_string()),
+                        method: Some("baz".to_string()),
+                    },
+                    ..MatchRoute::default()
+                }],
+
+                policy: Policy {
+                    authorizations: Arc::new([Authorization {
+                        authentication: Authentication::Unauthenticated,
+                        networks: vec![std::net::IpAddr::from([192, 168, 3, 3]).into()],
+                        meta: Arc::new(Meta::Resource {
+                            group: "policy.linkerd.io".into(),
+                            kind: "AuthorizatoinPolicy".into(),
+                            name: "test".into(),
+                        }),
+                    }]),
+                    filters: vec![Filter::InjectFailure(filter::InjectFailure {
+                        distribution: filter::Distribution::from_ratio(1, 1).unwrap(),
+                        response: filter::FailureResponse {
+                            code: 4,
+                            message: "oopsie".into(),
+                        },
+                    })],
+                    meta: rmeta.clone(),
                 },
-                ..MatchRoute::default()
             }],
-
-            policy: Policy {
-                authorizations: Arc::new([Authorization {
-                    authentication: Authentication::Unauthenticated,
-                    networks: vec![std::net::IpAddr::from([192, 168, 3, 3]).into()],
-                    meta: Arc::new(Meta::Resource {
-                        group: "policy.linkerd.io".into(),
-                        kind: "AuthorizatoinPolicy".into(),
-                        name: "test".into(),
-                    }),
-                }]),
-                filters: vec![Filter::InjectFailure(filter::InjectFailure {
-                    distribution: filter::Distribution::from_ratio(1, 1).unwrap(),
-                    response: filter::FailureResponse {
-                        code: 4,
-                        message: "oopsie".into(),
-                    },
-                })],
-                meta: rmeta.clone(),
-            },
-        }],
-    }]));
+        }]),
+        grpc_web: false,
+    };
     let inner = |_: HttpRoutePermit,
                  _: ::http::Request<BoxBody>|
      -> Result<::http::Response<BoxBody>> { unreachable!() };
@@ -675,3 +685,94 @@ async fn grpc_filter_inject_failure() {
         }
     );
 }
+
+#[tokio::test(flavor = "current_thread")]
+async fn grpc_filter_deadline() {
+    use linkerd_proxy_server_policy::{
+        grpc::{
+            r#match::{MatchRoute, MatchRpc},
+            Filter, Policy, Route, Rule,
+        },
+        http,
+    };
+    use std::time::Duration;
+
+    let proto = Protocol::Grpc {
+        routes: Arc::new([Route {
+            hosts: vec![],
+            rules: vec![Rule {
+                matches: vec![MatchRoute {
+                    rpc: MatchRpc {
+                        service: Some("foo.bar.bah".to_string()),
+                        method: None,
+                    },
+                    ..MatchRoute::default()
+                }],
+                policy: Policy {
+                    authorizations: Arc::new([Authorization {
+                        authentication: Authentication::Unauthenticated,
+                        networks: vec![std::net::IpAddr::from([192, 168, 3, 3]).into()],
+                        meta: Arc::new(Meta::Resource {
+                            group: "policy.linkerd.io".into(),
+                            kind: "AuthorizationPolicy".into(),
+                            name: "test".into(),
+                        }),
+                    }]),
+                    filters: vec![Filter::Deadline(http::filter::Deadline {
+                        http_header: None,
+                        max: Some(Duration::from_secs(1)),
+                    })],
+                    meta: Arc::new(Meta::Resource {
+                        group: "gateway.networking.k8s.io".into(),
+                        kind: "grpcroute".into(),
+                        name: "testrt".into(),
+                    }),
+                },
+            }],
+        }]),
+        grpc_web: false,
+    };
+    let inner = |_: HttpRoutePermit, req: ::http::Request<BoxBody>| -> Result<_> {
+        let timeouts = req
+            .extensions()
+            .get::<StreamTimeouts>()
+            .expect("deadline must be set");
+        let mut rsp = ::http::Response::builder()
+            .body(BoxBody::default())
+            .unwrap();
+        rsp.extensions_mut()
+            .insert(timeouts.limit.expect("limit must be set").lifetime);
+        Ok(rsp)
+    };
+    let (mut svc, _tx) = new_svc!(proto, conn!(), inner);
+
+    let req = |grpc_timeout: Option<&'static str>| {
+        let mut req = ::http::Request::builder()
+            .uri("/foo.bar.bah/baz")
+            .method(::http::Method::POST)
+            .header("content-type", "application/grpc");
+        if let Some(t) = grpc_timeout {
+            req = req.header("grpc-timeout", t);
+        }
+        req.body(BoxBody::default()).unwrap()
+    };
+
+    // The propagated deadline is applied.
+    let rsp = svc.call(req(Some("100m"))).await.expect("serves");
+    assert_eq!(
+        rsp.extensions().get::<Duration>(),
+        Some(&Duration::from_millis(100))
+    );
+
+    // The deadline is limited by the filter.
+    let rsp = svc.call(req(Some("5S"))).await.expect("serves");
+    assert_eq!(
+        rsp.extensions().get::<Duration>(),
+        Some(&Duration::from_secs(1))
+    );
+    let rsp = svc.call(req(None)).await.expect("serves");
+    assert_eq!(
+        rsp.extensions().get::<Duration>(),
+        Some(&Duration::from_secs(1))
+    );
+}
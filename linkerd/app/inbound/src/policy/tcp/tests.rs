@@ -85,6 +85,7 @@ async fn authenticated_identity() {
     let tls = tls::ConditionalServerTls::Some(tls::ServerTls::Established {
         client_id: Some(client_id()),
         negotiated_protocol: None,
+        server_id: None,
     });
     let permitted = check_authorized(&policy, orig_dst_addr(), client_addr(), &tls)
         .expect("unauthenticated connection must be permitted");
@@ -118,6 +119,7 @@ async fn authenticated_identity() {
                 .unwrap(),
         )),
         negotiated_protocol: None,
+        server_id: None,
     });
     check_authorized(&policy, orig_dst_addr(), client_addr(), &tls)
         .expect_err("policy must require a client identity");
@@ -152,6 +154,7 @@ async fn authenticated_suffix() {
     let tls = tls::ConditionalServerTls::Some(tls::ServerTls::Established {
         client_id: Some(client_id()),
         negotiated_protocol: None,
+        server_id: None,
     });
     assert_eq!(
         check_authorized(&policy, orig_dst_addr(), client_addr(), &tls)
@@ -184,6 +187,7 @@ async fn authenticated_suffix() {
                 .unwrap(),
         ),
         negotiated_protocol: None,
+        server_id: None,
     });
     check_authorized(&policy, orig_dst_addr(), client_addr(), &tls)
         .expect_err("policy must require a client identity");
@@ -215,6 +219,7 @@ async fn tls_unauthenticated() {
     let tls = tls::ConditionalServerTls::Some(tls::ServerTls::Established {
         client_id: None,
         negotiated_protocol: None,
+        server_id: None,
     });
     assert_eq!(
         check_authorized(&policy, orig_dst_addr(), client_addr(), &tls)
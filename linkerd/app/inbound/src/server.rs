@@ -10,6 +10,7 @@ use linkerd_app_core::{
     Error,
 };
 use linkerd_tonic_stream::ReceiveLimits;
+use linkerd_tonic_watch::Snapshots;
 use std::{fmt::Debug, sync::Arc};
 use tracing::debug_span;
 
@@ -27,6 +28,7 @@ impl Inbound<()> {
         client: C,
         backoff: ExponentialBackoff,
         limits: ReceiveLimits,
+        snapshots: Snapshots<u16, policy::ServerPolicy>,
     ) -> impl policy::GetPolicy + Clone + Send + Sync + 'static
     where
         C: tonic::client::GrpcService<tonic::body::Body, Error = Error>,
@@ -38,7 +40,7 @@ impl Inbound<()> {
         self.config
             .policy
             .clone()
-            .build(workload, client, backoff, limits)
+            .build(workload, client, backoff, limits, snapshots)
     }
 
     pub fn mk<A, I, P>(
@@ -49,6 +49,7 @@ pub fn default_config() -> Config {
         .into(),
         ports: Default::default(),
         opaque_ports: Default::default(),
+        overrides: Default::default(),
     };
 
     Config {
@@ -61,6 +62,7 @@ pub fn default_config() -> Config {
                 user_timeout: UserTimeout(None),
                 backlog: Backlog::default(),
                 http2: h2::ServerParams::default(),
+                proxy_protocol: Default::default(),
             },
             connect: config::ConnectConfig {
                 keepalive: Keepalive(None),
@@ -75,11 +77,13 @@ pub fn default_config() -> Config {
                 http1: h1::PoolSettings {
                     max_idle: 1,
                     idle_timeout: Duration::from_secs(1),
+                    limits: Default::default(),
                 },
                 http2: h2::ClientParams::default(),
             },
             max_in_flight_requests: 10_000,
             detect_protocol_timeout: Duration::from_secs(10),
+            histogram_encoding: Default::default(),
         },
         allowed_ips: Default::default(),
         http_request_queue: config::QueueConfig {
@@ -89,6 +93,8 @@ pub fn default_config() -> Config {
         discovery_idle_timeout: Duration::from_secs(20),
         profile_skip_timeout: Duration::from_secs(1),
         unsafe_authority_labels: false,
+        server_first_ports: Default::default(),
+        server_first_detect_timeout: Duration::from_millis(100),
     }
 }
 
@@ -102,6 +108,7 @@ pub fn runtime() -> (ProxyRuntime, drain::Signal) {
         tap,
         span_sink: None,
         drain,
+        connections: Default::default(),
     };
     (runtime, drain_tx)
 }
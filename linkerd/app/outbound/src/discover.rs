@@ -278,10 +278,12 @@ fn policy_for_backend(
         http1: policy::http::Http1 {
             routes: routes.clone(),
             failure_accrual: None,
+            pool_limits: Default::default(),
         },
         http2: policy::http::Http2 {
             routes,
             failure_accrual: None,
+            client_params: Default::default(),
         },
         opaque,
     };
@@ -13,7 +13,7 @@ use linkerd_app_core::{
 use std::{fmt::Debug, hash::Hash};
 use tokio::sync::watch;
 
-mod breaker;
+pub(crate) mod breaker;
 pub mod concrete;
 mod endpoint;
 mod handle_proxy_error_headers;
@@ -34,6 +34,7 @@ pub struct HttpMetrics {
     balancer: concrete::BalancerMetrics,
     http_route: policy::HttpRouteMetrics,
     grpc_route: policy::GrpcRouteMetrics,
+    h1_pool: h1::PoolMetrics,
 }
 
 pub fn spawn_routes<T>(
@@ -130,19 +131,29 @@ where
 // === impl HttpMetrics ===
 
 impl HttpMetrics {
-    pub fn register(registry: &mut prom::Registry) -> Self {
+    pub fn register(registry: &mut prom::Registry, histograms: prom::HistogramEncoding) -> Self {
         let http = registry.sub_registry_with_prefix("http");
-        let http_route = policy::HttpRouteMetrics::register(http.sub_registry_with_prefix("route"));
+        let http_route =
+            policy::HttpRouteMetrics::register(http.sub_registry_with_prefix("route"), histograms);
         let balancer =
             concrete::BalancerMetrics::register(http.sub_registry_with_prefix("balancer"));
 
         let grpc = registry.sub_registry_with_prefix("grpc");
-        let grpc_route = policy::GrpcRouteMetrics::register(grpc.sub_registry_with_prefix("route"));
+        let grpc_route =
+            policy::GrpcRouteMetrics::register(grpc.sub_registry_with_prefix("route"), histograms);
+
+        let h1_pool = h1::PoolMetrics::register(registry.sub_registry_with_prefix("http1_pool"));
 
         Self {
             balancer,
             http_route,
             grpc_route,
+            h1_pool,
         }
     }
+
+    /// Returns the registry of HTTP balancers' endpoints.
+    pub fn balancers(&self) -> concrete::Balancers {
+        self.balancer.balancers()
+    }
 }
@@ -1,5 +1,10 @@
-use linkerd_app_core::{classify, proxy::http::classify::gate, svc};
+use linkerd_app_core::{
+    classify,
+    proxy::{api_resolve::Metadata, balance::registry, http::classify::gate},
+    svc,
+};
 use linkerd_proxy_client_policy::FailureAccrual;
+use std::net::SocketAddr;
 use tracing::{trace_span, Instrument};
 
 mod consecutive_failures;
@@ -35,12 +40,50 @@ pub(crate) struct Params {
     pub(crate) channel_capacity: usize,
 }
 
+/// Builds breakers for a balancer's endpoints, describing each endpoint and
+/// the state of its breaker in the balancer's registry.
+#[derive(Clone, Debug)]
+pub(crate) struct RegisterParams {
+    pub(crate) params: Params,
+    pub(crate) balancer: registry::Balancer,
+}
+
+// === impl TripReason ===
+
+impl TripReason {
+    /// Describes the reason, e.g. for the admin server.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ConsecutiveFailures => "consecutive_failures",
+            Self::LowSuccessRate => "low_success_rate",
+        }
+    }
+}
+
+// === impl Params ===
+
 impl<T> svc::ExtractParam<gate::Params<classify::Class>, T> for Params {
     fn extract_param(&self, _: &T) -> gate::Params<classify::Class> {
+        self.spawn(None)
+    }
+}
+
+impl Params {
+    /// Spawns a breaker task for an endpoint, recording its trips on the
+    /// registered endpoint, if there is one.
+    fn spawn(&self, endpoint: Option<&registry::Endpoint>) -> gate::Params<classify::Class> {
         // Create a channel so that we can receive response summaries and
         // control the gate.
         let (prms, gate, rsps) = gate::Params::channel(self.channel_capacity);
 
+        if let (Some(endpoint), Some(_)) = (endpoint, &self.accrual) {
+            endpoint.set_gate(prms.gate.clone());
+        }
+        // The registered endpoint holds a clone of the gate, and the breaker
+        // task completes only once every gate is dropped, so the task must not
+        // keep the endpoint alive.
+        let endpoint = endpoint.map(|ep| ep.downgrade()).unwrap_or_default();
+
         match &self.accrual {
             None => {
                 // No failure accrual for this target; construct a gate
@@ -64,7 +107,8 @@ impl<T> svc::ExtractParam<gate::Params<classify::Class>, T> for Params {
                 // 2. After an ejection timeout, open the gate so that 1 request can be processed.
                 // 3. If that request succeeds, open the gate. If it fails, increase the
                 //    ejection timeout and repeat.
-                let breaker = ConsecutiveFailures::new(cf.max_failures, cf.backoff, gate, rsps);
+                let breaker = ConsecutiveFailures::new(cf.max_failures, cf.backoff, gate, rsps)
+                    .with_endpoint(endpoint);
                 tokio::spawn(
                     breaker
                         .run()
@@ -95,7 +139,8 @@ impl<T> svc::ExtractParam<gate::Params<classify::Class>, T> for Params {
                     u.min_requests as usize,
                     gate,
                     rsps,
-                );
+                )
+                .with_endpoint(endpoint);
                 tokio::spawn(
                     breaker
                         .run()
@@ -107,3 +152,16 @@ impl<T> svc::ExtractParam<gate::Params<classify::Class>, T> for Params {
         }
     }
 }
+
+// === impl RegisterParams ===
+
+impl svc::ExtractParam<gate::Params<classify::Class>, (SocketAddr, Metadata)> for RegisterParams {
+    fn extract_param(
+        &self,
+        (addr, metadata): &(SocketAddr, Metadata),
+    ) -> gate::Params<classify::Class> {
+        let endpoint = self.balancer.endpoint(*addr);
+        endpoint.set_labels(metadata.labels());
+        self.params.spawn(Some(&endpoint))
+    }
+}
@@ -1,5 +1,10 @@
+use super::TripReason;
 use futures::stream::StreamExt;
-use linkerd_app_core::{classify, exp_backoff::ExponentialBackoff, proxy::http::classify::gate};
+use linkerd_app_core::{
+    classify,
+    exp_backoff::ExponentialBackoff,
+    proxy::{balance::registry, http::classify::gate},
+};
 use tokio::sync::mpsc;
 
 pub struct ConsecutiveFailures {
@@ -7,6 +12,7 @@ pub struct ConsecutiveFailures {
     backoff: ExponentialBackoff,
     gate: gate::Tx,
     rsps: mpsc::Receiver<classify::Class>,
+    endpoint: registry::WeakEndpoint,
 }
 
 impl ConsecutiveFailures {
@@ -21,9 +27,15 @@ impl ConsecutiveFailures {
             backoff,
             gate,
             rsps,
+            endpoint: Default::default(),
         }
     }
 
+    /// Records trips on the endpoint so that they may be inspected.
+    pub fn with_endpoint(self, endpoint: registry::WeakEndpoint) -> Self {
+        Self { endpoint, ..self }
+    }
+
     pub(super) async fn run(mut self) {
         loop {
             if self.open().await.is_err() {
@@ -31,6 +43,8 @@ impl ConsecutiveFailures {
             }
 
             tracing::info!("Consecutive failure-accrual breaker closed");
+            self.endpoint
+                .record_trip(TripReason::ConsecutiveFailures.as_str());
             if self.closed().await.is_err() {
                 return;
             }
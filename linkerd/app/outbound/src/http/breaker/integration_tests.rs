@@ -11,7 +11,9 @@
 
 use super::*;
 
-use linkerd_app_core::{exp_backoff::ExponentialBackoff, svc::ExtractParam};
+use linkerd_app_core::{
+    exp_backoff::ExponentialBackoff, proxy::balance::registry::GateState, svc::ExtractParam,
+};
 use linkerd_proxy_client_policy::{
     ConsecutiveFailures, FailureAccrual, SuccessRateThreshold, Unified,
 };
@@ -366,3 +368,36 @@ async fn failure_accrual_none_produces_no_breaker_task() {
         "the gate stays open with no breaker task to close it",
     );
 }
+
+// A trip is recorded on the balancer's registry so that the admin server can
+// describe why an endpoint is unavailable.
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn trips_are_recorded_on_the_balancer() {
+    let _trace = linkerd_tracing::test::trace_init();
+
+    let params = RegisterParams {
+        params: endpoint_params(Some(consecutive_accrual(2))),
+        balancer: Default::default(),
+    };
+    // The balancer registers its endpoints before building their stacks.
+    let endpoint: (SocketAddr, Metadata) = (([192, 0, 2, 1], 8080).into(), Metadata::default());
+    let _registered = params.balancer.endpoint(endpoint.0);
+    let gate_params: gate::Params<classify::Class> = params.extract_param(&endpoint);
+
+    time::advance(Duration::from_millis(1)).await;
+    let [ep] = &params.balancer.snapshot()[..] else {
+        panic!("expected a single endpoint");
+    };
+    assert_eq!(ep.gate, Some(GateState::Open));
+    assert_eq!(ep.last_trip, None);
+
+    fail(&gate_params, 2).await;
+    let [ep] = &params.balancer.snapshot()[..] else {
+        panic!("expected a single endpoint");
+    };
+    assert_eq!(ep.gate, Some(GateState::Shut));
+    assert_eq!(
+        ep.last_trip.map(|t| t.reason),
+        Some(TripReason::ConsecutiveFailures.as_str()),
+    );
+}
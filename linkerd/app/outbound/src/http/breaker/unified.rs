@@ -67,7 +67,7 @@
 
 use super::{success_rate::SuccessRateWindow, TripReason};
 use futures::stream::StreamExt;
-use linkerd_app_core::proxy::http::classify::gate;
+use linkerd_app_core::proxy::{balance::registry, http::classify::gate};
 use linkerd_app_core::{classify, exp_backoff::ExponentialBackoff};
 use tokio::sync::mpsc;
 use tokio::time::{Duration, Instant};
@@ -100,6 +100,8 @@ pub struct UnifiedBreaker {
     gate: gate::Tx,
     /// Channel receiving response classifications.
     rsps: mpsc::Receiver<classify::Class>,
+    /// Records trips so that they may be inspected.
+    endpoint: registry::WeakEndpoint,
 
     /// Lets tests see the reason charged to each trip without affecting behavior.
     #[cfg(test)]
@@ -165,11 +167,17 @@ impl UnifiedBreaker {
             min_requests,
             gate,
             rsps,
+            endpoint: Default::default(),
             #[cfg(test)]
             trip_observer: None,
         }
     }
 
+    /// Records trips on the endpoint so that they may be inspected.
+    pub fn with_endpoint(self, endpoint: registry::WeakEndpoint) -> Self {
+        Self { endpoint, ..self }
+    }
+
     /// Attach a channel that receives the reason for each trip.
     ///
     /// Tests use this to check which condition the breaker charged a trip to.
@@ -209,6 +217,7 @@ impl UnifiedBreaker {
             }
 
             tracing::info!(?trip_reason, "Unified circuit breaker tripped");
+            self.endpoint.record_trip(trip_reason.as_str());
 
             // Shut state plus probation: close the gate, wait out the backoff,
             // then probe.
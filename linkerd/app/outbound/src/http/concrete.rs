@@ -14,7 +14,7 @@ use linkerd_app_core::{
     proxy::{
         api_resolve::{ConcreteAddr, Metadata, ProtocolHint},
         core::Resolve,
-        tap,
+        dns_resolve, tap,
     },
     svc, tls,
     transport::{self, addrs::*},
@@ -26,7 +26,7 @@ use tracing::info_span;
 
 mod balance;
 
-pub use self::balance::BalancerMetrics;
+pub use self::balance::{BalancerMetrics, Balancers};
 
 /// Parameter configuring dispatcher behavior.
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
@@ -35,6 +35,8 @@ pub enum Dispatch {
     /// estimator selects peak-EWMA endpoint selection or, when a policy opts in,
     /// the response-aware penalty estimator.
     Balance(NameAddr, Load),
+    /// Load balance over endpoints discovered through DNS.
+    BalanceDns(dns_resolve::Target, Load),
     Forward(Remote<ServerAddr>, Arc<Metadata>),
     /// A backend dispatcher that explicitly fails all requests.
     Fail {
@@ -80,6 +82,8 @@ impl<N> Outbound<N> {
         T: svc::Param<BackendRef>,
         T: svc::Param<Dispatch>,
         T: svc::Param<Option<FailureAccrual>>,
+        T: svc::Param<http::h2::ClientParams>,
+        T: svc::Param<http::h1::PoolLimits>,
         T: Clone + Debug + Send + Sync + 'static,
         // Endpoint resolution.
         R: Resolve<ConcreteAddr, Error = Error, Endpoint = Metadata>,
@@ -125,6 +129,16 @@ impl<N> Outbound<N> {
                             Dispatch::Balance(addr, load) => {
                                 svc::Either::Left(svc::Either::Left(balance::Balance {
                                     addr,
+                                    dns: None,
+                                    load,
+                                    parent,
+                                    queue,
+                                }))
+                            }
+                            Dispatch::BalanceDns(dns, load) => {
+                                svc::Either::Left(svc::Either::Left(balance::Balance {
+                                    addr: dns.addr.clone(),
+                                    dns: Some(dns),
                                     load,
                                     parent,
                                     queue,
@@ -133,7 +147,16 @@ impl<N> Outbound<N> {
                             Dispatch::Forward(addr, metadata) => {
                                 svc::Either::Left(svc::Either::Right({
                                     let is_local = inbound_ips.contains(&addr.ip());
-                                    let http2 = http2.override_from(metadata.http2_client_params());
+                                    // Policy overrides the proxy's configuration, and
+                                    // the endpoint's own metadata overrides the policy.
+                                    let http2 = http2
+                                        .override_from(&parent.param())
+                                        .override_from(metadata.http2_client_params());
+                                    let http1 = http::h1::PoolSettings {
+                                        limits: http1.limits.override_from(&parent.param()),
+                                        ..http1
+                                    };
+                                    tracing::debug!(%addr, ?http1, ?http2, "HTTP client settings");
                                     Endpoint {
                                         is_local,
                                         addr,
@@ -187,6 +210,14 @@ impl<T> svc::Param<Option<crate::tcp::tagged_transport::PortOverride>> for Endpo
     }
 }
 
+impl<T> svc::Param<Option<crate::tcp::DualStackAddr>> for Endpoint<T> {
+    fn param(&self) -> Option<crate::tcp::DualStackAddr> {
+        self.metadata
+            .dual_stack_addr()
+            .map(crate::tcp::DualStackAddr)
+    }
+}
+
 impl<T> svc::Param<Option<http::AuthorityOverride>> for Endpoint<T> {
     fn param(&self) -> Option<http::AuthorityOverride> {
         if self.is_local {
@@ -2,6 +2,7 @@ use super::Endpoint;
 use crate::{
     http::{self, breaker},
     metrics::{BalancerMetricsParams, ConcreteLabels},
+    resolve::{BalanceResolve, Discover},
     stack_labels, BackendRef, ParentRef,
 };
 use linkerd_app_core::{
@@ -9,7 +10,9 @@ use linkerd_app_core::{
     config::{ConnectConfig, QueueConfig},
     proxy::{
         api_resolve::{ConcreteAddr, Metadata},
+        balance,
         core::Resolve,
+        dns_resolve,
     },
     svc,
     transport::addrs::*,
@@ -23,6 +26,9 @@ use tracing::info_span;
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Balance<T> {
     pub addr: NameAddr,
+    /// Set when endpoints are discovered through DNS rather than the
+    /// destination controller.
+    pub dns: Option<dns_resolve::Target>,
     pub load: Load,
     pub queue: QueueConfig,
     pub parent: T,
@@ -39,6 +45,9 @@ pub struct BalanceError {
 
 pub type BalancerMetrics = BalancerMetricsParams<ConcreteLabels>;
 
+/// Describes the endpoints of each balancer, keyed by its parent and backend.
+pub type Balancers = balance::registry::Balancers<ConcreteLabels>;
+
 // === impl Balance ===
 
 impl<T> svc::Param<http::balance::EwmaConfig> for Balance<T> {
@@ -106,6 +115,8 @@ where
     T: svc::Param<ParentRef>,
     T: svc::Param<BackendRef>,
     T: svc::Param<Option<FailureAccrual>>,
+    T: svc::Param<http::h2::ClientParams>,
+    T: svc::Param<http::h1::PoolLimits>,
     T: Clone + Debug + Send + Sync + 'static,
 {
     pub(super) fn layer<N, NSvc, R>(
@@ -134,9 +145,10 @@ where
         let inbound_ips = config.inbound_ips.clone();
         let stack_metrics = rt.metrics.proxy.stack.clone();
         let balance_metrics = rt.metrics.prom.http.balancer.clone();
+        let balancers = balance_metrics.balancers();
 
-        let resolve = svc::stack(resolve.into_service())
-            .push_map_target(|t: Self| ConcreteAddr(t.addr))
+        let resolve = svc::stack(BalanceResolve::new(resolve, rt.dns.clone()))
+            .push_map_target(|t: Self| Discover::new(t.addr, t.dns))
             .into_inner();
 
         svc::layer::mk(move |inner: N| {
@@ -147,7 +159,16 @@ where
                     move |((addr, metadata), target): ((SocketAddr, Metadata), Self)| {
                         tracing::trace!(%addr, ?metadata, ?target, "Resolved endpoint");
                         let is_local = inbound_ips.contains(&addr.ip());
-                        let http2 = http2.override_from(metadata.http2_client_params());
+                        // Policy overrides the proxy's configuration, and the
+                        // endpoint's own metadata overrides the policy.
+                        let http2 = http2
+                            .override_from(&target.parent.param())
+                            .override_from(metadata.http2_client_params());
+                        let http1 = http::h1::PoolSettings {
+                            limits: http1.limits.override_from(&target.parent.param()),
+                            ..http1
+                        };
+                        tracing::debug!(%addr, ?http1, ?http2, "HTTP client settings");
                         Endpoint {
                             addr: Remote(ServerAddr(addr)),
                             metadata: metadata.into(),
@@ -169,9 +190,16 @@ where
                 .push(
                     http::NewClassifyGateSet::<classify::Response, _, _, _>::layer_via({
                         let channel_capacity = http_queue.capacity;
-                        move |target: &Self| breaker::Params {
-                            accrual: target.parent.param(),
-                            channel_capacity,
+                        let balancers = balancers.clone();
+                        move |target: &Self| breaker::RegisterParams {
+                            params: breaker::Params {
+                                accrual: target.parent.param(),
+                                channel_capacity,
+                            },
+                            balancer: balancers.balancer(ConcreteLabels(
+                                svc::Param::param(target),
+                                svc::Param::param(target),
+                            )),
                         }
                     }),
                 )
@@ -4,7 +4,11 @@ use super::{
     handle_proxy_error_headers::{self, NewHandleProxyErrorHeaders},
     NewRequireIdentity,
 };
-use crate::{tcp::tagged_transport, zone::TcpZoneLabels, Outbound};
+use crate::{
+    tcp::{tagged_transport, DualStackAddr},
+    zone::TcpZoneLabels,
+    Outbound,
+};
 use linkerd_app_core::{
     classify, config, errors, http_tracing, metrics,
     proxy::{api_resolve::ProtocolHint, http, tap},
@@ -52,14 +56,16 @@ impl<C> Outbound<C> {
         C::Metadata: Send + Unpin,
         C::Future: Send + Unpin + 'static,
     {
-        self.map_stack(|_, _, inner| {
+        self.map_stack(|_, rt, inner| {
             // Initiates an HTTP client on the underlying transport. Prior-knowledge HTTP/2
             // is typically used (i.e. when communicating with other proxies); though
             // HTTP/1.x fallback is supported as needed.
             svc::stack(inner.into_inner().into_service())
                 .check_service::<Connect<T>>()
                 .push_map_target(|(version, inner)| Connect { version, inner })
-                .push(http::client::layer())
+                .push(http::client::layer_with_metrics(
+                    rt.metrics.prom.http.h1_pool.clone(),
+                ))
                 .push_on_service(svc::MapErr::layer_boxed())
                 .check_service::<T>()
                 .into_new_service()
@@ -105,6 +111,11 @@ impl<T> Outbound<svc::ArcNewHttp<T, http::BoxBody>> {
                 .push(NewHandleProxyErrorHeaders::layer())
                 .push_on_service(http::BoxRequest::layer())
                 .push_on_service(http::EnforceTimeouts::layer())
+                // Tell the endpoint how much time remains before the request's
+                // deadline so that it does not outlive the caller.
+                .push_on_service(http::PropagateDeadlines::layer(
+                    config.http_deadline_header.clone(),
+                ))
                 // Handle connection-level errors eagerly so that we can report 5XX failures in tap
                 // and metrics. HTTP error metrics are not incremented here so that errors are not
                 // double-counted--i.e., endpoint metrics track these responses and error metrics
@@ -170,6 +181,9 @@ impl errors::HttpRescue<Error> for ClientRescue {
         if errors::is_caused_by::<errors::ConnectTimeout>(&*error) {
             return Ok(errors::SyntheticHttpResponse::gateway_timeout(error));
         }
+        if errors::is_caused_by::<http::h1::PoolExhausted>(&*error) {
+            return Ok(errors::SyntheticHttpResponse::unavailable(error));
+        }
 
         Err(error)
     }
@@ -222,6 +236,13 @@ impl<T: svc::Param<Option<tagged_transport::PortOverride>>>
     }
 }
 
+impl<T: svc::Param<Option<DualStackAddr>>> svc::Param<Option<DualStackAddr>> for Connect<T> {
+    #[inline]
+    fn param(&self) -> Option<DualStackAddr> {
+        self.inner.param()
+    }
+}
+
 impl<T: svc::Param<Option<http::AuthorityOverride>>> svc::Param<Option<http::AuthorityOverride>>
     for Connect<T>
 {
@@ -301,6 +301,12 @@ impl svc::Param<Option<tcp::tagged_transport::PortOverride>> for Endpoint {
     }
 }
 
+impl svc::Param<Option<tcp::DualStackAddr>> for Endpoint {
+    fn param(&self) -> Option<tcp::DualStackAddr> {
+        None
+    }
+}
+
 impl svc::Param<Option<http::AuthorityOverride>> for Endpoint {
     fn param(&self) -> Option<http::AuthorityOverride> {
         None
@@ -346,6 +352,7 @@ impl svc::Param<http::client::Params> for Endpoint {
                     http::client::Params::Http1(http::h1::PoolSettings {
                         max_idle: 1,
                         idle_timeout: std::time::Duration::from_secs(1),
+                        limits: Default::default(),
                     })
                 }
                 ProtocolHint::Http2 => http::client::Params::OrigProtoUpgrade(
@@ -353,6 +360,7 @@ impl svc::Param<http::client::Params> for Endpoint {
                     http::h1::PoolSettings {
                         max_idle: 1,
                         idle_timeout: std::time::Duration::from_secs(1),
+                        limits: Default::default(),
                     },
                 ),
             },
@@ -43,6 +43,8 @@ pub struct Concrete<T> {
     parent_ref: ParentRef,
     backend_ref: BackendRef,
     failure_accrual: Option<policy::FailureAccrual>,
+    client_params: Arc<http::h2::ClientParams>,
+    pool_limits: http::h1::PoolLimits,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -155,6 +157,8 @@ where
                                     authority: None,
                                     parent,
                                     failure_accrual: None,
+                                    client_params: Default::default(),
+                                    pool_limits: Default::default(),
                                 })
                             }
                             Self::Profile(profile) => {
@@ -257,6 +261,18 @@ impl<T> svc::Param<Option<policy::FailureAccrual>> for Concrete<T> {
     }
 }
 
+impl<T> svc::Param<http::h2::ClientParams> for Concrete<T> {
+    fn param(&self) -> http::h2::ClientParams {
+        (*self.client_params).clone()
+    }
+}
+
+impl<T> svc::Param<http::h1::PoolLimits> for Concrete<T> {
+    fn param(&self) -> http::h1::PoolLimits {
+        self.pool_limits
+    }
+}
+
 // === impl CanonicalDstHeader ===
 
 impl From<CanonicalDstHeader> for http::HeaderPair {
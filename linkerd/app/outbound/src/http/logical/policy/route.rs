@@ -1,7 +1,8 @@
 use super::super::Concrete;
 use crate::{ParentRef, RouteRef};
-use linkerd_app_core::{classify, proxy::http, svc, Addr, Error, Result};
+use linkerd_app_core::{classify, http_tracing, proxy::http, svc, Addr, Error, Result};
 use linkerd_distribute as distribute;
+use linkerd_http_compress as compress;
 use linkerd_http_prom::stream_label::LabelSet;
 use linkerd_http_route as http_route;
 use linkerd_proxy_client_policy as policy;
@@ -88,6 +89,8 @@ where
     Self: filters::Apply,
     Self: svc::Param<classify::Request>,
     Self: svc::Param<extensions::Params>,
+    Self: svc::Param<Option<Arc<http_route::http::filter::Compression>>>,
+    Self: svc::Param<http::upgrade::SessionTimeouts>,
     Self: metrics::MkStreamLabel,
     <Self as metrics::MkStreamLabel>::DurationLabels: LabelSet,
     <Self as metrics::MkStreamLabel>::StatusLabels: LabelSet,
@@ -129,7 +132,13 @@ where
                 // leaking tasks onto the runtime.
                 .push_on_service(svc::LoadShed::layer())
                 .push(filters::NewApplyFilters::<Self, _, _>::layer())
+                // Compress responses and decompress requests as configured by
+                // the route's filters.
+                .push(compress::NewCompress::layer(metrics.compression.clone()))
                 .push(retry::NewHttpRetry::<Self, _>::layer(metrics.retry.clone()))
+                // Limit and instrument connections upgraded by the route's
+                // requests.
+                .push(http::upgrade::NewSession::layer(metrics.upgrade.clone()))
                 .check_new::<Self>()
                 .check_new_service::<Self, http::Request<http::BoxBody>>()
                 // Set request extensions based on the route configuration
@@ -170,6 +179,8 @@ impl<T: Clone, M, F, P> svc::Param<BackendDistribution<T, F>> for MatchedRoute<T
 impl<T> filters::Apply for Http<T> {
     #[inline]
     fn apply_request<B>(&self, req: &mut ::http::Request<B>) -> Result<()> {
+        let route = &self.params.route_ref;
+        http_tracing::sample_route(req, route.kind(), route.name());
         filters::apply_http_request(&self.r#match, &self.params.filters, req)
     }
 
@@ -222,6 +233,23 @@ impl<T> svc::Param<extensions::Params> for Http<T> {
     }
 }
 
+impl<T> svc::Param<Option<Arc<http_route::http::filter::Compression>>> for Http<T> {
+    fn param(&self) -> Option<Arc<http_route::http::filter::Compression>> {
+        self.params.filters.iter().find_map(|f| match f {
+            policy::http::Filter::Compression(c) => Some(c.clone()),
+            _ => None,
+        })
+    }
+}
+
+impl<T> svc::Param<http::upgrade::SessionTimeouts> for Http<T> {
+    fn param(&self) -> http::upgrade::SessionTimeouts {
+        let policy::http::UpgradeTimeouts { idle, lifetime } =
+            self.params.params.upgrade_timeouts.clone();
+        http::upgrade::SessionTimeouts { idle, lifetime }
+    }
+}
+
 impl<T> svc::Param<classify::Request> for Http<T> {
     fn param(&self) -> classify::Request {
         classify::Request::ClientPolicy(classify::ClientPolicy::Http(
@@ -235,6 +263,8 @@ impl<T> svc::Param<classify::Request> for Http<T> {
 impl<T> filters::Apply for Grpc<T> {
     #[inline]
     fn apply_request<B>(&self, req: &mut ::http::Request<B>) -> Result<()> {
+        let route = &self.params.route_ref;
+        http_tracing::sample_route(req, route.kind(), route.name());
         filters::apply_grpc_request(&self.r#match, &self.params.filters, req)
     }
 
@@ -287,6 +317,20 @@ impl<T> svc::Param<extensions::Params> for Grpc<T> {
     }
 }
 
+impl<T> svc::Param<Option<Arc<http_route::http::filter::Compression>>> for Grpc<T> {
+    fn param(&self) -> Option<Arc<http_route::http::filter::Compression>> {
+        // gRPC negotiates message compression itself.
+        None
+    }
+}
+
+impl<T> svc::Param<http::upgrade::SessionTimeouts> for Grpc<T> {
+    fn param(&self) -> http::upgrade::SessionTimeouts {
+        // gRPC requests are not upgraded.
+        Default::default()
+    }
+}
+
 impl<T> svc::Param<classify::Request> for Grpc<T> {
     fn param(&self) -> classify::Request {
         classify::Request::ClientPolicy(
@@ -1,6 +1,7 @@
 use super::{super::Concrete, filters};
 use crate::{BackendRef, ParentRef, RouteRef};
 use linkerd_app_core::{proxy::http, svc, Error, Result};
+use linkerd_http_access_log::AccessLog;
 use linkerd_http_prom::stream_label::{LabelSet, MkStreamLabel};
 use linkerd_http_route as http_route;
 use linkerd_proxy_client_policy as policy;
@@ -117,6 +118,20 @@ where
     }
 }
 
+impl<T, M, F> MatchedBackend<T, M, F> {
+    /// Records the backend in the request's access log, if it has one.
+    fn record_access_log<B>(&self, req: &::http::Request<B>) {
+        if let Some(log) = req.extensions().get::<AccessLog>() {
+            let meta = &self.params.concrete.backend_ref;
+            log.record_backend(format_args!(
+                "{}/{}",
+                meta.kind().to_ascii_lowercase(),
+                meta.name()
+            ));
+        }
+    }
+}
+
 impl<T, M, F> svc::Param<ParentRef> for MatchedBackend<T, M, F> {
     fn param(&self) -> ParentRef {
         self.params.concrete.parent_ref.clone()
@@ -140,6 +155,7 @@ impl<T, M, F> svc::Param<BackendRef> for MatchedBackend<T, M, F> {
 impl<T> filters::Apply for Http<T> {
     #[inline]
     fn apply_request<B>(&self, req: &mut ::http::Request<B>) -> Result<()> {
+        self.record_access_log(req);
         filters::apply_http_request(&self.r#match, &self.params.filters, req)
     }
 
@@ -169,6 +185,7 @@ impl<T> metrics::MkStreamLabel for Http<T> {
 impl<T> filters::Apply for Grpc<T> {
     #[inline]
     fn apply_request<B>(&self, req: &mut ::http::Request<B>) -> Result<()> {
+        self.record_access_log(req);
         filters::apply_grpc_request(&self.r#match, &self.params.filters, req)
     }
 
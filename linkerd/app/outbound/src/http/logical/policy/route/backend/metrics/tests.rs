@@ -450,6 +450,8 @@ fn mock_http_route_backend_metrics(
                     ),
                     authority: None,
                     failure_accrual: None,
+                    client_params: Default::default(),
+                    pool_limits: Default::default(),
                     parent: (),
                     parent_ref: parent_ref.clone(),
                     backend_ref: backend_ref.clone(),
@@ -506,6 +508,8 @@ fn mock_grpc_route_backend_metrics(
                     ),
                     authority: None,
                     failure_accrual: None,
+                    client_params: Default::default(),
+                    pool_limits: Default::default(),
                     parent: (),
                     parent_ref: parent_ref.clone(),
                     backend_ref: backend_ref.clone(),
@@ -115,6 +115,7 @@ pub fn apply_http_request<B>(
                 return Err(errors::HttpInvalidPolicy(msg).into());
             }
             http::Filter::ResponseHeaders(_) => {} // ResponseHeaders filter does not apply to requests.
+            http::Filter::Compression(_) => {} // Compression is applied by the route's body middleware.
         }
     }
 
@@ -132,6 +133,7 @@ pub fn apply_http_response<B>(
             http::Filter::Redirect(_) => {}      // Redirect filter does not apply to responses.
             http::Filter::RequestHeaders(_) => {} // RequestHeaders filter does not apply to responses.
             http::Filter::InternalError(_) => {} // InternalError filter does not apply to responses.
+            http::Filter::Compression(_) => {} // Compression is applied by the route's body middleware.
             http::Filter::ResponseHeaders(rh) => rh.apply(rsp.headers_mut()),
         }
     }
@@ -35,6 +35,8 @@ where
     pub(super) statuses: status::StatusMetrics<R::StatusLabels>,
     pub(super) backend: backend::RouteBackendMetrics<B>,
     pub(super) body_data: RequestBodyFamilies<labels::Route>,
+    pub(super) compression: linkerd_http_compress::CompressMetrics,
+    pub(super) upgrade: http::upgrade::SessionFamilies<labels::Route>,
 }
 
 pub type HttpRouteMetrics = RouteMetrics<LabelHttpRouteRsp, LabelHttpRouteBackendRsp>;
@@ -176,6 +178,8 @@ where
             statuses: Default::default(),
             retry: Default::default(),
             body_data: Default::default(),
+            compression: Default::default(),
+            upgrade: Default::default(),
         }
     }
 }
@@ -196,6 +200,8 @@ where
             statuses: self.statuses.clone(),
             retry: self.retry.clone(),
             body_data: self.body_data.clone(),
+            compression: self.compression.clone(),
+            upgrade: self.upgrade.clone(),
         }
     }
 }
@@ -209,12 +215,13 @@ where
     B::DurationLabels: LabelSet,
     B::StatusLabels: LabelSet,
 {
-    pub fn register(reg: &mut prom::Registry) -> Self {
-        let requests = RequestMetrics::<R>::register(reg, Self::REQUEST_BUCKETS.iter().copied());
+    pub fn register(reg: &mut prom::Registry, histograms: prom::HistogramEncoding) -> Self {
+        let requests =
+            RequestMetrics::<R>::register(reg, histograms.buckets(Self::REQUEST_BUCKETS));
 
         let backend = backend::RouteBackendMetrics::register(
             reg.sub_registry_with_prefix("backend"),
-            Self::RESPONSE_BUCKETS.iter().copied(),
+            histograms.buckets(Self::RESPONSE_BUCKETS),
         );
 
         let statuses = status::StatusMetrics::register(
@@ -223,6 +230,11 @@ where
         );
         let retry = retry::RouteRetryMetrics::register(reg.sub_registry_with_prefix("retry"));
         let body_data = RequestBodyFamilies::register(reg);
+        let compression = linkerd_http_compress::CompressMetrics::register(
+            reg.sub_registry_with_prefix("compression"),
+        );
+        let upgrade =
+            http::upgrade::SessionFamilies::register(reg.sub_registry_with_prefix("upgrade"));
 
         Self {
             requests,
@@ -230,6 +242,8 @@ where
             backend,
             retry,
             body_data,
+            compression,
+            upgrade,
         }
     }
 
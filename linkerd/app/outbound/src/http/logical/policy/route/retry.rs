@@ -8,6 +8,7 @@ use linkerd_app_core::{
     svc::{self, http::h2},
     Error, Result,
 };
+use linkerd_http_access_log::AccessLog;
 use linkerd_http_retry::{self as retry, peek_trailers::PeekTrailersBody};
 use linkerd_proxy_client_policy as policy;
 use tokio::time;
@@ -107,6 +108,13 @@ impl retry::Policy for RetryPolicy {
         if let Some(classify) = src.get::<classify::Response>().cloned() {
             dst.insert(classify);
         }
+
+        // The server's access log, if any, records the number of retries and
+        // the backend of each attempt.
+        if let Some(log) = src.get::<AccessLog>().cloned() {
+            log.record_retries(usize::from(u16::from(attempt)) - 1);
+            dst.insert(log);
+        }
     }
 }
 
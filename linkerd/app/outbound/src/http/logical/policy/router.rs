@@ -19,6 +19,8 @@ pub struct Params<M, F, E> {
     pub routes: Arc<[http_route::Route<M, policy::RoutePolicy<F, E>>]>,
     pub backends: Arc<[policy::Backend]>,
     pub failure_accrual: Option<policy::FailureAccrual>,
+    pub client_params: Arc<http::h2::ClientParams>,
+    pub pool_limits: http::h1::PoolLimits,
 }
 
 pub type HttpParams =
@@ -81,6 +83,8 @@ where
     route::MatchedRoute<T, M::Summary, F, P>: route::filters::Apply
         + svc::Param<classify::Request>
         + svc::Param<route::extensions::Params>
+        + svc::Param<Option<Arc<http_route::http::filter::Compression>>>
+        + svc::Param<http::upgrade::SessionTimeouts>
         + route::metrics::MkStreamLabel
         + svc::ExtractParam<route::metrics::labels::Route, http::Request<http::BoxBody>>,
     <route::MatchedRoute<T, M::Summary, F, P> as route::metrics::MkStreamLabel>::DurationLabels:
@@ -143,6 +147,8 @@ where
             routes,
             backends,
             failure_accrual,
+            client_params,
+            pool_limits,
         } = rts;
 
         let mk_concrete = {
@@ -154,6 +160,9 @@ where
                 // discovery for now.
                 let authority = match target {
                     concrete::Dispatch::Balance(ref addr, ..) => Some(addr.as_http_authority()),
+                    concrete::Dispatch::BalanceDns(ref dns, ..) => {
+                        Some(dns.addr.as_http_authority())
+                    }
                     _ => None,
                 };
                 Concrete {
@@ -163,6 +172,8 @@ where
                     backend_ref,
                     parent_ref: parent_ref.clone(),
                     failure_accrual,
+                    client_params: client_params.clone(),
+                    pool_limits,
                 }
             }
         };
@@ -179,6 +190,13 @@ where
                     load,
                 ),
             ),
+            policy::BackendDispatcher::BalanceP2c(
+                load,
+                policy::EndpointDiscovery::Dns(ref dns),
+            ) => mk_concrete(
+                BackendRef(bke.meta.clone()),
+                concrete::Dispatch::BalanceDns(crate::resolve::dns_target(dns), load),
+            ),
             policy::BackendDispatcher::Forward(addr, ref md) => mk_concrete(
                 EndpointRef::new(md, addr.port().try_into().expect("port must not be 0")).into(),
                 concrete::Dispatch::Forward(Remote(ServerAddr(addr)), md.clone()),
@@ -121,6 +121,8 @@ async fn header_based_route() {
             .chain(Some(special_backend.clone()))
             .collect(),
         failure_accrual: None,
+        client_params: Default::default(),
+        pool_limits: Default::default(),
     });
 
     let metrics = HttpRouteMetrics::default();
@@ -232,6 +234,8 @@ async fn http_filter_request_headers() {
             }]),
             backends: std::iter::once(backend).collect(),
             failure_accrual: None,
+            client_params: Default::default(),
+            pool_limits: Default::default(),
         }
     });
 
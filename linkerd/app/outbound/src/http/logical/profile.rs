@@ -128,6 +128,8 @@ where
                 authority: Some(addr.as_http_authority()),
                 parent: parent.clone(),
                 failure_accrual: None,
+                client_params: Default::default(),
+                pool_limits: Default::default(),
             };
             let backends = std::iter::once(concrete.clone()).collect();
             let distribution = Distribution::first_available(std::iter::once(concrete));
@@ -144,6 +146,8 @@ where
                     authority: Some(t.addr.as_http_authority()),
                     parent: parent.clone(),
                     failure_accrual: None,
+                    client_params: Default::default(),
+                    pool_limits: Default::default(),
                 })
                 .collect();
             let distribution = Distribution::random_available(targets.iter().cloned().map(
@@ -157,6 +161,8 @@ where
                         target: concrete::Dispatch::Balance(addr, DEFAULT_LOAD),
                         parent: parent.clone(),
                         failure_accrual: None,
+                        client_params: Default::default(),
+                        pool_limits: Default::default(),
                     };
                     (concrete, weight)
                 },
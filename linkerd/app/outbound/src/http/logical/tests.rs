@@ -13,6 +13,7 @@ use tokio::sync::watch;
 use tracing::Instrument;
 
 mod basic;
+mod client_params;
 mod failure_accrual;
 mod headers;
 mod retries;
@@ -253,6 +254,8 @@ fn mock_http(params: client_policy::http::RouteParams) -> (svc::BoxCloneHttp, Ha
         backends: Arc::new([backend]),
         routes: Arc::new([route]),
         failure_accrual: None,
+        client_params: Default::default(),
+        pool_limits: Default::default(),
     }))
 }
 
@@ -266,6 +269,8 @@ fn mock_grpc(params: client_policy::grpc::RouteParams) -> (svc::BoxCloneHttp, Ha
         backends: Arc::new([backend]),
         routes: Arc::new([route]),
         failure_accrual: None,
+        client_params: Default::default(),
+        pool_limits: Default::default(),
     }))
 }
 
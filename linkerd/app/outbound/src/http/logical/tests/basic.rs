@@ -34,6 +34,8 @@ async fn routes() {
             backends: Arc::new([backend.clone()]),
             routes: Arc::new([default_route(backend)]),
             failure_accrual: None,
+            client_params: Default::default(),
+            pool_limits: Default::default(),
         })));
     let target = Target {
         num: 1,
@@ -0,0 +1,182 @@
+use super::*;
+use linkerd_app_core::{
+    proxy::{
+        api_resolve::Metadata,
+        http::{h1, h2},
+    },
+    trace,
+};
+
+/// Records the client settings of each endpoint the stack builds.
+#[derive(Clone, Default)]
+struct RecordParams {
+    connect: HttpConnect,
+    params: Arc<Mutex<Vec<http::client::Params>>>,
+}
+
+impl<T> svc::NewService<T> for RecordParams
+where
+    T: svc::Param<Remote<ServerAddr>> + svc::Param<http::client::Params>,
+{
+    type Service = svc::BoxHttp;
+    fn new_service(&self, target: T) -> Self::Service {
+        self.params.lock().push(target.param());
+        self.connect.new_service(target)
+    }
+}
+
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn balanced_endpoints() {
+    let _trace = trace::test::trace_init();
+
+    let addr = SocketAddr::new([192, 0, 2, 41].into(), 666);
+    let dest: NameAddr = "logical.test.svc.cluster.local:666".parse().unwrap();
+    let backend = default_backend(&dest);
+    let resolve = support::resolver().endpoint_exists(dest.clone(), addr, Default::default());
+    check_client_params(addr, dest, backend, resolve).await;
+}
+
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn forwarded_endpoints() {
+    let _trace = trace::test::trace_init();
+
+    let addr = SocketAddr::new([192, 0, 2, 41].into(), 666);
+    let dest: NameAddr = "logical.test.svc.cluster.local:666".parse().unwrap();
+    let backend = client_policy::Backend {
+        dispatcher: client_policy::BackendDispatcher::Forward(addr, Arc::new(Metadata::default())),
+        ..default_backend(&dest)
+    };
+    let resolve = support::resolver::<Metadata>();
+    check_client_params(addr, dest, backend, resolve).await;
+}
+
+/// Asserts that the parent's HTTP/2 client settings override the proxy's
+/// configuration on the endpoint that serves a request.
+async fn check_client_params(
+    addr: SocketAddr,
+    dest: NameAddr,
+    backend: client_policy::Backend,
+    resolve: support::resolver::Dst<Metadata>,
+) {
+    let overrides = h2::ClientParams {
+        flow_control: Some(h2::FlowControl::Fixed {
+            initial_stream_window_size: 1024 * 1024,
+            initial_connection_window_size: 4 * 1024 * 1024,
+        }),
+        keep_alive: Some(h2::ClientKeepAlive {
+            interval: Duration::from_secs(7),
+            timeout: Duration::from_secs(3),
+            while_idle: true,
+        }),
+        ..Default::default()
+    };
+    let config = default_config();
+    let expected = config.proxy.connect.http2.override_from(&overrides);
+
+    let (svc, mut handle) = tower_test::mock::pair();
+    let connect = RecordParams {
+        connect: HttpConnect::default().service(addr, svc),
+        ..Default::default()
+    };
+    let params = connect.params.clone();
+    let (rt, _shutdown) = runtime();
+    let stack = Outbound::new(config, rt, &mut Default::default())
+        .with_stack(svc::ArcNewService::new(connect))
+        .push_http_cached(resolve)
+        .into_inner();
+
+    let (_route_tx, routes) =
+        watch::channel(Routes::Policy(policy::Params::Http(policy::HttpParams {
+            addr: dest.into(),
+            meta: ParentRef(client_policy::Meta::new_default("parent")),
+            backends: Arc::new([backend.clone()]),
+            routes: Arc::new([default_route(backend)]),
+            failure_accrual: None,
+            client_params: Arc::new(overrides),
+            pool_limits: Default::default(),
+        })));
+    let svc = stack.new_service(Target {
+        num: 1,
+        version: http::Variant::H2,
+        routes,
+    });
+
+    handle.allow(1);
+    let rsp = send_req(svc, http_get());
+    serve(&mut handle, mk_rsp(StatusCode::OK, "good")).await;
+    assert_eq!(
+        rsp.await.expect("request must succeed").status(),
+        StatusCode::OK
+    );
+
+    assert_eq!(*params.lock(), vec![http::client::Params::H2(expected)]);
+}
+
+/// Asserts that the proxy's configured HTTP/1 pool limits are applied to
+/// endpoints, with the parent's limits taking precedence.
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn configured_pool_limits() {
+    let _trace = trace::test::trace_init();
+
+    let addr = SocketAddr::new([192, 0, 2, 41].into(), 666);
+    let dest: NameAddr = "logical.test.svc.cluster.local:666".parse().unwrap();
+    let backend = default_backend(&dest);
+    let resolve = support::resolver().endpoint_exists(dest.clone(), addr, Default::default());
+
+    let mut config = default_config();
+    config.proxy.connect.http1.limits = h1::PoolLimits {
+        max_connections: Some(2),
+        max_requests_per_connection: Some(100),
+        ..Default::default()
+    };
+    let overrides = h1::PoolLimits {
+        max_requests_per_connection: Some(10),
+        ..Default::default()
+    };
+    let expected = h1::PoolSettings {
+        limits: h1::PoolLimits {
+            max_connections: Some(2),
+            max_requests_per_connection: Some(10),
+            ..Default::default()
+        },
+        ..config.proxy.connect.http1
+    };
+
+    let (svc, mut handle) = tower_test::mock::pair();
+    let connect = RecordParams {
+        connect: HttpConnect::default().service(addr, svc),
+        ..Default::default()
+    };
+    let params = connect.params.clone();
+    let (rt, _shutdown) = runtime();
+    let stack = Outbound::new(config, rt, &mut Default::default())
+        .with_stack(svc::ArcNewService::new(connect))
+        .push_http_cached(resolve)
+        .into_inner();
+
+    let (_route_tx, routes) =
+        watch::channel(Routes::Policy(policy::Params::Http(policy::HttpParams {
+            addr: dest.into(),
+            meta: ParentRef(client_policy::Meta::new_default("parent")),
+            backends: Arc::new([backend.clone()]),
+            routes: Arc::new([default_route(backend)]),
+            failure_accrual: None,
+            client_params: Default::default(),
+            pool_limits: overrides,
+        })));
+    let svc = stack.new_service(Target {
+        num: 1,
+        version: http::Variant::Http1,
+        routes,
+    });
+
+    handle.allow(1);
+    let rsp = send_req(svc, http_get());
+    serve(&mut handle, mk_rsp(StatusCode::OK, "good")).await;
+    assert_eq!(
+        rsp.await.expect("request must succeed").status(),
+        StatusCode::OK
+    );
+
+    assert_eq!(*params.lock(), vec![http::client::Params::Http1(expected)]);
+}
@@ -56,6 +56,8 @@ async fn consecutive_failures_accrue() {
                     backoff,
                 },
             )),
+            client_params: Default::default(),
+            pool_limits: Default::default(),
         })));
     let target = Target {
         num: 1,
@@ -220,6 +222,8 @@ async fn balancer_doesnt_select_tripped_breakers() {
                     backoff,
                 },
             )),
+            client_params: Default::default(),
+            pool_limits: Default::default(),
         })));
     let target = Target {
         num: 1,
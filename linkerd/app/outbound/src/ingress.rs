@@ -7,9 +7,10 @@ use linkerd_app_core::{
         http::balance,
     },
     svc::{self, ServiceExt},
-    transport::addrs::*,
+    transport::{addrs::*, connections},
     Addr, Error, Infallible, NameAddr, Result,
 };
+use linkerd_http_h3 as h3;
 use once_cell::sync::Lazy;
 use std::{fmt::Debug, hash::Hash, sync::Arc};
 use thiserror::Error;
@@ -101,8 +102,68 @@ impl Outbound<()> {
                 .into_inner()
         };
 
-        let http = self
-            .to_tcp_connect()
+        self.mk_ingress_http(discover, resolve)
+            .push_ingress(opaque)
+            .push_tcp_instrument(|t: &T| tracing::info_span!("ingress", addr = %t.param()))
+            .into_inner()
+    }
+
+    /// Builds an ingress-mode stack for requests received by an HTTP/3
+    /// listener bound on `local_addr`.
+    ///
+    /// Requests are routed exactly as HTTP/2 requests received on the ingress
+    /// listener are. QUIC connections have no original destination address,
+    /// so requests without an `l5d-dst-override` header are routed to the
+    /// listener's own address.
+    #[cfg(feature = "http3")]
+    pub fn mk_ingress_http3<R>(
+        &self,
+        profiles: impl profiles::GetProfile<Error = Error>,
+        policies: impl policy::GetPolicy,
+        resolve: R,
+        local_addr: std::net::SocketAddr,
+    ) -> svc::ArcNewCloneHttp<std::net::SocketAddr, h3::server::RecvBody>
+    where
+        // Endpoint resolver.
+        R: Resolve<ConcreteAddr, Endpoint = Metadata, Error = Error>,
+        R::Resolution: Unpin,
+    {
+        let profiles = profiles::WithAllowlist::new(profiles, self.config.allow_discovery.clone());
+        let discover = self.ingress_resolver(profiles, policies);
+        self.mk_ingress_http(discover, resolve)
+            .push_ingress_router::<OrigDstAddr, _>()
+            .map_stack(|_, _, stk| {
+                stk.push_on_service(http::BoxRequest::layer())
+                    .push_map_target(move |_: std::net::SocketAddr| Http {
+                        version: http::Variant::H2,
+                        parent: OrigDstAddr(local_addr),
+                    })
+                    .arc_new_clone_http()
+            })
+            .into_inner()
+    }
+
+    /// Builds the stack that serves each request target discovered from an
+    /// ingress request.
+    fn mk_ingress_http<D, R>(
+        &self,
+        discover: D,
+        resolve: R,
+    ) -> Outbound<svc::ArcNewHttp<Http<RequestTarget>>>
+    where
+        // Discovery client.
+        D: svc::Service<
+            DiscoverAddr,
+            Error = Error,
+            Response = (Option<profiles::Receiver>, policy::Receiver),
+        >,
+        D: Clone + Send + Sync + 'static,
+        D::Future: Send + Unpin + 'static,
+        // Endpoint resolver.
+        R: Resolve<ConcreteAddr, Endpoint = Metadata, Error = Error>,
+        R::Resolution: Unpin,
+    {
+        self.to_tcp_connect()
             .push_tcp_endpoint()
             .push_http_tcp_client()
             .push_http_cached(resolve)
@@ -111,11 +172,7 @@ impl Outbound<()> {
                 stk.check_new_service::<Http<Logical>, _>()
                     .push_filter(Http::try_from)
             })
-            .push_discover(discover);
-
-        http.push_ingress(opaque)
-            .push_tcp_instrument(|t: &T| tracing::info_span!("ingress", addr = %t.param()))
-            .into_inner()
+            .push_discover(discover)
     }
 
     fn ingress_resolver(
@@ -273,28 +330,97 @@ impl<N> Outbound<N> {
         NSvc: Send + Unpin + 'static,
         NSvc::Future: Send,
     {
-        self.map_stack(|config, rt, inner| {
-            let detect_params = http::DetectParams {
-                read_timeout: config.proxy.detect_protocol_timeout,
-                metrics: rt
-                    .metrics
-                    .prom
-                    .http_detect
-                    .metrics(ParentRef(policy::Meta::new_default("ingress"))),
-            };
+        self.push_ingress_router::<T, NSvc>()
+            .map_stack(|config, rt, http| {
+                let detect_params = http::DetectParams {
+                    read_timeout: config.proxy.detect_protocol_timeout,
+                    metrics: rt
+                        .metrics
+                        .prom
+                        .http_detect
+                        .metrics(ParentRef(policy::Meta::new_default("ingress"))),
+                    sniffers: Default::default(),
+                    server_first_timeout: None,
+                };
 
-            // Route requests with destinations that can be discovered via the
-            // `l5d-dst-override` header through the (load balanced) logical
-            // stack. Route requests without the header through the endpoint
-            // stack.
-            //
-            // Errors are not handled gracefully by this stack -- they hit the
-            // Hyper server.
-            //
-            // This stack creates one-off services for each request--so it is
-            // important that the inner stack caches any state that should be
-            // shared across requests.
-            let http = inner
+                // Advertise the HTTP/3 listener, if one is configured, so that
+                // clients may upgrade subsequent requests to QUIC.
+                let alt_svc = config.http3.as_ref().map(h3::ServerConfig::alt_svc);
+                let http = http
+                    .push_on_service(h3::AltSvc::layer(alt_svc))
+                    .check_new_service::<Http<T>, http::Request<_>>();
+
+                // HTTP detection is **always** performed. If detection fails, then we
+                // use the `fallback` stack to process the connection by its original
+                // destination address.
+                http.check_new_service::<Http<T>, http::Request<_>>()
+                    .unlift_new()
+                    .push_on_service(connections::NewCountStreams::layer(
+                        rt.connections.clone(),
+                        connections::Direction::Out,
+                    ))
+                    .push(http::NewServeHttp::layer({
+                        let h2 = config.proxy.server.http2.clone();
+                        let drain = rt.drain.clone();
+                        move |http: &Http<T>| http::ServerParams {
+                            version: http.version,
+                            http2: h2.clone(),
+                            drain: drain.clone(),
+                        }
+                    }))
+                    .push(connections::NewRecord::layer_via(
+                        rt.connections.clone(),
+                        |http: &Http<T>| connections::Negotiated::outbound(http.version.into()),
+                    ))
+                    .check_new_service::<Http<T>, I>()
+                    .push_switch(
+                        |(detected, parent): (http::Detection, T)| -> Result<_, Infallible> {
+                            match detected {
+                                http::Detection::Http(version) => {
+                                    return Ok(svc::Either::Left(Http { version, parent }));
+                                }
+                                http::Detection::ReadTimeout(timeout) => {
+                                    tracing::info!("Continuing after timeout: {timeout:?}");
+                                }
+                                _ => {}
+                            }
+                            Ok(svc::Either::Right(parent))
+                        },
+                        fallback,
+                    )
+                    .lift_new_with_target()
+                    .push(http::NewDetect::layer(svc::CloneParam::from(detect_params)))
+                    .arc_new_tcp()
+            })
+    }
+
+    /// Routes requests with destinations that can be discovered via the
+    /// `l5d-dst-override` header through the (load balanced) logical stack.
+    /// Requests without the header are routed through the endpoint stack.
+    ///
+    /// Errors are not handled gracefully by this stack -- they hit the server.
+    ///
+    /// This stack creates one-off services for each request--so it is
+    /// important that the inner stack caches any state that should be shared
+    /// across requests.
+    fn push_ingress_router<T, NSvc>(self) -> Outbound<svc::ArcNewCloneHttp<Http<T>>>
+    where
+        // Target type describing an ingress connection.
+        T: svc::Param<OrigDstAddr>,
+        T: Clone + Send + Sync + 'static,
+        //  HTTP stack.
+        N: svc::NewService<Http<RequestTarget>, Service = NSvc>,
+        N: Clone + Send + Sync + 'static,
+        NSvc: svc::Service<
+            http::Request<http::BoxBody>,
+            Response = http::Response<http::BoxBody>,
+            Error = Error,
+        >,
+        NSvc: Send + 'static,
+        NSvc::Future: Send,
+    {
+        self.map_stack(|_, _, inner| {
+            inner
                 .check_new_service::<Http<RequestTarget>, http::Request<http::BoxBody>>()
                 .push_on_service(
                     svc::layers()
@@ -305,41 +431,8 @@ impl<N> Outbound<N> {
                 .push(svc::NewOneshotRoute::layer_via(|t: &Http<T>| {
                     SelectTarget(t.clone())
                 }))
-                .check_new_service::<Http<T>, http::Request<_>>();
-
-            // HTTP detection is **always** performed. If detection fails, then we
-            // use the `fallback` stack to process the connection by its original
-            // destination address.
-            http.check_new_service::<Http<T>, http::Request<_>>()
-                .unlift_new()
-                .push(http::NewServeHttp::layer({
-                    let h2 = config.proxy.server.http2.clone();
-                    let drain = rt.drain.clone();
-                    move |http: &Http<T>| http::ServerParams {
-                        version: http.version,
-                        http2: h2.clone(),
-                        drain: drain.clone(),
-                    }
-                }))
-                .check_new_service::<Http<T>, I>()
-                .push_switch(
-                    |(detected, parent): (http::Detection, T)| -> Result<_, Infallible> {
-                        match detected {
-                            http::Detection::Http(version) => {
-                                return Ok(svc::Either::Left(Http { version, parent }));
-                            }
-                            http::Detection::ReadTimeout(timeout) => {
-                                tracing::info!("Continuing after timeout: {timeout:?}");
-                            }
-                            _ => {}
-                        }
-                        Ok(svc::Either::Right(parent))
-                    },
-                    fallback,
-                )
-                .lift_new_with_target()
-                .push(http::NewDetect::layer(svc::CloneParam::from(detect_params)))
-                .arc_new_tcp()
+                .check_new_service::<Http<T>, http::Request<_>>()
+                .arc_new_clone_http()
         })
     }
 }
@@ -542,9 +635,19 @@ fn policy_routes(
             ref http2,
             ..
         } => {
-            let (routes, failure_accrual) = match version {
-                http::Variant::Http1 => (http1.routes.clone(), http1.failure_accrual),
-                http::Variant::H2 => (http2.routes.clone(), http2.failure_accrual),
+            let (routes, failure_accrual, client_params, pool_limits) = match version {
+                http::Variant::Http1 => (
+                    http1.routes.clone(),
+                    http1.failure_accrual,
+                    Default::default(),
+                    http1.pool_limits,
+                ),
+                http::Variant::H2 => (
+                    http2.routes.clone(),
+                    http2.failure_accrual,
+                    http2.client_params.clone(),
+                    Default::default(),
+                ),
             };
             Some(http::Routes::Policy(http::policy::Params::Http(
                 http::policy::HttpParams {
@@ -553,6 +656,8 @@ fn policy_routes(
                     backends: policy.backends.clone(),
                     routes,
                     failure_accrual,
+                    client_params,
+                    pool_limits,
                 },
             )))
         }
@@ -562,6 +667,7 @@ fn policy_routes(
         policy::Protocol::Http1(policy::http::Http1 {
             ref routes,
             failure_accrual,
+            pool_limits,
         }) => Some(http::Routes::Policy(http::policy::Params::Http(
             http::policy::HttpParams {
                 addr,
@@ -569,11 +675,14 @@ fn policy_routes(
                 backends: policy.backends.clone(),
                 routes: routes.clone(),
                 failure_accrual,
+                client_params: Default::default(),
+                pool_limits,
             },
         ))),
         policy::Protocol::Http2(policy::http::Http2 {
             ref routes,
             failure_accrual,
+            ref client_params,
         }) => Some(http::Routes::Policy(http::policy::Params::Http(
             http::policy::HttpParams {
                 addr,
@@ -581,11 +690,14 @@ fn policy_routes(
                 backends: policy.backends.clone(),
                 routes: routes.clone(),
                 failure_accrual,
+                client_params: client_params.clone(),
+                pool_limits: Default::default(),
             },
         ))),
         policy::Protocol::Grpc(policy::grpc::Grpc {
             ref routes,
             failure_accrual,
+            ref client_params,
         }) => Some(http::Routes::Policy(http::policy::Params::Grpc(
             http::policy::GrpcParams {
                 addr,
@@ -593,6 +705,8 @@ fn policy_routes(
                 backends: policy.backends.clone(),
                 routes: routes.clone(),
                 failure_accrual,
+                client_params: client_params.clone(),
+                pool_limits: Default::default(),
             },
         ))),
         _ => None,
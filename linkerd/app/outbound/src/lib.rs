@@ -22,10 +22,12 @@ use linkerd_app_core::{
     },
     svc::{self, ServiceExt},
     tls::ConnectMeta as TlsConnectMeta,
-    transport::addrs::*,
-    AddrMatch, Error, NameAddr, ProxyRuntime,
+    transport::{self, addrs::*},
+    Addr, AddrMatch, Error, NameAddr, ProxyRuntime,
 };
 use linkerd_tonic_stream::ReceiveLimits;
+use linkerd_tonic_watch::Snapshots;
+use rangemap::RangeInclusiveSet;
 use std::{
     collections::{HashMap, HashSet},
     fmt::Debug,
@@ -42,6 +44,7 @@ mod metrics;
 pub mod opaq;
 pub mod policy;
 mod protocol;
+mod resolve;
 mod sidecar;
 pub mod tcp;
 #[cfg(any(test, feature = "test-util"))]
@@ -81,8 +84,55 @@ pub struct Config {
     pub ingress_mode: bool,
     pub inbound_ips: Arc<HashSet<IpAddr>>,
 
+    // In "ingress mode", an HTTP/3 listener may also accept requests over
+    // QUIC. It is advertised on responses to HTTP/1 and HTTP/2 requests.
+    pub http3: Option<linkerd_http_h3::ServerConfig>,
+
     // Whether the proxy may include informational headers on HTTP responses.
     pub emit_headers: bool,
+
+    // Whether opaque connections forwarded to endpoints outside of the mesh
+    // are prefixed with a PROXY protocol header.
+    pub emit_proxy_protocol: bool,
+
+    // The header on which the remaining deadline of non-gRPC HTTP requests is
+    // propagated. gRPC requests always propagate it as `grpc-timeout`.
+    pub http_deadline_header: Option<proxy::http::HeaderName>,
+
+    // HTTP routes to these ports compress responses (and, optionally,
+    // decompress requests) as configured by `compression`, since the policy
+    // API does not describe compression.
+    pub compression_ports: RangeInclusiveSet<u16>,
+    pub compression: Arc<policy::http::filter::Compression>,
+
+    // Limits connections that are upgraded by HTTP routes' requests (e.g.
+    // WebSockets), since the policy API does not describe upgrades.
+    pub upgrade_timeouts: policy::http::UpgradeTimeouts,
+
+    // Opaque connections to these ports are proxied as Redis, so that
+    // per-command metrics are recorded and denied commands are rejected.
+    pub redis_ports: RangeInclusiveSet<u16>,
+    pub redis_deny_commands: Arc<[Arc<str>]>,
+
+    // Backends of Redis routes for these services, named as
+    // `<name>.<namespace>`, are read replicas to which read-only commands are
+    // sent.
+    pub redis_read_replicas: Arc<[Arc<str>]>,
+
+    // Opaque connections to these ports are proxied as Postgres, so that
+    // per-database query metrics are recorded and sessions that servers fail
+    // are classified as failures by Postgres circuit breakers.
+    pub postgres_ports: RangeInclusiveSet<u16>,
+
+    // Configures circuit breakers for the endpoints of balancers proxied as
+    // Postgres, since the policy API does not describe failure accrual for
+    // opaque routes. Other opaque endpoints are never ejected.
+    pub postgres_failure_accrual: Option<policy::FailureAccrual>,
+
+    // Bounds on how long endpoints discovered through DNS are used before
+    // their names are resolved again, regardless of the records' TTLs.
+    pub dns_discovery_min_ttl: Duration,
+    pub dns_discovery_max_ttl: Duration,
 }
 
 #[derive(Clone, Debug)]
@@ -99,6 +149,9 @@ struct Runtime {
     tap: tap::Registry,
     span_sink: Option<SpanSink>,
     drain: drain::Watch,
+    connections: transport::Connections,
+    /// Resolves backends that use DNS endpoint discovery, if enabled.
+    dns: Option<proxy::dns_resolve::DnsDiscover<proxy::dns_resolve::BoxLookup>>,
 }
 
 pub type ConnectMeta = TlsConnectMeta<Local<ClientAddr>>;
@@ -124,11 +177,13 @@ pub struct EndpointRef(Arc<policy::Meta>);
 impl Outbound<()> {
     pub fn new(config: Config, runtime: ProxyRuntime, prom: &mut prom::Registry) -> Self {
         let runtime = Runtime {
-            metrics: OutboundMetrics::new(runtime.metrics, prom),
+            metrics: OutboundMetrics::new(runtime.metrics, prom, config.proxy.histogram_encoding),
             identity: runtime.identity.new_client(),
             tap: runtime.tap,
             span_sink: runtime.span_sink,
             drain: runtime.drain,
+            connections: runtime.connections,
+            dns: None,
         };
         Self {
             config,
@@ -144,6 +199,7 @@ impl Outbound<()> {
         backoff: ExponentialBackoff,
         limits: ReceiveLimits,
         export_hostname_labels: bool,
+        snapshots: Snapshots<Addr, policy::ClientPolicy>,
     ) -> impl policy::GetPolicy
     where
         C: tonic::client::GrpcService<tonic::body::Body, Error = Error>,
@@ -152,11 +208,18 @@ impl Outbound<()> {
         C::ResponseBody: Send + 'static,
         C::Future: Send,
     {
+        let overrides = policy::ClientPolicyOverrides {
+            export_hostname_labels,
+            compression: Some(self.config.compression.clone()),
+            upgrade_timeouts: self.config.upgrade_timeouts.clone(),
+        };
         policy::Api::new(
             workload,
             limits,
             Duration::from_secs(10),
-            export_hostname_labels,
+            overrides,
+            self.config.compression_ports.clone(),
+            snapshots,
             client,
         )
         .into_watch(backoff)
@@ -187,10 +250,28 @@ impl<S> Outbound<S> {
         self.runtime.metrics.clone()
     }
 
+    /// Enables DNS endpoint discovery for backends that configure it.
+    ///
+    /// Without a resolver, such backends fail to resolve endpoints.
+    pub fn with_dns(mut self, dns: impl proxy::dns_resolve::Lookup) -> Self {
+        let lookup = proxy::dns_resolve::BoxLookup::new(dns);
+        self.runtime.dns = Some(proxy::dns_resolve::DnsDiscover::new(
+            lookup,
+            self.config.dns_discovery_min_ttl,
+            self.config.dns_discovery_max_ttl,
+        ));
+        self
+    }
+
     pub fn stack_metrics(&self) -> metrics::Stack {
         self.runtime.metrics.proxy.stack.clone()
     }
 
+    /// Returns a registry describing the endpoints of each HTTP balancer.
+    pub fn balancers(&self) -> http::concrete::Balancers {
+        self.runtime.metrics.prom.http.balancers()
+    }
+
     pub fn with_stack<Svc>(self, stack: Svc) -> Outbound<Svc> {
         self.map_stack(move |_, _, _| svc::stack(stack))
     }
@@ -270,6 +351,25 @@ pub fn trace_labels() -> HashMap<String, String> {
 
 // === impl ParentRef ===
 
+impl ParentRef {
+    /// Describes the parent resource as tap destination labels, e.g.
+    /// `service=web` and `namespace=default`.
+    pub(crate) fn tap_labels(&self) -> tap::Labels {
+        let mut labels = std::collections::BTreeMap::new();
+        if let policy::Meta::Resource {
+            kind,
+            name,
+            namespace,
+            ..
+        } = &*self.0
+        {
+            labels.insert(kind.to_ascii_lowercase(), name.clone());
+            labels.insert("namespace".to_string(), namespace.clone());
+        }
+        Arc::new(labels)
+    }
+}
+
 impl std::ops::Deref for ParentRef {
     type Target = policy::Meta;
 
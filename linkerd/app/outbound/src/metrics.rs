@@ -45,7 +45,10 @@ pub(crate) struct PromMetrics {
 pub struct ConcreteLabels(pub ParentRef, pub BackendRef);
 
 #[derive(Clone, Debug)]
-pub struct BalancerMetricsParams<K>(balance::MetricFamilies<K>);
+pub struct BalancerMetricsParams<K> {
+    families: balance::MetricFamilies<K>,
+    balancers: balance::registry::Balancers<K>,
+}
 
 struct ScopedKey<'a, 'b>(&'a str, &'b str);
 
@@ -56,11 +59,19 @@ where
     K: EncodeLabelSet + Clone + Eq + std::hash::Hash + std::fmt::Debug + Send + Sync + 'static,
 {
     pub fn register(reg: &mut prom::registry::Registry) -> Self {
-        Self(balance::MetricFamilies::register(reg))
+        Self {
+            families: balance::MetricFamilies::register(reg),
+            balancers: Default::default(),
+        }
     }
 
     pub fn metrics(&self, labels: &K) -> balance::Metrics {
-        self.0.metrics(labels)
+        self.families.metrics(labels)
+    }
+
+    /// Returns the registry of balancers' endpoints.
+    pub fn balancers(&self) -> balance::registry::Balancers<K> {
+        self.balancers.clone()
     }
 }
 
@@ -69,7 +80,9 @@ where
     T: svc::Param<ParentRef> + svc::Param<BackendRef>,
 {
     fn extract_param(&self, target: &T) -> balance::Metrics {
-        self.metrics(&ConcreteLabels(target.param(), target.param()))
+        let labels = ConcreteLabels(target.param(), target.param());
+        let balancer = self.balancers.balancer(labels.clone());
+        self.metrics(&labels).with_registry(balancer)
     }
 }
 
@@ -79,14 +92,17 @@ where
     L: Eq + Clone,
 {
     fn default() -> Self {
-        Self(balance::MetricFamilies::default())
+        Self {
+            families: balance::MetricFamilies::default(),
+            balancers: Default::default(),
+        }
     }
 }
 
 // === impl PromMetrics ===
 
 impl PromMetrics {
-    pub fn register(registry: &mut prom::Registry) -> Self {
+    pub fn register(registry: &mut prom::Registry, histograms: prom::HistogramEncoding) -> Self {
         let protocol = crate::protocol::MetricsFamilies::register(
             registry.sub_registry_with_prefix("tcp_protocol"),
         );
@@ -97,7 +113,7 @@ impl PromMetrics {
 
         // NOTE: HTTP metrics are scoped internally, since this configures both
         // HTTP and gRPC scopes.
-        let http = crate::http::HttpMetrics::register(registry);
+        let http = crate::http::HttpMetrics::register(registry, histograms);
 
         let opaq = crate::opaq::OpaqMetrics::register(registry.sub_registry_with_prefix("tcp"));
         let zone = crate::zone::TcpZoneMetrics::register(registry.sub_registry_with_prefix("tcp"));
@@ -117,12 +133,16 @@ impl PromMetrics {
 // === impl OutboundMetrics ===
 
 impl OutboundMetrics {
-    pub(crate) fn new(proxy: Proxy, registry: &mut prom::Registry) -> Self {
+    pub(crate) fn new(
+        proxy: Proxy,
+        registry: &mut prom::Registry,
+        histograms: prom::HistogramEncoding,
+    ) -> Self {
         Self {
             proxy,
             http_errors: error::Http::default(),
             tcp_errors: error::Tcp::default(),
-            prom: PromMetrics::register(registry),
+            prom: PromMetrics::register(registry, histograms),
         }
     }
 }
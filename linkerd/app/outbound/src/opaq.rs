@@ -1,4 +1,4 @@
-use crate::{policy, service_meta, tcp, Outbound, ParentRef, UNKNOWN_META};
+use crate::{http, policy, service_meta, tcp, Outbound, ParentRef, UNKNOWN_META};
 use linkerd_app_core::{
     io,
     metrics::prom,
@@ -6,17 +6,20 @@ use linkerd_app_core::{
     proxy::{
         api_resolve::{ConcreteAddr, Metadata},
         core::Resolve,
+        tap,
     },
-    svc,
+    svc, tls,
     transport::addrs::*,
     Addr, Error, NameAddr,
 };
 use once_cell::sync::Lazy;
-use std::{fmt::Debug, hash::Hash, sync::Arc};
+use std::{fmt::Debug, hash::Hash, net::SocketAddr, sync::Arc};
 use tokio::sync::watch;
 
+mod breaker;
 mod concrete;
 mod logical;
+mod proxy_protocol;
 
 pub use self::logical::{route::filters::errors::*, Concrete, Logical, Routes};
 
@@ -27,6 +30,15 @@ struct Opaq<T>(T);
 pub struct OpaqMetrics {
     balance: concrete::BalancerMetrics,
     route: logical::route::TcpRouteMetrics,
+    redis: logical::route::RedisMetrics,
+    postgres: logical::route::PostgresMetrics,
+    detected: prom::Family<DetectedLabels, prom::Counter>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct DetectedLabels {
+    parent: ParentRef,
+    protocol: Option<http::OpaqueProtocol>,
 }
 
 // === impl Outbound ===
@@ -56,8 +68,9 @@ impl<C> Outbound<C> {
         self.push_tcp_endpoint()
             .push_opaq_concrete(resolve)
             .push_opaq_logical()
-            .map_stack(|config, _rt, stk| {
-                stk.push_new_idle_cached(config.discovery_idle_timeout)
+            .map_stack(|config, rt, stk| {
+                stk.push(tap::NewTapTcp::layer(rt.tap.clone()))
+                    .push_new_idle_cached(config.discovery_idle_timeout)
                     // Use a dedicated target type to configure parameters for
                     // the opaque stack. It also helps narrow the cache key.
                     .push_map_target(Opaq)
@@ -77,6 +90,30 @@ where
     }
 }
 
+impl<T> tap::InspectTcp for Opaq<T>
+where
+    T: svc::Param<watch::Receiver<logical::Routes>>,
+{
+    fn src_tls(&self) -> tls::ConditionalServerTls {
+        tls::ConditionalServerTls::None(tls::NoServerTls::Loopback)
+    }
+
+    fn dst_addr(&self) -> Option<SocketAddr> {
+        match self.0.param().borrow().logical.addr {
+            Addr::Socket(addr) => Some(addr),
+            Addr::Name(_) => None,
+        }
+    }
+
+    fn dst_labels(&self) -> Option<tap::Labels> {
+        Some(self.0.param().borrow().logical.meta.tap_labels())
+    }
+
+    fn is_outbound(&self) -> bool {
+        true
+    }
+}
+
 // === impl OpaqMetrics ===
 
 impl OpaqMetrics {
@@ -85,7 +122,51 @@ impl OpaqMetrics {
             concrete::BalancerMetrics::register(registry.sub_registry_with_prefix("balancer"));
         let route =
             logical::route::TcpRouteMetrics::register(registry.sub_registry_with_prefix("route"));
-        Self { balance, route }
+        let redis =
+            logical::route::RedisMetrics::register(registry.sub_registry_with_prefix("redis"));
+        let postgres = logical::route::PostgresMetrics::register(
+            registry.sub_registry_with_prefix("postgres"),
+        );
+
+        let detected = prom::Family::default();
+        registry.register(
+            "detected_connections",
+            "Connections that failed HTTP detection by detected protocol",
+            detected.clone(),
+        );
+
+        Self {
+            balance,
+            route,
+            redis,
+            postgres,
+            detected,
+        }
+    }
+
+    /// Records a connection that was determined to be opaque by protocol
+    /// detection, labeled with the protocol that was identified, if any.
+    pub(crate) fn record_detected(
+        &self,
+        parent: ParentRef,
+        protocol: Option<http::OpaqueProtocol>,
+    ) {
+        self.detected
+            .get_or_create(&DetectedLabels { parent, protocol })
+            .inc();
+    }
+}
+
+// === impl DetectedLabels ===
+
+impl prom::encoding::EncodeLabelSet for DetectedLabels {
+    fn encode(&self, enc: &mut prom::encoding::LabelSetEncoder<'_>) -> std::fmt::Result {
+        use prom::encoding::*;
+
+        self.parent.encode_label_set(enc)?;
+        ("protocol", self.protocol.map_or("unknown", |p| p.as_str())).encode(enc.encode_label())?;
+
+        Ok(())
     }
 }
 
@@ -0,0 +1,214 @@
+use crate::http::breaker;
+use futures::{future::BoxFuture, FutureExt, TryFutureExt};
+use linkerd_app_core::{
+    classify, io,
+    proxy::{api_resolve::Metadata, http::classify::gate},
+    svc,
+};
+use linkerd_proxy_postgres as postgres;
+use std::{
+    net::SocketAddr,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tokio::sync::mpsc;
+
+/// Params configuring the circuit breakers of a balancer's endpoints.
+#[derive(Clone, Debug)]
+pub(super) struct Params {
+    pub(super) breaker: breaker::Params,
+    /// Set when connections are proxied as Postgres, so that sessions that
+    /// servers end because they are unable to serve them are failures.
+    pub(super) postgres: bool,
+}
+
+/// Builds a [`NewClassifyGate`] for each balancer.
+#[derive(Clone, Debug)]
+pub(super) struct NewClassifyGateSet<X, N> {
+    extract: X,
+    inner: N,
+}
+
+/// Builds a breaker for each of a balancer's endpoints.
+///
+/// Each endpoint's connections are classified as they complete and the
+/// classifications are sent to a failure accrual policy, which shuts the
+/// endpoint's [`svc::Gate`] so that the balancer does not select it. Only
+/// Postgres sessions are classified, so balancers that are not proxied as
+/// Postgres are configured without a failure accrual policy.
+#[derive(Clone, Debug)]
+pub(super) struct NewClassifyGate<N> {
+    params: Params,
+    inner: N,
+}
+
+#[derive(Clone, Debug)]
+pub(super) struct Classify<S> {
+    inner: S,
+    responses: mpsc::Sender<classify::Class>,
+    postgres: bool,
+}
+
+/// An endpoint connection that is classified when it is dropped.
+///
+/// A connection fails when the server ends a Postgres session with a
+/// [`postgres::ServerFailure`]; all other connections succeed.
+#[derive(Debug)]
+pub(super) struct ClassifyIo<I> {
+    io: I,
+    responses: mpsc::Sender<classify::Class>,
+    failures: Option<postgres::Failures>,
+}
+
+// === impl NewClassifyGateSet ===
+
+impl<X: Clone, N> NewClassifyGateSet<X, N> {
+    pub(super) fn layer_via(extract: X) -> impl svc::layer::Layer<N, Service = Self> + Clone {
+        svc::layer::mk(move |inner| Self {
+            extract: extract.clone(),
+            inner,
+        })
+    }
+}
+
+impl<T, X, N> svc::NewService<T> for NewClassifyGateSet<X, N>
+where
+    X: svc::ExtractParam<Params, T>,
+    N: svc::NewService<T>,
+{
+    type Service = NewClassifyGate<N::Service>;
+
+    fn new_service(&self, target: T) -> Self::Service {
+        let params = self.extract.extract_param(&target);
+        let inner = self.inner.new_service(target);
+        NewClassifyGate { params, inner }
+    }
+}
+
+// === impl NewClassifyGate ===
+
+impl<N> svc::NewService<(SocketAddr, Metadata)> for NewClassifyGate<N>
+where
+    N: svc::NewService<(SocketAddr, Metadata)>,
+{
+    type Service = svc::Gate<Classify<N::Service>>;
+
+    fn new_service(&self, target: (SocketAddr, Metadata)) -> Self::Service {
+        let gate::Params { responses, gate } =
+            svc::ExtractParam::<gate::Params<classify::Class>, _>::extract_param(
+                &self.params.breaker,
+                &target,
+            );
+        let inner = self.inner.new_service(target);
+        svc::Gate::new(
+            gate,
+            Classify {
+                inner,
+                responses,
+                postgres: self.params.postgres,
+            },
+        )
+    }
+}
+
+// === impl Classify ===
+
+impl<Req, S> svc::Service<Req> for Classify<S>
+where
+    S: svc::Service<Req>,
+    S::Response: Send + 'static,
+    S::Error: Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = ClassifyIo<S::Response>;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, S::Error>>;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Req) -> Self::Future {
+        let responses = self.responses.clone();
+        let failures = self.postgres.then(postgres::Failures::default);
+        self.inner
+            .call(req)
+            .map_ok(move |io| ClassifyIo {
+                io,
+                responses,
+                failures,
+            })
+            .inspect_err({
+                let responses = self.responses.clone();
+                move |_| {
+                    // Connection failures are always failures.
+                    let _ = responses.try_send(classify::Class::Opaque(Err(())));
+                }
+            })
+            .boxed()
+    }
+}
+
+// === impl ClassifyIo ===
+
+impl<I> ClassifyIo<I> {
+    fn class(&self) -> classify::Class {
+        match self.failures.as_ref().and_then(|f| f.failure()) {
+            Some(failure) => {
+                tracing::debug!(%failure, "Session failed");
+                classify::Class::Opaque(Err(()))
+            }
+            None => classify::Class::Opaque(Ok(())),
+        }
+    }
+}
+
+impl<I> Drop for ClassifyIo<I> {
+    fn drop(&mut self) {
+        // If the breaker's channel is full, the classification is dropped,
+        // as is done for HTTP responses.
+        let _ = self.responses.try_send(self.class());
+    }
+}
+
+impl<I: io::AsyncRead + Unpin> io::AsyncRead for ClassifyIo<I> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut io::ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        let filled = buf.filled().len();
+        futures::ready!(Pin::new(&mut this.io).poll_read(cx, buf))?;
+        if let Some(failures) = this.failures.as_mut() {
+            failures.scan_server(&buf.filled()[filled..]);
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<I: io::AsyncWrite + Unpin> io::AsyncWrite for ClassifyIo<I> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        let sz = futures::ready!(Pin::new(&mut this.io).poll_write(cx, buf))?;
+        if let Some(failures) = this.failures.as_mut() {
+            failures.scan_client(&buf[..sz]);
+        }
+        Poll::Ready(Ok(sz))
+    }
+
+    #[inline]
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().io).poll_flush(cx)
+    }
+
+    #[inline]
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().io).poll_shutdown(cx)
+    }
+}
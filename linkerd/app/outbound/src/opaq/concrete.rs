@@ -1,6 +1,8 @@
-use super::Logical;
+use super::{breaker, proxy_protocol, Logical};
 use crate::{
+    http,
     metrics::BalancerMetricsParams,
+    resolve::{BalanceResolve, Discover},
     stack_labels,
     zone::{tcp_zone_labels, TcpZoneLabels},
     BackendRef, Outbound, ParentRef,
@@ -12,6 +14,7 @@ use linkerd_app_core::{
     proxy::{
         api_resolve::{ConcreteAddr, Metadata},
         core::Resolve,
+        dns_resolve,
         http::AuthorityOverride,
         tcp::{self, balance},
     },
@@ -28,6 +31,8 @@ use tracing::info_span;
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum Dispatch {
     Balance(NameAddr, balance::EwmaConfig),
+    /// Load balance over endpoints discovered through DNS.
+    BalanceDns(dns_resolve::Target, balance::EwmaConfig),
     Forward(Remote<ServerAddr>, Arc<Metadata>),
     /// A backend dispatcher that explicitly fails all requests.
     Fail {
@@ -63,6 +68,9 @@ pub type BalancerMetrics = BalancerMetricsParams<ConcreteLabels>;
 #[derive(Clone, Debug, PartialEq, Eq)]
 struct Balance<T> {
     addr: NameAddr,
+    /// Set when endpoints are discovered through DNS rather than the
+    /// destination controller.
+    dns: Option<dns_resolve::Target>,
     ewma: balance::EwmaConfig,
     queue: QueueConfig,
     parent: T,
@@ -133,7 +141,7 @@ impl<C> Outbound<C> {
         T: svc::Param<BackendRef>,
         T: svc::Param<ParentRef>,
         // Server-side socket.
-        I: io::AsyncRead + io::AsyncWrite + Debug + Send + Unpin + 'static,
+        I: io::AsyncRead + io::AsyncWrite + io::PeerAddr + Debug + Send + Unpin + 'static,
         // Endpoint resolution.
         R: Resolve<ConcreteAddr, Endpoint = Metadata, Error = Error>,
         R::Resolution: Unpin,
@@ -144,16 +152,20 @@ impl<C> Outbound<C> {
         C::Future: Send,
         C: Send + Sync + 'static,
     {
-        let resolve =
-            svc::MapTargetLayer::new(|t: Balance<T>| -> ConcreteAddr { ConcreteAddr(t.addr) })
-                .layer(resolve.into_service());
-
         self.map_stack(|config, rt, inner| {
             let queue = config.tcp_connection_queue;
+            let accrual = config.postgres_failure_accrual;
+            let postgres_ports = Arc::new(config.postgres_ports.clone());
+            let resolve = svc::MapTargetLayer::new(|t: Balance<T>| -> Discover {
+                Discover::new(t.addr, t.dns)
+            })
+            .layer(BalanceResolve::new(resolve, rt.dns.clone()));
 
             let connect = inner
                 .push(svc::stack::WithoutConnectionMetadata::layer())
-                .push_new_thunk();
+                .push(proxy_protocol::NewEmitProxyProtocol::layer(
+                    config.emit_proxy_protocol,
+                ));
 
             let forward = connect
                 .clone()
@@ -193,6 +205,21 @@ impl<C> Outbound<C> {
                     },
                 )
                 .lift_new_with_target()
+                .push(breaker::NewClassifyGateSet::layer_via(
+                    move |t: &Balance<T>| {
+                        let Logical { addr, .. } = t.parent.param();
+                        let postgres = postgres_ports.contains(&addr.port());
+                        breaker::Params {
+                            breaker: http::breaker::Params {
+                                // Only Postgres sessions are classified, so
+                                // the gates of other endpoints never shut.
+                                accrual: accrual.filter(|_| postgres),
+                                channel_capacity: queue.capacity,
+                            },
+                            postgres,
+                        }
+                    },
+                ))
                 .push(tcp::NewBalance::layer(
                     resolve,
                     rt.metrics.prom.opaq.balance.clone(),
@@ -204,7 +231,12 @@ impl<C> Outbound<C> {
                         .stack
                         .layer(stack_labels("opaq", "balance")),
                 )
-                .instrument(|t: &Balance<T>| info_span!("balance", addr = %t.addr));
+                .instrument(|t: &Balance<T>| info_span!("balance", addr = %t.addr))
+                // Endpoint connections are proxied under the balancer so that
+                // each endpoint's breaker classifies its connections.
+                .push_on_service(proxy_protocol::Forward::layer());
+
+            let forward = forward.push_on_service(proxy_protocol::Forward::layer());
 
             balance
                 .push_switch(Ok::<_, Infallible>, forward.into_inner())
@@ -214,6 +246,16 @@ impl<C> Outbound<C> {
                             Dispatch::Balance(addr, ewma) => {
                                 svc::Either::Left(svc::Either::Left(Balance {
                                     addr,
+                                    dns: None,
+                                    ewma,
+                                    queue,
+                                    parent,
+                                }))
+                            }
+                            Dispatch::BalanceDns(dns, ewma) => {
+                                svc::Either::Left(svc::Either::Left(Balance {
+                                    addr: dns.addr.clone(),
+                                    dns: Some(dns),
                                     ewma,
                                     queue,
                                     parent,
@@ -233,7 +275,6 @@ impl<C> Outbound<C> {
                     },
                     svc::stack(fail).check_new_clone().into_inner(),
                 )
-                .push_on_service(tcp::Forward::layer())
                 .push_on_service(drain::Retain::layer(rt.drain.clone()))
                 .push(svc::ArcNewService::layer())
         })
@@ -310,6 +351,14 @@ impl<T> svc::Param<Option<crate::tcp::tagged_transport::PortOverride>> for Endpo
     }
 }
 
+impl<T> svc::Param<Option<crate::tcp::DualStackAddr>> for Endpoint<T> {
+    fn param(&self) -> Option<crate::tcp::DualStackAddr> {
+        self.metadata
+            .dual_stack_addr()
+            .map(crate::tcp::DualStackAddr)
+    }
+}
+
 impl<T> svc::Param<Option<AuthorityOverride>> for Endpoint<T> {
     fn param(&self) -> Option<AuthorityOverride> {
         if self.is_local {
@@ -59,19 +59,30 @@ impl<N> Outbound<N> {
         T: svc::Param<watch::Receiver<Routes>>,
         T: Eq + Hash + Clone + Debug + Send + Sync + 'static,
         // Server-side socket.
-        I: io::AsyncRead + io::AsyncWrite + Debug + Send + Unpin + 'static,
+        I: io::AsyncRead + io::AsyncWrite + io::PeerAddr + Debug + Send + Unpin + 'static,
         // Concrete stack.
         N: svc::NewService<Concrete<T>, Service = NSvc> + Clone + Send + Sync + 'static,
-        NSvc: svc::Service<I, Response = ()> + Clone + Send + Sync + 'static,
+        NSvc: svc::Service<route::BackendIo<I>, Response = ()>,
+        NSvc: Clone + Send + Sync + 'static,
         NSvc::Future: Send,
         NSvc::Error: Into<Error>,
     {
-        self.map_stack(|_config, rt, concrete| {
+        self.map_stack(|config, rt, concrete| {
             let metrics = rt.metrics.prom.opaq.route.clone();
+            let redis = route::redis::RedisParams {
+                ports: Arc::new(config.redis_ports.clone()),
+                deny_commands: config.redis_deny_commands.clone(),
+                read_replicas: config.redis_read_replicas.clone(),
+                metrics: rt.metrics.prom.opaq.redis.clone(),
+            };
+            let postgres = route::postgres::PostgresParams {
+                ports: Arc::new(config.postgres_ports.clone()),
+                metrics: rt.metrics.prom.opaq.postgres.clone(),
+            };
 
             concrete
                 .lift_new()
-                .push_on_service(router::Router::layer(metrics.clone()))
+                .push_on_service(router::Router::layer(metrics.clone(), redis, postgres))
                 .push_on_service(svc::NewMapErr::layer_from_target::<LogicalError, _>())
                 // Rebuild the inner router stack every time the watch changes.
                 .push(svc::NewSpawnWatch::<Routes, _>::layer_into::<
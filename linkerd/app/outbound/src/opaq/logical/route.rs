@@ -9,8 +9,16 @@ use linkerd_proxy_client_policy as policy;
 use std::{fmt::Debug, hash::Hash, sync::Arc};
 
 pub(crate) mod filters;
+pub(crate) mod postgres;
+pub(crate) mod redis;
 
 pub type TcpRouteMetrics = TransportRouteMetricsFamily<RouteLabels>;
+pub use self::{postgres::PostgresMetrics, redis::RedisMetrics};
+
+/// The connection type passed to a route's backends. Routes that proxy an
+/// application protocol pass their backends a boxed connection, e.g. the
+/// client's connection wrapped to inspect it as it is forwarded.
+pub type BackendIo<I> = io::EitherIo<I, io::BoxedIo>;
 
 #[derive(Debug, PartialEq, Eq, Hash)]
 pub(crate) struct Backend<T> {
@@ -31,6 +39,9 @@ pub(crate) struct Route<T> {
     pub(super) route_ref: RouteRef,
     pub(super) filters: Arc<[policy::opaq::Filter]>,
     pub(super) distribution: BackendDistribution<T>,
+    /// The policy from which `distribution` was built, so that protocol
+    /// layers may split a route's backends.
+    pub(super) policy_distribution: policy::RouteDistribution<policy::opaq::Filter>,
 }
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
@@ -77,12 +88,15 @@ where
     /// backends are expected to be cached/shared by the inner stack.
     pub(crate) fn layer<N, I, NSvc>(
         metrics: TcpRouteMetrics,
+        redis: redis::RedisParams,
+        postgres: postgres::PostgresParams,
     ) -> impl svc::Layer<N, Service = svc::ArcNewCloneTcp<Self, I>> + Clone
     where
-        I: io::AsyncRead + io::AsyncWrite + Debug + Send + Unpin + 'static,
+        I: io::AsyncRead + io::AsyncWrite + io::PeerAddr + Debug + Send + Unpin + 'static,
         // Inner stack.
         N: svc::NewService<Concrete<T>, Service = NSvc> + Clone + Send + Sync + 'static,
-        NSvc: svc::Service<I, Response = ()> + Clone + Send + Sync + 'static,
+        NSvc: svc::Service<BackendIo<I>, Response = ()>,
+        NSvc: Clone + Send + Sync + 'static,
         NSvc::Future: Send,
         NSvc::Error: Into<Error>,
     {
@@ -98,6 +112,8 @@ where
                 // consideration, so we must eagerly fail requests to prevent
                 // leaking tasks onto the runtime.
                 .push_on_service(svc::LoadShed::layer())
+                .push(postgres::NewPostgresRoute::layer(postgres.clone()))
+                .push(redis::NewRedisRoute::layer(redis.clone()))
                 // apply route level filters
                 .push(filters::NewApplyFilters::layer())
                 .push(svc::NewMapErr::layer_with(|rt: &Self| {
@@ -47,7 +47,7 @@ where
 
 impl<S> ApplyFilters<S> {
     fn apply_filters(&self) -> Result<(), Error> {
-        if let Some(filter) = self.filters.iter().next() {
+        for filter in self.filters.iter() {
             match filter {
                 opaq::Filter::Forbidden => {
                     return Err(errors::TCPForbiddenRoute.into());
@@ -0,0 +1,101 @@
+use super::{BackendIo, MatchedRoute, RouteLabels};
+use linkerd_app_core::{io, svc};
+use linkerd_proxy_postgres as postgres;
+use rangemap::RangeInclusiveSet;
+use std::{
+    fmt::Debug,
+    hash::Hash,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+pub type PostgresMetrics = postgres::SessionMetricsFamilies<RouteLabels>;
+
+/// A client's connection, inspected as Postgres as it is forwarded to a
+/// backend.
+pub type PostgresIo<I> = postgres::ClientIo<I, RouteLabels>;
+
+/// Configures Postgres proxying for opaque routes.
+#[derive(Clone, Debug)]
+pub(crate) struct PostgresParams {
+    /// Routes to these ports are proxied as Postgres.
+    pub(crate) ports: Arc<RangeInclusiveSet<u16>>,
+    pub(crate) metrics: PostgresMetrics,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct NewPostgresRoute<N> {
+    inner: N,
+    params: PostgresParams,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct PostgresRoute<S> {
+    inner: S,
+    params: Option<postgres::Params<RouteLabels>>,
+}
+
+// === impl NewPostgresRoute ===
+
+impl<N> NewPostgresRoute<N> {
+    pub(crate) fn layer(
+        params: PostgresParams,
+    ) -> impl svc::layer::Layer<N, Service = Self> + Clone {
+        svc::layer::mk(move |inner| Self {
+            inner,
+            params: params.clone(),
+        })
+    }
+}
+
+impl<T, N> svc::NewService<MatchedRoute<T>> for NewPostgresRoute<N>
+where
+    T: Debug + Eq + Hash + Clone,
+    N: svc::NewService<MatchedRoute<T>>,
+{
+    type Service = PostgresRoute<N::Service>;
+
+    fn new_service(&self, route: MatchedRoute<T>) -> Self::Service {
+        let enabled = self
+            .params
+            .ports
+            .contains(&route.params.logical.addr.port());
+        let params = enabled.then(|| postgres::Params {
+            metrics: self.params.metrics.metrics(svc::Param::param(&route)),
+        });
+
+        PostgresRoute {
+            inner: self.inner.new_service(route),
+            params,
+        }
+    }
+}
+
+// === impl PostgresRoute ===
+
+impl<I, S> svc::Service<BackendIo<I>> for PostgresRoute<S>
+where
+    I: io::AsyncRead + io::AsyncWrite + io::PeerAddr + Send + Unpin + 'static,
+    S: svc::Service<BackendIo<I>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, io: BackendIo<I>) -> Self::Future {
+        // Connections that are already inspected as another protocol are not
+        // inspected again.
+        let io = match (io, self.params.clone()) {
+            (io::EitherIo::Left(io), Some(params)) => {
+                io::EitherIo::Right(io::BoxedIo::new(PostgresIo::new(io, params)))
+            }
+            (io, _) => io,
+        };
+        self.inner.call(io)
+    }
+}
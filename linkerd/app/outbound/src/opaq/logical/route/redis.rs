@@ -0,0 +1,245 @@
+use super::{super::router, BackendIo, MatchedRoute, RouteLabels};
+use futures::{future, FutureExt, TryFutureExt};
+use linkerd_app_core::{io, svc, Error};
+use linkerd_proxy_client_policy as policy;
+use linkerd_proxy_redis as redis;
+use rangemap::RangeInclusiveSet;
+use std::{
+    fmt::Debug,
+    hash::Hash,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+pub type RedisMetrics = redis::CommandMetricsFamilies<RouteLabels>;
+
+/// A client's connection, inspected as Redis as it is forwarded to a backend.
+pub type RedisIo<I> = redis::ClientIo<I, RouteLabels>;
+
+/// Configures Redis proxying for opaque routes.
+#[derive(Clone, Debug)]
+pub(crate) struct RedisParams {
+    /// Routes to these ports are proxied as Redis.
+    pub(crate) ports: Arc<RangeInclusiveSet<u16>>,
+    /// The commands denied on routes enabled by `ports`.
+    pub(crate) deny_commands: Arc<[Arc<str>]>,
+    /// Services, named as `<name>.<namespace>`, that are Redis read replicas.
+    /// When a route's policy includes backends for these services, read-only
+    /// commands are sent to them and all other commands are sent to the
+    /// route's remaining backends. Routes whose backends are all replicas are
+    /// not split.
+    pub(crate) read_replicas: Arc<[Arc<str>]>,
+    pub(crate) metrics: RedisMetrics,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct NewRedisRoute<N> {
+    inner: N,
+    params: RedisParams,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) enum RedisRoute<S> {
+    /// The route is not proxied as Redis.
+    Opaque(S),
+    /// Connections are inspected as they are forwarded to the route's backends.
+    Inspect {
+        inner: S,
+        params: redis::Params<RouteLabels>,
+    },
+    /// Read-only commands are sent to the route's replica backends and all
+    /// other commands are sent to its primary backends.
+    Split {
+        primary: S,
+        replica: S,
+        params: redis::Params<RouteLabels>,
+    },
+}
+
+// === impl RedisParams ===
+
+impl RedisParams {
+    fn is_replica(&self, meta: &policy::Meta) -> bool {
+        meta.kind().eq_ignore_ascii_case("service")
+            && self
+                .read_replicas
+                .iter()
+                .any(|replica| replica.split_once('.') == Some((meta.name(), meta.namespace())))
+    }
+}
+
+// === impl NewRedisRoute ===
+
+impl<N> NewRedisRoute<N> {
+    pub(crate) fn layer(params: RedisParams) -> impl svc::layer::Layer<N, Service = Self> + Clone {
+        svc::layer::mk(move |inner| Self {
+            inner,
+            params: params.clone(),
+        })
+    }
+}
+
+impl<T, N> svc::NewService<MatchedRoute<T>> for NewRedisRoute<N>
+where
+    T: Debug + Eq + Hash + Clone,
+    N: svc::NewService<MatchedRoute<T>>,
+{
+    type Service = RedisRoute<N::Service>;
+
+    fn new_service(&self, route: MatchedRoute<T>) -> Self::Service {
+        if !self
+            .params
+            .ports
+            .contains(&route.params.logical.addr.port())
+        {
+            return RedisRoute::Opaque(self.inner.new_service(route));
+        }
+
+        let params = redis::Params {
+            deny_commands: self.params.deny_commands.clone(),
+            metrics: self.params.metrics.metrics(svc::Param::param(&route)),
+        };
+
+        let Some((primary, replica)) = split_replicas(&route.params.policy_distribution, |rb| {
+            self.params.is_replica(&rb.backend.meta)
+        }) else {
+            return RedisRoute::Inspect {
+                inner: self.inner.new_service(route),
+                params,
+            };
+        };
+
+        let mk_route = |distribution| {
+            let mut route = route.clone();
+            route.params.distribution = router::mk_distribution(
+                &route.params.parent,
+                &route.params.logical,
+                &route.params.route_ref,
+                &distribution,
+            );
+            route.params.policy_distribution = distribution;
+            self.inner.new_service(route)
+        };
+        RedisRoute::Split {
+            primary: mk_route(primary),
+            replica: mk_route(replica),
+            params,
+        }
+    }
+}
+
+// === impl RedisRoute ===
+
+impl<I, S> svc::Service<I> for RedisRoute<S>
+where
+    I: io::AsyncRead + io::AsyncWrite + io::PeerAddr + Send + Unpin + 'static,
+    S: svc::Service<BackendIo<I>, Response = ()> + Send + 'static,
+    S::Error: Into<Error>,
+    S::Future: Send,
+{
+    type Response = ();
+    type Error = Error;
+    type Future = future::Either<
+        future::ErrInto<S::Future, Error>,
+        future::BoxFuture<'static, Result<(), Error>>,
+    >;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        match self {
+            Self::Opaque(inner) | Self::Inspect { inner, .. } => {
+                inner.poll_ready(cx).map_err(Into::into)
+            }
+            Self::Split {
+                primary, replica, ..
+            } => {
+                if let Err(e) = futures::ready!(primary.poll_ready(cx)) {
+                    return Poll::Ready(Err(e.into()));
+                }
+                replica.poll_ready(cx).map_err(Into::into)
+            }
+        }
+    }
+
+    fn call(&mut self, io: I) -> Self::Future {
+        match self {
+            Self::Opaque(inner) => {
+                future::Either::Left(inner.call(io::EitherIo::Left(io)).err_into())
+            }
+            Self::Inspect { inner, params } => {
+                let io = io::BoxedIo::new(RedisIo::new(io, params.clone()));
+                future::Either::Left(inner.call(io::EitherIo::Right(io)).err_into())
+            }
+            Self::Split {
+                primary,
+                replica,
+                params,
+            } => {
+                let client_addr = match io.peer_addr() {
+                    Ok(addr) => addr,
+                    Err(e) => return future::Either::Right(future::err(e.into()).boxed()),
+                };
+
+                // Each backend is given a pipe over which the proxy sends
+                // commands.
+                let (pipe, primary_io) = io::Pipe::new(client_addr);
+                let primary = primary
+                    .call(io::EitherIo::Right(io::BoxedIo::new(pipe)))
+                    .err_into::<Error>();
+                let (pipe, replica_io) = io::Pipe::new(client_addr);
+                let replica = replica
+                    .call(io::EitherIo::Right(io::BoxedIo::new(pipe)))
+                    .err_into::<Error>();
+
+                let proxy =
+                    redis::split(io, primary_io, replica_io, params.clone()).err_into::<Error>();
+                future::Either::Right(
+                    future::try_join3(proxy, primary, replica)
+                        .map_ok(|_| ())
+                        .boxed(),
+                )
+            }
+        }
+    }
+}
+
+/// Splits a distribution into the backends that are not read replicas and
+/// those that are, if the distribution has backends of both kinds.
+///
+/// When every backend is a replica, or none is, commands are not split, so
+/// that each command has a backend to which it may be sent.
+fn split_replicas(
+    distribution: &policy::RouteDistribution<policy::opaq::Filter>,
+    is_replica: impl Fn(&policy::RouteBackend<policy::opaq::Filter>) -> bool,
+) -> Option<(
+    policy::RouteDistribution<policy::opaq::Filter>,
+    policy::RouteDistribution<policy::opaq::Filter>,
+)> {
+    use policy::RouteDistribution;
+
+    match distribution {
+        RouteDistribution::Empty => None,
+        RouteDistribution::FirstAvailable(backends) => {
+            let (replicas, primaries): (Vec<_>, Vec<_>) =
+                backends.iter().cloned().partition(&is_replica);
+            if replicas.is_empty() || primaries.is_empty() {
+                return None;
+            }
+            Some((
+                RouteDistribution::FirstAvailable(primaries.into()),
+                RouteDistribution::FirstAvailable(replicas.into()),
+            ))
+        }
+        RouteDistribution::RandomAvailable(backends) => {
+            let (replicas, primaries): (Vec<_>, Vec<_>) =
+                backends.iter().cloned().partition(|(rb, _)| is_replica(rb));
+            if replicas.is_empty() || primaries.is_empty() {
+                return None;
+            }
+            Some((
+                RouteDistribution::RandomAvailable(primaries.into()),
+                RouteDistribution::RandomAvailable(replicas.into()),
+            ))
+        }
+    }
+}
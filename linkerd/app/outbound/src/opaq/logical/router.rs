@@ -27,12 +27,15 @@ where
 {
     pub fn layer<N, I, NSvc>(
         metrics: route::TcpRouteMetrics,
+        redis: route::redis::RedisParams,
+        postgres: route::postgres::PostgresParams,
     ) -> impl svc::Layer<N, Service = svc::ArcNewCloneTcp<Self, I>> + Clone
     where
-        I: io::AsyncRead + io::AsyncWrite + Debug + Send + Unpin + 'static,
+        I: io::AsyncRead + io::AsyncWrite + io::PeerAddr + Debug + Send + Unpin + 'static,
         // Concrete stack.
         N: svc::NewService<Concrete<T>, Service = NSvc> + Clone + Send + Sync + 'static,
-        NSvc: svc::Service<I, Response = ()> + Clone + Send + Sync + 'static,
+        NSvc: svc::Service<route::BackendIo<I>, Response = ()>,
+        NSvc: Clone + Send + Sync + 'static,
         NSvc::Future: Send,
         NSvc::Error: Into<Error>,
     {
@@ -42,7 +45,11 @@ where
                 // Each route builds over concrete backends. All of these
                 // backends are cached here and shared across routes.
                 .push(NewBackendCache::layer())
-                .push_on_service(route::MatchedRoute::layer(metrics.clone()))
+                .push_on_service(route::MatchedRoute::layer(
+                    metrics.clone(),
+                    redis.clone(),
+                    postgres.clone(),
+                ))
                 .push(svc::NewOneshotRoute::<Self, (), _>::layer_cached())
                 .arc_new_clone_tcp()
                 .into_inner()
@@ -61,86 +68,6 @@ where
             backends,
         } = rts;
 
-        let mk_concrete = {
-            let parent = parent.clone();
-            let logical = logical.clone();
-
-            move |backend_ref: BackendRef, target: concrete::Dispatch| Concrete {
-                target,
-                parent: parent.clone(),
-                backend_ref,
-                logical: logical.clone(),
-            }
-        };
-
-        let mk_dispatch = move |bke: &policy::Backend| match bke.dispatcher {
-            policy::BackendDispatcher::BalanceP2c(
-                ref load,
-                policy::EndpointDiscovery::DestinationGet { ref path },
-            ) => {
-                // Opaque traffic balances on round-trip time, since the
-                // penalty estimator needs HTTP classification that does not
-                // apply here. Read the RTT configuration from whichever estimator
-                // the policy chose and drop any penalty fields, warning the
-                // operator when the dropped configuration was non-trivial.
-                if let Some(p) = load.dropped_penalty() {
-                    tracing::warn!(
-                        penalty = ?p.penalty,
-                        penalty_decay = ?p.penalty_decay,
-                        max_retry_after = ?p.max_retry_after,
-                        "Opaque balancer ignores the response-penalty estimator; only round-trip time applies",
-                    );
-                }
-                let (decay, default_rtt) = load.peak_ewma_rtt();
-                mk_concrete(
-                    BackendRef(bke.meta.clone()),
-                    concrete::Dispatch::Balance(
-                        path.parse::<NameAddr>()
-                            .expect("destination must be a nameaddr"),
-                        http::balance::EwmaConfig { decay, default_rtt },
-                    ),
-                )
-            }
-            policy::BackendDispatcher::Forward(addr, ref md) => mk_concrete(
-                EndpointRef::new(md, addr.port().try_into().expect("port must not be 0")).into(),
-                concrete::Dispatch::Forward(Remote(ServerAddr(addr)), md.clone()),
-            ),
-            policy::BackendDispatcher::Fail { ref message } => mk_concrete(
-                BackendRef(policy::Meta::new_default("fail")),
-                concrete::Dispatch::Fail {
-                    message: message.clone(),
-                },
-            ),
-        };
-
-        let mk_route_backend =
-            |route_ref: &RouteRef, rb: &policy::RouteBackend<policy::opaq::Filter>| {
-                let concrete = mk_dispatch(&rb.backend);
-                route::Backend {
-                    route_ref: route_ref.clone(),
-                    filters: rb.filters.clone(),
-                    concrete,
-                }
-            };
-
-        let mk_distribution =
-            |rr: &RouteRef, d: &policy::RouteDistribution<policy::opaq::Filter>| match d {
-                policy::RouteDistribution::Empty => route::BackendDistribution::Empty,
-                policy::RouteDistribution::FirstAvailable(backends) => {
-                    route::BackendDistribution::first_available(
-                        backends.iter().map(|b| mk_route_backend(rr, b)),
-                    )
-                }
-                policy::RouteDistribution::RandomAvailable(backends) => {
-                    route::BackendDistribution::random_available(
-                        backends
-                            .iter()
-                            .map(|(rb, weight)| (mk_route_backend(rr, rb), *weight)),
-                    )
-                    .expect("distribution must be valid")
-                }
-            };
-
         let mk_policy = |policy::RoutePolicy::<policy::opaq::Filter, ()> {
                              meta,
                              distribution,
@@ -148,15 +75,14 @@ where
                              ..
                          }| {
             let route_ref = RouteRef(meta);
-            let logical = logical.clone();
-
-            let distribution = mk_distribution(&route_ref, &distribution);
+            let backends = mk_distribution(&parent, &logical, &route_ref, &distribution);
             route::Route {
-                logical,
+                logical: logical.clone(),
                 parent: parent.clone(),
                 route_ref,
                 filters,
-                distribution,
+                distribution: backends,
+                policy_distribution: distribution,
             }
         };
 
@@ -164,7 +90,10 @@ where
             policy: mk_policy(route.policy.clone()),
         });
 
-        let backends = backends.iter().map(mk_dispatch).collect();
+        let backends = backends
+            .iter()
+            .map(|bke| mk_concrete(&parent, &logical, bke))
+            .collect();
 
         Self {
             routes,
@@ -175,6 +104,89 @@ where
     }
 }
 
+/// Builds a route's distribution over concrete backends from its policy.
+pub(super) fn mk_distribution<T: Clone>(
+    parent: &T,
+    logical: &Logical,
+    route_ref: &RouteRef,
+    distribution: &policy::RouteDistribution<policy::opaq::Filter>,
+) -> route::BackendDistribution<T> {
+    let mk_route_backend = |rb: &policy::RouteBackend<policy::opaq::Filter>| route::Backend {
+        route_ref: route_ref.clone(),
+        filters: rb.filters.clone(),
+        concrete: mk_concrete(parent, logical, &rb.backend),
+    };
+
+    match distribution {
+        policy::RouteDistribution::Empty => route::BackendDistribution::Empty,
+        policy::RouteDistribution::FirstAvailable(backends) => {
+            route::BackendDistribution::first_available(backends.iter().map(mk_route_backend))
+        }
+        policy::RouteDistribution::RandomAvailable(backends) => {
+            route::BackendDistribution::random_available(
+                backends
+                    .iter()
+                    .map(|(rb, weight)| (mk_route_backend(rb), *weight)),
+            )
+            .expect("distribution must be valid")
+        }
+    }
+}
+
+fn mk_concrete<T: Clone>(parent: &T, logical: &Logical, bke: &policy::Backend) -> Concrete<T> {
+    let mk = |backend_ref: BackendRef, target: concrete::Dispatch| Concrete {
+        target,
+        parent: parent.clone(),
+        backend_ref,
+        logical: logical.clone(),
+    };
+
+    match bke.dispatcher {
+        policy::BackendDispatcher::BalanceP2c(ref load, ref discovery) => {
+            // Opaque traffic balances on round-trip time, since the
+            // penalty estimator needs HTTP classification that does not
+            // apply here. Read the RTT configuration from whichever estimator
+            // the policy chose and drop any penalty fields, warning the
+            // operator when the dropped configuration was non-trivial.
+            if let Some(p) = load.dropped_penalty() {
+                tracing::warn!(
+                    penalty = ?p.penalty,
+                    penalty_decay = ?p.penalty_decay,
+                    max_retry_after = ?p.max_retry_after,
+                    "Opaque balancer ignores the response-penalty estimator; only round-trip time applies",
+                );
+            }
+            let (decay, default_rtt) = load.peak_ewma_rtt();
+            let ewma = http::balance::EwmaConfig { decay, default_rtt };
+            mk(
+                BackendRef(bke.meta.clone()),
+                match discovery {
+                    policy::EndpointDiscovery::DestinationGet { ref path } => {
+                        concrete::Dispatch::Balance(
+                            path.parse::<NameAddr>()
+                                .expect("destination must be a nameaddr"),
+                            ewma,
+                        )
+                    }
+                    policy::EndpointDiscovery::Dns(ref dns) => {
+                        concrete::Dispatch::BalanceDns(crate::resolve::dns_target(dns), ewma)
+                    }
+                },
+            )
+        }
+        policy::BackendDispatcher::Forward(addr, ref md) => mk(
+            EndpointRef::new(md, addr.port().try_into().expect("port must not be 0")).into(),
+            concrete::Dispatch::Forward(Remote(ServerAddr(addr)), md.clone()),
+        ),
+        policy::BackendDispatcher::Fail { ref message } => mk(
+            BackendRef(policy::Meta::new_default("fail")),
+            concrete::Dispatch::Fail {
+                message: message.clone(),
+            },
+        ),
+    }
+}
+
 impl<T, I> svc::router::SelectRoute<I> for Router<T>
 where
     T: Clone + Eq + Hash + Debug,
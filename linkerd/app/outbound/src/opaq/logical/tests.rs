@@ -63,6 +63,364 @@ async fn forward() {
     assert!(resolved.only_configured(), "endpoint not discovered?");
 }
 
+/// Tests that connections to Redis ports are proxied command-by-command, so
+/// that denied commands are rejected without reaching the endpoint.
+#[tokio::test]
+async fn redis_denies_commands() {
+    let _trace = linkerd_tracing::test::trace_init();
+    time::pause();
+
+    let laddr = "xyz.example.com:4444".parse::<NameAddr>().unwrap();
+    let addr = Addr::Socket("1.2.3.4:444".parse().unwrap());
+
+    let (_tx, policy_rx) = watch::channel(default_service_policy(laddr.clone()));
+    let target = Target::new(policy_rx, None, addr);
+
+    let ep_addr = SocketAddr::new([192, 0, 2, 30].into(), 3333);
+    let resolve = support::resolver().endpoint_exists(laddr, ep_addr, Default::default());
+
+    let mut config = default_config();
+    config.redis_ports.insert(444..=444);
+    config.redis_deny_commands = Arc::new([Arc::from("FLUSHALL")]);
+
+    // Only the permitted command is sent to the endpoint.
+    let (rt, _shutdown) = runtime();
+    let stack = Outbound::new(config, rt, &mut Default::default())
+        .with_stack(svc::mk(move |_: concrete::Endpoint<Concrete<Target>>| {
+            let mut io = support::io();
+            io.write(b"PING\r\n").read(b"+PONG\r\n");
+            let local = Local(ClientAddr(([0, 0, 0, 0], 4444).into()));
+            future::ok::<_, support::io::Error>((io.build(), local))
+        }))
+        .push_opaq_concrete(resolve)
+        .push_opaq_logical()
+        .into_inner();
+
+    let mut io = support::io();
+    io.read(b"*1\r\n$8\r\nFLUSHALL\r\nPING\r\n")
+        .write(b"-NOPERM the 'flushall' command is not allowed by policy\r\n")
+        .write(b"+PONG\r\n");
+    stack
+        .new_service(target)
+        .oneshot(io.build())
+        .await
+        .expect("proxying must not fail");
+}
+
+/// Tests that read-only commands on Redis routes are sent to the backends of
+/// services configured as read replicas, and other commands to the route's
+/// remaining backends.
+#[tokio::test]
+async fn redis_splits_reads_to_replicas() {
+    const SET: &[u8] = b"*3\r\n$3\r\nSET\r\n$1\r\nk\r\n$1\r\nv\r\n";
+    const GET: &[u8] = b"*2\r\n$3\r\nGET\r\n$1\r\nk\r\n";
+
+    let _trace = linkerd_tracing::test::trace_init();
+    time::pause();
+
+    let primary_addr = SocketAddr::new([192, 0, 2, 30].into(), 6379);
+    let replica_addr = SocketAddr::new([192, 0, 2, 31].into(), 6379);
+    let (_tx, policy_rx) = watch::channel(redis_policy(&[
+        ("cache", primary_addr),
+        ("cache-replica", replica_addr),
+    ]));
+    let target = Target::new(
+        policy_rx,
+        None,
+        Addr::Socket("1.2.3.4:6379".parse().unwrap()),
+    );
+
+    let mut config = default_config();
+    config.redis_ports.insert(6379..=6379);
+    config.redis_read_replicas = Arc::new([Arc::from("cache-replica.ns")]);
+
+    let (rt, _shutdown) = runtime();
+    let stack = Outbound::new(config, rt, &mut Default::default())
+        .with_stack(svc::mk(move |ep: concrete::Endpoint<Concrete<Target>>| {
+            let Remote(ServerAddr(ea)) = svc::Param::param(&ep);
+            let mut io = support::io();
+            if ea == replica_addr {
+                io.write(GET).read(b"$1\r\nv\r\n");
+            } else {
+                assert_eq!(ea, primary_addr, "unexpected endpoint");
+                io.write(SET).read(b"+OK\r\n");
+            }
+            let local = Local(ClientAddr(([0, 0, 0, 0], 4444).into()));
+            future::ok::<_, support::io::Error>((io.build(), local))
+        }))
+        .push_opaq_concrete(support::resolver())
+        .push_opaq_logical()
+        .into_inner();
+
+    let mut io = support::io();
+    io.read(SET)
+        .write(b"+OK\r\n")
+        .read(GET)
+        .write(b"$1\r\nv\r\n");
+    stack
+        .new_service(target)
+        .oneshot(io.build())
+        .await
+        .expect("proxying must not fail");
+}
+
+/// Tests that Redis routes whose backends are all read replicas are not
+/// split, so that commands that are not read-only are still sent to them.
+#[tokio::test]
+async fn redis_does_not_split_replica_only_routes() {
+    const SET: &[u8] = b"*3\r\n$3\r\nSET\r\n$1\r\nk\r\n$1\r\nv\r\n";
+    const GET: &[u8] = b"*2\r\n$3\r\nGET\r\n$1\r\nk\r\n";
+
+    let _trace = linkerd_tracing::test::trace_init();
+    time::pause();
+
+    let replica_addr = SocketAddr::new([192, 0, 2, 31].into(), 6379);
+    let (_tx, policy_rx) = watch::channel(redis_policy(&[("cache-replica", replica_addr)]));
+    let target = Target::new(
+        policy_rx,
+        None,
+        Addr::Socket("1.2.3.4:6379".parse().unwrap()),
+    );
+
+    let mut config = default_config();
+    config.redis_ports.insert(6379..=6379);
+    config.redis_read_replicas = Arc::new([Arc::from("cache-replica.ns")]);
+
+    let (rt, _shutdown) = runtime();
+    let stack = Outbound::new(config, rt, &mut Default::default())
+        .with_stack(svc::mk(move |ep: concrete::Endpoint<Concrete<Target>>| {
+            let Remote(ServerAddr(ea)) = svc::Param::param(&ep);
+            assert_eq!(ea, replica_addr, "unexpected endpoint");
+            let mut io = support::io();
+            io.write(SET)
+                .read(b"+OK\r\n")
+                .write(GET)
+                .read(b"$1\r\nv\r\n");
+            let local = Local(ClientAddr(([0, 0, 0, 0], 4444).into()));
+            future::ok::<_, support::io::Error>((io.build(), local))
+        }))
+        .push_opaq_concrete(support::resolver())
+        .push_opaq_logical()
+        .into_inner();
+
+    let mut io = support::io();
+    io.read(SET)
+        .write(b"+OK\r\n")
+        .read(GET)
+        .write(b"$1\r\nv\r\n");
+    stack
+        .new_service(target)
+        .oneshot(io.build())
+        .await
+        .expect("proxying must not fail");
+}
+
+/// Tests that endpoints whose Postgres servers end sessions because they are
+/// unable to serve them are ejected by the opaque circuit breaker.
+#[tokio::test]
+async fn postgres_classifies_server_failures() {
+    const STARTUP: &[u8] = b"\0\0\0\x24\0\x03\0\0user\0alice\0database\0orders\0\0";
+    const TOO_MANY_CLIENTS: &[u8] = b"E\0\0\0\x1aSFATAL\0VFATAL\0C53300\0\0";
+
+    let _trace = linkerd_tracing::test::trace_init();
+    time::pause();
+
+    let laddr = "xyz.example.com:4444".parse::<NameAddr>().unwrap();
+    let addr = Addr::Socket("1.2.3.4:444".parse().unwrap());
+
+    let (_tx, policy_rx) = watch::channel(default_service_policy(laddr.clone()));
+    let target = Target::new(policy_rx, None, addr);
+
+    let ep_addr = SocketAddr::new([192, 0, 2, 30].into(), 3333);
+    let resolve = support::resolver().endpoint_exists(laddr, ep_addr, Default::default());
+
+    let mut config = default_config();
+    config.postgres_ports.insert(444..=444);
+    config.postgres_failure_accrual = Some(policy::FailureAccrual::Consecutive(
+        policy::ConsecutiveFailures {
+            max_failures: 1,
+            backoff: linkerd_app_core::exp_backoff::ExponentialBackoff::try_new(
+                time::Duration::from_secs(60),
+                time::Duration::from_secs(120),
+                0.0,
+            )
+            .unwrap(),
+        },
+    ));
+
+    let (rt, _shutdown) = runtime();
+    let stack = Outbound::new(config, rt, &mut Default::default())
+        .with_stack(svc::mk(move |_: concrete::Endpoint<Concrete<Target>>| {
+            let mut io = support::io();
+            io.write(STARTUP).read(TOO_MANY_CLIENTS);
+            let local = Local(ClientAddr(([0, 0, 0, 0], 4444).into()));
+            future::ok::<_, support::io::Error>((io.build(), local))
+        }))
+        .push_opaq_concrete(resolve)
+        .push_opaq_logical()
+        .into_inner();
+    let svc = stack.new_service(target);
+
+    // The session is proxied, and the server's error is not the client's.
+    let mut io = support::io();
+    io.read(STARTUP).write(TOO_MANY_CLIENTS);
+    svc.clone()
+        .oneshot(io.build())
+        .await
+        .expect("proxying must not fail");
+
+    // The failed session trips the endpoint's breaker, so the balancer has no
+    // available endpoints.
+    tokio::task::yield_now().await;
+    let (io, task) = spawn_io();
+    let err = svc
+        .oneshot(io)
+        .await
+        .expect_err("a tripped endpoint must not be used");
+    task.abort();
+    assert!(
+        errors::is_caused_by::<FailFastError>(&*err),
+        "unexpected error: {err}"
+    );
+}
+
+/// Tests that the endpoints of opaque routes that are not proxied as Postgres
+/// are not ejected, even when a failure accrual policy is configured.
+#[tokio::test]
+async fn opaque_endpoints_are_not_ejected() {
+    let _trace = linkerd_tracing::test::trace_init();
+    time::pause();
+
+    let laddr = "xyz.example.com:4444".parse::<NameAddr>().unwrap();
+    let addr = Addr::Socket("1.2.3.4:444".parse().unwrap());
+
+    let (_tx, policy_rx) = watch::channel(default_service_policy(laddr.clone()));
+    let target = Target::new(policy_rx, None, addr);
+
+    let ep_addr = SocketAddr::new([192, 0, 2, 30].into(), 3333);
+    let resolve = support::resolver().endpoint_exists(laddr, ep_addr, Default::default());
+
+    let mut config = default_config();
+    config.postgres_failure_accrual = Some(policy::FailureAccrual::Consecutive(
+        policy::ConsecutiveFailures {
+            max_failures: 1,
+            backoff: linkerd_app_core::exp_backoff::ExponentialBackoff::try_new(
+                time::Duration::from_secs(60),
+                time::Duration::from_secs(120),
+                0.0,
+            )
+            .unwrap(),
+        },
+    ));
+
+    // The first connection to the endpoint fails.
+    let connects = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let (rt, _shutdown) = runtime();
+    let stack = Outbound::new(config, rt, &mut Default::default())
+        .with_stack(svc::mk(move |_: concrete::Endpoint<Concrete<Target>>| {
+            if connects.fetch_add(1, std::sync::atomic::Ordering::SeqCst) == 0 {
+                return future::err(support::io::Error::new(
+                    support::io::ErrorKind::ConnectionRefused,
+                    "refused",
+                ));
+            }
+            let mut io = support::io();
+            io.write(b"hola").read(b"mundo");
+            let local = Local(ClientAddr(([0, 0, 0, 0], 4444).into()));
+            future::ok::<_, support::io::Error>((io.build(), local))
+        }))
+        .push_opaq_concrete(resolve)
+        .push_opaq_logical()
+        .into_inner();
+    let svc = stack.new_service(target);
+
+    let (io, task) = spawn_io();
+    svc.clone()
+        .oneshot(io)
+        .await
+        .expect_err("the first connection must fail");
+    task.abort();
+
+    // The endpoint remains available after the failure.
+    tokio::task::yield_now().await;
+    let mut io = support::io();
+    io.read(b"hola").write(b"mundo");
+    svc.oneshot(io.build())
+        .await
+        .expect("forwarding must not fail");
+}
+
+/// Tests that backends configured with DNS discovery use endpoints resolved
+/// through DNS rather than the destination controller.
+#[tokio::test]
+async fn dns_discovery() {
+    let _trace = linkerd_tracing::test::trace_init();
+
+    let addr = Addr::Socket("1.2.3.4:444".parse().unwrap());
+    let (_tx, policy_rx) = watch::channel(service_policy(policy::EndpointDiscovery::Dns(
+        policy::DnsDiscovery {
+            authority: "db.example.com:3333".parse().unwrap(),
+            records: policy::DnsRecords::Addrs,
+        },
+    )));
+    let target = Target::new(policy_rx, None, addr);
+
+    // The destination controller is never consulted.
+    let resolve = support::resolver();
+    let resolved = resolve.handle();
+
+    let ep_addr = SocketAddr::new([192, 0, 2, 40].into(), 3333);
+    let (rt, _shutdown) = runtime();
+    let stack = Outbound::new(default_config(), rt, &mut Default::default())
+        .with_dns(MockDns(ep_addr))
+        .with_stack(svc::mk(move |ep: concrete::Endpoint<Concrete<Target>>| {
+            let Remote(ServerAddr(ea)) = svc::Param::param(&ep);
+            assert_eq!(ea, ep_addr, "unexpected endpoint");
+            let mut io = support::io();
+            io.write(b"hola").read(b"mundo");
+            let local = Local(ClientAddr(([0, 0, 0, 0], 4444).into()));
+            future::ok::<_, support::io::Error>((io.build(), local))
+        }))
+        .push_opaq_concrete(resolve)
+        .push_opaq_logical()
+        .into_inner();
+
+    let mut io = support::io();
+    io.read(b"hola").write(b"mundo");
+    stack
+        .new_service(target)
+        .oneshot(io.build())
+        .await
+        .expect("forwarding must not fail");
+    assert!(
+        resolved.only_configured(),
+        "destination must not be resolved"
+    );
+}
+
+/// Resolves `db.example.com` to a single endpoint.
+#[derive(Clone)]
+struct MockDns(SocketAddr);
+
+impl linkerd_app_core::proxy::dns_resolve::Lookup for MockDns {
+    type Future =
+        future::Ready<Result<(Vec<(SocketAddr, u32)>, time::Instant), linkerd_app_core::Error>>;
+
+    fn lookup(
+        &self,
+        addr: &NameAddr,
+        records: linkerd_app_core::proxy::dns_resolve::Records,
+    ) -> Self::Future {
+        assert_eq!(addr.name().as_str(), "db.example.com");
+        assert_eq!(
+            records,
+            linkerd_app_core::proxy::dns_resolve::Records::Addrs
+        );
+        let expiry = time::Instant::now() + time::Duration::from_secs(60);
+        future::ok((vec![(self.0, 1)], expiry))
+    }
+}
+
 /// Tests that the logical stack forwards connections to services with an arbitrary number of
 /// endpoints.
 ///
@@ -209,7 +567,56 @@ fn spawn_io() -> (
     (server_io, task)
 }
 
+/// Builds a policy whose opaque route forwards to a `Service` backend in the
+/// `ns` namespace for each name and address.
+fn redis_policy(backends: &[(&str, SocketAddr)]) -> policy::ClientPolicy {
+    let backends = backends
+        .iter()
+        .map(|(name, addr)| policy::RouteBackend {
+            backend: policy::Backend {
+                meta: Arc::new(policy::Meta::Resource {
+                    group: "core".to_string(),
+                    kind: "Service".to_string(),
+                    name: name.to_string(),
+                    namespace: "ns".to_string(),
+                    section: None,
+                    port: None,
+                }),
+                queue: policy::Queue {
+                    capacity: 100,
+                    failfast_timeout: std::time::Duration::from_secs(3),
+                },
+                dispatcher: policy::BackendDispatcher::Forward(*addr, Default::default()),
+            },
+            filters: Arc::new([]),
+        })
+        .collect::<Vec<_>>();
+    let meta = policy::Meta::new_default("test");
+    policy::ClientPolicy {
+        parent: meta.clone(),
+        protocol: policy::Protocol::Opaque(policy::opaq::Opaque {
+            routes: Some(policy::opaq::Route {
+                policy: policy::opaq::Policy {
+                    distribution: policy::RouteDistribution::FirstAvailable(
+                        backends.clone().into(),
+                    ),
+                    filters: Arc::new([]),
+                    meta,
+                    params: (),
+                },
+            }),
+        }),
+        backends: backends.into_iter().map(|rb| rb.backend).collect(),
+    }
+}
+
 fn default_service_policy(addr: NameAddr) -> policy::ClientPolicy {
+    service_policy(policy::EndpointDiscovery::DestinationGet {
+        path: addr.to_string(),
+    })
+}
+
+fn service_policy(discovery: policy::EndpointDiscovery) -> policy::ClientPolicy {
     let meta = policy::Meta::new_default("test");
     let queue = {
         policy::Queue {
@@ -226,12 +633,7 @@ fn default_service_policy(addr: NameAddr) -> policy::ClientPolicy {
     let backend = policy::Backend {
         meta: meta.clone(),
         queue,
-        dispatcher: policy::BackendDispatcher::BalanceP2c(
-            load,
-            policy::EndpointDiscovery::DestinationGet {
-                path: addr.to_string(),
-            },
-        ),
+        dispatcher: policy::BackendDispatcher::BalanceP2c(load, discovery),
     };
 
     let opaque = policy::opaq::Opaque {
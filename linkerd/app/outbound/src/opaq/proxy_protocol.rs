@@ -0,0 +1,237 @@
+use futures::prelude::*;
+use linkerd_app_core::{
+    io::{self, AsyncWriteExt},
+    svc, tls,
+    transport::{addrs::*, proxy_protocol},
+    Error, Result,
+};
+use linkerd_duplex::Duplex;
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tracing::debug;
+
+/// The address of the client whose connection is being forwarded, if it is
+/// known.
+#[derive(Copy, Clone, Debug)]
+pub(super) struct ClientPeer(Option<ClientAddr>);
+
+/// Forwards client connections to endpoints, connecting with the client's
+/// address so that it may be conveyed to the endpoint.
+#[derive(Clone, Debug)]
+pub(super) struct Forward<C> {
+    connect: C,
+}
+
+/// Builds endpoint connectors that prefix connections to endpoints outside of
+/// the mesh with a PROXY protocol header, so that these endpoints may observe
+/// the address of the original client.
+///
+/// Meshed endpoints learn the client's identity through mTLS, so headers are
+/// only sent to endpoints that discovery did not provide an identity for. This
+/// applies to balanced endpoints as well as forwarded ones.
+#[derive(Clone, Debug)]
+pub(super) struct NewEmitProxyProtocol<C> {
+    connect: C,
+    enabled: bool,
+}
+
+#[derive(Clone, Debug)]
+pub(super) struct EmitProxyProtocol<C, T> {
+    connect: C,
+    target: T,
+    server: Option<Remote<ServerAddr>>,
+}
+
+// === impl Forward ===
+
+impl<C> Forward<C> {
+    pub(super) fn layer() -> impl svc::layer::Layer<C, Service = Self> + Copy {
+        svc::layer::mk(|connect| Self { connect })
+    }
+}
+
+impl<C, I> svc::Service<I> for Forward<C>
+where
+    I: io::AsyncRead + io::AsyncWrite + io::PeerAddr + Send + Unpin + 'static,
+    C: svc::Service<ClientPeer> + Send + 'static,
+    C::Error: Into<Error>,
+    C::Future: Send + 'static,
+    C::Response: io::AsyncRead + io::AsyncWrite + Send + Unpin + 'static,
+{
+    type Response = ();
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<()>> + Send + 'static>>;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.connect.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, src_io: I) -> Self::Future {
+        let client = src_io
+            .peer_addr()
+            .map_err(|error| debug!(%error, "Unable to determine client address"))
+            .ok()
+            .map(ClientAddr);
+        Box::pin(
+            self.connect
+                .call(ClientPeer(client))
+                .err_into::<Error>()
+                .and_then(|dst_io| Duplex::new(src_io, dst_io).err_into::<Error>()),
+        )
+    }
+}
+
+// === impl NewEmitProxyProtocol ===
+
+impl<C> NewEmitProxyProtocol<C> {
+    pub(super) fn layer(enabled: bool) -> impl svc::layer::Layer<C, Service = Self> + Clone {
+        svc::layer::mk(move |connect| Self { connect, enabled })
+    }
+}
+
+impl<T, C> svc::NewService<T> for NewEmitProxyProtocol<C>
+where
+    T: svc::Param<Remote<ServerAddr>>,
+    T: svc::Param<tls::ConditionalClientTls>,
+    C: Clone,
+{
+    type Service = EmitProxyProtocol<C, T>;
+
+    fn new_service(&self, target: T) -> Self::Service {
+        let unmeshed = matches!(
+            target.param(),
+            tls::ConditionalClientTls::None(tls::NoClientTls::NotProvidedByServiceDiscovery)
+        );
+        let server = if self.enabled && unmeshed {
+            Some(target.param())
+        } else {
+            None
+        };
+        EmitProxyProtocol {
+            connect: self.connect.clone(),
+            target,
+            server,
+        }
+    }
+}
+
+// === impl EmitProxyProtocol ===
+
+impl<T, C> svc::Service<ClientPeer> for EmitProxyProtocol<C, T>
+where
+    T: Clone,
+    C: svc::Service<T>,
+    C::Error: Into<Error>,
+    C::Future: Send + 'static,
+    C::Response: io::AsyncWrite + Send + Unpin + 'static,
+{
+    type Response = C::Response;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<C::Response>> + Send + 'static>>;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.connect.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, ClientPeer(client): ClientPeer) -> Self::Future {
+        let header = match (self.server, client) {
+            (Some(Remote(server)), Some(client)) => {
+                Some(proxy_protocol::encode_v2(AddrPair(client, server)))
+            }
+            (Some(_), None) => {
+                debug!("Client address unknown; not sending a PROXY header");
+                None
+            }
+            (None, _) => None,
+        };
+        let connect = self.connect.call(self.target.clone()).err_into::<Error>();
+        Box::pin(async move {
+            let mut io = connect.await?;
+            if let Some(header) = header {
+                io.write_all(&header).await?;
+            }
+            Ok(io)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use linkerd_app_core::svc::{layer::Layer, ServiceExt};
+
+    #[derive(Clone, Debug)]
+    struct Endpoint(tls::ConditionalClientTls);
+
+    impl svc::Param<Remote<ServerAddr>> for Endpoint {
+        fn param(&self) -> Remote<ServerAddr> {
+            Remote(ServerAddr(([192, 0, 2, 2], 443).into()))
+        }
+    }
+
+    impl svc::Param<tls::ConditionalClientTls> for Endpoint {
+        fn param(&self) -> tls::ConditionalClientTls {
+            self.0.clone()
+        }
+    }
+
+    /// Connects to an endpoint, returning the bytes written to it.
+    async fn connect(enabled: bool, endpoint: Endpoint, client: Option<ClientAddr>) -> Vec<u8> {
+        let (dst, mut rx) = io::duplex(1024);
+        let dst = std::sync::Arc::new(parking_lot::Mutex::new(Some(dst)));
+        let connect = svc::mk(move |_: Endpoint| {
+            let dst = dst.lock().take().expect("must connect once");
+            future::ready(Ok::<_, io::Error>(dst))
+        });
+        let new_emit = NewEmitProxyProtocol::layer(enabled).layer(connect);
+        let io = svc::NewService::new_service(&new_emit, endpoint)
+            .oneshot(ClientPeer(client))
+            .await
+            .expect("connect must succeed");
+        drop(io);
+
+        let mut buf = Vec::new();
+        io::AsyncReadExt::read_to_end(&mut rx, &mut buf)
+            .await
+            .unwrap();
+        buf
+    }
+
+    fn unmeshed() -> Endpoint {
+        Endpoint(tls::ConditionalClientTls::None(
+            tls::NoClientTls::NotProvidedByServiceDiscovery,
+        ))
+    }
+
+    #[tokio::test]
+    async fn emits_header_to_unmeshed_endpoints() {
+        let client = ClientAddr(([192, 0, 2, 1], 56324).into());
+        let written = connect(true, unmeshed(), Some(client)).await;
+        let header = proxy_protocol::parse(&written)
+            .expect("header must parse")
+            .expect("header must be complete");
+        assert_eq!(header.len, written.len());
+        assert_eq!(
+            header.addrs,
+            Some(AddrPair(client, ServerAddr(([192, 0, 2, 2], 443).into())))
+        );
+    }
+
+    #[tokio::test]
+    async fn skips_header_when_disabled_or_unknown_client() {
+        let client = ClientAddr(([192, 0, 2, 1], 56324).into());
+        assert!(connect(false, unmeshed(), Some(client)).await.is_empty());
+        assert!(connect(true, unmeshed(), None).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn skips_header_for_local_endpoints() {
+        let client = ClientAddr(([192, 0, 2, 1], 56324).into());
+        let local = Endpoint(tls::ConditionalClientTls::None(tls::NoClientTls::Loopback));
+        assert!(connect(true, local, Some(client)).await.is_empty());
+    }
+}
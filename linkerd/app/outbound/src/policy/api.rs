@@ -10,7 +10,8 @@ use linkerd_app_core::{
 };
 use linkerd_proxy_client_policy::{ClientPolicy, ClientPolicyOverrides};
 use linkerd_tonic_stream::{LimitReceiveFuture, ReceiveLimits};
-use linkerd_tonic_watch::StreamWatch;
+use linkerd_tonic_watch::{Snapshots, StreamWatch};
+use rangemap::RangeInclusiveSet;
 use std::sync::Arc;
 use tokio::time;
 
@@ -19,7 +20,9 @@ pub(crate) struct Api<S> {
     workload: Arc<str>,
     limits: ReceiveLimits,
     default_detect_timeout: time::Duration,
-    export_hostname_labels: bool,
+    overrides: ClientPolicyOverrides,
+    compression_ports: Arc<RangeInclusiveSet<u16>>,
+    snapshots: Snapshots<Addr, ClientPolicy>,
     client: Client<S>,
 }
 
@@ -40,14 +43,18 @@ where
         workload: Arc<str>,
         limits: ReceiveLimits,
         default_detect_timeout: time::Duration,
-        export_hostname_labels: bool,
+        overrides: ClientPolicyOverrides,
+        compression_ports: RangeInclusiveSet<u16>,
+        snapshots: Snapshots<Addr, ClientPolicy>,
         client: S,
     ) -> Self {
         Self {
             workload,
             limits,
             default_detect_timeout,
-            export_hostname_labels,
+            overrides,
+            compression_ports: Arc::new(compression_ports),
+            snapshots,
             client: Client::new(client),
         }
     }
@@ -90,28 +97,36 @@ where
 
         let detect_timeout = self.default_detect_timeout;
         let overrides = ClientPolicyOverrides {
-            export_hostname_labels: self.export_hostname_labels,
+            // Routes are only compressed on the ports that enable it.
+            compression: self
+                .overrides
+                .compression
+                .clone()
+                .filter(|_| self.compression_ports.contains(&addr.port())),
+            ..self.overrides.clone()
         };
         let limits = self.limits;
+        let snapshots = self.snapshots.clone();
         let mut client = self.client.clone();
         Box::pin(async move {
             let rsp =
                 LimitReceiveFuture::new(limits, client.watch(tonic::Request::new(req))).await?;
             Ok(rsp.map(move |s| {
-                s.map_ok(move |up| {
+                let policies = s.map_ok(move |up| {
                     // If the server returned an invalid client policy, we
                     // default to using an invalid policy that causes all
                     // requests to report an internal error.
-                    let policy = ClientPolicy::try_from(overrides, up).unwrap_or_else(|error| {
-                        tracing::warn!(%error, "Client policy misconfigured");
-                        INVALID_POLICY
-                            .get_or_init(|| ClientPolicy::invalid(detect_timeout))
-                            .clone()
-                    });
+                    let policy =
+                        ClientPolicy::try_from(overrides.clone(), up).unwrap_or_else(|error| {
+                            tracing::warn!(%error, "Client policy misconfigured");
+                            INVALID_POLICY
+                                .get_or_init(|| ClientPolicy::invalid(detect_timeout))
+                                .clone()
+                        });
                     tracing::debug!(?policy);
                     policy
-                })
-                .boxed()
+                });
+                snapshots.observe(addr, policies.boxed())
             }))
         })
     }
@@ -1,5 +1,5 @@
 use crate::{http, Outbound, ParentRef};
-use linkerd_app_core::{io, svc, Error, Infallible};
+use linkerd_app_core::{io, svc, transport::connections, Error, Infallible};
 use std::{fmt::Debug, hash::Hash};
 
 mod metrics;
@@ -53,12 +53,22 @@ impl<N> Outbound<N> {
         NSvc: Clone + Send + Sync + Unpin + 'static,
         NSvc::Future: Send,
     {
-        let opaq = self.clone().into_stack();
+        let opaq = self.clone().map_stack(|_, rt, opaq| {
+            opaq.push(connections::NewRecord::layer_via(
+                rt.connections.clone(),
+                |_: &T| connections::Negotiated::outbound(connections::Protocol::Opaque),
+            ))
+        });
+        let opaq = opaq.into_stack();
 
         let http = self.with_stack(http).map_stack(|config, rt, stk| {
             let h2 = config.proxy.server.http2.clone();
             let drain = rt.drain.clone();
             stk.unlift_new()
+                .push_on_service(connections::NewCountStreams::layer(
+                    rt.connections.clone(),
+                    connections::Direction::Out,
+                ))
                 .push(http::NewServeHttp::layer(move |t: &Http<T>| {
                     http::ServerParams {
                         version: t.version,
@@ -66,24 +76,30 @@ impl<N> Outbound<N> {
                         drain: drain.clone(),
                     }
                 }))
+                .push(connections::NewRecord::layer(rt.connections.clone()))
                 .arc_new_tcp()
         });
 
         let detect = http.clone().map_stack(|config, rt, http| {
             let read_timeout = config.proxy.detect_protocol_timeout;
             let metrics = rt.metrics.prom.http_detect.clone();
+            let opaq_metrics = rt.metrics.prom.opaq.clone();
+            let sniffers = http::Sniffers::default();
 
             http.push_switch(
-                |(detected, parent): (http::Detection, T)| -> Result<_, Infallible> {
-                    match detected {
+                move |(detected, parent): (http::Detection, T)| -> Result<_, Infallible> {
+                    let protocol = match detected {
                         http::Detection::Http(version) => {
                             return Ok(svc::Either::Left(Http { version, parent }));
                         }
                         http::Detection::ReadTimeout(timeout) => {
                             tracing::info!("Continuing after timeout: {timeout:?}");
+                            None
                         }
-                        _ => {}
-                    }
+                        http::Detection::Opaque(protocol) => Some(protocol),
+                        http::Detection::NotHttp => None,
+                    };
+                    opaq_metrics.record_detected(parent.param(), protocol);
                     Ok(svc::Either::Right(parent))
                 },
                 opaq.clone().into_inner(),
@@ -98,6 +114,8 @@ impl<N> Outbound<N> {
                 http::DetectParams {
                     read_timeout,
                     metrics: metrics.metrics(parent.param()),
+                    sniffers: sniffers.clone(),
+                    server_first_timeout: None,
                 }
             }))
             .arc_new_tcp()
@@ -106,10 +124,16 @@ impl<N> Outbound<N> {
         http.map_stack(|_, rt, http| {
             // First separate traffic that needs protocol detection. Then switch
             // between traffic that is known to be HTTP or opaque.
+            // TLS connections are routed by SNI, but are otherwise opaque to
+            // the proxy.
+            let tls = svc::stack(tls.clone()).push(connections::NewRecord::layer_via(
+                rt.connections.clone(),
+                |_: &T| connections::Negotiated::outbound(connections::Protocol::Opaque),
+            ));
             let known = http.push_switch(
                 Ok::<_, Infallible>,
                 opaq.clone()
-                    .push_switch(Ok::<_, Infallible>, tls.clone())
+                    .push_switch(Ok::<_, Infallible>, tls.into_inner())
                     .into_inner(),
             );
 
@@ -157,6 +181,12 @@ impl<T> svc::Param<http::Variant> for Http<T> {
     }
 }
 
+impl<T> svc::Param<connections::Negotiated> for Http<T> {
+    fn param(&self) -> connections::Negotiated {
+        connections::Negotiated::outbound(self.version.into())
+    }
+}
+
 impl<T> svc::Param<http::normalize_uri::DefaultAuthority> for Http<T>
 where
     T: svc::Param<http::normalize_uri::DefaultAuthority>,
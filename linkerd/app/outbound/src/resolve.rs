@@ -0,0 +1,129 @@
+//! Resolves the endpoints of a balanced backend, either through the destination
+//! controller or through DNS.
+
+use futures::prelude::*;
+use linkerd_app_core::{
+    proxy::{
+        api_resolve::{ConcreteAddr, Metadata},
+        core::{Resolve, Update},
+        dns_resolve::{self, BoxLookup, DnsDiscover},
+    },
+    svc, Error, NameAddr,
+};
+use linkerd_proxy_client_policy as policy;
+use std::{
+    net::SocketAddr,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// Describes how a balancer discovers its endpoints.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum Discover {
+    Destination(ConcreteAddr),
+    Dns(dns_resolve::Target),
+}
+
+/// Resolves [`Discover`] targets through the destination controller or DNS.
+#[derive(Clone, Debug)]
+pub(crate) struct BalanceResolve<R> {
+    dst: R,
+    dns: Option<DnsDiscover<BoxLookup>>,
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("DNS endpoint discovery is not enabled")]
+pub(crate) struct DnsDiscoveryDisabled(());
+
+pub(crate) type Resolution =
+    Pin<Box<dyn Stream<Item = Result<Update<Metadata>, Error>> + Send + 'static>>;
+
+// === impl Discover ===
+
+impl Discover {
+    pub(crate) fn new(addr: NameAddr, dns: Option<dns_resolve::Target>) -> Self {
+        match dns {
+            Some(dns) => Self::Dns(dns),
+            None => Self::Destination(ConcreteAddr(addr)),
+        }
+    }
+}
+
+/// Builds a DNS discovery target from a backend's policy.
+pub(crate) fn dns_target(dns: &policy::DnsDiscovery) -> dns_resolve::Target {
+    dns_resolve::Target {
+        addr: dns.authority.clone(),
+        records: match dns.records {
+            policy::DnsRecords::Addrs => dns_resolve::Records::Addrs,
+            policy::DnsRecords::Srv => dns_resolve::Records::Srv,
+        },
+    }
+}
+
+// === impl BalanceResolve ===
+
+impl<R> BalanceResolve<R> {
+    pub(crate) fn new(dst: R, dns: Option<DnsDiscover<BoxLookup>>) -> Self {
+        Self { dst, dns }
+    }
+}
+
+impl<R> svc::Service<Discover> for BalanceResolve<R>
+where
+    R: Resolve<ConcreteAddr, Endpoint = Metadata, Error = Error>,
+{
+    type Response = Resolution;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Resolution, Error>> + Send + 'static>>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, target: Discover) -> Self::Future {
+        match target {
+            Discover::Destination(addr) => Box::pin(
+                self.dst
+                    .resolve(addr)
+                    .map_ok(|res| Box::pin(res) as Resolution),
+            ),
+            Discover::Dns(target) => {
+                let Some(mut dns) = self.dns.clone() else {
+                    return Box::pin(future::err::<Resolution, Error>(
+                        DnsDiscoveryDisabled(()).into(),
+                    ));
+                };
+                let records = target.records;
+                Box::pin(svc::Service::call(&mut dns, target).map_ok(move |updates| {
+                    match records {
+                        // Addresses of a name are paired across families so
+                        // that dual-stack endpoints are connected to with
+                        // Happy Eyeballs rather than balanced over twice.
+                        dns_resolve::Records::Addrs => Box::pin(
+                            dns_resolve::pair_updates(updates)
+                                .map_ok(|update| map_endpoints(update, dual_stack_metadata)),
+                        ) as Resolution,
+                        dns_resolve::Records::Srv => Box::pin(
+                            updates.map_ok(|update| map_endpoints(update, Metadata::from_weight)),
+                        ),
+                    }
+                }))
+            }
+        }
+    }
+}
+
+fn map_endpoints<T>(update: Update<T>, f: impl Fn(T) -> Metadata) -> Update<Metadata> {
+    let with_metadata =
+        |eps: Vec<(SocketAddr, T)>| eps.into_iter().map(|(addr, ep)| (addr, f(ep))).collect();
+    match update {
+        Update::Reset(eps) => Update::Reset(with_metadata(eps)),
+        Update::Add(eps) => Update::Add(with_metadata(eps)),
+        Update::Remove(addrs) => Update::Remove(addrs),
+        Update::DoesNotExist => Update::DoesNotExist,
+    }
+}
+
+fn dual_stack_metadata(ep: dns_resolve::DualStack<u32>) -> Metadata {
+    Metadata::from_weight(ep.endpoint).with_dual_stack_addr(ep.other_family)
+}
@@ -231,26 +231,49 @@ impl HttpSidecar {
         // protocol changes but remains HTTP-ish, we propagate those
         // changes. If the protocol flips to an opaque protocol, we ignore
         // the protocol update.
-        let (routes, failure_accrual) = match policy.protocol {
+        let (routes, failure_accrual, client_params, pool_limits) = match policy.protocol {
             policy::Protocol::Detect {
                 ref http1,
                 ref http2,
                 ..
             } => match version {
-                http::Variant::Http1 => (http1.routes.clone(), http1.failure_accrual),
-                http::Variant::H2 => (http2.routes.clone(), http2.failure_accrual),
+                http::Variant::Http1 => (
+                    http1.routes.clone(),
+                    http1.failure_accrual,
+                    Default::default(),
+                    http1.pool_limits,
+                ),
+                http::Variant::H2 => (
+                    http2.routes.clone(),
+                    http2.failure_accrual,
+                    http2.client_params.clone(),
+                    Default::default(),
+                ),
             },
             policy::Protocol::Http1(policy::http::Http1 {
                 ref routes,
                 failure_accrual,
-            }) => (routes.clone(), failure_accrual),
+                pool_limits,
+            }) => (
+                routes.clone(),
+                failure_accrual,
+                Default::default(),
+                pool_limits,
+            ),
             policy::Protocol::Http2(policy::http::Http2 {
                 ref routes,
                 failure_accrual,
-            }) => (routes.clone(), failure_accrual),
+                ref client_params,
+            }) => (
+                routes.clone(),
+                failure_accrual,
+                client_params.clone(),
+                Default::default(),
+            ),
             policy::Protocol::Grpc(policy::grpc::Grpc {
                 ref routes,
                 failure_accrual,
+                ref client_params,
             }) => {
                 return Some(http::Routes::Policy(http::policy::Params::Grpc(
                     http::policy::GrpcParams {
@@ -259,6 +282,8 @@ impl HttpSidecar {
                         backends: policy.backends.clone(),
                         routes: routes.clone(),
                         failure_accrual,
+                        client_params: client_params.clone(),
+                        pool_limits: Default::default(),
                     },
                 )))
             }
@@ -277,6 +302,8 @@ impl HttpSidecar {
                 routes,
                 backends: policy.backends.clone(),
                 failure_accrual,
+                client_params,
+                pool_limits,
             },
         )))
     }
@@ -1,4 +1,4 @@
-pub use self::connect::Connect;
+pub use self::connect::{Connect, DualStackAddr};
 use crate::Outbound;
 use linkerd_app_core::{
     io, svc,
@@ -2,16 +2,25 @@ use crate::Outbound;
 use futures::future;
 use linkerd_app_core::{
     io, svc, tls,
-    transport::{addrs::*, ConnectTcp},
+    transport::{addrs::*, happy_eyeballs, ConnectTcp, HappyEyeballs},
+};
+use std::{
+    net::SocketAddr,
+    task::{Context, Poll},
 };
-use std::task::{Context, Poll};
 
 #[derive(Clone, Debug)]
 pub struct Connect {
     addr: Remote<ServerAddr>,
+    dual_stack_addr: Option<SocketAddr>,
     tls: tls::ConditionalClientTls,
 }
 
+/// An address of the other IP family at which an endpoint may be reached.
+/// Connections to such an endpoint race both addresses with Happy Eyeballs.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct DualStackAddr(pub SocketAddr);
+
 /// Prevents outbound connections on the loopback interface, unless the
 /// `allow-loopback` feature is enabled.
 #[derive(Clone, Debug)]
@@ -20,10 +29,13 @@ pub struct PreventLoopback<S>(S);
 // === impl Outbound ===
 
 impl Outbound<()> {
-    pub fn to_tcp_connect(&self) -> Outbound<PreventLoopback<ConnectTcp>> {
-        let connect = PreventLoopback(ConnectTcp::new(
-            self.config.proxy.connect.keepalive,
-            self.config.proxy.connect.user_timeout,
+    pub fn to_tcp_connect(&self) -> Outbound<PreventLoopback<HappyEyeballs<ConnectTcp>>> {
+        let connect = PreventLoopback(HappyEyeballs::new(
+            ConnectTcp::new(
+                self.config.proxy.connect.keepalive,
+                self.config.proxy.connect.user_timeout,
+            ),
+            happy_eyeballs::DEFAULT_ATTEMPT_DELAY,
         ));
         self.clone().with_stack(connect)
     }
@@ -33,8 +45,8 @@ impl Outbound<()> {
 
 impl<S> PreventLoopback<S> {
     #[cfg(not(feature = "allow-loopback"))]
-    fn check_loopback(Remote(ServerAddr(addr)): Remote<ServerAddr>) -> io::Result<()> {
-        if addr.ip().is_loopback() {
+    fn check_loopback(Remote(ServerAddrs(addrs)): Remote<ServerAddrs>) -> io::Result<()> {
+        if addrs.iter().any(|a| a.ip().is_loopback()) {
             return Err(io::Error::new(
                 io::ErrorKind::ConnectionRefused,
                 "Outbound proxy cannot initiate connections on the loopback interface",
@@ -47,14 +59,14 @@ impl<S> PreventLoopback<S> {
     #[cfg(feature = "allow-loopback")]
     // the Result is necessary to have the same type signature regardless of
     // whether or not the `allow-loopback` feature is enabled...
-    fn check_loopback(_: Remote<ServerAddr>) -> io::Result<()> {
+    fn check_loopback(_: Remote<ServerAddrs>) -> io::Result<()> {
         Ok(())
     }
 }
 
 impl<T, S> svc::Service<T> for PreventLoopback<S>
 where
-    T: svc::Param<Remote<ServerAddr>>,
+    T: svc::Param<Remote<ServerAddrs>>,
     S: svc::Service<T, Error = io::Error>,
 {
     type Response = S::Response;
@@ -79,7 +91,18 @@ where
 
 impl Connect {
     pub fn new(addr: Remote<ServerAddr>, tls: tls::ConditionalClientTls) -> Self {
-        Self { addr, tls }
+        Self {
+            addr,
+            dual_stack_addr: None,
+            tls,
+        }
+    }
+
+    pub fn with_dual_stack_addr(self, addr: Option<DualStackAddr>) -> Self {
+        Self {
+            dual_stack_addr: addr.map(|DualStackAddr(a)| a),
+            ..self
+        }
     }
 }
 
@@ -89,6 +112,15 @@ impl svc::Param<Remote<ServerAddr>> for Connect {
     }
 }
 
+impl svc::Param<Remote<ServerAddrs>> for Connect {
+    fn param(&self) -> Remote<ServerAddrs> {
+        let Remote(ServerAddr(addr)) = self.addr;
+        Remote(ServerAddrs(
+            std::iter::once(addr).chain(self.dual_stack_addr).collect(),
+        ))
+    }
+}
+
 impl svc::Param<tls::ConditionalClientTls> for Connect {
     fn param(&self) -> tls::ConditionalClientTls {
         self.tls.clone()
@@ -101,6 +133,10 @@ impl Connect {
         &self.addr
     }
 
+    pub fn dual_stack_addr(&self) -> Option<SocketAddr> {
+        self.dual_stack_addr
+    }
+
     pub fn tls(&self) -> &tls::ConditionalClientTls {
         &self.tls
     }
@@ -19,6 +19,7 @@ impl<C> Outbound<C> {
         T: svc::Param<Remote<ServerAddr>>,
         T: svc::Param<tls::ConditionalClientTls>,
         T: svc::Param<Option<tagged_transport::PortOverride>>,
+        T: svc::Param<Option<DualStackAddr>>,
         T: svc::Param<Option<http::AuthorityOverride>>,
         T: svc::Param<Option<SessionProtocol>>,
         T: svc::Param<transport::labels::Key>,
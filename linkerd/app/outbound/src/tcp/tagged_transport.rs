@@ -1,4 +1,7 @@
-use crate::{tcp::Connect, ConnectMeta};
+use crate::{
+    tcp::{Connect, DualStackAddr},
+    ConnectMeta,
+};
 use futures::prelude::*;
 use linkerd_app_core::{
     dns,
@@ -47,6 +50,7 @@ where
     T: svc::Param<tls::ConditionalClientTls>
         + svc::Param<Remote<ServerAddr>>
         + svc::Param<Option<PortOverride>>
+        + svc::Param<Option<DualStackAddr>>
         + svc::Param<Option<http::AuthorityOverride>>
         + svc::Param<Option<SessionProtocol>>,
     S: svc::MakeConnection<Connect, Metadata = ConnectMeta> + Send + 'static,
@@ -65,9 +69,10 @@ where
 
     fn call(&mut self, ep: T) -> Self::Future {
         let tls: tls::ConditionalClientTls = ep.param();
+        let dual_stack_addr: Option<DualStackAddr> = ep.param();
         if let tls::ConditionalClientTls::None(reason) = tls {
             trace!(%reason, "Not attempting opaque transport");
-            let target = Connect::new(ep.param(), tls);
+            let target = Connect::new(ep.param(), tls).with_dual_stack_addr(dual_stack_addr);
             return Box::pin(self.inner.connect(target).err_into::<Error>());
         }
 
@@ -106,10 +111,13 @@ where
 
         let protocol: Option<SessionProtocol> = ep.param();
 
-        let connect = self.inner.connect(Connect::new(
-            Remote(ServerAddr((addr.ip(), connect_port).into())),
-            tls,
-        ));
+        let connect = self.inner.connect(
+            Connect::new(Remote(ServerAddr((addr.ip(), connect_port).into())), tls)
+                .with_dual_stack_addr(
+                    dual_stack_addr
+                        .map(|DualStackAddr(a)| DualStackAddr((a.ip(), connect_port).into())),
+                ),
+        );
         Box::pin(async move {
             let (mut io, meta) = connect.await.map_err(Into::into)?;
 
@@ -149,6 +157,7 @@ mod test {
         authority: Option<http::uri::Authority>,
         identity: Option<tls::ClientTls>,
         proto: Option<SessionProtocol>,
+        dual_stack: Option<std::net::SocketAddr>,
     }
 
     impl svc::Param<tls::ConditionalClientTls> for Endpoint {
@@ -181,6 +190,12 @@ mod test {
         }
     }
 
+    impl svc::Param<Option<DualStackAddr>> for Endpoint {
+        fn param(&self) -> Option<DualStackAddr> {
+            self.dual_stack.map(DualStackAddr)
+        }
+    }
+
     impl svc::Param<Option<http::AuthorityOverride>> for Endpoint {
         fn param(&self) -> Option<http::AuthorityOverride> {
             self.authority
@@ -240,6 +255,31 @@ mod test {
         io.write_all(b"hello").await.expect("Write must succeed");
     }
 
+    #[tokio::test(flavor = "current_thread")]
+    async fn plain_dual_stack() {
+        let _trace = linkerd_tracing::test::trace_init();
+
+        let svc = TaggedTransport {
+            inner: service_fn(|ep: Connect| {
+                assert_eq!(
+                    ep.dual_stack_addr(),
+                    Some(([0, 0, 0, 0, 0, 0, 0, 1], 4321).into())
+                );
+                let io = tokio_test::io::Builder::new().build();
+                let meta = tls::ConnectMeta {
+                    socket: Local(ClientAddr(([0, 0, 0, 0], 0).into())),
+                    tls: Conditional::Some(None),
+                };
+                future::ready(Ok::<_, io::Error>((io, meta)))
+            }),
+        };
+        let ep = Endpoint {
+            dual_stack: Some(([0, 0, 0, 0, 0, 0, 0, 1], 4321).into()),
+            ..Endpoint::default()
+        };
+        svc.oneshot(ep).await.expect("Connect must not fail");
+    }
+
     #[tokio::test(flavor = "current_thread")]
     async fn opaque_no_name() {
         let _trace = linkerd_tracing::test::trace_init();
@@ -260,6 +300,7 @@ mod test {
             identity: Some(tls::ClientTls::new(server_id, server_name)),
             authority: None,
             proto: None,
+            dual_stack: None,
         };
         let (mut io, _meta) = svc.oneshot(e).await.expect("Connect must not fail");
         io.write_all(b"hello").await.expect("Write must succeed");
@@ -285,6 +326,7 @@ mod test {
             identity: Some(tls::ClientTls::new(server_id, server_name)),
             authority: Some(http::uri::Authority::from_str("foo.bar.example.com:5555").unwrap()),
             proto: None,
+            dual_stack: None,
         };
         let (mut io, _meta) = svc.oneshot(e).await.expect("Connect must not fail");
         io.write_all(b"hello").await.expect("Write must succeed");
@@ -310,6 +352,7 @@ mod test {
             identity: Some(tls::ClientTls::new(server_id, server_name)),
             authority: None,
             proto: None,
+            dual_stack: None,
         };
         let (mut io, _meta) = svc.oneshot(e).await.expect("Connect must not fail");
         io.write_all(b"hello").await.expect("Write must succeed");
@@ -335,6 +378,7 @@ mod test {
             identity: Some(tls::ClientTls::new(server_id, server_name)),
             authority: None,
             proto: Some(SessionProtocol::Http1),
+            dual_stack: None,
         };
         let (mut io, _meta) = svc.oneshot(e).await.expect("Connect must not fail");
         io.write_all(b"hello").await.expect("Write must succeed");
@@ -360,6 +404,7 @@ mod test {
             identity: Some(tls::ClientTls::new(server_id, server_name)),
             authority: Some(http::uri::Authority::from_str("foo.bar.example.com:5555").unwrap()),
             proto: Some(SessionProtocol::Http1),
+            dual_stack: None,
         };
         let (mut io, _meta) = svc.oneshot(e).await.expect("Connect must not fail");
         io.write_all(b"hello").await.expect("Write must succeed");
@@ -385,6 +430,7 @@ mod test {
             identity: Some(tls::ClientTls::new(server_id, server_name)),
             authority: None,
             proto: Some(SessionProtocol::Http1),
+            dual_stack: None,
         };
         let (mut io, _meta) = svc.oneshot(e).await.expect("Connect must not fail");
         io.write_all(b"hello").await.expect("Write must succeed");
@@ -11,7 +11,7 @@ use linkerd_app_core::{
     IpMatch, IpNet, ProxyRuntime,
 };
 pub use linkerd_app_test as support;
-use std::{str::FromStr, time::Duration};
+use std::{str::FromStr, sync::Arc, time::Duration};
 
 pub(crate) fn default_config() -> Config {
     let buffer = QueueConfig {
@@ -21,6 +21,19 @@ pub(crate) fn default_config() -> Config {
     Config {
         ingress_mode: false,
         emit_headers: true,
+        emit_proxy_protocol: false,
+        http_deadline_header: None,
+        compression_ports: Default::default(),
+        compression: Default::default(),
+        upgrade_timeouts: Default::default(),
+        redis_ports: Default::default(),
+        redis_deny_commands: Arc::new([]),
+        redis_read_replicas: Arc::new([]),
+        postgres_ports: Default::default(),
+        postgres_failure_accrual: None,
+        dns_discovery_min_ttl: Duration::from_secs(5),
+        dns_discovery_max_ttl: Duration::from_secs(60),
+        http3: None,
         allow_discovery: IpMatch::new(Some(IpNet::from_str("0.0.0.0/0").unwrap())).into(),
         proxy: config::ProxyConfig {
             server: config::ServerConfig {
@@ -29,6 +42,7 @@ pub(crate) fn default_config() -> Config {
                 user_timeout: UserTimeout(None),
                 backlog: Backlog::default(),
                 http2: h2::ServerParams::default(),
+                proxy_protocol: Default::default(),
             },
             connect: config::ConnectConfig {
                 keepalive: Keepalive(None),
@@ -43,11 +57,13 @@ pub(crate) fn default_config() -> Config {
                 http1: h1::PoolSettings {
                     max_idle: 1,
                     idle_timeout: Duration::from_secs(1),
+                    limits: Default::default(),
                 },
                 http2: h2::ClientParams::default(),
             },
             max_in_flight_requests: 10_000,
             detect_protocol_timeout: Duration::from_secs(3),
+            histogram_encoding: Default::default(),
         },
         inbound_ips: Default::default(),
         discovery_idle_timeout: Duration::from_secs(60),
@@ -66,6 +82,7 @@ pub(crate) fn runtime() -> (ProxyRuntime, drain::Signal) {
         tap,
         span_sink: None,
         drain,
+        connections: Default::default(),
     };
     (runtime, drain_tx)
 }
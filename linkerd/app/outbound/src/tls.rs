@@ -5,13 +5,14 @@ use linkerd_app_core::{
     proxy::{
         api_resolve::{ConcreteAddr, Metadata},
         core::Resolve,
+        tap,
     },
     svc,
-    tls::{NewDetectRequiredSni, ServerName},
+    tls::{self, NewDetectRequiredSni, ServerName},
     transport::addrs::*,
-    Error,
+    Addr, Error,
 };
-use std::{fmt::Debug, hash::Hash};
+use std::{fmt::Debug, hash::Hash, net::SocketAddr};
 use tokio::sync::watch;
 
 mod concrete;
@@ -94,8 +95,9 @@ impl<C> Outbound<C> {
         self.push_tcp_endpoint()
             .push_tls_concrete(resolve)
             .push_tls_logical()
-            .map_stack(|config, _rt, stk| {
-                stk.push_new_idle_cached(config.discovery_idle_timeout)
+            .map_stack(|config, rt, stk| {
+                stk.push(tap::NewTapTcp::layer(rt.tap.clone()))
+                    .push_new_idle_cached(config.discovery_idle_timeout)
                     // Use a dedicated target type to configure parameters for
                     // the TLS stack. It also helps narrow the cache key.
                     .push_map_target(|(sni, parent): (ServerName, T)| Tls { sni, parent })
@@ -124,6 +126,34 @@ where
     }
 }
 
+impl<T> tap::InspectTcp for Tls<T>
+where
+    T: svc::Param<watch::Receiver<logical::Routes>>,
+{
+    fn src_tls(&self) -> tls::ConditionalServerTls {
+        tls::ConditionalServerTls::None(tls::NoServerTls::Loopback)
+    }
+
+    fn dst_addr(&self) -> Option<SocketAddr> {
+        match self.parent.param().borrow().addr {
+            Addr::Socket(addr) => Some(addr),
+            Addr::Name(_) => None,
+        }
+    }
+
+    fn dst_labels(&self) -> Option<tap::Labels> {
+        Some(self.parent.param().borrow().meta.tap_labels())
+    }
+
+    fn sni(&self) -> Option<ServerName> {
+        Some(self.sni.clone())
+    }
+
+    fn is_outbound(&self) -> bool {
+        true
+    }
+}
+
 // === impl TlsMetrics ===
 
 impl TlsMetrics {
@@ -1,5 +1,6 @@
 use crate::{
     metrics::BalancerMetricsParams,
+    resolve::{BalanceResolve, Discover},
     stack_labels,
     zone::{tcp_zone_labels, TcpZoneLabels},
     BackendRef, Outbound, ParentRef,
@@ -11,6 +12,7 @@ use linkerd_app_core::{
     proxy::{
         api_resolve::{ConcreteAddr, Metadata},
         core::Resolve,
+        dns_resolve,
         http::AuthorityOverride,
         tcp::{self, balance},
     },
@@ -27,6 +29,8 @@ use tracing::info_span;
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum Dispatch {
     Balance(NameAddr, balance::EwmaConfig),
+    /// Load balance over endpoints discovered through DNS.
+    BalanceDns(dns_resolve::Target, balance::EwmaConfig),
     Forward(Remote<ServerAddr>, Arc<Metadata>),
     /// A backend dispatcher that explicitly fails all requests.
     Fail {
@@ -62,6 +66,9 @@ pub type BalancerMetrics = BalancerMetricsParams<ConcreteLabels>;
 #[derive(Clone, Debug, PartialEq, Eq)]
 struct Balance<T> {
     concrete: NameAddr,
+    /// Set when endpoints are discovered through DNS rather than the
+    /// destination controller.
+    dns: Option<dns_resolve::Target>,
     ewma: balance::EwmaConfig,
     queue: QueueConfig,
     parent: T,
@@ -127,12 +134,12 @@ impl<C> Outbound<C> {
         C::Future: Send,
         C: Send + Sync + 'static,
     {
-        let resolve =
-            svc::MapTargetLayer::new(|t: Balance<T>| -> ConcreteAddr { ConcreteAddr(t.concrete) })
-                .layer(resolve.into_service());
-
         self.map_stack(|config, rt, inner| {
             let queue = config.tcp_connection_queue;
+            let resolve = svc::MapTargetLayer::new(|t: Balance<T>| -> Discover {
+                Discover::new(t.concrete, t.dns)
+            })
+            .layer(BalanceResolve::new(resolve, rt.dns.clone()));
 
             let connect = inner
                 .push(svc::stack::WithoutConnectionMetadata::layer())
@@ -187,6 +194,16 @@ impl<C> Outbound<C> {
                             Dispatch::Balance(concrete, ewma) => {
                                 svc::Either::Left(svc::Either::Left(Balance {
                                     concrete,
+                                    dns: None,
+                                    ewma,
+                                    queue,
+                                    parent,
+                                }))
+                            }
+                            Dispatch::BalanceDns(dns, ewma) => {
+                                svc::Either::Left(svc::Either::Left(Balance {
+                                    concrete: dns.addr.clone(),
+                                    dns: Some(dns),
                                     ewma,
                                     queue,
                                     parent,
@@ -283,6 +300,14 @@ impl<T> svc::Param<Option<crate::tcp::tagged_transport::PortOverride>> for Endpo
     }
 }
 
+impl<T> svc::Param<Option<crate::tcp::DualStackAddr>> for Endpoint<T> {
+    fn param(&self) -> Option<crate::tcp::DualStackAddr> {
+        self.metadata
+            .dual_stack_addr()
+            .map(crate::tcp::DualStackAddr)
+    }
+}
+
 impl<T> svc::Param<Option<AuthorityOverride>> for Endpoint<T> {
     fn param(&self) -> Option<AuthorityOverride> {
         if self.is_local {
@@ -79,10 +79,7 @@ where
         };
 
         let mk_dispatch = move |bke: &policy::Backend| match bke.dispatcher {
-            policy::BackendDispatcher::BalanceP2c(
-                ref load,
-                policy::EndpointDiscovery::DestinationGet { ref path },
-            ) => {
+            policy::BackendDispatcher::BalanceP2c(ref load, ref discovery) => {
                 // TLS traffic balances on round-trip time, since the penalty
                 // estimator needs HTTP classification that does not apply here.
                 // Read the RTT configuration from whichever estimator the policy
@@ -97,13 +94,21 @@ where
                     );
                 }
                 let (decay, default_rtt) = load.peak_ewma_rtt();
+                let ewma = http::balance::EwmaConfig { decay, default_rtt };
                 mk_concrete(
                     BackendRef(bke.meta.clone()),
-                    concrete::Dispatch::Balance(
-                        path.parse::<NameAddr>()
-                            .expect("destination must be a nameaddr"),
-                        http::balance::EwmaConfig { decay, default_rtt },
-                    ),
+                    match discovery {
+                        policy::EndpointDiscovery::DestinationGet { ref path } => {
+                            concrete::Dispatch::Balance(
+                                path.parse::<NameAddr>()
+                                    .expect("destination must be a nameaddr"),
+                                ewma,
+                            )
+                        }
+                        policy::EndpointDiscovery::Dns(ref dns) => {
+                            concrete::Dispatch::BalanceDns(crate::resolve::dns_target(dns), ewma)
+                        }
+                    },
                 )
             }
             policy::BackendDispatcher::Forward(addr, ref md) => mk_concrete(
@@ -1,19 +1,27 @@
-use crate::{dns, gateway, inbound, outbound, policy, spire, trace_collector};
+use crate::{dns, gateway, inbound, otlp_metrics, outbound, policy, spire, trace_collector};
 use linkerd_app_core::{
     addr,
     config::*,
     control::{Config as ControlConfig, ControlAddr},
-    proxy::http::{h1, h2},
+    http_tracing,
+    metrics::prom::HistogramEncoding,
+    proxy::{
+        http::{h1, h2},
+        tap,
+    },
     tls,
-    transport::{Backlog, DualListenAddr, Keepalive, ListenAddr, UserTimeout},
+    transport::{proxy_protocol, Backlog, DualListenAddr, Keepalive, ListenAddr, UserTimeout},
     AddrMatch, Conditional, IpNet,
 };
 use std::{collections::HashSet, net::SocketAddr, path::PathBuf, time::Duration};
 use thiserror::Error;
 use tracing::{debug, error, info, warn};
 
+mod compression;
 mod control;
+mod http1;
 mod http2;
+mod http3;
 mod identity;
 mod trace;
 mod types;
@@ -95,6 +103,27 @@ pub enum ParseError {
 
     #[error("authority labels may only be set to 'unsafe'")]
     NotAnAuthorityLabelsSetting,
+
+    #[error("not a valid header name")]
+    NotAHeaderName,
+
+    #[error("not a sampling rate between 0 and 1")]
+    NotASampleRate,
+
+    #[error("not a valid HTTP status code")]
+    NotAStatusCode,
+
+    #[error("not a <key>=<value> pair")]
+    NotAKeyValuePair,
+
+    #[error("not a <kind>/<name> route name")]
+    NotARouteName,
+
+    #[error("histogram encoding may only be set to 'classic' or 'native'")]
+    NotAHistogramEncoding,
+
+    #[error("not a supported content encoding")]
+    NotAnEncoding,
 }
 
 // Environment variables to look at when loading the configuration
@@ -106,10 +135,23 @@ pub const ENV_ADMIN_LISTEN_ADDR: &str = "LINKERD2_PROXY_ADMIN_LISTEN_ADDR";
 
 pub const ENV_METRICS_RETAIN_IDLE: &str = "LINKERD2_PROXY_METRICS_RETAIN_IDLE";
 
+/// Configures the buckets of request and response latency histograms. Either
+/// `classic` (the default) or `native`. Native buckets have the exponential
+/// bounds of a Prometheus native histogram and are exposed as classic `le`
+/// buckets, since the text exposition cannot carry sparse buckets.
+const ENV_METRICS_HISTOGRAM_ENCODING: &str = "LINKERD2_PROXY_METRICS_HISTOGRAM_ENCODING";
+
 pub const ENV_SHUTDOWN_ENDPOINT_ENABLED: &str = "LINKERD2_PROXY_SHUTDOWN_ENDPOINT_ENABLED";
 
 const ENV_INGRESS_MODE: &str = "LINKERD2_PROXY_INGRESS_MODE";
 
+/// Configures an ingress-mode proxy to accept HTTP/3 connections on a UDP
+/// address, e.g. `LINKERD2_PROXY_INGRESS_HTTP3_ADDR`.
+///
+/// Inbound listeners do not accept HTTP/3: meshed peers connect over mTLS on
+/// TCP, and inbound authorization policies are evaluated per TCP connection.
+const INGRESS_HTTP3_BASE: &str = "LINKERD2_PROXY_INGRESS_HTTP3";
+
 const ENV_INBOUND_HTTP_QUEUE_CAPACITY: &str = "LINKERD2_PROXY_INBOUND_HTTP_QUEUE_CAPACITY";
 const ENV_INBOUND_HTTP_FAILFAST_TIMEOUT: &str = "LINKERD2_PROXY_INBOUND_HTTP_FAILFAST_TIMEOUT";
 
@@ -149,6 +191,68 @@ pub const ENV_OUTBOUND_MAX_IN_FLIGHT: &str = "LINKERD2_PROXY_OUTBOUND_MAX_IN_FLI
 const ENV_OUTBOUND_DISABLE_INFORMATIONAL_HEADERS: &str =
     "LINKERD2_PROXY_OUTBOUND_DISABLE_INFORMATIONAL_HEADERS";
 
+/// Networks from which accepted connections must begin with a PROXY protocol
+/// header.
+const ENV_INBOUND_PROXY_PROTOCOL_TRUSTED_NETWORKS: &str =
+    "LINKERD2_PROXY_INBOUND_PROXY_PROTOCOL_TRUSTED_NETWORKS";
+const ENV_OUTBOUND_PROXY_PROTOCOL_TRUSTED_NETWORKS: &str =
+    "LINKERD2_PROXY_OUTBOUND_PROXY_PROTOCOL_TRUSTED_NETWORKS";
+
+/// Enables sending a PROXY protocol header on opaque connections that are
+/// forwarded to endpoints outside of the mesh.
+const ENV_OUTBOUND_PROXY_PROTOCOL_EMIT: &str = "LINKERD2_PROXY_OUTBOUND_PROXY_PROTOCOL_EMIT";
+
+/// A header on which the remaining deadline of non-gRPC HTTP requests is
+/// propagated to the destination. gRPC requests always use `grpc-timeout`.
+const ENV_OUTBOUND_HTTP_DEADLINE_HEADER: &str = "LINKERD2_PROXY_OUTBOUND_HTTP_DEADLINE_HEADER";
+
+/// Ports whose HTTP routes compress responses (and, optionally, decompress
+/// requests) as configured by the `LINKERD2_PROXY_HTTP_COMPRESSION_*`
+/// settings, since the policy API does not describe compression.
+const ENV_OUTBOUND_PORTS_COMPRESSION: &str = "LINKERD2_PROXY_OUTBOUND_PORTS_COMPRESSION";
+
+/// Closes connections upgraded by outbound HTTP requests (e.g. WebSockets and
+/// CONNECT tunnels) after they have been idle, or open, for this long. The
+/// policy API does not describe upgraded connections.
+const ENV_OUTBOUND_HTTP_UPGRADE_IDLE_TIMEOUT: &str =
+    "LINKERD2_PROXY_OUTBOUND_HTTP_UPGRADE_IDLE_TIMEOUT";
+const ENV_OUTBOUND_HTTP_UPGRADE_MAX_LIFETIME: &str =
+    "LINKERD2_PROXY_OUTBOUND_HTTP_UPGRADE_MAX_LIFETIME";
+
+/// Ports on which opaque connections are proxied as Redis, recording metrics
+/// for each command. Commands in `LINKERD2_PROXY_OUTBOUND_REDIS_DENY_COMMANDS`
+/// (e.g. `FLUSHALL,CONFIG`) are rejected on these connections.
+const ENV_OUTBOUND_PORTS_REDIS: &str = "LINKERD2_PROXY_OUTBOUND_PORTS_REDIS";
+const ENV_OUTBOUND_REDIS_DENY_COMMANDS: &str = "LINKERD2_PROXY_OUTBOUND_REDIS_DENY_COMMANDS";
+
+/// Services, named as `<name>.<namespace>` (e.g. `cache-replica.emojivoto`),
+/// that are Redis read replicas. When a Redis route's policy has backends for
+/// these services, read-only commands are sent to them and all other commands
+/// are sent to the route's other backends. Routes whose backends are all
+/// replicas are not split.
+const ENV_OUTBOUND_REDIS_READ_REPLICAS: &str = "LINKERD2_PROXY_OUTBOUND_REDIS_READ_REPLICAS";
+
+/// Ports on which opaque connections are proxied as Postgres, recording query
+/// metrics for each database. Sessions that servers end because they are
+/// unable to serve them are failures for the Postgres failure accrual policy.
+const ENV_OUTBOUND_PORTS_POSTGRES: &str = "LINKERD2_PROXY_OUTBOUND_PORTS_POSTGRES";
+
+/// Configures a consecutive-failures circuit breaker for the endpoints of
+/// balancers proxied as Postgres. The policy API does not describe failure
+/// accrual for opaque routes, so breakers are disabled unless this is set, and
+/// endpoints of other opaque routes are never ejected. Endpoints are probed
+/// with the backoff configured by
+/// `LINKERD2_PROXY_OUTBOUND_POSTGRES_FAILURE_ACCRUAL_EXP_BACKOFF_{MIN,MAX,JITTER}`.
+const ENV_OUTBOUND_POSTGRES_FAILURE_ACCRUAL_MAX_FAILURES: &str =
+    "LINKERD2_PROXY_OUTBOUND_POSTGRES_FAILURE_ACCRUAL_MAX_FAILURES";
+const OUTBOUND_POSTGRES_FAILURE_ACCRUAL_BASE: &str = "OUTBOUND_POSTGRES_FAILURE_ACCRUAL";
+
+/// Configures how long endpoints that policies discover through DNS are used
+/// before their names are resolved again. Lookups with TTLs outside of these
+/// bounds are refreshed at the nearest bound.
+const ENV_OUTBOUND_DNS_DISCOVERY_MIN_TTL: &str = "LINKERD2_PROXY_OUTBOUND_DNS_DISCOVERY_MIN_TTL";
+const ENV_OUTBOUND_DNS_DISCOVERY_MAX_TTL: &str = "LINKERD2_PROXY_OUTBOUND_DNS_DISCOVERY_MAX_TTL";
+
 const ENV_OUTBOUND_METRICS_HOSTNAME_LABELS: &str =
     "LINKERD2_PROXY_OUTBOUND_METRICS_HOSTNAME_LABELS";
 const ENV_INBOUND_METRICS_AUTHORITY_LABELS: &str =
@@ -157,6 +261,31 @@ const ENV_INBOUND_METRICS_AUTHORITY_LABELS: &str =
 const ENV_TRACE_ATTRIBUTES_PATH: &str = "LINKERD2_PROXY_TRACE_ATTRIBUTES_PATH";
 const ENV_TRACE_SERVICE_NAME: &str = "LINKERD2_PROXY_TRACE_SERVICE_NAME";
 const ENV_TRACE_EXTRA_ATTRIBUTES: &str = "LINKERD2_PROXY_TRACE_EXTRA_ATTRIBUTES";
+
+/// Configures the probability with which the proxy starts a trace for requests
+/// that have no trace context. Defaults to 0.
+const ENV_TRACE_SAMPLE_RATE: &str = "LINKERD2_PROXY_TRACE_SAMPLE_RATE";
+/// Configures a comma-separated list of `<route-kind>/<route-name>=<rate>`
+/// pairs that override `LINKERD2_PROXY_TRACE_SAMPLE_RATE` for requests routed by
+/// the named policy routes, e.g. `httproute/healthz=0,grpcroute/checkout=1`.
+const ENV_TRACE_SAMPLE_ROUTES: &str = "LINKERD2_PROXY_TRACE_SAMPLE_ROUTES";
+/// Configures the maximum number of traces the proxy starts each second.
+const ENV_TRACE_SAMPLE_MAX_PER_SECOND: &str = "LINKERD2_PROXY_TRACE_SAMPLE_MAX_PER_SECOND";
+/// When set, spans for requests that were not sampled are exported if their
+/// responses have at least this status code.
+const ENV_TRACE_TAIL_MIN_STATUS: &str = "LINKERD2_PROXY_TRACE_TAIL_MIN_STATUS";
+/// When set, spans for requests that were not sampled are exported if their
+/// responses take at least this long.
+const ENV_TRACE_TAIL_MIN_LATENCY: &str = "LINKERD2_PROXY_TRACE_TAIL_MIN_LATENCY";
+/// Configures a comma-separated list of W3C Baggage keys whose values are
+/// recorded as attributes of exported spans.
+const ENV_TRACE_BAGGAGE_SPAN_ATTRIBUTES: &str = "LINKERD2_PROXY_TRACE_BAGGAGE_SPAN_ATTRIBUTES";
+/// Configures a comma-separated list of `<key>=<value>` baggage entries that
+/// the proxy sets on requests, e.g. `workload=web.emojivoto`.
+const ENV_TRACE_BAGGAGE_ENTRIES: &str = "LINKERD2_PROXY_TRACE_BAGGAGE_ENTRIES";
+/// Configures the largest baggage header, in bytes, that the proxy parses or
+/// produces.
+const ENV_TRACE_BAGGAGE_MAX_BYTES: &str = "LINKERD2_PROXY_TRACE_BAGGAGE_MAX_BYTES";
 // This doesn't have the LINKERD2_ prefix because it is a conventional env var from OpenTelemetry:
 // https://opentelemetry.io/docs/specs/otel/configuration/sdk-environment-variables/#general-sdk-configuration
 const ENV_OTEL_TRACE_ATTRIBUTES: &str = "OTEL_RESOURCE_ATTRIBUTES";
@@ -197,6 +326,44 @@ pub const ENV_INBOUND_PORTS_REQUIRE_IDENTITY: &str =
 
 pub const ENV_INBOUND_PORTS_REQUIRE_TLS: &str = "LINKERD2_PROXY_INBOUND_PORTS_REQUIRE_TLS";
 
+/// Ports on which servers speak first (e.g. MySQL). Protocol detection on these
+/// ports only waits `LINKERD2_PROXY_INBOUND_SERVER_FIRST_DETECT_TIMEOUT` for the
+/// client to send data before handling the connection as opaque.
+pub const ENV_INBOUND_PORTS_SERVER_FIRST: &str = "LINKERD2_PROXY_INBOUND_PORTS_SERVER_FIRST";
+pub const ENV_INBOUND_SERVER_FIRST_DETECT_TIMEOUT: &str =
+    "LINKERD2_PROXY_INBOUND_SERVER_FIRST_DETECT_TIMEOUT";
+
+/// Ports on which gRPC servers also accept gRPC-Web requests (e.g. from
+/// browsers), translating them to gRPC before they are forwarded.
+///
+/// This only applies to ports whose server policy configures gRPC.
+pub const ENV_INBOUND_PORTS_GRPC_WEB: &str = "LINKERD2_PROXY_INBOUND_PORTS_GRPC_WEB";
+
+/// Ports on which requests are limited to the deadline propagated by the
+/// client's proxy. gRPC requests carry it in `grpc-timeout`; other HTTP
+/// requests carry it in `LINKERD2_PROXY_INBOUND_HTTP_DEADLINE_HEADER`, when
+/// set. `LINKERD2_PROXY_INBOUND_HTTP_DEADLINE_MAX` optionally bounds the
+/// deadline, including for requests that do not propagate one.
+pub const ENV_INBOUND_PORTS_DEADLINE: &str = "LINKERD2_PROXY_INBOUND_PORTS_DEADLINE";
+pub const ENV_INBOUND_HTTP_DEADLINE_HEADER: &str = "LINKERD2_PROXY_INBOUND_HTTP_DEADLINE_HEADER";
+pub const ENV_INBOUND_HTTP_DEADLINE_MAX: &str = "LINKERD2_PROXY_INBOUND_HTTP_DEADLINE_MAX";
+
+/// Ports whose HTTP routes compress responses (and, optionally, decompress
+/// requests) as configured by the `LINKERD2_PROXY_HTTP_COMPRESSION_*`
+/// settings.
+pub const ENV_INBOUND_PORTS_COMPRESSION: &str = "LINKERD2_PROXY_INBOUND_PORTS_COMPRESSION";
+
+/// Configures the compression applied on the ports listed in
+/// `LINKERD2_PROXY_INBOUND_PORTS_COMPRESSION` and
+/// `LINKERD2_PROXY_OUTBOUND_PORTS_COMPRESSION`:
+///
+/// - `_ENCODINGS`: encodings in order of preference (`zstd`, `br`, `gzip`);
+/// - `_CONTENT_TYPES`: eligible media types, e.g. `text/*`;
+/// - `_MIN_SIZE`: responses smaller than this are not compressed;
+/// - `_DECOMPRESS_REQUESTS`: whether encoded request bodies are decoded;
+/// - `_MAX_DECOMPRESSED_REQUEST_SIZE`: limits decoded request bodies.
+const HTTP_COMPRESSION_BASE: &str = "LINKERD2_PROXY_HTTP_COMPRESSION";
+
 /// Configures the default port policy for inbound connections.
 ///
 /// This must parse to a valid port policy (one of: `deny`, `authenticated`,
@@ -231,6 +398,18 @@ pub const ENV_IDENTITY_IDENTITY_SERVER_ID: &str = "LINKERD2_PROXY_IDENTITY_SERVE
 /// establish a TLS connection that shall be terminated by this proxy
 pub const ENV_IDENTITY_IDENTITY_SERVER_NAME: &str = "LINKERD2_PROXY_IDENTITY_SERVER_NAME";
 
+/// Configures additional local identities for which the inbound proxy terminates TLS.
+///
+/// The value is a comma-separated list of server names. Each entry may be of
+/// the form `<server-name>=<tls-id>` when the TLS Id differs from the server
+/// name (e.g. a SPIFFE ID). Clients select an identity by sending its server
+/// name in the SNI extension. When the Linkerd identity service is used, each
+/// additional identity's CSR, key, and service account token (`token`) are read
+/// from a subdirectory of `LINKERD2_PROXY_IDENTITY_DIR` named after the server
+/// name.
+pub const ENV_IDENTITY_ADDITIONAL_SERVER_NAMES: &str =
+    "LINKERD2_PROXY_IDENTITY_ADDITIONAL_SERVER_NAMES";
+
 // If this config is set, then the proxy will be configured to use Spire as identity
 // provider. On Unix systems this needs to be a path to a UDS while on Windows - a
 // named pipe path.
@@ -251,12 +430,40 @@ pub const ENV_HOSTNAME: &str = "HOSTNAME";
 
 pub const ENV_TRACE_COLLECTOR_SVC_BASE: &str = "LINKERD2_PROXY_TRACE_COLLECTOR_SVC";
 
+/// Configures the OTLP collector to which metrics are pushed. When unset,
+/// metrics are pushed to the trace collector if
+/// `LINKERD2_PROXY_OTLP_METRICS_ENABLED` is set.
+pub const ENV_OTLP_METRICS_COLLECTOR_SVC_BASE: &str = "LINKERD2_PROXY_OTLP_METRICS_COLLECTOR_SVC";
+/// Enables pushing metrics to an OTLP collector.
+const ENV_OTLP_METRICS_ENABLED: &str = "LINKERD2_PROXY_OTLP_METRICS_ENABLED";
+/// Configures how often metrics are pushed to the OTLP collector.
+const ENV_OTLP_METRICS_EXPORT_INTERVAL: &str = "LINKERD2_PROXY_OTLP_METRICS_EXPORT_INTERVAL";
+
 pub const ENV_DESTINATION_CONTEXT: &str = "LINKERD2_PROXY_DESTINATION_CONTEXT";
 pub const ENV_DESTINATION_PROFILE_INITIAL_TIMEOUT: &str =
     "LINKERD2_PROXY_DESTINATION_PROFILE_INITIAL_TIMEOUT";
 
 pub const ENV_TAP_SVC_NAME: &str = "LINKERD2_PROXY_TAP_SVC_NAME";
 
+/// Configures the number of bytes captured from the start of each request and
+/// response body observed by taps that extract headers. Bodies are not captured
+/// unless this is set.
+const ENV_TAP_BODY_CAPTURE_MAX_BYTES: &str = "LINKERD2_PROXY_TAP_BODY_CAPTURE_MAX_BYTES";
+/// Configures the number of body bytes that a single tap may capture.
+const ENV_TAP_BODY_CAPTURE_TAP_LIMIT: &str = "LINKERD2_PROXY_TAP_BODY_CAPTURE_TAP_LIMIT";
+/// Configures the number of captured body bytes that may be held by all taps at
+/// once.
+const ENV_TAP_BODY_CAPTURE_GLOBAL_LIMIT: &str = "LINKERD2_PROXY_TAP_BODY_CAPTURE_GLOBAL_LIMIT";
+/// Configures a comma-separated list of the content types of bodies that may be
+/// captured, e.g. `application/json,text/*`.
+const ENV_TAP_BODY_CAPTURE_CONTENT_TYPES: &str = "LINKERD2_PROXY_TAP_BODY_CAPTURE_CONTENT_TYPES";
+/// Configures a comma-separated list of headers whose values are redacted from
+/// tap events.
+const ENV_TAP_REDACT_HEADERS: &str = "LINKERD2_PROXY_TAP_REDACT_HEADERS";
+/// Configures a comma-separated list of fields whose values are redacted from
+/// captured JSON bodies.
+const ENV_TAP_REDACT_JSON_FIELDS: &str = "LINKERD2_PROXY_TAP_REDACT_JSON_FIELDS";
+
 /// Configures a minimum value for the TTL of DNS lookups.
 ///
 /// Lookups with TTLs below this value will use this value instead.
@@ -278,6 +485,23 @@ const ENV_INBOUND_HTTP1_CONNECTION_POOL_IDLE_TIMEOUT: &str =
 const ENV_OUTBOUND_HTTP1_CONNECTION_POOL_IDLE_TIMEOUT: &str =
     "LINKERD2_PROXY_OUTBOUND_HTTP1_CONNECTION_POOL_IDLE_TIMEOUT";
 
+/// Limits each endpoint's HTTP/1 connection pool. Outbound client policies may
+/// override these limits. Each limit is unbounded unless set:
+///
+/// - `_MAX_CONNECTIONS`: connections in use at once; further requests wait.
+/// - `_MAX_PENDING`: requests waiting for a connection; more are rejected.
+/// - `_MAX_REQUESTS_PER_CONNECTION`: requests sent before a connection is
+///   closed.
+/// - `_MAX_LIFETIME` (and `_MAX_LIFETIME_JITTER`): how long a connection is
+///   reused after it is established.
+const INBOUND_HTTP1_CONNECTION_POOL_BASE: &str = "LINKERD2_PROXY_INBOUND_HTTP1_CONNECTION_POOL";
+const OUTBOUND_HTTP1_CONNECTION_POOL_BASE: &str = "LINKERD2_PROXY_OUTBOUND_HTTP1_CONNECTION_POOL";
+
+/// Indicates that meshed peers accept extended CONNECT requests, so that
+/// WebSocket upgrades may be carried over HTTP/2 between proxies. Peers must
+/// set `LINKERD2_PROXY_INBOUND_SERVER_HTTP2_ENABLE_CONNECT_PROTOCOL`.
+const ENV_OUTBOUND_HTTP2_EXTENDED_CONNECT: &str = "LINKERD2_PROXY_OUTBOUND_HTTP2_EXTENDED_CONNECT";
+
 const ENV_SHUTDOWN_GRACE_PERIOD: &str = "LINKERD2_PROXY_SHUTDOWN_GRACE_PERIOD";
 
 // Default values for various configuration fields
@@ -291,6 +515,7 @@ const DEFAULT_METRICS_RETAIN_IDLE: Duration = Duration::from_secs(10 * 60);
 const DEFAULT_INBOUND_HTTP_QUEUE_CAPACITY: usize = 10_000;
 const DEFAULT_INBOUND_HTTP_FAILFAST_TIMEOUT: Duration = Duration::from_secs(1);
 const DEFAULT_INBOUND_DETECT_TIMEOUT: Duration = Duration::from_secs(10);
+const DEFAULT_INBOUND_SERVER_FIRST_DETECT_TIMEOUT: Duration = Duration::from_millis(100);
 const DEFAULT_INBOUND_CONNECT_TIMEOUT: Duration = Duration::from_millis(300);
 const DEFAULT_INBOUND_CONNECT_BACKOFF: ExponentialBackoff =
     ExponentialBackoff::new_unchecked(Duration::from_millis(100), Duration::from_secs(10), 0.1);
@@ -303,8 +528,13 @@ const DEFAULT_OUTBOUND_DETECT_TIMEOUT: Duration = Duration::from_secs(10);
 const DEFAULT_OUTBOUND_CONNECT_TIMEOUT: Duration = Duration::from_secs(1);
 const DEFAULT_OUTBOUND_CONNECT_BACKOFF: ExponentialBackoff =
     ExponentialBackoff::new_unchecked(Duration::from_millis(100), Duration::from_secs(60), 0.1);
+const DEFAULT_OUTBOUND_POSTGRES_FAILURE_ACCRUAL_BACKOFF: ExponentialBackoff =
+    ExponentialBackoff::new_unchecked(Duration::from_secs(1), Duration::from_secs(60), 0.1);
 
 const DEFAULT_CONTROL_QUEUE_CAPACITY: usize = 100;
+const DEFAULT_OTLP_METRICS_EXPORT_INTERVAL: Duration = Duration::from_secs(60);
+const DEFAULT_OTLP_METRICS_BACKOFF: ExponentialBackoff =
+    ExponentialBackoff::new_unchecked(Duration::from_secs(1), Duration::from_secs(60), 0.1);
 const DEFAULT_CONTROL_FAILFAST_TIMEOUT: Duration = Duration::from_secs(10);
 
 const DEFAULT_INITIAL_STREAM_WINDOW_SIZE: u32 = 65_535; // Protocol default
@@ -324,6 +554,8 @@ const DEFAULT_SHUTDOWN_GRACE_PERIOD: Duration = Duration::from_secs(2 * 60);
 // the application communicates with many destinations.
 const ENV_OUTBOUND_DISCOVERY_IDLE_TIMEOUT: &str = "LINKERD2_PROXY_OUTBOUND_DISCOVERY_IDLE_TIMEOUT";
 const DEFAULT_OUTBOUND_DISCOVERY_IDLE_TIMEOUT: Duration = Duration::from_secs(5);
+const DEFAULT_OUTBOUND_DNS_DISCOVERY_MIN_TTL: Duration = Duration::from_secs(5);
+const DEFAULT_OUTBOUND_DNS_DISCOVERY_MAX_TTL: Duration = Duration::from_secs(300);
 
 // On the inbound side, we may lookup per-port policy or per-service profile
 // configuration. We are more permissive in retaining inbound configuration,
@@ -361,6 +593,11 @@ const DEFAULT_DESTINATION_PROFILE_SKIP_TIMEOUT: Duration = Duration::from_millis
 const DEFAULT_IDENTITY_MIN_REFRESH: Duration = Duration::from_secs(10);
 const DEFAULT_IDENTITY_MAX_REFRESH: Duration = Duration::from_secs(60 * 60 * 24);
 
+const DEFAULT_TAP_BODY_CAPTURE_TAP_LIMIT: usize = 1024 * 1024;
+const DEFAULT_TAP_BODY_CAPTURE_GLOBAL_LIMIT: usize = 4 * 1024 * 1024;
+const DEFAULT_TAP_BODY_CAPTURE_CONTENT_TYPES: &str =
+    "application/json,application/x-www-form-urlencoded,text/*";
+
 const INBOUND_CONNECT_BASE: &str = "INBOUND_CONNECT";
 const OUTBOUND_CONNECT_BASE: &str = "OUTBOUND_CONNECT";
 
@@ -448,6 +685,12 @@ pub fn parse_config<S: Strings>(strings: &S) -> Result<super::Config, EnvError>
     let hostname = strings.get(ENV_HOSTNAME);
 
     let trace_collector_addr = parse_control_addr(strings, ENV_TRACE_COLLECTOR_SVC_BASE);
+    let otlp_metrics_addr = parse_control_addr(strings, ENV_OTLP_METRICS_COLLECTOR_SVC_BASE);
+    let otlp_metrics_enabled = parse(strings, ENV_OTLP_METRICS_ENABLED, parse_bool);
+    let otlp_metrics_interval = parse(strings, ENV_OTLP_METRICS_EXPORT_INTERVAL, parse_duration);
+    let otlp_metrics_backoff = parse_backoff(strings, "OTLP_METRICS", DEFAULT_OTLP_METRICS_BACKOFF);
+    let trace_sampler = parse_trace_sampler(strings);
+    let trace_baggage = parse_trace_baggage(strings);
 
     let gateway_suffixes = parse(strings, ENV_INBOUND_GATEWAY_SUFFIXES, parse_dns_suffixes);
 
@@ -466,6 +709,7 @@ pub fn parse_config<S: Strings>(strings: &S) -> Result<super::Config, EnvError>
     let dst_profile_networks = parse(strings, ENV_DESTINATION_PROFILE_NETWORKS, parse_networks);
 
     let tap = parse_tap_config(strings);
+    let tap_body_capture = parse_tap_body_capture(strings);
 
     let initial_stream_window_size = parse(strings, ENV_INITIAL_STREAM_WINDOW_SIZE, parse_number)?
         .unwrap_or(DEFAULT_INITIAL_STREAM_WINDOW_SIZE);
@@ -487,6 +731,23 @@ pub fn parse_config<S: Strings>(strings: &S) -> Result<super::Config, EnvError>
         std::sync::Arc::new(ips)
     };
 
+    let histogram_encoding = parse(strings, ENV_METRICS_HISTOGRAM_ENCODING, |s| {
+        if s.eq_ignore_ascii_case("classic") {
+            Ok(HistogramEncoding::Classic)
+        } else if s.eq_ignore_ascii_case("native") {
+            Ok(HistogramEncoding::Native)
+        } else {
+            Err(ParseError::NotAHistogramEncoding)
+        }
+    })?
+    .unwrap_or_default();
+
+    // Compression is configured once for both inbound and outbound ports.
+    let http_compression = std::sync::Arc::new(compression::parse_compression(
+        strings,
+        HTTP_COMPRESSION_BASE,
+    )?);
+
     let outbound = {
         let ingress_mode = parse(strings, ENV_INGRESS_MODE, parse_bool)?.unwrap_or(false);
 
@@ -509,6 +770,9 @@ pub fn parse_config<S: Strings>(strings: &S) -> Result<super::Config, EnvError>
             }
         };
 
+        let detect_protocol_timeout =
+            outbound_detect_timeout?.unwrap_or(DEFAULT_OUTBOUND_DETECT_TIMEOUT);
+
         let keepalive = Keepalive(outbound_accept_keepalive?);
         let user_timeout = UserTimeout(outbound_accept_user_timeout?);
         let backlog = Backlog(outbound_tcp_listen_backlog?);
@@ -518,7 +782,74 @@ pub fn parse_config<S: Strings>(strings: &S) -> Result<super::Config, EnvError>
             user_timeout,
             backlog,
             http2: http2::parse_server(strings, "LINKERD2_PROXY_OUTBOUND_SERVER_HTTP2")?,
+            proxy_protocol: parse_proxy_protocol(
+                strings,
+                ENV_OUTBOUND_PROXY_PROTOCOL_TRUSTED_NETWORKS,
+                detect_protocol_timeout,
+            )?,
         };
+        let emit_proxy_protocol =
+            parse(strings, ENV_OUTBOUND_PROXY_PROTOCOL_EMIT, parse_bool)?.unwrap_or(false);
+        let http_deadline_header = parse(
+            strings,
+            ENV_OUTBOUND_HTTP_DEADLINE_HEADER,
+            parse_header_name,
+        )?;
+        let compression_ports = parse(
+            strings,
+            ENV_OUTBOUND_PORTS_COMPRESSION,
+            parse_port_range_set,
+        )?
+        .unwrap_or_default();
+        let upgrade_timeouts = outbound::policy::http::UpgradeTimeouts {
+            idle: parse(
+                strings,
+                ENV_OUTBOUND_HTTP_UPGRADE_IDLE_TIMEOUT,
+                parse_duration,
+            )?,
+            lifetime: parse(
+                strings,
+                ENV_OUTBOUND_HTTP_UPGRADE_MAX_LIFETIME,
+                parse_duration,
+            )?,
+        };
+        let redis_ports =
+            parse(strings, ENV_OUTBOUND_PORTS_REDIS, parse_port_range_set)?.unwrap_or_default();
+        let redis_deny_commands = parse(strings, ENV_OUTBOUND_REDIS_DENY_COMMANDS, parse_list)?
+            .unwrap_or_default()
+            .into_iter()
+            .map(std::sync::Arc::from)
+            .collect();
+        let redis_read_replicas = parse(strings, ENV_OUTBOUND_REDIS_READ_REPLICAS, parse_list)?
+            .unwrap_or_default()
+            .into_iter()
+            .map(std::sync::Arc::from)
+            .collect();
+        let postgres_ports =
+            parse(strings, ENV_OUTBOUND_PORTS_POSTGRES, parse_port_range_set)?.unwrap_or_default();
+        let postgres_failure_accrual = match parse(
+            strings,
+            ENV_OUTBOUND_POSTGRES_FAILURE_ACCRUAL_MAX_FAILURES,
+            parse_number::<usize>,
+        )? {
+            Some(max_failures) => Some(outbound::policy::FailureAccrual::Consecutive(
+                outbound::policy::ConsecutiveFailures {
+                    max_failures,
+                    backoff: parse_backoff(
+                        strings,
+                        OUTBOUND_POSTGRES_FAILURE_ACCRUAL_BASE,
+                        DEFAULT_OUTBOUND_POSTGRES_FAILURE_ACCRUAL_BACKOFF,
+                    )?,
+                },
+            )),
+            None => None,
+        };
+        let dns_discovery_min_ttl =
+            parse(strings, ENV_OUTBOUND_DNS_DISCOVERY_MIN_TTL, parse_duration)?
+                .unwrap_or(DEFAULT_OUTBOUND_DNS_DISCOVERY_MIN_TTL);
+        let dns_discovery_max_ttl =
+            parse(strings, ENV_OUTBOUND_DNS_DISCOVERY_MAX_TTL, parse_duration)?
+                .unwrap_or(DEFAULT_OUTBOUND_DNS_DISCOVERY_MAX_TTL);
         let discovery_idle_timeout =
             outbound_discovery_idle_timeout?.unwrap_or(DEFAULT_OUTBOUND_DISCOVERY_IDLE_TIMEOUT);
         let max_idle =
@@ -530,6 +861,15 @@ pub fn parse_config<S: Strings>(strings: &S) -> Result<super::Config, EnvError>
             ENV_OUTBOUND_HTTP1_CONNECTION_POOL_IDLE_TIMEOUT,
             parse_duration,
         )?;
+        let extended_connect = parse(strings, ENV_OUTBOUND_HTTP2_EXTENDED_CONNECT, parse_bool)?;
+
+        let http3 = match http3::parse_server(strings, INGRESS_HTTP3_BASE)? {
+            Some(_) if !ingress_mode => {
+                warn!("{INGRESS_HTTP3_BASE}_ADDR is only supported in ingress mode");
+                None
+            }
+            http3 => http3,
+        };
 
         let connect = ConnectConfig {
             keepalive,
@@ -545,18 +885,17 @@ pub fn parse_config<S: Strings>(strings: &S) -> Result<super::Config, EnvError>
                     initial_stream_window_size,
                     initial_connection_window_size,
                 }),
+                extended_connect,
                 ..Default::default()
             },
             http1: h1::PoolSettings {
                 max_idle,
                 idle_timeout: connection_pool_timeout
                     .unwrap_or(DEFAULT_OUTBOUND_HTTP1_CONNECTION_POOL_IDLE_TIMEOUT),
+                limits: http1::parse_pool_limits(strings, OUTBOUND_HTTP1_CONNECTION_POOL_BASE)?,
             },
         };
 
-        let detect_protocol_timeout =
-            outbound_detect_timeout?.unwrap_or(DEFAULT_OUTBOUND_DETECT_TIMEOUT);
-
         let tcp_queue_capacity =
             outbound_tcp_queue_capacity?.unwrap_or(DEFAULT_OUTBOUND_TCP_QUEUE_CAPACITY);
         let tcp_failfast_timeout =
@@ -569,6 +908,19 @@ pub fn parse_config<S: Strings>(strings: &S) -> Result<super::Config, EnvError>
         outbound::Config {
             ingress_mode,
             emit_headers: !disable_headers,
+            emit_proxy_protocol,
+            http_deadline_header,
+            compression_ports,
+            compression: http_compression.clone(),
+            upgrade_timeouts,
+            redis_ports,
+            redis_deny_commands,
+            redis_read_replicas,
+            postgres_ports,
+            postgres_failure_accrual,
+            dns_discovery_min_ttl,
+            dns_discovery_max_ttl,
+            http3,
             allow_discovery: AddrMatch::new(dst_profile_suffixes.clone(), dst_profile_networks),
             proxy: ProxyConfig {
                 server,
@@ -576,6 +928,7 @@ pub fn parse_config<S: Strings>(strings: &S) -> Result<super::Config, EnvError>
                 max_in_flight_requests: outbound_max_in_flight?
                     .unwrap_or(DEFAULT_OUTBOUND_MAX_IN_FLIGHT),
                 detect_protocol_timeout,
+                histogram_encoding,
             },
             inbound_ips: inbound_ips.clone(),
             discovery_idle_timeout,
@@ -603,6 +956,9 @@ pub fn parse_config<S: Strings>(strings: &S) -> Result<super::Config, EnvError>
                 .unwrap_or_else(|| parse_socket_addr(DEFAULT_INBOUND_LISTEN_ADDR).unwrap()),
             None,
         );
+        let detect_protocol_timeout =
+            inbound_detect_timeout?.unwrap_or(DEFAULT_INBOUND_DETECT_TIMEOUT);
+
         let keepalive = Keepalive(inbound_accept_keepalive?);
         let user_timeout = UserTimeout(inbound_accept_user_timeout?);
         let backlog = Backlog(inbound_tcp_listen_backlog?);
@@ -612,6 +968,11 @@ pub fn parse_config<S: Strings>(strings: &S) -> Result<super::Config, EnvError>
             user_timeout,
             backlog,
             http2: http2::parse_server(strings, "LINKERD2_PROXY_INBOUND_SERVER_HTTP2")?,
+            proxy_protocol: parse_proxy_protocol(
+                strings,
+                ENV_INBOUND_PROXY_PROTOCOL_TRUSTED_NETWORKS,
+                detect_protocol_timeout,
+            )?,
         };
         let discovery_idle_timeout =
             inbound_discovery_idle_timeout?.unwrap_or(DEFAULT_INBOUND_DISCOVERY_IDLE_TIMEOUT);
@@ -644,12 +1005,10 @@ pub fn parse_config<S: Strings>(strings: &S) -> Result<super::Config, EnvError>
             http1: h1::PoolSettings {
                 max_idle,
                 idle_timeout: connection_pool_timeout,
+                limits: http1::parse_pool_limits(strings, INBOUND_HTTP1_CONNECTION_POOL_BASE)?,
             },
         };
 
-        let detect_protocol_timeout =
-            inbound_detect_timeout?.unwrap_or(DEFAULT_INBOUND_DETECT_TIMEOUT);
-
         let unsafe_authority_labels = parse(strings, ENV_INBOUND_METRICS_AUTHORITY_LABELS, |s| {
             if s.is_empty() {
                 Ok(false)
@@ -718,14 +1077,50 @@ pub fn parse_config<S: Strings>(strings: &S) -> Result<super::Config, EnvError>
             // and that's fine.
             .unwrap_or_default();
 
+            let overrides = inbound::policy::PortOverrides {
+                grpc_web_ports: parse(strings, ENV_INBOUND_PORTS_GRPC_WEB, parse_port_range_set)?
+                    .unwrap_or_default(),
+                deadline_ports: parse(strings, ENV_INBOUND_PORTS_DEADLINE, parse_port_range_set)?
+                    .unwrap_or_default(),
+                deadline: inbound::policy::Deadline {
+                    http_header: parse(
+                        strings,
+                        ENV_INBOUND_HTTP_DEADLINE_HEADER,
+                        parse_header_name,
+                    )?,
+                    max: parse(strings, ENV_INBOUND_HTTP_DEADLINE_MAX, parse_duration)?,
+                },
+                compression_ports: parse(
+                    strings,
+                    ENV_INBOUND_PORTS_COMPRESSION,
+                    parse_port_range_set,
+                )?
+                .unwrap_or_default(),
+                compression: http_compression,
+            };
+
             inbound::policy::Config::Discover {
                 default,
                 ports,
                 cache_max_idle_age: discovery_idle_timeout,
                 opaque_ports,
+                overrides,
             }
         };
 
+        let server_first_ports = parse(
+            strings,
+            ENV_INBOUND_PORTS_SERVER_FIRST,
+            parse_port_range_set,
+        )?
+        .unwrap_or_default();
+        let server_first_detect_timeout = parse(
+            strings,
+            ENV_INBOUND_SERVER_FIRST_DETECT_TIMEOUT,
+            parse_duration,
+        )?
+        .unwrap_or(DEFAULT_INBOUND_SERVER_FIRST_DETECT_TIMEOUT);
+
         inbound::Config {
             allow_discovery: dst_profile_suffixes.into_iter().collect(),
             proxy: ProxyConfig {
@@ -734,6 +1129,7 @@ pub fn parse_config<S: Strings>(strings: &S) -> Result<super::Config, EnvError>
                 max_in_flight_requests: inbound_max_in_flight?
                     .unwrap_or(DEFAULT_INBOUND_MAX_IN_FLIGHT),
                 detect_protocol_timeout,
+                histogram_encoding,
             },
             policy,
             profile_skip_timeout: dst_profile_skip_timeout?
@@ -748,6 +1144,8 @@ pub fn parse_config<S: Strings>(strings: &S) -> Result<super::Config, EnvError>
                     .unwrap_or(DEFAULT_INBOUND_HTTP_FAILFAST_TIMEOUT),
             },
             unsafe_authority_labels,
+            server_first_ports,
+            server_first_detect_timeout,
         }
     };
 
@@ -832,6 +1230,7 @@ pub fn parse_config<S: Strings>(strings: &S) -> Result<super::Config, EnvError>
             user_timeout: inbound.proxy.server.user_timeout,
             backlog: inbound.proxy.server.backlog,
             http2: inbound.proxy.server.http2.clone(),
+            proxy_protocol: Default::default(),
         },
 
         // TODO(ver) Currently we always enable profiling when the pprof feature
@@ -847,46 +1246,85 @@ pub fn parse_config<S: Strings>(strings: &S) -> Result<super::Config, EnvError>
         max_ttl: dns_max_ttl?,
     };
 
-    let trace_collector = match trace_collector_addr? {
+    // Collectors on the loopback interface are reached as if they were the
+    // local application.
+    let collector_control = |addr: ControlAddr| {
+        let connect = if addr.addr.is_loopback() {
+            inbound.proxy.connect.clone()
+        } else {
+            outbound.proxy.connect.clone()
+        };
+        let failfast_timeout = if addr.addr.is_loopback() {
+            inbound.http_request_queue.failfast_timeout
+        } else {
+            outbound.http_request_queue.failfast_timeout
+        };
+        ControlConfig {
+            addr,
+            connect,
+            buffer: QueueConfig {
+                capacity: DEFAULT_CONTROL_QUEUE_CAPACITY,
+                failfast_timeout,
+            },
+        }
+    };
+
+    let hostname = hostname?;
+    let trace_collector_addr = trace_collector_addr?;
+
+    let otlp_metrics_enabled = otlp_metrics_enabled?.unwrap_or(false);
+    let otlp_metrics_addr = match otlp_metrics_addr? {
+        Some(addr) => Some(addr),
+        None if otlp_metrics_enabled => match trace_collector_addr.clone() {
+            Some(addr) => Some(addr),
+            None => {
+                error!(
+                    "{ENV_OTLP_METRICS_ENABLED} requires {ENV_OTLP_METRICS_COLLECTOR_SVC_BASE}_ADDR \
+                    or {ENV_TRACE_COLLECTOR_SVC_BASE}_ADDR to be set"
+                );
+                return Err(EnvError::InvalidEnvVar);
+            }
+        },
+        None => None,
+    };
+    let otlp_metrics = match otlp_metrics_addr {
+        None => otlp_metrics::Config::Disabled,
+        Some(addr) => otlp_metrics::Config::Enabled(Box::new(otlp_metrics::EnabledConfig {
+            attributes: trace::TraceAttributes::new(strings).into_labels(),
+            hostname: hostname.clone(),
+            interval: otlp_metrics_interval?.unwrap_or(DEFAULT_OTLP_METRICS_EXPORT_INTERVAL),
+            backoff: otlp_metrics_backoff?,
+            control: collector_control(addr),
+        })),
+    };
+
+    let trace_collector = match trace_collector_addr {
         None => trace_collector::Config::Disabled,
         Some(addr) => {
-            let connect = if addr.addr.is_loopback() {
-                inbound.proxy.connect.clone()
-            } else {
-                outbound.proxy.connect.clone()
-            };
-            let failfast_timeout = if addr.addr.is_loopback() {
-                inbound.http_request_queue.failfast_timeout
-            } else {
-                outbound.http_request_queue.failfast_timeout
-            };
-
             let attributes = trace::TraceAttributes::new(strings).into_labels();
 
             trace_collector::Config::Enabled(Box::new(trace_collector::EnabledConfig {
                 attributes,
-                hostname: hostname?,
-                control: ControlConfig {
-                    addr,
-                    connect,
-                    buffer: QueueConfig {
-                        capacity: DEFAULT_CONTROL_QUEUE_CAPACITY,
-                        failfast_timeout,
-                    },
-                },
+                hostname,
+                sampler: trace_sampler?,
+                baggage: trace_baggage?,
+                control: collector_control(addr),
             }))
         }
     };
 
+    let tap_body_capture = tap_body_capture?;
     let tap = tap?
         .map(|(addr, permitted_client_id)| super::tap::Config::Enabled {
             permitted_client_id,
+            body_capture: tap_body_capture,
             config: ServerConfig {
                 addr: DualListenAddr(addr, None),
                 keepalive: inbound.proxy.server.keepalive,
                 user_timeout: inbound.proxy.server.user_timeout,
                 backlog: inbound.proxy.server.backlog,
                 http2: inbound.proxy.server.http2.clone(),
+                proxy_protocol: Default::default(),
             },
         })
         .unwrap_or(super::tap::Config::Disabled);
@@ -899,6 +1337,7 @@ pub fn parse_config<S: Strings>(strings: &S) -> Result<super::Config, EnvError>
         dst,
         tap,
         trace_collector,
+        otlp_metrics,
         policy,
         identity,
         outbound,
@@ -933,6 +1372,20 @@ impl Env {
 
 // === Parsing ===
 
+/// Reads the networks that are trusted to send PROXY protocol headers. Headers
+/// must be read within the listener's protocol detection timeout.
+fn parse_proxy_protocol(
+    strings: &dyn Strings,
+    name: &str,
+    header_timeout: Duration,
+) -> Result<proxy_protocol::Config, EnvError> {
+    let trusted_networks = parse(strings, name, parse_networks)?.unwrap_or_default();
+    Ok(proxy_protocol::Config {
+        trusted_networks: trusted_networks.into_iter().collect(),
+        header_timeout,
+    })
+}
+
 /// There is a dependency on identity being enabled for tap to work. The
 /// status of tap is determined by the ENV_TAP_SVC_NAME env variable being set
 /// or not set.
@@ -953,6 +1406,54 @@ fn parse_tap_config(
     Ok(None)
 }
 
+fn parse_tap_body_capture(strings: &dyn Strings) -> Result<tap::BodyCapture, EnvError> {
+    let max_body_bytes = parse(strings, ENV_TAP_BODY_CAPTURE_MAX_BYTES, parse_number)?;
+    let tap_limit = parse(strings, ENV_TAP_BODY_CAPTURE_TAP_LIMIT, parse_number)?;
+    let global_limit = parse(strings, ENV_TAP_BODY_CAPTURE_GLOBAL_LIMIT, parse_number)?;
+    let content_types = parse(strings, ENV_TAP_BODY_CAPTURE_CONTENT_TYPES, parse_list)?;
+    let redact_headers = parse(strings, ENV_TAP_REDACT_HEADERS, parse_header_names)?;
+    let redact_json_fields = parse(strings, ENV_TAP_REDACT_JSON_FIELDS, parse_list)?;
+    Ok(tap::BodyCapture {
+        max_body_bytes: max_body_bytes.unwrap_or(0),
+        tap_limit: tap_limit.unwrap_or(DEFAULT_TAP_BODY_CAPTURE_TAP_LIMIT),
+        global_limit: global_limit.unwrap_or(DEFAULT_TAP_BODY_CAPTURE_GLOBAL_LIMIT),
+        content_types: content_types
+            .unwrap_or_else(|| parse_list(DEFAULT_TAP_BODY_CAPTURE_CONTENT_TYPES).unwrap()),
+        redact_headers: redact_headers.unwrap_or_default(),
+        redact_json_fields: redact_json_fields.unwrap_or_default(),
+    })
+}
+
+fn parse_trace_sampler(strings: &dyn Strings) -> Result<http_tracing::SamplerConfig, EnvError> {
+    let head_rate = parse(strings, ENV_TRACE_SAMPLE_RATE, parse_sample_rate)?;
+    let route_rates = parse(strings, ENV_TRACE_SAMPLE_ROUTES, parse_sample_routes)?;
+    let max_traces_per_second = parse(strings, ENV_TRACE_SAMPLE_MAX_PER_SECOND, parse_number)?;
+    let min_status = parse(strings, ENV_TRACE_TAIL_MIN_STATUS, parse_status_code)?;
+    let min_latency = parse(strings, ENV_TRACE_TAIL_MIN_LATENCY, parse_duration)?;
+    let tail =
+        (min_status.is_some() || min_latency.is_some()).then_some(http_tracing::TailConfig {
+            min_status,
+            min_latency,
+        });
+    Ok(http_tracing::SamplerConfig {
+        head_rate: head_rate.unwrap_or(0.0),
+        route_rates: route_rates.unwrap_or_default(),
+        max_traces_per_second,
+        tail,
+    })
+}
+
+fn parse_trace_baggage(strings: &dyn Strings) -> Result<http_tracing::BaggageConfig, EnvError> {
+    let span_attributes = parse(strings, ENV_TRACE_BAGGAGE_SPAN_ATTRIBUTES, parse_list)?;
+    let entries = parse(strings, ENV_TRACE_BAGGAGE_ENTRIES, parse_key_values)?;
+    let max_bytes = parse(strings, ENV_TRACE_BAGGAGE_MAX_BYTES, parse_number)?;
+    Ok(http_tracing::BaggageConfig {
+        span_attributes: span_attributes.unwrap_or_default(),
+        entries: entries.unwrap_or_default(),
+        max_bytes: max_bytes.unwrap_or(http_tracing::BaggageConfig::DEFAULT_MAX_BYTES),
+    })
+}
+
 pub(super) fn parse<T, Parse>(
     strings: &dyn Strings,
     name: &str,
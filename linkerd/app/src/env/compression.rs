@@ -0,0 +1,78 @@
+use super::{parse, types::*, EnvError, ParseError, Strings};
+use linkerd_app_outbound::policy::http::filter::{Compression, Encoding};
+use tracing::error;
+
+/// Parses the compression filter applied to HTTP routes on ports that enable
+/// compression. Unset settings use the filter's defaults.
+pub(super) fn parse_compression<S: Strings>(
+    strings: &S,
+    base: &str,
+) -> Result<Compression, EnvError> {
+    let encodings = parse(strings, &format!("{base}_ENCODINGS"), parse_encodings);
+    let content_types = parse(strings, &format!("{base}_CONTENT_TYPES"), parse_list);
+    let min_size = parse(strings, &format!("{base}_MIN_SIZE"), parse_number);
+    let decompress_requests = parse(strings, &format!("{base}_DECOMPRESS_REQUESTS"), parse_bool);
+    let max_decompressed_request_size = parse(
+        strings,
+        &format!("{base}_MAX_DECOMPRESSED_REQUEST_SIZE"),
+        parse_number,
+    );
+
+    let default = Compression::default();
+    Ok(Compression {
+        encodings: encodings?.unwrap_or(default.encodings),
+        content_types: content_types?.unwrap_or(default.content_types),
+        min_size: min_size?.unwrap_or(default.min_size),
+        decompress_requests: decompress_requests?.unwrap_or(default.decompress_requests),
+        max_decompressed_request_size: max_decompressed_request_size?
+            .unwrap_or(default.max_decompressed_request_size),
+    })
+}
+
+fn parse_encodings(list: &str) -> Result<Vec<Encoding>, ParseError> {
+    parse_list(list)?
+        .into_iter()
+        .map(|token| {
+            Encoding::from_token(&token).ok_or_else(|| {
+                error!("Not a supported content encoding: {token}");
+                ParseError::NotAnEncoding
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn compression() {
+        let mut env = HashMap::default();
+
+        // Uses the filter's defaults if no relevant env vars are set.
+        assert_eq!(
+            parse_compression(&env, "TEST").unwrap(),
+            Compression::default()
+        );
+
+        env.insert("TEST_ENCODINGS", "br, gzip");
+        env.insert("TEST_CONTENT_TYPES", "application/json");
+        env.insert("TEST_MIN_SIZE", "256");
+        env.insert("TEST_DECOMPRESS_REQUESTS", "true");
+        env.insert("TEST_MAX_DECOMPRESSED_REQUEST_SIZE", "1024");
+        assert_eq!(
+            parse_compression(&env, "TEST").unwrap(),
+            Compression {
+                encodings: vec![Encoding::Brotli, Encoding::Gzip],
+                content_types: vec!["application/json".to_string()],
+                min_size: 256,
+                decompress_requests: true,
+                max_decompressed_request_size: 1024,
+            }
+        );
+
+        env.insert("TEST_ENCODINGS", "deflate");
+        assert!(parse_compression(&env, "TEST").is_err());
+    }
+}
@@ -0,0 +1,73 @@
+use super::{parse, types::*, EnvError, Strings};
+use linkerd_app_core::proxy::http::h1;
+
+/// Parses the limits on each endpoint's HTTP/1 connection pool. Limits that
+/// are not set are unbounded.
+pub(super) fn parse_pool_limits<S: Strings>(
+    strings: &S,
+    base: &str,
+) -> Result<h1::PoolLimits, EnvError> {
+    let max_lifetime = parse(strings, &format!("{base}_MAX_LIFETIME"), parse_duration)?;
+    let jitter = parse(
+        strings,
+        &format!("{base}_MAX_LIFETIME_JITTER"),
+        parse_duration,
+    )?;
+    Ok(h1::PoolLimits {
+        max_connections: parse(strings, &format!("{base}_MAX_CONNECTIONS"), parse_number)?,
+        max_pending: parse(strings, &format!("{base}_MAX_PENDING"), parse_number)?,
+        max_requests_per_connection: parse(
+            strings,
+            &format!("{base}_MAX_REQUESTS_PER_CONNECTION"),
+            parse_number,
+        )?,
+        max_lifetime: max_lifetime.map(|lifetime| h1::MaxLifetime {
+            lifetime,
+            jitter: jitter.unwrap_or_default(),
+        }),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{collections::HashMap, time::Duration};
+
+    #[test]
+    fn pool_limits() {
+        let mut env = HashMap::default();
+
+        // Produces unbounded limits if no relevant env vars are set.
+        assert_eq!(
+            parse_pool_limits(&env, "TEST").unwrap(),
+            h1::PoolLimits::default()
+        );
+
+        // Jitter is ignored unless a lifetime is set.
+        env.insert("TEST_MAX_LIFETIME_JITTER", "5s");
+        assert_eq!(
+            parse_pool_limits(&env, "TEST").unwrap(),
+            h1::PoolLimits::default()
+        );
+
+        env.insert("TEST_MAX_CONNECTIONS", "2");
+        env.insert("TEST_MAX_PENDING", "3");
+        env.insert("TEST_MAX_REQUESTS_PER_CONNECTION", "4");
+        env.insert("TEST_MAX_LIFETIME", "1m");
+        assert_eq!(
+            parse_pool_limits(&env, "TEST").unwrap(),
+            h1::PoolLimits {
+                max_connections: Some(2),
+                max_pending: Some(3),
+                max_requests_per_connection: Some(4),
+                max_lifetime: Some(h1::MaxLifetime {
+                    lifetime: Duration::from_secs(60),
+                    jitter: Duration::from_secs(5),
+                }),
+            }
+        );
+
+        env.insert("TEST_MAX_CONNECTIONS", "two");
+        assert!(parse_pool_limits(&env, "TEST").is_err());
+    }
+}
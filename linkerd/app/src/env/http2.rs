@@ -14,6 +14,12 @@ pub(super) fn parse_server<S: Strings>(
             &format!("{base}_MAX_CONCURRENT_STREAMS"),
             parse_number,
         )?,
+        enable_connect_protocol: parse(
+            strings,
+            &format!("{base}_ENABLE_CONNECT_PROTOCOL"),
+            parse_bool,
+        )?
+        .unwrap_or(false),
         max_frame_size: parse(strings, &format!("{base}_MAX_FRAME_SIZE"), parse_number)?,
         max_header_list_size: parse(
             strings,
@@ -99,6 +105,7 @@ mod tests {
 
         // Set all the fields.
         env.insert("TEST_MAX_CONCURRENT_STREAMS", "3");
+        env.insert("TEST_ENABLE_CONNECT_PROTOCOL", "true");
         env.insert("TEST_MAX_FRAME_SIZE", "4");
         env.insert("TEST_MAX_HEADER_LIST_SIZE", "5");
         env.insert("TEST_MAX_PENDING_ACCEPT_RESET_STREAMS", "6");
@@ -117,6 +124,7 @@ mod tests {
                 timeout: Duration::from_secs(1),
             }),
             max_concurrent_streams: Some(3),
+            enable_connect_protocol: true,
             max_frame_size: Some(4),
             max_header_list_size: Some(5),
             max_pending_accept_reset_streams: Some(6),
@@ -0,0 +1,85 @@
+use super::{parse, types::*, EnvError, Strings};
+use linkerd_http_h3 as h3;
+use std::path::PathBuf;
+use tracing::error;
+
+/// Parses the configuration for an HTTP/3 listener.
+///
+/// The listener is disabled unless `{base}_ADDR` is set, in which case a
+/// certificate and private key must also be configured.
+pub(super) fn parse_server<S: Strings>(
+    strings: &S,
+    base: &str,
+) -> Result<Option<h3::ServerConfig>, EnvError> {
+    let addr = parse(strings, &format!("{base}_ADDR"), parse_socket_addr);
+    let certificate = parse(strings, &format!("{base}_CERT"), |s| Ok(PathBuf::from(s)));
+    let private_key = parse(strings, &format!("{base}_KEY"), |s| Ok(PathBuf::from(s)));
+    let max_concurrent_streams = parse(
+        strings,
+        &format!("{base}_MAX_CONCURRENT_STREAMS"),
+        parse_number,
+    );
+    let idle_timeout = parse(strings, &format!("{base}_IDLE_TIMEOUT"), parse_duration);
+    let alt_svc_max_age = parse(strings, &format!("{base}_ALT_SVC_MAX_AGE"), parse_duration);
+
+    let params = h3::ServerParams {
+        max_concurrent_streams: max_concurrent_streams?,
+        idle_timeout: idle_timeout?,
+        alt_svc_max_age: alt_svc_max_age?
+            .unwrap_or_else(|| h3::ServerParams::default().alt_svc_max_age),
+    };
+    match (addr?, certificate?, private_key?) {
+        (None, _, _) => Ok(None),
+        (Some(addr), Some(certificate), Some(private_key)) => Ok(Some(h3::ServerConfig {
+            addr,
+            certificate,
+            private_key,
+            params,
+        })),
+        _ => {
+            error!("{base}_ADDR requires {base}_CERT and {base}_KEY to be set");
+            Err(EnvError::InvalidEnvVar)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{collections::HashMap, time::Duration};
+
+    #[test]
+    fn server_config() {
+        let mut env = HashMap::default();
+
+        // The listener is disabled if no address is set.
+        env.insert("TEST_CERT", "/var/run/h3/crt.pem");
+        assert_eq!(parse_server(&env, "TEST").unwrap(), None);
+
+        // A certificate and key are required.
+        env.insert("TEST_ADDR", "0.0.0.0:4143");
+        assert!(parse_server(&env, "TEST").is_err());
+
+        env.insert("TEST_KEY", "/var/run/h3/key.pem");
+        let expected = h3::ServerConfig {
+            addr: ([0, 0, 0, 0], 4143).into(),
+            certificate: "/var/run/h3/crt.pem".into(),
+            private_key: "/var/run/h3/key.pem".into(),
+            params: h3::ServerParams::default(),
+        };
+        assert_eq!(parse_server(&env, "TEST").unwrap(), Some(expected.clone()));
+
+        env.insert("TEST_MAX_CONCURRENT_STREAMS", "10");
+        env.insert("TEST_IDLE_TIMEOUT", "30s");
+        env.insert("TEST_ALT_SVC_MAX_AGE", "1h");
+        let expected = h3::ServerConfig {
+            params: h3::ServerParams {
+                max_concurrent_streams: Some(10),
+                idle_timeout: Some(Duration::from_secs(30)),
+                alt_svc_max_age: Duration::from_secs(60 * 60),
+            },
+            ..expected
+        };
+        assert_eq!(parse_server(&env, "TEST").unwrap(), Some(expected));
+    }
+}
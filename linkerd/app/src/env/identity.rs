@@ -7,6 +7,12 @@ pub fn parse_identity_config<S: Strings>(
     outbound: &outbound::Config,
 ) -> Result<crate::identity::Config, EnvError> {
     let (id, server_name, trust_anchors_pem) = parse_tls_params(strings)?;
+    let additional = parse(
+        strings,
+        ENV_IDENTITY_ADDITIONAL_SERVER_NAMES,
+        parse_additional_identities,
+    )?
+    .unwrap_or_default();
 
     match parse_deprecated(
         strings,
@@ -20,10 +26,23 @@ pub fn parse_identity_config<S: Strings>(
             crate::identity::Id::Uri(uri)
                 if uri.scheme().eq_ignore_ascii_case(SPIFFE_ID_URI_SCHEME) =>
             {
+                if let Some((id, _)) = additional.iter().find(|(id, _)| !is_spiffe_id(id)) {
+                    error!(%id, "Spire support requires a SPIFFE TLS Id for additional identities");
+                    return Err(EnvError::InvalidEnvVar);
+                }
+
                 Ok(crate::identity::Config::Spire {
                     id,
                     server_name,
                     trust_anchors_pem,
+                    additional: additional
+                        .into_iter()
+                        .map(|(id, server_name)| crate::identity::Additional {
+                            id,
+                            server_name,
+                            config: (),
+                        })
+                        .collect(),
                     client: spire::Config {
                         workload_api_addr: std::sync::Arc::new(workload_api_addr),
                         backoff: parse_backoff(
@@ -40,14 +59,30 @@ pub fn parse_identity_config<S: Strings>(
             }
         },
         None => {
-            match (&id, &server_name) {
-                (linkerd_app_core::identity::Id::Dns(id), sni) if id == sni => {}
-                (_id, _sni) => {
-                    return Err(EnvError::TlsIdAndServerNameNotMatching);
-                }
-            };
+            for (id, server_name) in std::iter::once((&id, &server_name))
+                .chain(additional.iter().map(|(id, name)| (id, name)))
+            {
+                match (id, server_name) {
+                    (linkerd_app_core::identity::Id::Dns(id), sni) if id == sni => {}
+                    (_id, _sni) => {
+                        return Err(EnvError::TlsIdAndServerNameNotMatching);
+                    }
+                };
+            }
 
             let (addr, certify) = self::identity::parse_linkerd_identity_config(strings)?;
+            let additional = additional
+                .into_iter()
+                .map(|(id, server_name)| {
+                    let certify =
+                        parse_additional_linkerd_identity_config(strings, &server_name, &certify)?;
+                    Ok(crate::identity::Additional {
+                        id,
+                        server_name,
+                        config: certify,
+                    })
+                })
+                .collect::<Result<Vec<_>, EnvError>>()?;
 
             // If the address doesn't have a server identity, then we're on localhost.
             let connect = if addr.addr.is_loopback() {
@@ -66,6 +101,7 @@ pub fn parse_identity_config<S: Strings>(
                 id,
                 server_name,
                 trust_anchors_pem,
+                additional,
                 client: ControlConfig {
                     addr,
                     connect,
@@ -128,6 +164,41 @@ fn parse_linkerd_identity_config<S: Strings>(
     }
 }
 
+fn is_spiffe_id(id: &Id) -> bool {
+    matches!(id, Id::Uri(uri) if uri.scheme().eq_ignore_ascii_case(SPIFFE_ID_URI_SCHEME))
+}
+
+/// Configures certification for an additional local identity. The identity's
+/// CSR, key, and token are loaded from a subdirectory of the identity directory
+/// named after its server name, since the identity controller only certifies
+/// an identity for a token issued to it. Only the refresh bounds are shared
+/// with the primary identity.
+fn parse_additional_linkerd_identity_config<S: Strings>(
+    strings: &S,
+    server_name: &dns::Name,
+    primary: &crate::identity::client::linkerd::Config,
+) -> Result<crate::identity::client::linkerd::Config, EnvError> {
+    let dir = parse(strings, ENV_IDENTITY_DIR, |s| Ok(PathBuf::from(s)))?
+        .ok_or(EnvError::InvalidEnvVar)?
+        .join(server_name.as_str());
+    let token = crate::identity::client::linkerd::TokenSource::if_nonempty_file(dir.join("token"))
+        .map_err(|error| {
+            error!(%error, %server_name, "Could not read identity token");
+            EnvError::InvalidEnvVar
+        })?;
+    let documents =
+        crate::identity::client::linkerd::certify::Documents::load(dir).map_err(|error| {
+            error!(%error, %server_name, "Failed to read identity documents");
+            EnvError::InvalidEnvVar
+        })?;
+    Ok(crate::identity::client::linkerd::Config {
+        token,
+        documents,
+        min_refresh: primary.min_refresh,
+        max_refresh: primary.max_refresh,
+    })
+}
+
 fn parse_tls_params<S: Strings>(strings: &S) -> Result<(Id, dns::Name, String), EnvError> {
     let ta = parse(strings, ENV_IDENTITY_TRUST_ANCHORS, |s| {
         if s.is_empty() {
@@ -1,5 +1,5 @@
 use super::ParseError;
-use linkerd_app_core::{dns, identity, Addr, IpNet};
+use linkerd_app_core::{dns, identity, proxy::http, Addr, IpNet};
 use rangemap::RangeInclusiveSet;
 use std::{
     collections::HashSet,
@@ -23,6 +23,22 @@ pub(crate) fn parse_identity(s: &str) -> Result<identity::Id, ParseError> {
     })
 }
 
+/// Parses a comma-separated list of `<server-name>[=<tls-id>]` entries. When
+/// the TLS Id is omitted, the server name is used as the TLS Id.
+pub(super) fn parse_additional_identities(
+    list: &str,
+) -> Result<Vec<(identity::Id, dns::Name)>, ParseError> {
+    let mut identities = Vec::new();
+    for item in list.split(',') {
+        let item = item.trim();
+        if !item.is_empty() {
+            let (name, id) = item.split_once('=').unwrap_or((item, item));
+            identities.push((parse_identity(id.trim())?, parse_dns_name(name.trim())?));
+        }
+    }
+    Ok(identities)
+}
+
 pub(super) fn parse_bool(s: &str) -> Result<bool, ParseError> {
     s.parse().map_err(Into::into)
 }
@@ -150,6 +166,73 @@ pub(super) fn parse_dns_suffix(s: &str) -> Result<dns::Suffix, ParseError> {
     dns::Suffix::from_str(s).map_err(|_| ParseError::NotADomainSuffix)
 }
 
+/// Parses a comma-separated list of values, ignoring empty entries.
+pub(super) fn parse_list(list: &str) -> Result<Vec<String>, ParseError> {
+    Ok(list
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(ToOwned::to_owned)
+        .collect())
+}
+
+pub(super) fn parse_header_names(list: &str) -> Result<Vec<http::HeaderName>, ParseError> {
+    parse_list(list)?
+        .into_iter()
+        .map(|name| parse_header_name(&name))
+        .collect()
+}
+
+pub(super) fn parse_header_name(name: &str) -> Result<http::HeaderName, ParseError> {
+    http::HeaderName::try_from(name.trim()).map_err(|_| {
+        error!("Not a valid header name: {name}");
+        ParseError::NotAHeaderName
+    })
+}
+
+pub(super) fn parse_sample_rate(s: &str) -> Result<f64, ParseError> {
+    let rate = s.parse::<f64>()?;
+    if !(0.0..=1.0).contains(&rate) {
+        return Err(ParseError::NotASampleRate);
+    }
+    Ok(rate)
+}
+
+/// Parses a comma-separated list of `<key>=<value>` pairs.
+pub(super) fn parse_key_values(list: &str) -> Result<Vec<(String, String)>, ParseError> {
+    parse_list(list)?
+        .into_iter()
+        .map(|item| {
+            let (key, value) = item.split_once('=').ok_or_else(|| {
+                error!("Expected <key>=<value>: {item}");
+                ParseError::NotAKeyValuePair
+            })?;
+            Ok((key.trim().to_owned(), value.trim().to_owned()))
+        })
+        .collect()
+}
+
+/// Parses a comma-separated list of `<route-kind>/<route-name>=<rate>` pairs.
+pub(super) fn parse_sample_routes(list: &str) -> Result<Vec<(String, f64)>, ParseError> {
+    parse_key_values(list)?
+        .into_iter()
+        .map(|(route, rate)| {
+            let valid = route
+                .split_once('/')
+                .is_some_and(|(kind, name)| !kind.is_empty() && !name.is_empty());
+            if !valid {
+                error!("Expected <kind>/<name>: {route}");
+                return Err(ParseError::NotARouteName);
+            }
+            Ok((route, parse_sample_rate(&rate)?))
+        })
+        .collect()
+}
+
+pub(super) fn parse_status_code(s: &str) -> Result<http::StatusCode, ParseError> {
+    http::StatusCode::from_bytes(s.as_bytes()).map_err(|_| ParseError::NotAStatusCode)
+}
+
 pub(super) fn parse_networks(list: &str) -> Result<HashSet<IpNet>, ParseError> {
     let mut nets = HashSet::new();
     for input in list.split(',') {
@@ -169,6 +252,56 @@ pub(super) fn parse_networks(list: &str) -> Result<HashSet<IpNet>, ParseError> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn additional_identities() {
+        let ids = parse_additional_identities(
+            "foo.ns.serviceaccount.identity.linkerd.cluster.local, bar.ns.svc=spiffe://td/ns/bar",
+        )
+        .expect("must parse");
+        assert_eq!(
+            ids,
+            vec![
+                (
+                    "foo.ns.serviceaccount.identity.linkerd.cluster.local"
+                        .parse()
+                        .unwrap(),
+                    "foo.ns.serviceaccount.identity.linkerd.cluster.local"
+                        .parse()
+                        .unwrap(),
+                ),
+                (
+                    "spiffe://td/ns/bar".parse().unwrap(),
+                    "bar.ns.svc".parse().unwrap(),
+                ),
+            ]
+        );
+        assert_eq!(parse_additional_identities(""), Ok(vec![]));
+        assert!(parse_additional_identities("not a name").is_err());
+    }
+
+    #[test]
+    fn sample_routes() {
+        assert_eq!(
+            parse_sample_routes("httproute/healthz=0, grpcroute/checkout = 1,"),
+            Ok(vec![
+                ("httproute/healthz".to_owned(), 0.0),
+                ("grpcroute/checkout".to_owned(), 1.0),
+            ])
+        );
+        assert_eq!(
+            parse_sample_routes("httproute/api=1.5"),
+            Err(ParseError::NotASampleRate)
+        );
+        assert_eq!(
+            parse_sample_routes("httproute/api"),
+            Err(ParseError::NotAKeyValuePair)
+        );
+        assert_eq!(
+            parse_sample_routes("/api=1"),
+            Err(ParseError::NotARouteName)
+        );
+    }
+
     fn test_unit<F: Fn(u64) -> Duration>(unit: &str, to_duration: F) {
         for v in &[0, 1, 23, 456_789] {
             let d = to_duration(*v);
@@ -9,7 +9,7 @@ use linkerd_app_core::{
     metrics::{prom, ControlHttp as ClientMetrics},
     Result,
 };
-use std::{future::Future, pin::Pin, time::SystemTime};
+use std::{collections::HashMap, future::Future, pin::Pin, time::SystemTime};
 use tokio::sync::watch;
 use tracing::Instrument;
 
@@ -22,18 +22,33 @@ pub enum Config {
         id: Id,
         server_name: dns::Name,
         trust_anchors_pem: String,
+        additional: Vec<Additional<client::linkerd::Config>>,
     },
     Spire {
         client: spire::Config,
         id: Id,
         server_name: dns::Name,
         trust_anchors_pem: String,
+        additional: Vec<Additional<()>>,
     },
 }
 
+/// Configures an additional local identity for which the inbound proxy
+/// terminates TLS. Clients select the identity by its server name (SNI).
+///
+/// Each additional identity is provisioned independently of the primary
+/// identity, and the proxy is not ready until every identity is certified.
+#[derive(Clone, Debug)]
+pub struct Additional<C> {
+    pub id: Id,
+    pub server_name: dns::Name,
+    pub config: C,
+}
+
 pub struct Identity {
     receiver: creds::Receiver,
-    ready: watch::Receiver<bool>,
+    /// Notified as the primary and each additional identity is certified.
+    ready: Vec<watch::Receiver<bool>>,
     task: Task,
 }
 
@@ -76,19 +91,51 @@ impl Config {
                 id,
                 server_name,
                 trust_anchors_pem,
+                additional,
             } => {
                 let certify = Certify::from(certify);
-                let (store, receiver, ready) =
-                    watch(id, server_name.clone(), trust_anchors_pem, metrics.cert)?;
+                let (store, additional_stores, receiver, ready) = watch(
+                    id,
+                    server_name.clone(),
+                    additional
+                        .iter()
+                        .map(|a| (a.id.clone(), a.server_name.clone())),
+                    trust_anchors_pem,
+                    metrics.cert,
+                )?;
 
                 let task = {
                     let addr = client.addr.clone();
+                    let additional = additional
+                        .into_iter()
+                        .zip(additional_stores)
+                        .map(|(additional, store)| {
+                            let Additional {
+                                id,
+                                server_name,
+                                config,
+                            } = additional;
+                            let svc = client.clone().build(
+                                dns.clone(),
+                                client_metrics.clone(),
+                                metrics.client.clone(),
+                                receiver.new_client(),
+                            );
+                            let span = tracing::info_span!("identity", server.addr = %addr, %id);
+                            Box::pin(
+                                Certify::from(config)
+                                    .run(server_name, store, svc)
+                                    .instrument(span.or_current()),
+                            ) as Task
+                        })
+                        .collect::<Vec<_>>();
+
                     let svc =
                         client.build(dns, client_metrics, metrics.client, receiver.new_client());
-
-                    Box::pin(certify.run(server_name, store, svc).instrument(
+                    let primary = certify.run(server_name, store, svc).instrument(
                         tracing::info_span!("identity", server.addr = %addr).or_current(),
-                    ))
+                    );
+                    Box::pin(run_all(primary, additional))
                 };
                 Identity {
                     receiver,
@@ -101,16 +148,36 @@ impl Config {
                 id,
                 server_name,
                 trust_anchors_pem,
+                additional,
             } => {
                 let addr = client.workload_api_addr.clone();
                 let spire = spire::client::Spire::new(id.clone());
 
-                let (store, receiver, ready) =
-                    watch(id, server_name, trust_anchors_pem, metrics.cert)?;
-                let task =
-                    Box::pin(spire.run(store, spire::Client::from(client)).instrument(
-                        tracing::info_span!("spire", server.addr = %addr).or_current(),
-                    ));
+                let (store, additional_stores, receiver, ready) = watch(
+                    id,
+                    server_name,
+                    additional
+                        .iter()
+                        .map(|a| (a.id.clone(), a.server_name.clone())),
+                    trust_anchors_pem,
+                    metrics.cert,
+                )?;
+                let additional = additional
+                    .into_iter()
+                    .zip(additional_stores)
+                    .map(|(Additional { id, .. }, store)| {
+                        let span = tracing::info_span!("spire", server.addr = %addr, %id);
+                        Box::pin(
+                            spire::client::Spire::new(id)
+                                .run(store, spire::Client::from(client.clone()))
+                                .instrument(span.or_current()),
+                        ) as Task
+                    })
+                    .collect::<Vec<_>>();
+                let primary = spire
+                    .run(store, spire::Client::from(client))
+                    .instrument(tracing::info_span!("spire", server.addr = %addr).or_current());
+                let task = Box::pin(run_all(primary, additional));
 
                 Identity {
                     receiver,
@@ -125,22 +192,47 @@ impl Config {
 fn watch(
     id: Id,
     server_name: dns::Name,
+    additional: impl IntoIterator<Item = (Id, dns::Name)>,
     trust_anchors_pem: String,
     metrics: CertMetrics,
 ) -> Result<(
     WithCertMetrics<NotifyReady>,
+    Vec<NotifyReady>,
     creds::Receiver,
-    watch::Receiver<bool>,
+    Vec<watch::Receiver<bool>>,
 )> {
-    let (tx, ready) = watch::channel(false);
-    let (store, receiver) =
-        linkerd_app_core::identity::creds::watch(id, server_name, &trust_anchors_pem)?;
-    let cred = WithCertMetrics::new(metrics, NotifyReady { store, tx });
-    Ok((cred, receiver, ready))
+    let (store, additional, receiver) = linkerd_app_core::identity::creds::watch_with_additional(
+        id,
+        server_name,
+        additional.into_iter().collect(),
+        &trust_anchors_pem,
+    )?;
+    let (cred, primary_ready) = NotifyReady::new(store);
+    let cred = WithCertMetrics::new(metrics, cred);
+    let (additional, additional_ready): (Vec<_>, Vec<_>) =
+        additional.into_iter().map(NotifyReady::new).unzip();
+    let ready = std::iter::once(primary_ready)
+        .chain(additional_ready)
+        .collect();
+    Ok((cred, additional, receiver, ready))
+}
+
+/// Drives the primary identity's task and each additional identity's task
+/// together, so that additional identities run on the same task as the
+/// primary identity and stop with it.
+async fn run_all(primary: impl Future<Output = ()>, additional: Vec<Task>) {
+    futures::future::join(primary, futures::future::join_all(additional)).await;
 }
 
 // === impl NotifyReady ===
 
+impl NotifyReady {
+    fn new(store: creds::Store) -> (Self, watch::Receiver<bool>) {
+        let (tx, ready) = watch::channel(false);
+        (Self { store, tx }, ready)
+    }
+}
+
 impl Credentials for NotifyReady {
     fn set_certificate(
         &mut self,
@@ -153,17 +245,24 @@ impl Credentials for NotifyReady {
         let _ = self.tx.send(true);
         Ok(())
     }
+
+    fn set_federated_roots(&mut self, roots: HashMap<String, Vec<DerX509>>) -> Result<()> {
+        self.store.set_federated_roots(roots)
+    }
 }
 
 // === impl Identity ===
 
 impl Identity {
-    /// Returns a future that is satisfied once certificates have been provisioned.
+    /// Returns a future that is satisfied once certificates have been
+    /// provisioned for the primary identity and every additional identity.
     pub fn ready(&self) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>> {
-        let mut ready = self.ready.clone();
+        let ready = self.ready.clone();
         Box::pin(async move {
-            while !*ready.borrow_and_update() {
-                ready.changed().await.expect("identity sender must be held");
+            for mut ready in ready {
+                while !*ready.borrow_and_update() {
+                    ready.changed().await.expect("identity sender must be held");
+                }
             }
         })
     }
@@ -7,6 +7,7 @@
 pub mod dst;
 pub mod env;
 pub mod identity;
+pub mod otlp_metrics;
 pub mod policy;
 pub mod spire;
 pub mod tap;
@@ -23,10 +24,14 @@ use linkerd_app_core::{
     serve,
     svc::Param,
     tls_info,
-    transport::{addrs::*, listen::Bind},
+    transport::{addrs::*, connections, listen::Bind, Connections},
     Error, ProxyRuntime,
 };
-pub use linkerd_app_core::{metrics, trace, transport::BindTcp, BUILD_INFO};
+pub use linkerd_app_core::{
+    metrics, trace,
+    transport::{BindTcp, BindWithProxyProtocol},
+    BUILD_INFO,
+};
 use linkerd_app_gateway as gateway;
 use linkerd_app_inbound::{self as inbound, Inbound};
 use linkerd_app_outbound::{self as outbound, Outbound};
@@ -63,6 +68,7 @@ pub struct Config {
     pub admin: admin::Config,
     pub tap: tap::Config,
     pub trace_collector: trace_collector::Config,
+    pub otlp_metrics: otlp_metrics::Config,
 
     /// Grace period for graceful shutdowns.
     ///
@@ -78,6 +84,7 @@ pub struct App {
     identity: identity::Identity,
     inbound_addr: Local<ServerAddr>,
     trace_collector: trace_collector::TraceCollector,
+    otlp_metrics: otlp_metrics::OtlpMetrics,
     outbound_addr: Local<ServerAddr>,
     outbound_addr_additional: Option<Local<ServerAddr>>,
     start_proxy: Pin<Box<dyn std::future::Future<Output = ()> + Send + 'static>>,
@@ -126,6 +133,7 @@ impl Config {
             identity,
             inbound,
             trace_collector,
+            otlp_metrics,
             outbound,
             gateway,
             tap,
@@ -211,12 +219,25 @@ impl Config {
             })
         }?;
 
+        // The OTLP metrics exporter's client metrics are registered before the
+        // registry is frozen; the exporter is built once it is.
+        let otlp_metrics_control = if let Some(prefix) = otlp_metrics.metrics_prefix() {
+            ControlMetrics::register(registry.sub_registry_with_prefix(prefix))
+        } else {
+            ControlMetrics::register(&mut prom::Registry::default())
+        };
+
+        // Accepted connections are recorded so that they may be inspected
+        // through the admin server.
+        let connections = Connections::default();
+
         let runtime = ProxyRuntime {
             identity: identity.receiver(),
             metrics: metrics.proxy,
             tap: tap.registry(),
             span_sink: trace_collector.span_sink(),
             drain: drain_rx.clone(),
+            connections: connections.clone(),
         };
         let inbound = Inbound::new(
             inbound,
@@ -227,13 +248,19 @@ impl Config {
             outbound,
             runtime,
             registry.sub_registry_with_prefix("outbound"),
-        );
+        )
+        .with_dns(dns.resolver("outbound"));
+
+        // Discovered policies are recorded so that they may be inspected
+        // through the admin server.
+        let policy_snapshots = admin::Policies::default();
 
         let inbound_policies = inbound.build_policies(
             policies.workload.clone(),
             policies.client.clone(),
             policies.backoff,
             policies.limits,
+            policy_snapshots.inbound.clone(),
         );
 
         let outbound_policies = outbound.build_policies(
@@ -242,6 +269,7 @@ impl Config {
             policies.backoff,
             policies.limits,
             export_hostname_labels,
+            policy_snapshots.outbound.clone(),
         );
 
         let gateway = gateway::Gateway::new(gateway, inbound.clone(), outbound.clone()).stack(
@@ -255,6 +283,7 @@ impl Config {
         let (inbound_addr, inbound_listen) = bind_in
             .bind(&inbound.config().proxy.server)
             .expect("Failed to bind inbound listener");
+        let inbound_listen = connections.track(connections::Direction::In, inbound_listen);
         let inbound_metrics = inbound.metrics();
         let inbound = inbound.mk(
             inbound_addr,
@@ -266,7 +295,30 @@ impl Config {
         let ((outbound_addr, outbound_addr_additional), outbound_listen) = bind_out
             .bind(&outbound.config().proxy.server)
             .expect("Failed to bind outbound listener");
+        let outbound_listen = connections.track(connections::Direction::Out, outbound_listen);
         let outbound_metrics = outbound.metrics();
+        let outbound_balancers = outbound.balancers();
+
+        // In ingress mode, an HTTP/3 listener may be bound alongside the
+        // outbound listener. Its requests are served by the same routing
+        // stacks.
+        #[cfg(feature = "http3")]
+        let http3 = match outbound.config().http3.as_ref() {
+            Some(config) if outbound.config().ingress_mode => {
+                let server = linkerd_http_h3::server::Server::bind(config)?;
+                let addr = server.local_addr()?;
+                info!(%addr, "HTTP/3 ingress listener bound");
+                let http3 = outbound.mk_ingress_http3(
+                    dst.profiles.clone(),
+                    outbound_policies.clone(),
+                    dst.resolve.clone(),
+                    addr,
+                );
+                Some((server, http3))
+            }
+            _ => None,
+        };
+
         let outbound = outbound.mk(dst.profiles.clone(), outbound_policies, dst.resolve.clone());
 
         // Build a task that initializes and runs the proxy stacks.
@@ -282,6 +334,15 @@ impl Config {
                         .instrument(info_span!("outbound").or_current()),
                 );
 
+                #[cfg(feature = "http3")]
+                if let Some((server, http3)) = http3 {
+                    tokio::spawn(
+                        server
+                            .serve(http3, drain_rx.clone())
+                            .instrument(info_span!("ingress_h3").or_current()),
+                    );
+                }
+
                 tokio::spawn(
                     serve::serve(inbound_listen, inbound, drain_rx.signaled())
                         .instrument(info_span!("inbound").or_current()),
@@ -296,6 +357,25 @@ impl Config {
         registry.register("proxy_build_info", "Proxy build info", BUILD_INFO.metric());
         registry.register("rustls_info", "Proxy TLS info", tls_info::metric());
 
+        let registry = prom::Report::from(registry);
+
+        debug!(config = ?otlp_metrics, "Building OTLP metrics exporter");
+        let otlp_metrics = {
+            let identity = identity.receiver().new_client();
+            let dns = dns.resolver("otlp_metrics");
+            let client_metrics = metrics.control.clone();
+            let registry = registry.clone();
+            info_span!("otlp_metrics").in_scope(|| {
+                otlp_metrics.build(
+                    identity,
+                    dns,
+                    registry,
+                    otlp_metrics_control,
+                    client_metrics,
+                )
+            })
+        }?;
+
         let admin = {
             let identity = identity.receiver().server();
             let metrics = inbound_metrics.clone();
@@ -304,11 +384,14 @@ impl Config {
                 .and_report(report)
                 // The prom registry reports an "# EOF" at the end of its export, so
                 // it should be emitted last.
-                .and_report(prom::Report::from(registry));
+                .and_report(registry);
             info_span!("admin").in_scope(move || {
                 admin.build(
                     bind_admin,
                     inbound_policies,
+                    policy_snapshots,
+                    outbound_balancers,
+                    connections,
                     identity,
                     report,
                     metrics,
@@ -326,6 +409,7 @@ impl Config {
             identity,
             inbound_addr,
             trace_collector,
+            otlp_metrics,
             outbound_addr,
             outbound_addr_additional,
             start_proxy,
@@ -400,6 +484,7 @@ impl App {
             drain,
             identity,
             trace_collector: collector,
+            otlp_metrics,
             start_proxy,
             tap,
             ..
@@ -468,6 +553,10 @@ impl App {
                             tokio::spawn(collector.task.instrument(info_span!("tracing")));
                         }
 
+                        if let otlp_metrics::OtlpMetrics::Enabled(exporter) = otlp_metrics {
+                            tokio::spawn(exporter.task.instrument(info_span!("otlp_metrics")));
+                        }
+
                         // we don't care if the admin shutdown channel is
                         // dropped or actually triggered.
                         let _ = admin_shutdown_rx.await;
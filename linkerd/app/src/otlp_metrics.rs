@@ -0,0 +1,91 @@
+use crate::trace_collector::otel_collector::{self, OtelCollectorAttributes};
+use linkerd_app_core::{
+    control, dns,
+    exp_backoff::ExponentialBackoff,
+    identity,
+    metrics::{prom, ControlHttp as HttpMetrics},
+    opentelemetry,
+    svc::NewService,
+};
+use linkerd_error::Error;
+use std::{collections::HashMap, future::Future, pin::Pin, time::Duration};
+use tracing::Instrument;
+
+/// Configures pushing the proxy's metrics to an OTLP collector.
+#[derive(Clone, Debug)]
+pub enum Config {
+    Disabled,
+    Enabled(Box<EnabledConfig>),
+}
+
+#[derive(Clone, Debug)]
+pub struct EnabledConfig {
+    pub control: control::Config,
+    pub attributes: HashMap<String, String>,
+    pub hostname: Option<String>,
+    pub interval: Duration,
+    pub backoff: ExponentialBackoff,
+}
+
+pub type Task = Pin<Box<dyn Future<Output = ()> + Send + 'static>>;
+
+pub enum OtlpMetrics {
+    Disabled,
+    Enabled(Box<EnabledExporter>),
+}
+
+pub struct EnabledExporter {
+    pub addr: control::ControlAddr,
+    pub task: Task,
+}
+
+impl Config {
+    pub fn metrics_prefix(&self) -> Option<&'static str> {
+        match self {
+            Config::Disabled => None,
+            Config::Enabled(_) => Some("otlp_metrics"),
+        }
+    }
+
+    pub fn build(
+        self,
+        identity: identity::NewClient,
+        dns: dns::Resolver,
+        registry: prom::Report,
+        control_metrics: control::Metrics,
+        client_metrics: HttpMetrics,
+    ) -> Result<OtlpMetrics, Error> {
+        match self {
+            Config::Disabled => Ok(OtlpMetrics::Disabled),
+            Config::Enabled(inner) => {
+                let addr = inner.control.addr.clone();
+                let svc = inner
+                    .control
+                    .build(dns, client_metrics, control_metrics, identity)
+                    .new_service(());
+
+                let resource = otel_collector::resource(OtelCollectorAttributes {
+                    hostname: inner.hostname,
+                    extra: inner.attributes,
+                });
+                let task = Box::pin(
+                    opentelemetry::export_metrics(
+                        svc,
+                        registry,
+                        resource,
+                        inner.interval,
+                        inner.backoff,
+                    )
+                    .instrument(
+                        tracing::debug_span!("otlp_metrics", peer.addr = %addr).or_current(),
+                    ),
+                );
+
+                Ok(OtlpMetrics::Enabled(Box::new(EnabledExporter {
+                    addr,
+                    task,
+                })))
+            }
+        }
+    }
+}
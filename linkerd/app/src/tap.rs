@@ -19,6 +19,7 @@ pub enum Config {
     Enabled {
         config: ServerConfig,
         permitted_client_id: tls::server::ClientId,
+        body_capture: tap::BodyCapture,
     },
 }
 
@@ -59,9 +60,10 @@ impl Config {
             Config::Enabled {
                 config,
                 permitted_client_id,
+                body_capture,
             } => {
                 let (listen_addr, listen) = bind.bind(&config)?;
-                let accept = svc::stack(server)
+                let accept = svc::stack(server.with_body_capture(body_capture))
                     .push(svc::layer::mk(move |service| {
                         tap::AcceptPermittedClients::new(
                             permitted_client_id.clone().into(),
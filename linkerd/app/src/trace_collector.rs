@@ -1,6 +1,10 @@
 use linkerd_app_core::{
-    control, dns, http_tracing::SpanSink, identity, metrics::ControlHttp as HttpMetrics,
-    opentelemetry, svc::NewService,
+    control, dns,
+    http_tracing::{BaggageConfig, SamplerConfig, SpanSink},
+    identity,
+    metrics::ControlHttp as HttpMetrics,
+    opentelemetry,
+    svc::NewService,
 };
 use linkerd_error::Error;
 use otel_collector::OtelCollectorAttributes;
@@ -21,6 +25,8 @@ pub struct EnabledConfig {
     pub control: control::Config,
     pub attributes: HashMap<String, String>,
     pub hostname: Option<String>,
+    pub sampler: SamplerConfig,
+    pub baggage: BaggageConfig,
 }
 
 pub type Task = Pin<Box<dyn Future<Output = ()> + Send + 'static>>;
@@ -79,6 +85,8 @@ impl Config {
                         addr.clone(),
                         attributes,
                         svc,
+                        inner.sampler,
+                        inner.baggage,
                         legacy_otel_metrics,
                     )
                 };
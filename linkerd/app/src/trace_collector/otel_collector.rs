@@ -1,5 +1,10 @@
 use super::EnabledCollector;
-use linkerd_app_core::{control::ControlAddr, proxy::http::Body, Error};
+use linkerd_app_core::{
+    control::ControlAddr,
+    http_tracing::{BaggageConfig, Sampler, SamplerConfig, SpanSink},
+    proxy::http::Body,
+    Error,
+};
 use linkerd_opentelemetry::{
     self as opentelemetry, metrics,
     proto::{
@@ -17,7 +22,7 @@ use tokio_stream::wrappers::ReceiverStream;
 use tonic::{body::Body as TonicBody, client::GrpcService};
 use tracing::Instrument;
 
-pub(super) struct OtelCollectorAttributes {
+pub(crate) struct OtelCollectorAttributes {
     pub hostname: Option<String>,
     pub extra: HashMap<String, String>,
 }
@@ -26,6 +31,8 @@ pub(super) fn create_collector<S>(
     addr: ControlAddr,
     attributes: OtelCollectorAttributes,
     svc: S,
+    sampler: SamplerConfig,
+    baggage: BaggageConfig,
     legacy_metrics: metrics::Registry,
 ) -> EnabledCollector
 where
@@ -35,9 +42,27 @@ where
     S::ResponseBody: Body<Data = tonic::codegen::Bytes> + Send + 'static,
     <S::ResponseBody as Body>::Error: Into<Error> + Send,
 {
-    let (span_sink, spans_rx) = mpsc::channel(crate::trace_collector::SPAN_BUFFER_CAPACITY);
+    let (spans_tx, spans_rx) = mpsc::channel(crate::trace_collector::SPAN_BUFFER_CAPACITY);
+    let span_sink = SpanSink::new(spans_tx, Sampler::new(sampler), baggage);
     let spans_rx = ReceiverStream::new(spans_rx);
 
+    let resources = resource(attributes);
+
+    let addr = addr.clone();
+    let task = Box::pin(
+        opentelemetry::export_spans(svc, spans_rx, resources, legacy_metrics)
+            .instrument(tracing::debug_span!("opentelemetry", peer.addr = %addr).or_current()),
+    );
+
+    EnabledCollector {
+        addr,
+        task,
+        span_sink,
+    }
+}
+
+/// Builds the OTLP resource that describes this proxy.
+pub(crate) fn resource(attributes: OtelCollectorAttributes) -> ResourceAttributesWithSchema {
     let mut resources = ResourceAttributesWithSchema::default();
 
     resources
@@ -66,17 +91,7 @@ where
             .map(|(key, value)| value.with_key(&key)),
     );
 
-    let addr = addr.clone();
-    let task = Box::pin(
-        opentelemetry::export_spans(svc, spans_rx, resources, legacy_metrics)
-            .instrument(tracing::debug_span!("opentelemetry", peer.addr = %addr).or_current()),
-    );
-
-    EnabledCollector {
-        addr,
-        task,
-        span_sink,
-    }
+    resources
 }
 
 trait IntoAnyValue
@@ -86,10 +86,12 @@ impl ClientPolicies {
             http1: http::Http1 {
                 routes: http_routes.clone(),
                 failure_accrual: None,
+                pool_limits: Default::default(),
             },
             http2: http::Http2 {
                 routes: http_routes,
                 failure_accrual: None,
+                client_params: Default::default(),
             },
             opaque: opaq::Opaque {
                 routes: Some(opaq::Route {
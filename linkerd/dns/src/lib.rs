@@ -37,14 +37,14 @@ pub struct Metrics {
 
 #[derive(Debug, Clone, Error)]
 #[error("invalid SRV record {:?}", self.0)]
-struct InvalidSrv(rdata::SRV);
+pub struct InvalidSrv(rdata::SRV);
 
 #[derive(Debug, Error)]
 #[error("failed to resolve A record: {0}")]
-struct ARecordError(#[from] hickory_resolver::net::NetError);
+pub struct ARecordError(#[from] hickory_resolver::net::NetError);
 
 #[derive(Debug, Error)]
-enum SrvRecordError {
+pub enum SrvRecordError {
     #[error("{0}")]
     Invalid(#[from] InvalidSrv),
     #[error("failed to resolve SRV record: {0}")]
@@ -137,6 +137,70 @@ impl Resolver {
         }
     }
 
+    /// Resolves a name's A/AAAA records to a set of addresses on the given
+    /// port.
+    pub async fn resolve_ip_addrs(
+        &self,
+        name: NameRef<'_>,
+        port: u16,
+    ) -> Result<(Vec<net::SocketAddr>, Instant), ARecordError> {
+        let (ips, valid_until) = self.resolve_a_or_aaaa(name).await?;
+        let addrs = ips
+            .into_iter()
+            .map(|ip| net::SocketAddr::new(ip, port))
+            .collect();
+        Ok((addrs, valid_until))
+    }
+
+    /// Resolves a name's SRV records to a set of weighted addresses.
+    ///
+    /// Only records with the most-preferred priority are returned. Unlike
+    /// [`Resolver::resolve_addrs`], SRV targets need not encode an IP address:
+    /// targets that are hostnames are resolved via A/AAAA records, with each of
+    /// the target's addresses inheriting the record's port and weight.
+    pub async fn resolve_srv_weighted(
+        &self,
+        name: NameRef<'_>,
+    ) -> Result<(Vec<(net::SocketAddr, u16)>, Instant), SrvRecordError> {
+        debug!(%name, "Resolving weighted SRV records");
+        let srv = self.dns.srv_lookup(name.as_str()).await?;
+
+        let mut valid_until = Instant::from_std(srv.valid_until());
+        let records = srv
+            .answers()
+            .iter()
+            .filter_map(|record| match &record.data {
+                hickory_resolver::proto::rr::RData::SRV(srv) => Some(srv),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        // Only the most-preferred (lowest-numbered) priority is used.
+        let priority = records.iter().map(|srv| srv.priority).min();
+        let mut addrs = Vec::new();
+        for srv in records
+            .into_iter()
+            .filter(|srv| Some(srv.priority) == priority)
+        {
+            if let Ok(addr) = Self::srv_to_socket_addr(srv) {
+                addrs.push((addr, srv.weight));
+                continue;
+            }
+
+            trace!(target = %srv.target, "Resolving SRV target");
+            let lookup = self.dns.lookup_ip(srv.target.to_string()).await?;
+            valid_until = valid_until.min(Instant::from_std(lookup.valid_until()));
+            addrs.extend(
+                lookup
+                    .iter()
+                    .map(|ip| (net::SocketAddr::new(ip, srv.port), srv.weight)),
+            );
+        }
+
+        debug!(ttl = ?valid_until - Instant::now(), ?addrs);
+        Ok((addrs, valid_until))
+    }
+
     async fn resolve_a_or_aaaa(
         &self,
         name: NameRef<'_>,
@@ -66,6 +66,11 @@ impl ExponentialBackoff {
         self.max
     }
 
+    /// The ratio of the base timeout that may be randomly added to a backoff.
+    pub const fn jitter_ratio(&self) -> f64 {
+        self.jitter
+    }
+
     pub fn try_new(
         min: time::Duration,
         max: time::Duration,
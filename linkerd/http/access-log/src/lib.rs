@@ -7,11 +7,14 @@ use linkerd_proxy_transport::{ClientAddr, Remote};
 use linkerd_stack as svc;
 use linkerd_tls as tls;
 use linkerd_tracing::access_log::TRACE_TARGET;
+use parking_lot::Mutex;
 use pin_project::pin_project;
 use std::{
+    fmt,
     future::Future,
     net::SocketAddr,
     pin::Pin,
+    sync::Arc,
     task::{Context, Poll},
     time::Duration,
 };
@@ -31,8 +34,18 @@ pub struct AccessLogContext<S> {
     client_id: Option<identity::Id>,
 }
 
-struct ResponseFutureInner {
+/// A handle on a request's access log.
+///
+/// When access logging is enabled, this is inserted into each request's
+/// extensions so that inner stacks can describe how the request was handled.
+#[derive(Clone, Debug)]
+pub struct AccessLog {
     span: Span,
+    response_flags: Arc<Mutex<Vec<&'static str>>>,
+}
+
+struct ResponseFutureInner {
+    log: AccessLog,
     start: Instant,
     processing: Duration,
 }
@@ -99,7 +112,7 @@ where
         self.inner.poll_ready(cx)
     }
 
-    fn call(&mut self, request: http::Request<B1>) -> Self::Future {
+    fn call(&mut self, mut request: http::Request<B1>) -> Self::Future {
         let get_header = |name: http::header::HeaderName| {
             request
                 .headers()
@@ -110,11 +123,17 @@ where
 
         let trace_id = || {
             let headers = request.headers();
+            let traceparent = || {
+                // The trace ID is the second field of a W3C `traceparent`.
+                let value = headers.get("traceparent")?.to_str().ok()?;
+                value.split('-').nth(1)
+            };
             headers
                 .get("x-b3-traceid")
                 .or_else(|| headers.get("x-request-id"))
-                .or_else(|| headers.get("x-amzn-trace-id"))
                 .and_then(|x| x.to_str().ok())
+                .or_else(traceparent)
+                .or_else(|| headers.get("x-amzn-trace-id")?.to_str().ok())
                 .unwrap_or_default()
         };
 
@@ -139,6 +158,12 @@ where
             processing_ns = field::Empty,
             user_agent = get_header(http::header::USER_AGENT),
             host = get_header(http::header::HOST),
+            server = field::Empty,
+            route = field::Empty,
+            backend = field::Empty,
+            endpoint = field::Empty,
+            retries = field::Empty,
+            response_flags = field::Empty,
         );
 
         // The access log span is only enabled by the `tracing` subscriber if
@@ -151,9 +176,15 @@ where
             };
         }
 
+        let log = AccessLog {
+            span,
+            response_flags: Default::default(),
+        };
+        request.extensions_mut().insert(log.clone());
+
         AccessLogFuture {
             data: Some(ResponseFutureInner {
-                span,
+                log,
                 start: Instant::now(),
                 processing: Duration::from_secs(0),
             }),
@@ -176,7 +207,7 @@ where
             None => return this.inner.try_poll(cx),
         };
 
-        let _enter = data.span.enter();
+        let _enter = data.log.span.enter();
         let poll_start = Instant::now();
 
         let response: http::Response<B2> = match this.inner.try_poll(cx) {
@@ -184,7 +215,11 @@ where
                 data.processing += Instant::now().saturating_duration_since(poll_start);
                 return Poll::Pending;
             }
-            Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+            Poll::Ready(Err(e)) => {
+                data.log.add_response_flag("error");
+                data.log.record_response_flags();
+                return Poll::Ready(Err(e));
+            }
             Poll::Ready(Ok(response)) => response,
         };
 
@@ -193,7 +228,11 @@ where
         let processing_ns =
             (now.saturating_duration_since(poll_start) + data.processing).as_nanos();
 
-        let span = &data.span;
+        if response.headers().contains_key("l5d-proxy-error") {
+            data.log.add_response_flag("proxy_error");
+        }
+        data.log.record_response_flags();
+        let span = &data.log.span;
 
         response
             .headers()
@@ -209,6 +248,51 @@ where
     }
 }
 
+// === impl AccessLog ===
+
+impl AccessLog {
+    /// Records the server that handled the request.
+    pub fn record_server(&self, server: impl fmt::Display) {
+        self.span.record("server", field::display(server));
+    }
+
+    /// Records the route that handled the request.
+    pub fn record_route(&self, route: impl fmt::Display) {
+        self.span.record("route", field::display(route));
+    }
+
+    /// Records the backend to which the request was dispatched.
+    pub fn record_backend(&self, backend: impl fmt::Display) {
+        self.span.record("backend", field::display(backend));
+    }
+
+    /// Records the endpoint to which the request was sent.
+    pub fn record_endpoint(&self, endpoint: SocketAddr) {
+        self.span.record("endpoint", field::display(endpoint));
+    }
+
+    /// Records the number of times the request was retried.
+    pub fn record_retries(&self, retries: usize) {
+        self.span.record("retries", retries as u64);
+    }
+
+    /// Adds a flag describing how the response was produced, e.g. because the
+    /// request was not authorized.
+    pub fn add_response_flag(&self, flag: &'static str) {
+        let mut flags = self.response_flags.lock();
+        if !flags.contains(&flag) {
+            flags.push(flag);
+        }
+    }
+
+    fn record_response_flags(&self) {
+        let flags = self.response_flags.lock();
+        if !flags.is_empty() {
+            self.span.record("response_flags", flags.join(","));
+        }
+    }
+}
+
 #[inline]
 fn now() -> String {
     jiff::Timestamp::now().to_string()
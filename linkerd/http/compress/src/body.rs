@@ -0,0 +1,173 @@
+use crate::{
+    codec::{Codec, DecompressedTooLarge},
+    metrics::BodyMetrics,
+};
+use bytes::{Buf, Bytes};
+use http::{HeaderMap, StatusCode};
+use http_body::{Frame, SizeHint};
+use linkerd_error::Error;
+use pin_project::pin_project;
+use std::{
+    io,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU16, Ordering},
+        Arc,
+    },
+    task::{ready, Context, Poll},
+};
+
+/// A body whose data is compressed or decompressed as it is read.
+#[pin_project]
+pub struct CodecBody<B> {
+    /// The inner body.
+    #[pin]
+    inner: B,
+    /// Transforms the inner body's data. Cleared once the stream completes.
+    codec: Option<Codec>,
+    /// Trailers received from the inner body, held until the codec's remaining
+    /// output has been emitted.
+    trailers: Option<HeaderMap>,
+    /// When set, the codec's output is flushed after each frame of input.
+    flush: bool,
+    /// Records why a decompressed request body failed.
+    failure: Option<RequestFailure>,
+    metrics: BodyMetrics,
+    read: u64,
+    written: u64,
+}
+
+/// Shares the reason a request body could not be decompressed with the
+/// request's response future, which describes it with a status code.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct RequestFailure(Arc<AtomicU16>);
+
+// === impl CodecBody ===
+
+impl<B> CodecBody<B> {
+    pub(crate) fn new(inner: B, codec: Codec, metrics: BodyMetrics) -> Self {
+        Self {
+            inner,
+            codec: Some(codec),
+            trailers: None,
+            flush: false,
+            failure: None,
+            metrics,
+            read: 0,
+            written: 0,
+        }
+    }
+
+    /// Flushes the codec's output after each frame so that streamed data is
+    /// not held back by the encoder.
+    pub(crate) fn flush_frames(self) -> Self {
+        Self {
+            flush: true,
+            ..self
+        }
+    }
+
+    /// Records codec failures to `failure`.
+    pub(crate) fn record_failure(self, failure: RequestFailure) -> Self {
+        Self {
+            failure: Some(failure),
+            ..self
+        }
+    }
+}
+
+impl<B> http_body::Body for CodecBody<B>
+where
+    B: http_body::Body,
+    B::Error: Into<Error>,
+{
+    type Data = Bytes;
+    type Error = Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let mut this = self.project();
+        loop {
+            let Some(codec) = this.codec.as_mut() else {
+                return Poll::Ready(this.trailers.take().map(|t| Ok(Frame::trailers(t))));
+            };
+
+            let output = match ready!(this.inner.as_mut().poll_frame(cx)) {
+                Some(Ok(frame)) => match frame.into_data() {
+                    Ok(mut data) => {
+                        *this.read += data.remaining() as u64;
+                        codec.write(&mut data, *this.flush)
+                    }
+                    Err(frame) => {
+                        // Trailers end the stream, so the codec is finished
+                        // before they are emitted.
+                        *this.trailers = frame.into_trailers().ok();
+                        this.codec.take().expect("codec must be set").finish()
+                    }
+                },
+                Some(Err(error)) => {
+                    *this.codec = None;
+                    return Poll::Ready(Some(Err(error.into())));
+                }
+                None => this.codec.take().expect("codec must be set").finish(),
+            };
+
+            let data = match output {
+                Ok(data) => data,
+                Err(error) => {
+                    *this.codec = None;
+                    *this.trailers = None;
+                    if let Some(failure) = this.failure.as_ref() {
+                        failure.record(&error);
+                    }
+                    return Poll::Ready(Some(Err(error.into())));
+                }
+            };
+            *this.written += data.len() as u64;
+            if this.codec.is_none() {
+                this.metrics.record(*this.read, *this.written);
+            }
+            if !data.is_empty() {
+                return Poll::Ready(Some(Ok(Frame::data(data))));
+            }
+        }
+    }
+
+    #[inline]
+    fn is_end_stream(&self) -> bool {
+        self.codec.is_none() && self.trailers.is_none()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> SizeHint {
+        // The size of the transformed body cannot be known in advance.
+        SizeHint::default()
+    }
+}
+
+// === impl RequestFailure ===
+
+impl RequestFailure {
+    fn record(&self, error: &io::Error) {
+        let status = if error
+            .get_ref()
+            .is_some_and(|e| e.is::<DecompressedTooLarge>())
+        {
+            StatusCode::PAYLOAD_TOO_LARGE
+        } else {
+            StatusCode::BAD_REQUEST
+        };
+        self.0.store(status.as_u16(), Ordering::Release);
+    }
+
+    /// Returns the status that describes the request's failure, if its body
+    /// could not be decompressed.
+    pub(crate) fn status(&self) -> Option<StatusCode> {
+        match self.0.load(Ordering::Acquire) {
+            0 => None,
+            code => StatusCode::from_u16(code).ok(),
+        }
+    }
+}
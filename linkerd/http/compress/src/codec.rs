@@ -0,0 +1,251 @@
+use bytes::{Buf, Bytes};
+use linkerd_http_route::http::filter::Encoding;
+use std::io::{self, Write};
+
+/// A decoded body exceeded its size limit.
+#[derive(Debug, thiserror::Error)]
+#[error("decompressed body exceeds {0} bytes")]
+pub struct DecompressedTooLarge(usize);
+
+/// The size of brotli's internal buffers.
+const BROTLI_BUFFER_SIZE: usize = 4096;
+
+/// Brotli's quality ranges from 0 to 11. Higher levels are too slow to apply
+/// to responses as they are proxied.
+const BROTLI_QUALITY: u32 = 5;
+
+/// The base-2 logarithm of brotli's window size.
+const BROTLI_LG_WINDOW: u32 = 22;
+
+/// Whether a [`Codec`] encodes or decodes the data written to it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Direction {
+    Compress,
+    Decompress,
+}
+
+/// Incrementally encodes or decodes a body's data.
+///
+/// Data is written into the codec, and any output it produces is drained into
+/// a buffer that is returned to the caller.
+pub(crate) enum Codec {
+    GzipEncode(flate2::write::GzEncoder<Sink>),
+    GzipDecode(flate2::write::GzDecoder<Sink>),
+    ZstdEncode(zstd::stream::write::Encoder<'static, Sink>),
+    ZstdDecode(zstd::stream::write::Decoder<'static, Sink>),
+    BrotliEncode(Box<brotli::CompressorWriter<Sink>>),
+    BrotliDecode(Box<brotli::DecompressorWriter<Sink>>),
+}
+
+/// Buffers a codec's output, failing once it exceeds a limit so that a small
+/// encoded input cannot expand without bound.
+pub(crate) struct Sink {
+    buf: Vec<u8>,
+    remaining: usize,
+    limit: usize,
+}
+
+// === impl Codec ===
+
+impl Codec {
+    /// Builds a codec whose total output may not exceed `limit` bytes.
+    pub(crate) fn new(direction: Direction, encoding: Encoding, limit: usize) -> io::Result<Self> {
+        let sink = Sink::new(limit);
+        let codec = match (direction, encoding) {
+            (Direction::Compress, Encoding::Gzip) => Self::GzipEncode(
+                flate2::write::GzEncoder::new(sink, flate2::Compression::default()),
+            ),
+            (Direction::Decompress, Encoding::Gzip) => {
+                Self::GzipDecode(flate2::write::GzDecoder::new(sink))
+            }
+            (Direction::Compress, Encoding::Zstd) => {
+                Self::ZstdEncode(zstd::stream::write::Encoder::new(sink, 0)?)
+            }
+            (Direction::Decompress, Encoding::Zstd) => {
+                Self::ZstdDecode(zstd::stream::write::Decoder::new(sink)?)
+            }
+            (Direction::Compress, Encoding::Brotli) => {
+                Self::BrotliEncode(Box::new(brotli::CompressorWriter::new(
+                    sink,
+                    BROTLI_BUFFER_SIZE,
+                    BROTLI_QUALITY,
+                    BROTLI_LG_WINDOW,
+                )))
+            }
+            (Direction::Decompress, Encoding::Brotli) => Self::BrotliDecode(Box::new(
+                brotli::DecompressorWriter::new(sink, BROTLI_BUFFER_SIZE),
+            )),
+        };
+        Ok(codec)
+    }
+
+    /// Writes a chunk of data into the codec, returning any output that is
+    /// ready.
+    ///
+    /// When `flush` is set, an encoder emits all of the data written so far
+    /// so that it may be delivered without waiting for more input. Decoders
+    /// always emit their output.
+    pub(crate) fn write(&mut self, data: &mut impl Buf, flush: bool) -> io::Result<Bytes> {
+        while data.has_remaining() {
+            let chunk = data.chunk();
+            let n = chunk.len();
+            match self {
+                Self::GzipEncode(w) => w.write_all(chunk)?,
+                Self::GzipDecode(w) => w.write_all(chunk)?,
+                Self::ZstdEncode(w) => w.write_all(chunk)?,
+                Self::ZstdDecode(w) => w.write_all(chunk)?,
+                Self::BrotliEncode(w) => w.write_all(chunk)?,
+                Self::BrotliDecode(w) => w.write_all(chunk)?,
+            }
+            data.advance(n);
+        }
+
+        match self {
+            Self::GzipEncode(w) if flush => w.flush()?,
+            Self::ZstdEncode(w) if flush => w.flush()?,
+            Self::BrotliEncode(w) if flush => w.flush()?,
+            Self::GzipEncode(_) | Self::ZstdEncode(_) | Self::BrotliEncode(_) => {}
+            // Flushing a decoder only drains the output it has buffered, so
+            // decoded data is always emitted as soon as it is available.
+            Self::GzipDecode(w) => w.flush()?,
+            Self::ZstdDecode(w) => w.flush()?,
+            Self::BrotliDecode(w) => w.flush()?,
+        }
+
+        let sink = match self {
+            Self::GzipEncode(w) => w.get_mut(),
+            Self::GzipDecode(w) => w.get_mut(),
+            Self::ZstdEncode(w) => w.get_mut(),
+            Self::ZstdDecode(w) => w.get_mut(),
+            Self::BrotliEncode(w) => w.get_mut(),
+            Self::BrotliDecode(w) => w.get_mut(),
+        };
+        Ok(std::mem::take(&mut sink.buf).into())
+    }
+
+    /// Completes the stream, returning the remaining output.
+    ///
+    /// Fails if a decoder has not received a complete stream.
+    pub(crate) fn finish(self) -> io::Result<Bytes> {
+        let sink = match self {
+            Self::GzipEncode(w) => w.finish()?,
+            Self::GzipDecode(w) => w.finish()?,
+            Self::ZstdEncode(w) => w.finish()?,
+            Self::ZstdDecode(mut w) => {
+                w.flush()?;
+                w.into_inner()
+            }
+            Self::BrotliEncode(w) => w.into_inner(),
+            Self::BrotliDecode(w) => w.into_inner().map_err(|_| {
+                io::Error::new(io::ErrorKind::UnexpectedEof, "incomplete brotli stream")
+            })?,
+        };
+        Ok(sink.buf.into())
+    }
+}
+
+// === impl Sink ===
+
+impl Sink {
+    fn new(limit: usize) -> Self {
+        Self {
+            buf: Vec::new(),
+            remaining: limit,
+            limit,
+        }
+    }
+}
+
+impl Write for Sink {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        if data.len() > self.remaining {
+            return Err(io::Error::other(DecompressedTooLarge(self.limit)));
+        }
+        self.remaining -= data.len();
+        self.buf.extend_from_slice(data);
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roundtrip(encoding: Encoding) {
+        let input = "hello, world! ".repeat(1000);
+
+        let mut encoder = Codec::new(Direction::Compress, encoding, usize::MAX).unwrap();
+        let mut encoded = Vec::new();
+        for chunk in input.as_bytes().chunks(100) {
+            encoded.extend_from_slice(&encoder.write(&mut &chunk[..], false).unwrap());
+        }
+        encoded.extend_from_slice(&encoder.finish().unwrap());
+        assert!(encoded.len() < input.len(), "{encoding} did not compress");
+
+        let mut decoder = Codec::new(Direction::Decompress, encoding, input.len()).unwrap();
+        let mut decoded = Vec::new();
+        for chunk in encoded.chunks(7) {
+            decoded.extend_from_slice(&decoder.write(&mut &chunk[..], false).unwrap());
+        }
+        decoded.extend_from_slice(&decoder.finish().unwrap());
+        assert_eq!(decoded, input.as_bytes(), "{encoding} did not roundtrip");
+    }
+
+    fn encode(encoding: Encoding, input: &[u8]) -> Vec<u8> {
+        let mut encoder = Codec::new(Direction::Compress, encoding, usize::MAX).unwrap();
+        let mut encoded = encoder.write(&mut &input[..], false).unwrap().to_vec();
+        encoded.extend_from_slice(&encoder.finish().unwrap());
+        encoded
+    }
+
+    fn limits_decoded_size(encoding: Encoding) {
+        let input = vec![0u8; 1024 * 1024];
+        let encoded = encode(encoding, &input);
+
+        let mut decoder = Codec::new(Direction::Decompress, encoding, 64 * 1024).unwrap();
+        let error = decoder
+            .write(&mut &encoded[..], false)
+            .and_then(|_| decoder.finish())
+            .expect_err("decoded body must exceed its limit");
+        assert!(
+            error
+                .get_ref()
+                .is_some_and(|e| e.is::<DecompressedTooLarge>()),
+            "{encoding} did not enforce its limit: {error}"
+        );
+    }
+
+    fn flushes(encoding: Encoding) {
+        let mut encoder = Codec::new(Direction::Compress, encoding, usize::MAX).unwrap();
+        let flushed = encoder.write(&mut &b"hello"[..], true).unwrap();
+
+        let mut decoder = Codec::new(Direction::Decompress, encoding, usize::MAX).unwrap();
+        let decoded = decoder.write(&mut &flushed[..], false).unwrap();
+        assert_eq!(&decoded[..], b"hello", "{encoding} did not flush");
+    }
+
+    #[test]
+    fn roundtrips() {
+        roundtrip(Encoding::Gzip);
+        roundtrip(Encoding::Zstd);
+        roundtrip(Encoding::Brotli);
+    }
+
+    #[test]
+    fn limits_decoded_sizes() {
+        limits_decoded_size(Encoding::Gzip);
+        limits_decoded_size(Encoding::Zstd);
+        limits_decoded_size(Encoding::Brotli);
+    }
+
+    #[test]
+    fn flushes_encoders() {
+        flushes(Encoding::Gzip);
+        flushes(Encoding::Zstd);
+        flushes(Encoding::Brotli);
+    }
+}
@@ -0,0 +1,22 @@
+//! HTTP middleware that applies a route's [`Compression`] filter.
+//!
+//! Responses are compressed with the most preferred encoding that the client
+//! accepts when their content type is eligible and they are not known to be
+//! smaller than the filter's minimum size. When enabled, encoded request bodies
+//! are decompressed before they are forwarded, up to the filter's size limit.
+
+#![deny(rust_2018_idioms, clippy::disallowed_methods, clippy::disallowed_types)]
+#![forbid(unsafe_code)]
+
+mod body;
+mod codec;
+mod metrics;
+mod service;
+
+pub use self::{
+    body::CodecBody,
+    codec::DecompressedTooLarge,
+    metrics::CompressMetrics,
+    service::{Compress, NewCompress, ResponseFuture},
+};
+pub use linkerd_http_route::http::filter::{Compression, Encoding};
@@ -0,0 +1,120 @@
+use crate::codec::Direction;
+use linkerd_http_route::http::filter::Encoding;
+use linkerd_metrics::prom::{
+    self, metrics::family::MetricConstructor, Counter, Family, Histogram, Registry, Unit,
+};
+
+/// Metrics describing the bodies compressed and decompressed by route filters.
+#[derive(Clone, Debug)]
+pub struct CompressMetrics {
+    ratio: Family<Labels, Histogram, NewRatioHisto>,
+    decoded_bytes: Family<Labels, Counter>,
+    encoded_bytes: Family<Labels, Counter>,
+}
+
+/// Metrics for a single compressed or decompressed body.
+#[derive(Clone, Debug)]
+pub(crate) struct BodyMetrics {
+    direction: Direction,
+    ratio: Histogram,
+    decoded_bytes: Counter,
+    encoded_bytes: Counter,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, prom::encoding::EncodeLabelSet)]
+struct Labels {
+    direction: &'static str,
+    encoding: &'static str,
+}
+
+/// A constructor for compression ratio [`Histogram`]s in a [`Family`].
+#[derive(Clone, Copy)]
+struct NewRatioHisto;
+
+// === impl CompressMetrics ===
+
+impl Default for CompressMetrics {
+    fn default() -> Self {
+        Self {
+            ratio: Family::new_with_constructor(NewRatioHisto),
+            decoded_bytes: Family::default(),
+            encoded_bytes: Family::default(),
+        }
+    }
+}
+
+impl CompressMetrics {
+    pub fn register(registry: &mut Registry) -> Self {
+        let ratio = Family::new_with_constructor(NewRatioHisto);
+        registry.register(
+            "ratio",
+            "The size of each encoded body relative to its decoded size",
+            ratio.clone(),
+        );
+
+        let decoded_bytes = Family::default();
+        registry.register_with_unit(
+            "decoded",
+            "The total size of bodies before compression or after decompression",
+            Unit::Bytes,
+            decoded_bytes.clone(),
+        );
+
+        let encoded_bytes = Family::default();
+        registry.register_with_unit(
+            "encoded",
+            "The total size of bodies after compression or before decompression",
+            Unit::Bytes,
+            encoded_bytes.clone(),
+        );
+
+        Self {
+            ratio,
+            decoded_bytes,
+            encoded_bytes,
+        }
+    }
+
+    pub(crate) fn body(&self, direction: Direction, encoding: Encoding) -> BodyMetrics {
+        let labels = Labels {
+            direction: match direction {
+                Direction::Compress => "compress",
+                Direction::Decompress => "decompress",
+            },
+            encoding: encoding.as_str(),
+        };
+        BodyMetrics {
+            direction,
+            ratio: self.ratio.get_or_create(&labels).clone(),
+            decoded_bytes: self.decoded_bytes.get_or_create(&labels).clone(),
+            encoded_bytes: self.encoded_bytes.get_or_create(&labels).clone(),
+        }
+    }
+}
+
+// === impl BodyMetrics ===
+
+impl BodyMetrics {
+    /// Records a completed body, given the number of bytes read from the inner
+    /// body and the number of bytes produced by the codec.
+    pub(crate) fn record(&self, read: u64, written: u64) {
+        let (decoded, encoded) = match self.direction {
+            Direction::Compress => (read, written),
+            Direction::Decompress => (written, read),
+        };
+        self.decoded_bytes.inc_by(decoded);
+        self.encoded_bytes.inc_by(encoded);
+        if decoded > 0 {
+            self.ratio
+                .observe(linkerd_metrics::to_f64(encoded) / linkerd_metrics::to_f64(decoded));
+        }
+    }
+}
+
+// === impl NewRatioHisto ===
+
+impl MetricConstructor<Histogram> for NewRatioHisto {
+    fn new_metric(&self) -> Histogram {
+        Histogram::new([0.05, 0.1, 0.25, 0.5, 0.75, 1.0])
+    }
+}
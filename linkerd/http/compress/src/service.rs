@@ -0,0 +1,401 @@
+use crate::{
+    body::{CodecBody, RequestFailure},
+    codec::{Codec, Direction},
+    metrics::CompressMetrics,
+};
+use http::{
+    header::{self, HeaderValue},
+    Method, Request, Response, StatusCode,
+};
+use linkerd_http_box::BoxBody;
+use linkerd_http_route::http::filter::{Compression, Encoding};
+use linkerd_stack::{layer, NewService, Param, Service};
+use pin_project::pin_project;
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{ready, Context, Poll},
+};
+
+/// A [`NewService<T>`] that builds [`Compress`] services from a target's
+/// optional [`Compression`] filter.
+///
+/// Requests whose body cannot be decompressed fail with a `413 Payload Too
+/// Large` when the decoded body exceeds the filter's limit, or a `400 Bad
+/// Request` when it is not validly encoded.
+#[derive(Clone, Debug)]
+pub struct NewCompress<N> {
+    inner: N,
+    metrics: CompressMetrics,
+}
+
+/// Compresses responses and decompresses requests for an inner `S`-typed
+/// [`Service`].
+///
+/// When the target has no [`Compression`] filter, requests and responses pass
+/// through unmodified.
+#[derive(Clone, Debug)]
+pub struct Compress<S> {
+    inner: S,
+    config: Option<Arc<Compression>>,
+    metrics: CompressMetrics,
+}
+
+#[pin_project]
+pub struct ResponseFuture<F> {
+    #[pin]
+    inner: F,
+    compress: Option<(Encoding, Arc<Compression>, CompressMetrics)>,
+    failure: Option<RequestFailure>,
+}
+
+// === impl NewCompress ===
+
+impl<N> NewCompress<N> {
+    pub fn layer(metrics: CompressMetrics) -> impl layer::Layer<N, Service = Self> + Clone {
+        layer::mk(move |inner| Self {
+            inner,
+            metrics: metrics.clone(),
+        })
+    }
+}
+
+impl<T, N> NewService<T> for NewCompress<N>
+where
+    T: Param<Option<Arc<Compression>>>,
+    N: NewService<T>,
+{
+    type Service = Compress<N::Service>;
+
+    fn new_service(&self, target: T) -> Self::Service {
+        let config = target.param();
+        Compress {
+            inner: self.inner.new_service(target),
+            config,
+            metrics: self.metrics.clone(),
+        }
+    }
+}
+
+// === impl Compress ===
+
+impl<S> Service<Request<BoxBody>> for Compress<S>
+where
+    S: Service<Request<BoxBody>, Response = Response<BoxBody>>,
+{
+    type Response = Response<BoxBody>;
+    type Error = S::Error;
+    type Future = ResponseFuture<S::Future>;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<BoxBody>) -> Self::Future {
+        let Some(config) = self.config.clone() else {
+            return ResponseFuture {
+                inner: self.inner.call(req),
+                compress: None,
+                failure: None,
+            };
+        };
+
+        let (req, failure) = if config.decompress_requests {
+            decompress_request(req, config.max_decompressed_request_size, &self.metrics)
+        } else {
+            (req, None)
+        };
+
+        let encoding = if req.method() == Method::HEAD {
+            None
+        } else {
+            config.negotiate(req.headers())
+        };
+
+        ResponseFuture {
+            inner: self.inner.call(req),
+            compress: encoding.map(|enc| (enc, config, self.metrics.clone())),
+            failure,
+        }
+    }
+}
+
+/// Decodes a request body that has a supported content-encoding, limiting
+/// the decoded body to `limit` bytes.
+///
+/// When the body is decoded, a [`RequestFailure`] is returned that records
+/// why decoding failed, if it does.
+fn decompress_request(
+    req: Request<BoxBody>,
+    limit: usize,
+    metrics: &CompressMetrics,
+) -> (Request<BoxBody>, Option<RequestFailure>) {
+    // Only a single, supported encoding can be removed. Requests with stacked
+    // or unknown encodings are forwarded as-is.
+    let Some(encoding) = req
+        .headers()
+        .get(header::CONTENT_ENCODING)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| Encoding::from_token(v.trim()))
+    else {
+        return (req, None);
+    };
+    let codec = match Codec::new(Direction::Decompress, encoding, limit) {
+        Ok(codec) => codec,
+        Err(error) => {
+            tracing::debug!(%error, %encoding, "Failed to initialize decoder");
+            return (req, None);
+        }
+    };
+
+    tracing::trace!(%encoding, limit, "Decompressing request body");
+    let (mut parts, body) = req.into_parts();
+    parts.headers.remove(header::CONTENT_ENCODING);
+    parts.headers.remove(header::CONTENT_LENGTH);
+    let failure = RequestFailure::default();
+    let body = CodecBody::new(body, codec, metrics.body(Direction::Decompress, encoding))
+        .record_failure(failure.clone());
+    (
+        Request::from_parts(parts, BoxBody::new(body)),
+        Some(failure),
+    )
+}
+
+fn compress_response(
+    rsp: Response<BoxBody>,
+    encoding: Encoding,
+    config: &Compression,
+    metrics: &CompressMetrics,
+) -> Response<BoxBody> {
+    use http_body::Body;
+
+    let status = rsp.status();
+    if status.is_informational()
+        || status == StatusCode::NO_CONTENT
+        || status == StatusCode::PARTIAL_CONTENT
+        || status == StatusCode::NOT_MODIFIED
+        || rsp.body().is_end_stream()
+        || !config.is_eligible(rsp.headers())
+    {
+        return rsp;
+    }
+    let codec = match Codec::new(Direction::Compress, encoding, usize::MAX) {
+        Ok(codec) => codec,
+        Err(error) => {
+            tracing::debug!(%error, %encoding, "Failed to initialize encoder");
+            return rsp;
+        }
+    };
+
+    let (mut parts, body) = rsp.into_parts();
+    let headers = &mut parts.headers;
+    // A response without a declared length may be streamed, so each frame is
+    // flushed rather than held until the encoder's buffers fill.
+    let streaming = !headers.contains_key(header::CONTENT_LENGTH);
+    tracing::trace!(%encoding, streaming, "Compressing response body");
+    headers.insert(
+        header::CONTENT_ENCODING,
+        HeaderValue::from_static(encoding.as_str()),
+    );
+    headers.remove(header::CONTENT_LENGTH);
+    headers.remove(header::ACCEPT_RANGES);
+    headers.append(header::VARY, HeaderValue::from_static("accept-encoding"));
+    // A strong validator identifies the uncompressed representation, so it is
+    // weakened to avoid conflating the two.
+    if let Some(etag) = headers.get(header::ETAG) {
+        if etag.as_bytes().starts_with(b"\"") {
+            let mut weak = b"W/".to_vec();
+            weak.extend_from_slice(etag.as_bytes());
+            if let Ok(weak) = HeaderValue::from_bytes(&weak) {
+                headers.insert(header::ETAG, weak);
+            }
+        }
+    }
+
+    let body = CodecBody::new(body, codec, metrics.body(Direction::Compress, encoding));
+    let body = if streaming { body.flush_frames() } else { body };
+    Response::from_parts(parts, BoxBody::new(body))
+}
+
+// === impl ResponseFuture ===
+
+impl<F, E> Future for ResponseFuture<F>
+where
+    F: Future<Output = Result<Response<BoxBody>, E>>,
+{
+    type Output = Result<Response<BoxBody>, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let rsp = match ready!(this.inner.poll(cx)) {
+            Ok(rsp) => rsp,
+            Err(error) => {
+                // The request may have failed because its body could not be
+                // decompressed, in which case the client is told why.
+                let Some(status) = this.failure.as_ref().and_then(|f| f.status()) else {
+                    return Poll::Ready(Err(error));
+                };
+                tracing::debug!(%status, "Request body could not be decompressed");
+                let mut rsp = Response::new(BoxBody::empty());
+                *rsp.status_mut() = status;
+                return Poll::Ready(Ok(rsp));
+            }
+        };
+        let rsp = match this.compress.take() {
+            Some((encoding, config, metrics)) => {
+                compress_response(rsp, encoding, &config, &metrics)
+            }
+            None => rsp,
+        };
+        Poll::Ready(Ok(rsp))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::Bytes;
+    use http_body_util::{BodyExt, Full};
+    use linkerd_error::Error;
+
+    fn config() -> Compression {
+        Compression {
+            min_size: 16,
+            decompress_requests: true,
+            ..Default::default()
+        }
+    }
+
+    async fn collect(body: BoxBody) -> Vec<u8> {
+        body.collect().await.unwrap().to_bytes().to_vec()
+    }
+
+    #[tokio::test]
+    async fn compresses_eligible_responses() {
+        let metrics = CompressMetrics::default();
+        let text = "hello, world! ".repeat(100);
+        let rsp = Response::builder()
+            .header(header::CONTENT_TYPE, "text/plain")
+            .header(header::CONTENT_LENGTH, text.len())
+            .body(BoxBody::new(Full::new(Bytes::from(text.clone()))))
+            .unwrap();
+
+        let rsp = compress_response(rsp, Encoding::Gzip, &config(), &metrics);
+        assert_eq!(rsp.headers()[header::CONTENT_ENCODING], "gzip");
+        assert_eq!(rsp.headers()[header::VARY], "accept-encoding");
+        assert!(!rsp.headers().contains_key(header::CONTENT_LENGTH));
+
+        let encoded = collect(rsp.into_body()).await;
+        assert!(encoded.len() < text.len());
+
+        // Decompress the encoded body as a request.
+        let req = Request::builder()
+            .header(header::CONTENT_ENCODING, "gzip")
+            .body(BoxBody::new(Full::new(Bytes::from(encoded))))
+            .unwrap();
+        let (req, _) = decompress_request(req, text.len(), &metrics);
+        assert!(!req.headers().contains_key(header::CONTENT_ENCODING));
+        assert_eq!(collect(req.into_body()).await, text.as_bytes());
+    }
+
+    #[tokio::test]
+    async fn skips_ineligible_responses() {
+        let metrics = CompressMetrics::default();
+        let rsp = Response::builder()
+            .header(header::CONTENT_TYPE, "text/plain")
+            .header(header::CONTENT_LENGTH, 5)
+            .body(BoxBody::from_static("hello"))
+            .unwrap();
+        let rsp = compress_response(rsp, Encoding::Gzip, &config(), &metrics);
+        assert!(!rsp.headers().contains_key(header::CONTENT_ENCODING));
+        assert_eq!(collect(rsp.into_body()).await, b"hello");
+
+        let rsp = Response::builder()
+            .status(StatusCode::NOT_MODIFIED)
+            .header(header::CONTENT_TYPE, "text/plain")
+            .body(BoxBody::from_static("hello, world! hello, world!"))
+            .unwrap();
+        let rsp = compress_response(rsp, Encoding::Gzip, &config(), &metrics);
+        assert!(!rsp.headers().contains_key(header::CONTENT_ENCODING));
+    }
+
+    #[tokio::test]
+    async fn flushes_streaming_responses() {
+        use futures::StreamExt;
+
+        let metrics = CompressMetrics::default();
+        // The body yields one frame and then never completes.
+        let frames = futures::stream::iter([Ok::<_, Error>(http_body::Frame::data(
+            Bytes::from_static(b"data: hello, world!"),
+        ))])
+        .chain(futures::stream::pending());
+        let rsp = Response::builder()
+            .header(header::CONTENT_TYPE, "text/plain")
+            .body(BoxBody::new(http_body_util::StreamBody::new(frames)))
+            .unwrap();
+
+        let rsp = compress_response(rsp, Encoding::Gzip, &config(), &metrics);
+        assert_eq!(rsp.headers()[header::CONTENT_ENCODING], "gzip");
+        let mut body = rsp.into_body();
+        let frame = body
+            .frame()
+            .await
+            .expect("body must yield a frame")
+            .expect("frame must not fail");
+        let Ok(mut encoded) = frame.into_data() else {
+            panic!("frame must be data");
+        };
+
+        let mut decoder = Codec::new(Direction::Decompress, Encoding::Gzip, usize::MAX).unwrap();
+        let decoded = decoder.write(&mut encoded, false).unwrap();
+        assert_eq!(&decoded[..], b"data: hello, world!");
+    }
+
+    #[tokio::test]
+    async fn rejects_oversized_requests() {
+        let config = Compression {
+            max_decompressed_request_size: 1024,
+            ..config()
+        };
+        let encoded = {
+            let mut encoder = Codec::new(Direction::Compress, Encoding::Gzip, usize::MAX).unwrap();
+            let mut encoded = encoder
+                .write(&mut &[0u8; 4096][..], false)
+                .unwrap()
+                .to_vec();
+            encoded.extend_from_slice(&encoder.finish().unwrap());
+            encoded
+        };
+        let status = send_encoded(config, Bytes::from(encoded)).await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[tokio::test]
+    async fn rejects_invalid_requests() {
+        let status = send_encoded(config(), Bytes::from_static(b"not gzip")).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    /// Sends a gzip-encoded request through a [`Compress`] service whose
+    /// inner service reads the request body, returning the response status.
+    async fn send_encoded(config: Compression, body: Bytes) -> StatusCode {
+        let mut svc = Compress {
+            inner: linkerd_stack::service_fn(|req: Request<BoxBody>| async move {
+                req.into_body().collect().await?;
+                Ok::<_, Error>(Response::new(BoxBody::empty()))
+            }),
+            config: Some(Arc::new(config)),
+            metrics: CompressMetrics::default(),
+        };
+        let req = Request::builder()
+            .header(header::CONTENT_ENCODING, "gzip")
+            .body(BoxBody::new(Full::new(body)))
+            .unwrap();
+        svc.call(req)
+            .await
+            .expect("response must not fail")
+            .status()
+    }
+}
@@ -12,19 +12,31 @@ use tokio::time;
 use tracing::{debug, trace};
 
 mod metrics;
+pub mod sniff;
 
-pub use self::metrics::{DetectMetrics, DetectMetricsFamilies};
+pub use self::{
+    metrics::{DetectMetrics, DetectMetricsFamilies},
+    sniff::{OpaqueProtocol, Sniff, Sniffers},
+};
 
 #[derive(Clone, Debug, Default)]
 pub struct DetectParams {
     pub read_timeout: time::Duration,
     pub metrics: metrics::DetectMetrics,
+
+    /// Identifies opaque protocols when the stream is not HTTP.
+    pub sniffers: Sniffers,
+
+    /// When set, the stream is treated as a server-first protocol if the
+    /// client sends no data within this timeout.
+    pub server_first_timeout: Option<time::Duration>,
 }
 
 #[derive(Debug, Clone)]
 pub enum Detection {
     NotHttp,
     Http(Variant),
+    Opaque(OpaqueProtocol),
     ReadTimeout(time::Duration),
 }
 
@@ -157,9 +169,15 @@ async fn detect<I: io::AsyncRead + Send + Unpin + 'static>(
 ) -> io::Result<Detection> {
     debug_assert!(buf.capacity() > 0, "buffer must have capacity");
 
-    trace!(capacity = buf.capacity(), timeout = ?params.read_timeout, "Reading");
-    let sz = match time::timeout(params.read_timeout, io.read_buf(buf)).await {
+    // Servers that speak first would leave us waiting for the full read
+    // timeout, so give up early when the client is silent.
+    let timeout = params.server_first_timeout.unwrap_or(params.read_timeout);
+    trace!(capacity = buf.capacity(), ?timeout, "Reading");
+    let sz = match time::timeout(timeout, io.read_buf(buf)).await {
         Ok(res) => res?,
+        Err(_) if params.server_first_timeout.is_some() => {
+            return Ok(Detection::Opaque(OpaqueProtocol::SERVER_FIRST));
+        }
         Err(_) => return Ok(Detection::ReadTimeout(params.read_timeout)),
     };
 
@@ -192,6 +210,11 @@ async fn detect<I: io::AsyncRead + Send + Unpin + 'static>(
         }
     }
 
+    trace!("Sniffing opaque protocols");
+    if let Some(protocol) = params.sniffers.sniff(&buf[..]) {
+        return Ok(Detection::Opaque(protocol));
+    }
+
     Ok(Detection::NotHttp)
 }
 
@@ -315,4 +338,58 @@ mod tests {
         assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof, "{err:?}");
         assert_eq!(&buf[..], b"");
     }
+
+    #[tokio::test(flavor = "current_thread", start_paused = true)]
+    async fn opaque() {
+        let _trace = linkerd_tracing::test::trace_init();
+
+        let params = DetectParams {
+            read_timeout: time::Duration::from_millis(1),
+            ..Default::default()
+        };
+        const PING: &[u8] = b"*1\r\n$4\r\nPING\r\n";
+        let mut buf = BytesMut::with_capacity(1024);
+        let mut io = io::Builder::new().read(PING).build();
+        let kind = detect(&params, &mut io, &mut buf).await.unwrap();
+        assert!(
+            matches!(kind, Detection::Opaque(OpaqueProtocol::REDIS)),
+            "{kind:?}"
+        );
+        assert_eq!(&buf[..], PING);
+
+        let params = DetectParams {
+            sniffers: Sniffers::none(),
+            ..params
+        };
+        let mut buf = BytesMut::with_capacity(1024);
+        let mut io = io::Builder::new().read(PING).build();
+        let kind = detect(&params, &mut io, &mut buf).await.unwrap();
+        assert!(matches!(kind, Detection::NotHttp), "{kind:?}");
+    }
+
+    #[tokio::test(flavor = "current_thread", start_paused = true)]
+    async fn server_first() {
+        let _trace = linkerd_tracing::test::trace_init();
+
+        let params = DetectParams {
+            read_timeout: time::Duration::from_secs(10),
+            server_first_timeout: Some(time::Duration::from_millis(100)),
+            ..Default::default()
+        };
+        let mut buf = BytesMut::with_capacity(1024);
+        let mut io = io::Builder::new()
+            .wait(time::Duration::from_millis(200))
+            .build();
+        let kind = detect(&params, &mut io, &mut buf).await.unwrap();
+        assert!(
+            matches!(kind, Detection::Opaque(OpaqueProtocol::SERVER_FIRST)),
+            "{kind:?}"
+        );
+
+        // Clients that speak first are detected as usual.
+        let mut buf = BytesMut::with_capacity(1024);
+        let mut io = io::Builder::new().read(HTTP11_LINE).build();
+        let kind = detect(&params, &mut io, &mut buf).await.unwrap();
+        assert_eq!(kind.variant(), Some(Variant::Http1), "{kind:?}");
+    }
 }
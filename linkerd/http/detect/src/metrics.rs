@@ -1,4 +1,6 @@
+use crate::OpaqueProtocol;
 use linkerd_http_variant::Variant;
+use parking_lot::RwLock;
 use prometheus_client::{
     encoding::EncodeLabelSet,
     metrics::{
@@ -8,7 +10,7 @@ use prometheus_client::{
     },
     registry::{Registry, Unit},
 };
-use std::{fmt::Debug, hash::Hash};
+use std::{collections::HashMap, fmt::Debug, hash::Hash, sync::Arc};
 use tokio::time;
 
 #[derive(Clone, Debug)]
@@ -18,6 +20,7 @@ where
 {
     duration: Family<L, Histogram, MkDurations>,
     results: Family<DetectLabels<L>, Counter>,
+    custom: Arc<RwLock<HashMap<L, CustomResults>>>,
 }
 
 #[derive(Clone, Debug)]
@@ -28,8 +31,29 @@ pub struct DetectMetrics {
     h2: Counter,
     read_timeout: Counter,
     error: Counter,
+    opaque: OpaqueResults,
 }
 
+/// Results counters for the opaque protocols identified by this crate's
+/// sniffers.
+#[derive(Clone, Debug)]
+struct OpaqueResults {
+    tls: Counter,
+    proxy_protocol: Counter,
+    postgres: Counter,
+    redis: Counter,
+    kafka: Counter,
+    server_first: Counter,
+    custom: CustomResults,
+}
+
+/// Gets the results counter for protocols identified by custom sniffers, since
+/// they are not known ahead of time.
+///
+/// These are cached by label set so that they are only built once.
+#[derive(Clone)]
+struct CustomResults(Arc<dyn Fn(OpaqueProtocol) -> Counter + Send + Sync>);
+
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 struct DetectLabels<L>
 where
@@ -44,6 +68,7 @@ enum DetectResult {
     NotHttp,
     Http1,
     H2,
+    Opaque(OpaqueProtocol),
     ReadTimeout,
     Error,
 }
@@ -61,6 +86,7 @@ where
         Self {
             duration: Family::new_with_constructor(MkDurations),
             results: Family::default(),
+            custom: Default::default(),
         }
     }
 }
@@ -81,47 +107,64 @@ where
         let results = Family::default();
         reg.register("results", "Protocol detection results", results.clone());
 
-        Self { duration, results }
+        Self {
+            duration,
+            results,
+            custom: Default::default(),
+        }
     }
 
     pub fn metrics(&self, labels: L) -> DetectMetrics {
         let duration = (*self.duration.get_or_create(&labels)).clone();
-
-        let not_http = (*self.results.get_or_create(&DetectLabels {
-            result: DetectResult::NotHttp,
-            labels: labels.clone(),
-        }))
-        .clone();
-        let http1 = (*self.results.get_or_create(&DetectLabels {
-            result: DetectResult::Http1,
-            labels: labels.clone(),
-        }))
-        .clone();
-        let h2 = (*self.results.get_or_create(&DetectLabels {
-            result: DetectResult::H2,
-            labels: labels.clone(),
-        }))
-        .clone();
-        let read_timeout = (*self.results.get_or_create(&DetectLabels {
-            result: DetectResult::ReadTimeout,
-            labels: labels.clone(),
-        }))
-        .clone();
-        let error = (*self.results.get_or_create(&DetectLabels {
-            result: DetectResult::Error,
-            labels,
-        }))
-        .clone();
+        let result = |result| {
+            (*self.results.get_or_create(&DetectLabels {
+                result,
+                labels: labels.clone(),
+            }))
+            .clone()
+        };
+        let opaque = |protocol| result(DetectResult::Opaque(protocol));
 
         DetectMetrics {
             duration,
-            not_http,
-            http1,
-            h2,
-            read_timeout,
-            error,
+            not_http: result(DetectResult::NotHttp),
+            http1: result(DetectResult::Http1),
+            h2: result(DetectResult::H2),
+            read_timeout: result(DetectResult::ReadTimeout),
+            error: result(DetectResult::Error),
+            opaque: OpaqueResults {
+                tls: opaque(OpaqueProtocol::TLS),
+                proxy_protocol: opaque(OpaqueProtocol::PROXY_PROTOCOL),
+                postgres: opaque(OpaqueProtocol::POSTGRES),
+                redis: opaque(OpaqueProtocol::REDIS),
+                kafka: opaque(OpaqueProtocol::KAFKA),
+                server_first: opaque(OpaqueProtocol::SERVER_FIRST),
+                custom: self.custom_results(&labels),
+            },
         }
     }
+
+    fn custom_results(&self, labels: &L) -> CustomResults {
+        if let Some(custom) = self.custom.read().get(labels) {
+            return custom.clone();
+        }
+
+        self.custom
+            .write()
+            .entry(labels.clone())
+            .or_insert_with(|| {
+                let results = self.results.clone();
+                let labels = labels.clone();
+                CustomResults(Arc::new(move |protocol| {
+                    (*results.get_or_create(&DetectLabels {
+                        result: DetectResult::Opaque(protocol),
+                        labels: labels.clone(),
+                    }))
+                    .clone()
+                }))
+            })
+            .clone()
+    }
 }
 
 // === impl DetectMetrics ===
@@ -135,6 +178,15 @@ impl Default for DetectMetrics {
             h2: Counter::default(),
             read_timeout: Counter::default(),
             error: Counter::default(),
+            opaque: OpaqueResults {
+                tls: Counter::default(),
+                proxy_protocol: Counter::default(),
+                postgres: Counter::default(),
+                redis: Counter::default(),
+                kafka: Counter::default(),
+                server_first: Counter::default(),
+                custom: CustomResults(Arc::new(|_| Counter::default())),
+            },
         }
     }
 }
@@ -149,6 +201,7 @@ impl DetectMetrics {
             Ok(super::Detection::NotHttp) => self.not_http.inc(),
             Ok(super::Detection::Http(Variant::Http1)) => self.http1.inc(),
             Ok(super::Detection::Http(Variant::H2)) => self.h2.inc(),
+            Ok(super::Detection::Opaque(protocol)) => self.opaque.inc(*protocol),
             Ok(super::Detection::ReadTimeout(_)) => self.read_timeout.inc(),
             Err(_) => self.error.inc(),
         };
@@ -174,6 +227,7 @@ where
                 DetectResult::NotHttp => "not_http",
                 DetectResult::Http1 => "http/1",
                 DetectResult::H2 => "http/2",
+                DetectResult::Opaque(protocol) => protocol.as_str(),
                 DetectResult::ReadTimeout => "read_timeout",
                 DetectResult::Error => "error",
             },
@@ -186,6 +240,30 @@ where
     }
 }
 
+// === impl OpaqueResults ===
+
+impl OpaqueResults {
+    fn inc(&self, protocol: OpaqueProtocol) -> u64 {
+        match protocol {
+            OpaqueProtocol::TLS => self.tls.inc(),
+            OpaqueProtocol::PROXY_PROTOCOL => self.proxy_protocol.inc(),
+            OpaqueProtocol::POSTGRES => self.postgres.inc(),
+            OpaqueProtocol::REDIS => self.redis.inc(),
+            OpaqueProtocol::KAFKA => self.kafka.inc(),
+            OpaqueProtocol::SERVER_FIRST => self.server_first.inc(),
+            protocol => (self.custom.0)(protocol).inc(),
+        }
+    }
+}
+
+// === impl CustomResults ===
+
+impl Debug for CustomResults {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("CustomResults").finish_non_exhaustive()
+    }
+}
+
 // === impl MkDurations ===
 
 impl MetricConstructor<Histogram> for MkDurations {
@@ -193,3 +271,43 @@ impl MetricConstructor<Histogram> for MkDurations {
         Histogram::new([0.001, 0.1])
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Detection;
+    use prometheus_client::encoding::text::encode;
+
+    #[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+    struct Labels {
+        port: u16,
+    }
+
+    #[test]
+    fn counts_opaque_protocols() {
+        let mut registry = Registry::default();
+        let families = DetectMetricsFamilies::register(&mut registry);
+
+        let observe = |protocol| {
+            families.metrics(Labels { port: 5432 }).observe(
+                &Ok(Detection::Opaque(protocol)),
+                time::Duration::from_millis(1),
+            )
+        };
+        observe(OpaqueProtocol::POSTGRES);
+        observe(OpaqueProtocol::POSTGRES);
+        observe(OpaqueProtocol::new("custom"));
+
+        let mut text = String::new();
+        encode(&mut text, &registry).unwrap();
+        assert!(
+            text.contains("results_total{result=\"postgres\",port=\"5432\"} 2"),
+            "{text}"
+        );
+        assert!(
+            text.contains("results_total{result=\"custom\",port=\"5432\"} 1"),
+            "{text}"
+        );
+        assert_eq!(families.custom.read().len(), 1);
+    }
+}
@@ -0,0 +1,287 @@
+//! Identifies common non-HTTP protocols from the first bytes sent by a client.
+//!
+//! Sniffing is only used to describe opaque connections (e.g. in metrics); it
+//! never changes how a connection is proxied.
+
+use linkerd_tls::server::client_hello;
+use std::{fmt, sync::Arc};
+
+/// Names an opaque protocol that was identified during detection.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct OpaqueProtocol(&'static str);
+
+/// Attempts to identify a protocol from the initial bytes read from a client.
+pub trait Sniff: fmt::Debug + Send + Sync + 'static {
+    fn sniff(&self, buf: &[u8]) -> Option<OpaqueProtocol>;
+}
+
+/// An ordered set of [`Sniff`] implementations.
+///
+/// The default set includes each of the sniffers provided by this module.
+#[derive(Clone, Debug)]
+pub struct Sniffers(Arc<[Arc<dyn Sniff>]>);
+
+/// Matches a TLS ClientHello.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Tls;
+
+/// Matches a PROXY protocol (v1 or v2) header.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct ProxyProtocol;
+
+/// Matches a PostgreSQL startup, SSL/GSSAPI negotiation, or cancel request.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Postgres;
+
+/// Matches a Redis command encoded as a RESP array.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Redis;
+
+/// Matches a Kafka request header.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Kafka;
+
+const PROXY_V1_PREFIX: &[u8] = b"PROXY ";
+const PROXY_V2_SIGNATURE: &[u8] = b"\r\n\r\n\0\r\nQUIT\n";
+
+const POSTGRES_PROTOCOL_V3: u32 = 196_608;
+const POSTGRES_CANCEL_REQUEST: u32 = 80_877_102;
+const POSTGRES_SSL_REQUEST: u32 = 80_877_103;
+const POSTGRES_GSSENC_REQUEST: u32 = 80_877_104;
+const POSTGRES_MAX_STARTUP_LEN: u32 = 10_000;
+
+// The largest API key assigned by Apache Kafka, with some headroom for newer
+// versions.
+const KAFKA_MAX_API_KEY: i16 = 100;
+const KAFKA_MAX_API_VERSION: i16 = 20;
+const KAFKA_MAX_REQUEST_LEN: i32 = 100 * 1024 * 1024;
+
+// === impl OpaqueProtocol ===
+
+impl OpaqueProtocol {
+    pub const TLS: Self = Self("tls");
+    pub const PROXY_PROTOCOL: Self = Self("proxy_protocol");
+    pub const POSTGRES: Self = Self("postgres");
+    pub const REDIS: Self = Self("redis");
+    pub const KAFKA: Self = Self("kafka");
+
+    /// Indicates that the client sent no data before the server-first timeout
+    /// elapsed, as is expected for protocols like MySQL and SMTP.
+    pub const SERVER_FIRST: Self = Self("server_first");
+
+    /// Names a protocol identified by a custom [`Sniff`] implementation.
+    pub const fn new(name: &'static str) -> Self {
+        Self(name)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        self.0
+    }
+}
+
+impl fmt::Display for OpaqueProtocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+// === impl Sniffers ===
+
+impl Sniffers {
+    pub fn new(sniffers: impl IntoIterator<Item = Arc<dyn Sniff>>) -> Self {
+        Self(sniffers.into_iter().collect())
+    }
+
+    /// Returns an empty set that never identifies a protocol.
+    pub fn none() -> Self {
+        Self::new(None)
+    }
+
+    /// Returns the protocol identified by the first matching sniffer.
+    pub fn sniff(&self, buf: &[u8]) -> Option<OpaqueProtocol> {
+        self.0.iter().find_map(|s| s.sniff(buf))
+    }
+}
+
+impl Default for Sniffers {
+    fn default() -> Self {
+        // Postgres is checked before Kafka, since a Postgres startup message is
+        // also a plausible Kafka request header.
+        Self::new([
+            Arc::new(Tls) as Arc<dyn Sniff>,
+            Arc::new(ProxyProtocol),
+            Arc::new(Postgres),
+            Arc::new(Redis),
+            Arc::new(Kafka),
+        ])
+    }
+}
+
+// === impl Tls ===
+
+impl Sniff for Tls {
+    fn sniff(&self, buf: &[u8]) -> Option<OpaqueProtocol> {
+        match client_hello::parse_sni(buf) {
+            Ok(Some(_)) => Some(OpaqueProtocol::TLS),
+            // The ClientHello may span multiple reads, so a valid record header
+            // is enough of a signal.
+            Err(client_hello::Incomplete) if buf.len() >= 5 => Some(OpaqueProtocol::TLS),
+            _ => None,
+        }
+    }
+}
+
+// === impl ProxyProtocol ===
+
+impl Sniff for ProxyProtocol {
+    fn sniff(&self, buf: &[u8]) -> Option<OpaqueProtocol> {
+        (buf.starts_with(PROXY_V1_PREFIX) || buf.starts_with(PROXY_V2_SIGNATURE))
+            .then_some(OpaqueProtocol::PROXY_PROTOCOL)
+    }
+}
+
+// === impl Postgres ===
+
+impl Sniff for Postgres {
+    fn sniff(&self, buf: &[u8]) -> Option<OpaqueProtocol> {
+        let len = read_u32(buf, 0)?;
+        let code = read_u32(buf, 4)?;
+        let matches = match code {
+            POSTGRES_PROTOCOL_V3 => (8..=POSTGRES_MAX_STARTUP_LEN).contains(&len),
+            POSTGRES_SSL_REQUEST | POSTGRES_GSSENC_REQUEST => len == 8,
+            POSTGRES_CANCEL_REQUEST => len == 16,
+            _ => false,
+        };
+        matches.then_some(OpaqueProtocol::POSTGRES)
+    }
+}
+
+// === impl Redis ===
+
+impl Sniff for Redis {
+    fn sniff(&self, buf: &[u8]) -> Option<OpaqueProtocol> {
+        // Commands are sent as an array of bulk strings, e.g.
+        // `*1\r\n$4\r\nPING\r\n`.
+        let rest = buf.strip_prefix(b"*")?;
+        let digits = rest.iter().take_while(|b| b.is_ascii_digit()).count();
+        if digits == 0 {
+            return None;
+        }
+        rest[digits..]
+            .starts_with(b"\r\n$")
+            .then_some(OpaqueProtocol::REDIS)
+    }
+}
+
+// === impl Kafka ===
+
+impl Sniff for Kafka {
+    fn sniff(&self, buf: &[u8]) -> Option<OpaqueProtocol> {
+        // A request is prefixed by its length and begins with a header
+        // containing the API key, API version, correlation ID, and a nullable
+        // client ID string.
+        let len = read_u32(buf, 0)? as i32;
+        let api_key = read_u16(buf, 4)? as i16;
+        let api_version = read_u16(buf, 6)? as i16;
+        let client_id_len = read_u16(buf, 12)? as i16;
+        if !(10..=KAFKA_MAX_REQUEST_LEN).contains(&len)
+            || !(0..=KAFKA_MAX_API_KEY).contains(&api_key)
+            || !(0..=KAFKA_MAX_API_VERSION).contains(&api_version)
+            || client_id_len < -1
+            || i32::from(client_id_len) > len - 10
+        {
+            return None;
+        }
+
+        // Client IDs are human-readable, so check whatever was read.
+        let client_id = buf.get(14..)?;
+        let client_id = &client_id[..client_id.len().min(client_id_len.max(0) as usize)];
+        client_id
+            .iter()
+            .all(|b| b.is_ascii_graphic())
+            .then_some(OpaqueProtocol::KAFKA)
+    }
+}
+
+fn read_u32(buf: &[u8], at: usize) -> Option<u32> {
+    let bytes = buf.get(at..at + 4)?;
+    Some(u32::from_be_bytes(bytes.try_into().ok()?))
+}
+
+fn read_u16(buf: &[u8], at: usize) -> Option<u16> {
+    let bytes = buf.get(at..at + 2)?;
+    Some(u16::from_be_bytes(bytes.try_into().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sniff(buf: &[u8]) -> Option<OpaqueProtocol> {
+        Sniffers::default().sniff(buf)
+    }
+
+    #[test]
+    fn tls() {
+        // A truncated ClientHello record.
+        let buf = b"\x16\x03\x01\x02\x00\x01\x00\x01\xfc\x03\x03";
+        assert_eq!(sniff(buf), Some(OpaqueProtocol::TLS));
+        assert_eq!(sniff(b"\x16\x03"), None);
+    }
+
+    #[test]
+    fn proxy_protocol() {
+        let v1 = b"PROXY TCP4 192.0.2.1 192.0.2.2 56324 443\r\n";
+        assert_eq!(sniff(v1), Some(OpaqueProtocol::PROXY_PROTOCOL));
+        let v2 = b"\r\n\r\n\0\r\nQUIT\n\x21\x11\0\x0c";
+        assert_eq!(sniff(v2), Some(OpaqueProtocol::PROXY_PROTOCOL));
+    }
+
+    #[test]
+    fn postgres() {
+        let ssl = b"\0\0\0\x08\x04\xd2\x16\x2f";
+        assert_eq!(sniff(ssl), Some(OpaqueProtocol::POSTGRES));
+        let startup = b"\0\0\0\x29\0\x03\0\0user\0postgres\0database\0postgres\0\0";
+        assert_eq!(sniff(startup), Some(OpaqueProtocol::POSTGRES));
+        // An SSLRequest has a fixed length.
+        assert_eq!(sniff(b"\0\0\0\x10\x04\xd2\x16\x2f"), None);
+    }
+
+    #[test]
+    fn redis() {
+        let ping = b"*1\r\n$4\r\nPING\r\n";
+        assert_eq!(sniff(ping), Some(OpaqueProtocol::REDIS));
+        assert_eq!(sniff(b"*\r\n$4\r\nPING\r\n"), None);
+        assert_eq!(sniff(b"PING\r\n"), None);
+    }
+
+    #[test]
+    fn kafka() {
+        // An ApiVersions v3 request from client `rdkafka`.
+        let api_versions = b"\0\0\0\x15\0\x12\0\x03\0\0\0\x01\0\x07rdkafka\0";
+        assert_eq!(sniff(api_versions), Some(OpaqueProtocol::KAFKA));
+        // The client ID must be readable.
+        let garbage = b"\0\0\0\x15\0\x12\0\x03\0\0\0\x01\0\x07\x01\x02\x03\x04\x05\x06\x07\0";
+        assert_eq!(sniff(garbage), None);
+    }
+
+    #[test]
+    fn custom() {
+        #[derive(Debug)]
+        struct Ping;
+        impl Sniff for Ping {
+            fn sniff(&self, buf: &[u8]) -> Option<OpaqueProtocol> {
+                buf.starts_with(b"PING")
+                    .then_some(OpaqueProtocol::new("ping"))
+            }
+        }
+
+        let sniffers = Sniffers::new([Arc::new(Ping) as Arc<dyn Sniff>]);
+        assert_eq!(
+            sniffers.sniff(b"PING\r\n"),
+            Some(OpaqueProtocol::new("ping"))
+        );
+        assert_eq!(sniffers.sniff(b"*1\r\n$4\r\nPING\r\n"), None);
+        assert_eq!(Sniffers::none().sniff(b"*1\r\n$4\r\nPING\r\n"), None);
+    }
+}
@@ -0,0 +1,292 @@
+use crate::Encoding;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use http::HeaderMap;
+use http_body::{Body, Frame};
+use linkerd_error::Error;
+use pin_project::pin_project;
+use std::{
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+/// Decodes the base64-encoded messages of an `application/grpc-web-text`
+/// request body.
+#[pin_project]
+#[derive(Debug)]
+pub struct RequestBody<B> {
+    #[pin]
+    inner: B,
+    buf: BytesMut,
+}
+
+/// Encodes a gRPC response body as a gRPC-Web response body.
+///
+/// The response's trailers are written as a final, flagged frame in the body.
+/// For `application/grpc-web-text` responses, the body is also
+/// base64-encoded.
+#[pin_project]
+#[derive(Debug)]
+pub struct ResponseBody<B> {
+    #[pin]
+    inner: B,
+    encoding: Encoding,
+    buf: BytesMut,
+    eos: bool,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum InvalidText {
+    #[error("invalid base64 in gRPC-Web text body: {0}")]
+    Decode(#[from] base64::DecodeError),
+
+    #[error("gRPC-Web text body ended with a partial base64 group")]
+    Truncated,
+}
+
+/// Marks a gRPC-Web frame as containing trailers rather than a message.
+const TRAILERS_FLAG: u8 = 0x80;
+
+// === impl RequestBody ===
+
+impl<B> RequestBody<B> {
+    pub fn new(inner: B) -> Self {
+        Self {
+            inner,
+            buf: BytesMut::new(),
+        }
+    }
+}
+
+impl<B> Body for RequestBody<B>
+where
+    B: Body,
+    B::Error: Into<Error>,
+{
+    type Data = Bytes;
+    type Error = Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Bytes>, Error>>> {
+        let mut this = self.project();
+        loop {
+            let frame = match ready!(this.inner.as_mut().poll_frame(cx)) {
+                Some(Ok(frame)) => frame,
+                Some(Err(error)) => return Poll::Ready(Some(Err(error.into()))),
+                None if this.buf.is_empty() => return Poll::Ready(None),
+                None => {
+                    this.buf.clear();
+                    return Poll::Ready(Some(Err(InvalidText::Truncated.into())));
+                }
+            };
+
+            match frame.into_data() {
+                Ok(mut data) => {
+                    while data.has_remaining() {
+                        let chunk = data.chunk();
+                        let len = chunk.len();
+                        this.buf.extend_from_slice(chunk);
+                        data.advance(len);
+                    }
+                    let decoded = decode_text(this.buf).map_err(InvalidText::from)?;
+                    if !decoded.is_empty() {
+                        return Poll::Ready(Some(Ok(Frame::data(decoded))));
+                    }
+                }
+                Err(frame) => {
+                    if let Ok(trailers) = frame.into_trailers() {
+                        return Poll::Ready(Some(Ok(Frame::trailers(trailers))));
+                    }
+                }
+            }
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream() && self.buf.is_empty()
+    }
+}
+
+/// Decodes as much of `buf` as forms complete base64 groups.
+///
+/// Clients may pad each message individually, so a padded group ends a
+/// segment that must be decoded on its own.
+fn decode_text(buf: &mut BytesMut) -> Result<Bytes, base64::DecodeError> {
+    let mut decoded = Vec::new();
+    loop {
+        let end = match buf.iter().position(|&b| b == b'=') {
+            Some(pad) => (pad / 4 + 1) * 4,
+            None => buf.len() - buf.len() % 4,
+        };
+        if end == 0 || end > buf.len() {
+            return Ok(decoded.into());
+        }
+        let segment = buf.split_to(end);
+        STANDARD.decode_vec(&segment, &mut decoded)?;
+    }
+}
+
+// === impl ResponseBody ===
+
+impl<B> ResponseBody<B> {
+    pub fn new(inner: B, encoding: Encoding) -> Self {
+        Self {
+            inner,
+            encoding,
+            buf: BytesMut::new(),
+            eos: false,
+        }
+    }
+}
+
+impl<B> Body for ResponseBody<B>
+where
+    B: Body,
+    B::Error: Into<Error>,
+{
+    type Data = Bytes;
+    type Error = Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Bytes>, Error>>> {
+        let mut this = self.project();
+        loop {
+            if *this.eos {
+                // Text bodies are encoded in three-byte groups so that padding
+                // is only written at the end of the stream.
+                if this.buf.is_empty() {
+                    return Poll::Ready(None);
+                }
+                let rest = this.buf.split();
+                return Poll::Ready(Some(Ok(Frame::data(STANDARD.encode(rest).into()))));
+            }
+
+            let frame = match ready!(this.inner.as_mut().poll_frame(cx)) {
+                Some(Ok(frame)) => frame,
+                Some(Err(error)) => return Poll::Ready(Some(Err(error.into()))),
+                None => {
+                    *this.eos = true;
+                    continue;
+                }
+            };
+            let bytes = match frame.into_data() {
+                Ok(mut data) => data.copy_to_bytes(data.remaining()),
+                Err(frame) => match frame.into_trailers() {
+                    Ok(trailers) => {
+                        *this.eos = true;
+                        encode_trailers(&trailers)
+                    }
+                    Err(_) => continue,
+                },
+            };
+
+            match this.encoding {
+                Encoding::Binary if bytes.is_empty() => {}
+                Encoding::Binary => return Poll::Ready(Some(Ok(Frame::data(bytes)))),
+                Encoding::Text => {
+                    this.buf.extend_from_slice(&bytes);
+                    let len = this.buf.len() - this.buf.len() % 3;
+                    if len > 0 {
+                        let groups = this.buf.split_to(len);
+                        return Poll::Ready(Some(Ok(Frame::data(STANDARD.encode(groups).into()))));
+                    }
+                }
+            }
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.eos && self.buf.is_empty()
+    }
+}
+
+/// Encodes trailers as a gRPC-Web trailers frame: a flag byte, a 4-byte
+/// length, and an HTTP/1-style header block.
+fn encode_trailers(trailers: &HeaderMap) -> Bytes {
+    let mut block = Vec::new();
+    for (name, value) in trailers {
+        block.extend_from_slice(name.as_str().as_bytes());
+        block.push(b':');
+        block.extend_from_slice(value.as_bytes());
+        block.extend_from_slice(b"\r\n");
+    }
+
+    let mut frame = BytesMut::with_capacity(5 + block.len());
+    frame.put_u8(TRAILERS_FLAG);
+    frame.put_u32(block.len() as u32);
+    frame.put_slice(&block);
+    frame.freeze()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::stream;
+    use http_body_util::{BodyExt, StreamBody};
+
+    type Frames = StreamBody<stream::Iter<std::vec::IntoIter<Result<Frame<Bytes>, Error>>>>;
+
+    fn frames(frames: Vec<Frame<Bytes>>) -> Frames {
+        StreamBody::new(stream::iter(frames.into_iter().map(Ok).collect::<Vec<_>>()))
+    }
+
+    #[tokio::test]
+    async fn decodes_text_requests() {
+        // Each message is padded separately, and the chunks split groups.
+        let body = RequestBody::new(frames(vec![
+            Frame::data(Bytes::from_static(b"aGVs")),
+            Frame::data(Bytes::from_static(b"bG8=LCB3")),
+            Frame::data(Bytes::from_static(b"b3JsZA==")),
+        ]));
+        let decoded = body.collect().await.unwrap().to_bytes();
+        assert_eq!(decoded, "hello, world");
+    }
+
+    #[tokio::test]
+    async fn rejects_truncated_text_requests() {
+        let body = RequestBody::new(frames(vec![Frame::data(Bytes::from_static(b"aGVsbG"))]));
+        let error = body.collect().await.unwrap_err();
+        assert!(error.is::<InvalidText>());
+    }
+
+    #[tokio::test]
+    async fn encodes_trailers_in_binary_responses() {
+        let mut trailers = HeaderMap::new();
+        trailers.insert("grpc-status", "0".parse().unwrap());
+        let body = ResponseBody::new(
+            frames(vec![
+                Frame::data(Bytes::from_static(b"\0\0\0\0\x01a")),
+                Frame::trailers(trailers),
+            ]),
+            Encoding::Binary,
+        );
+        let collected = body.collect().await.unwrap();
+        assert!(collected.trailers().is_none());
+        assert_eq!(
+            collected.to_bytes(),
+            &b"\0\0\0\0\x01a\x80\0\0\0\x0fgrpc-status:0\r\n"[..]
+        );
+    }
+
+    #[tokio::test]
+    async fn encodes_text_responses() {
+        let mut trailers = HeaderMap::new();
+        trailers.insert("grpc-status", "0".parse().unwrap());
+        let body = ResponseBody::new(
+            frames(vec![
+                Frame::data(Bytes::from_static(b"\0\0\0\0\x01a")),
+                Frame::trailers(trailers),
+            ]),
+            Encoding::Text,
+        );
+        let encoded = body.collect().await.unwrap().to_bytes();
+        assert_eq!(
+            STANDARD.decode(&encoded).unwrap(),
+            b"\0\0\0\0\x01a\x80\0\0\0\x0fgrpc-status:0\r\n"
+        );
+    }
+}
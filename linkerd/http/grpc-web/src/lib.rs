@@ -0,0 +1,49 @@
+//! HTTP middleware that translates [gRPC-Web] requests to gRPC.
+//!
+//! Browsers cannot read HTTP trailers or control HTTP/2 framing, so gRPC-Web
+//! clients send requests with an `application/grpc-web` (or base64-encoded
+//! `application/grpc-web-text`) content type and expect the response's
+//! trailers to be encoded at the end of the response body. [`GrpcWeb`]
+//! converts these requests to native gRPC requests over HTTP/2 and converts
+//! responses back into gRPC-Web responses.
+//!
+//! [gRPC-Web]: https://github.com/grpc/grpc/blob/master/doc/PROTOCOL-WEB.md
+
+#![deny(rust_2018_idioms, clippy::disallowed_methods, clippy::disallowed_types)]
+#![forbid(unsafe_code)]
+
+mod body;
+mod metrics;
+mod service;
+
+pub use self::{
+    body::{InvalidText, RequestBody, ResponseBody},
+    metrics::{GrpcWebMetrics, NewRecordGrpcWeb, RecordGrpcWeb},
+    service::{GrpcWeb, NewGrpcWeb, ResponseFuture},
+};
+
+/// Indicates whether gRPC-Web requests should be translated.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Enabled(pub bool);
+
+/// The encoding of a gRPC-Web message stream.
+///
+/// Translated requests carry their original encoding as an extension.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Encoding {
+    /// `application/grpc-web`: messages are sent as-is.
+    Binary,
+    /// `application/grpc-web-text`: messages are base64-encoded.
+    Text,
+}
+
+// === impl Encoding ===
+
+impl Encoding {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Binary => "binary",
+            Self::Text => "text",
+        }
+    }
+}
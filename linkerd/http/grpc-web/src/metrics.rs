@@ -0,0 +1,140 @@
+use crate::Encoding;
+use linkerd_metrics::prom::{
+    encoding::{EncodeLabel, EncodeLabelSet, LabelSetEncoder},
+    Counter, Family, Registry,
+};
+use linkerd_stack::{layer, NewService, Param, Service};
+use std::{
+    fmt::Debug,
+    hash::Hash,
+    task::{Context, Poll},
+};
+
+/// Counts the gRPC-Web requests translated to gRPC, labeled by `L`.
+#[derive(Clone, Debug)]
+pub struct GrpcWebMetrics<L> {
+    requests: Family<Labels<L>, Counter>,
+}
+
+/// A [`NewService`] that builds [`RecordGrpcWeb`] services with the target's
+/// `L`-typed labels.
+#[derive(Clone, Debug)]
+pub struct NewRecordGrpcWeb<L, N> {
+    metrics: GrpcWebMetrics<L>,
+    inner: N,
+}
+
+/// Records requests that were translated from gRPC-Web.
+///
+/// This must be layered below a [`GrpcWeb`](crate::GrpcWeb) service, which
+/// marks translated requests with their [`Encoding`].
+#[derive(Clone, Debug)]
+pub struct RecordGrpcWeb<L, S> {
+    labels: L,
+    metrics: GrpcWebMetrics<L>,
+    inner: S,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+struct Labels<L> {
+    labels: L,
+    encoding: Encoding,
+}
+
+// === impl GrpcWebMetrics ===
+
+impl<L> Default for GrpcWebMetrics<L>
+where
+    L: Clone + Debug + Hash + Eq + Send + Sync + EncodeLabelSet + 'static,
+{
+    fn default() -> Self {
+        Self {
+            requests: Family::default(),
+        }
+    }
+}
+
+impl<L> GrpcWebMetrics<L>
+where
+    L: Clone + Debug + Hash + Eq + Send + Sync + EncodeLabelSet + 'static,
+{
+    pub fn register(registry: &mut Registry) -> Self {
+        let requests = Family::default();
+        registry.register(
+            "requests",
+            "gRPC-Web requests translated to gRPC",
+            requests.clone(),
+        );
+        Self { requests }
+    }
+
+    fn record(&self, labels: &L, encoding: Encoding) {
+        self.requests
+            .get_or_create(&Labels {
+                labels: labels.clone(),
+                encoding,
+            })
+            .inc();
+    }
+}
+
+// === impl Labels ===
+
+impl<L: EncodeLabelSet> EncodeLabelSet for Labels<L> {
+    fn encode(&self, enc: &mut LabelSetEncoder<'_>) -> std::fmt::Result {
+        self.labels.encode(enc)?;
+        ("encoding", self.encoding.as_str()).encode(enc.encode_label())
+    }
+}
+
+// === impl NewRecordGrpcWeb ===
+
+impl<L: Clone, N> NewRecordGrpcWeb<L, N> {
+    pub fn layer(metrics: GrpcWebMetrics<L>) -> impl layer::Layer<N, Service = Self> + Clone {
+        layer::mk(move |inner| Self {
+            metrics: metrics.clone(),
+            inner,
+        })
+    }
+}
+
+impl<T, L, N> NewService<T> for NewRecordGrpcWeb<L, N>
+where
+    T: Param<L>,
+    L: Clone,
+    N: NewService<T>,
+{
+    type Service = RecordGrpcWeb<L, N::Service>;
+
+    fn new_service(&self, target: T) -> Self::Service {
+        RecordGrpcWeb {
+            labels: target.param(),
+            metrics: self.metrics.clone(),
+            inner: self.inner.new_service(target),
+        }
+    }
+}
+
+// === impl RecordGrpcWeb ===
+
+impl<B, L, S> Service<http::Request<B>> for RecordGrpcWeb<L, S>
+where
+    L: Clone + Debug + Hash + Eq + Send + Sync + EncodeLabelSet + 'static,
+    S: Service<http::Request<B>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: http::Request<B>) -> Self::Future {
+        if let Some(encoding) = req.extensions().get::<Encoding>() {
+            self.metrics.record(&self.labels, *encoding);
+        }
+        self.inner.call(req)
+    }
+}
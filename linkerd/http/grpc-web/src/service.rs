@@ -0,0 +1,360 @@
+use crate::{
+    body::{RequestBody, ResponseBody},
+    Enabled, Encoding,
+};
+use http::{
+    header::{self, HeaderValue},
+    HeaderMap, Request, Response, Version,
+};
+use linkerd_http_box::BoxBody;
+use linkerd_stack::{layer, NewService, Param, Service};
+use pin_project::pin_project;
+use std::{
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+/// A [`NewService<T>`] that builds [`GrpcWeb`] services.
+///
+/// The target's `P`-typed parameter is consulted on each request to determine
+/// whether translation is [`Enabled`], so that changes are honored while a
+/// connection is open.
+#[derive(Debug)]
+pub struct NewGrpcWeb<P, N> {
+    inner: N,
+    _marker: PhantomData<fn() -> P>,
+}
+
+/// Translates gRPC-Web requests to gRPC for an inner `S`-typed [`Service`].
+///
+/// Requests that are not gRPC-Web requests pass through unmodified.
+#[derive(Clone, Debug)]
+pub struct GrpcWeb<P, S> {
+    inner: S,
+    enabled: P,
+}
+
+#[pin_project]
+#[derive(Debug)]
+pub struct ResponseFuture<F> {
+    #[pin]
+    inner: F,
+    translate: Option<(Encoding, Version)>,
+}
+
+const GRPC: &str = "application/grpc";
+
+// === impl NewGrpcWeb ===
+
+impl<P, N> NewGrpcWeb<P, N> {
+    pub fn layer() -> impl layer::Layer<N, Service = Self> + Clone {
+        layer::mk(|inner| Self {
+            inner,
+            _marker: PhantomData,
+        })
+    }
+}
+
+impl<T, P, N> NewService<T> for NewGrpcWeb<P, N>
+where
+    T: Param<P>,
+    N: NewService<T>,
+{
+    type Service = GrpcWeb<P, N::Service>;
+
+    fn new_service(&self, target: T) -> Self::Service {
+        let enabled = target.param();
+        GrpcWeb {
+            inner: self.inner.new_service(target),
+            enabled,
+        }
+    }
+}
+
+impl<P, N: Clone> Clone for NewGrpcWeb<P, N> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            _marker: self._marker,
+        }
+    }
+}
+
+// === impl GrpcWeb ===
+
+impl<P, S> Service<Request<BoxBody>> for GrpcWeb<P, S>
+where
+    P: Param<Enabled>,
+    S: Service<Request<BoxBody>, Response = Response<BoxBody>>,
+{
+    type Response = Response<BoxBody>;
+    type Error = S::Error;
+    type Future = ResponseFuture<S::Future>;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<BoxBody>) -> Self::Future {
+        let Enabled(enabled) = self.enabled.param();
+        let web = enabled
+            .then(|| grpc_web_content_type(req.headers()))
+            .flatten();
+        let Some((encoding, content_type)) = web else {
+            return ResponseFuture {
+                inner: self.inner.call(req),
+                translate: None,
+            };
+        };
+
+        let version = req.version();
+        let req = translate_request(req, encoding, content_type);
+        ResponseFuture {
+            inner: self.inner.call(req),
+            translate: Some((encoding, version)),
+        }
+    }
+}
+
+/// Returns the encoding of a gRPC-Web request and the content type of the
+/// equivalent gRPC request.
+fn grpc_web_content_type(headers: &HeaderMap) -> Option<(Encoding, HeaderValue)> {
+    let content_type = headers.get(header::CONTENT_TYPE)?.to_str().ok()?;
+    let (encoding, subtype) = [Encoding::Text, Encoding::Binary]
+        .into_iter()
+        .find_map(|e| Some((e, content_type.strip_prefix(e.content_type())?)))?;
+    if !is_subtype(subtype) {
+        return None;
+    }
+    let content_type = HeaderValue::try_from(format!("{GRPC}{subtype}")).ok()?;
+    Some((encoding, content_type))
+}
+
+/// Returns true if `s` is empty or begins with a message format (e.g.
+/// `+proto`) or parameters.
+fn is_subtype(s: &str) -> bool {
+    s.is_empty() || s.starts_with('+') || s.starts_with(';')
+}
+
+fn translate_request(
+    req: Request<BoxBody>,
+    encoding: Encoding,
+    content_type: HeaderValue,
+) -> Request<BoxBody> {
+    let (mut parts, body) = req.into_parts();
+    parts.version = Version::HTTP_2;
+    parts.headers.insert(header::CONTENT_TYPE, content_type);
+    parts
+        .headers
+        .insert(header::TE, HeaderValue::from_static("trailers"));
+    parts.extensions.insert(encoding);
+
+    let body = match encoding {
+        Encoding::Binary => body,
+        Encoding::Text => {
+            parts.headers.remove(header::CONTENT_LENGTH);
+            BoxBody::new(RequestBody::new(body))
+        }
+    };
+    Request::from_parts(parts, body)
+}
+
+fn translate_response(
+    rsp: Response<BoxBody>,
+    encoding: Encoding,
+    version: Version,
+) -> Response<BoxBody> {
+    let (mut parts, body) = rsp.into_parts();
+    parts.version = version;
+    parts.headers.remove(header::CONTENT_LENGTH);
+
+    let content_type = parts
+        .headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok()?.strip_prefix(GRPC))
+        .filter(|subtype| is_subtype(subtype))
+        .and_then(|subtype| {
+            HeaderValue::try_from(format!("{}{subtype}", encoding.content_type())).ok()
+        });
+    if let Some(content_type) = content_type {
+        parts.headers.insert(header::CONTENT_TYPE, content_type);
+    }
+
+    Response::from_parts(parts, BoxBody::new(ResponseBody::new(body, encoding)))
+}
+
+// === impl ResponseFuture ===
+
+impl<F, E> Future for ResponseFuture<F>
+where
+    F: Future<Output = Result<Response<BoxBody>, E>>,
+{
+    type Output = Result<Response<BoxBody>, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let rsp = ready!(this.inner.poll(cx))?;
+        let rsp = match this.translate.take() {
+            Some((encoding, version)) => translate_response(rsp, encoding, version),
+            None => rsp,
+        };
+        Poll::Ready(Ok(rsp))
+    }
+}
+
+// === impl Encoding ===
+
+impl Encoding {
+    fn content_type(&self) -> &'static str {
+        match self {
+            Self::Binary => "application/grpc-web",
+            Self::Text => "application/grpc-web-text",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use bytes::Bytes;
+    use http_body::Frame;
+    use http_body_util::{BodyExt, Full, StreamBody};
+
+    #[derive(Clone, Debug)]
+    struct Target(bool);
+
+    impl Param<Enabled> for Target {
+        fn param(&self) -> Enabled {
+            Enabled(self.0)
+        }
+    }
+
+    /// A gRPC server that echoes the request's message and content type.
+    async fn echo(req: Request<BoxBody>) -> Result<Response<BoxBody>, ()> {
+        let content_type = req.headers()[header::CONTENT_TYPE].clone();
+        let version = req.version();
+        let te = req.headers().get(header::TE).cloned();
+        let encoding = req.extensions().get::<Encoding>().copied();
+        let message = req.into_body().collect().await.unwrap().to_bytes();
+
+        let mut trailers = HeaderMap::new();
+        trailers.insert("grpc-status", HeaderValue::from_static("0"));
+        let frames = vec![
+            Ok::<_, linkerd_error::Error>(Frame::data(message)),
+            Ok(Frame::trailers(trailers)),
+        ];
+        let mut rsp = Response::builder()
+            .header(header::CONTENT_TYPE, content_type)
+            .header("x-version", format!("{version:?}"))
+            .header("x-encoding", encoding.map_or("none", |e| e.as_str()));
+        if let Some(te) = te {
+            rsp = rsp.header("x-te", te);
+        }
+        Ok(rsp
+            .body(BoxBody::new(StreamBody::new(futures_util::stream::iter(
+                frames,
+            ))))
+            .unwrap())
+    }
+
+    fn svc(
+        enabled: bool,
+    ) -> GrpcWeb<
+        Target,
+        impl Service<
+            Request<BoxBody>,
+            Response = Response<BoxBody>,
+            Error = (),
+            Future = impl Future<Output = Result<Response<BoxBody>, ()>>,
+        >,
+    > {
+        GrpcWeb {
+            inner: linkerd_stack::service_fn(echo),
+            enabled: Target(enabled),
+        }
+    }
+
+    fn request(content_type: &'static str, body: &'static [u8]) -> Request<BoxBody> {
+        Request::post("http://example.com/pkg.Svc/Method")
+            .version(Version::HTTP_11)
+            .header(header::CONTENT_TYPE, content_type)
+            .body(BoxBody::new(Full::new(Bytes::from_static(body))))
+            .unwrap()
+    }
+
+    const MESSAGE: &[u8] = b"\0\0\0\0\x05hello";
+    const TRAILERS: &[u8] = b"\x80\0\0\0\x0fgrpc-status:0\r\n";
+
+    #[tokio::test]
+    async fn translates_binary_requests() {
+        let rsp = svc(true)
+            .call(request("application/grpc-web+proto", MESSAGE))
+            .await
+            .unwrap();
+        assert_eq!(rsp.version(), Version::HTTP_11);
+        assert_eq!(
+            rsp.headers()[header::CONTENT_TYPE],
+            "application/grpc-web+proto"
+        );
+        assert_eq!(rsp.headers()["x-version"], "HTTP/2.0");
+        assert_eq!(rsp.headers()["x-encoding"], "binary");
+        assert_eq!(rsp.headers()["x-te"], "trailers");
+
+        let body = rsp.into_body().collect().await.unwrap();
+        assert!(body.trailers().is_none());
+        assert_eq!(body.to_bytes(), [MESSAGE, TRAILERS].concat());
+    }
+
+    #[tokio::test]
+    async fn translates_text_requests() {
+        let encoded = STANDARD.encode(MESSAGE).leak().as_bytes();
+        let rsp = svc(true)
+            .call(request("application/grpc-web-text", encoded))
+            .await
+            .unwrap();
+        assert_eq!(
+            rsp.headers()[header::CONTENT_TYPE],
+            "application/grpc-web-text"
+        );
+        assert_eq!(rsp.headers()["x-encoding"], "text");
+
+        let body = rsp.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(
+            STANDARD.decode(&body).unwrap(),
+            [MESSAGE, TRAILERS].concat()
+        );
+    }
+
+    #[tokio::test]
+    async fn ignores_other_requests() {
+        let rsp = svc(true)
+            .call(request("application/grpc", MESSAGE))
+            .await
+            .unwrap();
+        assert_eq!(rsp.headers()[header::CONTENT_TYPE], "application/grpc");
+        assert_eq!(rsp.headers()["x-encoding"], "none");
+        let body = rsp.into_body().collect().await.unwrap();
+        assert!(body.trailers().is_some());
+
+        let rsp = svc(true)
+            .call(request("application/grpc-webby", MESSAGE))
+            .await
+            .unwrap();
+        assert_eq!(rsp.headers()["x-encoding"], "none");
+    }
+
+    #[tokio::test]
+    async fn disabled() {
+        let rsp = svc(false)
+            .call(request("application/grpc-web", MESSAGE))
+            .await
+            .unwrap();
+        assert_eq!(rsp.headers()[header::CONTENT_TYPE], "application/grpc-web");
+        assert_eq!(rsp.headers()["x-version"], "HTTP/1.1");
+        assert_eq!(rsp.headers()["x-encoding"], "none");
+    }
+}
@@ -0,0 +1,45 @@
+use std::time::Duration;
+
+/// Limits on an HTTP/1 client's pool of connections to an endpoint.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct PoolLimits {
+    /// The maximum number of connections that may be in use at once. When
+    /// the pool is full, requests wait for a connection to become available.
+    pub max_connections: Option<usize>,
+
+    /// The maximum number of requests that may wait for a connection when
+    /// the pool is full. Requests beyond this limit fail immediately.
+    pub max_pending: Option<usize>,
+
+    /// The maximum number of requests sent on a connection before it is
+    /// closed.
+    pub max_requests_per_connection: Option<usize>,
+
+    /// The maximum time a connection is reused for after it is established.
+    pub max_lifetime: Option<MaxLifetime>,
+}
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct MaxLifetime {
+    pub lifetime: Duration,
+
+    /// Extends each connection's lifetime by a random duration of up to
+    /// `jitter` so that connections established together are not all closed
+    /// together.
+    pub jitter: Duration,
+}
+
+// === impl PoolLimits ===
+
+impl PoolLimits {
+    pub fn override_from(&self, overrides: &Self) -> Self {
+        Self {
+            max_connections: overrides.max_connections.or(self.max_connections),
+            max_pending: overrides.max_pending.or(self.max_pending),
+            max_requests_per_connection: overrides
+                .max_requests_per_connection
+                .or(self.max_requests_per_connection),
+            max_lifetime: overrides.max_lifetime.or(self.max_lifetime),
+        }
+    }
+}
@@ -6,6 +6,10 @@ pub struct ServerParams {
     pub keep_alive: Option<KeepAlive>,
     pub max_concurrent_streams: Option<u32>,
 
+    /// Enables the extended CONNECT protocol (RFC 8441), so that clients may
+    /// bootstrap WebSockets over HTTP/2 streams.
+    pub enable_connect_protocol: bool,
+
     // Internals
     pub max_frame_size: Option<u32>,
     pub max_header_list_size: Option<u32>,
@@ -18,6 +22,10 @@ pub struct ClientParams {
     pub flow_control: Option<FlowControl>,
     pub keep_alive: Option<ClientKeepAlive>,
 
+    /// Enables carrying HTTP/1.1 WebSocket upgrades over HTTP/2 with the
+    /// extended CONNECT protocol (RFC 8441), when the server supports it.
+    pub extended_connect: Option<bool>,
+
     // Internals
     pub max_concurrent_reset_streams: Option<usize>,
     pub max_frame_size: Option<u32>,
@@ -53,6 +61,7 @@ impl ClientParams {
         Self {
             flow_control: overrides.flow_control.or(self.flow_control),
             keep_alive: overrides.keep_alive.or(self.keep_alive),
+            extended_connect: overrides.extended_connect.or(self.extended_connect),
             max_concurrent_reset_streams: overrides
                 .max_concurrent_reset_streams
                 .or(self.max_concurrent_reset_streams),
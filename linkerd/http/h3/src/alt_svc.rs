@@ -0,0 +1,138 @@
+use futures::TryFuture;
+use http::header::{HeaderValue, ALT_SVC};
+use linkerd_stack::layer;
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+/// Advertises an HTTP/3 endpoint on the same host with an `Alt-Svc` header.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AltSvc(HeaderValue);
+
+/// Adds an `Alt-Svc` header to responses that do not already have one.
+#[derive(Clone, Debug)]
+pub struct SetAltSvc<S> {
+    inner: S,
+    alt_svc: Option<AltSvc>,
+}
+
+#[pin_project::pin_project]
+#[derive(Debug)]
+pub struct ResponseFuture<F> {
+    #[pin]
+    inner: F,
+    alt_svc: Option<AltSvc>,
+}
+
+// === impl AltSvc ===
+
+impl AltSvc {
+    pub fn new(port: u16, max_age: Duration) -> Self {
+        let value = format!("h3=\":{port}\"; ma={}", max_age.as_secs());
+        Self(HeaderValue::try_from(value).expect("Alt-Svc value must be a valid header"))
+    }
+
+    /// Returns a layer that advertises `alt_svc`, if one is configured.
+    pub fn layer<S>(
+        alt_svc: Option<Self>,
+    ) -> impl tower::layer::Layer<S, Service = SetAltSvc<S>> + Clone {
+        layer::mk(move |inner| SetAltSvc {
+            inner,
+            alt_svc: alt_svc.clone(),
+        })
+    }
+
+    pub fn header_value(&self) -> &HeaderValue {
+        &self.0
+    }
+}
+
+// === impl SetAltSvc ===
+
+impl<S, Req, B> tower::Service<Req> for SetAltSvc<S>
+where
+    S: tower::Service<Req, Response = http::Response<B>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = ResponseFuture<S::Future>;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Req) -> Self::Future {
+        ResponseFuture {
+            inner: self.inner.call(req),
+            alt_svc: self.alt_svc.clone(),
+        }
+    }
+}
+
+// === impl ResponseFuture ===
+
+impl<F, B> Future for ResponseFuture<F>
+where
+    F: TryFuture<Ok = http::Response<B>>,
+{
+    type Output = Result<http::Response<B>, F::Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let mut rsp = futures::ready!(this.inner.try_poll(cx))?;
+        if let Some(AltSvc(value)) = this.alt_svc.take() {
+            rsp.headers_mut().entry(ALT_SVC).or_insert(value);
+        }
+        Poll::Ready(Ok(rsp))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower::{Layer, ServiceExt};
+
+    #[test]
+    fn formats_advertisement() {
+        let alt_svc = AltSvc::new(4143, Duration::from_secs(3600));
+        assert_eq!(alt_svc.header_value(), "h3=\":4143\"; ma=3600");
+    }
+
+    #[tokio::test]
+    async fn sets_header() {
+        let alt_svc = AltSvc::new(443, Duration::from_secs(60));
+        let svc = AltSvc::layer(Some(alt_svc.clone())).layer(tower::service_fn(
+            |_: http::Request<()>| async { Ok::<_, ()>(http::Response::new(())) },
+        ));
+        let rsp = svc.oneshot(http::Request::new(())).await.unwrap();
+        assert_eq!(rsp.headers().get(ALT_SVC), Some(alt_svc.header_value()));
+    }
+
+    #[tokio::test]
+    async fn preserves_existing_header() {
+        let svc = AltSvc::layer(Some(AltSvc::new(443, Duration::from_secs(60)))).layer(
+            tower::service_fn(|_: http::Request<()>| async {
+                let rsp = http::Response::builder()
+                    .header(ALT_SVC, "clear")
+                    .body(())
+                    .unwrap();
+                Ok::<_, ()>(rsp)
+            }),
+        );
+        let rsp = svc.oneshot(http::Request::new(())).await.unwrap();
+        assert_eq!(rsp.headers().get(ALT_SVC).unwrap(), "clear");
+    }
+
+    #[tokio::test]
+    async fn disabled() {
+        let svc = AltSvc::layer(None).layer(tower::service_fn(|_: http::Request<()>| async {
+            Ok::<_, ()>(http::Response::new(()))
+        }));
+        let rsp = svc.oneshot(http::Request::new(())).await.unwrap();
+        assert!(rsp.headers().get(ALT_SVC).is_none());
+    }
+}
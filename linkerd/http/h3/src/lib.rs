@@ -0,0 +1,79 @@
+//! HTTP/3 support for ingress-mode proxies.
+//!
+//! HTTP/3 ([RFC 9114]) carries HTTP semantics over QUIC rather than TCP.
+//! Clients only learn that a server speaks HTTP/3 when it is advertised with
+//! an `Alt-Svc` header ([RFC 7838]) on an HTTP/1 or HTTP/2 response, so this
+//! crate provides [`AltSvc`] middleware for the proxy's TCP listeners.
+//!
+//! When the `server` feature is enabled, a [`server::Server`] terminates
+//! HTTP/3 on a UDP socket and dispatches requests to an HTTP service.
+//!
+//! Only ingress-mode proxies serve HTTP/3, to clients outside of the mesh.
+//! Inbound listeners remain TCP-only.
+//!
+//! [RFC 9114]: https://www.rfc-editor.org/rfc/rfc9114
+//! [RFC 7838]: https://www.rfc-editor.org/rfc/rfc7838
+
+#![deny(rust_2018_idioms, clippy::disallowed_methods, clippy::disallowed_types)]
+#![forbid(unsafe_code)]
+
+use std::{net::SocketAddr, path::PathBuf, time::Duration};
+
+mod alt_svc;
+#[cfg(feature = "server")]
+pub mod server;
+
+pub use self::alt_svc::{AltSvc, SetAltSvc};
+
+/// The ALPN protocol identifier for HTTP/3.
+pub const ALPN_H3: &[u8] = b"h3";
+
+/// Configures an HTTP/3 listener.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ServerConfig {
+    /// The UDP address on which the listener is bound.
+    pub addr: SocketAddr,
+
+    /// A PEM-encoded certificate chain presented to clients.
+    pub certificate: PathBuf,
+
+    /// A PEM-encoded private key for the leaf certificate.
+    pub private_key: PathBuf,
+
+    pub params: ServerParams,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ServerParams {
+    /// Limits the number of concurrent request streams per connection.
+    pub max_concurrent_streams: Option<u32>,
+
+    /// Closes connections that have been idle for this long.
+    pub idle_timeout: Option<Duration>,
+
+    /// How long clients may cache the `Alt-Svc` advertisement.
+    pub alt_svc_max_age: Duration,
+}
+
+// === impl ServerConfig ===
+
+impl ServerConfig {
+    /// Returns the advertisement for this listener.
+    pub fn alt_svc(&self) -> AltSvc {
+        AltSvc::new(self.addr.port(), self.params.alt_svc_max_age)
+    }
+}
+
+// === impl ServerParams ===
+
+impl Default for ServerParams {
+    fn default() -> Self {
+        Self {
+            max_concurrent_streams: None,
+            idle_timeout: None,
+            // The default freshness lifetime for Alt-Svc entries; see RFC 7838,
+            // section 3.1.
+            alt_svc_max_age: Duration::from_secs(24 * 60 * 60),
+        }
+    }
+}
@@ -0,0 +1,328 @@
+//! Terminates HTTP/3 over QUIC.
+//!
+//! Each accepted QUIC connection is served by an HTTP/3 connection task, and
+//! each request stream is dispatched to a service built for the connection's
+//! client address. Requests are presented to that service as HTTP/2 requests:
+//! the two protocols share the same semantics (pseudo-headers, trailers, no
+//! connection-level headers), and the rest of the proxy only distinguishes
+//! HTTP/1 from HTTP/2.
+
+use crate::{ServerConfig, ServerParams, ALPN_H3};
+use bytes::{Buf, Bytes};
+use h3::error::Code;
+use http::header::{HeaderMap, HeaderName};
+use http_body::{Body, Frame};
+use http_body_util::BodyExt;
+use linkerd_error::{Error, Result};
+use linkerd_stack::NewService;
+use rustls_pki_types::{pem::PemObject, CertificateDer, PrivateKeyDer};
+use std::{
+    net::SocketAddr,
+    pin::{pin, Pin},
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tokio_rustls::rustls;
+use tower::ServiceExt;
+use tracing::{debug, info, info_span, Instrument};
+
+/// An HTTP/3 server bound to a UDP socket.
+#[derive(Debug)]
+pub struct Server {
+    endpoint: quinn::Endpoint,
+}
+
+/// A request body read from an HTTP/3 request stream.
+pub struct RecvBody {
+    stream: h3::server::RequestStream<h3_quinn::RecvStream, Bytes>,
+    data_done: bool,
+    trailers_done: bool,
+}
+
+type SendStream = h3::server::RequestStream<h3_quinn::SendStream<Bytes>, Bytes>;
+
+/// Connection-level headers that are prohibited in HTTP/3 messages. See RFC
+/// 9114, section 4.2.
+static CONNECTION_HEADERS: [HeaderName; 5] = [
+    http::header::CONNECTION,
+    HeaderName::from_static("keep-alive"),
+    HeaderName::from_static("proxy-connection"),
+    http::header::TRANSFER_ENCODING,
+    http::header::UPGRADE,
+];
+
+/// Builds a TLS configuration for an HTTP/3 server, using the proxy's crypto
+/// provider.
+pub fn tls_config(
+    certificates: Vec<CertificateDer<'static>>,
+    key: PrivateKeyDer<'static>,
+) -> Result<rustls::ServerConfig> {
+    let mut tls =
+        rustls::ServerConfig::builder_with_provider(linkerd_rustls::get_default_provider())
+            .with_protocol_versions(linkerd_rustls::TLS_VERSIONS)?
+            .with_no_client_auth()
+            .with_single_cert(certificates, key)?;
+    tls.alpn_protocols = vec![ALPN_H3.to_vec()];
+    Ok(tls)
+}
+
+// === impl Server ===
+
+impl Server {
+    /// Binds a server with the configured certificate and key.
+    pub fn bind(config: &ServerConfig) -> Result<Self> {
+        let certificates =
+            CertificateDer::pem_file_iter(&config.certificate)?.collect::<Result<Vec<_>, _>>()?;
+        let key = PrivateKeyDer::from_pem_file(&config.private_key)?;
+        let tls = tls_config(certificates, key)?;
+        Self::bind_tls(config.addr, tls, config.params)
+    }
+
+    pub fn bind_tls(
+        addr: SocketAddr,
+        tls: rustls::ServerConfig,
+        params: ServerParams,
+    ) -> Result<Self> {
+        let crypto = quinn::crypto::rustls::QuicServerConfig::try_from(tls)?;
+        let mut server = quinn::ServerConfig::with_crypto(Arc::new(crypto));
+
+        let mut transport = quinn::TransportConfig::default();
+        if let Some(max) = params.max_concurrent_streams {
+            transport.max_concurrent_bidi_streams(max.into());
+        }
+        transport.max_idle_timeout(
+            params
+                .idle_timeout
+                .map(quinn::IdleTimeout::try_from)
+                .transpose()?,
+        );
+        server.transport_config(Arc::new(transport));
+
+        let endpoint = quinn::Endpoint::server(server, addr)?;
+        Ok(Self { endpoint })
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        self.endpoint.local_addr().map_err(Into::into)
+    }
+
+    /// Accepts connections until `drain` is signaled.
+    ///
+    /// When the proxy drains, the server stops accepting connections and each
+    /// open connection is sent a GOAWAY so that in-flight requests may
+    /// complete.
+    pub async fn serve<N, S, B>(self, new_svc: N, drain: drain::Watch)
+    where
+        N: NewService<SocketAddr, Service = S>,
+        S: tower::Service<http::Request<RecvBody>, Response = http::Response<B>>,
+        S: Clone + Send + 'static,
+        S::Error: Into<Error>,
+        S::Future: Send,
+        B: Body + Send + 'static,
+        B::Data: Send,
+        B::Error: Into<Error> + Send,
+    {
+        let mut signaled = pin!(drain.clone().signaled());
+        loop {
+            let incoming = tokio::select! {
+                biased;
+                _ = &mut signaled => {
+                    debug!("Draining");
+                    return;
+                }
+                incoming = self.endpoint.accept() => match incoming {
+                    Some(incoming) => incoming,
+                    None => return,
+                },
+            };
+
+            let client = incoming.remote_address();
+            let svc = new_svc.new_service(client);
+            let drain = drain.clone();
+            tokio::spawn(
+                async move {
+                    match serve_connection(incoming, svc, drain).await {
+                        Ok(()) => debug!("Connection closed"),
+                        Err(error) => info!(%error, "Connection closed"),
+                    }
+                }
+                .instrument(info_span!("h3", client.addr = %client).or_current()),
+            );
+        }
+    }
+}
+
+async fn serve_connection<S, B>(
+    incoming: quinn::Incoming,
+    svc: S,
+    drain: drain::Watch,
+) -> Result<()>
+where
+    S: tower::Service<http::Request<RecvBody>, Response = http::Response<B>>,
+    S: Clone + Send + 'static,
+    S::Error: Into<Error>,
+    S::Future: Send,
+    B: Body + Send + 'static,
+    B::Data: Send,
+    B::Error: Into<Error> + Send,
+{
+    let conn = incoming.await?;
+    debug!("Connection established");
+    let mut conn = h3::server::Connection::<_, Bytes>::new(h3_quinn::Connection::new(conn)).await?;
+
+    // Holds the drain open until the connection has finished gracefully.
+    let mut release = None;
+    let mut signaled = pin!(drain.signaled());
+    loop {
+        let accepted = tokio::select! {
+            biased;
+            shutdown = &mut signaled, if release.is_none() => {
+                debug!("Sending GOAWAY");
+                conn.shutdown(0).await?;
+                release = Some(shutdown);
+                continue;
+            }
+            accepted = conn.accept() => accepted,
+        };
+
+        let resolver = match accepted {
+            Ok(Some(resolver)) => resolver,
+            Ok(None) => return Ok(()),
+            Err(error) if error.is_h3_no_error() => return Ok(()),
+            Err(error) => return Err(error.into()),
+        };
+
+        let svc = svc.clone();
+        tokio::spawn(
+            async move {
+                let (req, stream) = match resolver.resolve_request().await {
+                    Ok(req) => req,
+                    Err(error) => {
+                        debug!(%error, "Failed to read request");
+                        return;
+                    }
+                };
+                let (mut tx, rx) = stream.split();
+
+                let (mut parts, ()) = req.into_parts();
+                parts.version = http::Version::HTTP_2;
+                let req = http::Request::from_parts(parts, RecvBody::new(rx));
+
+                let rsp = match svc.oneshot(req).await {
+                    Ok(rsp) => rsp,
+                    Err(error) => {
+                        let error: Error = error.into();
+                        info!(%error, "Request failed");
+                        tx.stop_stream(Code::H3_INTERNAL_ERROR);
+                        return;
+                    }
+                };
+                if let Err(error) = send_response(&mut tx, rsp).await {
+                    debug!(%error, "Failed to send response");
+                    tx.stop_stream(Code::H3_INTERNAL_ERROR);
+                }
+            }
+            .in_current_span(),
+        );
+    }
+}
+
+async fn send_response<B>(tx: &mut SendStream, rsp: http::Response<B>) -> Result<()>
+where
+    B: Body,
+    B::Error: Into<Error>,
+{
+    let (mut parts, body) = rsp.into_parts();
+    strip_connection_headers(&mut parts.headers);
+    tx.send_response(http::Response::from_parts(parts, ()))
+        .await?;
+
+    let mut body = pin!(body);
+    while let Some(frame) = body.frame().await {
+        let frame = frame.map_err(Into::into)?;
+        match frame.into_data() {
+            Ok(mut data) => {
+                let data = data.copy_to_bytes(data.remaining());
+                tx.send_data(data).await?;
+            }
+            Err(frame) => {
+                if let Ok(mut trailers) = frame.into_trailers() {
+                    strip_connection_headers(&mut trailers);
+                    tx.send_trailers(trailers).await?;
+                    break;
+                }
+            }
+        }
+    }
+
+    tx.finish().await?;
+    Ok(())
+}
+
+fn strip_connection_headers(headers: &mut HeaderMap) {
+    for name in &CONNECTION_HEADERS {
+        headers.remove(name);
+    }
+}
+
+// === impl RecvBody ===
+
+impl RecvBody {
+    fn new(stream: h3::server::RequestStream<h3_quinn::RecvStream, Bytes>) -> Self {
+        Self {
+            stream,
+            data_done: false,
+            trailers_done: false,
+        }
+    }
+}
+
+impl Body for RecvBody {
+    type Data = Bytes;
+    type Error = Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Bytes>, Error>>> {
+        let this = self.get_mut();
+
+        if !this.data_done {
+            match futures::ready!(this.stream.poll_recv_data(cx)) {
+                Ok(Some(mut data)) => {
+                    let data = data.copy_to_bytes(data.remaining());
+                    return Poll::Ready(Some(Ok(Frame::data(data))));
+                }
+                Ok(None) => this.data_done = true,
+                Err(error) => return Poll::Ready(Some(Err(error.into()))),
+            }
+        }
+
+        if this.trailers_done {
+            return Poll::Ready(None);
+        }
+        let trailers = futures::ready!(this.stream.poll_recv_trailers(cx));
+        this.trailers_done = true;
+        match trailers {
+            Ok(Some(trailers)) => Poll::Ready(Some(Ok(Frame::trailers(trailers)))),
+            Ok(None) => Poll::Ready(None),
+            Err(error) => Poll::Ready(Some(Err(error.into()))),
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.data_done && self.trailers_done
+    }
+}
+
+impl std::fmt::Debug for RecvBody {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RecvBody")
+            .field("data_done", &self.data_done)
+            .field("trailers_done", &self.trailers_done)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,77 @@
+use super::*;
+use http_body_util::Full;
+use linkerd_tls_test_util::FOO_NS1;
+use std::future::poll_fn;
+
+#[tokio::test]
+async fn serves_requests_over_loopback() {
+    let tls = tls_config(
+        vec![CertificateDer::from(FOO_NS1.crt.to_vec())],
+        PrivateKeyDer::try_from(FOO_NS1.key.to_vec()).unwrap(),
+    )
+    .unwrap();
+    let server =
+        Server::bind_tls(([127, 0, 0, 1], 0).into(), tls, ServerParams::default()).unwrap();
+    let addr = server.local_addr().unwrap();
+
+    let (drain_tx, drain_rx) = drain::channel();
+    let new_svc = |_: SocketAddr| {
+        tower::service_fn(|req: http::Request<RecvBody>| async move {
+            assert_eq!(req.version(), http::Version::HTTP_2);
+            assert_eq!(req.uri().path(), "/hello");
+            let rsp = http::Response::builder()
+                .header("connection", "close")
+                .body(Full::new(Bytes::from_static(b"world")))
+                .unwrap();
+            Ok::<_, Error>(rsp)
+        })
+    };
+    let serve = tokio::spawn(server.serve(new_svc, drain_rx));
+
+    let client = {
+        let mut roots = rustls::RootCertStore::empty();
+        for cert in CertificateDer::pem_slice_iter(FOO_NS1.trust_anchors) {
+            roots.add(cert.unwrap()).unwrap();
+        }
+        let mut tls =
+            rustls::ClientConfig::builder_with_provider(linkerd_rustls::get_default_provider())
+                .with_protocol_versions(linkerd_rustls::TLS_VERSIONS)
+                .unwrap()
+                .with_root_certificates(roots)
+                .with_no_client_auth();
+        tls.alpn_protocols = vec![ALPN_H3.to_vec()];
+        let crypto = quinn::crypto::rustls::QuicClientConfig::try_from(tls).unwrap();
+        let mut endpoint = quinn::Endpoint::client(([127, 0, 0, 1], 0).into()).unwrap();
+        endpoint.set_default_client_config(quinn::ClientConfig::new(Arc::new(crypto)));
+        endpoint
+    };
+    let conn = client.connect(addr, FOO_NS1.name).unwrap().await.unwrap();
+    let (mut driver, mut send_request) = h3::client::new(h3_quinn::Connection::new(conn))
+        .await
+        .unwrap();
+    let driver = tokio::spawn(async move { poll_fn(|cx| driver.poll_close(cx)).await });
+
+    let req = http::Request::get(format!("https://{}/hello", FOO_NS1.name))
+        .body(())
+        .unwrap();
+    let mut stream = send_request.send_request(req).await.unwrap();
+    stream.finish().await.unwrap();
+
+    let rsp = stream.recv_response().await.unwrap();
+    assert_eq!(rsp.status(), http::StatusCode::OK);
+    assert!(rsp.headers().get("connection").is_none());
+    let mut body = stream
+        .recv_data()
+        .await
+        .unwrap()
+        .expect("response must have data");
+    assert_eq!(body.copy_to_bytes(body.remaining()), "world");
+    assert!(stream.recv_data().await.unwrap().is_none());
+
+    drop(send_request);
+    // Close with H3_NO_ERROR so that the server's connection task completes.
+    client.close(quinn::VarInt::from_u32(0x100), b"");
+    drain_tx.drain().await;
+    serve.await.unwrap();
+    driver.abort();
+}
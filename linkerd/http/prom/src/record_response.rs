@@ -3,9 +3,13 @@ use http_body::Body;
 use linkerd_error::Error;
 use linkerd_http_box::BoxBody;
 use linkerd_stack as svc;
-use prometheus_client::metrics::{
-    family::{Family, MetricConstructor},
-    histogram::Histogram,
+use linkerd_trace_context::SampledTrace;
+use prometheus_client::{
+    encoding::EncodeLabelSet,
+    metrics::{
+        exemplar::HistogramWithExemplars,
+        family::{Family, MetricConstructor},
+    },
 };
 use std::{
     future::Future,
@@ -18,6 +22,9 @@ use tokio::{sync::oneshot, time};
 mod request;
 mod response;
 
+#[cfg(test)]
+mod tests;
+
 pub use self::{
     request::{NewRequestDuration, RecordRequestDuration, RequestMetrics},
     response::{NewResponseDuration, RecordResponseDuration, ResponseMetrics},
@@ -82,6 +89,14 @@ struct ResponseState<L: StreamLabel> {
     labeler: L,
     duration: DurationFamily<L::DurationLabels>,
     start: StartTime,
+    exemplar: Option<TraceExemplar>,
+}
+
+/// Labels a duration observation with the trace of the sampled request that
+/// it was recorded for.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+struct TraceExemplar {
+    trace_id: String,
 }
 
 /// Start time for a duration measurement.
@@ -104,16 +119,30 @@ impl StartTime {
     }
 }
 
-type DurationFamily<L> = Family<L, Histogram, MkDurationHistogram>;
+type DurationHistogram = HistogramWithExemplars<TraceExemplar>;
+
+type DurationFamily<L> = Family<L, DurationHistogram, MkDurationHistogram>;
 
 #[derive(Clone, Debug)]
 struct MkDurationHistogram(Arc<[f64]>);
 
 // === impl MkDurationHistogram ===
 
-impl MetricConstructor<Histogram> for MkDurationHistogram {
-    fn new_metric(&self) -> Histogram {
-        Histogram::new(self.0.iter().copied())
+impl MetricConstructor<DurationHistogram> for MkDurationHistogram {
+    fn new_metric(&self) -> DurationHistogram {
+        DurationHistogram::new(self.0.iter().copied())
+    }
+}
+
+// === impl TraceExemplar ===
+
+impl TraceExemplar {
+    /// Returns an exemplar for the request if it was sampled for tracing.
+    fn from_request<B>(req: &http::Request<B>) -> Option<Self> {
+        let SampledTrace { trace_id } = req.extensions().get::<SampledTrace>()?;
+        Some(Self {
+            trace_id: trace_id.to_string(),
+        })
     }
 }
 
@@ -282,6 +311,7 @@ fn end_stream<L>(
         duration,
         mut start,
         mut labeler,
+        exemplar,
     }) = state.take()
     else {
         return;
@@ -294,7 +324,9 @@ fn end_stream<L>(
     } else {
         time::Duration::ZERO
     };
-    duration
-        .get_or_create(&labeler.duration_labels())
-        .observe(elapsed.as_secs_f64());
+    duration.get_or_create(&labeler.duration_labels()).observe(
+        elapsed.as_secs_f64(),
+        exemplar,
+        None,
+    );
 }
@@ -87,6 +87,7 @@ where
                 labeler,
                 start,
                 duration,
+                exemplar: super::TraceExemplar::from_request(&req),
             }
         });
 
@@ -85,6 +85,7 @@ where
         // If there's a labeler, wrap the request body to record the time that
         // the respond flushes.
         let state = if let Some(labeler) = self.labeler.mk_stream_labeler(&req) {
+            let exemplar = super::TraceExemplar::from_request(&req);
             let (tx, start) = oneshot::channel();
             req = req.map(|inner| {
                 BoxBody::new(RequestBody {
@@ -97,6 +98,7 @@ where
                 labeler,
                 start: super::StartTime::Pending(start),
                 duration,
+                exemplar,
             })
         } else {
             None
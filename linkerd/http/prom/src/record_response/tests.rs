@@ -0,0 +1,92 @@
+//! Unit tests for [`RecordDuration<L, M, S>`].
+
+use super::*;
+use bytes::Bytes;
+use http_body_util::BodyExt;
+use linkerd_stack::ServiceExt;
+use linkerd_trace_context::Id;
+use prometheus_client::registry::Registry;
+
+/// Demonstrates that durations of sampled requests are recorded with the
+/// request's trace ID as an exemplar.
+#[tokio::test]
+async fn records_trace_exemplars() {
+    let mut registry = Registry::default();
+    let metrics = RequestMetrics::<Labels>::register(&mut registry, [0.1, 1.0]);
+    let mk_svc = || {
+        RecordDuration::new(
+            MkLabel,
+            metrics.clone(),
+            svc::service_fn(|_: http::Request<BoxBody>| {
+                futures::future::ok::<_, Error>(http::Response::new(BoxBody::empty()))
+            }),
+        )
+    };
+
+    // An unsampled request is recorded without an exemplar.
+    let req = http::Request::new(BoxBody::empty());
+    let rsp = mk_svc().oneshot(req).await.expect("request succeeds");
+    rsp.into_body().collect().await.expect("a body");
+    assert!(!encode(&registry).contains("trace_id"));
+
+    let mut req = http::Request::new(BoxBody::empty());
+    req.extensions_mut().insert(SampledTrace {
+        trace_id: Id::from(Bytes::from_static(&[0x4b; 16])),
+    });
+    let rsp = mk_svc().oneshot(req).await.expect("request succeeds");
+    rsp.into_body().collect().await.expect("a body");
+    let text = encode(&registry);
+    let bucket = text
+        .lines()
+        .find(|l| l.starts_with("request_duration_seconds_bucket{le=\"0.1\""))
+        .expect("bucket");
+    assert!(
+        bucket.contains(" # {trace_id=\"4b4b4b4b4b4b4b4b4b4b4b4b4b4b4b4b\"} "),
+        "{text}"
+    );
+}
+
+fn encode(registry: &Registry) -> String {
+    let mut text = String::new();
+    prometheus_client::encoding::text::encode(&mut text, registry).expect("encodes");
+    text
+}
+
+/// A [`MkStreamLabel`] that labels every request.
+#[derive(Clone)]
+struct MkLabel;
+
+#[derive(Clone)]
+struct Label;
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+struct Labels {
+    route: &'static str,
+}
+
+impl MkStreamLabel for MkLabel {
+    type StreamLabel = Label;
+    type DurationLabels = Labels;
+    type StatusLabels = Labels;
+
+    fn mk_stream_labeler<B>(&self, _: &http::Request<B>) -> Option<Self::StreamLabel> {
+        Some(Label)
+    }
+}
+
+impl StreamLabel for Label {
+    type DurationLabels = Labels;
+    type StatusLabels = Labels;
+
+    fn init_response<B>(&mut self, _: &http::Response<B>) {}
+
+    fn end_response(&mut self, _: Result<Option<&http::HeaderMap>, &Error>) {}
+
+    fn status_labels(&self) -> Self::StatusLabels {
+        Labels { route: "test" }
+    }
+
+    fn duration_labels(&self) -> Self::DurationLabels {
+        Labels { route: "test" }
+    }
+}
@@ -1,8 +1,12 @@
+pub mod compression;
+pub mod deadline;
 pub mod inject_failure;
 pub mod modify_header;
 pub mod redirect;
 
 pub use self::{
+    compression::{Compression, Encoding},
+    deadline::Deadline,
     inject_failure::{Distribution, FailureResponse, InjectFailure},
     modify_header::ModifyHeader,
     redirect::{InvalidRedirect, RedirectRequest, Redirection},
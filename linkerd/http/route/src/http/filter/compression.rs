@@ -0,0 +1,227 @@
+use http::header::{self, HeaderMap};
+
+/// Compresses responses using an encoding the client accepts and, optionally,
+/// decompresses encoded request bodies before they are forwarded.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct Compression {
+    /// Encodings that may be applied to responses, in order of preference.
+    pub encodings: Vec<Encoding>,
+
+    /// Media types that are eligible for compression. A type ending with `/*`
+    /// matches all of its subtypes.
+    pub content_types: Vec<String>,
+
+    /// Responses that declare a `content-length` smaller than this are left
+    /// uncompressed.
+    pub min_size: usize,
+
+    /// When set, request bodies encoded with a supported encoding are decoded
+    /// before being forwarded.
+    pub decompress_requests: bool,
+
+    /// Decoded request bodies larger than this fail the request.
+    pub max_decompressed_request_size: usize,
+}
+
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum Encoding {
+    Gzip,
+    Zstd,
+    Brotli,
+}
+
+// === impl Compression ===
+
+impl Default for Compression {
+    fn default() -> Self {
+        Self {
+            encodings: vec![Encoding::Zstd, Encoding::Brotli, Encoding::Gzip],
+            content_types: [
+                "text/*",
+                "application/javascript",
+                "application/json",
+                "application/xml",
+                "image/svg+xml",
+            ]
+            .into_iter()
+            .map(Into::into)
+            .collect(),
+            min_size: 1024,
+            decompress_requests: false,
+            max_decompressed_request_size: 10 * 1024 * 1024,
+        }
+    }
+}
+
+impl Compression {
+    /// Returns the most-preferred configured encoding that is acceptable
+    /// according to the request's `accept-encoding` headers.
+    pub fn negotiate(&self, headers: &HeaderMap) -> Option<Encoding> {
+        let mut wildcard = None;
+        let mut accepted = Vec::new();
+        for value in headers.get_all(header::ACCEPT_ENCODING) {
+            let Ok(value) = value.to_str() else {
+                continue;
+            };
+            for item in value.split(',') {
+                let mut parts = item.split(';');
+                let coding = parts.next().unwrap_or_default().trim();
+                if coding.is_empty() {
+                    continue;
+                }
+                let acceptable = parts
+                    .filter_map(|p| p.trim().strip_prefix("q="))
+                    .next_back()
+                    .map(|q| q.trim().parse::<f32>().map(|q| q > 0.0).unwrap_or(false))
+                    .unwrap_or(true);
+                if coding == "*" {
+                    wildcard = Some(acceptable);
+                } else if let Some(encoding) = Encoding::from_token(coding) {
+                    accepted.push((encoding, acceptable));
+                }
+            }
+        }
+
+        self.encodings.iter().copied().find(|enc| {
+            accepted
+                .iter()
+                .find(|(e, _)| e == enc)
+                .map(|(_, ok)| *ok)
+                .or(wildcard)
+                .unwrap_or(false)
+        })
+    }
+
+    /// Returns true if a response with the given headers may be compressed.
+    pub fn is_eligible(&self, headers: &HeaderMap) -> bool {
+        if headers.contains_key(header::CONTENT_ENCODING) {
+            return false;
+        }
+
+        let size = headers
+            .get(header::CONTENT_LENGTH)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<usize>().ok());
+        if size.is_some_and(|sz| sz < self.min_size) {
+            return false;
+        }
+
+        let Some(content_type) = headers
+            .get(header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+        else {
+            return false;
+        };
+        let media = content_type
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase();
+        // Event streams are delivered incrementally, and buffering them in an
+        // encoder would delay each event indefinitely.
+        if media == "text/event-stream" {
+            return false;
+        }
+        self.content_types
+            .iter()
+            .any(|ct| match ct.strip_suffix("/*") {
+                Some(ty) => media
+                    .strip_prefix(ty)
+                    .is_some_and(|rest| rest.starts_with('/')),
+                None => ct.eq_ignore_ascii_case(&media),
+            })
+    }
+}
+
+// === impl Encoding ===
+
+impl Encoding {
+    /// Parses a `content-encoding` or `accept-encoding` token.
+    pub fn from_token(token: &str) -> Option<Self> {
+        if token.eq_ignore_ascii_case("gzip") || token.eq_ignore_ascii_case("x-gzip") {
+            Some(Self::Gzip)
+        } else if token.eq_ignore_ascii_case("zstd") {
+            Some(Self::Zstd)
+        } else if token.eq_ignore_ascii_case("br") {
+            Some(Self::Brotli)
+        } else {
+            None
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Gzip => "gzip",
+            Self::Zstd => "zstd",
+            Self::Brotli => "br",
+        }
+    }
+}
+
+impl std::fmt::Display for Encoding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http::HeaderValue;
+
+    fn headers(pairs: &[(header::HeaderName, &'static str)]) -> HeaderMap {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.clone(), HeaderValue::from_static(v)))
+            .collect()
+    }
+
+    #[test]
+    fn negotiates_preferred_encoding() {
+        let filter = Compression::default();
+        let accept = |v| headers(&[(header::ACCEPT_ENCODING, v)]);
+
+        assert_eq!(filter.negotiate(&HeaderMap::new()), None);
+        assert_eq!(filter.negotiate(&accept("identity")), None);
+        assert_eq!(filter.negotiate(&accept("gzip")), Some(Encoding::Gzip));
+        assert_eq!(
+            filter.negotiate(&accept("gzip, deflate, br")),
+            Some(Encoding::Brotli)
+        );
+        assert_eq!(
+            filter.negotiate(&accept("zstd;q=0, br;q=0.5, gzip")),
+            Some(Encoding::Brotli)
+        );
+        assert_eq!(filter.negotiate(&accept("*")), Some(Encoding::Zstd));
+        assert_eq!(
+            filter.negotiate(&accept("*;q=0, gzip;q=1.0")),
+            Some(Encoding::Gzip)
+        );
+    }
+
+    #[test]
+    fn checks_eligibility() {
+        let filter = Compression::default();
+
+        assert!(filter.is_eligible(&headers(&[(
+            header::CONTENT_TYPE,
+            "text/html; charset=utf-8"
+        )])));
+        assert!(filter.is_eligible(&headers(&[
+            (header::CONTENT_TYPE, "application/json"),
+            (header::CONTENT_LENGTH, "4096"),
+        ])));
+        assert!(!filter.is_eligible(&headers(&[
+            (header::CONTENT_TYPE, "application/json"),
+            (header::CONTENT_LENGTH, "12"),
+        ])));
+        assert!(!filter.is_eligible(&headers(&[
+            (header::CONTENT_TYPE, "text/plain"),
+            (header::CONTENT_ENCODING, "gzip"),
+        ])));
+        assert!(!filter.is_eligible(&headers(&[(header::CONTENT_TYPE, "image/png")])));
+        assert!(!filter.is_eligible(&headers(&[(header::CONTENT_TYPE, "text/event-stream")])));
+        assert!(!filter.is_eligible(&HeaderMap::new()));
+    }
+}
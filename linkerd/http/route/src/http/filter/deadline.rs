@@ -0,0 +1,18 @@
+use http::header::HeaderName;
+use std::time::Duration;
+
+/// Applies the deadline propagated by a client's proxy as the request's
+/// timeout.
+///
+/// gRPC requests carry their remaining deadline in the `grpc-timeout` header.
+/// Other HTTP requests carry it in `http_header`, when one is configured.
+#[derive(Clone, Debug, Default, Hash, PartialEq, Eq)]
+pub struct Deadline {
+    /// The header from which the remaining deadline of a non-gRPC request is
+    /// read.
+    pub http_header: Option<HeaderName>,
+
+    /// Limits the deadline applied to a request. When set, requests without
+    /// a propagated deadline are also limited to this duration.
+    pub max: Option<Duration>,
+}
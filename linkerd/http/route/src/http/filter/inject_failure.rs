@@ -51,6 +51,12 @@ impl Distribution {
             inner,
         })
     }
+
+    /// Returns the `(numerator, denominator)` ratio of requests that fail.
+    #[inline]
+    pub fn ratio(&self) -> (u32, u32) {
+        (self.numerator, self.denominator)
+    }
 }
 
 impl Default for Distribution {
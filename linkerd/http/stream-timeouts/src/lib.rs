@@ -1,6 +1,7 @@
 //! Tower middleware to express deadlines on streams.
 //!
-//! See [`EnforceTimeouts<S>`].
+//! See [`EnforceTimeouts<S>`]. Deadlines may be propagated to other proxies
+//! with [`PropagateDeadlines<S>`].
 
 use futures::FutureExt;
 use http_body::Frame;
@@ -17,6 +18,10 @@ use std::{
 use thiserror::Error;
 use tokio::{sync::oneshot, time};
 
+pub mod propagate;
+
+pub use self::propagate::{propagated_deadline, PropagateDeadlines};
+
 /// A request extension set on HTTP requests that expresses deadlines to be
 /// enforced by the proxy.
 #[derive(Clone, Debug, Default)]
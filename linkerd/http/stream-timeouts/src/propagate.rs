@@ -0,0 +1,238 @@
+//! Propagation of request deadlines across proxies.
+//!
+//! The remaining time before a request's [`StreamLifetime`] deadline is
+//! written to the request's headers so that the next hop may stop working on
+//! the request when the caller would give up on it. gRPC requests use the
+//! standard `grpc-timeout` header, and other HTTP requests may use a
+//! configured header whose value is a number of milliseconds, e.g. `1500ms`.
+//!
+//! [`StreamLifetime`]: crate::StreamLifetime
+
+use crate::StreamTimeouts;
+use http::header::{self, HeaderMap, HeaderName, HeaderValue};
+use linkerd_stack as svc;
+use std::task::{Context, Poll};
+use tokio::time;
+
+pub const GRPC_TIMEOUT: HeaderName = HeaderName::from_static("grpc-timeout");
+
+/// Writes the remaining time before each request's deadline to its headers.
+#[derive(Clone, Debug)]
+pub struct PropagateDeadlines<S> {
+    http_header: Option<HeaderName>,
+    inner: S,
+}
+
+/// The `grpc-timeout` value may have at most 8 digits.
+const MAX_GRPC_TIMEOUT_VALUE: u128 = 99_999_999;
+
+// === impl PropagateDeadlines ===
+
+impl<S> PropagateDeadlines<S> {
+    /// Returns a layer that propagates deadlines on gRPC requests and, when
+    /// `http_header` is set, on other HTTP requests.
+    pub fn layer(
+        http_header: Option<HeaderName>,
+    ) -> impl svc::layer::Layer<S, Service = Self> + Clone {
+        svc::layer::mk(move |inner| Self {
+            http_header: http_header.clone(),
+            inner,
+        })
+    }
+}
+
+impl<S, B> svc::Service<http::Request<B>> for PropagateDeadlines<S>
+where
+    S: svc::Service<http::Request<B>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: http::Request<B>) -> Self::Future {
+        let limit = req
+            .extensions()
+            .get::<StreamTimeouts>()
+            .and_then(|t| t.limit);
+        if let Some(limit) = limit {
+            let remaining = limit
+                .deadline
+                .saturating_duration_since(time::Instant::now());
+            tracing::trace!(?remaining, "Propagating deadline");
+            set_deadline(req.headers_mut(), remaining, self.http_header.as_ref());
+        }
+        self.inner.call(req)
+    }
+}
+
+/// Returns the remaining deadline propagated on a request.
+///
+/// The `grpc-timeout` header is read for gRPC requests; otherwise,
+/// `http_header` is read, if it is set.
+pub fn propagated_deadline(
+    headers: &HeaderMap,
+    http_header: Option<&HeaderName>,
+) -> Option<time::Duration> {
+    if is_grpc(headers) {
+        return headers.get(GRPC_TIMEOUT).and_then(parse_grpc_timeout);
+    }
+    headers.get(http_header?).and_then(parse_millis)
+}
+
+/// Sets a request's propagated deadline, unless it already carries a shorter
+/// one.
+fn set_deadline(
+    headers: &mut HeaderMap,
+    remaining: time::Duration,
+    http_header: Option<&HeaderName>,
+) {
+    let (name, encode): (_, fn(time::Duration) -> HeaderValue) = if is_grpc(headers) {
+        (&GRPC_TIMEOUT, encode_grpc_timeout)
+    } else if let Some(name) = http_header {
+        (name, encode_millis)
+    } else {
+        return;
+    };
+
+    if let Some(prior) = propagated_deadline(headers, http_header) {
+        if prior <= remaining {
+            return;
+        }
+    }
+    headers.insert(name.clone(), encode(remaining));
+}
+
+fn is_grpc(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|ct| ct.starts_with("application/grpc"))
+}
+
+/// Encodes a `grpc-timeout` value with the finest unit that can express it.
+pub fn encode_grpc_timeout(timeout: time::Duration) -> HeaderValue {
+    let nanos = timeout.as_nanos().max(1);
+    let (value, unit) = [
+        (1, 'n'),
+        (1_000, 'u'),
+        (1_000_000, 'm'),
+        (1_000_000_000, 'S'),
+        (60 * 1_000_000_000, 'M'),
+        (60 * 60 * 1_000_000_000, 'H'),
+    ]
+    .into_iter()
+    // Round up so that the propagated deadline is never shorter than the
+    // configured one.
+    .map(|(scale, unit)| (nanos.div_ceil(scale), unit))
+    .find(|(value, _)| *value <= MAX_GRPC_TIMEOUT_VALUE)
+    .unwrap_or((MAX_GRPC_TIMEOUT_VALUE, 'H'));
+    HeaderValue::try_from(format!("{value}{unit}")).expect("timeout must be a valid header")
+}
+
+/// Parses a `grpc-timeout` value.
+pub fn parse_grpc_timeout(value: &HeaderValue) -> Option<time::Duration> {
+    let value = value.to_str().ok()?;
+    let (digits, unit) = value.split_at(value.len().checked_sub(1)?);
+    if digits.is_empty() || digits.len() > 8 || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let n = digits.parse::<u64>().ok()?;
+    let timeout = match unit {
+        "H" => time::Duration::from_secs(n * 60 * 60),
+        "M" => time::Duration::from_secs(n * 60),
+        "S" => time::Duration::from_secs(n),
+        "m" => time::Duration::from_millis(n),
+        "u" => time::Duration::from_micros(n),
+        "n" => time::Duration::from_nanos(n),
+        _ => return None,
+    };
+    Some(timeout)
+}
+
+fn encode_millis(timeout: time::Duration) -> HeaderValue {
+    let millis = timeout.as_nanos().div_ceil(1_000_000);
+    HeaderValue::try_from(format!("{millis}ms")).expect("timeout must be a valid header")
+}
+
+fn parse_millis(value: &HeaderValue) -> Option<time::Duration> {
+    let millis = value.to_str().ok()?.trim().strip_suffix("ms")?;
+    millis.parse().ok().map(time::Duration::from_millis)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEADLINE: HeaderName = HeaderName::from_static("l5d-deadline");
+
+    fn grpc_headers() -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("application/grpc+proto"),
+        );
+        headers
+    }
+
+    #[test]
+    fn grpc_timeout_roundtrip() {
+        for (timeout, encoded) in [
+            (time::Duration::from_nanos(0), "1n"),
+            (time::Duration::from_millis(1500), "1500000u"),
+            (time::Duration::from_secs(100), "100000m"),
+            (time::Duration::from_secs(2 * 24 * 60 * 60), "172800S"),
+        ] {
+            let value = encode_grpc_timeout(timeout);
+            assert_eq!(value, encoded);
+            assert!(parse_grpc_timeout(&value).unwrap() >= timeout);
+        }
+
+        for invalid in ["", "m", "10", "10x", "-1S", "123456789n"] {
+            let value = HeaderValue::from_static(invalid);
+            assert_eq!(parse_grpc_timeout(&value), None, "{invalid:?}");
+        }
+    }
+
+    #[test]
+    fn sets_grpc_timeout() {
+        let mut headers = grpc_headers();
+        set_deadline(&mut headers, time::Duration::from_secs(2), Some(&DEADLINE));
+        assert_eq!(headers[GRPC_TIMEOUT], "2000000u");
+        assert!(headers.get(DEADLINE).is_none());
+
+        // A shorter deadline set by the client is preserved.
+        set_deadline(&mut headers, time::Duration::from_secs(3), Some(&DEADLINE));
+        assert_eq!(headers[GRPC_TIMEOUT], "2000000u");
+
+        set_deadline(&mut headers, time::Duration::from_secs(1), Some(&DEADLINE));
+        assert_eq!(
+            propagated_deadline(&headers, None),
+            Some(time::Duration::from_secs(1))
+        );
+    }
+
+    #[test]
+    fn sets_http_header() {
+        let mut headers = HeaderMap::new();
+        set_deadline(&mut headers, time::Duration::from_secs(2), None);
+        assert!(headers.is_empty());
+
+        set_deadline(
+            &mut headers,
+            time::Duration::from_micros(1500),
+            Some(&DEADLINE),
+        );
+        assert_eq!(headers[DEADLINE], "2ms");
+        assert!(headers.get(GRPC_TIMEOUT).is_none());
+        assert_eq!(
+            propagated_deadline(&headers, Some(&DEADLINE)),
+            Some(time::Duration::from_millis(2))
+        );
+        assert_eq!(propagated_deadline(&headers, None), None);
+    }
+}
@@ -9,8 +9,13 @@ use pin_project::{pin_project, pinned_drop};
 use std::{
     future::Future,
     pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
     task::{Context, Poll},
 };
+use tokio::time::Instant;
 use tracing::debug;
 
 /// Provides optional HTTP/1.1 upgrade support on the body.
@@ -37,6 +42,23 @@ pub struct Connection<T> {
     #[pin]
     transport: T,
     absolute_form: bool,
+    info: ConnectionInfo,
+}
+
+/// Describes a client connection established by [`HyperConnect`].
+///
+/// The connection's [`Connected`] metadata carries this as an extra, so that
+/// a client may track how each of its pooled connections is used.
+///
+/// [`Connected`]: hyper_util::client::legacy::connect::Connected
+#[derive(Clone, Debug)]
+pub struct ConnectionInfo(Arc<ConnectionState>);
+
+#[derive(Debug)]
+struct ConnectionState {
+    established: Instant,
+    responses: AtomicUsize,
+    jitter: f64,
 }
 
 /// Future returned by `HyperConnect`.
@@ -170,6 +192,7 @@ where
         Poll::Ready(Ok(hyper_util::rt::TokioIo::new(Connection {
             transport,
             absolute_form: *this.absolute_form,
+            info: ConnectionInfo::new(),
         })))
     }
 }
@@ -270,6 +293,38 @@ impl<C: hyper::rt::Write> hyper::rt::Write for Connection<C> {
 
 impl<C> hyper_util::client::legacy::connect::Connection for Connection<C> {
     fn connected(&self) -> hyper_util::client::legacy::connect::Connected {
-        hyper_util::client::legacy::connect::Connected::new().proxy(self.absolute_form)
+        hyper_util::client::legacy::connect::Connected::new()
+            .proxy(self.absolute_form)
+            .extra(self.info.clone())
+    }
+}
+
+// === impl ConnectionInfo ===
+
+impl ConnectionInfo {
+    fn new() -> Self {
+        Self(Arc::new(ConnectionState {
+            established: Instant::now(),
+            responses: AtomicUsize::new(0),
+            jitter: rand::random(),
+        }))
+    }
+
+    /// Returns the time at which the connection was established.
+    pub fn established(&self) -> Instant {
+        self.0.established
+    }
+
+    /// Records a response received on the connection, returning the number
+    /// of responses received on it so far.
+    pub fn record_response(&self) -> usize {
+        self.0.responses.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    /// Returns a random value in `[0, 1)` that is fixed for the lifetime of
+    /// the connection, so that deadlines derived from it are staggered across
+    /// connections.
+    pub fn jitter(&self) -> f64 {
+        self.0.jitter
     }
 }
@@ -14,17 +14,25 @@
 //!
 //! Note that HTTP/2 does *NOT* provide support for the `Upgrade` header field, per
 //! [RFC 9113 § 8.6][rfc9113]. HTTP/2 is a multiplexed protocol, and connection upgrades are
-//! thus inapplicable.
+//! thus inapplicable. Instead, HTTP/2 uses the extended CONNECT method, per
+//! [RFC 8441][rfc8441], to bootstrap WebSockets over a single stream. These requests are bridged in
+//! the same way as HTTP/1.1 upgrades.
 //!
 //! [rfc9110-connection]: https://www.rfc-editor.org/rfc/rfc9110#name-connection
 //! [rfc9110-upgrade]: https://www.rfc-editor.org/rfc/rfc9110#field.upgrade
 //! [rfc9110-101]: https://www.rfc-editor.org/rfc/rfc9110#name-101-switching-protocols
 //! [rfc9113]: https://www.rfc-editor.org/rfc/rfc9113.html#name-the-upgrade-header-field
+//! [rfc8441]: https://www.rfc-editor.org/rfc/rfc8441
 
-pub use self::upgrade::Service;
+pub use self::{
+    session::{NewSession, SessionFamilies, SessionMetrics, SessionTimeouts},
+    upgrade::Service,
+};
 
 pub mod glue;
+pub mod session;
 pub mod upgrade;
+mod websocket;
 
 /// Removes connection headers from the given [`HeaderMap`][http::HeaderMap].
 ///
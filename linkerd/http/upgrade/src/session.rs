@@ -0,0 +1,539 @@
+//! Upgraded connections as sessions.
+//!
+//! Once an HTTP/1.1 upgrade (or a CONNECT tunnel) succeeds, the connection is
+//! proxied as raw bytes. A [`Session`] configures timeouts for the upgraded
+//! connection and the metrics that describe it, so that upgraded connections
+//! may be treated according to the route that accepted them.
+
+use crate::{
+    upgrade::Http11Upgrade,
+    websocket::{self, FrameParser},
+};
+use linkerd_duplex::Duplex;
+use linkerd_io::{self as io, AsyncRead, AsyncWrite, AsyncWriteExt, ReadBuf};
+use linkerd_metrics::prom::{
+    encoding::{EncodeLabel, EncodeLabelSet, EncodeLabelValue, LabelSetEncoder},
+    Counter, Family, Registry, Unit,
+};
+use linkerd_stack::{layer, ExtractParam, NewService, Param, Service};
+use std::{
+    fmt::Debug,
+    hash::Hash,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::time::{self, Instant};
+use tracing::{debug, trace};
+
+/// Limits the lifetime of an upgraded connection.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct SessionTimeouts {
+    /// Closes the connection if no data is transferred in either direction
+    /// for this long.
+    pub idle: Option<Duration>,
+
+    /// Closes the connection once it has been open for this long.
+    pub lifetime: Option<Duration>,
+}
+
+/// Configures the session that follows a successful upgrade.
+#[derive(Clone, Debug, Default)]
+pub struct Session {
+    pub timeouts: SessionTimeouts,
+    pub metrics: SessionMetrics,
+}
+
+/// Metric families describing upgraded connections, labeled by `L`.
+#[derive(Clone, Debug)]
+pub struct SessionFamilies<L> {
+    upgrades: Family<Labels<L>, Counter>,
+    bytes: Family<Labels<L>, Counter>,
+    messages: Family<Labels<L>, Counter>,
+    closed: Family<Labels<L>, Counter>,
+}
+
+/// Metrics for the upgraded connections of a single route.
+#[derive(Clone, Debug, Default)]
+pub struct SessionMetrics {
+    upgraded: Counter,
+    failed: Counter,
+    request_bytes: Counter,
+    response_bytes: Counter,
+    request_messages: Counter,
+    response_messages: Counter,
+    closed_eof: Counter,
+    closed_error: Counter,
+    closed_idle: Counter,
+    closed_lifetime: Counter,
+    closed_shutdown: Counter,
+}
+
+/// A [`NewService`] that configures a [`Session`] on each upgrade request's
+/// [`Http11Upgrade`] extension.
+#[derive(Clone, Debug)]
+pub struct NewSession<L, N> {
+    families: SessionFamilies<L>,
+    inner: N,
+}
+
+#[derive(Clone, Debug)]
+pub struct SetSession<T, L, S> {
+    target: T,
+    families: SessionFamilies<L>,
+    timeouts: SessionTimeouts,
+    inner: S,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+struct Labels<L> {
+    labels: L,
+    kind: LabelKind,
+}
+
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+enum LabelKind {
+    Result(UpgradeResult),
+    Direction(Direction),
+    Reason(CloseReason),
+}
+
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq, EncodeLabelValue)]
+#[allow(non_camel_case_types)]
+enum UpgradeResult {
+    success,
+    failure,
+}
+
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq, EncodeLabelValue)]
+#[allow(non_camel_case_types)]
+enum Direction {
+    request,
+    response,
+}
+
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq, EncodeLabelValue)]
+#[allow(non_camel_case_types)]
+enum CloseReason {
+    eof,
+    error,
+    idle_timeout,
+    max_lifetime,
+    shutdown,
+}
+
+/// Records the bytes (and WebSocket messages) written to one side of an
+/// upgraded connection.
+struct Instrumented<T> {
+    io: T,
+    bytes: Counter,
+    messages: Counter,
+    frames: Option<FrameParser>,
+    activity: Arc<Activity>,
+}
+
+/// Tracks when data was last transferred on a session.
+#[derive(Debug)]
+struct Activity {
+    opened: Instant,
+    /// Milliseconds between `opened` and the most recent transfer.
+    last_ms: AtomicU64,
+}
+
+// === impl SessionFamilies ===
+
+impl<L> Default for SessionFamilies<L>
+where
+    L: Clone + Debug + Hash + Eq + Send + Sync + EncodeLabelSet + 'static,
+{
+    fn default() -> Self {
+        Self {
+            upgrades: Family::default(),
+            bytes: Family::default(),
+            messages: Family::default(),
+            closed: Family::default(),
+        }
+    }
+}
+
+impl<L> SessionFamilies<L>
+where
+    L: Clone + Debug + Hash + Eq + Send + Sync + EncodeLabelSet + 'static,
+{
+    pub fn register(registry: &mut Registry) -> Self {
+        let upgrades = Family::default();
+        registry.register(
+            "requests",
+            "Upgrade and CONNECT requests, by whether the connection was upgraded",
+            upgrades.clone(),
+        );
+
+        let bytes = Family::default();
+        registry.register_with_unit(
+            "transferred",
+            "Data transferred over upgraded connections",
+            Unit::Bytes,
+            bytes.clone(),
+        );
+
+        let messages = Family::default();
+        registry.register(
+            "websocket_messages",
+            "WebSocket data messages transferred over upgraded connections",
+            messages.clone(),
+        );
+
+        let closed = Family::default();
+        registry.register(
+            "closed",
+            "Upgraded connections closed, by reason",
+            closed.clone(),
+        );
+
+        Self {
+            upgrades,
+            bytes,
+            messages,
+            closed,
+        }
+    }
+
+    pub fn metrics(&self, labels: &L) -> SessionMetrics {
+        use self::{CloseReason as Close, Direction as Dir, LabelKind as K, UpgradeResult as R};
+
+        let get = |family: &Family<Labels<L>, Counter>, kind| {
+            family
+                .get_or_create(&Labels {
+                    labels: labels.clone(),
+                    kind,
+                })
+                .clone()
+        };
+        SessionMetrics {
+            upgraded: get(&self.upgrades, K::Result(R::success)),
+            failed: get(&self.upgrades, K::Result(R::failure)),
+            request_bytes: get(&self.bytes, K::Direction(Dir::request)),
+            response_bytes: get(&self.bytes, K::Direction(Dir::response)),
+            request_messages: get(&self.messages, K::Direction(Dir::request)),
+            response_messages: get(&self.messages, K::Direction(Dir::response)),
+            closed_eof: get(&self.closed, K::Reason(Close::eof)),
+            closed_error: get(&self.closed, K::Reason(Close::error)),
+            closed_idle: get(&self.closed, K::Reason(Close::idle_timeout)),
+            closed_lifetime: get(&self.closed, K::Reason(Close::max_lifetime)),
+            closed_shutdown: get(&self.closed, K::Reason(Close::shutdown)),
+        }
+    }
+}
+
+// === impl Labels ===
+
+impl<L: EncodeLabelSet> EncodeLabelSet for Labels<L> {
+    fn encode(&self, enc: &mut LabelSetEncoder<'_>) -> std::fmt::Result {
+        self.labels.encode(enc)?;
+        match self.kind {
+            LabelKind::Result(result) => ("result", result).encode(enc.encode_label()),
+            LabelKind::Direction(direction) => ("direction", direction).encode(enc.encode_label()),
+            LabelKind::Reason(reason) => ("reason", reason).encode(enc.encode_label()),
+        }
+    }
+}
+
+// === impl NewSession ===
+
+impl<L: Clone, N> NewSession<L, N> {
+    /// Returns a layer that configures sessions for upgrade requests.
+    ///
+    /// Timeouts are configured from each target, and metric labels are
+    /// extracted from each request by the target.
+    pub fn layer(families: SessionFamilies<L>) -> impl layer::Layer<N, Service = Self> + Clone {
+        layer::mk(move |inner| Self {
+            families: families.clone(),
+            inner,
+        })
+    }
+}
+
+impl<T, L, N> NewService<T> for NewSession<L, N>
+where
+    T: Param<SessionTimeouts> + Clone,
+    L: Clone,
+    N: NewService<T>,
+{
+    type Service = SetSession<T, L, N::Service>;
+
+    fn new_service(&self, target: T) -> Self::Service {
+        SetSession {
+            timeouts: target.param(),
+            families: self.families.clone(),
+            inner: self.inner.new_service(target.clone()),
+            target,
+        }
+    }
+}
+
+// === impl SetSession ===
+
+impl<B, T, L, S> Service<http::Request<B>> for SetSession<T, L, S>
+where
+    T: ExtractParam<L, http::Request<B>>,
+    L: Clone + Debug + Hash + Eq + Send + Sync + EncodeLabelSet + 'static,
+    S: Service<http::Request<B>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: http::Request<B>) -> Self::Future {
+        if let Some(upgrade) = req.extensions().get::<Http11Upgrade>() {
+            let labels = self.target.extract_param(&req);
+            upgrade.set_session(Session {
+                timeouts: self.timeouts.clone(),
+                metrics: self.families.metrics(&labels),
+            });
+        }
+        self.inner.call(req)
+    }
+}
+
+// === impl SessionMetrics ===
+
+impl SessionMetrics {
+    pub(crate) fn record_upgrade(&self, upgraded: bool) {
+        if upgraded {
+            self.upgraded.inc();
+        } else {
+            self.failed.inc();
+        }
+    }
+
+    fn record_close(&self, reason: CloseReason) {
+        match reason {
+            CloseReason::eof => self.closed_eof.inc(),
+            CloseReason::error => self.closed_error.inc(),
+            CloseReason::idle_timeout => self.closed_idle.inc(),
+            CloseReason::max_lifetime => self.closed_lifetime.inc(),
+            CloseReason::shutdown => self.closed_shutdown.inc(),
+        };
+    }
+}
+
+// === impl Session ===
+
+impl Session {
+    /// Proxies an upgraded connection until either side closes it, a timeout
+    /// elapses, or the process shuts down.
+    ///
+    /// `client` is the connection to the upstream server, and `server` is the
+    /// connection accepted from the downstream client. When the connection
+    /// carries WebSocket frames, a close frame is sent to each peer on
+    /// shutdown.
+    pub(crate) async fn run<C, S>(self, client: C, server: S, websocket: bool, drain: drain::Watch)
+    where
+        C: AsyncRead + AsyncWrite + Unpin,
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        let Self { timeouts, metrics } = self;
+        let activity = Arc::new(Activity {
+            opened: Instant::now(),
+            last_ms: AtomicU64::new(0),
+        });
+        let mut client = Instrumented {
+            io: client,
+            bytes: metrics.request_bytes.clone(),
+            messages: metrics.request_messages.clone(),
+            frames: websocket.then(FrameParser::default),
+            activity: activity.clone(),
+        };
+        let mut server = Instrumented {
+            io: server,
+            bytes: metrics.response_bytes.clone(),
+            messages: metrics.response_messages.clone(),
+            frames: websocket.then(FrameParser::default),
+            activity: activity.clone(),
+        };
+
+        let idle = async {
+            match timeouts.idle {
+                Some(idle) => activity.idle(idle).await,
+                None => futures::future::pending().await,
+            }
+        };
+        let lifetime = async {
+            match timeouts.lifetime {
+                Some(lifetime) => time::sleep(lifetime).await,
+                None => futures::future::pending().await,
+            }
+        };
+
+        let reason = tokio::select! {
+            res = Duplex::new(&mut client, &mut server) => match res {
+                Ok(()) => CloseReason::eof,
+                Err(error) => {
+                    debug!(%error, "Upgraded connection failed");
+                    CloseReason::error
+                }
+            },
+            () = idle => CloseReason::idle_timeout,
+            () = lifetime => CloseReason::max_lifetime,
+            release = drain.signaled() => {
+                // Hold the drain open until the peers have been notified.
+                close(&mut client, &mut server).await;
+                drop(release);
+                CloseReason::shutdown
+            }
+        };
+        if matches!(
+            reason,
+            CloseReason::idle_timeout | CloseReason::max_lifetime
+        ) {
+            close(&mut client, &mut server).await;
+        }
+        debug!(?reason, "Upgraded connection closed");
+        metrics.record_close(reason);
+    }
+}
+
+/// Sends close frames to WebSocket peers, where possible, and shuts down
+/// both connections.
+async fn close<C, S>(client: &mut Instrumented<C>, server: &mut Instrumented<S>)
+where
+    C: AsyncWrite + Unpin,
+    S: AsyncWrite + Unpin,
+{
+    // Frames sent to the upstream server must be masked.
+    if let Err(error) = client.close(true).await {
+        trace!(%error, "Failed to close upstream connection");
+    }
+    if let Err(error) = server.close(false).await {
+        trace!(%error, "Failed to close downstream connection");
+    }
+}
+
+// === impl Instrumented ===
+
+impl<T: AsyncWrite + Unpin> Instrumented<T> {
+    async fn close(&mut self, masked: bool) -> io::Result<()> {
+        // A close frame may only be sent between frames. If the proxy stopped
+        // in the middle of a frame, the connection is simply shut down.
+        if self.frames.as_ref().is_some_and(FrameParser::at_boundary) {
+            let frame = websocket::close_frame(websocket::GOING_AWAY, masked);
+            self.io.write_all(&frame).await?;
+        }
+        self.io.shutdown().await
+    }
+}
+
+impl<T: AsyncRead + Unpin> AsyncRead for Instrumented<T> {
+    #[inline]
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> io::Poll<()> {
+        Pin::new(&mut self.get_mut().io).poll_read(cx, buf)
+    }
+}
+
+impl<T: AsyncWrite + Unpin> AsyncWrite for Instrumented<T> {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> io::Poll<usize> {
+        let this = self.get_mut();
+        let n = futures::ready!(Pin::new(&mut this.io).poll_write(cx, buf))?;
+        this.bytes.inc_by(n as u64);
+        if let Some(frames) = this.frames.as_mut() {
+            let messages = frames.feed(&buf[..n]);
+            if messages > 0 {
+                this.messages.inc_by(messages as u64);
+            }
+        }
+        this.activity.touch();
+        Poll::Ready(Ok(n))
+    }
+
+    #[inline]
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> io::Poll<()> {
+        Pin::new(&mut self.get_mut().io).poll_flush(cx)
+    }
+
+    #[inline]
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> io::Poll<()> {
+        Pin::new(&mut self.get_mut().io).poll_shutdown(cx)
+    }
+}
+
+// === impl Activity ===
+
+impl Activity {
+    fn touch(&self) {
+        let ms = Instant::now()
+            .saturating_duration_since(self.opened)
+            .as_millis();
+        self.last_ms
+            .store(ms.try_into().unwrap_or(u64::MAX), Ordering::Relaxed);
+    }
+
+    /// Completes once no data has been transferred for `idle`.
+    async fn idle(&self, idle: Duration) {
+        loop {
+            let last = self.opened + Duration::from_millis(self.last_ms.load(Ordering::Relaxed));
+            let deadline = last + idle;
+            if Instant::now() >= deadline {
+                return;
+            }
+            time::sleep_until(deadline).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use linkerd_io::AsyncReadExt;
+
+    #[tokio::test(start_paused = true)]
+    async fn closes_idle_websockets() {
+        let (client, mut upstream) = io::duplex(1024);
+        let (server, mut downstream) = io::duplex(1024);
+        let (drain_tx, drain) = drain::channel();
+        let session = Session {
+            timeouts: SessionTimeouts {
+                idle: Some(Duration::from_secs(10)),
+                lifetime: None,
+            },
+            metrics: SessionMetrics::default(),
+        };
+        let metrics = session.metrics.clone();
+        let task = tokio::spawn(session.run(client, server, true, drain));
+
+        // An unmasked text frame from the server is forwarded downstream.
+        upstream.write_all(&[0x81, 0x02, b'h', b'i']).await.unwrap();
+        let mut buf = [0; 4];
+        downstream.read_exact(&mut buf).await.unwrap();
+        assert_eq!(buf, [0x81, 0x02, b'h', b'i']);
+
+        time::sleep(Duration::from_secs(11)).await;
+        task.await.unwrap();
+        assert_eq!(metrics.response_bytes.get(), 4);
+        assert_eq!(metrics.response_messages.get(), 1);
+        assert_eq!(metrics.closed_idle.get(), 1);
+
+        // Both peers are sent a close frame before the connections are shut
+        // down.
+        let mut frame = Vec::new();
+        downstream.read_to_end(&mut frame).await.unwrap();
+        assert_eq!(frame, websocket::close_frame(websocket::GOING_AWAY, false));
+        let mut frame = Vec::new();
+        upstream.read_to_end(&mut frame).await.unwrap();
+        assert_eq!(frame[..2], [0x88, 0x82]);
+
+        drop(drain_tx);
+    }
+}
@@ -1,19 +1,18 @@
 //! HTTP/1.1 Upgrades
 
-use crate::glue::UpgradeBody;
+use crate::{glue::UpgradeBody, session::Session};
 use futures::{
     future::{self, Either},
     TryFutureExt,
 };
 use hyper::upgrade::OnUpgrade;
-use linkerd_duplex::Duplex;
 use std::{
     fmt, mem,
     sync::Arc,
     task::{Context, Poll},
 };
 use tracing::instrument::Instrument;
-use tracing::{debug, info, trace};
+use tracing::{debug, trace};
 use try_lock::TryLock;
 
 /// A type inserted into `http::Extensions` to bridge together HTTP Upgrades.
@@ -22,6 +21,11 @@ use try_lock::TryLock;
 /// inserted into the `Request::extensions()`. If the HTTP1 client service
 /// also detects an upgrade, the two `OnUpgrade` futures will be joined
 /// together with the glue in this type.
+///
+/// HTTP/2 extended CONNECT requests ([RFC 8441][rfc8441]) are bridged in the
+/// same way.
+///
+/// [rfc8441]: https://www.rfc-editor.org/rfc/rfc8441
 pub struct Http11Upgrade {
     half: Half,
     inner: Option<Arc<Inner>>,
@@ -40,6 +44,9 @@ struct Http11UpgradeHalves {
 struct Inner {
     server: TryLock<Option<OnUpgrade>>,
     client: TryLock<Option<OnUpgrade>>,
+    session: TryLock<Option<Session>>,
+    /// Indicates that the upgraded connection carries WebSocket frames.
+    websocket: bool,
     upgrade_drain_signal: Option<drain::Watch>,
 }
 
@@ -70,10 +77,12 @@ impl Http11Upgrade {
     ///
     /// Each handle is used to insert 1 half of the upgrade. When both handles
     /// have inserted, the upgrade future will be spawned onto the executor.
-    fn halves(upgrade_drain_signal: drain::Watch) -> Http11UpgradeHalves {
+    fn halves(upgrade_drain_signal: drain::Watch, websocket: bool) -> Http11UpgradeHalves {
         let inner = Arc::new(Inner {
             server: TryLock::new(None),
             client: TryLock::new(None),
+            session: TryLock::new(None),
+            websocket,
             upgrade_drain_signal: Some(upgrade_drain_signal),
         });
 
@@ -118,6 +127,18 @@ impl Http11Upgrade {
             Self { inner: None, half } => Err(AlreadyInserted { half, upgrade }),
         }
     }
+
+    /// Configures the timeouts and metrics for the connection, once upgraded.
+    ///
+    /// As with [`insert_half()`][Self::insert_half], this has no effect when
+    /// called on a clone of the upgrade extension.
+    pub fn set_session(&self, session: Session) {
+        if let Some(inner) = self.inner.as_ref() {
+            if let Some(mut lock) = inner.session.try_lock() {
+                *lock = Some(session);
+            }
+        }
+    }
 }
 
 impl fmt::Debug for Http11Upgrade {
@@ -155,6 +176,9 @@ impl Drop for Inner {
         // We can safely take the futures out of their locks.
         let server = mem::replace(&mut self.server, TryLock::new(None)).into_inner();
         let client = mem::replace(&mut self.client, TryLock::new(None)).into_inner();
+        let session = mem::replace(&mut self.session, TryLock::new(None))
+            .into_inner()
+            .unwrap_or_default();
         if let (Some(server), Some(client)) = (server, client) {
             trace!("HTTP/1.1 upgrade has both halves");
 
@@ -162,30 +186,33 @@ impl Drop for Inner {
 
             let client_upgrade = client.map_err(|e| debug!("client HTTP upgrade error: {}", e));
 
+            let websocket = self.websocket;
+            let drain = self
+                .upgrade_drain_signal
+                .take()
+                .expect("only taken in drop");
             let both_upgrades = async move {
-                let (server_conn, client_conn) = tokio::try_join!(server_upgrade, client_upgrade)?;
+                let Ok((server_conn, client_conn)) =
+                    tokio::try_join!(server_upgrade, client_upgrade)
+                else {
+                    session.metrics.record_upgrade(false);
+                    return;
+                };
                 trace!("HTTP upgrade successful");
+                session.metrics.record_upgrade(true);
                 use hyper_util::rt::TokioIo;
                 let client_conn = TokioIo::new(client_conn);
                 let server_conn = TokioIo::new(server_conn);
-                if let Err(e) = Duplex::new(client_conn, server_conn).await {
-                    info!("tcp duplex error: {}", e)
-                }
-                Ok::<(), ()>(())
+                // The session holds the drain signal so that the process
+                // doesn't close before its peers have been notified.
+                session
+                    .run(client_conn, server_conn, websocket, drain)
+                    .await;
             };
-            // There's nothing to do when drain is signaled, we just have to hope
-            // the sockets finish soon. However, the drain signal still needs to
-            // 'watch' the TCP future so that the process doesn't close early.
-            tokio::spawn(
-                self.upgrade_drain_signal
-                    .take()
-                    .expect("only taken in drop")
-                    .ignore_signaled()
-                    .release_after(both_upgrades)
-                    .in_current_span(),
-            );
+            tokio::spawn(both_upgrades.in_current_span());
         } else {
             trace!("HTTP/1.1 upgrade half missing");
+            session.metrics.record_upgrade(false);
         }
     }
 }
@@ -226,6 +253,23 @@ where
     }
 
     fn call(&mut self, mut req: http::Request<ReqB>) -> Self::Future {
+        if req.version() == http::Version::HTTP_2 {
+            // HTTP/2 requests are only inspected for extended CONNECT.
+            let protocol = req
+                .extensions()
+                .get::<hyper::ext::Protocol>()
+                .filter(|_| req.method() == http::Method::CONNECT)
+                .map(|protocol| protocol.as_str().eq_ignore_ascii_case("websocket"));
+            let upgrade = protocol.map(|websocket| {
+                trace!(websocket, "server request is an extended CONNECT");
+                let halves = Http11Upgrade::halves(self.upgrade_drain_signal.clone(), websocket);
+                req.extensions_mut().insert(halves.client);
+                (halves.server, hyper::upgrade::on(&mut req))
+            });
+            let req = req.map(|body| UpgradeBody::new(body, upgrade));
+            return Either::Left(self.service.call(req));
+        }
+
         // Should this rejection happen later in the Service stack?
         //
         // Rejecting here means telemetry doesn't record anything about it...
@@ -244,7 +288,11 @@ where
             // cannot be removed.
 
             // Setup HTTP Upgrade machinery.
-            let halves = Http11Upgrade::halves(self.upgrade_drain_signal.clone());
+            let websocket = req
+                .headers()
+                .get(http::header::UPGRADE)
+                .is_some_and(|v| v.as_bytes().eq_ignore_ascii_case(b"websocket"));
+            let halves = Http11Upgrade::halves(self.upgrade_drain_signal.clone(), websocket);
             req.extensions_mut().insert(halves.client);
             let on_upgrade = hyper::upgrade::on(&mut req);
 
@@ -0,0 +1,195 @@
+//! Minimal WebSocket framing, per [RFC 6455 § 5][rfc6455-5].
+//!
+//! Upgraded connections are proxied as raw bytes. This module only parses
+//! enough of each frame's header to count messages and to find the boundaries
+//! between frames, where a close frame may safely be injected.
+//!
+//! [rfc6455-5]: https://www.rfc-editor.org/rfc/rfc6455#section-5
+
+/// The close status code indicating that an endpoint is going away, e.g. due
+/// to a server shutting down.
+pub(crate) const GOING_AWAY: u16 = 1001;
+
+/// The longest possible frame header: 2 bytes of flags and length, 8 bytes of
+/// extended length, and a 4-byte masking key.
+const MAX_HEADER_LEN: usize = 14;
+
+const OPCODE_CONTINUATION: u8 = 0x0;
+const OPCODE_TEXT: u8 = 0x1;
+const OPCODE_BINARY: u8 = 0x2;
+const OPCODE_CLOSE: u8 = 0x8;
+
+/// Incrementally parses frame headers from one direction of a WebSocket
+/// connection.
+#[derive(Debug, Default)]
+pub(crate) struct FrameParser {
+    header: [u8; MAX_HEADER_LEN],
+    header_len: usize,
+    /// The number of payload bytes remaining in the current frame.
+    payload: u64,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct FrameHeader {
+    fin: bool,
+    opcode: u8,
+    payload_len: u64,
+}
+
+// === impl FrameParser ===
+
+impl FrameParser {
+    /// Consumes bytes from the stream, returning the number of data messages
+    /// whose final frame was started.
+    pub(crate) fn feed(&mut self, mut buf: &[u8]) -> usize {
+        let mut messages = 0;
+        while !buf.is_empty() {
+            if self.payload > 0 {
+                let n = self.payload.min(buf.len() as u64);
+                self.payload -= n;
+                buf = &buf[n as usize..];
+                continue;
+            }
+
+            self.header[self.header_len] = buf[0];
+            self.header_len += 1;
+            buf = &buf[1..];
+            if let Some(header) = FrameHeader::parse(&self.header[..self.header_len]) {
+                self.header_len = 0;
+                self.payload = header.payload_len;
+                if header.fin && header.is_data() {
+                    messages += 1;
+                }
+            }
+        }
+        messages
+    }
+
+    /// Returns true if the stream is positioned between frames.
+    pub(crate) fn at_boundary(&self) -> bool {
+        self.header_len == 0 && self.payload == 0
+    }
+}
+
+// === impl FrameHeader ===
+
+impl FrameHeader {
+    /// Parses a complete frame header, returning `None` if more bytes are
+    /// needed.
+    fn parse(buf: &[u8]) -> Option<Self> {
+        let [b0, b1, rest @ ..] = buf else {
+            return None;
+        };
+        let masked = b1 & 0x80 != 0;
+        let (ext_len, payload_len) = match b1 & 0x7f {
+            126 => (2, None),
+            127 => (8, None),
+            len => (0, Some(u64::from(len))),
+        };
+        let mask_len = if masked { 4 } else { 0 };
+        if rest.len() < ext_len + mask_len {
+            return None;
+        }
+        let payload_len = payload_len.unwrap_or_else(|| {
+            rest[..ext_len]
+                .iter()
+                .fold(0u64, |len, b| (len << 8) | u64::from(*b))
+        });
+        Some(Self {
+            fin: b0 & 0x80 != 0,
+            opcode: b0 & 0x0f,
+            payload_len,
+        })
+    }
+
+    fn is_data(&self) -> bool {
+        matches!(
+            self.opcode,
+            OPCODE_CONTINUATION | OPCODE_TEXT | OPCODE_BINARY
+        )
+    }
+}
+
+/// Encodes a close frame with the given status code.
+///
+/// Frames sent to a server must be masked, and frames sent to a client must
+/// not be.
+pub(crate) fn close_frame(code: u16, masked: bool) -> Vec<u8> {
+    let payload = code.to_be_bytes();
+    let mut frame = vec![0x80 | OPCODE_CLOSE, payload.len() as u8];
+    if masked {
+        let key: [u8; 4] = rand::random();
+        frame[1] |= 0x80;
+        frame.extend_from_slice(&key);
+        frame.extend(payload.iter().zip(key.iter().cycle()).map(|(b, k)| b ^ k));
+    } else {
+        frame.extend_from_slice(&payload);
+    }
+    frame
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(fin: bool, opcode: u8, payload: &[u8], masked: bool) -> Vec<u8> {
+        let mut buf = vec![if fin { 0x80 } else { 0 } | opcode];
+        let mask = if masked { 0x80 } else { 0 };
+        match payload.len() {
+            len @ 0..=125 => buf.push(mask | len as u8),
+            len @ 126..=0xffff => {
+                buf.push(mask | 126);
+                buf.extend_from_slice(&(len as u16).to_be_bytes());
+            }
+            len => {
+                buf.push(mask | 127);
+                buf.extend_from_slice(&(len as u64).to_be_bytes());
+            }
+        }
+        if masked {
+            buf.extend_from_slice(&[1, 2, 3, 4]);
+        }
+        buf.extend_from_slice(payload);
+        buf
+    }
+
+    #[test]
+    fn counts_messages() {
+        let mut stream = Vec::new();
+        stream.extend(frame(true, OPCODE_TEXT, b"hello", false));
+        stream.extend(frame(false, OPCODE_BINARY, &[0; 200], true));
+        stream.extend(frame(true, 0x9, b"ping", true));
+        stream.extend(frame(true, OPCODE_CONTINUATION, &[0; 70_000], true));
+        stream.extend(frame(true, OPCODE_CLOSE, &[0x03, 0xe8], false));
+
+        // Feed the stream in uneven chunks to split headers and payloads.
+        let mut parser = FrameParser::default();
+        let mut messages = 0;
+        for chunk in stream.chunks(3) {
+            messages += parser.feed(chunk);
+        }
+        assert_eq!(messages, 2);
+        assert!(parser.at_boundary());
+
+        // A message is counted once its final frame's header is read.
+        let mut parser = FrameParser::default();
+        assert_eq!(parser.feed(&stream[..4]), 1);
+        assert!(!parser.at_boundary());
+    }
+
+    #[test]
+    fn encodes_close_frames() {
+        let unmasked = close_frame(GOING_AWAY, false);
+        assert_eq!(unmasked, [0x88, 0x02, 0x03, 0xe9]);
+
+        let masked = close_frame(GOING_AWAY, true);
+        assert_eq!(masked.len(), 8);
+        assert_eq!(masked[..2], [0x88, 0x82]);
+        let key = &masked[2..6];
+        assert_eq!([masked[6] ^ key[0], masked[7] ^ key[1]], [0x03, 0xe9]);
+
+        let mut parser = FrameParser::default();
+        assert_eq!(parser.feed(&masked), 0);
+        assert!(parser.at_boundary());
+    }
+}
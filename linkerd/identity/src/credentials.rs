@@ -1,5 +1,5 @@
 use linkerd_error::Result;
-use std::{ops::Deref, time::SystemTime};
+use std::{collections::HashMap, ops::Deref, time::SystemTime};
 
 /// Publishes certificates to be used by TLS implementations.
 pub trait Credentials {
@@ -13,6 +13,19 @@ pub trait Credentials {
         key: Vec<u8>,
         expiry: SystemTime,
     ) -> Result<()>;
+
+    /// Set the trust roots for federated SPIFFE trust domains, keyed by trust
+    /// domain name (e.g. `example.org`).
+    ///
+    /// Peers with an identity in one of these trust domains are verified
+    /// against that domain's roots instead of the local trust anchors. Each
+    /// call replaces all previously-set federated roots.
+    ///
+    /// Implementations that do not support federation ignore these roots.
+    fn set_federated_roots(&mut self, roots: HashMap<String, Vec<DerX509>>) -> Result<()> {
+        let _ = roots;
+        Ok(())
+    }
 }
 
 /// DER-formatted X.509 data.
@@ -26,6 +26,8 @@ pub enum Id {
     Uri(url::Url),
 }
 
+const SPIFFE_SCHEME: &str = "spiffe";
+
 #[derive(Debug, thiserror::Error)]
 #[error("invalid TLS id: {0}")]
 pub struct InvalidId(#[source] Error);
@@ -61,6 +63,19 @@ impl Id {
             .map_err(|e| InvalidId(e.into()))
     }
 
+    /// Returns the trust domain of a SPIFFE ID (e.g. `example.org` for
+    /// `spiffe://example.org/ns/default/sa/web`).
+    ///
+    /// Returns `None` for DNS-like identities and non-SPIFFE URIs.
+    pub fn spiffe_trust_domain(&self) -> Option<&str> {
+        match self {
+            Self::Uri(uri) if uri.scheme() == SPIFFE_SCHEME => {
+                uri.host_str().filter(|h| !h.is_empty())
+            }
+            _ => None,
+        }
+    }
+
     pub fn to_str(&self) -> std::borrow::Cow<'_, str> {
         match self {
             Self::Dns(dns) => dns.as_str().into(),
@@ -106,6 +121,20 @@ mod tests {
         assert_eq!(id, id.to_string().parse().unwrap());
     }
 
+    #[test]
+    fn spiffe_trust_domain() {
+        let id: Id = "spiffe://example.org/ns/default/sa/web".parse().unwrap();
+        assert_eq!(id.spiffe_trust_domain(), Some("example.org"));
+
+        let id: Id = "http://example.org/ns/default/sa/web".parse().unwrap();
+        assert_eq!(id.spiffe_trust_domain(), None);
+
+        let id: Id = "web.default.serviceaccount.identity.linkerd.cluster.local"
+            .parse()
+            .unwrap();
+        assert_eq!(id.spiffe_trust_domain(), None);
+    }
+
     #[test]
     fn cannot_parse_uri_as_dns() {
         assert!(Id::parse_dns_name("uri://host:1234/path").is_err());
@@ -2,6 +2,7 @@ use crate::{Credentials, DerX509};
 use linkerd_error::Result;
 use linkerd_metrics::prom;
 use std::{
+    collections::HashMap,
     sync::atomic::AtomicU64,
     time::{SystemTime, UNIX_EPOCH},
 };
@@ -117,6 +118,10 @@ where
 
         Ok(())
     }
+
+    fn set_federated_roots(&mut self, roots: HashMap<String, Vec<DerX509>>) -> Result<()> {
+        self.inner.set_federated_roots(roots)
+    }
 }
 #[cfg(test)]
 mod tests {
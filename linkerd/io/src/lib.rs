@@ -3,6 +3,7 @@
 
 mod boxed;
 mod either;
+mod pipe;
 mod prefixed;
 mod scoped;
 mod sensor;
@@ -10,6 +11,7 @@ mod sensor;
 pub use self::{
     boxed::BoxedIo,
     either::EitherIo,
+    pipe::Pipe,
     prefixed::PrefixedIo,
     scoped::ScopedIo,
     sensor::{Sensor, SensorIo},
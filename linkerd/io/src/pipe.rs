@@ -0,0 +1,79 @@
+use crate as io;
+use std::{
+    net::SocketAddr,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// One end of an in-memory connection that carries a client's traffic, e.g.
+/// so that a protocol-aware proxy may pass a connection to a backend stack.
+///
+/// The pipe reports the address of the client whose traffic it carries.
+#[derive(Debug)]
+pub struct Pipe {
+    io: io::DuplexStream,
+    client_addr: SocketAddr,
+}
+
+/// Data is written to the pipe as it is proxied, so the pipe only needs to
+/// accommodate a few reads at a time.
+const PIPE_CAPACITY: usize = 64 * 1024;
+
+// === impl Pipe ===
+
+impl Pipe {
+    /// Returns a pipe to be passed to a backend stack and the other end of the
+    /// pipe.
+    pub fn new(client_addr: SocketAddr) -> (Self, io::DuplexStream) {
+        let (io, proxy) = io::duplex(PIPE_CAPACITY);
+        (Self { io, client_addr }, proxy)
+    }
+}
+
+impl io::PeerAddr for Pipe {
+    fn peer_addr(&self) -> io::Result<SocketAddr> {
+        Ok(self.client_addr)
+    }
+}
+
+impl io::AsyncRead for Pipe {
+    #[inline]
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut io::ReadBuf<'_>,
+    ) -> io::Poll<()> {
+        Pin::new(&mut self.io).poll_read(cx, buf)
+    }
+}
+
+impl io::AsyncWrite for Pipe {
+    #[inline]
+    fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> io::Poll<usize> {
+        Pin::new(&mut self.io).poll_write(cx, buf)
+    }
+
+    #[inline]
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> io::Poll<()> {
+        Pin::new(&mut self.io).poll_flush(cx)
+    }
+
+    #[inline]
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> io::Poll<()> {
+        Pin::new(&mut self.io).poll_shutdown(cx)
+    }
+
+    #[inline]
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.io).poll_write_vectored(cx, bufs)
+    }
+
+    #[inline]
+    fn is_write_vectored(&self) -> bool {
+        self.io.is_write_vectored()
+    }
+}
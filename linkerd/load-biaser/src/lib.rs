@@ -34,7 +34,7 @@ use std::{
     future::Future,
     marker::PhantomData,
     pin::Pin,
-    sync::Arc,
+    sync::{Arc, Weak},
     task::{Context, Poll},
     time::Duration,
 };
@@ -337,6 +337,14 @@ impl Drop for Handle {
     }
 }
 
+/// Reads a [`LoadBiaser`]'s estimate without holding the service.
+///
+/// The probe holds a weak reference to the shared state, so it does not count
+/// as a pending request and it does not keep the state alive once the service
+/// is dropped.
+#[derive(Clone, Debug)]
+pub struct Probe(Weak<SharedState>);
+
 /// Response future that records a measurement and checks for failure responses.
 ///
 /// A failure sets a computed penalty and drops the handle immediately. A
@@ -379,6 +387,11 @@ impl<S, C> LoadBiaser<S, C> {
         }
     }
 
+    /// Returns a probe that reads this service's estimate.
+    pub fn probe(&self) -> Probe {
+        Probe(Arc::downgrade(&self.shared))
+    }
+
     fn handle(&self) -> Handle {
         Handle {
             shared: self.shared.clone(),
@@ -432,6 +445,20 @@ impl<S, C> Load for LoadBiaser<S, C> {
     }
 }
 
+// === impl Probe ===
+
+impl Probe {
+    /// Returns the decayed RTT estimate and the number of pending requests, or
+    /// `None` if the service has been dropped.
+    pub fn read(&self) -> Option<(Duration, u32)> {
+        let shared = self.0.upgrade()?;
+        let rtt = shared.rtt.read().get_at(Instant::now());
+        // Discount the service's reference and the one held by this probe.
+        let pending = (Arc::strong_count(&shared) as u32).saturating_sub(2);
+        Some((Duration::from_secs_f64(rtt.max(0.0)), pending))
+    }
+}
+
 impl<S, C, Req> Service<Req> for LoadBiaser<S, C>
 where
     S: Service<Req>,
@@ -1042,6 +1069,29 @@ mod tests {
         );
     }
 
+    #[tokio::test(flavor = "current_thread", start_paused = true)]
+    async fn test_probe_reads_without_counting() {
+        let inner = MockService::new(http::StatusCode::OK);
+        let mut biaser = LoadBiaser::new(inner, test_config());
+        let probe = biaser.probe();
+
+        let (rtt, pending) = probe.read().expect("service must be alive");
+        assert_eq!(pending, 0, "the probe must not count as pending");
+        assert!((rtt.as_secs_f64() - biaser.get_rtt()).abs() < 1e-9);
+        assert_eq!(biaser.get_pending(), 0);
+
+        let fut = biaser.call(());
+        assert_eq!(probe.read().map(|(_, p)| p), Some(1));
+        drive_to_first_frame(fut.await.unwrap()).await;
+        assert_eq!(probe.read().map(|(_, p)| p), Some(0));
+
+        drop(biaser);
+        assert!(
+            probe.read().is_none(),
+            "the probe must not retain the state"
+        );
+    }
+
     #[tokio::test(flavor = "current_thread", start_paused = true)]
     async fn test_two_concurrent_requests_counted() {
         // Two concurrent in-flight requests each hold a handle. The strong count
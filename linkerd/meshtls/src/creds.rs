@@ -6,6 +6,7 @@ pub use self::{receiver::Receiver, store::Store};
 use linkerd_dns_name as dns;
 use linkerd_error::Result;
 use linkerd_identity as id;
+use linkerd_tls as tls;
 use rustls_pki_types::{pem::PemObject as _, CertificateDer};
 use std::sync::Arc;
 use thiserror::Error;
@@ -22,6 +23,23 @@ pub fn watch(
     server_name: dns::Name,
     roots_pem: impl AsRef<[u8]>,
 ) -> Result<(Store, Receiver)> {
+    let (store, _, rx) = watch_with_additional(local_id, server_name, Vec::new(), roots_pem)?;
+    Ok((store, rx))
+}
+
+/// Like [`watch`], but the server also terminates TLS for each of the
+/// `additional` local identities. The server selects a certificate by the SNI
+/// value in each client's ClientHello, falling back to the primary identity.
+///
+/// A [`Store`] is returned for each additional identity (in order) so that
+/// each may be provisioned independently. Client connections always use the
+/// primary identity.
+pub fn watch_with_additional(
+    local_id: id::Id,
+    server_name: dns::Name,
+    additional: Vec<(id::Id, dns::Name)>,
+    roots_pem: impl AsRef<[u8]>,
+) -> Result<(Store, Vec<Store>, Receiver)> {
     let mut roots = rustls::RootCertStore::empty();
 
     let certs =
@@ -49,7 +67,11 @@ pub fn watch(
     // controlling the set of trusted signature algorithms), but they provide good enough
     // defaults for now.
     // TODO: lock down the verification further.
-    let server_cert_verifier = Arc::new(verify::AnySanVerifier::new(roots.clone()));
+    let federated = verify::FederatedRoots::default();
+    let server_cert_verifier = Arc::new(verify::AnySanVerifier::new(
+        roots.clone(),
+        federated.clone(),
+    ));
 
     let (client_tx, client_rx) = {
         // Since we don't have a certificate yet, build a client configuration
@@ -70,20 +92,57 @@ pub fn watch(
         // that handshaking always fails. Once we get a certificate, the `Store`
         // will publish a new configuration with a server certificate resolver.
         let empty_resolver = Arc::new(rustls::server::ResolvesServerCertUsingSni::new());
-        watch::channel(store::server_config(roots.clone(), empty_resolver))
+        watch::channel(store::server_config(
+            roots.clone(),
+            federated.clone(),
+            empty_resolver,
+        ))
     };
 
-    let rx = Receiver::new(local_id.clone(), server_name.clone(), client_rx, server_rx);
-    let store = Store::new(
+    let local = tls::LocalIdentities::with_additional(
+        tls::ServerName(server_name.clone()),
+        local_id.clone(),
+        additional
+            .iter()
+            .map(|(id, name)| (tls::ServerName(name.clone()), id.clone())),
+    );
+    let rx = Receiver::new(
+        local_id.clone(),
+        server_name.clone(),
+        local,
+        client_rx,
+        server_rx,
+    );
+
+    let server_keys = store::ServerKeys::new(
         roots,
-        server_cert_verifier,
+        federated.clone(),
+        server_tx,
+        std::iter::once(server_name.clone()).chain(additional.iter().map(|(_, n)| n.clone())),
+    );
+    let store = Store::new(
+        server_cert_verifier.clone(),
         local_id,
         server_name,
-        client_tx,
-        server_tx,
+        Some(client_tx),
+        server_keys.clone(),
+        federated.clone(),
     );
+    let additional = additional
+        .into_iter()
+        .map(|(id, name)| {
+            Store::new(
+                server_cert_verifier.clone(),
+                id,
+                name,
+                None,
+                server_keys.clone(),
+                federated.clone(),
+            )
+        })
+        .collect();
 
-    Ok((store, rx))
+    Ok((store, additional, rx))
 }
 
 #[cfg(feature = "test-util")]
@@ -1,6 +1,7 @@
 use crate::{NewClient, Server};
 use linkerd_dns_name as dns;
 use linkerd_identity::Id;
+use linkerd_tls as tls;
 use std::sync::Arc;
 use tokio::sync::watch;
 use tokio_rustls::rustls;
@@ -10,6 +11,7 @@ use tokio_rustls::rustls;
 pub struct Receiver {
     id: Id,
     name: dns::Name,
+    local: tls::LocalIdentities,
     client_rx: watch::Receiver<Arc<rustls::ClientConfig>>,
     server_rx: watch::Receiver<Arc<rustls::ServerConfig>>,
 }
@@ -20,12 +22,14 @@ impl Receiver {
     pub(super) fn new(
         id: Id,
         name: dns::Name,
+        local: tls::LocalIdentities,
         client_rx: watch::Receiver<Arc<rustls::ClientConfig>>,
         server_rx: watch::Receiver<Arc<rustls::ServerConfig>>,
     ) -> Self {
         Self {
             id,
             name,
+            local,
             client_rx,
             server_rx,
         }
@@ -41,6 +45,12 @@ impl Receiver {
         &self.name
     }
 
+    /// Returns all of the local identities for which the server terminates
+    /// TLS, including the primary identity.
+    pub fn local_identities(&self) -> &tls::LocalIdentities {
+        &self.local
+    }
+
     /// Returns a `NewClient` that can be used to establish TLS on client connections.
     pub fn new_client(&self) -> NewClient {
         NewClient::new(self.client_rx.clone())
@@ -48,7 +58,7 @@ impl Receiver {
 
     /// Returns a `Server` that can be used to terminate TLS on server connections.
     pub fn server(&self) -> Server {
-        Server::new(self.name.clone(), self.server_rx.clone())
+        Server::new(self.local.clone(), self.server_rx.clone())
     }
 }
 
@@ -56,6 +66,7 @@ impl std::fmt::Debug for Receiver {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Receiver")
             .field("name", &self.name)
+            .field("local", &self.local)
             .finish()
     }
 }
@@ -98,6 +109,10 @@ mod tests {
         let receiver = Receiver {
             name: "example".parse().unwrap(),
             id: "example".parse().unwrap(),
+            local: tls::LocalIdentities::new(
+                "example".parse().unwrap(),
+                "example".parse().unwrap(),
+            ),
             server_rx,
             client_rx,
         };
@@ -122,6 +137,10 @@ mod tests {
         let receiver = Receiver {
             id: "example".parse().unwrap(),
             name: "example".parse().unwrap(),
+            local: tls::LocalIdentities::new(
+                "example".parse().unwrap(),
+                "example".parse().unwrap(),
+            ),
             server_rx,
             client_rx,
         };
@@ -1,8 +1,10 @@
+use super::verify::{FederatedClientVerifier, FederatedRoots};
 use linkerd_dns_name as dns;
 use linkerd_error::Result;
 use linkerd_identity as id;
 use linkerd_meshtls_verifier as verifier;
-use std::{convert::TryFrom, sync::Arc};
+use parking_lot::Mutex;
+use std::{collections::HashMap, convert::TryFrom, sync::Arc};
 use tokio::sync::watch;
 use tokio_rustls::rustls::{
     self,
@@ -10,20 +12,47 @@ use tokio_rustls::rustls::{
     server::WebPkiClientVerifier,
     sign::CertifiedKey,
 };
-use tracing::debug;
+use tracing::{debug, warn};
 
 pub struct Store {
-    roots: rustls::RootCertStore,
     server_cert_verifier: Arc<dyn rustls::client::danger::ServerCertVerifier>,
     server_id: id::Id,
     server_name: dns::Name,
-    client_tx: watch::Sender<Arc<rustls::ClientConfig>>,
-    server_tx: watch::Sender<Arc<rustls::ServerConfig>>,
+    /// Only the primary identity's store publishes client configurations.
+    client_tx: Option<watch::Sender<Arc<rustls::ClientConfig>>>,
+    server_keys: ServerKeys,
+    federated: FederatedRoots,
+}
+
+/// Holds the certified keys for all of the local identities that share a
+/// server configuration.
+#[derive(Clone)]
+pub(super) struct ServerKeys(Arc<ServerKeysInner>);
+
+struct ServerKeysInner {
+    roots: rustls::RootCertStore,
+    federated: FederatedRoots,
+    tx: watch::Sender<Arc<rustls::ServerConfig>>,
+    /// Keys for each local server name. The first entry is the primary
+    /// identity.
+    keys: Mutex<Vec<(dns::Name, Option<Arc<CertifiedKey>>)>>,
 }
 
 #[derive(Clone, Debug)]
 struct CertResolver(Arc<rustls::sign::CertifiedKey>);
 
+/// Resolves a server certificate by the client's SNI, falling back to the
+/// primary identity's certificate when the SNI does not name a local identity.
+///
+/// A client that selects a local identity is only ever served that identity's
+/// certificate, so the identity reported for a connection is always the one
+/// that was presented.
+#[derive(Debug)]
+struct SniResolver {
+    primary: Option<Arc<CertifiedKey>>,
+    by_name: Vec<(dns::Name, Option<Arc<CertifiedKey>>)>,
+}
+
 pub(super) fn client_config_builder(
     cert_verifier: Arc<dyn rustls::client::danger::ServerCertVerifier>,
 ) -> rustls::ConfigBuilder<rustls::ClientConfig, rustls::client::WantsClientCert> {
@@ -46,6 +75,7 @@ pub(super) fn client_config_builder(
 
 pub(super) fn server_config(
     roots: rustls::RootCertStore,
+    federated: FederatedRoots,
     resolver: Arc<dyn rustls::server::ResolvesServerCert>,
 ) -> Arc<rustls::ServerConfig> {
     // Ask TLS clients for a certificate and accept any certificate issued by our trusted CA(s).
@@ -61,6 +91,10 @@ pub(super) fn server_config(
             .allow_unauthenticated()
             .build()
             .expect("server verifier must be valid");
+    let client_cert_verifier = Arc::new(FederatedClientVerifier::new(
+        client_cert_verifier,
+        federated,
+    ));
 
     rustls::ServerConfig::builder_with_provider(provider)
         .with_protocol_versions(linkerd_rustls::TLS_VERSIONS)
@@ -73,25 +107,34 @@ pub(super) fn server_config(
 // === impl Store ===
 
 impl Store {
-    #[allow(clippy::too_many_arguments)]
     pub(super) fn new(
-        roots: rustls::RootCertStore,
         server_cert_verifier: Arc<dyn rustls::client::danger::ServerCertVerifier>,
         server_id: id::Id,
         server_name: dns::Name,
-        client_tx: watch::Sender<Arc<rustls::ClientConfig>>,
-        server_tx: watch::Sender<Arc<rustls::ServerConfig>>,
+        client_tx: Option<watch::Sender<Arc<rustls::ClientConfig>>>,
+        server_keys: ServerKeys,
+        federated: FederatedRoots,
     ) -> Self {
         Self {
-            roots,
             server_cert_verifier,
             server_id,
             server_name,
             client_tx,
-            server_tx,
+            server_keys,
+            federated,
         }
     }
 
+    /// Returns the local identity provisioned by this store.
+    pub fn local_id(&self) -> &id::Id {
+        &self.server_id
+    }
+
+    /// Returns the server name that selects this store's identity.
+    pub fn server_name(&self) -> &dns::Name {
+        &self.server_name
+    }
+
     /// Builds a new TLS client configuration.
     fn client_config(&self, resolver: Arc<CertResolver>) -> Arc<rustls::ClientConfig> {
         let mut cfg = client_config_builder(self.server_cert_verifier.clone())
@@ -148,21 +191,93 @@ impl id::Credentials for Store {
         let key_der = PrivatePkcs8KeyDer::from(key);
         let provider = rustls::crypto::CryptoProvider::get_default()
             .expect("Failed to get default crypto provider");
-        let key = CertifiedKey::from_der(chain, key_der.into(), provider)?;
-        let resolver = Arc::new(CertResolver(Arc::new(key)));
+        let key = Arc::new(CertifiedKey::from_der(chain, key_der.into(), provider)?);
+
+        // Build and publish a new client config if this is the primary
+        // identity.
+        if let Some(client_tx) = self.client_tx.as_ref() {
+            let client = self.client_config(Arc::new(CertResolver(key.clone())));
+            let _ = client_tx.send(client);
+        }
 
-        // Build new client and server TLS configs.
-        let client = self.client_config(resolver.clone());
-        let server = server_config(self.roots.clone(), resolver);
+        // Publish a new server config that includes this identity's key.
+        self.server_keys.update(&self.server_name, key);
 
-        // Publish the new configs.
-        let _ = self.client_tx.send(client);
-        let _ = self.server_tx.send(server);
+        Ok(())
+    }
 
+    /// Updates the roots used to verify peers in federated trust domains.
+    ///
+    /// Verifiers consult these roots on each handshake, so existing client and
+    /// server configurations observe the update without being republished.
+    fn set_federated_roots(&mut self, domains: HashMap<String, Vec<id::DerX509>>) -> Result<()> {
+        let domains = domains
+            .into_iter()
+            .filter_map(|(td, certs)| {
+                let mut roots = rustls::RootCertStore::empty();
+                let (added, skipped) = roots.add_parsable_certificates(
+                    certs
+                        .into_iter()
+                        .map(|id::DerX509(der)| rustls::pki_types::CertificateDer::from(der)),
+                );
+                if skipped != 0 {
+                    warn!(trust_domain = %td, "Skipped {} invalid federated trust anchors", skipped);
+                }
+                if added == 0 {
+                    warn!(trust_domain = %td, "No valid federated trust anchors");
+                    return None;
+                }
+                // Trust domain names are case-insensitive.
+                Some((td.to_ascii_lowercase(), roots))
+            })
+            .collect::<HashMap<_, _>>();
+        debug!(
+            trust_domains = domains.len(),
+            "Updated federated trust roots"
+        );
+        self.federated.set(domains);
         Ok(())
     }
 }
 
+// === impl ServerKeys ===
+
+impl ServerKeys {
+    pub(super) fn new(
+        roots: rustls::RootCertStore,
+        federated: FederatedRoots,
+        tx: watch::Sender<Arc<rustls::ServerConfig>>,
+        names: impl IntoIterator<Item = dns::Name>,
+    ) -> Self {
+        let keys = names.into_iter().map(|n| (n, None)).collect();
+        Self(Arc::new(ServerKeysInner {
+            roots,
+            federated,
+            tx,
+            keys: Mutex::new(keys),
+        }))
+    }
+
+    /// Sets the key for the given server name and publishes a new server
+    /// configuration.
+    fn update(&self, name: &dns::Name, key: Arc<CertifiedKey>) {
+        let mut keys = self.0.keys.lock();
+        if let Some((_, k)) = keys.iter_mut().find(|(n, _)| n == name) {
+            *k = Some(key);
+        }
+
+        let resolver = SniResolver {
+            primary: keys.first().and_then(|(_, k)| k.clone()),
+            by_name: keys.clone(),
+        };
+        let _ = self.0.tx.send(server_config(
+            self.0.roots.clone(),
+            self.0.federated.clone(),
+            Arc::new(resolver),
+        ));
+    }
+}
+
 // === impl CertResolver ===
 
 impl CertResolver {
@@ -171,8 +286,7 @@ impl CertResolver {
         &self,
         sigschemes: &[rustls::SignatureScheme],
     ) -> Option<Arc<rustls::sign::CertifiedKey>> {
-        if !sigschemes.contains(&linkerd_rustls::SIGNATURE_ALG_RUSTLS_SCHEME) {
-            debug!("Signature scheme not supported -> no certificate");
+        if !supports_sigschemes(sigschemes) {
             return None;
         }
 
@@ -194,11 +308,38 @@ impl rustls::client::ResolvesClientCert for CertResolver {
     }
 }
 
-impl rustls::server::ResolvesServerCert for CertResolver {
+// === impl SniResolver ===
+
+impl rustls::server::ResolvesServerCert for SniResolver {
     fn resolve(
         &self,
         hello: rustls::server::ClientHello<'_>,
     ) -> Option<Arc<rustls::sign::CertifiedKey>> {
-        self.resolve_(hello.signature_schemes())
+        if !supports_sigschemes(hello.signature_schemes()) {
+            return None;
+        }
+
+        let sni = hello
+            .server_name()
+            .and_then(|n| n.parse::<dns::Name>().ok());
+        if let Some(sni) = sni {
+            if let Some((_, key)) = self.by_name.iter().find(|(n, _)| *n == sni) {
+                if key.is_none() {
+                    debug!(%sni, "Certificate not yet provisioned -> no certificate");
+                }
+                return key.clone();
+            }
+            debug!(%sni, "No identity for SNI; using the primary identity");
+        }
+
+        self.primary.clone()
+    }
+}
+
+fn supports_sigschemes(sigschemes: &[rustls::SignatureScheme]) -> bool {
+    if !sigschemes.contains(&linkerd_rustls::SIGNATURE_ALG_RUSTLS_SCHEME) {
+        debug!("Signature scheme not supported -> no certificate");
+        return false;
     }
+    true
 }
@@ -1,5 +1,7 @@
+use linkerd_meshtls_verifier as verifier;
 use linkerd_rustls::SUPPORTED_SIG_ALGS;
-use std::{convert::TryFrom, sync::Arc};
+use parking_lot::RwLock;
+use std::{collections::HashMap, convert::TryFrom, sync::Arc};
 use tokio_rustls::rustls::{
     self,
     client::{
@@ -7,20 +9,96 @@ use tokio_rustls::rustls::{
         danger::{ServerCertVerified, ServerCertVerifier},
     },
     pki_types::{CertificateDer, ServerName, UnixTime},
-    server::ParsedCertificate,
-    RootCertStore,
+    server::{
+        danger::{ClientCertVerified, ClientCertVerifier},
+        ParsedCertificate, WebPkiClientVerifier,
+    },
+    DistinguishedName, RootCertStore,
 };
 use tracing::trace;
 
+/// Trust roots for federated SPIFFE trust domains, shared by all verifiers
+/// built from the same credentials.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct FederatedRoots(Arc<RwLock<HashMap<String, TrustDomainRoots>>>);
+
+#[derive(Clone, Debug)]
+struct TrustDomainRoots {
+    roots: Arc<RootCertStore>,
+    client_verifier: Arc<dyn ClientCertVerifier>,
+}
+
 #[derive(Debug)]
 pub(crate) struct AnySanVerifier {
     roots: Arc<RootCertStore>,
+    federated: FederatedRoots,
+}
+
+/// Verifies client certificates against the roots of the client's SPIFFE
+/// trust domain, if it is federated, and otherwise against the local roots.
+#[derive(Debug)]
+pub(crate) struct FederatedClientVerifier {
+    local: Arc<dyn ClientCertVerifier>,
+    federated: FederatedRoots,
 }
 
+// === impl FederatedRoots ===
+
+impl FederatedRoots {
+    /// Replaces the roots for all federated trust domains.
+    pub(crate) fn set(&self, domains: HashMap<String, RootCertStore>) {
+        let provider = linkerd_rustls::get_default_provider();
+        let domains = domains
+            .into_iter()
+            .filter_map(|(td, roots)| {
+                let roots = Arc::new(roots);
+                let client_verifier = match WebPkiClientVerifier::builder_with_provider(
+                    roots.clone(),
+                    provider.clone(),
+                )
+                .build()
+                {
+                    Ok(v) => v,
+                    Err(error) => {
+                        tracing::warn!(%error, trust_domain = %td, "Invalid federated trust roots");
+                        return None;
+                    }
+                };
+                Some((
+                    td,
+                    TrustDomainRoots {
+                        roots,
+                        client_verifier,
+                    },
+                ))
+            })
+            .collect();
+        *self.0.write() = domains;
+    }
+
+    /// Returns the federated roots for the trust domain that issued the given
+    /// end-entity certificate, if any.
+    fn get(&self, end_entity: &CertificateDer<'_>) -> Option<TrustDomainRoots> {
+        let domains = self.0.read();
+        if domains.is_empty() {
+            return None;
+        }
+        let td = verifier::spiffe_trust_domain(end_entity)?;
+        let roots = domains.get(&td).cloned();
+        if roots.is_some() {
+            trace!(trust_domain = %td, "Using federated trust roots");
+        }
+        roots
+    }
+}
+
+// === impl AnySanVerifier ===
+
 impl AnySanVerifier {
-    pub(crate) fn new(roots: impl Into<Arc<RootCertStore>>) -> Self {
+    pub(crate) fn new(roots: impl Into<Arc<RootCertStore>>, federated: FederatedRoots) -> Self {
         Self {
             roots: roots.into(),
+            federated,
         }
     }
 }
@@ -36,7 +114,8 @@ impl AnySanVerifier {
 // want to support alternative SAN types (e.g. URI).
 impl ServerCertVerifier for AnySanVerifier {
     /// Will verify the certificate is valid in the following ways:
-    /// - Signed by a  trusted `RootCertStore` CA
+    /// - Signed by a  trusted `RootCertStore` CA (or, for SPIFFE IDs in a
+    ///   federated trust domain, by that trust domain's CA)
     /// - Not Expired
     fn verify_server_cert(
         &self,
@@ -48,9 +127,11 @@ impl ServerCertVerifier for AnySanVerifier {
     ) -> Result<ServerCertVerified, rustls::Error> {
         let cert = ParsedCertificate::try_from(end_entity)?;
 
+        let federated = self.federated.get(end_entity);
+        let roots = federated.as_ref().map_or(&self.roots, |td| &td.roots);
         client::verify_server_cert_signed_by_trust_anchor(
             &cert,
-            &self.roots,
+            roots,
             intermediates,
             now,
             SUPPORTED_SIG_ALGS.all,
@@ -85,3 +166,63 @@ impl ServerCertVerifier for AnySanVerifier {
         SUPPORTED_SIG_ALGS.supported_schemes()
     }
 }
+
+// === impl FederatedClientVerifier ===
+
+impl FederatedClientVerifier {
+    pub(crate) fn new(local: Arc<dyn ClientCertVerifier>, federated: FederatedRoots) -> Self {
+        Self { local, federated }
+    }
+}
+
+impl ClientCertVerifier for FederatedClientVerifier {
+    fn offer_client_auth(&self) -> bool {
+        self.local.offer_client_auth()
+    }
+
+    fn client_auth_mandatory(&self) -> bool {
+        self.local.client_auth_mandatory()
+    }
+
+    fn root_hint_subjects(&self) -> &[DistinguishedName] {
+        self.local.root_hint_subjects()
+    }
+
+    fn verify_client_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        now: UnixTime,
+    ) -> Result<ClientCertVerified, rustls::Error> {
+        match self.federated.get(end_entity) {
+            Some(td) => td
+                .client_verifier
+                .verify_client_cert(end_entity, intermediates, now),
+            None => self
+                .local
+                .verify_client_cert(end_entity, intermediates, now),
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &rustls::DigitallySignedStruct,
+    ) -> Result<client::danger::HandshakeSignatureValid, rustls::Error> {
+        self.local.verify_tls12_signature(message, cert, dss)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &rustls::DigitallySignedStruct,
+    ) -> Result<client::danger::HandshakeSignatureValid, rustls::Error> {
+        self.local.verify_tls13_signature(message, cert, dss)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<rustls::SignatureScheme> {
+        self.local.supported_verify_schemes()
+    }
+}
@@ -20,6 +20,6 @@ mod tests;
 
 pub use self::{
     client::{ClientIo, Connect, ConnectFuture, NewClient},
-    creds::watch,
+    creds::{watch, watch_with_additional},
     server::{Server, ServerIo, TerminateFuture},
 };
@@ -1,9 +1,11 @@
 use futures::prelude::*;
-use linkerd_dns_name as dns;
 use linkerd_io as io;
 use linkerd_meshtls_verifier as verifier;
 use linkerd_stack::{Param, Service};
-use linkerd_tls::{ClientId, NegotiatedProtocol, NegotiatedProtocolRef, ServerName, ServerTls};
+use linkerd_tls::{
+    ClientId, LocalIdentities, NegotiatedProtocol, NegotiatedProtocolRef, ServerId, ServerName,
+    ServerTls,
+};
 use std::{pin::Pin, sync::Arc, task::Context};
 use thiserror::Error;
 use tokio::sync::watch;
@@ -11,16 +13,20 @@ use tokio_rustls::rustls::{pki_types::CertificateDer, ServerConfig};
 use tracing::debug;
 
 /// A Service that terminates TLS connections using a dynamically updated server configuration.
+///
+/// The server may terminate TLS for several local identities, in which case
+/// the certificate is selected by the client's SNI.
 #[derive(Clone)]
 pub struct Server {
-    name: dns::Name,
+    local: LocalIdentities,
     rx: watch::Receiver<Arc<ServerConfig>>,
 }
 
-pub type TerminateFuture<I> = futures::future::MapOk<
-    tokio_rustls::Accept<I>,
-    fn(tokio_rustls::server::TlsStream<I>) -> (ServerTls, ServerIo<I>),
->;
+/// Completes a TLS handshake and describes the established session.
+pub struct TerminateFuture<I> {
+    accept: tokio_rustls::Accept<I>,
+    local: LocalIdentities,
+}
 
 #[derive(Debug)]
 pub struct ServerIo<I>(tokio_rustls::server::TlsStream<I>);
@@ -30,8 +36,8 @@ pub struct ServerIo<I>(tokio_rustls::server::TlsStream<I>);
 pub struct LostStore(());
 
 impl Server {
-    pub(crate) fn new(name: dns::Name, rx: watch::Receiver<Arc<ServerConfig>>) -> Self {
-        Self { name, rx }
+    pub(crate) fn new(local: LocalIdentities, rx: watch::Receiver<Arc<ServerConfig>>) -> Self {
+        Self { local, rx }
     }
 
     #[cfg(test)]
@@ -79,13 +85,19 @@ impl Server {
             }
         });
 
-        Ok(Self::new(self.name, rx))
+        Ok(Self::new(self.local, rx))
     }
 }
 
 impl Param<ServerName> for Server {
     fn param(&self) -> ServerName {
-        ServerName(self.name.clone())
+        self.local.primary().clone()
+    }
+}
+
+impl Param<LocalIdentities> for Server {
+    fn param(&self) -> LocalIdentities {
+        self.local.clone()
     }
 }
 
@@ -104,25 +116,43 @@ where
 
     #[inline]
     fn call(&mut self, io: I) -> Self::Future {
-        tokio_rustls::TlsAcceptor::from((*self.rx.borrow()).clone())
-            .accept(io)
-            .map_ok(|io| {
-                // Determine the peer's identity, if it exist.
-                let client_id = client_identity(&io);
-
-                let negotiated_protocol = io
-                    .get_ref()
-                    .1
-                    .alpn_protocol()
-                    .map(|b| NegotiatedProtocol(b.into()));
-
-                debug!(client.id = ?client_id, alpn = ?negotiated_protocol, "Accepted TLS connection");
-                let tls = ServerTls::Established {
-                    client_id,
-                    negotiated_protocol,
-                };
-                (tls, ServerIo(io))
-            })
+        TerminateFuture {
+            accept: tokio_rustls::TlsAcceptor::from((*self.rx.borrow()).clone()).accept(io),
+            local: self.local.clone(),
+        }
+    }
+}
+
+// === impl TerminateFuture ===
+
+impl<I> Future for TerminateFuture<I>
+where
+    I: io::AsyncRead + io::AsyncWrite + Unpin,
+{
+    type Output = io::Result<(ServerTls, ServerIo<I>)>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> io::Poll<(ServerTls, ServerIo<I>)> {
+        let io = futures::ready!(Pin::new(&mut self.accept).poll(cx))?;
+
+        // Determine the peer's identity, if it exist.
+        let client_id = client_identity(&io);
+
+        // Determine which of the local identities was presented.
+        let server_id = server_identity(&io, &self.local);
+
+        let negotiated_protocol = io
+            .get_ref()
+            .1
+            .alpn_protocol()
+            .map(|b| NegotiatedProtocol(b.into()));
+
+        debug!(client.id = ?client_id, server.id = ?server_id, alpn = ?negotiated_protocol, "Accepted TLS connection");
+        let tls = ServerTls::Established {
+            client_id,
+            negotiated_protocol,
+            server_id,
+        };
+        io::Poll::Ready(Ok((tls, ServerIo(io))))
     }
 }
 
@@ -134,6 +164,26 @@ fn client_identity<I>(tls: &tokio_rustls::server::TlsStream<I>) -> Option<Client
     verifier::client_identity(c).map(ClientId)
 }
 
+/// Returns the local identity selected by the client's SNI, or the primary
+/// identity if the client did not send a known SNI value.
+///
+/// The certificate resolver never serves another identity's certificate to a
+/// client that selected a local identity, so this is the identity whose
+/// certificate was presented.
+fn server_identity<I>(
+    tls: &tokio_rustls::server::TlsStream<I>,
+    local: &LocalIdentities,
+) -> Option<Arc<ServerId>> {
+    let (_io, session) = tls.get_ref();
+    let primary = local.primary();
+    let id = session
+        .server_name()
+        .and_then(|sni| sni.parse::<ServerName>().ok())
+        .and_then(|sni| local.get(&sni))
+        .or_else(|| local.get(primary))?;
+    Some(id.clone())
+}
+
 // === impl ServerIo ===
 
 impl<I: io::AsyncRead + io::AsyncWrite + Unpin> io::AsyncRead for ServerIo<I> {
@@ -18,6 +18,11 @@ async fn proxy_to_proxy_tls_works() {
     util::proxy_to_proxy_tls_works().await;
 }
 
+#[tokio::test(flavor = "current_thread")]
+async fn proxy_to_proxy_tls_works_for_additional_identity() {
+    util::proxy_to_proxy_tls_works_for_additional_identity().await;
+}
+
 #[tokio::test(flavor = "current_thread")]
 async fn proxy_to_proxy_tls_pass_through_when_identity_does_not_match() {
     util::proxy_to_proxy_tls_pass_through_when_identity_does_not_match().await;
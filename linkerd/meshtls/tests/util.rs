@@ -7,7 +7,7 @@ use linkerd_dns_name::Name;
 use linkerd_error::Infallible;
 use linkerd_identity::{Credentials, DerX509, Id};
 use linkerd_io::{self as io, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
-use linkerd_meshtls::{self as meshtls, watch};
+use linkerd_meshtls::{self as meshtls, watch, watch_with_additional};
 use linkerd_proxy_transport::{
     addrs::*,
     listen::{Addrs, Bind, BindTcp},
@@ -22,7 +22,7 @@ use rcgen::{BasicConstraints, CertificateParams, IsCa, Issuer, KeyPair, SanType}
 use std::str::FromStr;
 use std::{
     net::SocketAddr,
-    sync::mpsc,
+    sync::{mpsc, Arc},
     time::{Duration, SystemTime},
 };
 use tokio::net::TcpStream;
@@ -118,6 +118,43 @@ pub async fn proxy_to_proxy_tls_works() {
         Some(Conditional::Some(tls::ServerTls::Established {
             client_id: Some(tls::ClientId(test_util::BAR_NS1.name.parse().unwrap())),
             negotiated_protocol: None,
+            server_id: Some(Arc::new(tls::ServerId(
+                test_util::FOO_NS1.id.parse().unwrap()
+            ))),
+        }))
+    );
+    assert_eq!(&server_result.result.expect("ping")[..], PING);
+}
+
+pub async fn proxy_to_proxy_tls_works_for_additional_identity() {
+    let (_foo, _bar, server_tls) = load_with_additional(&test_util::FOO_NS1, &test_util::BAR_NS1);
+    let (_default, client_tls, _) = load(&test_util::DEFAULT_DEFAULT);
+    let server_id = tls::ServerId(test_util::BAR_NS1.id.parse().unwrap());
+    let server_name = tls::ServerName(test_util::BAR_NS1.name.parse().unwrap());
+    let (client_result, server_result) = run_test(
+        client_tls.clone(),
+        Conditional::Some(tls::ClientTls::new(server_id.clone(), server_name.clone())),
+        |conn| write_then_read(conn, PING),
+        server_tls,
+        |(_, conn)| read_then_write(conn, PING.len(), PONG),
+    )
+    .await;
+    assert_eq!(
+        client_result.tls,
+        Some(Conditional::Some(tls::ClientTls::new(
+            server_id.clone(),
+            server_name,
+        )))
+    );
+    assert_eq!(&client_result.result.expect("pong")[..], PONG);
+    assert_eq!(
+        server_result.tls,
+        Some(Conditional::Some(tls::ServerTls::Established {
+            client_id: Some(tls::ClientId(
+                test_util::DEFAULT_DEFAULT.name.parse().unwrap()
+            )),
+            negotiated_protocol: None,
+            server_id: Some(Arc::new(server_id)),
         }))
     );
     assert_eq!(&server_result.result.expect("ping")[..], PING);
@@ -180,6 +217,43 @@ fn load(ent: &test_util::Entity) -> (meshtls::creds::Store, meshtls::NewClient,
     (store, rx.new_client(), rx.server())
 }
 
+fn load_with_additional(
+    primary: &test_util::Entity,
+    additional: &test_util::Entity,
+) -> (
+    meshtls::creds::Store,
+    meshtls::creds::Store,
+    meshtls::Server,
+) {
+    let roots_pem = std::str::from_utf8(primary.trust_anchors).expect("valid PEM");
+    let (mut store, mut additional_stores, rx) = watch_with_additional(
+        primary.id.parse().unwrap(),
+        primary.name.parse().unwrap(),
+        vec![(
+            additional.id.parse().unwrap(),
+            additional.name.parse().unwrap(),
+        )],
+        roots_pem,
+    )
+    .expect("credentials must be readable");
+    let mut additional_store = additional_stores
+        .pop()
+        .expect("additional store must exist");
+
+    for (store, ent) in [(&mut store, primary), (&mut additional_store, additional)] {
+        store
+            .set_certificate(
+                DerX509(ent.crt.to_vec()),
+                vec![],
+                ent.key.to_vec(),
+                SystemTime::now() + Duration::from_secs(1000),
+            )
+            .expect("certificate must be valid");
+    }
+
+    (store, additional_store, rx.server())
+}
+
 struct Transported<I, R> {
     tls: Option<I>,
 
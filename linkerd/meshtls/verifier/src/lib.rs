@@ -40,6 +40,26 @@ pub fn client_identity(cert: &[u8]) -> Option<Id> {
     ids.first().cloned()
 }
 
+/// Returns the (lowercased) SPIFFE trust domain that issued the given
+/// end-entity certificate.
+///
+/// A trust domain is only returned when every SAN in the certificate is a
+/// SPIFFE ID in that same trust domain. Certificates with DNS SANs (or SPIFFE
+/// IDs in several trust domains) never select a federated trust domain, so
+/// they are always verified against the local roots.
+pub fn spiffe_trust_domain(cert: &[u8]) -> Option<String> {
+    let ids = extract_ids_from_cert(cert).ok()?;
+    let (first, rest) = ids.split_first()?;
+    let td = first.spiffe_trust_domain()?.to_ascii_lowercase();
+    if rest.iter().all(|id| {
+        id.spiffe_trust_domain()
+            .is_some_and(|t| t.eq_ignore_ascii_case(&td))
+    }) {
+        return Some(td);
+    }
+    None
+}
+
 pub fn verify_id(cert: &[u8], expected_id: &Id) -> io::Result<()> {
     let ids = extract_ids_from_cert(cert)
         .map_err(|error| tracing::warn!(%error, "Failed to extract tls id from client end cert"))
@@ -56,6 +76,7 @@ pub fn verify_id(cert: &[u8], expected_id: &Id) -> io::Result<()> {
 #[cfg(test)]
 mod tests {
     use crate::client_identity;
+    use crate::spiffe_trust_domain;
     use crate::verify_id;
     use linkerd_identity::Id;
     use rcgen::{CertificateParams, KeyPair, SanType};
@@ -288,4 +309,30 @@ mod tests {
         let client_id = client_identity(&cert);
         assert_eq!(client_id, None);
     }
+
+    #[test]
+    fn extracts_spiffe_trust_domain() {
+        let cert = generate_cert_with_names(vec![SanType::URI(
+            "spiffe://example.org/ns/default/sa/web".parse().unwrap(),
+        )]);
+        assert_eq!(spiffe_trust_domain(&cert), Some("example.org".to_string()));
+    }
+
+    #[test]
+    fn no_spiffe_trust_domain_with_dns_san() {
+        let cert = generate_cert_with_names(vec![
+            SanType::URI("spiffe://example.org/ns/default/sa/web".parse().unwrap()),
+            SanType::DnsName("web.default.svc.cluster.local".parse().unwrap()),
+        ]);
+        assert_eq!(spiffe_trust_domain(&cert), None);
+    }
+
+    #[test]
+    fn no_spiffe_trust_domain_with_mixed_trust_domains() {
+        let cert = generate_cert_with_names(vec![
+            SanType::URI("spiffe://example.org/ns/default/sa/web".parse().unwrap()),
+            SanType::URI("spiffe://example.com/ns/default/sa/web".parse().unwrap()),
+        ]);
+        assert_eq!(spiffe_trust_domain(&cert), None);
+    }
 }
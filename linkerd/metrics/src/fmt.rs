@@ -5,6 +5,12 @@ use std::marker::PhantomData;
 pub trait FmtMetrics {
     fn fmt_metrics(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result;
 
+    /// Indicates whether the metrics are formatted as a single, complete
+    /// OpenMetrics exposition, so that they may be served as such.
+    fn is_openmetrics(&self) -> bool {
+        false
+    }
+
     fn as_display(&self) -> DisplayMetrics<&Self>
     where
         Self: Sized,
@@ -193,6 +199,11 @@ impl<'a, A: FmtMetrics + 'a> FmtMetrics for &'a A {
     fn fmt_metrics(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         (*self).fmt_metrics(f)
     }
+
+    #[inline]
+    fn is_openmetrics(&self) -> bool {
+        (*self).is_openmetrics()
+    }
 }
 
 impl<M: FmtMetrics> FmtMetrics for Option<M> {
@@ -203,6 +214,11 @@ impl<M: FmtMetrics> FmtMetrics for Option<M> {
         }
         Ok(())
     }
+
+    #[inline]
+    fn is_openmetrics(&self) -> bool {
+        self.as_ref().is_some_and(FmtMetrics::is_openmetrics)
+    }
 }
 
 impl<A: FmtMetrics, B: FmtMetrics> FmtMetrics for AndThen<A, B> {
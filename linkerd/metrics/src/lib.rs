@@ -54,6 +54,9 @@ pub mod legacy {
 pub mod prom {
     use std::sync::Arc;
 
+    mod histogram_encoding;
+
+    pub use self::histogram_encoding::HistogramEncoding;
     pub use prometheus_client::{
         metrics::{
             counter::{ConstCounter, Counter},
@@ -73,6 +76,11 @@ pub mod prom {
         fn fmt_metrics(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
             encoding::text::encode(f, self)
         }
+
+        #[inline]
+        fn is_openmetrics(&self) -> bool {
+            true
+        }
     }
 }
 
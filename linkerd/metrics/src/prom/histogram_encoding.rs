@@ -0,0 +1,74 @@
+/// Determines the buckets used by latency histograms.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum HistogramEncoding {
+    /// Each histogram uses its own small set of fixed buckets.
+    #[default]
+    Classic,
+
+    /// Histograms use exponentially growing buckets whose bounds are those of
+    /// a Prometheus native histogram, so that observations keep the same
+    /// relative precision across the whole range of latencies.
+    ///
+    /// The OpenMetrics text format cannot represent sparse native histograms,
+    /// so these buckets are exposed as classic `le` buckets.
+    Native,
+}
+
+// === impl HistogramEncoding ===
+
+impl HistogramEncoding {
+    /// The native histogram schema whose bucket bounds are used. Each bucket
+    /// is `2^(2^-SCHEMA)` times larger than the previous one.
+    const NATIVE_SCHEMA: i32 = 1;
+
+    /// The smallest native bucket bound, roughly one millisecond.
+    const NATIVE_MIN_EXP: i32 = -10;
+
+    /// Returns the bucket bounds for a histogram whose classic buckets are
+    /// `classic`.
+    ///
+    /// Native buckets cover latencies up to the largest classic bucket.
+    pub fn buckets(self, classic: &[f64]) -> Vec<f64> {
+        match self {
+            Self::Classic => classic.to_vec(),
+            Self::Native => {
+                let max = classic.iter().copied().fold(0.0, f64::max);
+                let per_power = 1 << Self::NATIVE_SCHEMA;
+                let mut buckets = Vec::new();
+                let mut i = Self::NATIVE_MIN_EXP * per_power;
+                loop {
+                    let bound = 2f64.powf(f64::from(i) / f64::from(per_power));
+                    buckets.push(bound);
+                    if bound >= max {
+                        return buckets;
+                    }
+                    i += 1;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classic_buckets() {
+        let classic = [0.05, 0.5, 1.0, 10.0];
+        assert_eq!(HistogramEncoding::Classic.buckets(&classic), classic);
+    }
+
+    #[test]
+    fn native_buckets() {
+        let buckets = HistogramEncoding::Native.buckets(&[0.05, 0.5, 1.0, 10.0]);
+        assert_eq!(buckets.first(), Some(&2f64.powi(-10)));
+        assert_eq!(buckets.len(), 28);
+        assert!(buckets[26] < 10.0 && buckets[27] >= 10.0, "{buckets:?}");
+        assert!(buckets.contains(&1.0), "{buckets:?}");
+        for pair in buckets.windows(2) {
+            let factor = pair[1] / pair[0];
+            assert!((factor - 2f64.sqrt()).abs() < 1e-9, "{pair:?}");
+        }
+    }
+}
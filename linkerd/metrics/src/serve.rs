@@ -1,17 +1,26 @@
 use bytes::Bytes;
 use deflate::{write::GzEncoder, CompressionOptions};
 use linkerd_http_box::BoxBody;
-use std::io::Write;
+use std::io::{self, Write};
 use tracing::trace;
 
 use super::legacy::FmtMetrics;
 
+const OPENMETRICS: &str = "application/openmetrics-text";
+const OPENMETRICS_CONTENT_TYPE: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
 /// Serve Prometheues metrics.
 #[derive(Debug, Clone)]
 pub struct Serve<M> {
     metrics: M,
 }
 
+/// Removes exemplars from a text exposition as it is written.
+struct StripExemplars<W> {
+    inner: W,
+    line: Vec<u8>,
+}
+
 // === impl Serve ===
 
 impl<M> Serve<M> {
@@ -31,30 +40,239 @@ impl<M> Serve<M> {
                     .unwrap_or(false)
             })
     }
+
+    /// Indicates whether the client prefers OpenMetrics to the Prometheus text
+    /// format, according to the quality values of its `Accept` header.
+    fn prefers_openmetrics<B>(req: &http::Request<B>) -> bool {
+        let mut openmetrics = 0.0f32;
+        let mut text = 0.0f32;
+        let ranges = req
+            .headers()
+            .get_all(http::header::ACCEPT)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','));
+        for range in ranges {
+            let mut params = range.split(';').map(str::trim);
+            let media_type = params.next().unwrap_or_default();
+            let q = params
+                .find_map(|p| p.strip_prefix("q="))
+                .map_or(Some(1.0), |q| q.parse::<f32>().ok())
+                .unwrap_or(0.0);
+            if media_type.eq_ignore_ascii_case(OPENMETRICS) {
+                openmetrics = openmetrics.max(q);
+            } else if ["text/plain", "text/*", "*/*"]
+                .iter()
+                .any(|t| media_type.eq_ignore_ascii_case(t))
+            {
+                text = text.max(q);
+            }
+        }
+        openmetrics > 0.0 && openmetrics > text
+    }
 }
 
 impl<M: FmtMetrics> Serve<M> {
     pub fn serve<B>(&self, req: http::Request<B>) -> std::io::Result<http::Response<BoxBody>> {
+        // OpenMetrics may only be served when all metrics are formatted as a
+        // single OpenMetrics exposition. Otherwise, exemplars are removed,
+        // since they are only valid in the OpenMetrics format.
+        let openmetrics = self.metrics.is_openmetrics() && Self::prefers_openmetrics(&req);
+        let content_type = if openmetrics {
+            OPENMETRICS_CONTENT_TYPE
+        } else {
+            "text/plain"
+        };
+
         if Self::is_gzip(&req) {
             trace!("gzipping metrics");
             let mut writer = GzEncoder::new(Vec::<u8>::new(), CompressionOptions::fast());
-            write!(&mut writer, "{}", self.metrics.as_display())?;
+            self.write_metrics(&mut writer, openmetrics)?;
             Ok(http::Response::builder()
                 .header(http::header::CONTENT_ENCODING, "gzip")
-                .header(http::header::CONTENT_TYPE, "text/plain")
+                .header(http::header::CONTENT_TYPE, content_type)
                 .body(BoxBody::new(http_body_util::Full::<Bytes>::from(
                     writer.finish().map(Bytes::from)?,
                 )))
                 .expect("Response must be valid"))
         } else {
             let mut writer = Vec::<u8>::new();
-            write!(&mut writer, "{}", self.metrics.as_display())?;
+            self.write_metrics(&mut writer, openmetrics)?;
             Ok(http::Response::builder()
-                .header(http::header::CONTENT_TYPE, "text/plain")
+                .header(http::header::CONTENT_TYPE, content_type)
                 .body(BoxBody::new(http_body_util::Full::<Bytes>::from(
                     Bytes::from(writer),
                 )))
                 .expect("Response must be valid"))
         }
     }
+
+    fn write_metrics(&self, writer: &mut impl Write, openmetrics: bool) -> io::Result<()> {
+        if openmetrics {
+            return write!(writer, "{}", self.metrics.as_display());
+        }
+        let mut writer = StripExemplars::new(writer);
+        write!(&mut writer, "{}", self.metrics.as_display())?;
+        writer.flush()
+    }
+}
+
+// === impl StripExemplars ===
+
+impl<W: Write> StripExemplars<W> {
+    fn new(inner: W) -> Self {
+        Self {
+            inner,
+            line: Vec::new(),
+        }
+    }
+
+    fn write_line(&mut self) -> io::Result<()> {
+        let len = std::str::from_utf8(&self.line).map_or(self.line.len(), sample_len);
+        self.inner.write_all(&self.line[..len])?;
+        self.line.clear();
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for StripExemplars<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut rest = buf;
+        while let Some(i) = rest.iter().position(|b| *b == b'\n') {
+            self.line.extend_from_slice(&rest[..i]);
+            self.write_line()?;
+            self.inner.write_all(b"\n")?;
+            rest = &rest[i + 1..];
+        }
+        self.line.extend_from_slice(rest);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.write_line()?;
+        self.inner.flush()
+    }
+}
+
+/// Returns the length of a line without its exemplar.
+fn sample_len(line: &str) -> usize {
+    if line.starts_with('#') {
+        return line.len();
+    }
+
+    // Skip over the label set, whose quoted values may contain anything.
+    let mut start = 0;
+    if let Some(open) = line.find('{') {
+        let mut quoted = false;
+        let mut escaped = false;
+        for (i, c) in line.char_indices().skip_while(|(i, _)| *i <= open) {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' if quoted => escaped = true,
+                '"' => quoted = !quoted,
+                '}' if !quoted => {
+                    start = i;
+                    break;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    match line[start..].find(" #") {
+        Some(i) => start + i,
+        None => line.len(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXPOSITION: &str = "\
+# HELP request_duration_seconds Request durations.
+# TYPE request_duration_seconds histogram
+request_duration_seconds_bucket{le=\"0.5\",route=\"a # {b}\"} 1 # {trace_id=\"abc\"} 0.25
+request_duration_seconds_bucket{le=\"+Inf\",route=\"a # {b}\"} 1
+request_duration_seconds_count 1
+# EOF
+";
+
+    struct Exposition(bool);
+
+    impl FmtMetrics for Exposition {
+        fn fmt_metrics(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str(EXPOSITION)
+        }
+
+        fn is_openmetrics(&self) -> bool {
+            self.0
+        }
+    }
+
+    async fn scrape(metrics: Exposition, accept: &str) -> (String, String) {
+        let req = http::Request::builder()
+            .header(http::header::ACCEPT, accept)
+            .body(())
+            .unwrap();
+        let rsp = Serve::new(metrics).serve(req).unwrap();
+        let content_type = rsp.headers()[http::header::CONTENT_TYPE]
+            .to_str()
+            .unwrap()
+            .to_owned();
+        let body = http_body_util::BodyExt::collect(rsp.into_body())
+            .await
+            .unwrap()
+            .to_bytes();
+        (content_type, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[test]
+    fn strips_exemplars() {
+        let mut out = Vec::new();
+        let mut writer = StripExemplars::new(&mut out);
+        // Lines may be split across writes.
+        for chunk in EXPOSITION.as_bytes().chunks(7) {
+            writer.write_all(chunk).unwrap();
+        }
+        writer.flush().unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "\
+# HELP request_duration_seconds Request durations.
+# TYPE request_duration_seconds histogram
+request_duration_seconds_bucket{le=\"0.5\",route=\"a # {b}\"} 1
+request_duration_seconds_bucket{le=\"+Inf\",route=\"a # {b}\"} 1
+request_duration_seconds_count 1
+# EOF
+"
+        );
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn negotiates_openmetrics() {
+        let prometheus = "application/openmetrics-text;version=1.0.0;q=0.5,\
+            application/openmetrics-text;version=0.0.1;q=0.4,\
+            text/plain;version=0.0.4;q=0.3,*/*;q=0.2";
+        let (content_type, body) = scrape(Exposition(true), prometheus).await;
+        assert_eq!(content_type, OPENMETRICS_CONTENT_TYPE);
+        assert_eq!(body, EXPOSITION);
+
+        for accept in [
+            "text/plain",
+            "application/openmetrics-text;q=0.5, text/plain",
+            "application/openmetrics-text;q=0",
+            "*/*",
+        ] {
+            let (content_type, body) = scrape(Exposition(true), accept).await;
+            assert_eq!(content_type, "text/plain", "{accept}");
+            assert!(!body.contains("trace_id"), "{accept}");
+        }
+
+        // Metrics that are not a single OpenMetrics exposition are always
+        // served as text.
+        let (content_type, body) = scrape(Exposition(false), prometheus).await;
+        assert_eq!(content_type, "text/plain");
+        assert!(!body.contains("trace_id"));
+    }
 }
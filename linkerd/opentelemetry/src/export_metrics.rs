@@ -0,0 +1,111 @@
+use self::convert::Times;
+use futures::StreamExt;
+use http_body::Body;
+use linkerd_error::Error;
+use linkerd_exp_backoff::ExponentialBackoff;
+use linkerd_metrics::prom;
+use opentelemetry_proto::{
+    tonic::{
+        collector::metrics::v1::{
+            metrics_service_client::MetricsServiceClient, ExportMetricsServiceRequest,
+        },
+        metrics::v1::{ResourceMetrics, ScopeMetrics},
+        resource::v1::Resource,
+    },
+    transform::common::tonic::ResourceAttributesWithSchema,
+};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::time::{self, Duration, MissedTickBehavior};
+use tonic::{self as grpc, body::Body as TonicBody, client::GrpcService};
+use tracing::{debug, trace};
+
+mod convert;
+
+/// Periodically exports the metrics in `registry` to an OTLP metrics
+/// collector.
+///
+/// Failed exports are retried with `backoff` until they succeed. Each attempt
+/// exports the registry's current values.
+pub async fn export_metrics<T>(
+    client: T,
+    registry: prom::Report,
+    resource: ResourceAttributesWithSchema,
+    interval: Duration,
+    backoff: ExponentialBackoff,
+) where
+    T: GrpcService<TonicBody>,
+    T::Error: Into<Error>,
+    T::ResponseBody: Body<Data = tonic::codegen::Bytes> + Send + 'static,
+    <T::ResponseBody as Body>::Error: Into<Error> + Send,
+{
+    debug!("Metrics exporter running");
+    let start = unix_nanos(SystemTime::now());
+    let resource = Resource {
+        attributes: resource.attributes.0,
+        dropped_attributes_count: 0,
+        entity_refs: vec![],
+    };
+    let mut svc = MetricsServiceClient::new(client);
+
+    let mut interval = time::interval(interval);
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    loop {
+        interval.tick().await;
+
+        let mut backoff = backoff.stream();
+        loop {
+            let req = request(&registry, &resource, start);
+            trace!("Exporting metrics");
+            match svc.export(grpc::Request::new(req)).await {
+                Ok(rsp) => {
+                    if let Some(partial_success) = rsp.into_inner().partial_success {
+                        if !partial_success.error_message.is_empty() {
+                            debug!(
+                                %partial_success.error_message,
+                                rejected_data_points = partial_success.rejected_data_points,
+                                "Response partially successful",
+                            );
+                        }
+                    }
+                    break;
+                }
+                Err(status) => {
+                    debug!(%status, "Failed to export metrics");
+                }
+            }
+            backoff.next().await;
+        }
+    }
+}
+
+fn request(
+    registry: &prom::Report,
+    resource: &Resource,
+    start: u64,
+) -> ExportMetricsServiceRequest {
+    let mut text = String::new();
+    if let Err(error) = prom::encoding::text::encode(&mut text, registry) {
+        debug!(%error, "Failed to encode metrics");
+    }
+    let times = Times {
+        start,
+        now: unix_nanos(SystemTime::now()),
+    };
+    ExportMetricsServiceRequest {
+        resource_metrics: vec![ResourceMetrics {
+            resource: Some(resource.clone()),
+            scope_metrics: vec![ScopeMetrics {
+                scope: None,
+                metrics: convert::to_otlp(&text, times),
+                schema_url: String::new(),
+            }],
+            schema_url: String::new(),
+        }],
+    }
+}
+
+fn unix_nanos(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or_default()
+}
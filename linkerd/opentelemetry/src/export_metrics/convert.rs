@@ -0,0 +1,421 @@
+//! Converts the OpenMetrics text exposition of a `prometheus_client` registry
+//! into OTLP metrics.
+
+use opentelemetry_proto::tonic::{
+    common::v1::{any_value, AnyValue, KeyValue},
+    metrics::v1::{
+        metric, number_data_point, AggregationTemporality, Gauge, Histogram, HistogramDataPoint,
+        Metric, NumberDataPoint, Sum,
+    },
+};
+
+/// The times that are recorded on each data point.
+#[derive(Copy, Clone, Debug)]
+pub(super) struct Times {
+    /// When the proxy started recording metrics, in nanoseconds since the
+    /// Unix epoch.
+    pub start: u64,
+    /// When the metrics were collected, in nanoseconds since the Unix epoch.
+    pub now: u64,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Kind {
+    Counter,
+    Gauge,
+    Histogram,
+    Info,
+    Unknown,
+}
+
+#[derive(Debug)]
+struct Family<'t> {
+    name: &'t str,
+    kind: Kind,
+    help: String,
+    unit: &'t str,
+    samples: Vec<Sample<'t>>,
+}
+
+#[derive(Debug)]
+struct Sample<'t> {
+    suffix: &'t str,
+    labels: Vec<(&'t str, String)>,
+    value: &'t str,
+}
+
+/// Converts an OpenMetrics text exposition into OTLP metrics.
+///
+/// Counters become monotonic cumulative sums, histograms become cumulative
+/// explicit-bucket histograms, and all other metric types become gauges.
+/// Exemplars and `_created` samples are ignored.
+pub(super) fn to_otlp(text: &str, times: Times) -> Vec<Metric> {
+    parse(text)
+        .into_iter()
+        .filter(|f| !f.samples.is_empty())
+        .map(|f| f.into_otlp(times))
+        .collect()
+}
+
+fn parse(text: &str) -> Vec<Family<'_>> {
+    let mut families = Vec::<Family<'_>>::new();
+    for line in text.lines() {
+        if let Some(comment) = line.strip_prefix("# ") {
+            let mut parts = comment.splitn(3, ' ');
+            let (Some(keyword), Some(name)) = (parts.next(), parts.next()) else {
+                continue;
+            };
+            let rest = parts.next().unwrap_or_default();
+            if !matches!(families.last(), Some(f) if f.name == name) {
+                families.push(Family::new(name, Kind::Unknown));
+            }
+            let family = families.last_mut().expect("family must exist");
+            match keyword {
+                "TYPE" => family.kind = Kind::parse(rest),
+                "HELP" => family.help = unescape(rest),
+                "UNIT" => family.unit = rest,
+                _ => {}
+            }
+            continue;
+        }
+
+        let Some((name, labels, value)) = parse_sample(line) else {
+            continue;
+        };
+        if !matches!(families.last(), Some(f) if name.starts_with(f.name)) {
+            // Samples without metadata are treated as untyped.
+            families.push(Family::new(name, Kind::Unknown));
+        }
+        let family = families.last_mut().expect("family must exist");
+        family.samples.push(Sample {
+            suffix: &name[family.name.len()..],
+            labels,
+            value,
+        });
+    }
+    families
+}
+
+/// Parses a `name{label="value",...} value [timestamp] [# exemplar]` line.
+fn parse_sample(line: &str) -> Option<(&str, Vec<(&str, String)>, &str)> {
+    let name_end = line.find(['{', ' '])?;
+    let (name, mut rest) = line.split_at(name_end);
+
+    let mut labels = Vec::new();
+    if let Some(mut r) = rest.strip_prefix('{') {
+        loop {
+            r = r.trim_start_matches(',');
+            if let Some(after) = r.strip_prefix('}') {
+                rest = after;
+                break;
+            }
+            let (key, after) = r.split_once("=\"")?;
+            let (value, after) = split_label_value(after)?;
+            labels.push((key, value));
+            r = after;
+        }
+    }
+
+    let value = rest.trim_start().split(' ').next()?;
+    Some((name, labels, value))
+}
+
+/// Splits an escaped label value from the rest of the line, which follows
+/// its closing quote.
+fn split_label_value(s: &str) -> Option<(String, &str)> {
+    let mut value = String::new();
+    let mut chars = s.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Some((value, &s[i + 1..])),
+            '\\' => match chars.next()?.1 {
+                'n' => value.push('\n'),
+                c => value.push(c),
+            },
+            c => value.push(c),
+        }
+    }
+    None
+}
+
+fn unescape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('\\', Some('n')) => {
+                out.push('\n');
+                chars.next();
+            }
+            ('\\', Some(c @ ('\\' | '"'))) => {
+                out.push(c);
+                chars.next();
+            }
+            (c, _) => out.push(c),
+        }
+    }
+    out
+}
+
+fn parse_value(value: &str) -> f64 {
+    match value {
+        "+Inf" => f64::INFINITY,
+        "-Inf" => f64::NEG_INFINITY,
+        v => v.parse().unwrap_or(f64::NAN),
+    }
+}
+
+fn number_value(value: &str) -> number_data_point::Value {
+    match value.parse::<i64>() {
+        Ok(v) => number_data_point::Value::AsInt(v),
+        Err(_) => number_data_point::Value::AsDouble(parse_value(value)),
+    }
+}
+
+fn attributes(labels: &[(&str, String)]) -> Vec<KeyValue> {
+    labels
+        .iter()
+        .map(|(k, v)| KeyValue {
+            key: k.to_string(),
+            value: Some(AnyValue {
+                value: Some(any_value::Value::StringValue(v.clone())),
+            }),
+            key_strindex: 0,
+        })
+        .collect()
+}
+
+// === impl Kind ===
+
+impl Kind {
+    fn parse(s: &str) -> Self {
+        match s {
+            "counter" => Self::Counter,
+            "gauge" => Self::Gauge,
+            "histogram" => Self::Histogram,
+            "info" => Self::Info,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+// === impl Family ===
+
+impl<'t> Family<'t> {
+    fn new(name: &'t str, kind: Kind) -> Self {
+        Self {
+            name,
+            kind,
+            help: String::new(),
+            unit: "",
+            samples: Vec::new(),
+        }
+    }
+
+    fn into_otlp(self, times: Times) -> Metric {
+        let data = match self.kind {
+            Kind::Counter => metric::Data::Sum(Sum {
+                data_points: self.number_points(times, &["_total", ""]),
+                aggregation_temporality: AggregationTemporality::Cumulative as i32,
+                is_monotonic: true,
+            }),
+            Kind::Histogram => metric::Data::Histogram(Histogram {
+                data_points: self.histogram_points(times),
+                aggregation_temporality: AggregationTemporality::Cumulative as i32,
+            }),
+            Kind::Info => metric::Data::Gauge(Gauge {
+                data_points: self.number_points(times, &["_info"]),
+            }),
+            Kind::Gauge | Kind::Unknown => metric::Data::Gauge(Gauge {
+                data_points: self.number_points(times, &[""]),
+            }),
+        };
+        Metric {
+            name: self.name.to_string(),
+            description: self.help,
+            unit: self.unit.to_string(),
+            metadata: vec![],
+            data: Some(data),
+        }
+    }
+
+    fn number_points(&self, times: Times, suffixes: &[&str]) -> Vec<NumberDataPoint> {
+        self.samples
+            .iter()
+            .filter(|s| suffixes.contains(&s.suffix))
+            .map(|s| NumberDataPoint {
+                attributes: attributes(&s.labels),
+                start_time_unix_nano: times.start,
+                time_unix_nano: times.now,
+                exemplars: vec![],
+                flags: 0,
+                value: Some(number_value(s.value)),
+            })
+            .collect()
+    }
+
+    fn histogram_points(&self, times: Times) -> Vec<HistogramDataPoint> {
+        let mut points = Vec::<(Vec<(&str, String)>, HistogramDataPoint)>::new();
+        for sample in &self.samples {
+            let mut labels = sample.labels.clone();
+            let le = labels
+                .iter()
+                .position(|(k, _)| *k == "le")
+                .map(|i| labels.remove(i).1);
+
+            let i = match points.iter().position(|(l, _)| *l == labels) {
+                Some(i) => i,
+                None => {
+                    let point = HistogramDataPoint {
+                        attributes: attributes(&labels),
+                        start_time_unix_nano: times.start,
+                        time_unix_nano: times.now,
+                        ..Default::default()
+                    };
+                    points.push((labels, point));
+                    points.len() - 1
+                }
+            };
+            let point = &mut points[i].1;
+
+            let value = parse_value(sample.value);
+            match (sample.suffix, le) {
+                ("_bucket", Some(le)) => {
+                    // Buckets are exposed cumulatively, but OTLP bucket counts
+                    // are not.
+                    let count = value as u64;
+                    let prior = point.bucket_counts.iter().sum::<u64>();
+                    point.bucket_counts.push(count.saturating_sub(prior));
+                    let bound = parse_value(&le);
+                    if bound.is_finite() {
+                        point.explicit_bounds.push(bound);
+                    }
+                }
+                ("_sum", _) => point.sum = Some(value),
+                ("_count", _) => point.count = value as u64,
+                _ => {}
+            }
+        }
+        points.into_iter().map(|(_, point)| point).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use linkerd_metrics::prom;
+
+    const TIMES: Times = Times { start: 1, now: 2 };
+
+    fn points(metric: &Metric) -> Vec<(Vec<(String, String)>, number_data_point::Value)> {
+        let points = match metric.data.as_ref().expect("data") {
+            metric::Data::Sum(sum) => &sum.data_points,
+            metric::Data::Gauge(gauge) => &gauge.data_points,
+            data => panic!("unexpected data: {data:?}"),
+        };
+        points
+            .iter()
+            .map(|p| {
+                let labels = p
+                    .attributes
+                    .iter()
+                    .map(|kv| {
+                        let Some(any_value::Value::StringValue(v)) =
+                            kv.value.as_ref().and_then(|v| v.value.clone())
+                        else {
+                            panic!("unexpected value: {kv:?}")
+                        };
+                        (kv.key.clone(), v)
+                    })
+                    .collect();
+                (labels, p.value.expect("value"))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn converts_registry() {
+        let mut registry = prom::Registry::default();
+        let requests = prom::Family::<Vec<(String, String)>, prom::Counter>::default();
+        registry.register("requests", "Total \"requests\"", requests.clone());
+        requests
+            .get_or_create(&vec![("route".to_string(), "a,b} c".to_string())])
+            .inc_by(3);
+        let conns = prom::Gauge::<i64>::default();
+        registry.register("connections", "Open connections", conns.clone());
+        conns.set(-2);
+        let latency = prom::Histogram::new([0.1, 1.0]);
+        registry.register_with_unit(
+            "latency",
+            "Request latency",
+            prom::Unit::Seconds,
+            latency.clone(),
+        );
+        latency.observe(0.0625);
+        latency.observe(0.5);
+        latency.observe(4.0);
+
+        let mut text = String::new();
+        prom::encoding::text::encode(&mut text, &registry).expect("encode");
+        let metrics = to_otlp(&text, TIMES);
+        assert_eq!(metrics.len(), 3, "{metrics:#?}");
+
+        assert_eq!(metrics[0].name, "requests");
+        assert_eq!(metrics[0].description, "Total \"requests\".");
+        let Some(metric::Data::Sum(ref sum)) = metrics[0].data else {
+            panic!("expected a sum")
+        };
+        assert!(sum.is_monotonic);
+        assert_eq!(
+            points(&metrics[0]),
+            vec![(
+                vec![("route".to_string(), "a,b} c".to_string())],
+                number_data_point::Value::AsInt(3)
+            )]
+        );
+
+        assert_eq!(metrics[1].name, "connections");
+        assert_eq!(
+            points(&metrics[1]),
+            vec![(vec![], number_data_point::Value::AsInt(-2))]
+        );
+
+        assert_eq!(metrics[2].name, "latency_seconds");
+        assert_eq!(metrics[2].unit, "seconds");
+        let Some(metric::Data::Histogram(ref histogram)) = metrics[2].data else {
+            panic!("expected a histogram")
+        };
+        let point = &histogram.data_points[0];
+        assert_eq!(point.count, 3);
+        assert_eq!(point.sum, Some(4.5625));
+        assert_eq!(point.bucket_counts, vec![1, 1, 1]);
+        assert_eq!(point.explicit_bounds, vec![0.1, 1.0]);
+        assert_eq!(point.start_time_unix_nano, 1);
+        assert_eq!(point.time_unix_nano, 2);
+    }
+
+    #[test]
+    fn ignores_exemplars_and_created() {
+        let text = "\
+# TYPE reqs counter
+reqs_total{a=\"1 # 2\"} 5 # {trace_id=\"abc\"} 1.0
+reqs_created{a=\"1 # 2\"} 1700000000.0
+untyped 1.5
+# EOF
+";
+        let metrics = to_otlp(text, TIMES);
+        assert_eq!(metrics.len(), 2);
+        assert_eq!(
+            points(&metrics[0]),
+            vec![(
+                vec![("a".to_string(), "1 # 2".to_string())],
+                number_data_point::Value::AsInt(5)
+            )]
+        );
+        assert_eq!(metrics[1].name, "untyped");
+        assert_eq!(
+            points(&metrics[1]),
+            vec![(vec![], number_data_point::Value::AsDouble(1.5))]
+        );
+    }
+}
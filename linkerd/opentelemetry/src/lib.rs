@@ -1,8 +1,10 @@
 #![deny(rust_2018_idioms, clippy::disallowed_methods, clippy::disallowed_types)]
 #![forbid(unsafe_code)]
 
+mod export_metrics;
 pub mod metrics;
 
+pub use self::export_metrics::export_metrics;
 use self::metrics::Registry;
 use futures::stream::{Stream, StreamExt};
 use http_body::Body;
@@ -251,10 +253,19 @@ fn convert_span(span: ExportSpan) -> Result<SpanData, Error> {
     for (k, v) in span.labels.into_iter() {
         attributes.push(KeyValue::new(k, v));
     }
+    for (k, v) in span.baggage.into_iter() {
+        attributes.push(KeyValue::new(k, v));
+    }
     let is_remote = kind != trace_context::export::SpanKind::Client;
+    // Traces that the proxy starts have no parent span.
+    let (parent_span_id, parent_span_is_remote) = if span.parent_id.as_ref().is_empty() {
+        (SpanId::INVALID, false)
+    } else {
+        (SpanId::from_bytes(span.parent_id.into_bytes()?), true)
+    };
     Ok(SpanData {
-        parent_span_id: SpanId::from_bytes(span.parent_id.into_bytes()?),
-        parent_span_is_remote: true, // we do not originate any spans locally
+        parent_span_id,
+        parent_span_is_remote,
         span_kind: match kind {
             trace_context::export::SpanKind::Server => SpanKind::Server,
             trace_context::export::SpanKind::Client => SpanKind::Client,
@@ -313,6 +324,7 @@ mod tests {
                 start,
                 end,
                 labels: HashMap::new(),
+                baggage: vec![],
             },
             kind: SpanKind::Server,
             labels: Arc::new(Default::default()),
@@ -1,7 +1,7 @@
 use http::uri::Authority;
 use linkerd_http_h2::ClientParams as HTTP2ClientParams;
 use linkerd_tls::client::ClientTls;
-use std::collections::BTreeMap;
+use std::{collections::BTreeMap, net::SocketAddr};
 
 /// Endpoint labels are lexigraphically ordered by key.
 pub type Labels = std::sync::Arc<BTreeMap<String, String>>;
@@ -28,6 +28,10 @@ pub struct Metadata {
 
     http2: HTTP2ClientParams,
     is_zone_local: Option<bool>,
+
+    /// An address of the other IP family at which the same endpoint may be
+    /// reached, if one was discovered.
+    dual_stack_addr: Option<SocketAddr>,
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
@@ -55,6 +59,7 @@ impl Default for Metadata {
             protocol_hint: ProtocolHint::Unknown,
             http2: HTTP2ClientParams::default(),
             is_zone_local: None,
+            dual_stack_addr: None,
         }
     }
 }
@@ -80,6 +85,25 @@ impl Metadata {
             weight,
             http2,
             is_zone_local,
+            dual_stack_addr: None,
+        }
+    }
+
+    /// Returns metadata for an endpoint that was discovered without the
+    /// destination controller (e.g. via DNS), so only its weight is known.
+    pub fn from_weight(weight: u32) -> Self {
+        Self {
+            weight,
+            ..Self::default()
+        }
+    }
+
+    /// Sets an address of the other IP family at which the endpoint may be
+    /// reached.
+    pub fn with_dual_stack_addr(self, addr: Option<SocketAddr>) -> Self {
+        Self {
+            dual_stack_addr: addr,
+            ..self
         }
     }
 
@@ -112,6 +136,10 @@ impl Metadata {
         self.authority_override.as_ref()
     }
 
+    pub fn dual_stack_addr(&self) -> Option<SocketAddr> {
+        self.dual_stack_addr
+    }
+
     pub fn http2_client_params(&self) -> &HTTP2ClientParams {
         &self.http2
     }
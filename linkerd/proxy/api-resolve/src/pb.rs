@@ -192,6 +192,9 @@ fn to_http2_client_params(pb: Http2ClientParams) -> linkerd_http_h2::ClientParam
                 while_idle: pb.while_idle,
             })
         }),
+        // The destination API does not describe whether endpoints support
+        // extended CONNECT.
+        extended_connect: None,
         max_concurrent_reset_streams: pb
             .internals
             .as_ref()
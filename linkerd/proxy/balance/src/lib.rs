@@ -15,11 +15,17 @@ use linkerd_proxy_core::Resolve;
 use linkerd_stack::{layer, queue, ExtractParam, Gate, NewService, Param, Service};
 use std::{fmt::Debug, marker::PhantomData, net::SocketAddr};
 use tokio::time;
-use tower::load::{self, PeakEwma};
+use tower::load;
 
+pub mod peak_ewma;
+pub mod registry;
+
+use self::{
+    peak_ewma::PeakEwma,
+    registry::{LoadProbe, LoadSample, NewRegisterEndpoint, ProbeLoad},
+};
 pub use linkerd_load_biaser::{FailureHint, ResponseFailureHint};
 pub use linkerd_proxy_balance_queue::{Pool, QueueMetricFamilies, QueueMetrics, Update};
-pub use tower::load::peak_ewma;
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct EwmaConfig {
@@ -49,6 +55,7 @@ pub struct Metrics {
     queue: QueueMetrics,
     p2c: P2cMetrics,
     endpoints: EndpointsGauges,
+    registry: registry::Balancer,
 }
 
 /// Configures a stack to resolve targets to balance requests over `N`-typed
@@ -79,7 +86,7 @@ pub struct NewPenaltyPeakEwmaBalance<Req, X, R, N> {
 pub type Balance<Req, F> = Gate<PoolQueue<Req, F>>;
 
 /// The pool service produced by the peak-EWMA estimator. This wraps each
-/// endpoint in a Tower [`PeakEwma`] load tracker.
+/// endpoint in a [`PeakEwma`] load tracker.
 pub type PeakEwmaBalance<C, Req, S> =
     Balance<Req, future::ErrInto<<PeakEwma<S, C> as Service<Req>>::Future, Error>>;
 
@@ -116,6 +123,7 @@ struct PoolSetup<N> {
     queue: QueueMetrics,
     capacity: usize,
     failfast: time::Duration,
+    registry: registry::Balancer,
     inner: NewGaugeBalancerEndpoint<N>,
 }
 
@@ -137,6 +145,7 @@ where
         p2c,
         queue,
         endpoints,
+        registry,
     } = params.extract_param(&target);
 
     // The pool wraps the inner endpoint stack so that its inner ready cache can
@@ -148,6 +157,7 @@ where
         queue,
         capacity,
         failfast,
+        registry,
         inner,
     }
 }
@@ -186,7 +196,7 @@ where
     S: Service<Req> + Send + 'static,
     S::Future: Send + 'static,
     S::Error: Into<Error>,
-    C: load::TrackCompletion<load::peak_ewma::Handle, S::Response> + Default + Send + 'static,
+    C: load::TrackCompletion<peak_ewma::Handle, S::Response> + Default + Send + 'static,
     Req: Send + 'static,
     PeakEwmaBalance<C, Req, S>: Service<Req>,
 {
@@ -210,12 +220,18 @@ where
             queue,
             capacity,
             failfast,
+            registry,
             inner,
         } = pool_setup(&self.params, &self.inner, target);
 
-        // Wrap each endpoint in a Tower peak-EWMA load tracker using the RTT
-        // configuration from the target.
-        let new_endpoint = NewPeakEwma::new(config, inner);
+        // Wrap each endpoint in a peak-EWMA load tracker using the RTT
+        // configuration from the target, and register it so that its load may
+        // be inspected.
+        let disco = disco.inspect_ok({
+            let registry = registry.clone();
+            move |update| registry.discovered(update)
+        });
+        let new_endpoint = NewRegisterEndpoint::new(registry, NewPeakEwma::new(config, inner));
         let pool = P2cPool::new(p2c, new_endpoint);
 
         // The queue runs on a dedicated task, owning the resolution stream and
@@ -299,6 +315,7 @@ where
             queue,
             capacity,
             failfast,
+            registry,
             inner,
         } = pool_setup(&self.params, &self.inner, target);
 
@@ -306,9 +323,13 @@ where
         // rate-limited endpoints are de-prioritized for the configured penalty
         // window. The biaser records the round-trip time at the first response
         // data frame, which is the same point the peak-EWMA path measures it.
+        let disco = disco.inspect_ok({
+            let registry = registry.clone();
+            move |update| registry.discovered(update)
+        });
         let new_endpoint: NewLoadBiaser<_, Req> =
             NewLoadBiaser::new(penalty_biaser_config(ppe), inner);
-        let pool = P2cPool::new(p2c, new_endpoint);
+        let pool = P2cPool::new(p2c, NewRegisterEndpoint::new(registry, new_endpoint));
 
         // The queue runs on a dedicated task that owns the resolution stream and
         // all of the inner endpoint services. The returned Service is cloneable
@@ -374,34 +395,52 @@ impl<C, Req, N> NewPeakEwma<C, Req, N> {
 
 impl<C, T, N, Req, S> NewService<T> for NewPeakEwma<C, Req, N>
 where
-    C: load::TrackCompletion<load::peak_ewma::Handle, S::Response> + Default,
+    C: load::TrackCompletion<peak_ewma::Handle, S::Response> + Default,
     N: NewService<T, Service = S>,
     S: Service<Req>,
 {
     type Service = PeakEwma<S, C>;
 
     fn new_service(&self, target: T) -> Self::Service {
-        // Converts durations to nanos in f64.
-        //
-        // Due to a lossy transformation, the maximum value that can be
-        // represented is ~585 years, which, I hope, is more than enough to
-        // represent request latencies.
-        fn nanos(d: time::Duration) -> f64 {
-            const NANOS_PER_SEC: u64 = 1_000_000_000;
-            let n = f64::from(d.subsec_nanos());
-            let s = d.as_secs().saturating_mul(NANOS_PER_SEC) as f64;
-            n + s
-        }
-
         PeakEwma::new(
             self.inner.new_service(target),
             self.config.default_rtt.max(MIN_DEFAULT_RTT),
-            nanos(self.config.decay),
+            self.config.decay,
             C::default(),
         )
     }
 }
 
+// === impl ProbeLoad ===
+
+impl<S, C> ProbeLoad for PeakEwma<S, C> {
+    fn probe_load(&self) -> LoadProbe {
+        let probe = self.probe();
+        LoadProbe::new(move || {
+            let (rtt, in_flight) = probe.read()?;
+            Some(LoadSample {
+                load: rtt.as_secs_f64() * f64::from(in_flight.saturating_add(1)),
+                rtt: Some(rtt),
+                in_flight: Some(in_flight),
+            })
+        })
+    }
+}
+
+impl<S, C> ProbeLoad for LoadBiaser<S, C> {
+    fn probe_load(&self) -> LoadProbe {
+        let probe = self.probe();
+        LoadProbe::new(move || {
+            let (rtt, in_flight) = probe.read()?;
+            Some(LoadSample {
+                load: rtt.as_secs_f64() * f64::from(in_flight.saturating_add(1)),
+                rtt: Some(rtt),
+                in_flight: Some(in_flight),
+            })
+        })
+    }
+}
+
 // === impl MetricFamilies ===
 
 impl<L> MetricFamilies<L>
@@ -426,10 +465,20 @@ where
             p2c: self.p2c.metrics(labels),
             queue: self.queue.metrics(labels),
             endpoints: self.endpoints.metrics(labels),
+            registry: Default::default(),
         }
     }
 }
 
+// === impl Metrics ===
+
+impl Metrics {
+    /// Registers the balancer's endpoints so that they may be inspected.
+    pub fn with_registry(self, registry: registry::Balancer) -> Self {
+        Self { registry, ..self }
+    }
+}
+
 impl<L> Default for MetricFamilies<L>
 where
     L: prom::encoding::EncodeLabelSet + std::fmt::Debug + std::hash::Hash,
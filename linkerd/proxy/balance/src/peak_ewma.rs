@@ -0,0 +1,163 @@
+//! Peak-EWMA load tracking.
+//!
+//! This is the estimator of Tower's `PeakEwma`: an endpoint's load is the
+//! peak-EWMA of its round-trip times scaled by its pending requests. Tower's
+//! estimate is private, so this tracker keeps its estimate in a
+//! [`linkerd_ewma::Ewma`] that a [`Probe`] may read without holding the
+//! service, e.g. to describe a balancer's endpoints.
+
+use linkerd_ewma::Ewma;
+use linkerd_stack::Service;
+use parking_lot::RwLock;
+use std::{
+    sync::{Arc, Weak},
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::time::Instant;
+use tower::load::{completion::TrackCompletionFuture, CompleteOnResponse, Load, TrackCompletion};
+
+/// Wraps a service so that its load is tracked by the peak-EWMA of its
+/// round-trip times.
+#[derive(Debug)]
+pub struct PeakEwma<S, C = CompleteOnResponse> {
+    inner: S,
+    rtt: Arc<RwLock<Ewma>>,
+    completion: C,
+}
+
+/// Tracks an in-flight request and records its round-trip time when dropped.
+///
+/// Each handle holds a clone of the service's estimate, so the number of
+/// pending requests is the estimate's strong count, less the service's own
+/// reference.
+#[derive(Debug)]
+pub struct Handle {
+    rtt: Arc<RwLock<Ewma>>,
+    sent_at: Instant,
+}
+
+/// Reads a [`PeakEwma`]'s estimate without holding the service.
+///
+/// The probe holds a weak reference to the estimate, so it is not counted as a
+/// pending request and it does not keep the estimate alive once the service is
+/// dropped.
+#[derive(Clone, Debug)]
+pub struct Probe(Weak<RwLock<Ewma>>);
+
+// === impl PeakEwma ===
+
+impl<S, C> PeakEwma<S, C> {
+    pub fn new(inner: S, default_rtt: Duration, decay: Duration, completion: C) -> Self {
+        let rtt = Ewma::new_with_value(decay, Instant::now(), default_rtt.as_secs_f64());
+        Self {
+            inner,
+            rtt: Arc::new(RwLock::new(rtt)),
+            completion,
+        }
+    }
+
+    /// Returns a probe that reads this service's estimate.
+    pub fn probe(&self) -> Probe {
+        Probe(Arc::downgrade(&self.rtt))
+    }
+
+    fn handle(&self) -> Handle {
+        Handle {
+            rtt: self.rtt.clone(),
+            sent_at: Instant::now(),
+        }
+    }
+}
+
+impl<S, C, Req> Service<Req> for PeakEwma<S, C>
+where
+    S: Service<Req>,
+    C: TrackCompletion<Handle, S::Response> + Clone,
+{
+    type Response = C::Output;
+    type Error = S::Error;
+    type Future = TrackCompletionFuture<S::Future, C, Handle>;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Req) -> Self::Future {
+        TrackCompletionFuture::new(self.completion.clone(), self.handle(), self.inner.call(req))
+    }
+}
+
+impl<S, C> Load for PeakEwma<S, C> {
+    type Metric = f64;
+
+    fn load(&self) -> Self::Metric {
+        // Pending is the strong count of the estimate less the service's own
+        // reference.
+        let pending = (Arc::strong_count(&self.rtt) as u32).saturating_sub(1);
+        let rtt = self.rtt.read().get_at(Instant::now());
+        let load = rtt * f64::from(pending.saturating_add(1));
+        tracing::trace!(rtt_secs = rtt, pending, load, "PeakEwma::load");
+        load
+    }
+}
+
+// === impl Handle ===
+
+impl Drop for Handle {
+    fn drop(&mut self) {
+        let now = Instant::now();
+        let rtt = now.saturating_duration_since(self.sent_at).as_secs_f64();
+        self.rtt.write().add_peak(rtt, now);
+    }
+}
+
+// === impl Probe ===
+
+impl Probe {
+    /// Returns the decayed round-trip time estimate and the number of pending
+    /// requests, or `None` if the service has been dropped.
+    pub fn read(&self) -> Option<(Duration, u32)> {
+        let rtt = self.0.upgrade()?;
+        let estimate = rtt.read().get_at(Instant::now());
+        // Discount the service's reference and the one held by this probe.
+        let pending = (Arc::strong_count(&rtt) as u32).saturating_sub(2);
+        Some((Duration::from_secs_f64(estimate.max(0.0)), pending))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(flavor = "current_thread", start_paused = true)]
+    async fn tracks_peak_rtt() {
+        let mut svc = PeakEwma::new(
+            linkerd_stack::service_fn(|()| futures::future::ok::<_, ()>(())),
+            Duration::from_millis(20),
+            Duration::from_secs(10),
+            CompleteOnResponse::default(),
+        );
+        let probe = svc.probe();
+        assert_eq!(probe.read(), Some((Duration::from_millis(20), 0)));
+        assert!((svc.load() - 0.02).abs() < 1e-6);
+
+        let rsp = svc.call(());
+        assert_eq!(probe.read().map(|(_, pending)| pending), Some(1));
+        assert!(
+            (svc.load() - 0.04).abs() < 1e-6,
+            "pending requests scale the load"
+        );
+
+        // A slow response raises the estimate to its round-trip time.
+        tokio::time::advance(Duration::from_millis(100)).await;
+        rsp.await.unwrap();
+        let (rtt, pending) = probe.read().unwrap();
+        assert_eq!(pending, 0);
+        assert!((rtt.as_secs_f64() - 0.1).abs() < 1e-6, "{rtt:?}");
+
+        drop(svc);
+        assert_eq!(probe.read(), None);
+    }
+}
@@ -0,0 +1,518 @@
+//! Records the endpoints of each balancer so that their state may be
+//! inspected, e.g. by the admin server.
+//!
+//! Endpoints register probes as they are built, and these probes are only read
+//! when the registry is inspected. Endpoints are removed from the registry as
+//! soon as they are removed from discovery.
+
+use linkerd_proxy_core::Update;
+use linkerd_stack::{gate, NewService, Service};
+use parking_lot::Mutex;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    hash::Hash,
+    net::SocketAddr,
+    sync::{Arc, Weak},
+    task::{Context, Poll},
+    time::{Duration, SystemTime},
+};
+
+/// Endpoint labels, ordered by key.
+pub type Labels = Arc<BTreeMap<String, String>>;
+
+/// Records the balancers for `K`-typed backends.
+#[derive(Debug)]
+pub struct Balancers<K> {
+    inner: Arc<Mutex<HashMap<K, Weak<Registry>>>>,
+}
+
+/// A balancer's endpoints.
+///
+/// A balancer that is not obtained from [`Balancers`] is not inspected.
+#[derive(Clone, Debug, Default)]
+pub struct Balancer(Arc<Registry>);
+
+/// A handle that describes a balancer endpoint.
+///
+/// The endpoint is removed from its balancer once all of its handles are
+/// dropped.
+#[derive(Clone, Debug)]
+pub struct Endpoint(Arc<EndpointInner>);
+
+/// A handle that describes a balancer endpoint without keeping it registered.
+///
+/// This is held by tasks that outlive the endpoint's service, e.g. its circuit
+/// breaker, which completes when the endpoint's gate is dropped.
+#[derive(Clone, Debug, Default)]
+pub struct WeakEndpoint(Weak<EndpointInner>);
+
+/// Reads an endpoint's load estimate.
+#[derive(Clone)]
+pub struct LoadProbe(Arc<dyn Fn() -> Option<LoadSample> + Send + Sync>);
+
+/// An endpoint's load estimate.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct LoadSample {
+    /// The load, in seconds, that the estimator reports to the balancer: the
+    /// round-trip time estimate scaled by the number of in-flight requests,
+    /// including the one that would be dispatched.
+    pub load: f64,
+    /// The decayed peak-EWMA round-trip time estimate, if the estimator
+    /// exposes it.
+    pub rtt: Option<Duration>,
+    /// The number of requests awaiting a response, if the estimator exposes
+    /// it.
+    pub in_flight: Option<u32>,
+}
+
+/// The state of an endpoint's circuit breaker.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GateState {
+    /// Requests are admitted.
+    Open,
+    /// A single request is admitted to probe whether the endpoint recovered.
+    Probing,
+    /// No requests are admitted.
+    Shut,
+}
+
+/// Describes the last time an endpoint's circuit breaker tripped.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Trip {
+    pub reason: &'static str,
+    pub at: SystemTime,
+}
+
+#[derive(Clone, Debug)]
+pub struct BalancerSnapshot<K> {
+    pub key: K,
+    pub endpoints: Vec<EndpointSnapshot>,
+}
+
+#[derive(Clone, Debug)]
+pub struct EndpointSnapshot {
+    pub addr: SocketAddr,
+    pub labels: Labels,
+    /// Unset when the endpoint's load is not tracked.
+    pub load: Option<LoadSample>,
+    /// Unset when the endpoint is not gated by a circuit breaker.
+    pub gate: Option<GateState>,
+    pub last_trip: Option<Trip>,
+}
+
+/// Wraps the endpoints built by an inner stack so that they are registered in
+/// a [`Balancer`] along with a probe of their load.
+#[derive(Clone, Debug)]
+pub(crate) struct NewRegisterEndpoint<N> {
+    balancer: Balancer,
+    inner: N,
+}
+
+/// An endpoint service that holds its registration.
+#[derive(Debug)]
+pub(crate) struct Registered<S> {
+    inner: S,
+    _endpoint: Endpoint,
+}
+
+/// A load-tracking service whose estimate may be probed.
+pub(crate) trait ProbeLoad {
+    fn probe_load(&self) -> LoadProbe;
+}
+
+#[derive(Debug, Default)]
+struct Registry {
+    endpoints: Mutex<HashMap<SocketAddr, Weak<EndpointInner>>>,
+}
+
+#[derive(Debug)]
+struct EndpointInner {
+    addr: SocketAddr,
+    registry: Weak<Registry>,
+    state: Mutex<EndpointState>,
+}
+
+#[derive(Debug, Default)]
+struct EndpointState {
+    labels: Labels,
+    load: Option<LoadProbe>,
+    gate: Option<gate::Rx>,
+    last_trip: Option<Trip>,
+}
+
+// === impl Balancers ===
+
+impl<K> Default for Balancers<K> {
+    fn default() -> Self {
+        Self {
+            inner: Default::default(),
+        }
+    }
+}
+
+impl<K> Clone for Balancers<K> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<K: Clone + Eq + Hash> Balancers<K> {
+    /// Returns the balancer for `key`, registering it if it is not already
+    /// active.
+    ///
+    /// The balancer is removed once all of its handles are dropped.
+    pub fn balancer(&self, key: K) -> Balancer {
+        let mut balancers = self.inner.lock();
+        if let Some(registry) = balancers.get(&key).and_then(Weak::upgrade) {
+            return Balancer(registry);
+        }
+        balancers.retain(|_, r| r.strong_count() > 0);
+        let registry = Arc::new(Registry::default());
+        balancers.insert(key, Arc::downgrade(&registry));
+        Balancer(registry)
+    }
+
+    /// Describes the endpoints of all active balancers.
+    pub fn snapshot(&self) -> Vec<BalancerSnapshot<K>> {
+        let balancers = self
+            .inner
+            .lock()
+            .iter()
+            .filter_map(|(k, r)| Some((k.clone(), r.upgrade()?)))
+            .collect::<Vec<_>>();
+        balancers
+            .into_iter()
+            .map(|(key, registry)| BalancerSnapshot {
+                key,
+                endpoints: Balancer(registry).snapshot(),
+            })
+            .collect()
+    }
+}
+
+// === impl Balancer ===
+
+impl Balancer {
+    /// Returns a handle for the endpoint at `addr`, registering it if it is not
+    /// already registered.
+    pub fn endpoint(&self, addr: SocketAddr) -> Endpoint {
+        let mut endpoints = self.0.endpoints.lock();
+        if let Some(ep) = endpoints.get(&addr).and_then(Weak::upgrade) {
+            return Endpoint(ep);
+        }
+        let ep = Arc::new(EndpointInner {
+            addr,
+            registry: Arc::downgrade(&self.0),
+            state: Default::default(),
+        });
+        endpoints.insert(addr, Arc::downgrade(&ep));
+        Endpoint(ep)
+    }
+
+    /// Unregisters endpoints that are no longer discovered, so that their
+    /// gates and load probes are released even if their services have not yet
+    /// been dropped.
+    pub(crate) fn discovered<T>(&self, update: &Update<T>) {
+        let removed = {
+            let mut endpoints = self.0.endpoints.lock();
+            match update {
+                Update::Add(_) => return,
+                Update::Remove(addrs) => addrs
+                    .iter()
+                    .filter_map(|addr| endpoints.remove(addr))
+                    .collect::<Vec<_>>(),
+                Update::Reset(eps) => {
+                    let mut removed = Vec::new();
+                    endpoints.retain(|addr, ep| {
+                        let keep = eps.iter().any(|(a, _)| a == addr);
+                        if !keep {
+                            removed.push(ep.clone());
+                        }
+                        keep
+                    });
+                    removed
+                }
+                Update::DoesNotExist => endpoints.drain().map(|(_, ep)| ep).collect(),
+            }
+        };
+        // The endpoint handles must be upgraded after the lock is released,
+        // since dropping the last handle locks the registry to unregister the
+        // endpoint.
+        for ep in removed.iter().filter_map(Weak::upgrade) {
+            let mut state = ep.state.lock();
+            state.gate = None;
+            state.load = None;
+        }
+    }
+
+    /// Describes the balancer's endpoints, ordered by address.
+    pub fn snapshot(&self) -> Vec<EndpointSnapshot> {
+        // The endpoint handles must be dropped after the lock is released,
+        // since dropping the last handle unregisters the endpoint.
+        let endpoints = self
+            .0
+            .endpoints
+            .lock()
+            .values()
+            .filter_map(Weak::upgrade)
+            .collect::<Vec<_>>();
+        let mut snapshots = endpoints.iter().map(|ep| ep.snapshot()).collect::<Vec<_>>();
+        snapshots.sort_by_key(|ep| ep.addr);
+        snapshots
+    }
+}
+
+// === impl Endpoint ===
+
+impl Endpoint {
+    pub fn set_labels(&self, labels: Labels) {
+        self.0.state.lock().labels = labels;
+    }
+
+    pub fn downgrade(&self) -> WeakEndpoint {
+        WeakEndpoint(Arc::downgrade(&self.0))
+    }
+
+    /// Describes the endpoint's circuit breaker gate.
+    ///
+    /// The gate is held only while the endpoint is registered, so that the
+    /// breaker is not kept alive once the endpoint is dropped.
+    pub fn set_gate(&self, gate: gate::Rx) {
+        self.0.state.lock().gate = Some(gate);
+    }
+
+    pub fn set_load(&self, probe: LoadProbe) {
+        self.0.state.lock().load = Some(probe);
+    }
+}
+
+impl WeakEndpoint {
+    /// Records that the endpoint's circuit breaker tripped, if the endpoint is
+    /// still registered.
+    pub fn record_trip(&self, reason: &'static str) {
+        if let Some(ep) = self.0.upgrade() {
+            ep.state.lock().last_trip = Some(Trip {
+                reason,
+                at: SystemTime::now(),
+            });
+        }
+    }
+}
+
+impl EndpointInner {
+    fn snapshot(&self) -> EndpointSnapshot {
+        let EndpointState {
+            labels,
+            load,
+            gate,
+            last_trip,
+        } = &*self.state.lock();
+        EndpointSnapshot {
+            addr: self.addr,
+            labels: labels.clone(),
+            load: load.as_ref().and_then(LoadProbe::sample),
+            gate: gate.as_ref().map(|rx| match rx.state() {
+                gate::State::Open => GateState::Open,
+                gate::State::Limited(_) => GateState::Probing,
+                gate::State::Shut => GateState::Shut,
+            }),
+            last_trip: *last_trip,
+        }
+    }
+}
+
+impl Drop for EndpointInner {
+    fn drop(&mut self) {
+        if let Some(registry) = self.registry.upgrade() {
+            let mut endpoints = registry.endpoints.lock();
+            // The endpoint may have been re-registered since this handle's
+            // strong count dropped to zero.
+            if endpoints
+                .get(&self.addr)
+                .is_some_and(|ep| ep.strong_count() == 0)
+            {
+                endpoints.remove(&self.addr);
+            }
+        }
+    }
+}
+
+// === impl LoadProbe ===
+
+impl LoadProbe {
+    pub fn new(probe: impl Fn() -> Option<LoadSample> + Send + Sync + 'static) -> Self {
+        Self(Arc::new(probe))
+    }
+
+    /// Reads the endpoint's estimate, or `None` if the endpoint has been
+    /// dropped.
+    pub fn sample(&self) -> Option<LoadSample> {
+        (self.0)()
+    }
+}
+
+impl fmt::Debug for LoadProbe {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("LoadProbe").finish()
+    }
+}
+
+// === impl NewRegisterEndpoint ===
+
+impl<N> NewRegisterEndpoint<N> {
+    pub(crate) fn new(balancer: Balancer, inner: N) -> Self {
+        Self { balancer, inner }
+    }
+}
+
+impl<E, N, S> NewService<(SocketAddr, E)> for NewRegisterEndpoint<N>
+where
+    N: NewService<(SocketAddr, E), Service = S>,
+    S: ProbeLoad,
+{
+    type Service = Registered<S>;
+
+    fn new_service(&self, (addr, ep): (SocketAddr, E)) -> Self::Service {
+        // The endpoint is registered before the inner stack is built so that
+        // the inner stack may describe it, e.g. with its breaker's gate.
+        let endpoint = self.balancer.endpoint(addr);
+        let inner = self.inner.new_service((addr, ep));
+        endpoint.set_load(inner.probe_load());
+        Registered {
+            inner,
+            _endpoint: endpoint,
+        }
+    }
+}
+
+// === impl Registered ===
+
+impl<Req, S: Service<Req>> Service<Req> for Registered<S> {
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    #[inline]
+    fn call(&mut self, req: Req) -> Self::Future {
+        self.inner.call(req)
+    }
+}
+
+impl<S: Load> Load for Registered<S> {
+    type Metric = S::Metric;
+
+    #[inline]
+    fn load(&self) -> Self::Metric {
+        self.inner.load()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unregisters_dropped_endpoints() {
+        let balancers = Balancers::<&'static str>::default();
+        let balancer = balancers.balancer("a");
+        let addr = SocketAddr::from(([192, 0, 2, 1], 8080));
+
+        let ep0 = balancer.endpoint(addr);
+        ep0.set_labels(Arc::new([("pod".into(), "a-0".into())].into()));
+        let (gate_tx, gate_rx) = gate::channel();
+        ep0.set_gate(gate_rx);
+        ep0.set_load(LoadProbe::new(|| {
+            Some(LoadSample {
+                load: 0.02,
+                rtt: Some(Duration::from_millis(10)),
+                in_flight: Some(1),
+            })
+        }));
+        ep0.downgrade().record_trip("consecutive_failures");
+        gate_tx.shut().unwrap();
+
+        // Handles for the same address share the endpoint.
+        let ep1 = balancers.balancer("a").endpoint(addr);
+        let snaps = balancers.snapshot();
+        assert_eq!(snaps.len(), 1);
+        let [ep] = &snaps[0].endpoints[..] else {
+            panic!("expected one endpoint: {:?}", snaps[0].endpoints);
+        };
+        assert_eq!(ep.addr, addr);
+        assert_eq!(ep.labels.get("pod").map(String::as_str), Some("a-0"));
+        assert_eq!(ep.gate, Some(GateState::Shut));
+        assert_eq!(ep.last_trip.map(|t| t.reason), Some("consecutive_failures"));
+        assert_eq!(ep.load.and_then(|l| l.in_flight), Some(1));
+
+        drop(ep0);
+        assert_eq!(balancer.snapshot().len(), 1);
+        let weak = ep1.downgrade();
+        drop(ep1);
+        assert!(balancer.snapshot().is_empty());
+        weak.record_trip("low_success_rate");
+        assert!(balancer.snapshot().is_empty());
+
+        drop(balancer);
+        assert!(balancers.snapshot().is_empty());
+    }
+
+    #[test]
+    fn unregisters_undiscovered_endpoints() {
+        let balancer = Balancer::default();
+        let a = SocketAddr::from(([192, 0, 2, 1], 8080));
+        let b = SocketAddr::from(([192, 0, 2, 2], 8080));
+        let ep_a = balancer.endpoint(a);
+        let (_gate_tx, gate_rx) = gate::channel();
+        ep_a.set_gate(gate_rx);
+        let _ep_b = balancer.endpoint(b);
+
+        balancer.discovered(&Update::Reset(vec![(b, ())]));
+        let [ep] = &balancer.snapshot()[..] else {
+            panic!("expected a single endpoint");
+        };
+        assert_eq!(ep.addr, b);
+        // The removed endpoint no longer holds its gate, even though its
+        // handle is still alive.
+        assert_eq!(ep_a.0.snapshot().gate, None);
+
+        balancer.discovered(&Update::Add(vec![(a, ())]));
+        assert_eq!(balancer.snapshot().len(), 1);
+        balancer.discovered(&Update::<()>::Remove(vec![b]));
+        assert!(balancer.snapshot().is_empty());
+
+        // An endpoint that is rediscovered is registered anew.
+        let _ep_a = balancer.endpoint(a);
+        drop(ep_a);
+        assert_eq!(balancer.snapshot().len(), 1);
+        balancer.discovered(&Update::<()>::DoesNotExist);
+        assert!(balancer.snapshot().is_empty());
+    }
+
+    #[test]
+    fn probes_peak_ewma_load() {
+        let svc = crate::peak_ewma::PeakEwma::new(
+            (),
+            Duration::from_millis(20),
+            Duration::from_secs(10),
+            tower::load::CompleteOnResponse::default(),
+        );
+        let probe = svc.probe_load();
+
+        // The estimate is read on demand, without the balancer reading the
+        // service's load.
+        let sample = probe.sample().expect("load must be readable");
+        assert!((sample.load - 0.02).abs() < 1e-3, "{sample:?}");
+        assert_eq!(sample.in_flight, Some(0));
+
+        drop(svc);
+        assert_eq!(probe.sample(), None);
+    }
+}
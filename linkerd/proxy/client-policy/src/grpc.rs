@@ -1,5 +1,6 @@
 use crate::FailureAccrual;
 use linkerd_exp_backoff::ExponentialBackoff;
+use linkerd_http_h2 as h2;
 use linkerd_http_route::{grpc, http};
 use std::{sync::Arc, time};
 
@@ -17,7 +18,6 @@ pub struct RouteParams {
     pub export_hostname_labels: bool,
 }
 
-// TODO HTTP2 settings
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Grpc {
     pub routes: Arc<[Route]>,
@@ -26,6 +26,10 @@ pub struct Grpc {
     /// failure accrual.
     // TODO(ver) Move this to backends and scope to endpoints.
     pub failure_accrual: Option<FailureAccrual>,
+
+    /// Overrides the proxy's HTTP/2 client settings on connections to this
+    /// parent's backends.
+    pub client_params: Arc<h2::ClientParams>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
@@ -67,6 +71,7 @@ impl Default for Grpc {
         Self {
             routes: Arc::new([]),
             failure_accrual: None,
+            client_params: Default::default(),
         }
     }
 }
@@ -187,7 +192,7 @@ pub mod proto {
 
     impl Grpc {
         pub fn try_from(
-            overrides: ClientPolicyOverrides,
+            overrides: &ClientPolicyOverrides,
             proto: outbound::proxy_protocol::Grpc,
         ) -> Result<Self, InvalidGrpcRoute> {
             let routes = proto
@@ -201,6 +206,10 @@ pub mod proto {
                     .failure_accrual
                     .map(FailureAccrual::try_from)
                     .transpose()?,
+                // The policy API does not yet carry HTTP/2 client settings, so
+                // the proxy's own settings are used, overridden per-endpoint by
+                // the destination API's metadata.
+                client_params: Default::default(),
             })
         }
 
@@ -214,7 +223,7 @@ pub mod proto {
     }
 
     fn try_route(
-        overrides: ClientPolicyOverrides,
+        overrides: &ClientPolicyOverrides,
         proto: outbound::GrpcRoute,
     ) -> Result<Route, InvalidGrpcRoute> {
         let outbound::GrpcRoute {
@@ -242,7 +251,7 @@ pub mod proto {
 
     fn try_rule(
         meta: &Arc<Meta>,
-        overrides: ClientPolicyOverrides,
+        overrides: &ClientPolicyOverrides,
         proto: outbound::grpc_route::Rule,
     ) -> Result<Rule, InvalidGrpcRoute> {
         #[allow(deprecated)]
@@ -291,7 +300,7 @@ pub mod proto {
             timeouts: Option<linkerd2_proxy_api::http_route::Timeouts>,
             retry: Option<grpc_route::Retry>,
             allow_l5d_request_headers: bool,
-            overrides: ClientPolicyOverrides,
+            overrides: &ClientPolicyOverrides,
         ) -> Result<Self, InvalidGrpcRoute> {
             Ok(Self {
                 retry: retry.map(Retry::try_from).transpose()?,
@@ -1,5 +1,7 @@
 use crate::FailureAccrual;
 use linkerd_exp_backoff::ExponentialBackoff;
+use linkerd_http_h1 as h1;
+use linkerd_http_h2 as h2;
 use linkerd_http_route::http;
 use std::{ops::RangeInclusive, sync::Arc, time};
 
@@ -15,9 +17,9 @@ pub struct RouteParams {
     pub retry: Option<Retry>,
     pub allow_l5d_request_headers: bool,
     pub export_hostname_labels: bool,
+    pub upgrade_timeouts: UpgradeTimeouts,
 }
 
-// TODO: keepalive settings, etc.
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Http1 {
     pub routes: Arc<[Route]>,
@@ -25,9 +27,12 @@ pub struct Http1 {
     /// Configures how endpoints accrue observed failures. `None` disables
     /// failure accrual.
     pub failure_accrual: Option<FailureAccrual>,
+
+    /// Limits the proxy's HTTP/1 connection pools to this parent's
+    /// backends.
+    pub pool_limits: h1::PoolLimits,
 }
 
-// TODO: window sizes, etc
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Http2 {
     pub routes: Arc<[Route]>,
@@ -35,6 +40,10 @@ pub struct Http2 {
     /// Configures how endpoints accrue observed failures. `None` disables
     /// failure accrual.
     pub failure_accrual: Option<FailureAccrual>,
+
+    /// Overrides the proxy's HTTP/2 client settings on connections to this
+    /// parent's backends.
+    pub client_params: Arc<h2::ClientParams>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
@@ -43,6 +52,7 @@ pub enum Filter {
     Redirect(filter::RedirectRequest),
     RequestHeaders(filter::ModifyHeader),
     ResponseHeaders(filter::ModifyHeader),
+    Compression(Arc<filter::Compression>),
     InternalError(&'static str),
 }
 
@@ -65,6 +75,17 @@ pub struct Timeouts {
     pub request: Option<time::Duration>,
 }
 
+/// Limits connections that are upgraded by a route's requests, e.g. WebSockets
+/// and CONNECT tunnels.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct UpgradeTimeouts {
+    /// Closes the connection after this long without any data transferred.
+    pub idle: Option<time::Duration>,
+
+    /// Closes the connection after it has been open for this long.
+    pub lifetime: Option<time::Duration>,
+}
+
 pub fn default(distribution: crate::RouteDistribution<Filter>) -> Route {
     Route {
         hosts: vec![],
@@ -87,6 +108,7 @@ impl Default for Http1 {
         Self {
             routes: Arc::new([]),
             failure_accrual: None,
+            pool_limits: Default::default(),
         }
     }
 }
@@ -98,6 +120,7 @@ impl Default for Http2 {
         Self {
             routes: Arc::new([]),
             failure_accrual: None,
+            client_params: Default::default(),
         }
     }
 }
@@ -221,7 +244,7 @@ pub mod proto {
 
     impl Http1 {
         pub fn try_from(
-            overrides: ClientPolicyOverrides,
+            overrides: &ClientPolicyOverrides,
             proto: outbound::proxy_protocol::Http1,
         ) -> Result<Self, InvalidHttpRoute> {
             let routes = proto
@@ -235,13 +258,17 @@ pub mod proto {
                     .failure_accrual
                     .map(FailureAccrual::try_from)
                     .transpose()?,
+                // The policy API does not yet carry HTTP/1 pool limits, so
+                // the proxy's own limits (i.e. those configured by
+                // `LINKERD2_PROXY_OUTBOUND_HTTP1_CONNECTION_POOL_*`) are used.
+                pool_limits: Default::default(),
             })
         }
     }
 
     impl Http2 {
         pub fn try_from(
-            overrides: ClientPolicyOverrides,
+            overrides: &ClientPolicyOverrides,
             proto: outbound::proxy_protocol::Http2,
         ) -> Result<Self, InvalidHttpRoute> {
             let routes = proto
@@ -255,12 +282,16 @@ pub mod proto {
                     .failure_accrual
                     .map(FailureAccrual::try_from)
                     .transpose()?,
+                // The policy API does not yet carry HTTP/2 client settings, so
+                // the proxy's own settings are used, overridden per-endpoint by
+                // the destination API's metadata.
+                client_params: Default::default(),
             })
         }
     }
 
     fn try_route(
-        overrides: ClientPolicyOverrides,
+        overrides: &ClientPolicyOverrides,
         proto: outbound::HttpRoute,
     ) -> Result<Route, InvalidHttpRoute> {
         let outbound::HttpRoute {
@@ -288,7 +319,7 @@ pub mod proto {
 
     fn try_rule(
         meta: &Arc<Meta>,
-        overrides: ClientPolicyOverrides,
+        overrides: &ClientPolicyOverrides,
         proto: outbound::http_route::Rule,
     ) -> Result<Rule, InvalidHttpRoute> {
         #[allow(deprecated)]
@@ -310,6 +341,12 @@ pub mod proto {
         let filters = filters
             .into_iter()
             .map(Filter::try_from)
+            .chain(
+                overrides
+                    .compression
+                    .clone()
+                    .map(|c| Ok(Filter::Compression(c))),
+            )
             .collect::<Result<Arc<[_]>, _>>()?;
 
         let distribution = backends
@@ -337,7 +374,7 @@ pub mod proto {
             timeouts: Option<linkerd2_proxy_api::http_route::Timeouts>,
             retry: Option<http_route::Retry>,
             allow_l5d_request_headers: bool,
-            overrides: ClientPolicyOverrides,
+            overrides: &ClientPolicyOverrides,
         ) -> Result<Self, InvalidHttpRoute> {
             Ok(Self {
                 retry: retry.map(Retry::try_from).transpose()?,
@@ -347,6 +384,7 @@ pub mod proto {
                     .unwrap_or_default(),
                 allow_l5d_request_headers,
                 export_hostname_labels: overrides.export_hostname_labels,
+                upgrade_timeouts: overrides.upgrade_timeouts.clone(),
             })
         }
     }
@@ -1,6 +1,7 @@
 #![deny(rust_2018_idioms, clippy::disallowed_methods, clippy::disallowed_types)]
 #![forbid(unsafe_code)]
 
+use linkerd_addr::NameAddr;
 use once_cell::sync::Lazy;
 use std::{borrow::Cow, fmt, hash::Hash, net::SocketAddr, num::NonZeroU16, sync::Arc, time};
 
@@ -19,9 +20,17 @@ pub struct ClientPolicy {
     pub backends: Arc<[Backend]>,
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct ClientPolicyOverrides {
     pub export_hostname_labels: bool,
+
+    /// Added to every HTTP route's filters, since the policy API does not
+    /// describe compression.
+    pub compression: Option<Arc<http::filter::Compression>>,
+
+    /// Limits connections upgraded by HTTP routes' requests, since the policy
+    /// API does not describe upgrades.
+    pub upgrade_timeouts: http::UpgradeTimeouts,
 }
 
 // TODO additional server configs (e.g. concurrency limits, window sizes, etc)
@@ -107,7 +116,35 @@ pub enum BackendDispatcher {
 
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub enum EndpointDiscovery {
-    DestinationGet { path: String },
+    DestinationGet {
+        path: String,
+    },
+    /// Resolves endpoints through DNS instead of the destination controller,
+    /// e.g. for dependencies outside of the mesh.
+    ///
+    /// Selected by a destination path with a `dns://` or `dns+srv://` scheme
+    /// (see `docs/PROXY_API.md`).
+    Dns(DnsDiscovery),
+}
+
+/// Configures DNS-based endpoint discovery for a balanced backend.
+///
+/// How long resolved endpoints are used before the name is resolved again is
+/// configured by the proxy, not by the policy.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct DnsDiscovery {
+    /// The name to resolve. The port is used for A/AAAA records and is
+    /// ignored for SRV records, whose names need not specify one.
+    pub authority: NameAddr,
+    pub records: DnsRecords,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum DnsRecords {
+    /// A and AAAA records. Endpoints use the authority's port.
+    Addrs,
+    /// SRV records. Endpoints use each record's port and weight.
+    Srv,
 }
 
 /// Configures the load balancing strategy for a backend.
@@ -291,10 +328,12 @@ impl ClientPolicy {
                 http1: http::Http1 {
                     routes: HTTP_ROUTES.clone(),
                     failure_accrual: None,
+                    pool_limits: Default::default(),
                 },
                 http2: http::Http2 {
                     routes: HTTP_ROUTES.clone(),
                     failure_accrual: None,
+                    client_params: Default::default(),
                 },
 
                 opaque: opaq::Opaque {
@@ -331,10 +370,12 @@ impl ClientPolicy {
                 http1: http::Http1 {
                     routes: NO_HTTP_ROUTES.clone(),
                     failure_accrual: None,
+                    pool_limits: Default::default(),
                 },
                 http2: http::Http2 {
                     routes: NO_HTTP_ROUTES.clone(),
                     failure_accrual: None,
+                    client_params: Default::default(),
                 },
                 opaque: opaq::Opaque { routes: None },
             },
@@ -589,6 +630,13 @@ pub mod proto {
     pub enum InvalidDiscovery {
         #[error("missing discovery kind")]
         Missing,
+
+        #[error("invalid DNS authority {authority:?}: {source}")]
+        DnsAuthority {
+            authority: String,
+            #[source]
+            source: linkerd_addr::Error,
+        },
     }
 
     #[derive(Debug, thiserror::Error)]
@@ -644,13 +692,13 @@ pub mod proto {
                         ))?
                         .try_into()?;
                     let http1 = http::Http1::try_from(
-                        overrides,
+                        &overrides,
                         http1.ok_or(InvalidPolicy::Protocol(
                             "Detect missing HTTP/1 configuration",
                         ))?,
                     )?;
                     let http2 = http::Http2::try_from(
-                        overrides,
+                        &overrides,
                         http2.ok_or(InvalidPolicy::Protocol(
                             "Detect missing HTTP/2 configuration",
                         ))?,
@@ -670,17 +718,17 @@ pub mod proto {
                 }
 
                 proxy_protocol::Kind::Http1(http) => {
-                    Protocol::Http1(http::Http1::try_from(overrides, http)?)
+                    Protocol::Http1(http::Http1::try_from(&overrides, http)?)
                 }
                 proxy_protocol::Kind::Http2(http) => {
-                    Protocol::Http2(http::Http2::try_from(overrides, http)?)
+                    Protocol::Http2(http::Http2::try_from(&overrides, http)?)
                 }
                 proxy_protocol::Kind::Opaque(opaque) => Protocol::Opaque(opaque.try_into()?),
                 proxy_protocol::Kind::Grpc(grpc) => {
-                    Protocol::Grpc(grpc::Grpc::try_from(overrides, grpc)?)
+                    Protocol::Grpc(grpc::Grpc::try_from(&overrides, grpc)?)
                 }
                 proxy_protocol::Kind::Tls(tls) => {
-                    Protocol::Tls(tls::Tls::try_from(overrides, tls)?)
+                    Protocol::Tls(tls::Tls::try_from(&overrides, tls)?)
                 }
             };
 
@@ -939,12 +987,45 @@ pub mod proto {
             use outbound::backend::endpoint_discovery;
             match proto.kind.ok_or(InvalidDiscovery::Missing)? {
                 endpoint_discovery::Kind::Dst(endpoint_discovery::DestinationGet { path }) => {
+                    // The API does not (yet) describe DNS discovery, so a
+                    // destination path with a DNS scheme selects it, as
+                    // documented in `docs/PROXY_API.md`.
+                    let dns = |authority: &str, records| {
+                        let parsed = match records {
+                            // SRV records name their endpoints' ports, so an
+                            // SRV name need not specify one.
+                            DnsRecords::Srv if !authority.contains(':') => {
+                                NameAddr::from_str_and_port(authority, 0)
+                            }
+                            _ => authority.parse::<NameAddr>(),
+                        };
+                        let authority =
+                            parsed.map_err(|source| InvalidDiscovery::DnsAuthority {
+                                authority: authority.to_string(),
+                                source,
+                            })?;
+                        Ok(EndpointDiscovery::Dns(DnsDiscovery { authority, records }))
+                    };
+                    if let Some(authority) = path.strip_prefix(DNS_SRV_SCHEME) {
+                        return dns(authority, DnsRecords::Srv);
+                    }
+                    if let Some(authority) = path.strip_prefix(DNS_SCHEME) {
+                        return dns(authority, DnsRecords::Addrs);
+                    }
                     Ok(EndpointDiscovery::DestinationGet { path })
                 }
             }
         }
     }
 
+    /// Destination paths with this prefix are discovered through A/AAAA
+    /// records, e.g. `dns://db.example.com:5432`.
+    const DNS_SCHEME: &str = "dns://";
+
+    /// Destination paths with this prefix are discovered through SRV records,
+    /// e.g. `dns+srv://_postgres._tcp.example.com`.
+    const DNS_SRV_SCHEME: &str = "dns+srv://";
+
     /// Lower bound on the success-rate window length. The breaker realizes the
     /// window as ten one-millisecond floored buckets at minimum, so a window
     /// below ten milliseconds cannot be honored at its configured value. Rejecting it
@@ -1320,3 +1401,76 @@ mod failure_accrual_proto_tests {
         }
     }
 }
+
+#[cfg(all(test, feature = "proto"))]
+mod discovery_proto_tests {
+    use super::proto::InvalidDiscovery;
+    use super::{DnsRecords, EndpointDiscovery};
+    use linkerd2_proxy_api::outbound::backend::{endpoint_discovery, EndpointDiscovery as Proto};
+
+    fn dst(path: &str) -> Proto {
+        Proto {
+            kind: Some(endpoint_discovery::Kind::Dst(
+                endpoint_discovery::DestinationGet {
+                    path: path.to_string(),
+                },
+            )),
+        }
+    }
+
+    #[test]
+    fn destination_paths() {
+        let discovery = EndpointDiscovery::try_from(dst("foo.ns.svc.cluster.local:8080")).unwrap();
+        assert_eq!(
+            discovery,
+            EndpointDiscovery::DestinationGet {
+                path: "foo.ns.svc.cluster.local:8080".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn dns_paths() {
+        match EndpointDiscovery::try_from(dst("dns://db.example.com:5432")).unwrap() {
+            EndpointDiscovery::Dns(dns) => {
+                assert_eq!(dns.authority, "db.example.com:5432".parse().unwrap());
+                assert_eq!(dns.records, DnsRecords::Addrs);
+            }
+            other => panic!("expected DNS discovery, got {other:?}"),
+        }
+
+        match EndpointDiscovery::try_from(dst("dns+srv://_pg._tcp.example.com:5432")).unwrap() {
+            EndpointDiscovery::Dns(dns) => {
+                assert_eq!(dns.authority, "_pg._tcp.example.com:5432".parse().unwrap());
+                assert_eq!(dns.records, DnsRecords::Srv);
+            }
+            other => panic!("expected DNS discovery, got {other:?}"),
+        }
+
+        match EndpointDiscovery::try_from(dst("dns+srv://_pg._tcp.example.com")).unwrap() {
+            EndpointDiscovery::Dns(dns) => {
+                assert_eq!(dns.authority.name().to_string(), "_pg._tcp.example.com");
+                assert_eq!(dns.records, DnsRecords::Srv);
+            }
+            other => panic!("expected DNS discovery, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_dns_authorities() {
+        for path in [
+            "dns://db.example.com",
+            "dns+srv://:5432",
+            "dns+srv://",
+            "dns://not a name:80",
+        ] {
+            assert!(
+                matches!(
+                    EndpointDiscovery::try_from(dst(path)),
+                    Err(InvalidDiscovery::DnsAuthority { .. })
+                ),
+                "{path} must be rejected"
+            );
+        }
+    }
+}
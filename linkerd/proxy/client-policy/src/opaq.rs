@@ -9,9 +9,6 @@ pub struct Opaque {
     pub routes: Option<Route>,
 }
 
-#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
-pub struct NonIoErrors;
-
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum Filter {
     Forbidden,
@@ -19,13 +16,6 @@ pub enum Filter {
     InternalError(&'static str),
 }
 
-impl NonIoErrors {
-    pub fn contains(&self, e: &(dyn std::error::Error + 'static)) -> bool {
-        // Naively assume that all non-I/O errors are failures.
-        !linkerd_error::is_caused_by::<std::io::Error>(e)
-    }
-}
-
 #[cfg(feature = "proto")]
 pub(crate) mod proto {
     use super::*;
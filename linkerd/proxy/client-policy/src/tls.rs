@@ -90,7 +90,7 @@ pub(crate) mod proto {
 
     impl Tls {
         pub fn try_from(
-            overrides: ClientPolicyOverrides,
+            overrides: &ClientPolicyOverrides,
             proto: outbound::proxy_protocol::Tls,
         ) -> Result<Self, InvalidTlsRoute> {
             let routes = proto
@@ -110,7 +110,7 @@ pub(crate) mod proto {
 
     fn try_route(
         proto: outbound::TlsRoute,
-        overrides: ClientPolicyOverrides,
+        overrides: &ClientPolicyOverrides,
     ) -> Result<Route, InvalidTlsRoute> {
         let outbound::TlsRoute {
             rules,
@@ -147,7 +147,7 @@ pub(crate) mod proto {
     fn try_rule(
         meta: &Arc<Meta>,
         tls_route::Rule { backends, filters }: tls_route::Rule,
-        overrides: ClientPolicyOverrides,
+        overrides: &ClientPolicyOverrides,
     ) -> Result<Policy, InvalidTlsRoute> {
         let distribution = backends
             .ok_or(InvalidTlsRoute::Missing("distribution"))?
@@ -168,10 +168,10 @@ pub(crate) mod proto {
 
     impl RouteParams {
         fn try_from_proto(
-            ClientPolicyOverrides {
+            &ClientPolicyOverrides {
                 export_hostname_labels,
                 ..
-            }: ClientPolicyOverrides,
+            }: &ClientPolicyOverrides,
         ) -> Result<Self, InvalidTlsRoute> {
             Ok(Self {
                 export_hostname_labels,
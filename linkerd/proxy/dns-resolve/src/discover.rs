@@ -0,0 +1,342 @@
+use futures::prelude::*;
+use linkerd_addr::NameAddr;
+use linkerd_dns as dns;
+use linkerd_error::Error;
+use linkerd_proxy_core::resolve::Update;
+use std::{
+    net::SocketAddr,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::{
+    sync::mpsc,
+    time::{self, Instant},
+};
+use tokio_stream::wrappers::ReceiverStream;
+use tracing::{debug, instrument::Instrument, trace};
+
+/// Discovers weighted endpoints for a balancer through DNS.
+///
+/// Unlike [`crate::DnsResolve`], records are refreshed within the configured
+/// TTL bounds and lookup failures do not end the resolution: the last known
+/// endpoints are retained until a lookup succeeds.
+#[derive(Clone, Debug)]
+pub struct DnsDiscover<L = dns::Resolver> {
+    lookup: L,
+    ttls: Ttls,
+}
+
+/// Bounds on how long a lookup's results are used before refreshing,
+/// regardless of the record TTLs.
+#[derive(Copy, Clone, Debug)]
+struct Ttls {
+    min: Duration,
+    max: Duration,
+}
+
+/// A name to discover through DNS.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Target {
+    pub addr: NameAddr,
+    pub records: Records,
+}
+
+/// The type of DNS records used to discover endpoints.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Records {
+    /// A and AAAA records. Each endpoint uses the target's port and a weight
+    /// of 1.
+    Addrs,
+    /// SRV records. Each endpoint uses its record's port and weight.
+    Srv,
+}
+
+/// Looks up the weighted endpoints for a [`Target`].
+pub trait Lookup: Clone + Send + Sync + 'static {
+    type Future: Future<Output = Result<(Vec<(SocketAddr, u32)>, Instant), Error>> + Send + 'static;
+
+    fn lookup(&self, addr: &NameAddr, records: Records) -> Self::Future;
+}
+
+/// A type-erased [`Lookup`].
+#[derive(Clone)]
+pub struct BoxLookup(Arc<LookupFn>);
+
+type LookupFn = dyn Fn(&NameAddr, Records) -> LookupFuture + Send + Sync;
+
+type LookupFuture =
+    Pin<Box<dyn Future<Output = Result<(Vec<(SocketAddr, u32)>, Instant), Error>> + Send>>;
+
+pub type DiscoverStream =
+    Pin<Box<dyn Stream<Item = Result<Update<u32>, Error>> + Send + Sync + 'static>>;
+
+// === impl DnsDiscover ===
+
+impl<L> DnsDiscover<L> {
+    /// Discovers endpoints with `lookup`, refreshing them no sooner than
+    /// `min_ttl` and no later than `max_ttl` after each lookup.
+    pub fn new(lookup: L, min_ttl: Duration, max_ttl: Duration) -> Self {
+        Self {
+            lookup,
+            ttls: Ttls {
+                min: min_ttl,
+                max: max_ttl.max(min_ttl),
+            },
+        }
+    }
+}
+
+impl<L: Lookup> tower::Service<Target> for DnsDiscover<L> {
+    type Response = DiscoverStream;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<DiscoverStream, Error>> + Send + 'static>>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, target: Target) -> Self::Future {
+        Box::pin(discover(self.lookup.clone(), self.ttls, target).in_current_span())
+    }
+}
+
+async fn discover<L: Lookup>(
+    lookup: L,
+    ttls: Ttls,
+    target: Target,
+) -> Result<DiscoverStream, Error> {
+    // Don't return a stream before the initial resolution completes. Then,
+    // spawn a task to drive the continued resolution.
+    let (mut endpoints, expiry) = lookup.lookup(&target.addr, target.records).await?;
+    debug!(?endpoints, name = %target.addr);
+    let (tx, rx) = mpsc::channel(1);
+    tokio::spawn(
+        async move {
+            if tx.send(Ok(Update::Reset(endpoints.clone()))).await.is_err() {
+                trace!("Closed");
+                return;
+            }
+            let mut refresh = ttls.refresh_at(expiry);
+
+            loop {
+                tokio::select! {
+                    _ = time::sleep_until(refresh) => {}
+                    _ = tx.closed() => {
+                        trace!("Closed");
+                        return;
+                    }
+                }
+
+                match lookup.lookup(&target.addr, target.records).await {
+                    Ok((eps, expiry)) => {
+                        refresh = ttls.refresh_at(expiry);
+                        if eps == endpoints {
+                            trace!(name = %target.addr, "Endpoints unchanged");
+                            continue;
+                        }
+                        debug!(endpoints = ?eps, name = %target.addr);
+                        endpoints = eps;
+                        if tx.send(Ok(Update::Reset(endpoints.clone()))).await.is_err() {
+                            trace!("Closed");
+                            return;
+                        }
+                    }
+                    Err(error) => {
+                        // Keep using the last known endpoints until the name
+                        // can be resolved again.
+                        debug!(%error, name = %target.addr, "DNS lookup failed");
+                        refresh = Instant::now() + ttls.min;
+                    }
+                }
+            }
+        }
+        .in_current_span(),
+    );
+
+    Ok(Box::pin(ReceiverStream::new(rx)))
+}
+
+// === impl Ttls ===
+
+impl Ttls {
+    /// Returns when a lookup that expires at `expiry` should be refreshed.
+    fn refresh_at(&self, expiry: Instant) -> Instant {
+        let now = Instant::now();
+        expiry.max(now + self.min).min(now + self.max)
+    }
+}
+
+// === impl Lookup ===
+
+impl Lookup for dns::Resolver {
+    type Future = LookupFuture;
+
+    fn lookup(&self, addr: &NameAddr, records: Records) -> Self::Future {
+        let dns = self.clone();
+        let addr = addr.clone();
+        Box::pin(async move {
+            match records {
+                Records::Addrs => {
+                    let (addrs, expiry) = dns
+                        .resolve_ip_addrs(addr.name().as_ref(), addr.port())
+                        .await?;
+                    Ok((addrs.into_iter().map(|a| (a, 1)).collect(), expiry))
+                }
+                Records::Srv => {
+                    let (addrs, expiry) = dns.resolve_srv_weighted(addr.name().as_ref()).await?;
+                    let addrs = addrs.into_iter().map(|(a, w)| (a, w.into())).collect();
+                    Ok((addrs, expiry))
+                }
+            }
+        })
+    }
+}
+
+// === impl BoxLookup ===
+
+impl BoxLookup {
+    pub fn new<L: Lookup>(lookup: L) -> Self {
+        Self(Arc::new(move |addr, records| {
+            Box::pin(lookup.lookup(addr, records))
+        }))
+    }
+}
+
+impl Lookup for BoxLookup {
+    type Future = LookupFuture;
+
+    fn lookup(&self, addr: &NameAddr, records: Records) -> Self::Future {
+        (self.0)(addr, records)
+    }
+}
+
+impl std::fmt::Debug for BoxLookup {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("BoxLookup").finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use tower::ServiceExt;
+
+    /// Endpoints and their TTL, or a lookup error.
+    type MockResult = Result<(Vec<(SocketAddr, u32)>, Duration), Error>;
+
+    /// A lookup that returns queued results, failing once they're exhausted.
+    #[derive(Clone, Default)]
+    struct MockLookup {
+        results: Arc<parking_lot::Mutex<Vec<MockResult>>>,
+    }
+
+    impl MockLookup {
+        fn push(&self, result: MockResult) {
+            self.results.lock().push(result);
+        }
+    }
+
+    impl Lookup for MockLookup {
+        type Future = future::Ready<Result<(Vec<(SocketAddr, u32)>, Instant), Error>>;
+
+        fn lookup(&self, _: &NameAddr, _: Records) -> Self::Future {
+            let mut results = self.results.lock();
+            let res = if results.is_empty() {
+                Err("no more results".into())
+            } else {
+                results
+                    .remove(0)
+                    .map(|(eps, ttl)| (eps, Instant::now() + ttl))
+            };
+            future::ready(res)
+        }
+    }
+
+    fn target() -> Target {
+        Target {
+            addr: "example.com:8080".parse().unwrap(),
+            records: Records::Srv,
+        }
+    }
+
+    fn discover(lookup: MockLookup) -> DnsDiscover<MockLookup> {
+        DnsDiscover::new(lookup, Duration::from_secs(5), Duration::from_secs(60))
+    }
+
+    fn eps(ports: &[u16]) -> Vec<(SocketAddr, u32)> {
+        ports
+            .iter()
+            .map(|p| (SocketAddr::from(([192, 0, 2, 1], *p)), u32::from(*p)))
+            .collect()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn refreshes_within_ttl_bounds() {
+        let lookup = MockLookup::default();
+        // The first TTL is below the minimum; the second exceeds the maximum.
+        lookup.push(Ok((eps(&[1]), Duration::from_secs(1))));
+        lookup.push(Ok((eps(&[2]), Duration::from_secs(3600))));
+        lookup.push(Ok((eps(&[3]), Duration::from_secs(30))));
+
+        let start = Instant::now();
+        let mut updates = discover(lookup).oneshot(target()).await.unwrap();
+
+        assert_eq!(
+            updates.next().await.unwrap().unwrap(),
+            Update::Reset(eps(&[1]))
+        );
+        assert_eq!(
+            updates.next().await.unwrap().unwrap(),
+            Update::Reset(eps(&[2]))
+        );
+        assert_eq!(
+            Instant::now().saturating_duration_since(start),
+            Duration::from_secs(5)
+        );
+        assert_eq!(
+            updates.next().await.unwrap().unwrap(),
+            Update::Reset(eps(&[3]))
+        );
+        assert_eq!(
+            Instant::now().saturating_duration_since(start),
+            Duration::from_secs(65)
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn retains_endpoints_on_lookup_failure() {
+        let lookup = MockLookup::default();
+        lookup.push(Ok((eps(&[1]), Duration::from_secs(10))));
+        lookup.push(Err("SERVFAIL".into()));
+        lookup.push(Ok((eps(&[1]), Duration::from_secs(10))));
+        lookup.push(Ok((eps(&[2]), Duration::from_secs(10))));
+
+        let start = Instant::now();
+        let mut updates = discover(lookup).oneshot(target()).await.unwrap();
+
+        assert_eq!(
+            updates.next().await.unwrap().unwrap(),
+            Update::Reset(eps(&[1]))
+        );
+        // The failed lookup is retried after the minimum TTL and unchanged
+        // results are not published.
+        assert_eq!(
+            updates.next().await.unwrap().unwrap(),
+            Update::Reset(eps(&[2]))
+        );
+        assert_eq!(
+            Instant::now().saturating_duration_since(start),
+            Duration::from_secs(25)
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn fails_initial_lookup() {
+        let lookup = MockLookup::default();
+        lookup.push(Err("NXDOMAIN".into()));
+        assert!(discover(lookup).oneshot(target()).await.is_err());
+    }
+}
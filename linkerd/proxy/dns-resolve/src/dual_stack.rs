@@ -0,0 +1,150 @@
+use futures::prelude::*;
+use linkerd_proxy_core::resolve::Update;
+use std::net::SocketAddr;
+
+/// A resolved endpoint and, if it was paired with an endpoint of the other
+/// address family, that endpoint's address.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DualStack<T> {
+    pub endpoint: T,
+    pub other_family: Option<SocketAddr>,
+}
+
+/// Pairs resolved IPv6 and IPv4 endpoints that share a port into a single
+/// endpoint, so that a dual-stack destination can be connected to by racing
+/// both addresses rather than being balanced over as two endpoints.
+///
+/// Each paired endpoint is keyed by its IPv6 address. Because a removed IPv4
+/// address may be part of a pair, the resolution's endpoints are tracked and
+/// every update is published as a reset of the re-paired endpoints.
+pub fn pair_updates<T, E, S>(
+    updates: S,
+) -> impl Stream<Item = Result<Update<DualStack<T>>, E>> + Send + 'static
+where
+    T: Clone + Send + 'static,
+    E: Send + 'static,
+    S: Stream<Item = Result<Update<T>, E>> + Send + 'static,
+{
+    updates.scan(Vec::<(SocketAddr, T)>::new(), |endpoints, update| {
+        let update = update.map(|update| match update {
+            Update::Reset(eps) => {
+                *endpoints = eps;
+                Update::Reset(pair_families(endpoints.clone()))
+            }
+            Update::Add(eps) => {
+                for (addr, ep) in eps {
+                    match endpoints.iter_mut().find(|(a, _)| *a == addr) {
+                        Some((_, existing)) => *existing = ep,
+                        None => endpoints.push((addr, ep)),
+                    }
+                }
+                Update::Reset(pair_families(endpoints.clone()))
+            }
+            Update::Remove(addrs) => {
+                endpoints.retain(|(a, _)| !addrs.contains(a));
+                Update::Reset(pair_families(endpoints.clone()))
+            }
+            Update::DoesNotExist => {
+                endpoints.clear();
+                Update::DoesNotExist
+            }
+        });
+        future::ready(Some(update))
+    })
+}
+
+/// Pairs each IPv6 endpoint with an IPv4 endpoint on the same port, if there
+/// is one. Endpoints that can't be paired are returned on their own.
+pub fn pair_families<T>(eps: Vec<(SocketAddr, T)>) -> Vec<(SocketAddr, DualStack<T>)> {
+    let (ipv6, mut ipv4): (Vec<_>, Vec<_>) = eps.into_iter().partition(|(a, _)| a.is_ipv6());
+    let mut paired = Vec::with_capacity(ipv6.len().max(ipv4.len()));
+    for (addr, endpoint) in ipv6 {
+        let other_family = ipv4
+            .iter()
+            .position(|(a, _)| a.port() == addr.port())
+            .map(|i| ipv4.remove(i).0);
+        paired.push((
+            addr,
+            DualStack {
+                endpoint,
+                other_family,
+            },
+        ));
+    }
+    paired.extend(ipv4.into_iter().map(|(addr, endpoint)| {
+        (
+            addr,
+            DualStack {
+                endpoint,
+                other_family: None,
+            },
+        )
+    }));
+    paired
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use linkerd_error::Error;
+
+    fn addr(s: &str) -> SocketAddr {
+        s.parse().unwrap()
+    }
+
+    fn paired(eps: &[(&str, Option<&str>)]) -> Vec<(SocketAddr, DualStack<()>)> {
+        eps.iter()
+            .map(|(a, b)| {
+                (
+                    addr(a),
+                    DualStack {
+                        endpoint: (),
+                        other_family: b.map(addr),
+                    },
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn pairs_families_by_port() {
+        let eps = ["192.0.2.1:80", "[2001:db8::1]:80", "192.0.2.2:81"]
+            .into_iter()
+            .map(|a| (addr(a), ()))
+            .collect();
+        assert_eq!(
+            pair_families(eps),
+            paired(&[
+                ("[2001:db8::1]:80", Some("192.0.2.1:80")),
+                ("192.0.2.2:81", None),
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn removes_pairs_by_either_family() {
+        let v4 = addr("192.0.2.1:80");
+        let v6 = addr("[2001:db8::1]:80");
+        let updates = stream::iter(vec![
+            Ok::<_, Error>(Update::Reset(vec![(v4, ()), (v6, ())])),
+            Ok(Update::Remove(vec![v4])),
+            Ok(Update::Add(vec![(v4, ())])),
+            Ok(Update::Remove(vec![v6])),
+        ]);
+        let updates = pair_updates(updates)
+            .map(Result::unwrap)
+            .collect::<Vec<_>>()
+            .await;
+
+        let pair = paired(&[("[2001:db8::1]:80", Some("192.0.2.1:80"))]);
+        assert_eq!(
+            updates,
+            vec![
+                Update::Reset(pair.clone()),
+                Update::Reset(paired(&[("[2001:db8::1]:80", None)])),
+                Update::Reset(pair),
+                Update::Reset(paired(&[("192.0.2.1:80", None)])),
+            ]
+        );
+    }
+}
@@ -16,6 +16,14 @@ use tokio::sync::mpsc;
 use tracing::instrument::Instrument;
 use tracing::{debug, trace};
 
+mod discover;
+mod dual_stack;
+
+pub use self::{
+    discover::{BoxLookup, DiscoverStream, DnsDiscover, Lookup, Records, Target},
+    dual_stack::{pair_families, pair_updates, DualStack},
+};
+
 /// A Resolver that attempts to lookup targets via DNS.
 ///
 /// SRV records are checked first, A records are used as a fallback.
@@ -28,6 +28,7 @@ pub enum Params {
 pub struct MakeClient<X, C, B> {
     connect: C,
     params: X,
+    h1_metrics: h1::PoolMetrics,
     _marker: PhantomData<fn(B)>,
 }
 
@@ -43,6 +44,7 @@ pub fn layer_via<X: Clone, C, B>(
     layer::mk(move |connect: C| MakeClient {
         connect,
         params: params.clone(),
+        h1_metrics: Default::default(),
         _marker: PhantomData,
     })
 }
@@ -51,6 +53,19 @@ pub fn layer<C, B>() -> impl layer::Layer<C, Service = MakeClient<(), C, B>> + C
     layer_via(())
 }
 
+/// Like [`layer`], but records how HTTP/1 connection pool limits are enforced
+/// to `h1_metrics`.
+pub fn layer_with_metrics<C, B>(
+    h1_metrics: h1::PoolMetrics,
+) -> impl layer::Layer<C, Service = MakeClient<(), C, B>> + Clone {
+    layer::mk(move |connect: C| MakeClient {
+        connect,
+        params: (),
+        h1_metrics: h1_metrics.clone(),
+        _marker: PhantomData,
+    })
+}
+
 // === impl MakeClient ===
 
 type MakeFuture<C, T, B> = Pin<Box<dyn Future<Output = Result<Client<C, T, B>>> + Send + 'static>>;
@@ -79,6 +94,7 @@ where
     fn call(&mut self, target: T) -> Self::Future {
         let connect = self.connect.clone();
         let settings = self.params.extract_param(&target);
+        let h1_metrics = self.h1_metrics.clone();
 
         Box::pin(async move {
             debug!(?settings, "Building HTTP client");
@@ -87,13 +103,16 @@ where
                     let h2 = h2::Connect::new(connect, params).oneshot(target).await?;
                     Client::H2(h2)
                 }
-                Params::Http1(params) => Client::Http1(h1::Client::new(connect, target, params)),
+                Params::Http1(params) => {
+                    Client::Http1(h1::Client::new(connect, target, params, h1_metrics))
+                }
                 Params::OrigProtoUpgrade(h2params, h1params) => {
+                    let extended_connect = h2params.extended_connect.unwrap_or(false);
                     let h2 = h2::Connect::new(connect.clone(), h2params)
                         .oneshot(target.clone())
                         .await?;
-                    let http1 = h1::Client::new(connect, target, h1params);
-                    Client::OrigProtoUpgrade(orig_proto::Upgrade::new(http1, h2))
+                    let http1 = h1::Client::new(connect, target, h1params, h1_metrics);
+                    Client::OrigProtoUpgrade(orig_proto::Upgrade::new(http1, h2, extended_connect))
                 }
             };
 
@@ -107,6 +126,7 @@ impl<X: Clone, C: Clone, B> Clone for MakeClient<X, C, B> {
         Self {
             connect: self.connect.clone(),
             params: self.params.clone(),
+            h1_metrics: self.h1_metrics.clone(),
             _marker: self._marker,
         }
     }
@@ -4,13 +4,34 @@ use http::{
     header::{CONTENT_LENGTH, TRANSFER_ENCODING},
     uri::Uri,
 };
+use http_body::Frame;
+use hyper_util::client::legacy::connect::{capture_connection, CaptureConnection};
 use linkerd_error::{Error, Result};
 use linkerd_http_box::BoxBody;
-use linkerd_http_upgrade::{glue::HyperConnect, upgrade::Http11Upgrade};
+use linkerd_http_upgrade::{
+    glue::{ConnectionInfo, HyperConnect},
+    upgrade::Http11Upgrade,
+};
+use linkerd_metrics::prom;
 use linkerd_stack::MakeConnection;
-use std::{pin::Pin, time::Duration};
+use pin_project::pin_project;
+use std::{
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
 use tracing::{debug, trace};
 
+pub use linkerd_http_h1::{MaxLifetime, PoolLimits};
+
+#[cfg(test)]
+mod tests;
+
 #[derive(Copy, Clone, Debug)]
 pub struct WasAbsoluteForm(pub(crate) ());
 
@@ -18,8 +39,22 @@ pub struct WasAbsoluteForm(pub(crate) ());
 pub struct PoolSettings {
     pub max_idle: usize,
     pub idle_timeout: Duration,
+    pub limits: PoolLimits,
 }
 
+/// Metrics describing how HTTP/1 connection pool limits are enforced.
+#[derive(Clone, Debug, Default)]
+pub struct PoolMetrics {
+    pending: prom::Gauge,
+    rejected: prom::Counter,
+    retired_max_requests: prom::Counter,
+    retired_max_lifetime: prom::Counter,
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("too many requests are waiting for an HTTP/1 connection")]
+pub struct PoolExhausted(());
+
 /// Communicates with HTTP/1.x servers.
 ///
 /// The client handles both absolute-form and origin-form requests by lazily
@@ -33,15 +68,40 @@ pub struct Client<C, T, B> {
     absolute_form: Option<hyper_util::client::legacy::Client<HyperConnect<C, T>, B>>,
     origin_form: Option<hyper_util::client::legacy::Client<HyperConnect<C, T>, B>>,
     pool: PoolSettings,
+    limiter: Limiter,
+}
+
+/// Enforces a pool's [`PoolLimits`]. Shared by all clones of a [`Client`].
+#[derive(Clone, Debug)]
+struct Limiter {
+    limits: PoolLimits,
+    connections: Option<Arc<Semaphore>>,
+    pending: Arc<AtomicUsize>,
+    metrics: PoolMetrics,
+}
+
+/// Counts a request waiting for a connection until it is dropped.
+struct Pending<'a> {
+    limiter: &'a Limiter,
+    count: usize,
+}
+
+/// Holds a connection permit until the response body completes.
+#[pin_project]
+struct PermitBody<B> {
+    #[pin]
+    inner: B,
+    permit: Option<OwnedSemaphorePermit>,
 }
 
 impl<C, T, B> Client<C, T, B> {
-    pub fn new(connect: C, target: T, pool: PoolSettings) -> Self {
+    pub fn new(connect: C, target: T, pool: PoolSettings, metrics: PoolMetrics) -> Self {
         Self {
             connect,
             target,
             absolute_form: None,
             origin_form: None,
+            limiter: Limiter::new(pool.limits, metrics),
             pool,
         }
     }
@@ -55,6 +115,7 @@ impl<C: Clone, T: Clone, B> Clone for Client<C, T, B> {
             absolute_form: self.absolute_form.clone(),
             origin_form: self.origin_form.clone(),
             pool: self.pool,
+            limiter: self.limiter.clone(),
         }
     }
 }
@@ -94,12 +155,12 @@ where
             .map(|v| v.is_empty())
             .unwrap_or(true);
 
-        let rsp_fut = if req.version() == http::Version::HTTP_10 || is_missing_host {
+        let (rsp_fut, captured) = if req.version() == http::Version::HTTP_10 || is_missing_host {
             // If there's no authority, we assume we're on some weird HTTP/1.0
             // ish, so we just build a one-off client for the connection.
             // There's no real reason to hold the client for re-use.
             debug!(use_absolute_form, is_missing_host, "Using one-off client");
-            hyper_util::client::legacy::Client::builder(TokioExecutor::new())
+            let rsp_fut = hyper_util::client::legacy::Client::builder(TokioExecutor::new())
                 .pool_max_idle_per_host(0)
                 .set_host(use_absolute_form)
                 .build(HyperConnect::new(
@@ -107,7 +168,8 @@ where
                     self.target.clone(),
                     use_absolute_form,
                 ))
-                .request(req)
+                .request(req);
+            (rsp_fut, None)
         } else {
             // Otherwise, use a cached client to take advantage of the
             // connection pool. The client needs to be configured for absolute
@@ -137,11 +199,23 @@ where
                 );
             }
 
-            client.as_ref().unwrap().request(req)
+            // Capture the connection's metadata so that it may be retired once
+            // it reaches its request or lifetime limit.
+            let captured = capture_connection(&mut req);
+            (client.as_ref().unwrap().request(req), Some(captured))
         };
 
+        let limiter = self.limiter.clone();
         Box::pin(async move {
+            // Wait for a connection to become available if the pool is full.
+            // The request's connection is not checked out until the response
+            // future is polled.
+            let permit = limiter.acquire().await?;
             let mut rsp = rsp_fut.await?;
+            if let Some(captured) = captured {
+                limiter.record_response(&captured);
+            }
+
             if is_http_connect {
                 // Strip headers that may not be transmitted to the server, per RFC 9110:
                 //
@@ -166,11 +240,197 @@ where
                 linkerd_http_upgrade::strip_connection_headers(rsp.headers_mut());
             }
 
-            Ok(rsp.map(BoxBody::new))
+            Ok(match permit {
+                Some(permit) => rsp.map(|inner| {
+                    BoxBody::new(PermitBody {
+                        inner,
+                        permit: Some(permit),
+                    })
+                }),
+                None => rsp.map(BoxBody::new),
+            })
         })
     }
 }
 
+// === impl Limiter ===
+
+impl Limiter {
+    fn new(limits: PoolLimits, metrics: PoolMetrics) -> Self {
+        Self {
+            connections: limits
+                .max_connections
+                .map(|max| Arc::new(Semaphore::new(max))),
+            pending: Default::default(),
+            limits,
+            metrics,
+        }
+    }
+
+    /// Waits for a connection permit if the pool limits the number of
+    /// connections in use.
+    async fn acquire(&self) -> Result<Option<OwnedSemaphorePermit>> {
+        let Some(connections) = self.connections.clone() else {
+            return Ok(None);
+        };
+        if let Ok(permit) = connections.clone().try_acquire_owned() {
+            return Ok(Some(permit));
+        }
+
+        let pending = Pending::new(self);
+        if self
+            .limits
+            .max_pending
+            .is_some_and(|max| pending.count > max)
+        {
+            self.metrics.rejected.inc();
+            return Err(PoolExhausted(()).into());
+        }
+
+        trace!(pending = pending.count, "Waiting for a connection");
+        let permit = connections.acquire_owned().await;
+        Ok(Some(
+            permit.expect("connection semaphore must not be closed"),
+        ))
+    }
+
+    /// Retires the connection on which a response was received if it has
+    /// reached its request or lifetime limit. Retired connections are closed
+    /// instead of being returned to the pool when the response completes.
+    fn record_response(&self, captured: &CaptureConnection) {
+        let connected = captured.connection_metadata();
+        let Some(connected) = connected.as_ref() else {
+            return;
+        };
+        let mut extras = http::Extensions::new();
+        connected.get_extras(&mut extras);
+        let Some(info) = extras.get::<ConnectionInfo>() else {
+            return;
+        };
+
+        let responses = info.record_response();
+        if self
+            .limits
+            .max_requests_per_connection
+            .is_some_and(|max| responses >= max)
+        {
+            debug!(responses, "Retiring connection after its last request");
+            self.metrics.retired_max_requests.inc();
+            connected.poison();
+            return;
+        }
+
+        if let Some(MaxLifetime { lifetime, jitter }) = self.limits.max_lifetime {
+            let lifetime = lifetime + jitter.mul_f64(info.jitter());
+            let age = tokio::time::Instant::now().saturating_duration_since(info.established());
+            if age >= lifetime {
+                debug!(?lifetime, "Retiring connection that exceeded its lifetime");
+                self.metrics.retired_max_lifetime.inc();
+                connected.poison();
+            }
+        }
+    }
+}
+
+// === impl Pending ===
+
+impl<'a> Pending<'a> {
+    fn new(limiter: &'a Limiter) -> Self {
+        let count = limiter.pending.fetch_add(1, Ordering::AcqRel) + 1;
+        limiter.metrics.pending.inc();
+        Self { limiter, count }
+    }
+}
+
+impl Drop for Pending<'_> {
+    fn drop(&mut self) {
+        self.limiter.pending.fetch_sub(1, Ordering::AcqRel);
+        self.limiter.metrics.pending.dec();
+    }
+}
+
+// === impl PoolMetrics ===
+
+impl PoolMetrics {
+    pub fn register(registry: &mut prom::Registry) -> Self {
+        let pending = prom::Gauge::default();
+        registry.register(
+            "pending_requests",
+            "The number of HTTP/1 requests waiting for a connection from a full pool",
+            pending.clone(),
+        );
+
+        let rejected = prom::Counter::default();
+        registry.register(
+            "rejected_requests",
+            "HTTP/1 requests failed because too many requests were waiting for a connection",
+            rejected.clone(),
+        );
+
+        #[derive(Clone, Debug, PartialEq, Eq, Hash, prom::encoding::EncodeLabelSet)]
+        struct RetiredLabelSet {
+            reason: RetiredReason,
+        }
+        #[derive(Clone, Debug, PartialEq, Eq, Hash, prom::encoding::EncodeLabelValue)]
+        #[allow(non_camel_case_types)]
+        enum RetiredReason {
+            max_requests,
+            max_lifetime,
+        }
+        let retired = prom::Family::<_, prom::Counter>::default();
+        registry.register(
+            "retired_connections",
+            "HTTP/1 connections closed because they reached their request or lifetime limit",
+            retired.clone(),
+        );
+        let retired_max_requests = retired
+            .get_or_create(&RetiredLabelSet {
+                reason: RetiredReason::max_requests,
+            })
+            .clone();
+        let retired_max_lifetime = retired
+            .get_or_create(&RetiredLabelSet {
+                reason: RetiredReason::max_lifetime,
+            })
+            .clone();
+
+        Self {
+            pending,
+            rejected,
+            retired_max_requests,
+            retired_max_lifetime,
+        }
+    }
+}
+
+// === impl PermitBody ===
+
+impl<B: crate::Body> crate::Body for PermitBody<B> {
+    type Data = B::Data;
+    type Error = B::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.project();
+        let frame = futures::ready!(this.inner.poll_frame(cx));
+        if !matches!(frame, Some(Ok(_))) {
+            // The connection is free once the response completes.
+            drop(this.permit.take());
+        }
+        Poll::Ready(frame)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> http_body::SizeHint {
+        self.inner.size_hint()
+    }
+}
+
 /// Checks responses to determine if they are successful HTTP upgrades.
 fn is_upgrade<B>(rsp: &http::Response<B>, is_http_connect: bool) -> bool {
     use http::Version;
@@ -0,0 +1,197 @@
+use super::*;
+use bytes::Bytes;
+use http_body_util::{BodyExt, Full};
+use linkerd_io as io;
+use std::convert::Infallible;
+use tokio::time;
+
+#[tokio::test(flavor = "current_thread")]
+async fn unlimited_pools_do_not_hold_permits() {
+    let limiter = Limiter::new(PoolLimits::default(), PoolMetrics::default());
+    assert!(limiter.acquire().await.unwrap().is_none());
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn permit_released_when_body_completes() {
+    let limiter = Limiter::new(max_connections(1), PoolMetrics::default());
+    let permit = limiter.acquire().await.unwrap().expect("pool is limited");
+    assert!(
+        limiter.acquire().now_or_never().is_none(),
+        "pool must be full"
+    );
+
+    let mut body = PermitBody {
+        inner: Full::new(Bytes::from_static(b"hello")),
+        permit: Some(permit),
+    };
+    assert!(body.frame().await.is_some());
+    assert!(
+        limiter.acquire().now_or_never().is_none(),
+        "permit must be held while the body is read"
+    );
+    assert!(body.frame().await.is_none());
+    assert!(
+        limiter.acquire().now_or_never().is_some(),
+        "permit must be released when the body completes"
+    );
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn permit_released_when_body_dropped() {
+    let limiter = Limiter::new(max_connections(1), PoolMetrics::default());
+    let permit = limiter.acquire().await.unwrap().expect("pool is limited");
+
+    let body = PermitBody {
+        inner: Full::new(Bytes::from_static(b"hello")),
+        permit: Some(permit),
+    };
+    assert!(
+        limiter.acquire().now_or_never().is_none(),
+        "pool must be full"
+    );
+    drop(body);
+    assert!(
+        limiter.acquire().now_or_never().is_some(),
+        "permit must be released when the body is dropped"
+    );
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn waits_for_connections() {
+    let metrics = PoolMetrics::default();
+    let limiter = Limiter::new(max_connections(1), metrics.clone());
+    let permit = limiter.acquire().await.unwrap();
+
+    let mut waiting = std::pin::pin!(limiter.acquire());
+    assert!(waiting.as_mut().now_or_never().is_none());
+    assert_eq!(metrics.pending.get(), 1);
+
+    drop(permit);
+    let permit = waiting.await.unwrap();
+    assert!(permit.is_some());
+    assert_eq!(metrics.pending.get(), 0);
+    assert_eq!(metrics.rejected.get(), 0);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn rejects_requests_beyond_max_pending() {
+    let metrics = PoolMetrics::default();
+    let limits = PoolLimits {
+        max_pending: Some(1),
+        ..max_connections(1)
+    };
+    let limiter = Limiter::new(limits, metrics.clone());
+    let _permit = limiter.acquire().await.unwrap();
+
+    let mut waiting = std::pin::pin!(limiter.acquire());
+    assert!(waiting.as_mut().now_or_never().is_none());
+
+    let err = limiter
+        .acquire()
+        .await
+        .expect_err("request beyond max_pending must fail");
+    assert!(err.is::<PoolExhausted>());
+    assert_eq!(metrics.pending.get(), 1);
+    assert_eq!(metrics.rejected.get(), 1);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn reuses_idle_connections() {
+    let connects = send_two(settings(PoolLimits::default()), PoolMetrics::default()).await;
+    assert_eq!(connects, 1, "the idle connection must be reused");
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn closes_connections_beyond_max_idle() {
+    let pool = PoolSettings {
+        max_idle: 0,
+        ..settings(PoolLimits::default())
+    };
+    let connects = send_two(pool, PoolMetrics::default()).await;
+    assert_eq!(connects, 2, "no connections may be kept idle");
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn retires_connections_after_max_requests() {
+    let metrics = PoolMetrics::default();
+    let limits = PoolLimits {
+        max_requests_per_connection: Some(1),
+        ..Default::default()
+    };
+    let connects = send_two(settings(limits), metrics.clone()).await;
+    assert_eq!(connects, 2, "each connection may serve one request");
+    assert_eq!(metrics.retired_max_requests.get(), 2);
+    assert_eq!(metrics.retired_max_lifetime.get(), 0);
+}
+
+// === Utilities ===
+
+fn max_connections(max: usize) -> PoolLimits {
+    PoolLimits {
+        max_connections: Some(max),
+        ..Default::default()
+    }
+}
+
+fn settings(limits: PoolLimits) -> PoolSettings {
+    PoolSettings {
+        max_idle: 10,
+        idle_timeout: Duration::from_secs(60),
+        limits,
+    }
+}
+
+/// Sends two requests in sequence through a client and returns the number of
+/// connections it established.
+async fn send_two(pool: PoolSettings, metrics: PoolMetrics) -> usize {
+    let connect = Connect::default();
+    let mut client = Client::<_, (), BoxBody>::new(connect.clone(), (), pool, metrics);
+    for _ in 0..2 {
+        let req = http::Request::get("http://example.test/")
+            .header(http::header::HOST, "example.test")
+            .body(BoxBody::empty())
+            .unwrap();
+        let rsp = time::timeout(
+            Duration::from_secs(5),
+            tower::Service::call(&mut client, req),
+        )
+        .await
+        .expect("request timed out")
+        .expect("request must succeed");
+        assert_eq!(rsp.status(), http::StatusCode::OK);
+        rsp.into_body().collect().await.expect("body must succeed");
+        // Let the connection return to the pool.
+        time::sleep(Duration::from_millis(10)).await;
+    }
+    connect.count.load(Ordering::Acquire)
+}
+
+/// Connects to an in-memory HTTP/1 server, counting connections.
+#[derive(Clone, Default)]
+struct Connect {
+    count: Arc<AtomicUsize>,
+}
+
+impl tower::Service<(crate::Variant, ())> for Connect {
+    type Response = (io::DuplexStream, ());
+    type Error = Infallible;
+    type Future = future::Ready<Result<(io::DuplexStream, ()), Infallible>>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, _: (crate::Variant, ())) -> Self::Future {
+        self.count.fetch_add(1, Ordering::AcqRel);
+        let (client, server) = io::duplex(64 * 1024);
+        tokio::spawn(hyper::server::conn::http1::Builder::new().serve_connection(
+            hyper_util::rt::TokioIo::new(server),
+            hyper::service::service_fn(|_| {
+                future::ok::<_, Infallible>(http::Response::new(Full::new(Bytes::from_static(
+                    b"ok",
+                ))))
+            }),
+        ));
+        future::ok((client, ()))
+    }
+}
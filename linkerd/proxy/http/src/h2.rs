@@ -1,10 +1,15 @@
 use crate::{Body, TokioExecutor};
 use futures::prelude::*;
 use linkerd_error::{Error, Result};
+use linkerd_http_upgrade::upgrade::Http11Upgrade;
 use linkerd_stack::{MakeConnection, Service};
 use std::{
     marker::PhantomData,
     pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
     task::{Context, Poll},
 };
 use tracing::instrument::Instrument;
@@ -13,6 +18,9 @@ use tracing::{debug, debug_span, trace_span};
 pub use h2::{Error as H2Error, Reason};
 pub use linkerd_http_h2::{ClientKeepAlive, ClientParams, FlowControl, KeepAlive, ServerParams};
 
+#[cfg(test)]
+mod tests;
+
 #[derive(Debug)]
 pub struct Connect<C, B> {
     connect: C,
@@ -23,6 +31,7 @@ pub struct Connect<C, B> {
 #[derive(Debug)]
 pub struct Connection<B> {
     tx: hyper::client::conn::http2::SendRequest<B>,
+    extended_connect: Arc<AtomicBool>,
 }
 
 // === impl Connect ===
@@ -72,6 +81,7 @@ where
         let ClientParams {
             flow_control,
             keep_alive,
+            extended_connect: _,
             max_concurrent_reset_streams,
             max_frame_size,
             max_send_buf_size,
@@ -128,12 +138,31 @@ where
                     .instrument(trace_span!("handshake").or_current())
                     .await?;
 
+                // Track the peer's SETTINGS_ENABLE_CONNECT_PROTOCOL as the
+                // connection's settings are received.
+                let extended_connect = Arc::new(AtomicBool::new(false));
+                let enabled = extended_connect.clone();
                 tokio::spawn(
-                    conn.map_err(|error| debug!(%error, "failed"))
-                        .instrument(trace_span!("conn").or_current()),
+                    async move {
+                        let mut conn = std::pin::pin!(conn);
+                        future::poll_fn(|cx| {
+                            let poll = conn.as_mut().poll(cx);
+                            enabled.store(
+                                conn.is_extended_connect_protocol_enabled(),
+                                Ordering::Release,
+                            );
+                            poll
+                        })
+                        .await
+                    }
+                    .map_err(|error| debug!(%error, "failed"))
+                    .instrument(trace_span!("conn").or_current()),
                 );
 
-                Ok(Connection { tx })
+                Ok(Connection {
+                    tx,
+                    extended_connect,
+                })
             }
             .instrument(debug_span!("h2").or_current()),
         )
@@ -142,6 +171,14 @@ where
 
 // === impl Connection ===
 
+impl<B> Connection<B> {
+    /// Returns whether the server has enabled the extended CONNECT protocol
+    /// (RFC 8441) on this connection.
+    pub fn is_extended_connect_protocol_enabled(&self) -> bool {
+        self.extended_connect.load(Ordering::Acquire)
+    }
+}
+
 impl<B> tower::Service<http::Request<B>> for Connection<B>
 where
     B: Body + Send + 'static,
@@ -172,6 +209,22 @@ where
             *req.version_mut() = http::Version::HTTP_11;
         }
 
-        self.tx.send_request(req).boxed()
+        // Extended CONNECT requests, marked by `upgrade`, are bridged once the
+        // server accepts the stream.
+        let upgrade = req.extensions_mut().remove::<Http11Upgrade>();
+        let rsp = self.tx.send_request(req);
+        match upgrade {
+            None => rsp.boxed(),
+            Some(upgrade) => rsp
+                .map_ok(move |mut rsp| {
+                    if rsp.status() == http::StatusCode::OK {
+                        if let Err(error) = upgrade.insert_half(hyper::upgrade::on(&mut rsp)) {
+                            debug!(%error, "Failed to bridge extended CONNECT");
+                        }
+                    }
+                    rsp
+                })
+                .boxed(),
+        }
     }
 }
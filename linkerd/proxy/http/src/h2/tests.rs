@@ -0,0 +1,64 @@
+use super::*;
+use http_body_util::Full;
+use linkerd_http_box::BoxBody;
+use linkerd_io as io;
+use std::convert::Infallible;
+use tokio::time;
+use tower::ServiceExt;
+
+#[tokio::test(flavor = "current_thread")]
+async fn extended_connect_enabled_by_server() {
+    let conn = connect(true).await;
+    // Wait for the server's settings to be received.
+    time::sleep(time::Duration::from_millis(10)).await;
+    assert!(conn.is_extended_connect_protocol_enabled());
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn extended_connect_disabled_by_server() {
+    let conn = connect(false).await;
+    time::sleep(time::Duration::from_millis(10)).await;
+    assert!(!conn.is_extended_connect_protocol_enabled());
+}
+
+// === Utilities ===
+
+async fn connect(enable_connect_protocol: bool) -> Connection<BoxBody> {
+    let params = ClientParams {
+        extended_connect: Some(true),
+        ..Default::default()
+    };
+    Connect::new(Server(enable_connect_protocol), params)
+        .oneshot(())
+        .await
+        .expect("client must connect")
+}
+
+/// Connects to an in-memory HTTP/2 server.
+#[derive(Clone)]
+struct Server(bool);
+
+impl tower::Service<(crate::Variant, ())> for Server {
+    type Response = (io::DuplexStream, ());
+    type Error = Infallible;
+    type Future = future::Ready<Result<(io::DuplexStream, ()), Infallible>>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, _: (crate::Variant, ())) -> Self::Future {
+        let (client, server) = io::duplex(64 * 1024);
+        let mut builder = hyper::server::conn::http2::Builder::new(TokioExecutor::new());
+        if self.0 {
+            builder.enable_connect_protocol();
+        }
+        tokio::spawn(builder.serve_connection(
+            hyper_util::rt::TokioIo::new(server),
+            hyper::service::service_fn(|_| {
+                future::ok::<_, Infallible>(http::Response::new(Full::new(bytes::Bytes::new())))
+            }),
+        ));
+        future::ok((client, ()))
+    }
+}
@@ -38,12 +38,15 @@ pub use hyper_util::rt::tokio::TokioExecutor;
 pub use linkerd_http_box::{BoxBody, BoxRequest, BoxResponse, EraseResponse};
 pub use linkerd_http_classify as classify;
 pub use linkerd_http_detect::{
-    DetectMetrics, DetectMetricsFamilies, DetectParams, Detection, NewDetect,
+    sniff, DetectMetrics, DetectMetricsFamilies, DetectParams, Detection, NewDetect,
+    OpaqueProtocol, Sniff, Sniffers,
 };
 pub use linkerd_http_insert as insert;
 pub use linkerd_http_override_authority::{AuthorityOverride, NewOverrideAuthority};
 pub use linkerd_http_retain::{self as retain, Retain};
-pub use linkerd_http_stream_timeouts::{self as stream_timeouts, EnforceTimeouts, StreamTimeouts};
+pub use linkerd_http_stream_timeouts::{
+    self as stream_timeouts, EnforceTimeouts, PropagateDeadlines, StreamTimeouts,
+};
 pub use linkerd_http_upgrade as upgrade;
 pub use linkerd_http_variant::{Unsupported as UnsupportedVariant, Variant};
 
@@ -1,9 +1,10 @@
 use super::{h1, h2, Body};
 use futures::prelude::*;
-use http::header::{HeaderValue, TRANSFER_ENCODING};
+use http::header::{HeaderValue, CONNECTION, TRANSFER_ENCODING, UPGRADE};
 use http_body::Frame;
 use linkerd_error::Result;
 use linkerd_http_box::BoxBody;
+use linkerd_http_upgrade::upgrade::Http11Upgrade;
 use linkerd_stack::{layer, MakeConnection, Service};
 use std::{
     pin::Pin,
@@ -15,10 +16,14 @@ use tracing::{debug, trace, warn};
 pub const L5D_ORIG_PROTO: &str = "l5d-orig-proto";
 
 /// Upgrades HTTP requests from their original protocol to HTTP2.
+///
+/// When the server supports extended CONNECT, HTTP/1.1 WebSocket upgrades are
+/// carried over an HTTP/2 stream as well.
 #[derive(Debug)]
 pub struct Upgrade<C, T, B> {
     http1: h1::Client<C, T, B>,
     h2: h2::Connection<B>,
+    extended_connect: bool,
 }
 
 #[derive(Clone, Copy, Debug, Error)]
@@ -57,11 +62,22 @@ pub enum Error {
     H2(hyper::Error),
 }
 
+type UpgradeFuture =
+    Pin<Box<dyn Future<Output = Result<http::Response<BoxBody>, Error>> + Send + 'static>>;
+
 // === impl Upgrade ===
 
 impl<C, T, B> Upgrade<C, T, B> {
-    pub(crate) fn new(http1: h1::Client<C, T, B>, h2: h2::Connection<B>) -> Self {
-        Self { http1, h2 }
+    pub(crate) fn new(
+        http1: h1::Client<C, T, B>,
+        h2: h2::Connection<B>,
+        extended_connect: bool,
+    ) -> Self {
+        Self {
+            http1,
+            h2,
+            extended_connect,
+        }
     }
 }
 
@@ -77,13 +93,11 @@ where
 {
     type Response = http::Response<BoxBody>;
     type Error = Error;
-    type Future = Pin<
-        Box<dyn Future<Output = Result<http::Response<BoxBody>, Self::Error>> + Send + 'static>,
-    >;
+    type Future = UpgradeFuture;
 
     #[inline]
     fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
-        let Self { http1, h2 } = self;
+        let Self { http1, h2, .. } = self;
 
         match http1.poll_ready(cx).map_err(Error::H1) {
             Poll::Ready(Ok(())) => {}
@@ -95,11 +109,14 @@ where
 
     fn call(&mut self, mut req: http::Request<B>) -> Self::Future {
         debug_assert!(req.version() != http::Version::HTTP_2);
-        if req
-            .extensions()
-            .get::<linkerd_http_upgrade::upgrade::Http11Upgrade>()
-            .is_some()
-        {
+        if req.extensions().get::<Http11Upgrade>().is_some() {
+            if self.extended_connect && is_websocket(req.headers()) {
+                if self.h2.is_extended_connect_protocol_enabled() {
+                    return self.call_websocket(req);
+                }
+                debug!("Server does not support extended CONNECT");
+            }
+
             debug!("Skipping orig-proto upgrade due to HTTP/1.1 upgrade");
             return Box::pin(
                 self.http1
@@ -116,18 +133,10 @@ where
             .is_some();
         debug!(version = ?orig_version, absolute_form, "Upgrading request");
 
-        // absolute-form is far less common, origin-form is the usual,
-        // so only encode the extra information if it's different than
-        // the normal.
-        let header = match (orig_version, absolute_form) {
-            (http::Version::HTTP_11, false) => "HTTP/1.1",
-            (http::Version::HTTP_11, true) => "HTTP/1.1; absolute-form",
-            (http::Version::HTTP_10, false) => "HTTP/1.0",
-            (http::Version::HTTP_10, true) => "HTTP/1.0; absolute-form",
-            (v, _) => unreachable!("bad orig-proto version: {:?}", v),
-        };
-        req.headers_mut()
-            .insert(L5D_ORIG_PROTO, HeaderValue::from_static(header));
+        req.headers_mut().insert(
+            L5D_ORIG_PROTO,
+            orig_proto_header(orig_version, absolute_form),
+        );
 
         // transfer-encoding is illegal in HTTP2
         req.headers_mut().remove(TRANSFER_ENCODING);
@@ -155,6 +164,82 @@ where
     }
 }
 
+impl<C, T, B> Upgrade<C, T, B>
+where
+    B: crate::Body + Send + Unpin + 'static,
+    B::Data: Send,
+    B::Error: Into<linkerd_error::Error> + Send + Sync,
+{
+    /// Carries an HTTP/1.1 WebSocket upgrade over an HTTP/2 extended CONNECT
+    /// stream, per RFC 8441.
+    ///
+    /// The `sec-websocket-*` headers are forwarded as-is so that the
+    /// handshake is completed by the endpoints, once the request is
+    /// downgraded by the server's proxy.
+    fn call_websocket(&mut self, mut req: http::Request<B>) -> UpgradeFuture {
+        let upgrade = req
+            .extensions_mut()
+            .remove::<Http11Upgrade>()
+            .expect("upgrade extension must be set");
+        let absolute_form = req
+            .extensions_mut()
+            .remove::<h1::WasAbsoluteForm>()
+            .is_some();
+        debug!(
+            absolute_form,
+            "Upgrading WebSocket request to extended CONNECT"
+        );
+
+        let header = orig_proto_header(req.version(), absolute_form);
+        linkerd_http_upgrade::strip_connection_headers(req.headers_mut());
+        req.headers_mut().insert(L5D_ORIG_PROTO, header);
+        req.headers_mut().remove(TRANSFER_ENCODING);
+        *req.method_mut() = http::Method::CONNECT;
+        *req.version_mut() = http::Version::HTTP_2;
+        req.extensions_mut()
+            .insert(hyper::ext::Protocol::from_static("websocket"));
+
+        Box::pin(self.h2.call(req).map(move |rsp| {
+            let mut rsp = rsp.map_err(Error::h2)?;
+            rsp.headers_mut().remove(L5D_ORIG_PROTO);
+            *rsp.version_mut() = http::Version::HTTP_11;
+            if rsp.status() == http::StatusCode::OK {
+                trace!("Extended CONNECT succeeded; switching protocols");
+                upgrade
+                    .insert_half(hyper::upgrade::on(&mut rsp))
+                    .map_err(|e| Error::H1(e.into()))?;
+                *rsp.status_mut() = http::StatusCode::SWITCHING_PROTOCOLS;
+                rsp.headers_mut()
+                    .insert(CONNECTION, HeaderValue::from_static("upgrade"));
+                rsp.headers_mut()
+                    .insert(UPGRADE, HeaderValue::from_static("websocket"));
+            }
+            Ok(rsp.map(|inner| BoxBody::new(UpgradeResponseBody { inner })))
+        }))
+    }
+}
+
+fn orig_proto_header(version: http::Version, absolute_form: bool) -> HeaderValue {
+    // absolute-form is far less common, origin-form is the usual,
+    // so only encode the extra information if it's different than
+    // the normal.
+    let header = match (version, absolute_form) {
+        (http::Version::HTTP_11, false) => "HTTP/1.1",
+        (http::Version::HTTP_11, true) => "HTTP/1.1; absolute-form",
+        (http::Version::HTTP_10, false) => "HTTP/1.0",
+        (http::Version::HTTP_10, true) => "HTTP/1.0; absolute-form",
+        (v, _) => unreachable!("bad orig-proto version: {:?}", v),
+    };
+    HeaderValue::from_static(header)
+}
+
+/// Returns true if the headers request (or accept) a WebSocket upgrade.
+fn is_websocket(headers: &http::HeaderMap) -> bool {
+    headers
+        .get(UPGRADE)
+        .is_some_and(|v| v.as_bytes().eq_ignore_ascii_case(b"websocket"))
+}
+
 // === impl Error ===
 
 impl Error {
@@ -293,6 +378,7 @@ where
 
     fn call(&mut self, mut req: http::Request<A>) -> Self::Future {
         let mut upgrade_response = false;
+        let mut websocket = false;
 
         if req.version() == http::Version::HTTP_2 {
             if let Some(orig_proto) = req.headers_mut().remove(L5D_ORIG_PROTO) {
@@ -313,12 +399,44 @@ where
                 }
                 req.extensions_mut().insert(WasUpgrade(()));
                 upgrade_response = true;
+
+                // An extended CONNECT is restored to the HTTP/1.1 WebSocket
+                // upgrade that it carries.
+                if req.method() == http::Method::CONNECT
+                    && req
+                        .extensions()
+                        .get::<hyper::ext::Protocol>()
+                        .is_some_and(|p| p.as_str().eq_ignore_ascii_case("websocket"))
+                {
+                    debug!("translating extended CONNECT to WebSocket upgrade");
+                    req.extensions_mut().remove::<hyper::ext::Protocol>();
+                    *req.method_mut() = http::Method::GET;
+                    req.headers_mut()
+                        .insert(CONNECTION, HeaderValue::from_static("upgrade"));
+                    req.headers_mut()
+                        .insert(UPGRADE, HeaderValue::from_static("websocket"));
+                    websocket = true;
+                }
             }
         }
 
         let fut = self.inner.call(req);
 
-        if upgrade_response {
+        if websocket {
+            fut.map_ok(|mut res| {
+                // A successful upgrade is acknowledged with a `200 OK` on the
+                // extended CONNECT stream.
+                if res.status() == http::StatusCode::SWITCHING_PROTOCOLS {
+                    *res.status_mut() = http::StatusCode::OK;
+                }
+                linkerd_http_upgrade::strip_connection_headers(res.headers_mut());
+                res.headers_mut()
+                    .insert(L5D_ORIG_PROTO, HeaderValue::from_static("HTTP/1.1"));
+                res.headers_mut().remove(TRANSFER_ENCODING);
+                *res.version_mut() = http::Version::HTTP_2;
+                res
+            })
+        } else if upgrade_response {
             fut.map_ok(|mut res| {
                 let orig_proto = match res.version() {
                     http::Version::HTTP_11 => "HTTP/1.1",
@@ -3,7 +3,7 @@ use hyper_util::rt::tokio::TokioExecutor;
 use linkerd_error::Error;
 use linkerd_http_box::BoxRequest;
 use linkerd_io::{self as io, PeerAddr};
-use linkerd_stack::{layer, ExtractParam, NewService};
+use linkerd_stack::{layer, Either, ExtractParam, NewService};
 use std::{
     future::Future,
     pin::Pin,
@@ -36,6 +36,7 @@ pub struct ServeHttp<N> {
     version: Variant,
     http1: hyper::server::conn::http1::Builder,
     http2: hyper::server::conn::http2::Builder<TokioExecutor>,
+    enable_connect_protocol: bool,
     inner: N,
     drain: drain::Watch,
 }
@@ -70,6 +71,7 @@ where
             keep_alive,
             flow_control,
             max_concurrent_streams,
+            enable_connect_protocol,
             max_frame_size,
             max_header_list_size,
             max_send_buf_size,
@@ -110,6 +112,9 @@ where
         if let Some(sz) = max_send_buf_size {
             http2.max_send_buf_size(sz);
         }
+        if enable_connect_protocol {
+            http2.enable_connect_protocol();
+        }
 
         let mut http1 = hyper::server::conn::http1::Builder::new();
         http1
@@ -124,6 +129,7 @@ where
             drain,
             http1,
             http2,
+            enable_connect_protocol,
         }
     }
 }
@@ -154,6 +160,7 @@ where
         let drain = self.drain.clone();
         let http1 = self.http1.clone();
         let http2 = self.http2.clone();
+        let enable_connect_protocol = self.enable_connect_protocol;
 
         let res = io.peer_addr().map(|pa| {
             let (handle, closed) = ClientHandle::new(pa);
@@ -196,8 +203,17 @@ where
                     }
 
                     Variant::H2 => {
-                        let svc =
-                            hyper_util::service::TowerToHyperService::new(BoxRequest::new(svc));
+                        // Extended CONNECT (websockets) is only bridged when
+                        // the server advertises support for it.
+                        let svc = if enable_connect_protocol {
+                            Either::Left(linkerd_http_upgrade::upgrade::Service::new(
+                                BoxRequest::new(svc),
+                                drain.clone(),
+                            ))
+                        } else {
+                            Either::Right(BoxRequest::new(svc))
+                        };
+                        let svc = hyper_util::service::TowerToHyperService::new(svc);
                         let io = hyper_util::rt::TokioIo::new(io);
                         let mut conn = http2.serve_connection(io, svc);
 
@@ -159,6 +159,47 @@ async fn h2_stream_window_exhaustion() {
     */
 }
 
+/// Tests that the server bridges HTTP/2 extended CONNECT requests.
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn h2_extended_connect() {
+    let _trace = linkerd_tracing::test::with_default_filter(LOG_LEVEL);
+
+    let mut server = TestServer::connect_h2(
+        h2::ServerParams {
+            enable_connect_protocol: true,
+            ..Default::default()
+        },
+        &mut hyper::client::conn::http2::Builder::new(TokioExecutor::new()),
+    )
+    .await;
+
+    // Wait for the server's settings to be acknowledged by the client.
+    time::sleep(time::Duration::from_millis(10)).await;
+
+    server.server.allow(1);
+    let mut req = http::Request::builder()
+        .method(http::Method::CONNECT)
+        .uri("http://example.com/chat")
+        .body(BoxBody::default())
+        .unwrap();
+    req.extensions_mut()
+        .insert(hyper::ext::Protocol::from_static("websocket"));
+    let mut call0 = server.client.send_request(req).boxed();
+    let (req, next) = tokio::select! {
+        _ = (&mut call0) => unreachable!("client cannot receive a response"),
+        next = server.server.next_request() => next.expect("server not dropped"),
+    };
+    assert!(
+        req.extensions()
+            .get::<linkerd_http_upgrade::upgrade::Http11Upgrade>()
+            .is_some(),
+        "extended CONNECT must be marked for upgrade"
+    );
+    next.send_response(http::Response::new(BoxBody::default()));
+    let rsp = timeout(call0).await.expect("timed out").expect("response");
+    assert_eq!(rsp.status(), http::StatusCode::OK);
+}
+
 // === Utilities ===
 
 const LOG_LEVEL: &str = "h2::proto=trace,hyper=trace,linkerd=trace,info";
@@ -0,0 +1,21 @@
+//! PostgreSQL protocol awareness for opaque TCP connections.
+//!
+//! Sessions are scanned as they are proxied in order to record per-database
+//! query and transaction latencies, labeled by the class of errors that servers
+//! return, and to classify sessions that servers end because they are unable to
+//! serve them.
+
+#![deny(rust_2018_idioms, clippy::disallowed_methods, clippy::disallowed_types)]
+#![forbid(unsafe_code)]
+
+mod message;
+mod metrics;
+mod proxy;
+mod sqlstate;
+
+pub use self::{
+    message::ProtocolError,
+    metrics::{SessionMetrics, SessionMetricsFamilies},
+    proxy::{ClientIo, Params},
+    sqlstate::{Failures, ServerFailure, SqlState},
+};
@@ -0,0 +1,327 @@
+//! Framing for the PostgreSQL [frontend/backend protocol] (version 3).
+//!
+//! Messages are scanned as they are proxied. Only the bodies of messages that
+//! the proxy inspects are buffered; all others are skipped.
+//!
+//! [frontend/backend protocol]: https://www.postgresql.org/docs/current/protocol.html
+
+use crate::SqlState;
+
+/// Servers reject messages larger than 1GB.
+const MAX_MESSAGE_LEN: u32 = 1 << 30;
+
+/// Servers reject startup packets larger than this.
+const MAX_STARTUP_LEN: u32 = 10_000;
+
+/// Inspected messages (e.g. error responses) that are larger than this are
+/// skipped.
+const MAX_INSPECTED_LEN: u32 = 64 * 1024;
+
+/// Protocol versions are a major version in the high 16 bits and a minor
+/// version in the low 16 bits.
+const PROTOCOL_V3: u32 = 3;
+const CANCEL_REQUEST: u32 = 80_877_102;
+const SSL_REQUEST: u32 = 80_877_103;
+const GSSENC_REQUEST: u32 = 80_877_104;
+
+/// Servers truncate identifiers to this length (`NAMEDATALEN - 1`).
+const MAX_NAME_LEN: usize = 63;
+
+#[derive(Debug, thiserror::Error)]
+#[error("invalid Postgres message: {0}")]
+pub struct ProtocolError(&'static str);
+
+/// A message scanned from one direction of a connection.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Frame<'a> {
+    /// An untyped message that a client sends to begin a connection.
+    Startup(Startup),
+    /// A server's single-byte response to an SSL or GSSAPI encryption request.
+    Negotiation(u8),
+    Message {
+        tag: u8,
+        /// The message's body, if it was inspected.
+        body: Option<&'a [u8]>,
+    },
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Startup {
+    Params {
+        user: Option<String>,
+        database: Option<String>,
+    },
+    SslRequest,
+    GssEncRequest,
+    CancelRequest,
+}
+
+/// The fields of an `ErrorResponse` that the proxy inspects.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct ErrorFields {
+    pub(crate) code: Option<SqlState>,
+    /// Set when the error ends the session (i.e. it is `FATAL` or `PANIC`).
+    pub(crate) fatal: bool,
+}
+
+/// Scans messages from one direction of a connection.
+#[derive(Debug)]
+pub(crate) struct Scanner {
+    /// Whether messages begin with a type byte. Only the messages that a
+    /// client sends before startup are untyped.
+    typed: bool,
+    /// Set when the next byte is a server's response to an encryption
+    /// request.
+    negotiating: bool,
+    /// Determines which typed messages are buffered so that their bodies may
+    /// be inspected.
+    inspect: fn(u8) -> bool,
+    buf: Vec<u8>,
+    /// The number of bytes of an uninspected message that remain to be
+    /// skipped.
+    skip: usize,
+}
+
+// === impl Scanner ===
+
+impl Scanner {
+    /// Scans messages sent by a client.
+    pub(crate) fn frontend() -> Self {
+        Self::new(false, |_| false)
+    }
+
+    /// Scans messages sent by a server, inspecting `ErrorResponse` and
+    /// `ReadyForQuery` messages.
+    pub(crate) fn backend() -> Self {
+        Self::new(true, |tag| matches!(tag, b'E' | b'Z'))
+    }
+
+    fn new(typed: bool, inspect: fn(u8) -> bool) -> Self {
+        Self {
+            typed,
+            negotiating: false,
+            inspect,
+            buf: Vec::new(),
+            skip: 0,
+        }
+    }
+
+    /// Indicates that the server's next byte responds to an encryption
+    /// request.
+    pub(crate) fn expect_negotiation(&mut self) {
+        self.negotiating = true;
+    }
+
+    /// Scans `input`, passing each complete message to `f`. Incomplete
+    /// messages are completed by subsequent calls.
+    pub(crate) fn scan(
+        &mut self,
+        mut input: &[u8],
+        mut f: impl FnMut(Frame<'_>),
+    ) -> Result<(), ProtocolError> {
+        while !input.is_empty() {
+            if self.skip > 0 {
+                let n = self.skip.min(input.len());
+                self.skip -= n;
+                input = &input[n..];
+                continue;
+            }
+
+            if self.negotiating {
+                self.negotiating = false;
+                f(Frame::Negotiation(input[0]));
+                input = &input[1..];
+                continue;
+            }
+
+            let header_len = if self.typed { 5 } else { 4 };
+            if self.buf.len() < header_len {
+                let n = (header_len - self.buf.len()).min(input.len());
+                self.buf.extend_from_slice(&input[..n]);
+                input = &input[n..];
+                if self.buf.len() < header_len {
+                    break;
+                }
+            }
+
+            // The length includes itself, but not the type byte.
+            let len = read_u32(&self.buf, header_len - 4).expect("header must be complete");
+            let max = if self.typed {
+                MAX_MESSAGE_LEN
+            } else {
+                MAX_STARTUP_LEN
+            };
+            if !(4..=max).contains(&len) {
+                return Err(ProtocolError("invalid message length"));
+            }
+            let body_len = len as usize - 4;
+
+            let tag = self.typed.then(|| self.buf[0]);
+            if let Some(tag) = tag {
+                if !(self.inspect)(tag) || len > MAX_INSPECTED_LEN {
+                    f(Frame::Message { tag, body: None });
+                    self.buf.clear();
+                    self.skip = body_len;
+                    continue;
+                }
+            }
+
+            let total = header_len + body_len;
+            let n = (total - self.buf.len()).min(input.len());
+            self.buf.extend_from_slice(&input[..n]);
+            input = &input[n..];
+            if self.buf.len() < total {
+                break;
+            }
+
+            let body = &self.buf[header_len..];
+            match tag {
+                Some(tag) => f(Frame::Message {
+                    tag,
+                    body: Some(body),
+                }),
+                None => {
+                    let startup = startup(body)?;
+                    // Once a connection starts, all messages are typed.
+                    self.typed = matches!(startup, Startup::Params { .. });
+                    f(Frame::Startup(startup));
+                }
+            }
+            self.buf.clear();
+        }
+
+        Ok(())
+    }
+}
+
+fn startup(body: &[u8]) -> Result<Startup, ProtocolError> {
+    let code = read_u32(body, 0).ok_or(ProtocolError("startup message too short"))?;
+    match code {
+        SSL_REQUEST => Ok(Startup::SslRequest),
+        GSSENC_REQUEST => Ok(Startup::GssEncRequest),
+        CANCEL_REQUEST => Ok(Startup::CancelRequest),
+        code if code >> 16 == PROTOCOL_V3 => {
+            // Parameters are pairs of null-terminated names and values,
+            // followed by a null byte.
+            let mut user = None;
+            let mut database = None;
+            let mut params = body[4..].split(|b| *b == 0);
+            while let (Some(name), Some(value)) = (params.next(), params.next()) {
+                match name {
+                    b"" => break,
+                    b"user" => user = Some(identifier(value)),
+                    b"database" => database = Some(identifier(value)),
+                    _ => {}
+                }
+            }
+            Ok(Startup::Params { user, database })
+        }
+        _ => Err(ProtocolError("unsupported protocol version")),
+    }
+}
+
+/// Parses the body of an `ErrorResponse`, which consists of fields that are
+/// each identified by a byte and followed by a null-terminated value.
+pub(crate) fn error_fields(body: &[u8]) -> ErrorFields {
+    let mut code = None;
+    let mut severity = None;
+    let mut localized_severity = None;
+    for field in body.split(|b| *b == 0) {
+        let Some((&kind, value)) = field.split_first() else {
+            break;
+        };
+        match kind {
+            b'C' => code = SqlState::parse(value),
+            b'V' => severity = Some(value),
+            b'S' => localized_severity = Some(value),
+            _ => {}
+        }
+    }
+
+    // Older servers only send a (possibly localized) severity.
+    let fatal = matches!(severity.or(localized_severity), Some(b"FATAL" | b"PANIC"));
+    ErrorFields { code, fatal }
+}
+
+fn identifier(value: &[u8]) -> String {
+    let value = &value[..value.len().min(MAX_NAME_LEN)];
+    String::from_utf8_lossy(value).into_owned()
+}
+
+fn read_u32(buf: &[u8], at: usize) -> Option<u32> {
+    let bytes = buf.get(at..at + 4)?;
+    Some(u32::from_be_bytes(bytes.try_into().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scan(scanner: &mut Scanner, input: &[u8]) -> Vec<String> {
+        let mut frames = vec![];
+        scanner
+            .scan(input, |frame| frames.push(format!("{frame:?}")))
+            .unwrap();
+        frames
+    }
+
+    #[test]
+    fn frontend() {
+        const SSL: &[u8] = b"\0\0\0\x08\x04\xd2\x16\x2f";
+        const STARTUP: &[u8] = b"\0\0\0\x24\0\x03\0\0user\0alice\0database\0orders\0\0";
+        const QUERY: &[u8] = b"Q\0\0\0\x0dSELECT 1\0";
+
+        let mut scanner = Scanner::frontend();
+        assert_eq!(scan(&mut scanner, SSL), ["Startup(SslRequest)"]);
+
+        // Messages may be split across reads.
+        let mut input = STARTUP.to_vec();
+        input.extend_from_slice(QUERY);
+        let (a, b) = input.split_at(10);
+        assert!(scan(&mut scanner, a).is_empty());
+        assert_eq!(
+            scan(&mut scanner, b),
+            [
+                r#"Startup(Params { user: Some("alice"), database: Some("orders") })"#,
+                "Message { tag: 81, body: None }"
+            ]
+        );
+
+        let mut scanner = Scanner::frontend();
+        assert!(scanner.scan(b"\0\0\0\x08\0\x02\0\0", |_| {}).is_err());
+        assert!(scanner.scan(b"\0\0\0\x02", |_| {}).is_err());
+    }
+
+    #[test]
+    fn backend() {
+        let mut scanner = Scanner::backend();
+        scanner.expect_negotiation();
+        assert_eq!(scan(&mut scanner, b"N"), ["Negotiation(78)"]);
+
+        // Uninspected messages are skipped without being buffered.
+        let mut row = b"D\0\0\x10\x04".to_vec();
+        row.resize(0x1004 + 1, b'x');
+        let (a, b) = row.split_at(100);
+        assert_eq!(scan(&mut scanner, a), ["Message { tag: 68, body: None }"]);
+        assert!(scanner.buf.is_empty());
+        assert!(scan(&mut scanner, b).is_empty());
+
+        assert_eq!(
+            scan(&mut scanner, b"Z\0\0\0\x05I"),
+            ["Message { tag: 90, body: Some([73]) }"]
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let fields = error_fields(b"SERROR\0VERROR\0C42P01\0Mrelation \"x\" does not exist\0\0");
+        assert_eq!(fields.code, SqlState::parse(b"42P01"));
+        assert!(!fields.fatal);
+
+        let fields = error_fields(b"SFATAL\0C53300\0Msorry, too many clients already\0\0");
+        assert_eq!(fields.code, SqlState::parse(b"53300"));
+        assert!(fields.fatal);
+
+        assert_eq!(error_fields(b"\0"), ErrorFields::default());
+    }
+}
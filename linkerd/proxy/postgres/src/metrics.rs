@@ -0,0 +1,212 @@
+use crate::SqlState;
+use prometheus_client::{
+    encoding::EncodeLabelSet,
+    metrics::{
+        counter::Counter,
+        family::{Family, MetricConstructor},
+        histogram::Histogram,
+    },
+    registry::{Registry, Unit},
+};
+use std::{fmt::Debug, hash::Hash, sync::Arc};
+use tokio::time;
+
+#[derive(Clone, Debug)]
+pub struct SessionMetricsFamilies<L>
+where
+    L: Clone + Hash + Eq + EncodeLabelSet + Debug + Send + Sync + 'static,
+{
+    queries: Family<SessionLabels<L>, Histogram, MkDurations>,
+    transactions: Family<SessionLabels<L>, Histogram, MkDurations>,
+    errors: Family<ErrorLabels<L>, Counter>,
+}
+
+/// Records metrics for the sessions on a connection.
+#[derive(Clone, Debug)]
+pub struct SessionMetrics<L>
+where
+    L: Clone + Hash + Eq + EncodeLabelSet + Debug + Send + Sync + 'static,
+{
+    families: SessionMetricsFamilies<L>,
+    labels: L,
+}
+
+/// Identifies the database and user of a session.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub(crate) struct SessionLabels<L>
+where
+    L: Clone + Hash + Eq + EncodeLabelSet + Debug + Send + Sync + 'static,
+{
+    database: Arc<str>,
+    user: Arc<str>,
+    labels: L,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+struct ErrorLabels<L>
+where
+    L: Clone + Hash + Eq + EncodeLabelSet + Debug + Send + Sync + 'static,
+{
+    session: SessionLabels<L>,
+    /// The two-character SQLSTATE class.
+    class: [u8; 2],
+}
+
+#[derive(Clone, Debug)]
+struct MkDurations(&'static [f64]);
+
+const QUERY_BUCKETS: &[f64] = &[0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0, 5.0, 10.0];
+const TRANSACTION_BUCKETS: &[f64] = &[0.005, 0.01, 0.05, 0.1, 0.5, 1.0, 5.0, 10.0, 30.0, 60.0];
+
+// === impl SessionMetricsFamilies ===
+
+impl<L> Default for SessionMetricsFamilies<L>
+where
+    L: Clone + Hash + Eq + EncodeLabelSet + Debug + Send + Sync + 'static,
+{
+    fn default() -> Self {
+        Self {
+            queries: Family::new_with_constructor(MkDurations(QUERY_BUCKETS)),
+            transactions: Family::new_with_constructor(MkDurations(TRANSACTION_BUCKETS)),
+            errors: Family::default(),
+        }
+    }
+}
+
+impl<L> SessionMetricsFamilies<L>
+where
+    L: Clone + Hash + Eq + EncodeLabelSet + Debug + Send + Sync + 'static,
+{
+    pub fn register(reg: &mut Registry) -> Self {
+        let queries = Family::new_with_constructor(MkDurations(QUERY_BUCKETS));
+        reg.register_with_unit(
+            "query_duration",
+            "Time from sending a query to the server being ready for the next query",
+            Unit::Seconds,
+            queries.clone(),
+        );
+
+        let transactions = Family::new_with_constructor(MkDurations(TRANSACTION_BUCKETS));
+        reg.register_with_unit(
+            "transaction_duration",
+            "Time from the start of a transaction until it is committed or rolled back",
+            Unit::Seconds,
+            transactions.clone(),
+        );
+
+        let errors = Family::default();
+        reg.register(
+            "errors",
+            "Error responses sent by servers, by SQLSTATE class",
+            errors.clone(),
+        );
+
+        Self {
+            queries,
+            transactions,
+            errors,
+        }
+    }
+
+    pub fn metrics(&self, labels: L) -> SessionMetrics<L> {
+        SessionMetrics {
+            families: self.clone(),
+            labels,
+        }
+    }
+}
+
+// === impl SessionMetrics ===
+
+impl<L> SessionMetrics<L>
+where
+    L: Clone + Hash + Eq + EncodeLabelSet + Debug + Send + Sync + 'static,
+{
+    pub(crate) fn session(&self, database: &str, user: &str) -> SessionLabels<L> {
+        SessionLabels {
+            database: database.into(),
+            user: user.into(),
+            labels: self.labels.clone(),
+        }
+    }
+
+    pub(crate) fn query(&self, session: &SessionLabels<L>, elapsed: time::Duration) {
+        self.families
+            .queries
+            .get_or_create(session)
+            .observe(elapsed.as_secs_f64());
+    }
+
+    pub(crate) fn transaction(&self, session: &SessionLabels<L>, elapsed: time::Duration) {
+        self.families
+            .transactions
+            .get_or_create(session)
+            .observe(elapsed.as_secs_f64());
+    }
+
+    pub(crate) fn error(&self, session: &SessionLabels<L>, code: Option<SqlState>) {
+        let class = match code {
+            Some(code) => code
+                .class()
+                .as_bytes()
+                .try_into()
+                .expect("class must be 2 bytes"),
+            None => *b"??",
+        };
+        self.families
+            .errors
+            .get_or_create(&ErrorLabels {
+                session: session.clone(),
+                class,
+            })
+            .inc();
+    }
+}
+
+// === impl SessionLabels ===
+
+impl<L> EncodeLabelSet for SessionLabels<L>
+where
+    L: Clone + Hash + Eq + EncodeLabelSet + Debug + Send + Sync + 'static,
+{
+    fn encode(
+        &self,
+        enc: &mut prometheus_client::encoding::LabelSetEncoder<'_>,
+    ) -> Result<(), std::fmt::Error> {
+        use prometheus_client::encoding::EncodeLabel;
+
+        ("database", &*self.database).encode(enc.encode_label())?;
+        ("user", &*self.user).encode(enc.encode_label())?;
+        self.labels.encode(enc)?;
+
+        Ok(())
+    }
+}
+
+// === impl ErrorLabels ===
+
+impl<L> EncodeLabelSet for ErrorLabels<L>
+where
+    L: Clone + Hash + Eq + EncodeLabelSet + Debug + Send + Sync + 'static,
+{
+    fn encode(
+        &self,
+        enc: &mut prometheus_client::encoding::LabelSetEncoder<'_>,
+    ) -> Result<(), std::fmt::Error> {
+        use prometheus_client::encoding::EncodeLabel;
+
+        let class = std::str::from_utf8(&self.class).unwrap_or("??");
+        ("class", class).encode(enc.encode_label())?;
+        self.session.encode(enc)?;
+
+        Ok(())
+    }
+}
+
+// === impl MkDurations ===
+
+impl MetricConstructor<Histogram> for MkDurations {
+    fn new_metric(&self) -> Histogram {
+        Histogram::new(self.0.iter().copied())
+    }
+}
@@ -0,0 +1,490 @@
+use crate::{
+    message::{self, Frame, Scanner, Startup},
+    metrics::{SessionLabels, SessionMetrics},
+};
+use linkerd_io as io;
+use prometheus_client::encoding::EncodeLabelSet;
+use std::{
+    collections::VecDeque,
+    fmt::Debug,
+    hash::Hash,
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+use tokio::time;
+use tracing::{debug, trace};
+
+/// Configures how sessions are proxied.
+#[derive(Clone, Debug)]
+pub struct Params<L>
+where
+    L: Clone + Hash + Eq + EncodeLabelSet + Debug + Send + Sync + 'static,
+{
+    pub metrics: SessionMetrics<L>,
+}
+
+/// A client's connection, which scans a Postgres session as it is forwarded
+/// to and from a server.
+///
+/// The stack that forwards the connection reads the client's messages from it
+/// and writes the server's messages to it. Messages are scanned as they pass
+/// through, so each direction is forwarded independently and only the bodies
+/// of inspected messages are buffered. Scanning records the session's
+/// database and user, the latency of queries and transactions, and the errors
+/// returned by the server.
+///
+/// When the client negotiates SSL or GSSAPI encryption, the remainder of the
+/// session is passed through without inspection. Sessions that do not speak
+/// the protocol are also passed through.
+#[derive(Debug)]
+pub struct ClientIo<I, L>
+where
+    L: Clone + Hash + Eq + EncodeLabelSet + Debug + Send + Sync + 'static,
+{
+    io: I,
+    session: Session<L>,
+    frontend: Scanner,
+    backend: Scanner,
+}
+
+/// The state of a session, as observed from the messages that are proxied.
+#[derive(Debug)]
+struct Session<L>
+where
+    L: Clone + Hash + Eq + EncodeLabelSet + Debug + Send + Sync + 'static,
+{
+    metrics: SessionMetrics<L>,
+    labels: Option<SessionLabels<L>>,
+    /// Set when messages are no longer inspected, e.g. because the session is
+    /// encrypted.
+    passthrough: bool,
+    /// When the first message of an extended query was sent, if it has not
+    /// yet been followed by a `Sync`.
+    extended: Option<time::Instant>,
+    /// When each query that awaits a `ReadyForQuery` was sent.
+    queries: VecDeque<time::Instant>,
+    transaction: Option<time::Instant>,
+}
+
+// === impl ClientIo ===
+
+impl<I, L> ClientIo<I, L>
+where
+    L: Clone + Hash + Eq + EncodeLabelSet + Debug + Send + Sync + 'static,
+{
+    pub fn new(io: I, params: Params<L>) -> Self {
+        Self {
+            io,
+            session: Session::new(params.metrics),
+            frontend: Scanner::frontend(),
+            backend: Scanner::backend(),
+        }
+    }
+
+    fn scan_client(&mut self, buf: &[u8]) {
+        if self.session.passthrough {
+            return;
+        }
+        let Self {
+            session,
+            frontend,
+            backend,
+            ..
+        } = self;
+        if let Err(error) = frontend.scan(buf, |frame| session.client_frame(frame, backend)) {
+            session.pass_through(error);
+        }
+    }
+
+    fn scan_server(&mut self, buf: &[u8]) {
+        if self.session.passthrough {
+            return;
+        }
+        let Self {
+            session, backend, ..
+        } = self;
+        if let Err(error) = backend.scan(buf, |frame| session.server_frame(frame)) {
+            session.pass_through(error);
+        }
+    }
+}
+
+impl<I, L> io::AsyncRead for ClientIo<I, L>
+where
+    I: io::AsyncRead + Unpin,
+    L: Clone + Hash + Eq + EncodeLabelSet + Debug + Send + Sync + Unpin + 'static,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut io::ReadBuf<'_>,
+    ) -> io::Poll<()> {
+        let this = self.get_mut();
+        let filled = buf.filled().len();
+        ready!(Pin::new(&mut this.io).poll_read(cx, buf))?;
+        let read = &buf.filled()[filled..];
+        if read.is_empty() {
+            trace!("Client closed");
+        }
+        this.scan_client(read);
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<I, L> io::AsyncWrite for ClientIo<I, L>
+where
+    I: io::AsyncWrite + Unpin,
+    L: Clone + Hash + Eq + EncodeLabelSet + Debug + Send + Sync + Unpin + 'static,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> io::Poll<usize> {
+        let this = self.get_mut();
+        // Only the bytes that the client accepts are scanned, so that they are
+        // not scanned again when the remainder is written.
+        let n = ready!(Pin::new(&mut this.io).poll_write(cx, buf))?;
+        this.scan_server(&buf[..n]);
+        Poll::Ready(Ok(n))
+    }
+
+    #[inline]
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> io::Poll<()> {
+        Pin::new(&mut self.get_mut().io).poll_flush(cx)
+    }
+
+    #[inline]
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> io::Poll<()> {
+        Pin::new(&mut self.get_mut().io).poll_shutdown(cx)
+    }
+}
+
+impl<I, L> io::PeerAddr for ClientIo<I, L>
+where
+    I: io::PeerAddr,
+    L: Clone + Hash + Eq + EncodeLabelSet + Debug + Send + Sync + 'static,
+{
+    fn peer_addr(&self) -> io::Result<std::net::SocketAddr> {
+        self.io.peer_addr()
+    }
+}
+
+// === impl Session ===
+
+impl<L> Session<L>
+where
+    L: Clone + Hash + Eq + EncodeLabelSet + Debug + Send + Sync + 'static,
+{
+    fn new(metrics: SessionMetrics<L>) -> Self {
+        Self {
+            metrics,
+            labels: None,
+            passthrough: false,
+            extended: None,
+            queries: VecDeque::new(),
+            transaction: None,
+        }
+    }
+
+    fn pass_through(&mut self, error: message::ProtocolError) {
+        // Once encryption is negotiated, the remaining bytes are not messages.
+        if !self.passthrough {
+            debug!(%error, "Passing session through without inspection");
+            self.passthrough = true;
+        }
+    }
+
+    fn client_frame(&mut self, frame: Frame<'_>, backend: &mut Scanner) {
+        if self.passthrough {
+            return;
+        }
+
+        match frame {
+            Frame::Startup(Startup::SslRequest | Startup::GssEncRequest) => {
+                backend.expect_negotiation();
+            }
+            Frame::Startup(Startup::Params { user, database }) => {
+                let user = user.unwrap_or_default();
+                // Servers use the user's name when a database is not specified.
+                let database = database.unwrap_or_else(|| user.clone());
+                debug!(%database, %user, "Session started");
+                self.labels = Some(self.metrics.session(&database, &user));
+            }
+            Frame::Startup(Startup::CancelRequest) => {
+                trace!("Canceling a query");
+            }
+            Frame::Negotiation(_) => {}
+            Frame::Message { tag, .. } => {
+                let now = time::Instant::now();
+                match tag {
+                    // A simple query or function call is answered with a
+                    // `ReadyForQuery`.
+                    b'Q' | b'F' => self.queries.push_back(now),
+                    // Extended queries are answered once they are followed by a
+                    // `Sync`.
+                    b'P' | b'B' | b'E' | b'D' | b'C' | b'H' => {
+                        self.extended.get_or_insert(now);
+                    }
+                    b'S' => {
+                        let sent = self.extended.take().unwrap_or(now);
+                        self.queries.push_back(sent);
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    fn server_frame(&mut self, frame: Frame<'_>) {
+        if self.passthrough {
+            return;
+        }
+
+        match frame {
+            Frame::Negotiation(b'S' | b'G') => {
+                debug!("Session is encrypted");
+                self.passthrough = true;
+            }
+            Frame::Message {
+                tag: b'Z',
+                body: Some(status),
+            } => {
+                let now = time::Instant::now();
+                // The server is ready for a query once it has authenticated the
+                // client, without one having been sent.
+                let Some(sent) = self.queries.pop_front() else {
+                    return;
+                };
+                if let Some(labels) = self.labels.as_ref() {
+                    self.metrics
+                        .query(labels, now.saturating_duration_since(sent));
+                }
+
+                match status {
+                    // Idle.
+                    b"I" => {
+                        if let Some(began) = self.transaction.take() {
+                            if let Some(labels) = self.labels.as_ref() {
+                                self.metrics
+                                    .transaction(labels, now.saturating_duration_since(began));
+                            }
+                        }
+                    }
+                    // In a transaction, or in a failed transaction.
+                    b"T" | b"E" => {
+                        self.transaction.get_or_insert(sent);
+                    }
+                    _ => {}
+                }
+            }
+            Frame::Message { tag: b'E', body } => {
+                let fields = body.map(message::error_fields).unwrap_or_default();
+                debug!(code = ?fields.code, fatal = fields.fatal, "Server returned an error");
+                if let Some(labels) = self.labels.as_ref() {
+                    self.metrics.error(labels, fields.code);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SessionMetricsFamilies;
+    use io::{AsyncReadExt, AsyncWriteExt};
+    use prometheus_client::{encoding::text::encode, registry::Registry};
+
+    #[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+    struct Labels {
+        route: &'static str,
+    }
+
+    fn params() -> (Params<Labels>, Registry) {
+        let mut registry = Registry::default();
+        let families = SessionMetricsFamilies::register(&mut registry);
+        let params = Params {
+            metrics: families.metrics(Labels { route: "test" }),
+        };
+        (params, registry)
+    }
+
+    /// Forwards a client's connection to a server, as an opaque backend does.
+    fn forward(
+        params: Params<Labels>,
+    ) -> (
+        io::DuplexStream,
+        io::DuplexStream,
+        tokio::task::JoinHandle<io::Result<(u64, u64)>>,
+    ) {
+        let (client, proxy_client) = io::duplex(1024);
+        let (server, mut proxy_server) = io::duplex(1024);
+        let task = tokio::spawn(async move {
+            let mut client = ClientIo::new(proxy_client, params);
+            tokio::io::copy_bidirectional(&mut client, &mut proxy_server).await
+        });
+        (client, server, task)
+    }
+
+    /// Writes `msg` to `from` and expects that it is proxied to `to`.
+    async fn send(from: &mut io::DuplexStream, to: &mut io::DuplexStream, msg: &[u8]) {
+        from.write_all(msg).await.unwrap();
+        let mut buf = vec![0; msg.len()];
+        to.read_exact(&mut buf).await.unwrap();
+        assert_eq!(buf, msg);
+    }
+
+    const SSL: &[u8] = b"\0\0\0\x08\x04\xd2\x16\x2f";
+    const STARTUP: &[u8] = b"\0\0\0\x24\0\x03\0\0user\0alice\0database\0orders\0\0";
+    const AUTH_OK: &[u8] = b"R\0\0\0\x08\0\0\0\0";
+    const READY_IDLE: &[u8] = b"Z\0\0\0\x05I";
+    const READY_TX: &[u8] = b"Z\0\0\0\x05T";
+
+    #[tokio::test(start_paused = true)]
+    async fn records_queries() {
+        let (params, registry) = params();
+        let (mut client, mut server, task) = forward(params);
+
+        send(&mut client, &mut server, SSL).await;
+        send(&mut server, &mut client, b"N").await;
+        send(&mut client, &mut server, STARTUP).await;
+        let mut ready = AUTH_OK.to_vec();
+        ready.extend_from_slice(READY_IDLE);
+        send(&mut server, &mut client, &ready).await;
+
+        send(&mut client, &mut server, b"Q\0\0\0\x0aBEGIN\0").await;
+        time::sleep(time::Duration::from_millis(3)).await;
+        send(&mut server, &mut client, READY_TX).await;
+
+        // An extended query, answered after its `Sync`.
+        send(
+            &mut client,
+            &mut server,
+            b"P\0\0\0\x10\0SELECT 1\0\0\0B\0\0\0\x0c\0\0\0\0\0\0\0\0E\0\0\0\x09\0\0\0\0\0S\0\0\0\x04",
+        )
+        .await;
+        let mut error = b"E\0\0\0\x29SERROR\0VERROR\0C42P01\0Mno such table\0\0".to_vec();
+        error.extend_from_slice(b"Z\0\0\0\x05E");
+        send(&mut server, &mut client, &error).await;
+
+        send(&mut client, &mut server, b"Q\0\0\0\x0dROLLBACK\0").await;
+        time::sleep(time::Duration::from_millis(20)).await;
+        send(&mut server, &mut client, READY_IDLE).await;
+
+        drop(client);
+        drop(server);
+        task.await.unwrap().unwrap();
+
+        let mut text = String::new();
+        encode(&mut text, &registry).unwrap();
+        let labels = r#"database="orders",user="alice",route="test""#;
+        assert!(
+            text.contains(&format!("query_duration_seconds_count{{{labels}}} 3")),
+            "{text}"
+        );
+        assert!(
+            text.contains(&format!(
+                "transaction_duration_seconds_bucket{{le=\"0.01\",{labels}}} 0"
+            )),
+            "{text}"
+        );
+        assert!(
+            text.contains(&format!("transaction_duration_seconds_count{{{labels}}} 1")),
+            "{text}"
+        );
+        assert!(
+            text.contains(&format!("errors_total{{class=\"42\",{labels}}} 1")),
+            "{text}"
+        );
+    }
+
+    #[tokio::test]
+    async fn forwards_directions_independently() {
+        let (params, registry) = params();
+        let (mut client, mut server, task) = forward(params);
+
+        send(&mut client, &mut server, STARTUP).await;
+        let mut ready = AUTH_OK.to_vec();
+        ready.extend_from_slice(READY_IDLE);
+        send(&mut server, &mut client, &ready).await;
+        send(
+            &mut client,
+            &mut server,
+            b"Q\0\0\0\x1cCOPY t FROM STDIN\0\0\0\0\0",
+        )
+        .await;
+        send(&mut server, &mut client, b"G\0\0\0\x07\0\0\0").await;
+
+        // The client sends a large COPY while the server sends many notices.
+        // Like a single-threaded server, the server does not read the COPY
+        // until it has written its notices, so the client's data is forwarded
+        // only as the notices are.
+        const ROWS: usize = 1024;
+        let row = b"d\0\0\x04\x04"
+            .iter()
+            .copied()
+            .chain([b'x'; 1024])
+            .collect::<Vec<_>>();
+        let notice = b"N\0\0\x04\x04"
+            .iter()
+            .copied()
+            .chain([b'n'; 1024])
+            .collect::<Vec<_>>();
+        let (mut client_rx, mut client_tx) = tokio::io::split(&mut client);
+        let send_copy = async {
+            for _ in 0..ROWS {
+                client_tx.write_all(&row).await.unwrap();
+            }
+            client_tx.write_all(b"c\0\0\0\x04").await.unwrap();
+        };
+        let recv_notices = async {
+            let mut buf = vec![0; notice.len() * ROWS];
+            client_rx.read_exact(&mut buf).await.unwrap();
+        };
+        let serve = async {
+            for _ in 0..ROWS {
+                server.write_all(&notice).await.unwrap();
+            }
+            let mut buf = vec![0; row.len() * ROWS + 5];
+            server.read_exact(&mut buf).await.unwrap();
+            assert!(buf.ends_with(b"c\0\0\0\x04"));
+        };
+        tokio::join!(send_copy, recv_notices, serve);
+
+        let mut done = b"C\0\0\0\x0eCOPY 1024\0".to_vec();
+        done.extend_from_slice(READY_IDLE);
+        send(&mut server, &mut client, &done).await;
+
+        drop(client);
+        drop(server);
+        task.await.unwrap().unwrap();
+
+        let mut text = String::new();
+        encode(&mut text, &registry).unwrap();
+        assert!(
+            text.contains(
+                r#"query_duration_seconds_count{database="orders",user="alice",route="test"} 1"#
+            ),
+            "{text}"
+        );
+    }
+
+    #[tokio::test]
+    async fn passes_through_encrypted_sessions() {
+        let (params, _registry) = params();
+        let (mut client, mut server, task) = forward(params);
+
+        send(&mut client, &mut server, SSL).await;
+        send(&mut server, &mut client, b"S").await;
+        // A TLS record is not a valid Postgres message.
+        send(&mut client, &mut server, b"\x16\x03\x01\x02\x00\x01").await;
+        send(
+            &mut server,
+            &mut client,
+            b"\x16\x03\x03\0\x7aE\0\0\0\x28SFATAL\0VFATAL\0C53300\0\0",
+        )
+        .await;
+
+        drop(client);
+        drop(server);
+        task.await.unwrap().unwrap();
+    }
+}
@@ -0,0 +1,196 @@
+use crate::message::{self, Frame, Scanner, Startup};
+use std::fmt;
+
+/// A five-character error code sent by a server in an `ErrorResponse`.
+///
+/// The first two characters identify the error's class (e.g. `42` for syntax
+/// errors and access rule violations).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SqlState([u8; 5]);
+
+/// Indicates that a server ended a session with an error that reflects the
+/// server's health, rather than the client's requests.
+#[derive(Debug, thiserror::Error)]
+#[error("server failed with SQLSTATE {0}")]
+pub struct ServerFailure(pub SqlState);
+
+/// Classifies a session as failed when its server ends it with a
+/// [`ServerFailure`], e.g. for circuit breaking.
+///
+/// Errors caused by a client's queries, such as syntax errors or constraint
+/// violations, are not failures, so a failed session reflects the server's
+/// health. The messages exchanged by a client and a server are scanned as
+/// they are forwarded; encrypted sessions and connections that do not speak
+/// the protocol are never classified as failed.
+#[derive(Debug)]
+pub struct Failures {
+    frontend: Scanner,
+    backend: Scanner,
+    /// Set when messages are no longer scanned.
+    passthrough: bool,
+    failure: Option<ServerFailure>,
+}
+
+// === impl SqlState ===
+
+impl SqlState {
+    pub(crate) fn parse(code: &[u8]) -> Option<Self> {
+        let code: [u8; 5] = code.try_into().ok()?;
+        code.iter()
+            .all(|b| b.is_ascii_digit() || b.is_ascii_uppercase())
+            .then_some(Self(code))
+    }
+
+    pub fn as_str(&self) -> &str {
+        std::str::from_utf8(&self.0).expect("SQLSTATE must be ASCII")
+    }
+
+    /// Returns the error's two-character class.
+    pub fn class(&self) -> &str {
+        &self.as_str()[..2]
+    }
+
+    /// Returns true if the error indicates that the server is unable to serve
+    /// requests, e.g. because it is out of resources or shutting down.
+    pub fn is_failure(&self) -> bool {
+        match self.class() {
+            // Connection exceptions, insufficient resources, system errors,
+            // and internal errors.
+            "08" | "53" | "58" | "XX" => true,
+            // Operator intervention (e.g. `admin_shutdown`), except for
+            // queries canceled by clients or statement timeouts.
+            "57" => self.as_str() != "57014",
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for SqlState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+// === impl Failures ===
+
+impl Default for Failures {
+    fn default() -> Self {
+        Self {
+            frontend: Scanner::frontend(),
+            backend: Scanner::backend(),
+            passthrough: false,
+            failure: None,
+        }
+    }
+}
+
+impl Failures {
+    /// Scans bytes that the client sent to the server.
+    pub fn scan_client(&mut self, buf: &[u8]) {
+        if self.passthrough {
+            return;
+        }
+        let Self {
+            frontend, backend, ..
+        } = self;
+        let scanned = frontend.scan(buf, |frame| {
+            if let Frame::Startup(Startup::SslRequest | Startup::GssEncRequest) = frame {
+                backend.expect_negotiation();
+            }
+        });
+        if scanned.is_err() {
+            self.passthrough = true;
+        }
+    }
+
+    /// Scans bytes that the server sent to the client.
+    pub fn scan_server(&mut self, buf: &[u8]) {
+        if self.passthrough {
+            return;
+        }
+        let Self {
+            backend,
+            passthrough,
+            failure,
+            ..
+        } = self;
+        let scanned = backend.scan(buf, |frame| match frame {
+            Frame::Negotiation(b'S' | b'G') => *passthrough = true,
+            Frame::Message {
+                tag: b'E',
+                body: Some(body),
+            } if !*passthrough => {
+                let fields = message::error_fields(body);
+                if let Some(code) = fields.code.filter(|c| fields.fatal && c.is_failure()) {
+                    *failure = Some(ServerFailure(code));
+                }
+            }
+            _ => {}
+        });
+        if scanned.is_err() {
+            self.passthrough = true;
+        }
+    }
+
+    /// Returns the failure with which the server ended the session, if it
+    /// did.
+    pub fn failure(&self) -> Option<&ServerFailure> {
+        self.failure.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies() {
+        for (code, failure) in [
+            ("08006", true),
+            ("53300", true),
+            ("57P01", true),
+            ("57014", false),
+            ("XX000", true),
+            ("42P01", false),
+            ("23505", false),
+            ("40001", false),
+        ] {
+            let state = SqlState::parse(code.as_bytes()).unwrap();
+            assert_eq!(state.is_failure(), failure, "{code}");
+        }
+
+        assert_eq!(SqlState::parse(b"42P01").unwrap().class(), "42");
+        assert!(SqlState::parse(b"42p01").is_none());
+        assert!(SqlState::parse(b"4201").is_none());
+    }
+
+    #[test]
+    fn classifies_sessions() {
+        const STARTUP: &[u8] = b"\0\0\0\x24\0\x03\0\0user\0alice\0database\0orders\0\0";
+
+        // A server that is out of connections ends the session.
+        let mut failures = Failures::default();
+        failures.scan_client(STARTUP);
+        failures.scan_server(b"E\0\0\0\x1aSFATAL\0VFATAL\0C53300\0\0");
+        assert_eq!(failures.failure().unwrap().0.as_str(), "53300");
+
+        // Errors caused by queries are not failures.
+        let mut failures = Failures::default();
+        failures.scan_client(STARTUP);
+        failures.scan_server(b"R\0\0\0\x08\0\0\0\0Z\0\0\0\x05I");
+        failures.scan_client(b"Q\0\0\0\x0dSELECT x\0");
+        failures.scan_server(b"E\0\0\0\x1aSERROR\0VERROR\0C42P01\0\0");
+        assert!(failures.failure().is_none());
+
+        // Authentication failures are fatal, but they are not failures.
+        failures.scan_server(b"E\0\0\0\x1aSFATAL\0VFATAL\0C28P01\0\0");
+        assert!(failures.failure().is_none());
+
+        // Encrypted sessions are not inspected.
+        let mut failures = Failures::default();
+        failures.scan_client(b"\0\0\0\x08\x04\xd2\x16\x2f");
+        failures.scan_server(b"S");
+        failures.scan_server(b"\x16\x03\x03\0\x7aE\0\0\0\x1aSFATAL\0VFATAL\0C53300\0\0");
+        assert!(failures.failure().is_none());
+    }
+}
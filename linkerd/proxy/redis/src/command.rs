@@ -0,0 +1,346 @@
+/// Describes a command sent by a client.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Command {
+    /// The lowercase command name used in metrics, or `unknown` for commands
+    /// that are not in the table below.
+    pub(crate) name: &'static str,
+    pub(crate) kind: Kind,
+}
+
+/// Determines how a command is routed when reads are split from writes.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Kind {
+    /// A read-only command, which may be served by a replica.
+    Read,
+    /// A command that must be served by the primary.
+    Write,
+    /// A command that changes the state of the connection (e.g. `AUTH` or
+    /// `SELECT`), which is sent to every server.
+    Connection,
+    /// Begins a transaction. All commands are sent to the primary until it
+    /// ends.
+    Multi,
+    /// Ends a transaction.
+    EndMulti,
+    /// A command (e.g. `SUBSCRIBE` or `MONITOR`) after which the server sends
+    /// messages that do not answer commands. Subsequent commands are sent to
+    /// the primary.
+    Passthrough,
+}
+
+/// Commands are limited to this length so that they may be uppercased on the
+/// stack.
+const MAX_NAME_LEN: usize = 32;
+
+impl Command {
+    pub(crate) fn parse(name: &[u8]) -> Self {
+        let mut upper = [0u8; MAX_NAME_LEN];
+        let Some(upper) = upper.get_mut(..name.len()) else {
+            return Self::UNKNOWN;
+        };
+        upper.copy_from_slice(name);
+        upper.make_ascii_uppercase();
+        let Ok(upper) = std::str::from_utf8(upper) else {
+            return Self::UNKNOWN;
+        };
+
+        use Kind::*;
+        let (name, kind) = match upper {
+            // Connection state.
+            "AUTH" => ("auth", Connection),
+            "HELLO" => ("hello", Connection),
+            "SELECT" => ("select", Connection),
+            "CLIENT" => ("client", Connection),
+            "READONLY" => ("readonly", Connection),
+            "READWRITE" => ("readwrite", Connection),
+            "RESET" => ("reset", Connection),
+
+            // Transactions.
+            "MULTI" => ("multi", Multi),
+            "EXEC" => ("exec", EndMulti),
+            "DISCARD" => ("discard", EndMulti),
+
+            // Pub/sub and monitoring, after which the connection is passed
+            // through.
+            "SUBSCRIBE" => ("subscribe", Passthrough),
+            "PSUBSCRIBE" => ("psubscribe", Passthrough),
+            "SSUBSCRIBE" => ("ssubscribe", Passthrough),
+            "MONITOR" => ("monitor", Passthrough),
+
+            // Keys.
+            "EXISTS" => ("exists", Read),
+            "TYPE" => ("type", Read),
+            "TTL" => ("ttl", Read),
+            "PTTL" => ("pttl", Read),
+            "EXPIRETIME" => ("expiretime", Read),
+            "PEXPIRETIME" => ("pexpiretime", Read),
+            "KEYS" => ("keys", Read),
+            "SCAN" => ("scan", Read),
+            "RANDOMKEY" => ("randomkey", Read),
+            "DBSIZE" => ("dbsize", Read),
+            "DUMP" => ("dump", Read),
+            "OBJECT" => ("object", Read),
+            "TOUCH" => ("touch", Read),
+            "SORT_RO" => ("sort_ro", Read),
+            "DEL" => ("del", Write),
+            "UNLINK" => ("unlink", Write),
+            "EXPIRE" => ("expire", Write),
+            "PEXPIRE" => ("pexpire", Write),
+            "EXPIREAT" => ("expireat", Write),
+            "PEXPIREAT" => ("pexpireat", Write),
+            "PERSIST" => ("persist", Write),
+            "RENAME" => ("rename", Write),
+            "RENAMENX" => ("renamenx", Write),
+            "COPY" => ("copy", Write),
+            "MOVE" => ("move", Write),
+            "RESTORE" => ("restore", Write),
+            "SORT" => ("sort", Write),
+            "WATCH" => ("watch", Write),
+            "UNWATCH" => ("unwatch", Write),
+
+            // Strings.
+            "GET" => ("get", Read),
+            "MGET" => ("mget", Read),
+            "STRLEN" => ("strlen", Read),
+            "GETRANGE" => ("getrange", Read),
+            "SUBSTR" => ("substr", Read),
+            "LCS" => ("lcs", Read),
+            "SET" => ("set", Write),
+            "SETNX" => ("setnx", Write),
+            "SETEX" => ("setex", Write),
+            "PSETEX" => ("psetex", Write),
+            "MSET" => ("mset", Write),
+            "MSETNX" => ("msetnx", Write),
+            "GETSET" => ("getset", Write),
+            "GETDEL" => ("getdel", Write),
+            "GETEX" => ("getex", Write),
+            "APPEND" => ("append", Write),
+            "SETRANGE" => ("setrange", Write),
+            "INCR" => ("incr", Write),
+            "INCRBY" => ("incrby", Write),
+            "INCRBYFLOAT" => ("incrbyfloat", Write),
+            "DECR" => ("decr", Write),
+            "DECRBY" => ("decrby", Write),
+
+            // Bitmaps and HyperLogLogs.
+            "GETBIT" => ("getbit", Read),
+            "BITCOUNT" => ("bitcount", Read),
+            "BITPOS" => ("bitpos", Read),
+            "BITFIELD_RO" => ("bitfield_ro", Read),
+            "PFCOUNT" => ("pfcount", Read),
+            "SETBIT" => ("setbit", Write),
+            "BITOP" => ("bitop", Write),
+            "BITFIELD" => ("bitfield", Write),
+            "PFADD" => ("pfadd", Write),
+            "PFMERGE" => ("pfmerge", Write),
+
+            // Hashes.
+            "HGET" => ("hget", Read),
+            "HMGET" => ("hmget", Read),
+            "HGETALL" => ("hgetall", Read),
+            "HKEYS" => ("hkeys", Read),
+            "HVALS" => ("hvals", Read),
+            "HLEN" => ("hlen", Read),
+            "HEXISTS" => ("hexists", Read),
+            "HSTRLEN" => ("hstrlen", Read),
+            "HSCAN" => ("hscan", Read),
+            "HRANDFIELD" => ("hrandfield", Read),
+            "HSET" => ("hset", Write),
+            "HSETNX" => ("hsetnx", Write),
+            "HMSET" => ("hmset", Write),
+            "HDEL" => ("hdel", Write),
+            "HINCRBY" => ("hincrby", Write),
+            "HINCRBYFLOAT" => ("hincrbyfloat", Write),
+
+            // Lists.
+            "LRANGE" => ("lrange", Read),
+            "LLEN" => ("llen", Read),
+            "LINDEX" => ("lindex", Read),
+            "LPOS" => ("lpos", Read),
+            "LPUSH" => ("lpush", Write),
+            "RPUSH" => ("rpush", Write),
+            "LPUSHX" => ("lpushx", Write),
+            "RPUSHX" => ("rpushx", Write),
+            "LPOP" => ("lpop", Write),
+            "RPOP" => ("rpop", Write),
+            "LINSERT" => ("linsert", Write),
+            "LSET" => ("lset", Write),
+            "LREM" => ("lrem", Write),
+            "LTRIM" => ("ltrim", Write),
+            "LMOVE" => ("lmove", Write),
+            "LMPOP" => ("lmpop", Write),
+            "RPOPLPUSH" => ("rpoplpush", Write),
+            "BLPOP" => ("blpop", Write),
+            "BRPOP" => ("brpop", Write),
+            "BLMOVE" => ("blmove", Write),
+            "BLMPOP" => ("blmpop", Write),
+            "BRPOPLPUSH" => ("brpoplpush", Write),
+
+            // Sets.
+            "SMEMBERS" => ("smembers", Read),
+            "SISMEMBER" => ("sismember", Read),
+            "SMISMEMBER" => ("smismember", Read),
+            "SCARD" => ("scard", Read),
+            "SRANDMEMBER" => ("srandmember", Read),
+            "SSCAN" => ("sscan", Read),
+            "SINTER" => ("sinter", Read),
+            "SINTERCARD" => ("sintercard", Read),
+            "SUNION" => ("sunion", Read),
+            "SDIFF" => ("sdiff", Read),
+            "SADD" => ("sadd", Write),
+            "SREM" => ("srem", Write),
+            "SPOP" => ("spop", Write),
+            "SMOVE" => ("smove", Write),
+            "SINTERSTORE" => ("sinterstore", Write),
+            "SUNIONSTORE" => ("sunionstore", Write),
+            "SDIFFSTORE" => ("sdiffstore", Write),
+
+            // Sorted sets.
+            "ZRANGE" => ("zrange", Read),
+            "ZRANGEBYSCORE" => ("zrangebyscore", Read),
+            "ZRANGEBYLEX" => ("zrangebylex", Read),
+            "ZREVRANGE" => ("zrevrange", Read),
+            "ZREVRANGEBYSCORE" => ("zrevrangebyscore", Read),
+            "ZREVRANGEBYLEX" => ("zrevrangebylex", Read),
+            "ZSCORE" => ("zscore", Read),
+            "ZMSCORE" => ("zmscore", Read),
+            "ZCARD" => ("zcard", Read),
+            "ZCOUNT" => ("zcount", Read),
+            "ZLEXCOUNT" => ("zlexcount", Read),
+            "ZRANK" => ("zrank", Read),
+            "ZREVRANK" => ("zrevrank", Read),
+            "ZSCAN" => ("zscan", Read),
+            "ZRANDMEMBER" => ("zrandmember", Read),
+            "ZINTER" => ("zinter", Read),
+            "ZINTERCARD" => ("zintercard", Read),
+            "ZUNION" => ("zunion", Read),
+            "ZDIFF" => ("zdiff", Read),
+            "ZADD" => ("zadd", Write),
+            "ZINCRBY" => ("zincrby", Write),
+            "ZREM" => ("zrem", Write),
+            "ZREMRANGEBYSCORE" => ("zremrangebyscore", Write),
+            "ZREMRANGEBYRANK" => ("zremrangebyrank", Write),
+            "ZREMRANGEBYLEX" => ("zremrangebylex", Write),
+            "ZPOPMIN" => ("zpopmin", Write),
+            "ZPOPMAX" => ("zpopmax", Write),
+            "BZPOPMIN" => ("bzpopmin", Write),
+            "BZPOPMAX" => ("bzpopmax", Write),
+            "ZMPOP" => ("zmpop", Write),
+            "BZMPOP" => ("bzmpop", Write),
+            "ZRANGESTORE" => ("zrangestore", Write),
+            "ZINTERSTORE" => ("zinterstore", Write),
+            "ZUNIONSTORE" => ("zunionstore", Write),
+            "ZDIFFSTORE" => ("zdiffstore", Write),
+
+            // Geospatial indexes.
+            "GEODIST" => ("geodist", Read),
+            "GEOHASH" => ("geohash", Read),
+            "GEOPOS" => ("geopos", Read),
+            "GEOSEARCH" => ("geosearch", Read),
+            "GEORADIUS_RO" => ("georadius_ro", Read),
+            "GEORADIUSBYMEMBER_RO" => ("georadiusbymember_ro", Read),
+            "GEOADD" => ("geoadd", Write),
+            "GEOSEARCHSTORE" => ("geosearchstore", Write),
+            "GEORADIUS" => ("georadius", Write),
+            "GEORADIUSBYMEMBER" => ("georadiusbymember", Write),
+
+            // Streams.
+            "XRANGE" => ("xrange", Read),
+            "XREVRANGE" => ("xrevrange", Read),
+            "XLEN" => ("xlen", Read),
+            "XREAD" => ("xread", Read),
+            "XINFO" => ("xinfo", Read),
+            "XPENDING" => ("xpending", Read),
+            "XADD" => ("xadd", Write),
+            "XDEL" => ("xdel", Write),
+            "XTRIM" => ("xtrim", Write),
+            "XACK" => ("xack", Write),
+            "XCLAIM" => ("xclaim", Write),
+            "XAUTOCLAIM" => ("xautoclaim", Write),
+            "XGROUP" => ("xgroup", Write),
+            "XREADGROUP" => ("xreadgroup", Write),
+            "XSETID" => ("xsetid", Write),
+
+            // Scripting.
+            "EVAL_RO" => ("eval_ro", Read),
+            "EVALSHA_RO" => ("evalsha_ro", Read),
+            "FCALL_RO" => ("fcall_ro", Read),
+            "EVAL" => ("eval", Write),
+            "EVALSHA" => ("evalsha", Write),
+            "FCALL" => ("fcall", Write),
+            "FUNCTION" => ("function", Write),
+            "SCRIPT" => ("script", Write),
+
+            // Pub/sub commands that do not change the connection's mode.
+            "PUBLISH" => ("publish", Write),
+            "SPUBLISH" => ("spublish", Write),
+            "PUBSUB" => ("pubsub", Write),
+
+            // Server management.
+            "PING" => ("ping", Write),
+            "ECHO" => ("echo", Write),
+            "QUIT" => ("quit", Write),
+            "INFO" => ("info", Write),
+            "TIME" => ("time", Write),
+            "ROLE" => ("role", Write),
+            "WAIT" => ("wait", Write),
+            "COMMAND" => ("command", Write),
+            "CONFIG" => ("config", Write),
+            "FLUSHDB" => ("flushdb", Write),
+            "FLUSHALL" => ("flushall", Write),
+            "SWAPDB" => ("swapdb", Write),
+            "SAVE" => ("save", Write),
+            "BGSAVE" => ("bgsave", Write),
+            "BGREWRITEAOF" => ("bgrewriteaof", Write),
+            "LASTSAVE" => ("lastsave", Write),
+            "SHUTDOWN" => ("shutdown", Write),
+            "DEBUG" => ("debug", Write),
+            "SLOWLOG" => ("slowlog", Write),
+            "MEMORY" => ("memory", Write),
+            "LATENCY" => ("latency", Write),
+            "ACL" => ("acl", Write),
+            "MODULE" => ("module", Write),
+            "REPLICAOF" => ("replicaof", Write),
+            "SLAVEOF" => ("slaveof", Write),
+            "MIGRATE" => ("migrate", Write),
+            "CLUSTER" => ("cluster", Write),
+
+            _ => return Self::UNKNOWN,
+        };
+        Self { name, kind }
+    }
+
+    /// Returns true for commands (e.g. `SUBSCRIBE` or `MONITOR`) after which
+    /// the server sends messages that do not answer commands.
+    pub(crate) fn is_passthrough(&self) -> bool {
+        self.kind == Kind::Passthrough
+    }
+
+    const UNKNOWN: Self = Self {
+        name: "unknown",
+        kind: Kind::Write,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            Command::parse(b"get"),
+            Command {
+                name: "get",
+                kind: Kind::Read
+            }
+        );
+        assert_eq!(Command::parse(b"FlushAll").name, "flushall");
+        assert_eq!(Command::parse(b"multi").kind, Kind::Multi);
+        assert!(!Command::parse(b"multi").is_passthrough());
+        assert!(Command::parse(b"Subscribe").is_passthrough());
+        assert_eq!(Command::parse(b"NOPE"), Command::UNKNOWN);
+        assert_eq!(Command::parse(&[b'A'; 64]), Command::UNKNOWN);
+        assert_eq!(Command::parse(b"\xff"), Command::UNKNOWN);
+    }
+}
@@ -0,0 +1,21 @@
+//! Redis protocol awareness for opaque TCP connections.
+//!
+//! Commands are framed as they are proxied so that replies can be attributed
+//! to the commands that caused them. This enables per-command metrics,
+//! denying commands by policy, and splitting read-only commands from writes
+//! across a primary and a replica.
+
+#![deny(rust_2018_idioms, clippy::disallowed_methods, clippy::disallowed_types)]
+#![forbid(unsafe_code)]
+
+mod command;
+mod metrics;
+mod proxy;
+pub mod resp;
+mod split;
+
+pub use self::{
+    metrics::{CommandMetrics, CommandMetricsFamilies},
+    proxy::{ClientIo, Params},
+    split::split,
+};
@@ -0,0 +1,159 @@
+use prometheus_client::{
+    encoding::EncodeLabelSet,
+    metrics::{
+        counter::Counter,
+        family::{Family, MetricConstructor},
+        histogram::Histogram,
+    },
+    registry::{Registry, Unit},
+};
+use std::{fmt::Debug, hash::Hash};
+use tokio::time;
+
+#[derive(Clone, Debug)]
+pub struct CommandMetricsFamilies<L>
+where
+    L: Clone + Hash + Eq + EncodeLabelSet + Debug + Send + Sync + 'static,
+{
+    duration: Family<CommandLabels<L>, Histogram, MkDurations>,
+    errors: Family<CommandLabels<L>, Counter>,
+    denied: Family<CommandLabels<L>, Counter>,
+}
+
+/// Records metrics for the commands sent on a connection.
+#[derive(Clone, Debug)]
+pub struct CommandMetrics<L>
+where
+    L: Clone + Hash + Eq + EncodeLabelSet + Debug + Send + Sync + 'static,
+{
+    families: CommandMetricsFamilies<L>,
+    labels: L,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+struct CommandLabels<L>
+where
+    L: Clone + Hash + Eq + EncodeLabelSet + Debug + Send + Sync + 'static,
+{
+    command: &'static str,
+    labels: L,
+}
+
+#[derive(Clone, Debug, Default)]
+struct MkDurations;
+
+// === impl CommandMetricsFamilies ===
+
+impl<L> Default for CommandMetricsFamilies<L>
+where
+    L: Clone + Hash + Eq + EncodeLabelSet + Debug + Send + Sync + 'static,
+{
+    fn default() -> Self {
+        Self {
+            duration: Family::new_with_constructor(MkDurations),
+            errors: Family::default(),
+            denied: Family::default(),
+        }
+    }
+}
+
+impl<L> CommandMetricsFamilies<L>
+where
+    L: Clone + Hash + Eq + EncodeLabelSet + Debug + Send + Sync + 'static,
+{
+    pub fn register(reg: &mut Registry) -> Self {
+        let duration = Family::new_with_constructor(MkDurations);
+        reg.register_with_unit(
+            "command_duration",
+            "Time from sending a command to receiving its reply",
+            Unit::Seconds,
+            duration.clone(),
+        );
+
+        let errors = Family::default();
+        reg.register(
+            "command_errors",
+            "Commands that received an error reply",
+            errors.clone(),
+        );
+
+        let denied = Family::default();
+        reg.register(
+            "command_denied",
+            "Commands that were rejected by policy",
+            denied.clone(),
+        );
+
+        Self {
+            duration,
+            errors,
+            denied,
+        }
+    }
+
+    pub fn metrics(&self, labels: L) -> CommandMetrics<L> {
+        CommandMetrics {
+            families: self.clone(),
+            labels,
+        }
+    }
+}
+
+// === impl CommandMetrics ===
+
+impl<L> CommandMetrics<L>
+where
+    L: Clone + Hash + Eq + EncodeLabelSet + Debug + Send + Sync + 'static,
+{
+    pub(crate) fn reply(&self, command: &'static str, elapsed: time::Duration, error: bool) {
+        let labels = self.labels(command);
+        self.families
+            .duration
+            .get_or_create(&labels)
+            .observe(elapsed.as_secs_f64());
+        if error {
+            self.families.errors.get_or_create(&labels).inc();
+        }
+    }
+
+    pub(crate) fn denied(&self, command: &'static str) {
+        self.families
+            .denied
+            .get_or_create(&self.labels(command))
+            .inc();
+    }
+
+    fn labels(&self, command: &'static str) -> CommandLabels<L> {
+        CommandLabels {
+            command,
+            labels: self.labels.clone(),
+        }
+    }
+}
+
+// === impl CommandLabels ===
+
+impl<L> EncodeLabelSet for CommandLabels<L>
+where
+    L: Clone + Hash + Eq + EncodeLabelSet + Debug + Send + Sync + 'static,
+{
+    fn encode(
+        &self,
+        enc: &mut prometheus_client::encoding::LabelSetEncoder<'_>,
+    ) -> Result<(), std::fmt::Error> {
+        use prometheus_client::encoding::EncodeLabel;
+
+        ("command", self.command).encode(enc.encode_label())?;
+        self.labels.encode(enc)?;
+
+        Ok(())
+    }
+}
+
+// === impl MkDurations ===
+
+impl MetricConstructor<Histogram> for MkDurations {
+    fn new_metric(&self) -> Histogram {
+        Histogram::new([0.0005, 0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0])
+    }
+}
@@ -0,0 +1,652 @@
+use crate::{
+    command::Command,
+    metrics::CommandMetrics,
+    resp::{self, ReplyKind, Scanner},
+};
+use bytes::{Buf, Bytes, BytesMut};
+use linkerd_io as io;
+use prometheus_client::encoding::EncodeLabelSet;
+use std::{
+    collections::VecDeque,
+    fmt::Debug,
+    hash::Hash,
+    pin::Pin,
+    sync::Arc,
+    task::{ready, Context, Poll},
+};
+use tokio::time;
+use tracing::{debug, trace};
+
+/// Configures how commands are proxied.
+#[derive(Clone, Debug)]
+pub struct Params<L>
+where
+    L: Clone + Hash + Eq + EncodeLabelSet + Debug + Send + Sync + 'static,
+{
+    /// Commands that are answered with an error instead of being sent to a
+    /// server. Names are matched case-insensitively.
+    pub deny_commands: Arc<[Arc<str>]>,
+    pub metrics: CommandMetrics<L>,
+}
+
+/// A client's connection, which inspects Redis commands and replies as they
+/// are forwarded to and from a server.
+///
+/// The stack that forwards the connection reads commands from it and writes
+/// the server's replies to it, so commands and replies are not buffered
+/// beyond the lines that frame them. Replies are attributed to the commands
+/// that caused them in the order in which commands were sent, so pipelining
+/// is preserved.
+///
+/// Denied commands are not forwarded. They are answered with a `NOPERM` error,
+/// in order with the server's replies. Note that a denied command does not
+/// abort a transaction that is being queued.
+///
+/// Once the client subscribes to channels or runs `MONITOR`, the server sends
+/// messages that do not answer commands, so replies are no longer attributed
+/// to commands. Denied commands are still rejected, and their replies are
+/// written between the server's messages.
+#[derive(Debug)]
+pub struct ClientIo<I, L>
+where
+    L: Clone + Hash + Eq + EncodeLabelSet + Debug + Send + Sync + 'static,
+{
+    io: I,
+    params: Params<L>,
+    commands: Commands,
+    /// The reply that the server is sending, if one is in progress.
+    reply: Option<ReplyKind>,
+    replies: Scanner,
+    /// Commands that await replies, in the order in which they were sent.
+    pending: VecDeque<Pending>,
+    /// Bytes that have not yet been written to the client.
+    tx: BytesMut,
+}
+
+/// Commands read from the client.
+#[derive(Debug, Default)]
+struct Commands {
+    buf: BytesMut,
+    /// The number of bytes at the front of `buf` that may be forwarded.
+    ready: usize,
+    scanner: Scanner,
+    state: State,
+    /// Set once commands no longer await replies.
+    passthrough: bool,
+    eof: bool,
+}
+
+#[derive(Debug, Default)]
+enum State {
+    /// Awaiting the head of a command.
+    #[default]
+    Head,
+    /// Scanning the arguments of a command, which are discarded if the command
+    /// was denied.
+    Args { forward: bool },
+}
+
+#[derive(Debug)]
+enum Pending {
+    Command {
+        command: Command,
+        sent: time::Instant,
+    },
+    /// A denied command, which is answered with this reply once the replies to
+    /// the commands before it have been returned.
+    Denied(Bytes),
+    /// Replies are no longer attributed to commands once this command's reply
+    /// is due.
+    Passthrough,
+}
+
+const BUFFER_CAPACITY: usize = 16 * 1024;
+
+/// Commands are no longer read while this many bytes await being written to
+/// the client, so that a client that does not read the replies to its denied
+/// commands cannot exhaust the proxy's memory. Replies from the server are
+/// accepted in chunks of at most [`BUFFER_CAPACITY`] bytes, so they alone do
+/// not stop commands from being read.
+const MAX_UNWRITTEN: usize = 4 * BUFFER_CAPACITY;
+
+// === impl ClientIo ===
+
+impl<I, L> ClientIo<I, L>
+where
+    L: Clone + Hash + Eq + EncodeLabelSet + Debug + Send + Sync + 'static,
+{
+    pub fn new(io: I, params: Params<L>) -> Self {
+        Self {
+            io,
+            params,
+            commands: Commands::default(),
+            reply: None,
+            replies: Scanner::default(),
+            pending: VecDeque::new(),
+            tx: BytesMut::new(),
+        }
+    }
+
+    /// Frames the commands that the client has sent, so that permitted commands
+    /// may be forwarded and denied commands are discarded.
+    fn read_commands(&mut self) -> Result<(), resp::ProtocolError> {
+        let cmds = &mut self.commands;
+        loop {
+            match cmds.state {
+                State::Args { forward } => {
+                    // The arguments of a denied command are discarded once the
+                    // commands before it have been forwarded.
+                    if !forward && cmds.ready > 0 {
+                        return Ok(());
+                    }
+                    let buf = &cmds.buf[cmds.ready..];
+                    let end = cmds.scanner.scan(buf)?;
+                    let n = end.unwrap_or(buf.len());
+                    if forward {
+                        cmds.ready += n;
+                    } else {
+                        cmds.buf.advance(n);
+                    }
+                    if end.is_none() {
+                        return Ok(());
+                    }
+                    cmds.state = State::Head;
+                }
+
+                State::Head => {
+                    let Some(req) = resp::request(&cmds.buf[cmds.ready..])? else {
+                        return Ok(());
+                    };
+                    if req.name.is_empty() {
+                        // Servers ignore empty inline commands without
+                        // replying.
+                        cmds.ready += req.len;
+                        continue;
+                    }
+
+                    let command = Command::parse(req.name);
+                    let (len, frames) = (req.len, req.frames);
+                    if is_denied(&self.params.deny_commands, req.name) {
+                        if cmds.ready > 0 {
+                            return Ok(());
+                        }
+                        debug!(command = command.name, "Command denied");
+                        self.params.metrics.denied(command.name);
+                        self.pending
+                            .push_back(Pending::Denied(denied_reply(req.name)));
+                        cmds.buf.advance(len);
+                        cmds.scanner.start(frames);
+                        cmds.state = State::Args { forward: false };
+                        if self.reply.is_none() {
+                            Self::release_denied(&mut self.pending, &mut self.tx);
+                        }
+                    } else {
+                        trace!(command = command.name, "Forwarding command");
+                        // Once replies are no longer attributed to commands,
+                        // commands do not await them.
+                        if !cmds.passthrough {
+                            if command.is_passthrough() {
+                                debug!(command = command.name, "Passing replies through");
+                                self.pending.push_back(Pending::Passthrough);
+                                cmds.passthrough = true;
+                            } else {
+                                self.pending.push_back(Pending::Command {
+                                    command,
+                                    sent: time::Instant::now(),
+                                });
+                            }
+                        }
+                        cmds.ready += len;
+                        cmds.scanner.start(frames);
+                        cmds.state = State::Args { forward: true };
+                    }
+                }
+            }
+        }
+    }
+
+    /// Scans the bytes that the server sends, recording the commands that
+    /// they answer and returning replies to denied commands in order.
+    fn write_replies(&mut self, mut buf: &[u8]) -> Result<(), resp::ProtocolError> {
+        while let Some(&first) = buf.first() {
+            let kind = match self.reply {
+                Some(kind) => kind,
+                None => {
+                    self.replies.start(1);
+                    *self.reply.insert(resp::reply_kind(first))
+                }
+            };
+            let Some(n) = self.replies.scan(buf)? else {
+                self.tx.extend_from_slice(buf);
+                return Ok(());
+            };
+            self.tx.extend_from_slice(&buf[..n]);
+            buf = &buf[n..];
+            self.reply = None;
+
+            // Push messages do not answer commands.
+            if kind != ReplyKind::Push {
+                self.replied(kind);
+            }
+            Self::release_denied(&mut self.pending, &mut self.tx);
+        }
+        Ok(())
+    }
+
+    fn replied(&mut self, kind: ReplyKind) {
+        match self.pending.front() {
+            Some(Pending::Command { command, sent }) => {
+                let elapsed = time::Instant::now().saturating_duration_since(*sent);
+                self.params
+                    .metrics
+                    .reply(command.name, elapsed, kind == ReplyKind::Error);
+                self.pending.pop_front();
+            }
+            Some(Pending::Passthrough) => {}
+            _ => debug!(?kind, "Server replied without a command"),
+        }
+    }
+
+    /// Returns the replies to denied commands that are due, i.e. those that
+    /// are not preceded by commands that await replies.
+    fn release_denied(pending: &mut VecDeque<Pending>, tx: &mut BytesMut) {
+        let i = usize::from(matches!(pending.front(), Some(Pending::Passthrough)));
+        while let Some(Pending::Denied(_)) = pending.get(i) {
+            if let Some(Pending::Denied(reply)) = pending.remove(i) {
+                tx.extend_from_slice(&reply);
+            }
+        }
+    }
+}
+
+impl<I, L> ClientIo<I, L>
+where
+    I: io::AsyncWrite + Unpin,
+    L: Clone + Hash + Eq + EncodeLabelSet + Debug + Send + Sync + 'static,
+{
+    /// Writes buffered bytes to the client.
+    fn poll_drain(&mut self, cx: &mut Context<'_>) -> io::Poll<()> {
+        while !self.tx.is_empty() {
+            let n = ready!(io::poll_write_buf(Pin::new(&mut self.io), cx, &mut self.tx))?;
+            if n == 0 {
+                return Poll::Ready(Err(io::ErrorKind::WriteZero.into()));
+            }
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<I, L> io::AsyncRead for ClientIo<I, L>
+where
+    I: io::AsyncRead + io::AsyncWrite + Unpin,
+    L: Clone + Hash + Eq + EncodeLabelSet + Debug + Send + Sync + Unpin + 'static,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut io::ReadBuf<'_>,
+    ) -> io::Poll<()> {
+        let this = self.get_mut();
+        loop {
+            let cmds = &mut this.commands;
+            if cmds.ready > 0 {
+                let n = cmds.ready.min(buf.remaining());
+                buf.put_slice(&cmds.buf[..n]);
+                cmds.buf.advance(n);
+                cmds.ready -= n;
+                return Poll::Ready(Ok(()));
+            }
+            if cmds.eof {
+                return Poll::Ready(Ok(()));
+            }
+
+            this.read_commands().map_err(invalid_data)?;
+
+            // The server does not reply to denied commands, so their replies
+            // are written as commands are read.
+            if this.poll_drain(cx)?.is_pending() && this.tx.len() >= MAX_UNWRITTEN {
+                return Poll::Pending;
+            }
+            if this.commands.ready > 0 {
+                continue;
+            }
+
+            let cmds = &mut this.commands;
+            if cmds.buf.capacity() == cmds.buf.len() {
+                cmds.buf.reserve(BUFFER_CAPACITY);
+            }
+            if ready!(io::poll_read_buf(Pin::new(&mut this.io), cx, &mut cmds.buf))? == 0 {
+                trace!("Client closed");
+                cmds.eof = true;
+                // An incomplete command is forwarded as it was sent, unless it
+                // was denied.
+                if !matches!(cmds.state, State::Args { forward: false }) {
+                    cmds.ready = cmds.buf.len();
+                }
+            }
+        }
+    }
+}
+
+impl<I, L> io::AsyncWrite for ClientIo<I, L>
+where
+    I: io::AsyncWrite + Unpin,
+    L: Clone + Hash + Eq + EncodeLabelSet + Debug + Send + Sync + Unpin + 'static,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> io::Poll<usize> {
+        let this = self.get_mut();
+        ready!(this.poll_drain(cx))?;
+
+        let buf = &buf[..buf.len().min(BUFFER_CAPACITY)];
+        this.write_replies(buf).map_err(invalid_data)?;
+        // The write has been accepted; it completes as the connection is
+        // flushed.
+        let _ = this.poll_drain(cx)?;
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> io::Poll<()> {
+        let this = self.get_mut();
+        ready!(this.poll_drain(cx))?;
+        Pin::new(&mut this.io).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> io::Poll<()> {
+        let this = self.get_mut();
+        ready!(this.poll_drain(cx))?;
+        Pin::new(&mut this.io).poll_shutdown(cx)
+    }
+}
+
+impl<I, L> io::PeerAddr for ClientIo<I, L>
+where
+    I: io::PeerAddr,
+    L: Clone + Hash + Eq + EncodeLabelSet + Debug + Send + Sync + 'static,
+{
+    fn peer_addr(&self) -> io::Result<std::net::SocketAddr> {
+        self.io.peer_addr()
+    }
+}
+
+pub(crate) fn is_denied(deny: &[Arc<str>], name: &[u8]) -> bool {
+    deny.iter().any(|d| d.as_bytes().eq_ignore_ascii_case(name))
+}
+
+pub(crate) fn denied_reply(name: &[u8]) -> Bytes {
+    let name = name
+        .iter()
+        .filter(|b| b.is_ascii_graphic() && **b != b'\'')
+        .take(64)
+        .map(|b| char::from(b.to_ascii_lowercase()))
+        .collect::<String>();
+    format!("-NOPERM the '{name}' command is not allowed by policy\r\n").into()
+}
+
+pub(crate) fn invalid_data(e: resp::ProtocolError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CommandMetricsFamilies;
+    use io::{AsyncReadExt, AsyncWriteExt};
+    use prometheus_client::encoding::text::encode;
+    use prometheus_client::registry::Registry;
+
+    #[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+    struct Labels {
+        route: &'static str,
+    }
+
+    fn params(deny: &[&str]) -> (Params<Labels>, Registry) {
+        let mut registry = Registry::default();
+        let families = CommandMetricsFamilies::register(&mut registry);
+        let params = Params {
+            deny_commands: deny.iter().map(|d| Arc::from(*d)).collect(),
+            metrics: families.metrics(Labels { route: "test" }),
+        };
+        (params, registry)
+    }
+
+    /// Forwards a client's connection to a server, as an opaque backend does.
+    fn forward(
+        params: Params<Labels>,
+    ) -> (
+        io::DuplexStream,
+        io::DuplexStream,
+        tokio::task::JoinHandle<io::Result<(u64, u64)>>,
+    ) {
+        let (client, proxy_client) = io::duplex(1024);
+        let (server, mut proxy_server) = io::duplex(1024);
+        let task = tokio::spawn(async move {
+            let mut client = ClientIo::new(proxy_client, params);
+            tokio::io::copy_bidirectional(&mut client, &mut proxy_server).await
+        });
+        (client, server, task)
+    }
+
+    /// Reads exactly `expected.len()` bytes from `io` and compares them.
+    async fn expect(io: &mut io::DuplexStream, expected: &[u8]) {
+        let mut buf = vec![0; expected.len()];
+        io.read_exact(&mut buf).await.unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(&buf),
+            String::from_utf8_lossy(expected)
+        );
+    }
+
+    #[tokio::test]
+    async fn pipelines_commands() {
+        let (params, registry) = params(&[]);
+        let (mut client, mut server, task) = forward(params);
+
+        client
+            .write_all(
+                b"*3\r\n$3\r\nSET\r\n$1\r\nk\r\n$1\r\nv\r\n*2\r\n$3\r\nGET\r\n$1\r\nk\r\nPING\r\n",
+            )
+            .await
+            .unwrap();
+        expect(
+            &mut server,
+            b"*3\r\n$3\r\nSET\r\n$1\r\nk\r\n$1\r\nv\r\n*2\r\n$3\r\nGET\r\n$1\r\nk\r\nPING\r\n",
+        )
+        .await;
+
+        // Replies may be split across reads.
+        server.write_all(b"+OK\r\n$1\r").await.unwrap();
+        expect(&mut client, b"+OK\r\n$1\r").await;
+        server.write_all(b"\nv\r\n-ERR nope\r\n").await.unwrap();
+        expect(&mut client, b"\nv\r\n-ERR nope\r\n").await;
+
+        drop(client);
+        drop(server);
+        task.await.unwrap().unwrap();
+
+        let mut text = String::new();
+        encode(&mut text, &registry).unwrap();
+        assert!(
+            text.contains(r#"command_duration_seconds_count{command="get",route="test"} 1"#),
+            "{text}"
+        );
+        assert!(
+            text.contains(r#"command_errors_total{command="ping",route="test"} 1"#),
+            "{text}"
+        );
+        assert!(
+            !text.contains(r#"command_errors_total{command="set""#),
+            "{text}"
+        );
+    }
+
+    #[tokio::test]
+    async fn denies_commands() {
+        let (params, registry) = params(&["FLUSHALL", "config"]);
+        let (mut client, mut server, task) = forward(params);
+
+        client
+            .write_all(b"*1\r\n$4\r\nPING\r\n*1\r\n$8\r\nflushall\r\n*1\r\n$4\r\nPING\r\n")
+            .await
+            .unwrap();
+        expect(&mut server, b"*1\r\n$4\r\nPING\r\n*1\r\n$4\r\nPING\r\n").await;
+
+        // The denial is returned in order with the server's replies.
+        server.write_all(b"+PONG\r\n").await.unwrap();
+        expect(
+            &mut client,
+            b"+PONG\r\n-NOPERM the 'flushall' command is not allowed by policy\r\n",
+        )
+        .await;
+        server.write_all(b"+PONG\r\n").await.unwrap();
+        expect(&mut client, b"+PONG\r\n").await;
+
+        // Denied commands are answered without waiting for the server, and
+        // their arguments are discarded as they are read.
+        client.write_all(b"CONFIG SET x y\r\n").await.unwrap();
+        expect(
+            &mut client,
+            b"-NOPERM the 'config' command is not allowed by policy\r\n",
+        )
+        .await;
+        client
+            .write_all(b"*3\r\n$6\r\nconfig\r\n$3\r\nSET\r\n$4096\r\n")
+            .await
+            .unwrap();
+        client.write_all(&[b'x'; 4096]).await.unwrap();
+        client.write_all(b"\r\nPING\r\n").await.unwrap();
+        expect(
+            &mut client,
+            b"-NOPERM the 'config' command is not allowed by policy\r\n",
+        )
+        .await;
+        expect(&mut server, b"PING\r\n").await;
+
+        drop(client);
+        drop(server);
+        task.await.unwrap().unwrap();
+
+        let mut text = String::new();
+        encode(&mut text, &registry).unwrap();
+        assert!(
+            text.contains(r#"command_denied_total{command="flushall",route="test"} 1"#),
+            "{text}"
+        );
+        assert!(
+            text.contains(r#"command_denied_total{command="config",route="test"} 2"#),
+            "{text}"
+        );
+    }
+
+    #[tokio::test]
+    async fn streams_large_values() {
+        let (params, registry) = params(&[]);
+        let (mut client, mut server, task) = forward(params);
+
+        // Values are forwarded in both directions at once, without being
+        // buffered by the proxy.
+        const LEN: usize = 1024 * 1024;
+        let value = vec![b'v'; LEN];
+        let header = format!("*3\r\n$3\r\nSET\r\n$1\r\nk\r\n${LEN}\r\n");
+        let reply = format!("${LEN}\r\n");
+
+        // Each end writes while it reads, so neither waits for the other to
+        // finish writing.
+        let (mut client_rx, mut client_tx) = tokio::io::split(&mut client);
+        let (mut server_rx, mut server_tx) = tokio::io::split(&mut server);
+        let send_commands = async {
+            client_tx.write_all(header.as_bytes()).await.unwrap();
+            client_tx.write_all(&value).await.unwrap();
+            client_tx.write_all(b"\r\nGET k\r\n").await.unwrap();
+        };
+        let recv_replies = async {
+            let mut buf = vec![0; reply.len() + LEN + 2 + 5];
+            client_rx.read_exact(&mut buf).await.unwrap();
+            assert!(buf.starts_with(reply.as_bytes()));
+            assert!(buf.ends_with(b"\r\n+OK\r\n"));
+        };
+        let serve = async {
+            // The server replies to a previous command while the client is
+            // sending a large value.
+            let send_reply = async {
+                server_tx.write_all(reply.as_bytes()).await.unwrap();
+                server_tx.write_all(&value).await.unwrap();
+                server_tx.write_all(b"\r\n").await.unwrap();
+            };
+            let recv_commands = async {
+                let mut buf = vec![0; header.len() + LEN + 2 + 7];
+                server_rx.read_exact(&mut buf).await.unwrap();
+                assert!(buf.ends_with(b"\r\nGET k\r\n"));
+            };
+            tokio::join!(send_reply, recv_commands);
+            server_tx.write_all(b"+OK\r\n").await.unwrap();
+        };
+        tokio::join!(send_commands, recv_replies, serve);
+
+        drop(client);
+        drop(server);
+        task.await.unwrap().unwrap();
+
+        let mut text = String::new();
+        encode(&mut text, &registry).unwrap();
+        assert!(
+            text.contains(r#"command_duration_seconds_count{command="set",route="test"} 1"#),
+            "{text}"
+        );
+    }
+
+    #[tokio::test]
+    async fn passes_through_subscriptions() {
+        let (params, registry) = params(&["FLUSHALL"]);
+        let (mut client, mut server, task) = forward(params);
+
+        client.write_all(b"PING\r\nSUBSCRIBE ch\r\n").await.unwrap();
+        expect(&mut server, b"PING\r\nSUBSCRIBE ch\r\n").await;
+        server.write_all(b"+PONG\r\n").await.unwrap();
+        expect(&mut client, b"+PONG\r\n").await;
+
+        let subscribed = b"*3\r\n$9\r\nsubscribe\r\n$2\r\nch\r\n:1\r\n";
+        let msg = b"*3\r\n$7\r\nmessage\r\n$2\r\nch\r\n$2\r\nhi\r\n";
+        server.write_all(subscribed).await.unwrap();
+        server.write_all(&msg[..10]).await.unwrap();
+        expect(&mut client, subscribed).await;
+        expect(&mut client, &msg[..10]).await;
+
+        // Denied commands are still rejected, and the rejection is written
+        // once the server's message is complete.
+        client.write_all(b"FLUSHALL\r\nPING\r\n").await.unwrap();
+        expect(&mut server, b"PING\r\n").await;
+        server.write_all(&msg[10..]).await.unwrap();
+        expect(&mut client, &msg[10..]).await;
+        expect(
+            &mut client,
+            b"-NOPERM the 'flushall' command is not allowed by policy\r\n",
+        )
+        .await;
+
+        drop(client);
+        drop(server);
+        task.await.unwrap().unwrap();
+
+        // Messages are not attributed to commands once the client subscribes.
+        let mut text = String::new();
+        encode(&mut text, &registry).unwrap();
+        assert!(
+            text.contains(r#"command_duration_seconds_count{command="ping",route="test"} 1"#),
+            "{text}"
+        );
+        assert!(!text.contains(r#"command="subscribe""#), "{text}");
+    }
+
+    #[tokio::test]
+    async fn rejects_invalid_replies() {
+        let (params, _) = params(&[]);
+        let (mut client, mut server, task) = forward(params);
+
+        client.write_all(b"GET a\r\n").await.unwrap();
+        expect(&mut server, b"GET a\r\n").await;
+        server.write_all(b"?\r\n").await.unwrap();
+
+        let err = task.await.unwrap().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,347 @@
+//! Framing for the [RESP] protocol (versions 2 and 3).
+//!
+//! Frames are not decoded into values: the proxy only needs to know where each
+//! message ends, which command a request invokes, and whether a reply is an
+//! error. Messages are scanned as they are forwarded, so bulk strings are
+//! never buffered.
+//!
+//! [RESP]: https://redis.io/docs/latest/develop/reference/protocol-spec/
+
+/// Redis limits the length of inline commands to 64KB. The lines that frame
+/// other values (and command names) are held to the same limit.
+const MAX_LINE_LEN: usize = 64 * 1024;
+
+#[derive(Debug, thiserror::Error)]
+#[error("invalid RESP frame: {0}")]
+pub struct ProtocolError(&'static str);
+
+/// The head of a request read from a client, through the command name.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct Request<'a> {
+    /// The length of the head in bytes.
+    pub(crate) len: usize,
+    /// The command name, as sent by the client.
+    pub(crate) name: &'a [u8],
+    /// The number of frames (i.e. the command's arguments) that follow the
+    /// head.
+    pub(crate) frames: u64,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum ReplyKind {
+    Value,
+    Error,
+    /// An out-of-band RESP3 push message, which does not answer a request.
+    Push,
+}
+
+/// Finds the end of a message as its bytes are forwarded.
+///
+/// Incomplete lines are retained until they are completed by later bytes, but
+/// bulk strings are skipped without being retained.
+#[derive(Debug, Default)]
+pub(crate) struct Scanner {
+    /// The number of frames that remain in the message, including those
+    /// nested in aggregates.
+    frames: u64,
+    /// The number of bytes that remain in the current bulk string, including
+    /// its CRLF.
+    bulk: u64,
+    line: Vec<u8>,
+}
+
+/// Returns the head of the first request in `buf`, if it is complete.
+///
+/// Requests are usually arrays of bulk strings, but clients may also send
+/// space-delimited "inline" commands, which have no frames after their head.
+pub(crate) fn request(buf: &[u8]) -> Result<Option<Request<'_>>, ProtocolError> {
+    match buf.first() {
+        None => Ok(None),
+        Some(b'*') => {
+            let Some((count, pos)) = header(buf, 0)? else {
+                return Ok(None);
+            };
+            if count < 1 {
+                return Err(ProtocolError("empty command"));
+            }
+            // The command name is the first element of the array.
+            match buf.get(pos) {
+                None => return Ok(None),
+                Some(b'$') => {}
+                Some(_) => return Err(ProtocolError("command must be a bulk string")),
+            }
+            let Some((name_len, start)) = header(buf, pos)? else {
+                return Ok(None);
+            };
+            let end = usize::try_from(name_len)
+                .ok()
+                .filter(|len| *len <= MAX_LINE_LEN)
+                .ok_or(ProtocolError("invalid command name"))?
+                + start;
+            match buf.get(end..end + 2) {
+                None => Ok(None),
+                Some(b"\r\n") => Ok(Some(Request {
+                    len: end + 2,
+                    name: &buf[start..end],
+                    frames: count as u64 - 1,
+                })),
+                Some(_) => Err(ProtocolError("bulk string must end with CRLF")),
+            }
+        }
+        Some(_) => {
+            let Some(end) = buf.iter().position(|b| *b == b'\n') else {
+                if buf.len() > MAX_LINE_LEN {
+                    return Err(ProtocolError("inline command too long"));
+                }
+                return Ok(None);
+            };
+            let name = buf[..end]
+                .split(|b| b.is_ascii_whitespace())
+                .find(|s| !s.is_empty())
+                .unwrap_or_default();
+            Ok(Some(Request {
+                len: end + 1,
+                name,
+                frames: 0,
+            }))
+        }
+    }
+}
+
+/// Returns the kind of reply that begins with `first`.
+pub(crate) fn reply_kind(first: u8) -> ReplyKind {
+    match first {
+        b'-' | b'!' => ReplyKind::Error,
+        b'>' => ReplyKind::Push,
+        _ => ReplyKind::Value,
+    }
+}
+
+// === impl Scanner ===
+
+impl Scanner {
+    /// Begins a message that consists of `frames` frames.
+    pub(crate) fn start(&mut self, frames: u64) {
+        debug_assert!(self.frames == 0 && self.bulk == 0);
+        self.frames = frames;
+    }
+
+    /// Scans the next bytes of the message, returning the number of bytes that
+    /// complete it, or `None` if it continues beyond `buf`.
+    pub(crate) fn scan(&mut self, buf: &[u8]) -> Result<Option<usize>, ProtocolError> {
+        let mut pos = 0;
+        loop {
+            if self.bulk > 0 {
+                // The bulk string's CRLF is not validated; a malformed message
+                // is rejected by the server.
+                let n = usize::try_from(self.bulk)
+                    .unwrap_or(usize::MAX)
+                    .min(buf.len() - pos);
+                self.bulk -= n as u64;
+                pos += n;
+                if self.bulk > 0 {
+                    return Ok(None);
+                }
+            }
+            if self.frames == 0 {
+                return Ok(Some(pos));
+            }
+
+            let rest = &buf[pos..];
+            let Some(i) = rest.iter().position(|b| *b == b'\n') else {
+                if self.line.len() + rest.len() > MAX_LINE_LEN {
+                    return Err(ProtocolError("line too long"));
+                }
+                self.line.extend_from_slice(rest);
+                return Ok(None);
+            };
+            pos += i + 1;
+            if self.line.is_empty() {
+                self.frame(&rest[..=i])?;
+            } else {
+                let mut line = std::mem::take(&mut self.line);
+                line.extend_from_slice(&rest[..=i]);
+                let res = self.frame(&line);
+                line.clear();
+                self.line = line;
+                res?;
+            }
+        }
+    }
+
+    /// Reads the line that begins a frame.
+    fn frame(&mut self, line: &[u8]) -> Result<(), ProtocolError> {
+        let Some((&kind, value)) = line
+            .strip_suffix(b"\r\n")
+            .and_then(|line| line.split_first())
+        else {
+            return Err(ProtocolError("line must end with CRLF"));
+        };
+        self.frames -= 1;
+        match kind {
+            // Simple strings, errors, integers, nulls, booleans, doubles, and
+            // big numbers are a single line.
+            b'+' | b'-' | b':' | b'_' | b'#' | b',' | b'(' => {}
+            // Bulk strings, bulk errors, and verbatim strings are a length
+            // followed by that many bytes. A RESP2 null bulk string has no
+            // body.
+            b'$' | b'!' | b'=' => {
+                if let Ok(len) = u64::try_from(length(value)?) {
+                    self.bulk = len + 2;
+                }
+            }
+            // Arrays, sets, and pushes are a count followed by that many
+            // frames; maps have a key and a value for each entry. Attributes
+            // are a map that precedes the frame they describe.
+            b'*' | b'~' | b'>' | b'%' | b'|' => {
+                let count = u64::try_from(length(value)?).unwrap_or(0);
+                let frames = match kind {
+                    b'%' => count * 2,
+                    b'|' => count * 2 + 1,
+                    _ => count,
+                };
+                self.frames = self
+                    .frames
+                    .checked_add(frames)
+                    .ok_or(ProtocolError("too many frames"))?;
+            }
+            _ => return Err(ProtocolError("unknown frame type")),
+        }
+        Ok(())
+    }
+}
+
+/// Parses the length or count following the type byte at `pos`, returning it
+/// with the position after the header's line.
+fn header(buf: &[u8], pos: usize) -> Result<Option<(i64, usize)>, ProtocolError> {
+    let Some(end) = line_end(buf, pos + 1)? else {
+        return Ok(None);
+    };
+    Ok(Some((length(&buf[pos + 1..end - 2])?, end)))
+}
+
+fn length(value: &[u8]) -> Result<i64, ProtocolError> {
+    std::str::from_utf8(value)
+        .ok()
+        .and_then(|s| s.parse::<i64>().ok())
+        .filter(|n| *n >= -1)
+        .ok_or(ProtocolError("invalid length"))
+}
+
+/// Returns the position after the CRLF that terminates the line starting at
+/// `pos`.
+fn line_end(buf: &[u8], pos: usize) -> Result<Option<usize>, ProtocolError> {
+    let Some(rest) = buf.get(pos..) else {
+        return Ok(None);
+    };
+    match rest.iter().position(|b| *b == b'\n') {
+        Some(0) => Err(ProtocolError("line must end with CRLF")),
+        Some(i) if rest[i - 1] == b'\r' => Ok(Some(pos + i + 1)),
+        Some(_) => Err(ProtocolError("line must end with CRLF")),
+        None if rest.len() > MAX_LINE_LEN => Err(ProtocolError("line too long")),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requests() {
+        const SET: &[u8] = b"*3\r\n$3\r\nSET\r\n$1\r\nk\r\n$5\r\nhello\r\n";
+        const HEAD: usize = b"*3\r\n$3\r\nSET\r\n".len();
+        for i in 0..HEAD {
+            assert_eq!(request(&SET[..i]).unwrap(), None, "{i}");
+        }
+        assert_eq!(
+            request(SET).unwrap(),
+            Some(Request {
+                len: HEAD,
+                name: b"SET",
+                frames: 2,
+            })
+        );
+
+        assert_eq!(
+            request(b"ping\r\n").unwrap(),
+            Some(Request {
+                len: 6,
+                name: b"ping",
+                frames: 0,
+            })
+        );
+        assert_eq!(
+            request(b"  GET k\n").unwrap(),
+            Some(Request {
+                len: 8,
+                name: b"GET",
+                frames: 0,
+            })
+        );
+
+        assert!(request(b"*0\r\n").is_err());
+        assert!(request(b"*1\r\n:1\r\n").is_err());
+        assert!(request(b"*1\r\n$3\r\nGETX\r\n").is_err());
+        assert!(request(b"*1\r\n$1073741824\r\n").is_err());
+    }
+
+    #[test]
+    fn replies() {
+        for (frame, kind) in [
+            (&b"+OK\r\n"[..], ReplyKind::Value),
+            (b"-ERR unknown command\r\n", ReplyKind::Error),
+            (b":1000\r\n", ReplyKind::Value),
+            (b"$-1\r\n", ReplyKind::Value),
+            (b"$0\r\n\r\n", ReplyKind::Value),
+            (b"*-1\r\n", ReplyKind::Value),
+            (b"*2\r\n$1\r\na\r\n*1\r\n:1\r\n", ReplyKind::Value),
+            (b"%1\r\n+key\r\n#t\r\n", ReplyKind::Value),
+            (b"|1\r\n+ttl\r\n:10\r\n$1\r\nv\r\n", ReplyKind::Value),
+            (b"!9\r\nERR oops!\r\n", ReplyKind::Error),
+            (b">3\r\n+message\r\n+ch\r\n+hi\r\n", ReplyKind::Push),
+            (b"=8\r\ntxt:text\r\n", ReplyKind::Value),
+        ] {
+            assert_eq!(reply_kind(frame[0]), kind, "{frame:?}");
+
+            let mut buf = frame.to_vec();
+            buf.extend_from_slice(b"+OK\r\n");
+            let mut scanner = Scanner::default();
+            scanner.start(1);
+            assert_eq!(scanner.scan(&buf).unwrap(), Some(frame.len()), "{frame:?}");
+
+            // The message may be split anywhere.
+            for i in 0..frame.len() {
+                let mut scanner = Scanner::default();
+                scanner.start(1);
+                assert_eq!(scanner.scan(&frame[..i]).unwrap(), None, "{frame:?}[..{i}]");
+                assert_eq!(
+                    scanner.scan(&buf[i..]).unwrap(),
+                    Some(frame.len() - i),
+                    "{frame:?}[{i}..]"
+                );
+            }
+        }
+
+        for invalid in [&b"?\r\n"[..], b"+OK\n", b"$-2\r\n"] {
+            let mut scanner = Scanner::default();
+            scanner.start(1);
+            assert!(scanner.scan(invalid).is_err(), "{invalid:?}");
+        }
+    }
+
+    #[test]
+    fn skips_bulk_strings() {
+        // Large bulk strings are skipped as they are scanned, without being
+        // retained.
+        let mut scanner = Scanner::default();
+        scanner.start(1);
+        assert_eq!(scanner.scan(b"$1073741824\r\n").unwrap(), None);
+        let chunk = vec![0; 1024 * 1024];
+        for _ in 0..1024 {
+            assert_eq!(scanner.scan(&chunk).unwrap(), None);
+        }
+        assert!(scanner.line.capacity() < 1024);
+        assert_eq!(scanner.scan(b"\r\n+OK\r\n").unwrap(), Some(2));
+    }
+}
@@ -0,0 +1,525 @@
+use crate::{
+    command::{Command, Kind},
+    metrics::CommandMetrics,
+    proxy::{denied_reply, invalid_data, is_denied, Params},
+    resp::{self, ReplyKind, Scanner},
+};
+use bytes::{Buf, Bytes, BytesMut};
+use linkerd_io::{self as io, AsyncReadExt, AsyncWriteExt};
+use prometheus_client::encoding::EncodeLabelSet;
+use std::{collections::VecDeque, fmt::Debug, hash::Hash};
+use tokio::{sync::mpsc, time};
+use tracing::{debug, trace};
+
+/// A command that awaits a reply, in the order in which it was sent.
+#[derive(Debug)]
+enum Pending {
+    Command {
+        command: Command,
+        target: Target,
+        sent: time::Instant,
+    },
+    /// A denied command, which is answered with this reply.
+    Denied(Bytes),
+    /// Replies are no longer attributed to commands once this command, which
+    /// was sent to the primary, is due.
+    Passthrough,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Target {
+    Primary,
+    Replica,
+    /// The command was sent to both servers, and the primary's reply is
+    /// returned to the client.
+    Both,
+}
+
+/// Replies read from a server.
+struct Server<R> {
+    io: R,
+    buf: BytesMut,
+    scanner: Scanner,
+    /// The kind of the reply that is being scanned, if one is in progress.
+    reply: Option<ReplyKind>,
+}
+
+const BUFFER_CAPACITY: usize = 16 * 1024;
+
+/// Limits the number of commands that await replies, so that a client that
+/// does not read replies cannot exhaust the proxy's memory.
+const MAX_PENDING: usize = 1024;
+
+/// Proxies Redis commands from `client`, sending read-only commands to
+/// `replica` and all other commands to `primary`.
+///
+/// Read-only commands are sent to the primary within transactions. Commands
+/// that change the connection's state (e.g. `AUTH` or `SELECT`) are sent to
+/// both servers, and the primary's reply is returned. Commands and replies are
+/// forwarded as they are read, and replies are returned to the client in the
+/// order in which commands were sent, so pipelining is preserved. Only the
+/// complete replies of the server whose reply is not yet due are buffered.
+///
+/// Denied commands are not forwarded and are answered with a `NOPERM` error,
+/// as they are by [`ClientIo`](crate::ClientIo).
+///
+/// Once the client subscribes to channels or runs `MONITOR`, all commands are
+/// sent to the primary and its messages are passed through to the client.
+/// Denied commands are still rejected.
+pub async fn split<C, S, L>(client: C, primary: S, replica: S, params: Params<L>) -> io::Result<()>
+where
+    C: io::AsyncRead + io::AsyncWrite + Unpin,
+    S: io::AsyncRead + io::AsyncWrite + Unpin,
+    L: Clone + Hash + Eq + EncodeLabelSet + Debug + Send + Sync + 'static,
+{
+    let (client_rx, client_tx) = tokio::io::split(client);
+    let (primary_rx, primary_tx) = tokio::io::split(primary);
+    let (replica_rx, replica_tx) = tokio::io::split(replica);
+    let (pending_tx, pending_rx) = mpsc::channel(MAX_PENDING);
+
+    let commands = send_commands(client_rx, primary_tx, replica_tx, pending_tx, &params);
+    let replies = send_replies(
+        client_tx,
+        Server::new(primary_rx),
+        Server::new(replica_rx),
+        pending_rx,
+        &params.metrics,
+    );
+    tokio::try_join!(commands, replies)?;
+    Ok(())
+}
+
+/// Reads commands from the client and forwards each to its target.
+async fn send_commands<C, S, L>(
+    mut client: C,
+    mut primary: S,
+    mut replica: S,
+    pending: mpsc::Sender<Pending>,
+    params: &Params<L>,
+) -> io::Result<()>
+where
+    C: io::AsyncRead + Unpin,
+    S: io::AsyncWrite + Unpin,
+    L: Clone + Hash + Eq + EncodeLabelSet + Debug + Send + Sync + 'static,
+{
+    let mut buf = BytesMut::with_capacity(BUFFER_CAPACITY);
+    let mut scanner = Scanner::default();
+    let mut in_multi = false;
+    let mut passthrough = false;
+
+    'commands: loop {
+        while let Some(req) = resp::request(&buf).map_err(invalid_data)? {
+            if req.name.is_empty() {
+                // Servers ignore empty inline commands without replying.
+                buf.advance(req.len);
+                continue;
+            }
+
+            let command = Command::parse(req.name);
+            let (len, frames) = (req.len, req.frames);
+            let (target, entry) = if is_denied(&params.deny_commands, req.name) {
+                debug!(command = command.name, "Command denied");
+                params.metrics.denied(command.name);
+                (None, Some(Pending::Denied(denied_reply(req.name))))
+            } else if passthrough {
+                // Once replies are no longer attributed to commands, commands
+                // do not await them.
+                (Some(Target::Primary), None)
+            } else if command.kind == Kind::Passthrough {
+                debug!(command = command.name, "Passing replies through");
+                passthrough = true;
+                (Some(Target::Primary), Some(Pending::Passthrough))
+            } else {
+                let target = match command.kind {
+                    Kind::Multi => {
+                        in_multi = true;
+                        Target::Primary
+                    }
+                    Kind::EndMulti => {
+                        in_multi = false;
+                        Target::Primary
+                    }
+                    Kind::Read if !in_multi => Target::Replica,
+                    Kind::Connection => Target::Both,
+                    _ => Target::Primary,
+                };
+                let entry = Pending::Command {
+                    command,
+                    target,
+                    sent: time::Instant::now(),
+                };
+                (Some(target), Some(entry))
+            };
+
+            trace!(command = command.name, ?target, "Sending command");
+            if let Some(entry) = entry {
+                if pending.send(entry).await.is_err() {
+                    // Replies are no longer returned to the client.
+                    break 'commands;
+                }
+            }
+
+            // The command's head and arguments are forwarded as they are read.
+            let mut chunk = buf.split_to(len);
+            scanner.start(frames);
+            loop {
+                if let Some(target) = target {
+                    write(&mut primary, &mut replica, target, &chunk).await?;
+                }
+                let end = scanner.scan(&buf).map_err(invalid_data)?;
+                chunk = buf.split_to(end.unwrap_or(buf.len()));
+                if end.is_some() {
+                    if let Some(target) = target {
+                        write(&mut primary, &mut replica, target, &chunk).await?;
+                    }
+                    break;
+                }
+                if read(&mut client, &mut buf, &mut primary, &mut replica).await? == 0 {
+                    trace!("Client closed during a command");
+                    break 'commands;
+                }
+            }
+        }
+
+        if read(&mut client, &mut buf, &mut primary, &mut replica).await? == 0 {
+            trace!("Client closed");
+            break;
+        }
+    }
+
+    let _ = primary.shutdown().await;
+    let _ = replica.shutdown().await;
+    Ok(())
+}
+
+/// Returns replies to the client in the order in which commands were sent.
+async fn send_replies<C, R, L>(
+    mut client: C,
+    mut primary: Server<R>,
+    mut replica: Server<R>,
+    mut pending: mpsc::Receiver<Pending>,
+    metrics: &CommandMetrics<L>,
+) -> io::Result<()>
+where
+    C: io::AsyncWrite + Unpin,
+    R: io::AsyncRead + Unpin,
+    L: Clone + Hash + Eq + EncodeLabelSet + Debug + Send + Sync + 'static,
+{
+    while let Some(entry) = pending.recv().await {
+        match entry {
+            Pending::Denied(reply) => client.write_all(&reply).await?,
+            Pending::Command {
+                command,
+                target,
+                sent,
+            } => {
+                let kind = match target {
+                    Target::Primary => primary.reply(Some(&mut client)).await?,
+                    Target::Replica => replica.reply(Some(&mut client)).await?,
+                    Target::Both => {
+                        let replica_kind = replica.reply(None::<&mut C>).await?;
+                        let kind = primary.reply(Some(&mut client)).await?;
+                        if replica_kind != kind {
+                            debug!(?kind, ?replica_kind, "Replica reply differs from primary");
+                        }
+                        kind
+                    }
+                };
+                let elapsed = time::Instant::now().saturating_duration_since(sent);
+                metrics.reply(command.name, elapsed, kind == ReplyKind::Error);
+            }
+            Pending::Passthrough => {
+                drop(replica);
+                return passthrough(client, primary, pending).await;
+            }
+        }
+        if pending.is_empty() {
+            client.flush().await?;
+        }
+    }
+
+    trace!("Closing connection");
+    let _ = client.shutdown().await;
+    Ok(())
+}
+
+/// Passes the primary's messages through to the client, writing the replies
+/// to denied commands between them.
+async fn passthrough<C, R>(
+    mut client: C,
+    mut primary: Server<R>,
+    mut pending: mpsc::Receiver<Pending>,
+) -> io::Result<()>
+where
+    C: io::AsyncWrite + Unpin,
+    R: io::AsyncRead + Unpin,
+{
+    let mut denied = VecDeque::<Bytes>::new();
+    let mut commands_closed = false;
+    loop {
+        // The primary's messages are scanned so that replies to denied
+        // commands are only written between them.
+        while !primary.buf.is_empty() {
+            if primary.reply.is_none() {
+                primary.scanner.start(1);
+                primary.reply = Some(resp::reply_kind(primary.buf[0]));
+            }
+            let end = primary.scanner.scan(&primary.buf).map_err(invalid_data)?;
+            let chunk = primary.buf.split_to(end.unwrap_or(primary.buf.len()));
+            client.write_all(&chunk).await?;
+            if end.is_some() {
+                primary.reply = None;
+            }
+        }
+        if primary.reply.is_none() {
+            while let Some(reply) = denied.pop_front() {
+                client.write_all(&reply).await?;
+            }
+        }
+        client.flush().await?;
+
+        tokio::select! {
+            entry = pending.recv(), if !commands_closed => match entry {
+                Some(Pending::Denied(reply)) => denied.push_back(reply),
+                Some(_) => {}
+                None => commands_closed = true,
+            },
+            res = primary.io.read_buf(&mut primary.buf) => {
+                if res? == 0 {
+                    trace!("Primary closed");
+                    let _ = client.shutdown().await;
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+/// Writes a command's bytes to its target.
+async fn write<S>(primary: &mut S, replica: &mut S, target: Target, buf: &[u8]) -> io::Result<()>
+where
+    S: io::AsyncWrite + Unpin,
+{
+    if buf.is_empty() {
+        return Ok(());
+    }
+    if matches!(target, Target::Primary | Target::Both) {
+        primary.write_all(buf).await?;
+    }
+    if matches!(target, Target::Replica | Target::Both) {
+        replica.write_all(buf).await?;
+    }
+    Ok(())
+}
+
+/// Reads from the client, flushing the commands that have been forwarded
+/// before waiting for more.
+async fn read<C, S>(
+    client: &mut C,
+    buf: &mut BytesMut,
+    primary: &mut S,
+    replica: &mut S,
+) -> io::Result<usize>
+where
+    C: io::AsyncRead + Unpin,
+    S: io::AsyncWrite + Unpin,
+{
+    primary.flush().await?;
+    replica.flush().await?;
+    if buf.capacity() == buf.len() {
+        buf.reserve(BUFFER_CAPACITY);
+    }
+    client.read_buf(buf).await
+}
+
+// === impl Server ===
+
+impl<R: io::AsyncRead + Unpin> Server<R> {
+    fn new(io: R) -> Self {
+        Self {
+            io,
+            buf: BytesMut::with_capacity(BUFFER_CAPACITY),
+            scanner: Scanner::default(),
+            reply: None,
+        }
+    }
+
+    /// Reads the server's next reply, writing it to `client` as it is read, or
+    /// discarding it if no client is provided.
+    ///
+    /// Push messages that precede the reply are written to the client as
+    /// well.
+    async fn reply<C>(&mut self, mut client: Option<&mut C>) -> io::Result<ReplyKind>
+    where
+        C: io::AsyncWrite + Unpin,
+    {
+        loop {
+            if self.buf.is_empty() {
+                if self.buf.capacity() == 0 {
+                    self.buf.reserve(BUFFER_CAPACITY);
+                }
+                if self.io.read_buf(&mut self.buf).await? == 0 {
+                    return Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "server closed the connection before replying",
+                    ));
+                }
+            }
+
+            let kind = match self.reply {
+                Some(kind) => kind,
+                None => {
+                    self.scanner.start(1);
+                    *self.reply.insert(resp::reply_kind(self.buf[0]))
+                }
+            };
+            let end = self.scanner.scan(&self.buf).map_err(invalid_data)?;
+            let chunk = self.buf.split_to(end.unwrap_or(self.buf.len()));
+            if let Some(client) = client.as_mut() {
+                client.write_all(&chunk).await?;
+            }
+            if end.is_some() {
+                self.reply = None;
+                if kind != ReplyKind::Push {
+                    return Ok(kind);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CommandMetricsFamilies;
+    use prometheus_client::encoding::text::encode;
+    use prometheus_client::registry::Registry;
+    use std::sync::Arc;
+
+    #[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+    struct Labels {
+        route: &'static str,
+    }
+
+    fn params(deny: &[&str]) -> (Params<Labels>, Registry) {
+        let mut registry = Registry::default();
+        let families = CommandMetricsFamilies::register(&mut registry);
+        let params = Params {
+            deny_commands: deny.iter().map(|d| Arc::from(*d)).collect(),
+            metrics: families.metrics(Labels { route: "test" }),
+        };
+        (params, registry)
+    }
+
+    /// Reads exactly `expected.len()` bytes from `io` and compares them.
+    async fn expect(io: &mut io::DuplexStream, expected: &[u8]) {
+        let mut buf = vec![0; expected.len()];
+        io.read_exact(&mut buf).await.unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(&buf),
+            String::from_utf8_lossy(expected)
+        );
+    }
+
+    #[tokio::test]
+    async fn splits_reads() {
+        let (mut client, proxy_client) = io::duplex(1024);
+        let (mut primary, proxy_primary) = io::duplex(1024);
+        let (mut replica, proxy_replica) = io::duplex(1024);
+        let (params, registry) = params(&["FLUSHALL"]);
+        let task = tokio::spawn(split(proxy_client, proxy_primary, proxy_replica, params));
+
+        client
+            .write_all(b"SELECT 1\r\nSET k v\r\nGET k\r\nFLUSHALL\r\nMULTI\r\nGET k\r\nEXEC\r\n")
+            .await
+            .unwrap();
+        expect(
+            &mut primary,
+            b"SELECT 1\r\nSET k v\r\nMULTI\r\nGET k\r\nEXEC\r\n",
+        )
+        .await;
+        expect(&mut replica, b"SELECT 1\r\nGET k\r\n").await;
+
+        // Replies are returned in the order in which commands were sent, even
+        // when the replica replies first.
+        replica.write_all(b"+OK\r\n$1\r\nv\r\n").await.unwrap();
+        primary
+            .write_all(b"+OK\r\n+OK\r\n+OK\r\n+QUEUED\r\n*1\r\n$1\r\nv\r\n")
+            .await
+            .unwrap();
+        expect(
+            &mut client,
+            b"+OK\r\n+OK\r\n$1\r\nv\r\n-NOPERM the 'flushall' command is not allowed by policy\r\n+OK\r\n+QUEUED\r\n*1\r\n$1\r\nv\r\n",
+        )
+        .await;
+
+        drop(client);
+        drop(primary);
+        drop(replica);
+        task.await.unwrap().unwrap();
+
+        let mut text = String::new();
+        encode(&mut text, &registry).unwrap();
+        assert!(
+            text.contains(r#"command_duration_seconds_count{command="get",route="test"} 2"#),
+            "{text}"
+        );
+        assert!(
+            text.contains(r#"command_denied_total{command="flushall",route="test"} 1"#),
+            "{text}"
+        );
+    }
+
+    #[tokio::test]
+    async fn passes_through_subscriptions() {
+        let (mut client, proxy_client) = io::duplex(1024);
+        let (mut primary, proxy_primary) = io::duplex(1024);
+        let (replica, proxy_replica) = io::duplex(1024);
+        let (params, _) = params(&["FLUSHALL"]);
+        let task = tokio::spawn(split(proxy_client, proxy_primary, proxy_replica, params));
+
+        client.write_all(b"SUBSCRIBE ch\r\n").await.unwrap();
+        expect(&mut primary, b"SUBSCRIBE ch\r\n").await;
+
+        let subscribed = b"*3\r\n$9\r\nsubscribe\r\n$2\r\nch\r\n:1\r\n";
+        let msg = b"*3\r\n$7\r\nmessage\r\n$2\r\nch\r\n$2\r\nhi\r\n";
+        primary.write_all(subscribed).await.unwrap();
+        primary.write_all(&msg[..10]).await.unwrap();
+        expect(&mut client, subscribed).await;
+        expect(&mut client, &msg[..10]).await;
+
+        // Read-only commands are sent to the primary, and the rejection of a
+        // denied command is written once the primary's message is complete.
+        client.write_all(b"FLUSHALL\r\nGET k\r\n").await.unwrap();
+        expect(&mut primary, b"GET k\r\n").await;
+        primary.write_all(&msg[10..]).await.unwrap();
+        expect(&mut client, &msg[10..]).await;
+        expect(
+            &mut client,
+            b"-NOPERM the 'flushall' command is not allowed by policy\r\n",
+        )
+        .await;
+
+        drop(client);
+        drop(primary);
+        drop(replica);
+        task.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn fails_when_a_server_closes_before_replying() {
+        let (mut client, proxy_client) = io::duplex(1024);
+        let (primary, proxy_primary) = io::duplex(1024);
+        let (mut replica, proxy_replica) = io::duplex(1024);
+        let (params, _) = params(&[]);
+        let task = tokio::spawn(split(proxy_client, proxy_primary, proxy_replica, params));
+
+        client.write_all(b"GET k\r\n").await.unwrap();
+        expect(&mut replica, b"GET k\r\n").await;
+        drop(replica);
+
+        let err = task.await.unwrap().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        drop(primary);
+    }
+}
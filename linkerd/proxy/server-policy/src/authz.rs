@@ -1,4 +1,5 @@
 use super::Meta;
+use linkerd_identity as id;
 use std::{collections::BTreeSet, sync::Arc};
 
 mod network;
@@ -27,6 +28,8 @@ pub struct Suffix {
     ends_with: String,
 }
 
+const SPIFFE_PREFIX: &str = "spiffe://";
+
 // === impl Suffix ===
 
 impl From<Vec<String>> for Suffix {
@@ -51,6 +54,83 @@ impl Suffix {
     pub fn contains(&self, name: &str) -> bool {
         name.ends_with(&self.ends_with)
     }
+
+    /// Returns the string that identities must end with.
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        &self.ends_with
+    }
+}
+
+// === impl Authentication ===
+
+impl Authentication {
+    /// Returns true if a client authenticated with the given identity is
+    /// permitted.
+    ///
+    /// DNS-like identities must match an identity exactly or end with one of
+    /// the suffixes. SPIFFE IDs must match an identity exactly or match a
+    /// pattern ending in `/*`: `spiffe://example.org/*` permits any workload in
+    /// the `example.org` trust domain, while `spiffe://example.org/ns/web/*`
+    /// permits only workloads under that path.
+    pub fn permits_client_id(&self, client_id: &id::Id) -> bool {
+        let (identities, suffixes) = match self {
+            Self::Unauthenticated | Self::TlsUnauthenticated => return true,
+            Self::TlsAuthenticated {
+                identities,
+                suffixes,
+            } => (identities, suffixes),
+        };
+
+        let name = client_id.to_str();
+        if identities.contains(&*name) {
+            return true;
+        }
+
+        match client_id {
+            id::Id::Dns(_) => suffixes.iter().any(|s| s.contains(&name)),
+            id::Id::Uri(_) => {
+                client_id.spiffe_trust_domain().is_some()
+                    && identities
+                        .iter()
+                        .any(|pattern| spiffe_pattern_matches(pattern, &name))
+            }
+        }
+    }
+}
+
+/// Returns true if `pattern` is a SPIFFE ID wildcard (e.g.
+/// `spiffe://example.org/ns/web/*`) that matches the given SPIFFE ID.
+///
+/// Trust domains are compared case-insensitively; paths must match exactly
+/// on segment boundaries.
+fn spiffe_pattern_matches(pattern: &str, spiffe_id: &str) -> bool {
+    let pattern = match pattern
+        .strip_prefix(SPIFFE_PREFIX)
+        .and_then(|p| p.strip_suffix("/*"))
+    {
+        Some(p) => p,
+        None => return false,
+    };
+    let (td, path) = match spiffe_id
+        .strip_prefix(SPIFFE_PREFIX)
+        .and_then(|id| id.split_once('/'))
+    {
+        Some(parts) => parts,
+        None => return false,
+    };
+
+    let (pattern_td, pattern_path) = pattern.split_once('/').unwrap_or((pattern, ""));
+    if pattern_td.is_empty() || !td.eq_ignore_ascii_case(pattern_td) {
+        return false;
+    }
+
+    if pattern_path.is_empty() {
+        return !path.is_empty();
+    }
+    path.strip_prefix(pattern_path)
+        .and_then(|rest| rest.strip_prefix('/'))
+        .is_some_and(|rest| !rest.is_empty())
 }
 
 #[cfg(feature = "proto")]
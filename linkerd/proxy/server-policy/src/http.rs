@@ -10,6 +10,8 @@ pub enum Filter {
     InjectFailure(filter::InjectFailure),
     Redirect(filter::RedirectRequest),
     RequestHeaders(filter::ModifyHeader),
+    Compression(std::sync::Arc<filter::Compression>),
+    Deadline(filter::Deadline),
     InternalError(&'static str),
 }
 
@@ -32,7 +32,11 @@ pub enum Protocol {
     },
     Http1(Arc<[http::Route]>),
     Http2(Arc<[http::Route]>),
-    Grpc(Arc<[grpc::Route]>),
+    Grpc {
+        routes: Arc<[grpc::Route]>,
+        /// Whether gRPC-Web requests are translated to gRPC for this server.
+        grpc_web: bool,
+    },
     Tls(Arc<[Authorization]>),
     Opaque(Arc<[Authorization]>),
 }
@@ -198,8 +202,14 @@ pub mod proto {
                     local_rate_limit: _,
                 }) => Protocol::Http2(mk_routes!(http, routes, authorizations)?),
 
+                // The API does not yet configure gRPC-Web, so it is disabled
+                // unless the proxy enables it for the server's port (see
+                // `LINKERD2_PROXY_INBOUND_PORTS_GRPC_WEB`).
                 api::proxy_protocol::Kind::Grpc(api::proxy_protocol::Grpc { routes }) => {
-                    Protocol::Grpc(mk_routes!(grpc, routes, authorizations)?)
+                    Protocol::Grpc {
+                        routes: mk_routes!(grpc, routes, authorizations)?,
+                        grpc_web: false,
+                    }
                 }
 
                 api::proxy_protocol::Kind::Tls(_) => Protocol::Tls(authorizations),
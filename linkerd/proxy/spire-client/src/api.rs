@@ -31,6 +31,8 @@ pub struct Svid {
 #[derive(Clone)]
 pub struct SvidUpdate {
     svids: HashMap<Id, Svid>,
+    /// Trust roots for federated trust domains, keyed by trust domain name.
+    federated_bundles: HashMap<String, Vec<DerX509>>,
 }
 
 #[derive(Clone, Debug)]
@@ -43,7 +45,7 @@ pub struct GrpcRecover(ExponentialBackoff);
 
 pub type Watch<S> = StreamWatch<GrpcRecover, Api<S>>;
 
-// === impl Svid ===
+// === impl SvidUpdate ===
 
 impl SvidUpdate {
     pub(super) fn new(svids: Vec<Svid>) -> Self {
@@ -52,8 +54,49 @@ impl SvidUpdate {
             svids_map.insert(svid.spiffe_id.clone(), svid);
         }
 
-        SvidUpdate { svids: svids_map }
+        SvidUpdate {
+            svids: svids_map,
+            federated_bundles: HashMap::default(),
+        }
+    }
+
+    pub(super) fn with_federated_bundles(mut self, bundles: HashMap<String, Vec<DerX509>>) -> Self {
+        self.federated_bundles = bundles;
+        self
+    }
+}
+
+/// Parses the federated bundles from a Workload API response.
+///
+/// Bundles are keyed by the trust domain's SPIFFE ID (e.g.
+/// `spiffe://example.org`) and hold concatenated DER-encoded certificates.
+/// Invalid bundles are skipped.
+fn parse_federated_bundles(bundles: HashMap<String, Vec<u8>>) -> HashMap<String, Vec<DerX509>> {
+    bundles
+        .into_iter()
+        .filter_map(|(td_id, der)| match parse_bundle(&td_id, &der) {
+            Ok(bundle) => Some(bundle),
+            Err(error) => {
+                error!(trust_domain = %td_id, "could not parse federated bundle: {}", error);
+                None
+            }
+        })
+        .collect()
+}
+
+fn parse_bundle(td_id: &str, der: &[u8]) -> Result<(String, Vec<DerX509>)> {
+    let td = Id::parse_uri(td_id)?
+        .spiffe_trust_domain()
+        .ok_or("not a SPIFFE trust domain")?
+        .to_string();
+    let certs = asn1::from_der(der)?
+        .iter()
+        .map(|block| asn1::to_der(block).map(DerX509))
+        .collect::<Result<Vec<_>, _>>()?;
+    if certs.is_empty() {
+        return Err("empty bundle".into());
     }
+    Ok((td, certs))
 }
 
 // === impl Svid ===
@@ -160,6 +203,7 @@ where
             Ok(rsp.map(|svids| {
                 svids
                     .map_ok(move |s| {
+                        let federated_bundles = parse_federated_bundles(s.federated_bundles);
                         let svids = s
                             .svids
                             .into_iter()
@@ -171,7 +215,7 @@ where
                             })
                             .collect();
 
-                        SvidUpdate::new(svids)
+                        SvidUpdate::new(svids).with_federated_bundles(federated_bundles)
                     })
                     .boxed()
             }))
@@ -211,7 +255,8 @@ where
         let exp: u64 = parsed_cert.validity().not_after.timestamp().try_into()?;
         let exp = UNIX_EPOCH + Duration::from_secs(exp);
 
-        return credentials.set_certificate(svid.leaf, svid.intermediates, svid.private_key, exp);
+        credentials.set_certificate(svid.leaf, svid.intermediates, svid.private_key, exp)?;
+        return credentials.set_federated_roots(update.federated_bundles);
     }
 
     Err(NoMatchingSVIDFound(()).into())
@@ -219,7 +264,7 @@ where
 
 #[cfg(test)]
 mod tests {
-    use crate::api::Svid;
+    use crate::api::{parse_federated_bundles, Svid};
     use rcgen::{CertificateParams, KeyPair, SanType};
     use spiffe_proto::client as api;
 
@@ -266,4 +311,25 @@ mod tests {
         svid_pb.x509_svid_key = Vec::default();
         assert!(Svid::try_from(svid_pb).is_err());
     }
+
+    #[test]
+    fn parses_federated_bundles() {
+        let key = KeyPair::generate().expect("should generate key");
+        let ca = CertificateParams::default()
+            .self_signed(&key)
+            .expect("should generate cert");
+
+        let bundles = parse_federated_bundles(
+            [
+                ("spiffe://example.org".to_string(), ca.der().to_vec()),
+                ("example.com".to_string(), ca.der().to_vec()),
+                ("spiffe://example.net".to_string(), Vec::default()),
+            ]
+            .into_iter()
+            .collect(),
+        );
+        assert_eq!(bundles.len(), 1);
+        assert_eq!(bundles["example.org"].len(), 1);
+        assert_eq!(bundles["example.org"][0].0, ca.der().to_vec());
+    }
 }
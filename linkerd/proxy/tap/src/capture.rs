@@ -0,0 +1,397 @@
+use bytes::BytesMut;
+use linkerd2_proxy_api::http_types;
+use parking_lot::Mutex;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+/// The value that replaces redacted header values and JSON fields.
+const REDACTED: &str = "REDACTED";
+
+/// Configures how request and response bodies are captured into the events of
+/// taps that extract headers.
+///
+/// Bodies are not captured when `max_body_bytes` is zero.
+#[derive(Clone, Debug, Default)]
+pub struct BodyCapture {
+    /// The number of bytes captured from the start of each body.
+    pub max_body_bytes: usize,
+
+    /// The number of body bytes that a single tap may capture over its
+    /// lifetime.
+    pub tap_limit: usize,
+
+    /// The number of captured body bytes that may be held by all taps at once.
+    pub global_limit: usize,
+
+    /// The content types of bodies that may be captured. Entries may be
+    /// full media types (`application/json`) or wildcards (`text/*`).
+    pub content_types: Vec<String>,
+
+    /// Headers whose values are redacted from tap events.
+    pub redact_headers: Vec<http::HeaderName>,
+
+    /// Fields whose values are redacted from captured JSON bodies.
+    pub redact_json_fields: Vec<String>,
+}
+
+/// Enforces a `BodyCapture` configuration across all taps.
+#[derive(Debug, Default)]
+pub(crate) struct Capturer {
+    config: BodyCapture,
+    /// The number of captured bytes that are held by all taps.
+    in_use: AtomicUsize,
+}
+
+/// A single tap's handle on the `Capturer`.
+#[derive(Clone, Debug)]
+pub(crate) struct TapCapture {
+    capturer: Arc<Capturer>,
+    /// The number of bytes that the tap has captured.
+    captured: Arc<AtomicUsize>,
+    /// Whether the tap captures bodies at all.
+    enabled: bool,
+}
+
+/// The captured prefix of a single request or response body.
+#[derive(Debug)]
+pub(crate) struct Capture {
+    tap: TapCapture,
+    is_json: bool,
+    state: Mutex<State>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    buf: BytesMut,
+    truncated: bool,
+}
+
+// === impl Capturer ===
+
+impl Capturer {
+    pub(crate) fn new(config: BodyCapture) -> Self {
+        Self {
+            config,
+            in_use: AtomicUsize::new(0),
+        }
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.config.max_body_bytes > 0 && self.config.tap_limit > 0
+    }
+
+    fn allows(&self, content_type: &str) -> bool {
+        self.config
+            .content_types
+            .iter()
+            .any(|allowed| match allowed.strip_suffix("/*") {
+                Some(ty) => content_type
+                    .split_once('/')
+                    .is_some_and(|(t, _)| t.eq_ignore_ascii_case(ty)),
+                None => allowed.eq_ignore_ascii_case(content_type),
+            })
+    }
+}
+
+// === impl TapCapture ===
+
+impl TapCapture {
+    /// Returns a handle for a tap. Only taps that extract headers capture
+    /// bodies, though all taps redact headers.
+    pub(crate) fn new(capturer: Arc<Capturer>, extract_headers: bool) -> Self {
+        let enabled = extract_headers && capturer.is_enabled();
+        Self {
+            capturer,
+            captured: Arc::new(AtomicUsize::new(0)),
+            enabled,
+        }
+    }
+
+    /// Indicates whether the tap may capture more bytes.
+    pub(crate) fn is_under_limit(&self) -> bool {
+        !self.enabled || self.captured.load(Ordering::Relaxed) < self.capturer.config.tap_limit
+    }
+
+    /// Starts capturing a body if its content type is allowed.
+    pub(crate) fn capture(&self, headers: &http::HeaderMap) -> Option<Arc<Capture>> {
+        if !self.enabled || !self.is_under_limit() {
+            return None;
+        }
+
+        let content_type = headers
+            .get(http::header::CONTENT_TYPE)?
+            .to_str()
+            .ok()?
+            .split(';')
+            .next()?
+            .trim();
+        if !self.capturer.allows(content_type) {
+            return None;
+        }
+        let is_json = content_type.eq_ignore_ascii_case("application/json")
+            || content_type.to_ascii_lowercase().ends_with("+json");
+
+        Some(Arc::new(Capture {
+            tap: self.clone(),
+            is_json,
+            state: Mutex::default(),
+        }))
+    }
+
+    /// Converts headers to their protobuf form, redacting configured values.
+    pub(crate) fn headers_to_pb(
+        &self,
+        pseudos: impl IntoIterator<Item = http_types::headers::Header>,
+        headers: &http::HeaderMap,
+    ) -> http_types::Headers {
+        let redact = &self.capturer.config.redact_headers;
+        http_types::Headers {
+            headers: pseudos
+                .into_iter()
+                .chain(
+                    headers
+                        .iter()
+                        .map(|(name, value)| http_types::headers::Header {
+                            name: name.as_str().to_owned(),
+                            value: if redact.contains(name) {
+                                REDACTED.into()
+                            } else {
+                                value.as_bytes().into()
+                            },
+                        }),
+                )
+                .collect(),
+        }
+    }
+
+    /// Reserves up to `want` bytes against the tap's and the global limits,
+    /// returning the number of bytes that may be captured.
+    fn reserve(&self, want: usize) -> usize {
+        let config = &self.capturer.config;
+        let granted = reserve(&self.captured, config.tap_limit, want);
+        let held = reserve(&self.capturer.in_use, config.global_limit, granted);
+        if held < granted {
+            self.captured.fetch_sub(granted - held, Ordering::Relaxed);
+        }
+        held
+    }
+}
+
+fn reserve(counter: &AtomicUsize, limit: usize, want: usize) -> usize {
+    let mut granted = 0;
+    let _ = counter.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |used| {
+        granted = want.min(limit.saturating_sub(used));
+        Some(used + granted)
+    });
+    granted
+}
+
+// === impl Capture ===
+
+impl Capture {
+    /// Records a chunk of the body.
+    pub(crate) fn data(&self, chunk: &[u8]) {
+        let mut state = self.state.lock();
+        if state.truncated {
+            return;
+        }
+
+        let remaining = self
+            .tap
+            .capturer
+            .config
+            .max_body_bytes
+            .saturating_sub(state.buf.len());
+        let sz = self.tap.reserve(chunk.len().min(remaining));
+        state.buf.extend_from_slice(&chunk[..sz]);
+        if sz < chunk.len() {
+            state.truncated = true;
+        }
+    }
+
+    /// Describes the captured body as `:<prefix>-body` and
+    /// `:<prefix>-body-truncated` pseudo-headers.
+    ///
+    /// JSON bodies are omitted when fields must be redacted and the body
+    /// cannot be parsed, e.g. because it was truncated.
+    pub(crate) fn to_pb(&self, prefix: &str) -> Vec<http_types::headers::Header> {
+        let state = self.state.lock();
+        let mut headers = Vec::with_capacity(2);
+
+        let fields = &self.tap.capturer.config.redact_json_fields;
+        let body = if self.is_json && !fields.is_empty() {
+            serde_json::from_slice(&state.buf)
+                .ok()
+                .and_then(|mut json| {
+                    redact_json(&mut json, fields);
+                    serde_json::to_vec(&json).ok()
+                })
+        } else {
+            Some(state.buf.to_vec())
+        };
+        if let Some(value) = body {
+            headers.push(http_types::headers::Header {
+                name: format!(":{prefix}-body"),
+                value,
+            });
+        }
+        if state.truncated {
+            headers.push(http_types::headers::Header {
+                name: format!(":{prefix}-body-truncated"),
+                value: "true".into(),
+            });
+        }
+        headers
+    }
+}
+
+impl Drop for Capture {
+    fn drop(&mut self) {
+        // Release the captured bytes from the global limit. They continue to
+        // count against the tap's limit.
+        let sz = self.state.get_mut().buf.len();
+        self.tap.capturer.in_use.fetch_sub(sz, Ordering::Relaxed);
+    }
+}
+
+fn redact_json(value: &mut serde_json::Value, fields: &[String]) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (k, v) in map.iter_mut() {
+                if fields.contains(k) {
+                    *v = serde_json::Value::String(REDACTED.to_owned());
+                } else {
+                    redact_json(v, fields);
+                }
+            }
+        }
+        serde_json::Value::Array(values) => {
+            for v in values {
+                redact_json(v, fields);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tap(config: BodyCapture) -> TapCapture {
+        TapCapture::new(Arc::new(Capturer::new(config)), true)
+    }
+
+    fn json_headers() -> http::HeaderMap {
+        let mut headers = http::HeaderMap::new();
+        headers.insert(
+            http::header::CONTENT_TYPE,
+            "application/json; charset=utf-8".parse().unwrap(),
+        );
+        headers
+    }
+
+    fn body(headers: &[http_types::headers::Header]) -> Option<&[u8]> {
+        headers
+            .iter()
+            .find(|h| h.name.ends_with("-body"))
+            .map(|h| &h.value[..])
+    }
+
+    #[test]
+    fn captures_within_limits() {
+        let tap = tap(BodyCapture {
+            max_body_bytes: 4,
+            tap_limit: 6,
+            global_limit: 1024,
+            content_types: vec!["text/*".to_owned()],
+            ..Default::default()
+        });
+
+        let mut headers = http::HeaderMap::new();
+        assert!(tap.capture(&headers).is_none(), "requires a content type");
+        headers.insert(http::header::CONTENT_TYPE, "image/png".parse().unwrap());
+        assert!(tap.capture(&headers).is_none(), "must be allowed");
+        headers.insert(http::header::CONTENT_TYPE, "text/plain".parse().unwrap());
+
+        let first = tap.capture(&headers).expect("must capture");
+        first.data(b"hel");
+        first.data(b"lo");
+        let pb = first.to_pb("request");
+        assert_eq!(body(&pb), Some(&b"hell"[..]));
+        assert!(pb.iter().any(|h| h.name == ":request-body-truncated"));
+
+        // Only two bytes remain under the tap's limit.
+        let second = tap.capture(&headers).expect("must capture");
+        second.data(b"world");
+        assert_eq!(body(&second.to_pb("response")), Some(&b"wo"[..]));
+        assert!(!tap.is_under_limit());
+        assert!(tap.capture(&headers).is_none());
+
+        assert_eq!(tap.capturer.in_use.load(Ordering::Relaxed), 6);
+        drop((first, second));
+        assert_eq!(tap.capturer.in_use.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn enforces_global_limit() {
+        let capturer = Arc::new(Capturer::new(BodyCapture {
+            max_body_bytes: 4,
+            tap_limit: 1024,
+            global_limit: 6,
+            content_types: vec!["application/json".to_owned()],
+            ..Default::default()
+        }));
+        let tap0 = TapCapture::new(capturer.clone(), true);
+        let tap1 = TapCapture::new(capturer, true);
+
+        let c0 = tap0.capture(&json_headers()).unwrap();
+        c0.data(b"1234");
+        let c1 = tap1.capture(&json_headers()).unwrap();
+        c1.data(b"5678");
+        assert_eq!(body(&c1.to_pb("request")), Some(&b"56"[..]));
+        assert_eq!(tap1.captured.load(Ordering::Relaxed), 2);
+
+        // Bytes are released once captures are dropped.
+        drop(c0);
+        let c2 = tap1.capture(&json_headers()).unwrap();
+        c2.data(b"1234");
+        assert_eq!(body(&c2.to_pb("request")), Some(&b"1234"[..]));
+    }
+
+    #[test]
+    fn redacts() {
+        let redact = tap(BodyCapture {
+            max_body_bytes: 1024,
+            tap_limit: 1024,
+            global_limit: 1024,
+            content_types: vec!["application/json".to_owned()],
+            redact_headers: vec![http::header::AUTHORIZATION],
+            redact_json_fields: vec!["password".to_owned()],
+        });
+
+        let mut headers = json_headers();
+        headers.insert(http::header::AUTHORIZATION, "Bearer x".parse().unwrap());
+        let pb = redact.headers_to_pb(std::iter::empty(), &headers);
+        let auth = pb
+            .headers
+            .iter()
+            .find(|h| h.name == "authorization")
+            .unwrap();
+        assert_eq!(auth.value, b"REDACTED");
+
+        let capture = redact.capture(&headers).unwrap();
+        capture.data(br#"{"user":"a","nested":[{"password":"b"}]}"#);
+        assert_eq!(
+            body(&capture.to_pb("request")),
+            Some(&br#"{"nested":[{"password":"REDACTED"}],"user":"a"}"#[..])
+        );
+
+        // Incomplete JSON cannot be redacted, so it is omitted.
+        let capture = redact.capture(&headers).unwrap();
+        capture.data(br#"{"password":"#);
+        assert_eq!(body(&capture.to_pb("request")), None);
+    }
+}
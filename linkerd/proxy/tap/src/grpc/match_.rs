@@ -1,4 +1,4 @@
-use crate::Inspect;
+use crate::{Inspect, InspectTcp};
 use ipnet::{Ipv4Net, Ipv6Net};
 use linkerd2_proxy_api::net::ip_address;
 use linkerd2_proxy_api::tap::observe_request;
@@ -15,6 +15,7 @@ pub enum Match {
     DestinationLabel(LabelMatch),
     RouteLabel(LabelMatch),
     Http(HttpMatch),
+    Connection(ConnectionMatch),
 }
 
 #[derive(Debug, Eq, PartialEq, Error)]
@@ -29,6 +30,8 @@ pub enum InvalidMatch {
     InvalidHttpMethod,
     #[error("invalid request scheme")]
     InvalidScheme,
+    #[error("invalid connection match")]
+    InvalidConnection,
 }
 
 #[derive(Clone, Debug)]
@@ -50,6 +53,24 @@ pub enum NetMatch {
     Net6(Ipv6Net),
 }
 
+/// Matches attributes of opaque TCP and TLS connections that HTTP requests do
+/// not have. A tap that uses any of these predicates observes connections
+/// instead of HTTP requests.
+///
+/// The tap API does not describe connections, so these predicates are encoded
+/// as route label matches with reserved keys:
+///
+/// - `tap.linkerd.io/connection=true` matches any connection;
+/// - `tap.linkerd.io/sni=<name>` matches connections routed on an SNI value;
+/// - `tap.linkerd.io/client-id=<id>` matches connections from a client
+///   authenticated with an mTLS identity.
+#[derive(Clone, Debug)]
+pub enum ConnectionMatch {
+    Any,
+    Sni(String),
+    ClientId(String),
+}
+
 #[derive(Clone, Debug)]
 pub enum HttpMatch {
     Scheme(http::uri::Scheme),
@@ -92,6 +113,51 @@ impl Match {
                 .map(|l| lbl.matches(l.as_ref()))
                 .unwrap_or(false),
             Match::Http(ref http) => http.matches(req, inspect),
+            Match::Connection(_) => false,
+        }
+    }
+
+    /// Matches a connection, given its client address.
+    ///
+    /// HTTP predicates never match a connection.
+    pub fn matches_tcp<I: InspectTcp>(
+        &self,
+        inspect: &I,
+        src_addr: Option<net::SocketAddr>,
+    ) -> bool {
+        match self {
+            Match::Any(ref ms) => ms.iter().any(|m| m.matches_tcp(inspect, src_addr)),
+            Match::All(ref ms) => ms.iter().all(|m| m.matches_tcp(inspect, src_addr)),
+            Match::Not(ref not) => !not.matches_tcp(inspect, src_addr),
+            Match::Source(ref src) => src_addr.map(|s| src.matches(s)).unwrap_or(false),
+            Match::Destination(ref dst) => {
+                inspect.dst_addr().map(|d| dst.matches(d)).unwrap_or(false)
+            }
+            Match::DestinationLabel(ref lbl) => inspect
+                .dst_labels()
+                .map(|l| lbl.matches(&l))
+                .unwrap_or(false),
+            Match::RouteLabel(ref lbl) => inspect
+                .route_labels()
+                .map(|l| lbl.matches(l.as_ref()))
+                .unwrap_or(false),
+            Match::Http(_) => false,
+            Match::Connection(ref conn) => conn.matches(inspect),
+        }
+    }
+
+    /// Returns true if the match uses connection predicates, i.e. if the tap
+    /// observes connections rather than HTTP requests.
+    pub fn is_connection(&self) -> bool {
+        match self {
+            Match::Any(ref ms) | Match::All(ref ms) => ms.iter().any(Self::is_connection),
+            Match::Not(ref not) => not.is_connection(),
+            Match::Connection(_) => true,
+            Match::Source(_)
+            | Match::Destination(_)
+            | Match::DestinationLabel(_)
+            | Match::RouteLabel(_)
+            | Match::Http(_) => false,
         }
     }
 }
@@ -124,6 +190,9 @@ impl TryFrom<observe_request::r#match::Match> for Match {
             r#match::Match::DestinationLabel(l) => {
                 LabelMatch::try_from(l).map(Match::DestinationLabel)
             }
+            r#match::Match::RouteLabel(l) if l.key.starts_with(ConnectionMatch::LABEL_PREFIX) => {
+                ConnectionMatch::try_from(l).map(Match::Connection)
+            }
             r#match::Match::RouteLabel(l) => LabelMatch::try_from(l).map(Match::RouteLabel),
             r#match::Match::Http(http) => HttpMatch::try_from(http).map(Match::Http),
         }
@@ -153,6 +222,43 @@ impl TryFrom<observe_request::r#match::Label> for LabelMatch {
     }
 }
 
+// === impl ConnectionMatch ===
+
+impl ConnectionMatch {
+    const LABEL_PREFIX: &'static str = "tap.linkerd.io/";
+
+    fn matches<I: InspectTcp>(&self, inspect: &I) -> bool {
+        match self {
+            ConnectionMatch::Any => true,
+            ConnectionMatch::Sni(ref sni) => inspect.sni().is_some_and(|s| s.as_str() == sni),
+            ConnectionMatch::ClientId(ref id) => match inspect.src_tls() {
+                linkerd_tls::ConditionalServerTls::Some(linkerd_tls::ServerTls::Established {
+                    client_id: Some(client_id),
+                    ..
+                }) => client_id.to_string() == *id,
+                _ => false,
+            },
+        }
+    }
+}
+
+impl TryFrom<observe_request::r#match::Label> for ConnectionMatch {
+    type Error = InvalidMatch;
+
+    fn try_from(m: observe_request::r#match::Label) -> Result<Self, InvalidMatch> {
+        if m.value.is_empty() {
+            return Err(InvalidMatch::Empty);
+        }
+
+        match m.key.strip_prefix(Self::LABEL_PREFIX) {
+            Some("connection") if m.value == "true" => Ok(ConnectionMatch::Any),
+            Some("sni") => Ok(ConnectionMatch::Sni(m.value)),
+            Some("client-id") => Ok(ConnectionMatch::ClientId(m.value)),
+            _ => Err(InvalidMatch::InvalidConnection),
+        }
+    }
+}
+
 // === impl TcpMatch ===
 
 impl TcpMatch {
@@ -351,6 +457,115 @@ mod tests {
         }
     }
 
+    struct Conn {
+        dst: net::SocketAddr,
+        labels: crate::Labels,
+        sni: Option<linkerd_tls::ServerName>,
+    }
+
+    impl InspectTcp for Conn {
+        fn src_tls(&self) -> linkerd_tls::ConditionalServerTls {
+            linkerd_tls::ConditionalServerTls::None(linkerd_tls::NoServerTls::Loopback)
+        }
+
+        fn dst_addr(&self) -> Option<net::SocketAddr> {
+            Some(self.dst)
+        }
+
+        fn dst_labels(&self) -> Option<crate::Labels> {
+            Some(self.labels.clone())
+        }
+
+        fn sni(&self) -> Option<linkerd_tls::ServerName> {
+            self.sni.clone()
+        }
+
+        fn is_outbound(&self) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn matches_connections() {
+        let conn = Conn {
+            dst: ([10, 1, 2, 3], 5432).into(),
+            labels: std::sync::Arc::new(
+                [("service".to_string(), "db".to_string())]
+                    .into_iter()
+                    .collect(),
+            ),
+            sni: Some("db.example.com".parse().unwrap()),
+        };
+        let src = Some(([10, 4, 5, 6], 40000).into());
+        let label = |value: &str| {
+            Match::DestinationLabel(LabelMatch {
+                key: "service".to_string(),
+                value: value.to_string(),
+            })
+        };
+        let http = Match::Http(HttpMatch::Method(::http::Method::GET));
+
+        assert!(label("db").matches_tcp(&conn, src));
+        assert!(!label("web").matches_tcp(&conn, src));
+        assert!(Match::Destination(TcpMatch::PortRange(5432, 5432)).matches_tcp(&conn, src));
+        assert!(Match::Source(TcpMatch::PortRange(40000, 40000)).matches_tcp(&conn, src));
+        assert!(!Match::Source(TcpMatch::PortRange(40000, 40000)).matches_tcp(&conn, None));
+
+        // HTTP predicates never match connections.
+        assert!(!http.matches_tcp(&conn, src));
+        assert!(!Match::All(vec![label("db"), http.clone()]).matches_tcp(&conn, src));
+        assert!(Match::Any(vec![label("db"), http]).matches_tcp(&conn, src));
+
+        // Connection predicates only consult the connection itself.
+        let sni = |value: &str| Match::Connection(ConnectionMatch::Sni(value.to_string()));
+        assert!(sni("db.example.com").matches_tcp(&conn, src));
+        assert!(!sni("web.example.com").matches_tcp(&conn, src));
+        assert!(Match::Connection(ConnectionMatch::Any).matches_tcp(&conn, src));
+        assert!(!Match::Connection(ConnectionMatch::ClientId("web".into())).matches_tcp(&conn, src));
+    }
+
+    #[test]
+    fn connection_matches_from_proto() {
+        use observe_request::r#match::{Label, Match as Pb, Seq};
+
+        let route_label = |key: &str, value: &str| observe_request::Match {
+            r#match: Some(Pb::RouteLabel(Label {
+                key: key.to_string(),
+                value: value.to_string(),
+            })),
+        };
+        let decode = |m: observe_request::Match| Match::try_new(Some(m));
+
+        let m = decode(observe_request::Match {
+            r#match: Some(Pb::All(Seq {
+                matches: vec![
+                    route_label("tap.linkerd.io/sni", "db.example.com"),
+                    route_label("route", "db"),
+                ],
+            })),
+        })
+        .unwrap();
+        assert!(m.is_connection());
+        assert!(matches!(
+            m,
+            Match::All(ref ms) if matches!(ms[0], Match::Connection(ConnectionMatch::Sni(_)))
+                && matches!(ms[1], Match::RouteLabel(_))
+        ));
+
+        assert!(decode(route_label("tap.linkerd.io/connection", "true"))
+            .unwrap()
+            .is_connection());
+        assert!(!decode(route_label("route", "db")).unwrap().is_connection());
+        assert_eq!(
+            decode(route_label("tap.linkerd.io/connection", "false")).err(),
+            Some(InvalidMatch::InvalidConnection)
+        );
+        assert_eq!(
+            decode(route_label("tap.linkerd.io/unknown", "x")).err(),
+            Some(InvalidMatch::InvalidConnection)
+        );
+    }
+
     quickcheck! {
         fn tcp_from_proto(tcp: observe_request::r#match::Tcp) -> bool {
             use self::observe_request::r#match::tcp;
@@ -1,5 +1,8 @@
 use super::match_::Match;
-use crate::{Inspect, Registry};
+use crate::{
+    capture::{BodyCapture, Capture, Capturer, TapCapture},
+    Inspect, InspectTcp, Registry,
+};
 use futures::ready;
 use futures::stream::Stream;
 use http_body::Body;
@@ -9,10 +12,12 @@ use linkerd_proxy_http::HasH2Reason;
 use linkerd_tls as tls;
 use pin_project::pin_project;
 use std::iter;
+use std::net::SocketAddr;
 use std::pin::Pin;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Weak};
 use std::task::{Context, Poll};
+use std::time::Duration;
 use tokio::sync::mpsc;
 use tokio::time::Instant;
 use tonic::{self as grpc, Response};
@@ -22,6 +27,7 @@ use tracing::{debug, trace, warn};
 pub struct Server {
     base_id: Arc<AtomicUsize>,
     registry: Registry,
+    capturer: Arc<Capturer>,
 }
 
 #[pin_project]
@@ -39,6 +45,7 @@ struct Shared {
     limit: usize,
     match_: Match,
     extract: ExtractKind,
+    capture: TapCapture,
     events_tx: mpsc::Sender<api::TapEvent>,
 }
 
@@ -59,6 +66,8 @@ pub struct TapResponse {
     request_init_at: Instant,
     /// Should headers be extracted?
     extract_headers: bool,
+    capture: TapCapture,
+    request_body: Option<Arc<Capture>>,
     tap: TapTx,
 }
 
@@ -71,10 +80,54 @@ pub struct TapResponsePayload {
     tap: TapTx,
     /// Should headers be extracted?
     extract_headers: bool,
+    capture: TapCapture,
+    request_body: Option<Arc<Capture>>,
+    response_body: Option<Arc<Capture>>,
     // Response-headers may include grpc-status when there is no response body.
     grpc_status: Option<u32>,
 }
 
+/// Emits the lifecycle events of a tapped TCP connection.
+///
+/// Connections are only tapped by taps whose match includes a connection
+/// predicate (see [`ConnectionMatch`](super::match_::ConnectionMatch)).
+#[derive(Debug)]
+pub struct TapConnection {
+    base_event: api::TapEvent,
+    opened_at: Instant,
+    tx: mpsc::Sender<api::TapEvent>,
+}
+
+/// A lifecycle event of a tapped TCP connection.
+///
+/// The tap API does not yet describe connection events, so each event is
+/// emitted as a `TapEvent` without an `event`. Instead, its route metadata
+/// carries the following labels (in addition to the connection's route
+/// labels):
+///
+/// - `tcp`: the event kind: `open`, `close`, or `error`;
+/// - `connection`: an identifier that is unique within the tap and shared by
+///   all of a connection's events;
+/// - `sni`: the SNI on which a TLS connection was routed, if any;
+/// - `duration_ms`, `bytes_read`, `bytes_written`: the connection's lifetime
+///   and byte counts, on `close` and `error` events;
+/// - `error`: the error that terminated the connection, on `error` events.
+#[derive(Debug)]
+enum ConnectionEvent {
+    Open,
+    Close {
+        duration: Duration,
+        bytes_read: u64,
+        bytes_written: u64,
+    },
+    Error {
+        duration: Duration,
+        bytes_read: u64,
+        bytes_written: u64,
+        error: String,
+    },
+}
+
 /// Indicates what tap data should be extracted from traffic.
 ///
 /// This is constructed from the protobuf `Extract` message, and represents the
@@ -82,7 +135,11 @@ pub struct TapResponsePayload {
 /// need to represent nullability the way the protobuf message does.
 #[derive(Debug)]
 enum ExtractKind {
-    Http { headers: bool },
+    Http {
+        headers: bool,
+    },
+    /// Connection events are extracted from opaque TCP and TLS connections.
+    Tcp,
 }
 
 // === impl Server ===
@@ -90,7 +147,20 @@ enum ExtractKind {
 impl Server {
     pub(crate) fn new(registry: Registry) -> Self {
         let base_id = Arc::new(0.into());
-        Self { base_id, registry }
+        Self {
+            base_id,
+            registry,
+            capturer: Default::default(),
+        }
+    }
+
+    /// Configures the capture of request and response bodies, and the
+    /// redaction of headers, in tap events.
+    pub fn with_body_capture(self, config: BodyCapture) -> Self {
+        Self {
+            capturer: Arc::new(Capturer::new(config)),
+            ..self
+        }
     }
 
     fn invalid_arg(message: String) -> grpc::Status {
@@ -139,6 +209,13 @@ impl api::tap_server::Tap for Server {
             // behavior that older control planes know about --- extract
             // HTTP data without headers.
             .unwrap_or_default();
+        // The tap API does not yet describe connection extraction, so taps
+        // that match on connection predicates extract connection events.
+        let extract = if match_.is_connection() {
+            ExtractKind::Tcp
+        } else {
+            extract
+        };
 
         // Wrapping is okay. This is realy just to disambiguate events within a
         // single tap session (i.e. that may consist of several tap requests).
@@ -154,12 +231,17 @@ impl api::tap_server::Tap for Server {
         let (events_tx, events_rx) =
             mpsc::channel(super::super::PER_RESPONSE_EVENT_BUFFER_CAPACITY);
 
+        let capture = TapCapture::new(
+            self.capturer.clone(),
+            matches!(extract, ExtractKind::Http { headers: true }),
+        );
         let shared = Arc::new(Shared {
             base_id,
             count: AtomicUsize::new(0),
             limit,
             match_,
             extract,
+            capture,
             events_tx,
         });
 
@@ -208,8 +290,11 @@ impl Stream for ResponseStream {
 // === impl Shared ===
 
 impl Shared {
+    /// Indicates whether the tap may observe more traffic, i.e. whether it
+    /// has neither tapped `limit` requests nor exhausted its body capture
+    /// limit.
     fn is_under_limit(&self) -> bool {
-        self.count.load(Ordering::Relaxed) < self.limit
+        self.count.load(Ordering::Relaxed) < self.limit && self.capture.is_under_limit()
     }
 }
 
@@ -233,12 +318,12 @@ impl Tap {
             return None;
         }
 
-        // Note: if we add other `ExtractKind`s in the future, this method
-        // should return `None` here if we're not extracting HTTP data --- it's
-        // HTTP-specific.
         let ExtractKind::Http {
             headers: extract_headers,
-        } = shared.extract;
+        } = shared.extract
+        else {
+            return None;
+        };
 
         let id = {
             let next_id = shared.count.fetch_add(1, Ordering::Relaxed);
@@ -252,6 +337,7 @@ impl Tap {
             }
         };
         let events_tx = shared.events_tx.clone();
+        let capture = shared.capture.clone();
 
         let request_init_at = Instant::now();
 
@@ -289,9 +375,9 @@ impl Tap {
                             .unwrap_or_default(),
                     },
                 ];
-                headers_to_pb(pseudos, req.headers())
+                capture.headers_to_pb(pseudos, req.headers())
             } else {
-                headers_to_pb(iter::empty(), req.headers())
+                capture.headers_to_pb(iter::empty(), req.headers())
             };
             Some(headers)
         } else {
@@ -318,19 +404,166 @@ impl Tap {
         events_tx.try_send(event).ok()?;
 
         let tap = TapTx { id, tx: events_tx };
+        let request_body = capture.capture(req.headers());
 
         Some(TapResponse {
             tap,
             base_event,
             request_init_at,
             extract_headers,
+            capture,
+            request_body,
         })
     }
 }
 
+impl Tap {
+    pub(crate) fn tap_tcp<I: InspectTcp>(
+        &mut self,
+        inspect: &I,
+        src_addr: Option<SocketAddr>,
+    ) -> Option<TapConnection> {
+        let shared = self.shared.upgrade()?;
+        if !matches!(shared.extract, ExtractKind::Tcp) {
+            return None;
+        }
+        if !shared.match_.matches_tcp(inspect, src_addr) {
+            return None;
+        }
+
+        let id = shared.count.fetch_add(1, Ordering::Relaxed);
+        if id >= shared.limit {
+            return None;
+        }
+        let events_tx = shared.events_tx.clone();
+
+        let opened_at = Instant::now();
+
+        let mut base_event = api::TapEvent {
+            proxy_direction: if inspect.is_outbound() {
+                api::tap_event::ProxyDirection::Outbound.into()
+            } else {
+                api::tap_event::ProxyDirection::Inbound.into()
+            },
+            source: src_addr.map(Into::into),
+            source_meta: Some(source_meta(inspect.src_tls())),
+            destination: inspect.dst_addr().map(Into::into),
+            destination_meta: inspect.dst_labels().map(|labels| {
+                let mut m = api::tap_event::EndpointMeta::default();
+                m.labels
+                    .extend(labels.iter().map(|(k, v)| (k.clone(), v.clone())));
+                m
+            }),
+            route_meta: None,
+            event: None,
+        };
+        let route_meta = base_event.route_meta.get_or_insert_with(Default::default);
+        if let Some(labels) = inspect.route_labels() {
+            route_meta
+                .labels
+                .extend(labels.iter().map(|(k, v)| (k.clone(), v.clone())));
+        }
+        route_meta.labels.insert(
+            "connection".to_owned(),
+            format!("{}:{}", shared.base_id, id),
+        );
+        if let Some(sni) = inspect.sni() {
+            route_meta.labels.insert("sni".to_owned(), sni.to_string());
+        }
+
+        let conn = TapConnection {
+            base_event,
+            opened_at,
+            tx: events_tx,
+        };
+
+        // If try_send fails, just return `None`...
+        conn.tx.try_send(conn.event(ConnectionEvent::Open)).ok()?;
+
+        Some(conn)
+    }
+}
+
+// === impl TapConnection ===
+
+impl TapConnection {
+    pub(crate) fn close(self, bytes_read: u64, bytes_written: u64) {
+        let event = ConnectionEvent::Close {
+            duration: self.duration(),
+            bytes_read,
+            bytes_written,
+        };
+        let _ = self.tx.try_send(self.event(event));
+    }
+
+    pub(crate) fn fail(self, error: &linkerd_error::Error, bytes_read: u64, bytes_written: u64) {
+        let event = ConnectionEvent::Error {
+            duration: self.duration(),
+            bytes_read,
+            bytes_written,
+            error: error.to_string(),
+        };
+        let _ = self.tx.try_send(self.event(event));
+    }
+
+    fn duration(&self) -> Duration {
+        Instant::now().saturating_duration_since(self.opened_at)
+    }
+
+    fn event(&self, event: ConnectionEvent) -> api::TapEvent {
+        let mut tap_event = self.base_event.clone();
+        tap_event
+            .route_meta
+            .get_or_insert_with(Default::default)
+            .labels
+            .extend(event.into_labels());
+        tap_event
+    }
+}
+
+// === impl ConnectionEvent ===
+
+impl ConnectionEvent {
+    fn into_labels(self) -> Vec<(String, String)> {
+        let (kind, end) = match self {
+            Self::Open => ("open", None),
+            Self::Close {
+                duration,
+                bytes_read,
+                bytes_written,
+            } => ("close", Some((duration, bytes_read, bytes_written, None))),
+            Self::Error {
+                duration,
+                bytes_read,
+                bytes_written,
+                error,
+            } => (
+                "error",
+                Some((duration, bytes_read, bytes_written, Some(error))),
+            ),
+        };
+
+        let mut labels = vec![("tcp".to_owned(), kind.to_owned())];
+        if let Some((duration, bytes_read, bytes_written, error)) = end {
+            labels.push(("duration_ms".to_owned(), duration.as_millis().to_string()));
+            labels.push(("bytes_read".to_owned(), bytes_read.to_string()));
+            labels.push(("bytes_written".to_owned(), bytes_written.to_string()));
+            if let Some(error) = error {
+                labels.push(("error".to_owned(), error));
+            }
+        }
+        labels
+    }
+}
+
 // === impl TapResponse ===
 
 impl TapResponse {
+    /// Returns the capture of the request body, if it is being captured.
+    pub(crate) fn request_body(&self) -> Option<Arc<Capture>> {
+        self.request_body.clone()
+    }
+
     pub(crate) fn tap<B: Body>(self, rsp: &http::Response<B>) -> TapResponsePayload {
         let response_init_at = Instant::now();
 
@@ -340,9 +573,9 @@ impl TapResponse {
                     name: ":status".to_owned(),
                     value: rsp.status().as_str().as_bytes().into(),
                 });
-                headers_to_pb(pseudos, rsp.headers())
+                self.capture.headers_to_pb(pseudos, rsp.headers())
             } else {
-                headers_to_pb(iter::empty(), rsp.headers())
+                self.capture.headers_to_pb(iter::empty(), rsp.headers())
             };
             Some(headers)
         } else {
@@ -371,6 +604,9 @@ impl TapResponse {
             response_bytes: 0,
             tap: self.tap,
             extract_headers: self.extract_headers,
+            response_body: self.capture.capture(rsp.headers()),
+            capture: self.capture,
+            request_body: self.request_body,
             grpc_status: rsp
                 .headers()
                 .get("grpc-status")
@@ -383,6 +619,11 @@ impl TapResponse {
         let response_end_at = Instant::now();
         let reason = err.h2_reason();
         let since_request_init = response_end_at.saturating_duration_since(self.request_init_at);
+        let trailers = if self.extract_headers {
+            end_headers(&self.capture, None, [(&self.request_body, "request")])
+        } else {
+            None
+        };
         let end = api::tap_event::http::Event::ResponseEnd(api::tap_event::http::ResponseEnd {
             id: Some(self.tap.id),
             since_request_init: pb_duration(since_request_init),
@@ -391,7 +632,7 @@ impl TapResponse {
             eos: Some(api::Eos {
                 end: reason.map(|r| api::eos::End::ResetErrorCode(r.into())),
             }),
-            trailers: None,
+            trailers,
         });
 
         let event = api::TapEvent {
@@ -407,6 +648,13 @@ impl TapResponse {
 // === impl TapResponsePayload ===
 
 impl TapResponsePayload {
+    /// Records a chunk of the response body.
+    pub(crate) fn data(&self, chunk: &[u8]) {
+        if let Some(body) = self.response_body.as_ref() {
+            body.data(chunk);
+        }
+    }
+
     pub(crate) fn eos(self, trls: Option<&http::HeaderMap>) {
         let status = match trls {
             None => self.grpc_status,
@@ -429,7 +677,14 @@ impl TapResponsePayload {
     fn send(self, end: Option<api::eos::End>, trls: Option<&http::HeaderMap>) {
         let response_end_at = Instant::now();
         let trailers = if self.extract_headers {
-            trls.map(|trls| headers_to_pb(iter::empty(), trls))
+            end_headers(
+                &self.capture,
+                trls,
+                [
+                    (&self.request_body, "request"),
+                    (&self.response_body, "response"),
+                ],
+            )
         } else {
             None
         };
@@ -490,26 +745,7 @@ fn base_event<B, I: Inspect>(req: &http::Request<B>, inspect: &I) -> api::TapEve
             api::tap_event::ProxyDirection::Inbound.into()
         },
         source: inspect.src_addr(req).map(|a| a.into()),
-        source_meta: {
-            let mut m = api::tap_event::EndpointMeta::default();
-            match inspect.src_tls(req) {
-                Conditional::None(reason) => {
-                    m.labels.insert("tls".to_owned(), reason.to_string());
-                }
-                Conditional::Some(tls::ServerTls::Established { client_id, .. }) => {
-                    m.labels.insert("tls".to_owned(), "true".to_owned());
-                    m.labels.insert(
-                        "client_id".to_owned(),
-                        client_id.map(|id| id.to_string()).unwrap_or_default(),
-                    );
-                }
-                Conditional::Some(tls::ServerTls::Passthru { sni }) => {
-                    m.labels.insert("tls".to_owned(), "passthru".to_owned());
-                    m.labels.insert("sni".to_owned(), sni.to_string());
-                }
-            }
-            Some(m)
-        },
+        source_meta: Some(source_meta(inspect.src_tls(req))),
         destination: inspect.dst_addr(req).map(|a| a.into()),
         destination_meta: inspect.dst_labels(req).map(|labels| {
             let mut m = api::tap_event::EndpointMeta::default();
@@ -537,22 +773,46 @@ fn base_event<B, I: Inspect>(req: &http::Request<B>, inspect: &I) -> api::TapEve
     }
 }
 
-fn headers_to_pb(
-    pseudos: impl IntoIterator<Item = http_types::headers::Header>,
-    headers: &http::HeaderMap,
-) -> http_types::Headers {
-    http_types::Headers {
-        headers: pseudos
-            .into_iter()
-            .chain(
-                headers
-                    .iter()
-                    .map(|(name, value)| http_types::headers::Header {
-                        name: name.as_str().to_owned(),
-                        value: value.as_bytes().into(),
-                    }),
-            )
-            .collect(),
+fn source_meta(tls: tls::ConditionalServerTls) -> api::tap_event::EndpointMeta {
+    let mut m = api::tap_event::EndpointMeta::default();
+    match tls {
+        Conditional::None(reason) => {
+            m.labels.insert("tls".to_owned(), reason.to_string());
+        }
+        Conditional::Some(tls::ServerTls::Established { client_id, .. }) => {
+            m.labels.insert("tls".to_owned(), "true".to_owned());
+            m.labels.insert(
+                "client_id".to_owned(),
+                client_id.map(|id| id.to_string()).unwrap_or_default(),
+            );
+        }
+        Conditional::Some(tls::ServerTls::Passthru { sni }) => {
+            m.labels.insert("tls".to_owned(), "passthru".to_owned());
+            m.labels.insert("sni".to_owned(), sni.to_string());
+        }
+    }
+    m
+}
+
+/// Builds the headers of a `ResponseEnd` event from the response's trailers and
+/// any captured bodies.
+///
+/// The tap API does not describe bodies, so captured bodies are described by
+/// `:request-body` and `:response-body` pseudo-headers.
+fn end_headers<'a>(
+    capture: &TapCapture,
+    trls: Option<&http::HeaderMap>,
+    bodies: impl IntoIterator<Item = (&'a Option<Arc<Capture>>, &'static str)>,
+) -> Option<http_types::Headers> {
+    let pseudos = bodies
+        .into_iter()
+        .filter_map(|(body, prefix)| Some(body.as_ref()?.to_pb(prefix)))
+        .flatten()
+        .collect::<Vec<_>>();
+    match trls {
+        Some(trls) => Some(capture.headers_to_pb(pseudos, trls)),
+        None if !pseudos.is_empty() => Some(http_types::Headers { headers: pseudos }),
+        None => None,
     }
 }
 
@@ -5,11 +5,19 @@ use linkerd_tls as tls;
 use std::{net, sync::Arc};
 
 mod accept;
+mod capture;
 mod grpc;
 mod registry;
 mod service;
+mod tcp;
 
-pub use self::{accept::AcceptPermittedClients, registry::Registry, service::NewTapHttp};
+pub use self::{
+    accept::AcceptPermittedClients,
+    capture::BodyCapture,
+    registry::Registry,
+    service::NewTapHttp,
+    tcp::{NewTapTcp, TapIo},
+};
 
 // The number of events that may be buffered for a given response.
 const PER_RESPONSE_EVENT_BUFFER_CAPACITY: usize = 400;
@@ -57,3 +65,32 @@ pub trait Inspect {
             })
     }
 }
+
+/// Inspects a connection for a `Stack`.
+///
+/// Connections are only tapped by taps that request connection events and are
+/// matched without HTTP predicates.
+pub trait InspectTcp {
+    /// The connection's client address. When unset, the socket's peer address
+    /// is used.
+    fn src_addr(&self) -> Option<net::SocketAddr> {
+        None
+    }
+
+    fn src_tls(&self) -> tls::ConditionalServerTls;
+
+    fn dst_addr(&self) -> Option<net::SocketAddr>;
+
+    fn dst_labels(&self) -> Option<Labels>;
+
+    fn route_labels(&self) -> Option<Labels> {
+        None
+    }
+
+    /// The SNI value on which a TLS connection was routed.
+    fn sni(&self) -> Option<tls::ServerName> {
+        None
+    }
+
+    fn is_outbound(&self) -> bool;
+}
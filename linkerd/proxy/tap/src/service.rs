@@ -1,10 +1,12 @@
-use crate::{grpc::TapResponsePayload, registry::Registry, Inspect};
+use crate::{capture::Capture, grpc::TapResponsePayload, registry::Registry, Inspect};
+use bytes::Buf;
 use futures::ready;
 use linkerd_proxy_http::HasH2Reason;
 use linkerd_stack::{layer, NewService};
 use pin_project::{pin_project, pinned_drop};
 use std::future::Future;
 use std::pin::Pin;
+use std::sync::Arc;
 use std::task::{Context, Poll};
 
 /// Makes wrapped Services to record taps.
@@ -35,6 +37,15 @@ where
     taps: Vec<TapResponsePayload>,
 }
 
+// A request `Body` whose data is captured by taps.
+#[pin_project]
+#[derive(Debug)]
+pub struct RequestBody<B> {
+    #[pin]
+    inner: B,
+    captures: Vec<Arc<Capture>>,
+}
+
 // === NewTapHttp ===
 
 impl<N> NewTapHttp<N> {
@@ -66,7 +77,7 @@ where
 
 impl<S, I, A, B> tower::Service<http::Request<A>> for TapHttp<S, I>
 where
-    S: tower::Service<http::Request<A>, Response = http::Response<B>>,
+    S: tower::Service<http::Request<RequestBody<A>>, Response = http::Response<B>>,
     S::Error: HasH2Reason,
     S::Future: Send + 'static,
     I: Inspect,
@@ -94,7 +105,10 @@ where
             }
         }
 
-        let call = self.inner.call(req);
+        let captures = rsp_taps.iter().filter_map(|t| t.request_body()).collect();
+        let call = self
+            .inner
+            .call(req.map(|inner| RequestBody { inner, captures }));
         Box::pin(async move {
             match call.await {
                 Ok(rsp) => {
@@ -179,6 +193,11 @@ where
             }
         };
 
+        if let Some(data) = frame.data_ref() {
+            let chunk = data.chunk();
+            taps.iter().for_each(|t| t.data(chunk));
+        }
+
         // If we received a trailers frame, we have reached the end of the stream.
         if let trailers @ Some(_) = frame.trailers_ref() {
             taps.drain(..).for_each(|t| t.eos(trailers));
@@ -210,3 +229,45 @@ where
         taps.drain(..).for_each(|t| t.eos(None));
     }
 }
+
+// === impl RequestBody ===
+
+impl<B: Default> Default for RequestBody<B> {
+    fn default() -> Self {
+        Self {
+            inner: B::default(),
+            captures: Vec::default(),
+        }
+    }
+}
+
+impl<B> linkerd_proxy_http::Body for RequestBody<B>
+where
+    B: linkerd_proxy_http::Body,
+{
+    type Data = B::Data;
+    type Error = B::Error;
+
+    #[inline]
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<http_body::Frame<Self::Data>, Self::Error>>> {
+        let this = self.project();
+        let frame = ready!(this.inner.poll_frame(cx));
+        if let Some(data) = frame.as_ref().and_then(|f| f.as_ref().ok()?.data_ref()) {
+            let chunk = data.chunk();
+            this.captures.iter().for_each(|c| c.data(chunk));
+        }
+        Poll::Ready(frame)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> hyper::body::SizeHint {
+        self.inner.size_hint()
+    }
+}
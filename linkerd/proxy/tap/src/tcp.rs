@@ -0,0 +1,311 @@
+use crate::{registry::Registry, InspectTcp};
+use futures::{
+    future::{self, Either},
+    TryFutureExt,
+};
+use linkerd_errno::Errno;
+use linkerd_error::Error;
+use linkerd_io as io;
+use linkerd_stack::{layer, NewService};
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+};
+
+/// Makes wrapped Services to record taps.
+#[derive(Clone, Debug)]
+pub struct NewTapTcp<N> {
+    inner: N,
+    registry: Registry,
+}
+
+/// A middleware that records TCP taps.
+#[derive(Clone, Debug)]
+pub struct TapTcp<S, T> {
+    inner: S,
+    inspect: T,
+    registry: Registry,
+}
+
+/// A connection's I/O, instrumented to count the bytes transferred on tapped
+/// connections.
+pub type TapIo<I> = io::SensorIo<I, Sensor>;
+
+#[derive(Clone, Debug, Default)]
+pub struct Sensor(Option<Arc<Transferred>>);
+
+#[derive(Debug, Default)]
+struct Transferred {
+    read: AtomicU64,
+    written: AtomicU64,
+}
+
+// === NewTapTcp ===
+
+impl<N> NewTapTcp<N> {
+    pub fn layer(registry: Registry) -> impl layer::Layer<N, Service = Self> + Clone {
+        layer::mk(move |inner| Self {
+            inner,
+            registry: registry.clone(),
+        })
+    }
+}
+
+impl<N, T> NewService<T> for NewTapTcp<N>
+where
+    N: NewService<T>,
+    T: InspectTcp + Clone,
+{
+    type Service = TapTcp<N::Service, T>;
+
+    fn new_service(&self, target: T) -> Self::Service {
+        TapTcp {
+            inspect: target.clone(),
+            inner: self.inner.new_service(target),
+            registry: self.registry.clone(),
+        }
+    }
+}
+
+// === Service ===
+
+impl<S, T, I> tower::Service<I> for TapTcp<S, T>
+where
+    S: tower::Service<TapIo<I>, Response = ()>,
+    S::Error: Into<Error>,
+    S::Future: Send + 'static,
+    T: InspectTcp,
+    I: io::PeerAddr,
+{
+    type Response = ();
+    type Error = Error;
+    type Future = Either<
+        future::ErrInto<S::Future, Error>,
+        Pin<Box<dyn Future<Output = Result<(), Error>> + Send + 'static>>,
+    >;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, io: I) -> Self::Future {
+        let src_addr = self.inspect.src_addr().or_else(|| io.peer_addr().ok());
+
+        // Record the connection's opening and obtain taps for its completion.
+        let mut taps = Vec::new();
+        for mut t in self.registry.get_taps() {
+            if let Some(conn_tap) = t.tap_tcp(&self.inspect, src_addr) {
+                taps.push(conn_tap);
+            }
+        }
+
+        if taps.is_empty() {
+            let call = self.inner.call(io::SensorIo::new(io, Sensor(None)));
+            return Either::Left(call.err_into());
+        }
+
+        let transferred = Arc::new(Transferred::default());
+        let call = self
+            .inner
+            .call(io::SensorIo::new(io, Sensor(Some(transferred.clone()))));
+        Either::Right(Box::pin(async move {
+            let res = call.await.map_err(Into::into);
+            let read = transferred.read.load(Ordering::Relaxed);
+            let written = transferred.written.load(Ordering::Relaxed);
+            match res {
+                Ok(()) => {
+                    for tap in taps.drain(..) {
+                        tap.close(read, written);
+                    }
+                    Ok(())
+                }
+                Err(e) => {
+                    for tap in taps.drain(..) {
+                        tap.fail(&e, read, written);
+                    }
+                    Err(e)
+                }
+            }
+        }))
+    }
+}
+
+// === impl Sensor ===
+
+impl io::Sensor for Sensor {
+    fn record_read(&mut self, sz: usize) {
+        if let Some(t) = self.0.as_ref() {
+            t.read.fetch_add(sz as u64, Ordering::Relaxed);
+        }
+    }
+
+    fn record_write(&mut self, sz: usize) {
+        if let Some(t) = self.0.as_ref() {
+            t.written.fetch_add(sz as u64, Ordering::Relaxed);
+        }
+    }
+
+    fn record_close(&mut self, _: Option<Errno>) {}
+
+    fn record_error<T>(&mut self, op: io::Poll<T>) -> io::Poll<T> {
+        op
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+    use linkerd2_proxy_api::tap::{self as api, tap_server::Tap as _};
+    use linkerd_tls as tls;
+    use std::net::SocketAddr;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tower::{Service, ServiceExt};
+
+    #[derive(Clone)]
+    struct Target;
+
+    impl InspectTcp for Target {
+        fn src_tls(&self) -> tls::ConditionalServerTls {
+            tls::ConditionalServerTls::None(tls::NoServerTls::Loopback)
+        }
+
+        fn dst_addr(&self) -> Option<SocketAddr> {
+            Some(([10, 1, 2, 3], 5432).into())
+        }
+
+        fn dst_labels(&self) -> Option<crate::Labels> {
+            None
+        }
+
+        fn is_outbound(&self) -> bool {
+            true
+        }
+    }
+
+    fn route_label_tap(key: &str, value: &str) -> api::ObserveRequest {
+        use api::observe_request::{
+            r#match::{Label, Match},
+            Match as Pb,
+        };
+        api::ObserveRequest {
+            limit: 1,
+            r#match: Some(Pb {
+                r#match: Some(Match::RouteLabel(Label {
+                    key: key.to_string(),
+                    value: value.to_string(),
+                })),
+            }),
+            extract: None,
+        }
+    }
+
+    /// Echoes a single message back to the client.
+    #[derive(Clone)]
+    struct Echo;
+
+    impl Service<TapIo<io::Pipe>> for Echo {
+        type Response = ();
+        type Error = Error;
+        type Future = Pin<Box<dyn Future<Output = Result<(), Error>> + Send + 'static>>;
+
+        fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, mut io: TapIo<io::Pipe>) -> Self::Future {
+            Box::pin(async move {
+                let mut buf = [0u8; 5];
+                io.read_exact(&mut buf).await?;
+                io.write_all(&buf).await?;
+                Ok(())
+            })
+        }
+    }
+
+    fn tap_echo(registry: Registry) -> TapTcp<Echo, Target> {
+        TapTcp {
+            inner: Echo,
+            inspect: Target,
+            registry,
+        }
+    }
+
+    fn client_pipe() -> io::Pipe {
+        io::Pipe::new(([10, 4, 5, 6], 40000).into()).0
+    }
+
+    async fn echo(
+        svc: impl tower::Service<io::Pipe, Response = (), Error = Error>,
+    ) -> Result<(), Error> {
+        let (pipe, mut client) = io::Pipe::new(([10, 4, 5, 6], 40000).into());
+        client.write_all(b"hello").await?;
+        let res = svc.oneshot(pipe).await;
+        let mut buf = [0u8; 5];
+        client.read_exact(&mut buf).await?;
+        assert_eq!(&buf, b"hello");
+        res
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn untapped_connections_pass_through() {
+        let (registry, _server) = crate::new();
+        let mut svc = tap_echo(registry);
+        assert!(matches!(svc.call(client_pipe()), Either::Left(_)));
+        echo(svc).await.expect("connection must succeed");
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn taps_connection_events() {
+        let (registry, server) = crate::new();
+        let mut events = server
+            .observe(tonic::Request::new(route_label_tap(
+                "tap.linkerd.io/connection",
+                "true",
+            )))
+            .await
+            .expect("tap must be valid")
+            .into_inner();
+
+        echo(tap_echo(registry))
+            .await
+            .expect("connection must succeed");
+
+        let label = |ev: &api::TapEvent, key: &str| {
+            ev.route_meta.as_ref().unwrap().labels.get(key).cloned()
+        };
+        let open = events.next().await.unwrap().unwrap();
+        assert!(open.event.is_none());
+        assert_eq!(label(&open, "tcp").as_deref(), Some("open"));
+        assert_eq!(label(&open, "bytes_read"), None);
+
+        let close = events.next().await.unwrap().unwrap();
+        assert_eq!(label(&close, "tcp").as_deref(), Some("close"));
+        assert_eq!(label(&close, "connection"), label(&open, "connection"));
+        assert_eq!(label(&close, "bytes_read").as_deref(), Some("5"));
+        assert_eq!(label(&close, "bytes_written").as_deref(), Some("5"));
+        assert!(label(&close, "duration_ms").is_some());
+
+        // The tap's limit has been reached.
+        assert!(events.next().await.is_none());
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn http_taps_ignore_connections() {
+        let (registry, server) = crate::new();
+        let _events = server
+            .observe(tonic::Request::new(route_label_tap("route", "db")))
+            .await
+            .expect("tap must be valid")
+            .into_inner();
+
+        let mut svc = tap_echo(registry);
+        assert!(matches!(svc.call(client_pipe()), Either::Left(_)));
+    }
+}
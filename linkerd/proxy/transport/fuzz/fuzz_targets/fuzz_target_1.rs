@@ -0,0 +1,9 @@
+#![no_main]
+
+#[cfg(fuzzing)]
+use libfuzzer_sys::fuzz_target;
+
+#[cfg(fuzzing)]
+fuzz_target!(|data: &[u8]| {
+    linkerd_proxy_transport::proxy_protocol::fuzz_logic::fuzz_entry(data);
+});
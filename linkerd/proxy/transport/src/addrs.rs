@@ -18,6 +18,10 @@ pub struct DualListenAddr(pub SocketAddr, pub Option<SocketAddr>);
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 pub struct ServerAddr(pub SocketAddr);
 
+/// A set of addresses for a single server, e.g. its IPv4 and IPv6 addresses.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct ServerAddrs(pub Vec<SocketAddr>);
+
 /// An SO_ORIGINAL_DST address.
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 pub struct OrigDstAddr(pub SocketAddr);
@@ -0,0 +1,262 @@
+//! Dual-stack connection establishment, as described by [RFC 8305][rfc].
+//!
+//! [rfc]: https://www.rfc-editor.org/rfc/rfc8305
+
+use crate::{ClientAddr, Local, Remote, ServerAddr, ServerAddrs};
+use futures::{
+    future::Either,
+    stream::{FuturesUnordered, StreamExt},
+};
+use linkerd_io as io;
+use linkerd_stack::{Oneshot, Param, Service, ServiceExt};
+use std::{
+    future::Future,
+    net::SocketAddr,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::time;
+use tracing::{debug, trace};
+
+/// Connects to one of a set of candidate addresses, racing connection attempts
+/// with staggered starts and keeping the first to succeed.
+///
+/// Candidates are attempted in the order described by RFC 8305, alternating
+/// address families starting with the family of the first candidate. A new
+/// attempt is started whenever the attempt delay elapses or an outstanding
+/// attempt fails. A single candidate is connected to directly.
+#[derive(Copy, Clone, Debug)]
+pub struct HappyEyeballs<C> {
+    connect: C,
+    attempt_delay: Duration,
+}
+
+/// The recommended "Connection Attempt Delay" from RFC 8305, section 5.
+pub const DEFAULT_ATTEMPT_DELAY: Duration = Duration::from_millis(250);
+
+#[derive(Debug, thiserror::Error)]
+#[error("no candidate addresses")]
+struct NoCandidates(());
+
+// === impl HappyEyeballs ===
+
+impl<C> HappyEyeballs<C> {
+    pub fn new(connect: C, attempt_delay: Duration) -> Self {
+        Self {
+            connect,
+            attempt_delay,
+        }
+    }
+}
+
+impl<T, C, I> Service<T> for HappyEyeballs<C>
+where
+    T: Param<Remote<ServerAddrs>>,
+    C: Service<Remote<ServerAddr>, Response = (I, Local<ClientAddr>), Error = io::Error>,
+    C: Clone + Send + 'static,
+    C::Future: Send + 'static,
+    I: Send + 'static,
+{
+    type Response = (I, Local<ClientAddr>);
+    type Error = io::Error;
+    type Future = Either<
+        Oneshot<C, Remote<ServerAddr>>,
+        Pin<Box<dyn Future<Output = io::Result<Self::Response>> + Send + 'static>>,
+    >;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, t: T) -> Self::Future {
+        let Remote(ServerAddrs(addrs)) = t.param();
+        if let [addr] = addrs[..] {
+            return Either::Left(self.connect.clone().oneshot(Remote(ServerAddr(addr))));
+        }
+        Either::Right(Box::pin(race(
+            self.connect.clone(),
+            interleave(addrs),
+            self.attempt_delay,
+        )))
+    }
+}
+
+async fn race<C, I>(
+    connect: C,
+    addrs: Vec<SocketAddr>,
+    attempt_delay: Duration,
+) -> io::Result<(I, Local<ClientAddr>)>
+where
+    C: Service<Remote<ServerAddr>, Response = (I, Local<ClientAddr>), Error = io::Error>,
+    C: Clone,
+{
+    let mut addrs = addrs.into_iter();
+    let mut attempts = FuturesUnordered::new();
+    let mut last_error = None;
+    loop {
+        if let Some(addr) = addrs.next() {
+            debug!(server.addr = %addr, "Attempting connection");
+            attempts.push(connect.clone().oneshot(Remote(ServerAddr(addr))));
+        }
+
+        if attempts.is_empty() {
+            return Err(last_error
+                .unwrap_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, NoCandidates(()))));
+        }
+
+        // Wait for an attempt to complete, or for the attempt delay to elapse
+        // if there are remaining candidates to try.
+        tokio::select! {
+            res = attempts.next() => match res.expect("attempts must not be empty") {
+                Ok(conn) => return Ok(conn),
+                Err(error) => {
+                    debug!(%error, "Connection attempt failed");
+                    last_error = Some(error);
+                }
+            },
+            _ = time::sleep(attempt_delay), if addrs.len() > 0 => {
+                trace!("Connection attempt delay elapsed");
+            }
+        }
+    }
+}
+
+/// Orders candidate addresses so that address families alternate, starting
+/// with the family of the first address. The relative order of addresses
+/// within each family is preserved.
+fn interleave(addrs: Vec<SocketAddr>) -> Vec<SocketAddr> {
+    let Some(ipv6_first) = addrs.first().map(SocketAddr::is_ipv6) else {
+        return addrs;
+    };
+    let len = addrs.len();
+    let (preferred, other): (Vec<_>, Vec<_>) =
+        addrs.into_iter().partition(|a| a.is_ipv6() == ipv6_first);
+    let (mut preferred, mut other) = (preferred.into_iter(), other.into_iter());
+    let mut ordered = Vec::with_capacity(len);
+    loop {
+        match (preferred.next(), other.next()) {
+            (None, None) => return ordered,
+            (a, b) => ordered.extend(a.into_iter().chain(b)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use linkerd_stack::service_fn;
+    use tokio::time::Instant;
+
+    fn addrs(addrs: &[&str]) -> Vec<SocketAddr> {
+        addrs.iter().map(|a| a.parse().unwrap()).collect()
+    }
+
+    /// Returns a connector that fails immediately for ports in `refused`,
+    /// and otherwise succeeds after a delay of `port` milliseconds.
+    fn connect(
+        refused: &'static [u16],
+    ) -> impl Service<
+        Remote<ServerAddr>,
+        Response = (SocketAddr, Local<ClientAddr>),
+        Error = io::Error,
+        Future = impl Send,
+    > + Clone
+           + Send
+           + 'static {
+        service_fn(
+            move |Remote(ServerAddr(addr)): Remote<ServerAddr>| async move {
+                if refused.contains(&addr.port()) {
+                    return Err(io::ErrorKind::ConnectionRefused.into());
+                }
+                time::sleep(Duration::from_millis(addr.port().into())).await;
+                let local = ClientAddr(SocketAddr::new(addr.ip(), 40_000));
+                Ok((addr, Local(local)))
+            },
+        )
+    }
+
+    #[test]
+    fn interleaves_families() {
+        assert_eq!(
+            interleave(addrs(&[
+                "[::1]:1",
+                "[::2]:2",
+                "[::3]:3",
+                "192.0.2.1:4",
+                "192.0.2.2:5",
+            ])),
+            addrs(&[
+                "[::1]:1",
+                "192.0.2.1:4",
+                "[::2]:2",
+                "192.0.2.2:5",
+                "[::3]:3",
+            ])
+        );
+        assert_eq!(
+            interleave(addrs(&["192.0.2.1:1", "[::1]:2", "192.0.2.2:3"])),
+            addrs(&["192.0.2.1:1", "[::1]:2", "192.0.2.2:3"])
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn staggers_attempts() {
+        // The first candidate stalls for 10s, so the second is attempted once
+        // the attempt delay elapses.
+        let start = Instant::now();
+        let hev = HappyEyeballs::new(connect(&[]), DEFAULT_ATTEMPT_DELAY);
+        let (addr, _) = hev
+            .oneshot(Remote(ServerAddrs(addrs(&[
+                "[::1]:10000",
+                "192.0.2.1:100",
+            ]))))
+            .await
+            .unwrap();
+        assert_eq!(addr, "192.0.2.1:100".parse().unwrap());
+        assert_eq!(
+            Instant::now().saturating_duration_since(start),
+            Duration::from_millis(350)
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn connects_single_candidate() {
+        let hev = HappyEyeballs::new(connect(&[]), DEFAULT_ATTEMPT_DELAY);
+        let (addr, _) = hev
+            .oneshot(Remote(ServerAddrs(addrs(&["192.0.2.1:1000"]))))
+            .await
+            .unwrap();
+        assert_eq!(addr, "192.0.2.1:1000".parse().unwrap());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn starts_next_attempt_on_failure() {
+        let start = Instant::now();
+        let hev = HappyEyeballs::new(connect(&[1]), DEFAULT_ATTEMPT_DELAY);
+        let (addr, Local(ClientAddr(local))) = hev
+            .oneshot(Remote(ServerAddrs(addrs(&["[::1]:1", "192.0.2.1:100"]))))
+            .await
+            .unwrap();
+        assert_eq!(addr, "192.0.2.1:100".parse().unwrap());
+        assert!(local.is_ipv4());
+        assert_eq!(
+            Instant::now().saturating_duration_since(start),
+            Duration::from_millis(100)
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn fails_when_all_attempts_fail() {
+        let hev = HappyEyeballs::new(connect(&[1, 2]), DEFAULT_ATTEMPT_DELAY);
+        let error = hev
+            .oneshot(Remote(ServerAddrs(addrs(&["[::1]:1", "192.0.2.1:2"]))))
+            .await
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::ConnectionRefused);
+
+        let hev = HappyEyeballs::new(connect(&[]), DEFAULT_ATTEMPT_DELAY);
+        let error = hev.oneshot(Remote(ServerAddrs(vec![]))).await.unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    }
+}
@@ -11,16 +11,21 @@
 
 pub mod addrs;
 mod connect;
+pub mod happy_eyeballs;
 pub mod listen;
 pub mod orig_dst;
+pub mod proxy_protocol;
 
 pub use self::{
     addrs::{
         AddrPair, ClientAddr, DualListenAddr, ListenAddr, Local, OrigDstAddr, Remote, ServerAddr,
+        ServerAddrs,
     },
     connect::ConnectTcp,
+    happy_eyeballs::HappyEyeballs,
     listen::{Bind, BindTcp},
     orig_dst::BindWithOrigDst,
+    proxy_protocol::BindWithProxyProtocol,
 };
 use linkerd_io as io;
 use socket2::TcpKeepalive;
@@ -0,0 +1,622 @@
+//! Support for the [PROXY protocol][spec], which load balancers use to convey
+//! the original client address of a proxied connection.
+//!
+//! Versions 1 (text) and 2 (binary) headers are accepted from trusted
+//! networks; version 2 headers may be emitted.
+//!
+//! [spec]: https://www.haproxy.org/download/2.9/doc/proxy-protocol.txt
+
+use crate::{
+    addrs::*,
+    listen::{self, Bind},
+};
+use futures::prelude::*;
+use ipnet::IpNet;
+use linkerd_error::Result;
+use linkerd_io::{self as io, AsyncReadExt};
+use linkerd_stack::Param;
+use std::{
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    pin::Pin,
+    str,
+    sync::Arc,
+    time::Duration,
+};
+use thiserror::Error;
+use tokio::net::TcpStream;
+use tracing::debug;
+
+/// Configures which clients are expected to send PROXY protocol headers.
+///
+/// Headers are only read from clients in the trusted networks, so the default
+/// configuration disables PROXY protocol support.
+#[derive(Clone, Debug, Default)]
+pub struct Config {
+    pub trusted_networks: Arc<[IpNet]>,
+    pub header_timeout: Duration,
+}
+
+/// Reads PROXY protocol headers from connections accepted by an inner
+/// [`Bind`], replacing the connection's client address with the one conveyed
+/// by the header.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct BindWithProxyProtocol<B = listen::BindTcp> {
+    inner: B,
+}
+
+#[derive(Clone, Debug)]
+pub struct Addrs<A> {
+    pub inner: A,
+    /// The client address read from the connection's PROXY protocol header,
+    /// if one was read and it describes a proxied connection.
+    pub proxied: Option<AddrPair>,
+}
+
+/// A parsed PROXY protocol header.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Header {
+    /// The encoded length of the header.
+    pub len: usize,
+    /// The addresses of the proxied connection. This is `None` for `LOCAL`
+    /// (v2) or `UNKNOWN` (v1) connections, which should use the addresses of
+    /// the connection itself.
+    pub addrs: Option<AddrPair>,
+}
+
+#[derive(Debug, Error)]
+pub enum ParseError {
+    #[error("not a PROXY protocol header")]
+    NotProxyProtocol,
+    #[error("invalid PROXY protocol v1 header")]
+    InvalidV1,
+    #[error("invalid PROXY protocol v2 header")]
+    InvalidV2,
+    #[error("PROXY protocol header exceeds {MAX_LEN} bytes")]
+    TooLong,
+}
+
+#[derive(Debug, Error)]
+#[error("timed out reading PROXY protocol header after {0:?}")]
+pub struct HeaderTimeout(Duration);
+
+/// The maximum number of accepted connections that may be waiting on a
+/// PROXY protocol header at once.
+const MAX_PENDING_HEADERS: usize = 1_024;
+
+const V1_PREFIX: &[u8] = b"PROXY ";
+const V1_MAX_LEN: usize = 107;
+const V2_SIGNATURE: [u8; 12] = *b"\r\n\r\n\0\r\nQUIT\n";
+const V2_HEADER_LEN: usize = 16;
+
+/// The maximum length of a v2 header, including TLVs, that will be read.
+pub const MAX_LEN: usize = 4096;
+
+// === impl Config ===
+
+impl Config {
+    fn is_trusted(&self, ip: IpAddr) -> bool {
+        self.trusted_networks.iter().any(|net| net.contains(&ip))
+    }
+}
+
+// === impl Addrs ===
+
+impl<A> Param<Remote<ClientAddr>> for Addrs<A>
+where
+    A: Param<Remote<ClientAddr>>,
+{
+    #[inline]
+    fn param(&self) -> Remote<ClientAddr> {
+        match self.proxied {
+            Some(AddrPair(client, _)) => Remote(client),
+            None => self.inner.param(),
+        }
+    }
+}
+
+impl<A> Param<AddrPair> for Addrs<A>
+where
+    A: Param<AddrPair>,
+{
+    #[inline]
+    fn param(&self) -> AddrPair {
+        // The server address is preserved, since it describes where this
+        // connection was routed rather than the address on which the load
+        // balancer accepted the client.
+        let AddrPair(client, server) = self.inner.param();
+        let client = self.proxied.map_or(client, |AddrPair(client, _)| client);
+        AddrPair(client, server)
+    }
+}
+
+impl<A> Param<OrigDstAddr> for Addrs<A>
+where
+    A: Param<OrigDstAddr>,
+{
+    #[inline]
+    fn param(&self) -> OrigDstAddr {
+        self.inner.param()
+    }
+}
+
+impl<A> Param<Local<ServerAddr>> for Addrs<A>
+where
+    A: Param<Local<ServerAddr>>,
+{
+    #[inline]
+    fn param(&self) -> Local<ServerAddr> {
+        self.inner.param()
+    }
+}
+
+// === impl BindWithProxyProtocol ===
+
+impl<B> From<B> for BindWithProxyProtocol<B> {
+    fn from(inner: B) -> Self {
+        Self { inner }
+    }
+}
+
+impl<T, B> Bind<T> for BindWithProxyProtocol<B>
+where
+    T: Param<Config>,
+    B: Bind<T, Io = TcpStream> + 'static,
+    B::Addrs: Param<Remote<ClientAddr>>,
+{
+    type Addrs = Addrs<B::Addrs>;
+    type BoundAddrs = B::BoundAddrs;
+    type Io = TcpStream;
+    type Incoming =
+        Pin<Box<dyn Stream<Item = Result<(Self::Addrs, TcpStream)>> + Send + Sync + 'static>>;
+
+    fn bind(self, t: &T) -> Result<(Self::BoundAddrs, Self::Incoming)> {
+        let config: Config = t.param();
+        let (addr, incoming) = self.inner.bind(t)?;
+
+        if config.trusted_networks.is_empty() {
+            let incoming = incoming.map_ok(|(inner, tcp)| {
+                let addrs = Addrs {
+                    inner,
+                    proxied: None,
+                };
+                (addrs, tcp)
+            });
+            return Ok((addr, Box::pin(incoming)));
+        }
+
+        // Headers are read concurrently so that a slow client does not block
+        // connections from being accepted. Connections without a valid header
+        // are dropped rather than failing the listener.
+        let incoming = incoming
+            .map(move |res| accept(res, config.clone()))
+            .buffer_unordered(MAX_PENDING_HEADERS)
+            .filter_map(future::ready);
+        Ok((addr, Box::pin(incoming)))
+    }
+}
+
+/// Reads a header from a trusted client's connection.
+///
+/// Returns `None` if the connection is dropped because it did not start with a
+/// valid header. This is logged at debug level, since a misconfigured or
+/// malicious client could otherwise flood the proxy's logs.
+async fn accept<A: Param<Remote<ClientAddr>>>(
+    res: Result<(A, TcpStream)>,
+    config: Config,
+) -> Option<Result<(Addrs<A>, TcpStream)>> {
+    let (inner, mut tcp) = match res {
+        Ok(accepted) => accepted,
+        Err(error) => return Some(Err(error)),
+    };
+    let Remote(ClientAddr(client)) = inner.param();
+    if !config.is_trusted(client.ip()) {
+        let addrs = Addrs {
+            inner,
+            proxied: None,
+        };
+        return Some(Ok((addrs, tcp)));
+    }
+
+    let header = match tokio::time::timeout(config.header_timeout, read_header(&mut tcp)).await {
+        Ok(Ok(header)) => header,
+        Ok(Err(error)) => {
+            debug!(%error, client.addr = %client, "Dropping connection without a valid PROXY protocol header");
+            return None;
+        }
+        Err(_) => {
+            let error = HeaderTimeout(config.header_timeout);
+            debug!(%error, client.addr = %client, "Dropping connection without a valid PROXY protocol header");
+            return None;
+        }
+    };
+    debug!(client.addr = %client, proxied = ?header.addrs, "Read PROXY protocol header");
+    let addrs = Addrs {
+        inner,
+        proxied: header.addrs,
+    };
+    Some(Ok((addrs, tcp)))
+}
+
+/// Reads exactly one PROXY protocol header from `io`, leaving any data that
+/// follows it unread.
+pub async fn read_header<I: io::AsyncRead + Unpin>(io: &mut I) -> Result<Header> {
+    let mut buf = vec![0; V2_HEADER_LEN];
+    io.read_exact(&mut buf[..V2_SIGNATURE.len()]).await?;
+
+    if buf[..V2_SIGNATURE.len()] == V2_SIGNATURE {
+        io.read_exact(&mut buf[V2_SIGNATURE.len()..]).await?;
+        let len = V2_HEADER_LEN + usize::from(u16::from_be_bytes([buf[14], buf[15]]));
+        if len > MAX_LEN {
+            return Err(ParseError::TooLong.into());
+        }
+        buf.resize(len, 0);
+        io.read_exact(&mut buf[V2_HEADER_LEN..]).await?;
+    } else if buf.starts_with(V1_PREFIX) {
+        // The v1 header is terminated by a CRLF, so it must be read a byte at
+        // a time to avoid consuming data that follows it.
+        buf.truncate(V2_SIGNATURE.len());
+        while !buf.ends_with(b"\r\n") {
+            if buf.len() == V1_MAX_LEN {
+                return Err(ParseError::InvalidV1.into());
+            }
+            buf.push(io.read_u8().await?);
+        }
+    } else {
+        return Err(ParseError::NotProxyProtocol.into());
+    }
+
+    match parse(&buf)? {
+        Some(header) if header.len == buf.len() => Ok(header),
+        _ => Err(ParseError::NotProxyProtocol.into()),
+    }
+}
+
+/// Parses a PROXY protocol header from the start of `buf`.
+///
+/// Returns `Ok(None)` if `buf` holds an incomplete header.
+pub fn parse(buf: &[u8]) -> Result<Option<Header>, ParseError> {
+    if buf.starts_with(&V2_SIGNATURE) {
+        return parse_v2(buf);
+    }
+    if buf.starts_with(V1_PREFIX) {
+        return parse_v1(buf);
+    }
+    if V2_SIGNATURE.starts_with(buf) || V1_PREFIX.starts_with(buf) {
+        return Ok(None);
+    }
+    Err(ParseError::NotProxyProtocol)
+}
+
+fn parse_v1(buf: &[u8]) -> Result<Option<Header>, ParseError> {
+    let Some(end) = buf.windows(2).position(|w| w == b"\r\n") else {
+        if buf.len() >= V1_MAX_LEN {
+            return Err(ParseError::InvalidV1);
+        }
+        return Ok(None);
+    };
+    let len = end + 2;
+    if len > V1_MAX_LEN {
+        return Err(ParseError::InvalidV1);
+    }
+
+    let line = str::from_utf8(&buf[V1_PREFIX.len()..end]).map_err(|_| ParseError::InvalidV1)?;
+    let mut parts = line.split(' ');
+    let addrs = match parts.next() {
+        Some("UNKNOWN") => None,
+        Some(proto @ ("TCP4" | "TCP6")) => {
+            let mut next = || parts.next().ok_or(ParseError::InvalidV1);
+            let (src, dst) = (next()?, next()?);
+            let (sport, dport) = (next()?, next()?);
+            let ip = |s: &str| -> Result<IpAddr, ParseError> {
+                let ip = if proto == "TCP4" {
+                    s.parse::<Ipv4Addr>().map(IpAddr::V4)
+                } else {
+                    s.parse::<Ipv6Addr>().map(IpAddr::V6)
+                };
+                ip.map_err(|_| ParseError::InvalidV1)
+            };
+            let port = |s: &str| s.parse::<u16>().map_err(|_| ParseError::InvalidV1);
+            if parts.next().is_some() {
+                return Err(ParseError::InvalidV1);
+            }
+            Some(AddrPair(
+                ClientAddr(SocketAddr::new(ip(src)?, port(sport)?)),
+                ServerAddr(SocketAddr::new(ip(dst)?, port(dport)?)),
+            ))
+        }
+        _ => return Err(ParseError::InvalidV1),
+    };
+
+    Ok(Some(Header { len, addrs }))
+}
+
+fn parse_v2(buf: &[u8]) -> Result<Option<Header>, ParseError> {
+    if buf.len() < V2_HEADER_LEN {
+        return Ok(None);
+    }
+    let len = V2_HEADER_LEN + usize::from(u16::from_be_bytes([buf[14], buf[15]]));
+    if len > MAX_LEN {
+        return Err(ParseError::TooLong);
+    }
+    if buf.len() < len {
+        return Ok(None);
+    }
+
+    let ver_cmd = buf[12];
+    if ver_cmd >> 4 != 2 {
+        return Err(ParseError::InvalidV2);
+    }
+    let local = match ver_cmd & 0x0f {
+        0x0 => true,
+        0x1 => false,
+        _ => return Err(ParseError::InvalidV2),
+    };
+
+    let body = &buf[V2_HEADER_LEN..len];
+    let addrs = match buf[13] {
+        // TCP over IPv4.
+        0x11 => {
+            let b = body.get(..12).ok_or(ParseError::InvalidV2)?;
+            let src = Ipv4Addr::new(b[0], b[1], b[2], b[3]);
+            let dst = Ipv4Addr::new(b[4], b[5], b[6], b[7]);
+            Some((src.into(), dst.into(), &b[8..12]))
+        }
+        // TCP over IPv6.
+        0x21 => {
+            let b = body.get(..36).ok_or(ParseError::InvalidV2)?;
+            let src = <[u8; 16]>::try_from(&b[..16]).expect("slice must be 16 bytes");
+            let dst = <[u8; 16]>::try_from(&b[16..32]).expect("slice must be 16 bytes");
+            Some((
+                Ipv6Addr::from(src).into(),
+                Ipv6Addr::from(dst).into(),
+                &b[32..36],
+            ))
+        }
+        // Other transports and address families are not proxied.
+        _ => None,
+    };
+
+    let addrs = match addrs {
+        Some((src, dst, ports)) if !local => {
+            let sport = u16::from_be_bytes([ports[0], ports[1]]);
+            let dport = u16::from_be_bytes([ports[2], ports[3]]);
+            Some(AddrPair(
+                ClientAddr(SocketAddr::new(src, sport)),
+                ServerAddr(SocketAddr::new(dst, dport)),
+            ))
+        }
+        _ => None,
+    };
+
+    Ok(Some(Header { len, addrs }))
+}
+
+/// Encodes a v2 `PROXY` header describing a connection from `client` to
+/// `server`.
+///
+/// If the addresses are of different families, IPv4 addresses are encoded as
+/// IPv4-mapped IPv6 addresses.
+pub fn encode_v2(AddrPair(ClientAddr(client), ServerAddr(server)): AddrPair) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(V2_HEADER_LEN + 36);
+    buf.extend_from_slice(&V2_SIGNATURE);
+    // Version 2, PROXY command.
+    buf.push(0x21);
+    match (client.ip(), server.ip()) {
+        (IpAddr::V4(src), IpAddr::V4(dst)) => {
+            buf.push(0x11);
+            buf.extend_from_slice(&12u16.to_be_bytes());
+            buf.extend_from_slice(&src.octets());
+            buf.extend_from_slice(&dst.octets());
+        }
+        (src, dst) => {
+            let v6 = |ip: IpAddr| match ip {
+                IpAddr::V4(ip) => ip.to_ipv6_mapped(),
+                IpAddr::V6(ip) => ip,
+            };
+            buf.push(0x21);
+            buf.extend_from_slice(&36u16.to_be_bytes());
+            buf.extend_from_slice(&v6(src).octets());
+            buf.extend_from_slice(&v6(dst).octets());
+        }
+    }
+    buf.extend_from_slice(&client.port().to_be_bytes());
+    buf.extend_from_slice(&server.port().to_be_bytes());
+    buf
+}
+
+#[cfg(fuzzing)]
+pub mod fuzz_logic {
+    pub fn fuzz_entry(input: &[u8]) {
+        let _ = super::parse(input);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Backlog, Keepalive, UserTimeout};
+    use io::AsyncWriteExt;
+
+    fn pair(client: &str, server: &str) -> AddrPair {
+        AddrPair(
+            ClientAddr(client.parse().unwrap()),
+            ServerAddr(server.parse().unwrap()),
+        )
+    }
+
+    #[test]
+    fn parses_v1() {
+        let hdr = b"PROXY TCP4 192.0.2.1 192.0.2.2 56324 443\r\nGET /";
+        assert_eq!(
+            parse(hdr).unwrap(),
+            Some(Header {
+                len: hdr.len() - 5,
+                addrs: Some(pair("192.0.2.1:56324", "192.0.2.2:443")),
+            })
+        );
+
+        let hdr = b"PROXY TCP6 2001:db8::1 2001:db8::2 56324 443\r\n";
+        assert_eq!(
+            parse(hdr).unwrap().unwrap().addrs,
+            Some(pair("[2001:db8::1]:56324", "[2001:db8::2]:443"))
+        );
+
+        let hdr = b"PROXY UNKNOWN ffff::1 ffff::2 1 2\r\n";
+        assert_eq!(
+            parse(hdr).unwrap(),
+            Some(Header {
+                len: hdr.len(),
+                addrs: None
+            })
+        );
+
+        assert_eq!(parse(b"PROXY TCP4 192.0.2.1").unwrap(), None);
+        assert_eq!(parse(b"PRO").unwrap(), None);
+        assert!(parse(b"PROXY TCP4 192.0.2.1 2001:db8::2 1 2\r\n").is_err());
+        assert!(parse(b"PROXY TCP4 192.0.2.1 192.0.2.2 1\r\n").is_err());
+        assert!(parse(b"GET / HTTP/1.1\r\n").is_err());
+    }
+
+    #[test]
+    fn parses_v2() {
+        for addrs in [
+            pair("192.0.2.1:56324", "192.0.2.2:443"),
+            pair("[2001:db8::1]:56324", "[2001:db8::2]:443"),
+        ] {
+            let hdr = encode_v2(addrs);
+            assert_eq!(
+                parse(&hdr).unwrap(),
+                Some(Header {
+                    len: hdr.len(),
+                    addrs: Some(addrs),
+                })
+            );
+            assert_eq!(parse(&hdr[..hdr.len() - 1]).unwrap(), None);
+        }
+
+        // A LOCAL command uses the connection's own addresses.
+        let mut hdr = encode_v2(pair("192.0.2.1:56324", "192.0.2.2:443"));
+        hdr[12] = 0x20;
+        assert_eq!(parse(&hdr).unwrap().unwrap().addrs, None);
+
+        // Mixed families are encoded as IPv6.
+        let hdr = encode_v2(pair("192.0.2.1:56324", "[2001:db8::2]:443"));
+        assert_eq!(
+            parse(&hdr).unwrap().unwrap().addrs,
+            Some(pair("[::ffff:192.0.2.1]:56324", "[2001:db8::2]:443"))
+        );
+
+        let mut hdr = encode_v2(pair("192.0.2.1:56324", "192.0.2.2:443"));
+        hdr[12] = 0x11;
+        assert!(parse(&hdr).is_err());
+    }
+
+    #[derive(Clone)]
+    struct Params(Config);
+
+    impl Param<ListenAddr> for Params {
+        fn param(&self) -> ListenAddr {
+            ListenAddr("127.0.0.1:0".parse().unwrap())
+        }
+    }
+
+    impl Param<Keepalive> for Params {
+        fn param(&self) -> Keepalive {
+            Keepalive(None)
+        }
+    }
+
+    impl Param<UserTimeout> for Params {
+        fn param(&self) -> UserTimeout {
+            UserTimeout(None)
+        }
+    }
+
+    impl Param<Backlog> for Params {
+        fn param(&self) -> Backlog {
+            Backlog(None)
+        }
+    }
+
+    impl Param<Config> for Params {
+        fn param(&self) -> Config {
+            self.0.clone()
+        }
+    }
+
+    async fn accept_with(config: Config, prefix: &[u8]) -> Result<(AddrPair, Vec<u8>)> {
+        let bind = BindWithProxyProtocol::from(listen::BindTcp::default());
+        let (Local(ServerAddr(addr)), mut incoming) = bind.bind(&Params(config))?;
+
+        let mut client = TcpStream::connect(addr).await?;
+        client.write_all(prefix).await?;
+        client.write_all(b"hello, world").await?;
+
+        let (addrs, mut io) = incoming.next().await.expect("must accept")?;
+        let mut data = vec![0; 12];
+        io.read_exact(&mut data).await?;
+        Ok((addrs.param(), data))
+    }
+
+    #[tokio::test]
+    async fn bind_reads_header_from_trusted_clients() {
+        let config = Config {
+            trusted_networks: vec!["127.0.0.0/8".parse().unwrap()].into(),
+            header_timeout: Duration::from_secs(10),
+        };
+
+        let proxied = pair("192.0.2.1:56324", "192.0.2.2:443");
+        let (AddrPair(client, _), data) = accept_with(config.clone(), &encode_v2(proxied))
+            .await
+            .unwrap();
+        assert_eq!(client, proxied.0);
+        assert_eq!(data, b"hello, world");
+
+        let (AddrPair(client, _), data) = accept_with(
+            config.clone(),
+            b"PROXY TCP4 192.0.2.1 192.0.2.2 56324 443\r\n",
+        )
+        .await
+        .unwrap();
+        assert_eq!(client, proxied.0);
+        assert_eq!(data, b"hello, world");
+    }
+
+    #[tokio::test]
+    async fn bind_drops_trusted_clients_without_header() {
+        let config = Config {
+            trusted_networks: vec!["127.0.0.0/8".parse().unwrap()].into(),
+            header_timeout: Duration::from_secs(10),
+        };
+        let bind = BindWithProxyProtocol::from(listen::BindTcp::default());
+        let (Local(ServerAddr(addr)), mut incoming) = bind.bind(&Params(config)).unwrap();
+
+        // The first client doesn't send a header, so its connection is dropped
+        // without failing the listener.
+        let mut headerless = TcpStream::connect(addr).await.unwrap();
+        headerless.write_all(b"hello, world").await.unwrap();
+
+        let proxied = pair("192.0.2.1:56324", "192.0.2.2:443");
+        let mut client = TcpStream::connect(addr).await.unwrap();
+        client.write_all(&encode_v2(proxied)).await.unwrap();
+
+        let (addrs, _io) = incoming.next().await.expect("must accept").unwrap();
+        let AddrPair(client_addr, _) = addrs.param();
+        assert_eq!(client_addr, proxied.0);
+
+        // The dropped connection is closed or reset.
+        let mut buf = [0; 1];
+        assert!(!matches!(headerless.read(&mut buf).await, Ok(n) if n > 0));
+    }
+
+    #[tokio::test]
+    async fn bind_ignores_untrusted_clients() {
+        let config = Config {
+            trusted_networks: vec!["192.0.2.0/24".parse().unwrap()].into(),
+            header_timeout: Duration::from_secs(10),
+        };
+        let (AddrPair(ClientAddr(client), _), data) = accept_with(config, b"").await.unwrap();
+        assert!(client.ip().is_loopback());
+        assert_eq!(data, b"hello, world");
+    }
+}
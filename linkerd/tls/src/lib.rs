@@ -10,9 +10,9 @@ pub use self::{
         ConnectMeta, NoClientTls, ServerId,
     },
     server::{
-        ClientId, ConditionalServerTls, ConditionalServerTlsLabels, NewDetectRequiredSni,
-        NewDetectTls, NoServerTls, NoSniFoundError, ServerTls, ServerTlsLabels,
-        SniDetectionTimeoutError,
+        ClientId, ConditionalServerTls, ConditionalServerTlsLabels, LocalIdentities,
+        NewDetectRequiredSni, NewDetectTls, NoServerTls, NoSniFoundError, ServerTls,
+        ServerTlsLabels, SniDetectionTimeoutError,
     },
 };
 
@@ -1,7 +1,7 @@
-mod client_hello;
+pub mod client_hello;
 mod required_sni;
 
-use crate::{NegotiatedProtocol, ServerName};
+use crate::{NegotiatedProtocol, ServerId, ServerName};
 use bytes::BytesMut;
 use futures::prelude::*;
 use linkerd_conditional::Conditional;
@@ -13,6 +13,7 @@ use std::{
     fmt,
     ops::Deref,
     pin::Pin,
+    sync::Arc,
     task::{Context, Poll},
 };
 use thiserror::Error;
@@ -31,12 +32,25 @@ pub enum ServerTls {
     Established {
         client_id: Option<ClientId>,
         negotiated_protocol: Option<NegotiatedProtocol>,
+        /// The local identity that was presented to the client, if known.
+        ///
+        /// Shared with the [`LocalIdentities`] from which it was selected, so
+        /// that the connection's TLS status stays small.
+        server_id: Option<Arc<ServerId>>,
     },
     Passthru {
         sni: ServerName,
     },
 }
 
+/// Describes the local identities for which a TLS server terminates
+/// connections.
+///
+/// Each identity is selected by the SNI value that clients send to it. The
+/// first entry is the proxy's primary identity.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LocalIdentities(Arc<[(ServerName, Arc<ServerId>)]>);
+
 /// Prometheus labels for a [`ServerTls`].
 #[derive(Clone, Debug, Eq, PartialEq, Hash)]
 pub enum ServerTlsLabels {
@@ -146,7 +160,7 @@ where
     T: Clone + Send + 'static,
     P: InsertParam<ConditionalServerTls, T> + Clone + Send + Sync + 'static,
     P::Target: Send + 'static,
-    L: Param<ServerName> + Clone + Send + 'static,
+    L: Param<LocalIdentities> + Clone + Send + 'static,
     L: Service<DetectIo<I>, Response = (ServerTls, LIo), Error = io::Error>,
     L::Future: Send,
     LIo: io::AsyncRead + io::AsyncWrite + Send + Sync + Unpin + 'static,
@@ -176,10 +190,11 @@ where
         Box::pin(async move {
             let (sni, io) = detect.await.map_err(|_| ServerTlsTimeoutError(()))??;
 
-            let local_server_name = tls.param();
+            let local = tls.param();
             let (peer, io) = match sni {
-                // If we detected an SNI matching this proxy, terminate TLS.
-                Some(sni) if sni == local_server_name => {
+                // If we detected an SNI matching one of this proxy's
+                // identities, terminate TLS.
+                Some(sni) if local.contains(&sni) => {
                     trace!("Identified local SNI");
                     let (peer, io) = tls.oneshot(io).await?;
                     (Conditional::Some(peer), EitherIo::Left(io))
@@ -290,6 +305,47 @@ impl std::str::FromStr for ClientId {
     }
 }
 
+// === impl LocalIdentities ===
+
+impl LocalIdentities {
+    /// Returns a set of local identities with only a primary identity.
+    pub fn new(name: ServerName, id: id::Id) -> Self {
+        Self(Arc::new([(name, Arc::new(ServerId(id)))]))
+    }
+
+    /// Returns a set of local identities with a primary identity and any
+    /// number of additional identities.
+    pub fn with_additional(
+        name: ServerName,
+        id: id::Id,
+        additional: impl IntoIterator<Item = (ServerName, id::Id)>,
+    ) -> Self {
+        Self(
+            std::iter::once((name, id))
+                .chain(additional)
+                .map(|(name, id)| (name, Arc::new(ServerId(id))))
+                .collect(),
+        )
+    }
+
+    /// Returns the primary server name.
+    pub fn primary(&self) -> &ServerName {
+        &self.0[0].0
+    }
+
+    /// Returns true if TLS is terminated for the given SNI value.
+    pub fn contains(&self, sni: &ServerName) -> bool {
+        self.get(sni).is_some()
+    }
+
+    /// Returns the local identity selected by the given SNI value.
+    pub fn get(&self, sni: &ServerName) -> Option<&Arc<ServerId>> {
+        self.0
+            .iter()
+            .find_map(|(name, id)| (name == sni).then_some(id))
+    }
+}
+
 // === impl NoClientId ===
 
 impl fmt::Display for NoServerTls {
@@ -318,6 +374,7 @@ impl ServerTls {
             Self::Established {
                 client_id,
                 negotiated_protocol: _,
+                server_id: _,
             } => ServerTlsLabels::Established {
                 client_id: client_id.clone(),
             },
@@ -8,6 +8,10 @@ use std::task::{Context, Poll};
 use tokio::sync::watch;
 use tracing::{debug, trace, Instrument};
 
+mod snapshot;
+
+pub use self::snapshot::{Snapshot, Snapshots};
+
 /// A service that streams updates from an inner service into a `tokio::sync::watch::Receiver` on a
 /// background task.
 ///
@@ -303,6 +307,50 @@ mod tests {
         assert_eq!(*rx.borrow(), 345);
     }
 
+    #[tokio::test(flavor = "current_thread")]
+    async fn watch_reconnect_preserves_snapshot() {
+        let _trace = linkerd_tracing::test::trace_init();
+
+        time::pause();
+
+        let snapshots = Snapshots::<(), u16>::default();
+        let (mock, mut handle) = mk_svc::<(), u16>();
+        let watch = StreamWatch::new(recover::Immediately::default(), mock);
+
+        handle.allow(1);
+        let (tx0, rx0) = mpsc::channel::<Result<u16>>(3);
+        let stream0 = snapshots.observe((), Box::pin(ReceiverStream::new(rx0)));
+        let send_req = handle.next_request().map(move |req| {
+            let ((), rsp) = req.unwrap();
+            rsp.send_response(tonic::Response::new(stream0))
+        });
+        let (_, _, rx) = tokio::join!(tx0.send(Ok(123u16)), send_req, watch.spawn_watch(()));
+        let rx = rx.unwrap().into_inner();
+        assert_eq!(*rx.borrow(), 123);
+        let snap = snapshots.get(&()).expect("must have a snapshot");
+        assert_eq!((snap.value, snap.generation), (123, 1));
+
+        // The stream fails and is re-established.
+        tx0.send(Err(tonic::Status::ok("disconnect")))
+            .await
+            .unwrap();
+
+        handle.allow(1);
+        let ((), rsp) = handle.next_request().await.unwrap();
+        let (tx1, rx1) = mpsc::channel(3);
+        rsp.send_response(tonic::Response::new(
+            snapshots.observe((), Box::pin(ReceiverStream::new(rx1))),
+        ));
+        tx1.send(Ok(345u16)).await.unwrap();
+
+        // We need to give the background task an opportunity to process the update.
+        tokio::task::yield_now().await;
+
+        assert_eq!(*rx.borrow(), 345);
+        let snap = snapshots.get(&()).expect("must have a snapshot");
+        assert_eq!((snap.value, snap.generation), (345, 2));
+    }
+
     #[tokio::test(flavor = "current_thread")]
     async fn block_on_initial_failure() {
         let _trace = linkerd_tracing::test::trace_init();
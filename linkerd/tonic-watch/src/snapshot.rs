@@ -0,0 +1,261 @@
+use futures::prelude::*;
+use parking_lot::RwLock;
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+use tokio::time::Instant;
+
+/// Records the latest value published for each of a set of watched targets,
+/// so that the state of all active watches may be inspected.
+///
+/// A target's snapshot is retained for a time after the last stream observed
+/// for it is dropped, so a watch's generation is preserved when its stream is
+/// re-established. Only targets with an observed stream are reported.
+#[derive(Debug)]
+pub struct Snapshots<T, U> {
+    inner: Arc<RwLock<HashMap<T, Entry<U>>>>,
+}
+
+/// The latest value published for a target.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Snapshot<U> {
+    pub value: U,
+    /// The number of values that have been published for the target.
+    pub generation: u64,
+    pub last_update: SystemTime,
+}
+
+#[derive(Debug)]
+struct Entry<U> {
+    latest: Option<Snapshot<U>>,
+    /// The number of observed streams for the target.
+    streams: usize,
+    /// Set when the number of observed streams drops to zero. Entries that are
+    /// not permanent are removed once they have been idle for
+    /// [`IDLE_RETENTION`].
+    idle_since: Option<Instant>,
+    permanent: bool,
+}
+
+/// How long a target's snapshot is retained without an observed stream.
+const IDLE_RETENTION: Duration = Duration::from_secs(60);
+
+/// Held by an observed stream, updating the target's snapshot.
+struct Observer<T: Eq + Hash, U> {
+    snapshots: Snapshots<T, U>,
+    target: T,
+}
+
+// === impl Snapshots ===
+
+impl<T, U> Default for Snapshots<T, U> {
+    fn default() -> Self {
+        Self {
+            inner: Default::default(),
+        }
+    }
+}
+
+impl<T, U> Clone for Snapshots<T, U> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T, U> Snapshots<T, U>
+where
+    T: Clone + Eq + Hash + Send + Sync + 'static,
+    U: Clone + Send + Sync + 'static,
+{
+    /// Records a value that is not published by a stream, e.g. from static
+    /// configuration. The target's snapshot is never removed.
+    pub fn record(&self, target: T, value: U) {
+        let mut entries = self.inner.write();
+        let entry = entries.entry(target).or_insert_with(Entry::new);
+        entry.permanent = true;
+        entry.update(value);
+    }
+
+    /// Wraps a stream so that each value it yields is recorded as the latest
+    /// value for `target`.
+    pub fn observe<E: Send + 'static>(
+        &self,
+        target: T,
+        stream: stream::BoxStream<'static, Result<U, E>>,
+    ) -> stream::BoxStream<'static, Result<U, E>> {
+        let mut entries = self.inner.write();
+        let now = Instant::now();
+        entries.retain(|_, e| !e.is_expired(now));
+        let entry = entries.entry(target.clone()).or_insert_with(Entry::new);
+        entry.streams += 1;
+        entry.idle_since = None;
+        drop(entries);
+
+        let observer = Observer {
+            snapshots: self.clone(),
+            target,
+        };
+        stream
+            .inspect_ok(move |value| observer.update(value.clone()))
+            .boxed()
+    }
+
+    pub fn get(&self, target: &T) -> Option<Snapshot<U>> {
+        let entries = self.inner.read();
+        let entry = entries.get(target).filter(|e| e.is_active())?;
+        entry.latest.clone()
+    }
+
+    /// Returns the latest values for all active targets that have published a
+    /// value.
+    pub fn snapshot(&self) -> Vec<(T, Snapshot<U>)> {
+        self.inner
+            .read()
+            .iter()
+            .filter(|(_, e)| e.is_active())
+            .filter_map(|(t, e)| Some((t.clone(), e.latest.clone()?)))
+            .collect()
+    }
+}
+
+// === impl Entry ===
+
+impl<U> Entry<U> {
+    fn new() -> Self {
+        Self {
+            latest: None,
+            streams: 0,
+            idle_since: None,
+            permanent: false,
+        }
+    }
+
+    fn is_active(&self) -> bool {
+        self.permanent || self.streams > 0
+    }
+
+    fn is_expired(&self, now: Instant) -> bool {
+        !self.permanent
+            && self
+                .idle_since
+                .is_some_and(|t| now.saturating_duration_since(t) >= IDLE_RETENTION)
+    }
+
+    fn update(&mut self, value: U) {
+        let generation = self.latest.as_ref().map_or(0, |s| s.generation) + 1;
+        self.latest = Some(Snapshot {
+            value,
+            generation,
+            last_update: SystemTime::now(),
+        });
+    }
+}
+
+// === impl Observer ===
+
+impl<T: Eq + Hash, U> Observer<T, U> {
+    fn update(&self, value: U) {
+        if let Some(entry) = self.snapshots.inner.write().get_mut(&self.target) {
+            entry.update(value);
+        }
+    }
+}
+
+impl<T: Eq + Hash, U> Drop for Observer<T, U> {
+    fn drop(&mut self) {
+        let mut entries = self.snapshots.inner.write();
+        if let Some(entry) = entries.get_mut(&self.target) {
+            entry.streams -= 1;
+            if entry.streams == 0 {
+                entry.idle_since = Some(Instant::now());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::mpsc;
+    use tokio_stream::wrappers::UnboundedReceiverStream;
+
+    fn updates(
+        values: Vec<u16>,
+    ) -> (
+        mpsc::UnboundedSender<Result<u16, ()>>,
+        stream::BoxStream<'static, Result<u16, ()>>,
+    ) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        for v in values {
+            tx.send(Ok(v)).unwrap();
+        }
+        (tx, UnboundedReceiverStream::new(rx).boxed())
+    }
+
+    #[tokio::test]
+    async fn tracks_generations_across_streams() {
+        let snapshots = Snapshots::<&'static str, u16>::default();
+
+        let (_tx0, rx0) = updates(vec![1, 2]);
+        let mut rx0 = snapshots.observe("a", rx0);
+        assert_eq!(snapshots.get(&"a"), None);
+        rx0.next().await;
+        rx0.next().await;
+        let snap = snapshots.get(&"a").expect("must have a snapshot");
+        assert_eq!((snap.value, snap.generation), (2, 2));
+
+        // The stream is re-established before the prior stream is dropped.
+        let (_tx1, rx1) = updates(vec![3]);
+        let mut rx1 = snapshots.observe("a", rx1);
+        drop(rx0);
+        rx1.next().await;
+        let snap = snapshots.get(&"a").expect("must have a snapshot");
+        assert_eq!((snap.value, snap.generation), (3, 3));
+
+        drop(rx1);
+        assert_eq!(snapshots.get(&"a"), None);
+        assert!(snapshots.snapshot().is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn retains_generations_while_idle() {
+        let snapshots = Snapshots::<&'static str, u16>::default();
+
+        let (_tx0, rx0) = updates(vec![1]);
+        let mut rx0 = snapshots.observe("a", rx0);
+        rx0.next().await;
+        drop(rx0);
+
+        // The stream is re-established after the prior stream is dropped.
+        let (_tx1, rx1) = updates(vec![2]);
+        let mut rx1 = snapshots.observe("a", rx1);
+        rx1.next().await;
+        let snap = snapshots.get(&"a").expect("must have a snapshot");
+        assert_eq!((snap.value, snap.generation), (2, 2));
+        drop(rx1);
+
+        // Once the target has been idle for the retention period, its
+        // generation is reset.
+        tokio::time::sleep(IDLE_RETENTION).await;
+        let (_tx2, rx2) = updates(vec![3]);
+        let mut rx2 = snapshots.observe("a", rx2);
+        rx2.next().await;
+        let snap = snapshots.get(&"a").expect("must have a snapshot");
+        assert_eq!((snap.value, snap.generation), (3, 1));
+    }
+
+    #[test]
+    fn retains_recorded_values() {
+        let snapshots = Snapshots::<&'static str, u16>::default();
+        snapshots.record("a", 1);
+        snapshots.record("a", 2);
+        let snaps = snapshots.snapshot();
+        assert_eq!(snaps.len(), 1);
+        assert_eq!((snaps[0].1.value, snaps[0].1.generation), (2, 2));
+    }
+}
@@ -3,9 +3,14 @@
 
 pub mod export;
 mod propagation;
+mod sampler;
 mod service;
 
-pub use self::service::TraceContext;
+pub use self::{
+    propagation::BaggageConfig,
+    sampler::{sample_route, Sampler, SamplerConfig, TailConfig},
+    service::TraceContext,
+};
 use bytes::Bytes;
 use linkerd_error::Error;
 use rand::Rng;
@@ -15,6 +20,7 @@ use std::time::SystemTime;
 use thiserror::Error;
 
 const SPAN_ID_LEN: usize = 8;
+const TRACE_ID_LEN: usize = 16;
 
 #[derive(Debug, Default, Clone, Eq, PartialEq)]
 pub struct Id(Vec<u8>);
@@ -43,6 +49,13 @@ impl Id {
 #[derive(Debug, Default)]
 pub struct Flags(u8);
 
+/// A request extension that identifies the trace of a request that was sampled
+/// by the proxy.
+#[derive(Clone, Debug)]
+pub struct SampledTrace {
+    pub trace_id: Id,
+}
+
 #[derive(Debug, Error)]
 #[error("insufficient bytes when decoding binary header")]
 pub struct InsufficientBytes;
@@ -56,11 +69,25 @@ pub struct Span {
     pub start: SystemTime,
     pub end: SystemTime,
     pub labels: HashMap<&'static str, String>,
+    /// Baggage entries that are recorded as span attributes.
+    pub baggage: Vec<(String, String)>,
 }
 
 pub trait SpanSink {
     fn is_enabled(&self) -> bool;
 
+    /// Returns the policy used to make sampling decisions for requests that
+    /// are not already sampled. When `None`, only sampled requests are traced.
+    fn sampler(&self) -> Option<&Sampler> {
+        None
+    }
+
+    /// Returns the configuration used to handle W3C Baggage. When `None`,
+    /// baggage is forwarded unchanged.
+    fn baggage(&self) -> Option<&BaggageConfig> {
+        None
+    }
+
     fn try_send(&mut self, span: Span) -> Result<(), Error>;
 }
 
@@ -70,6 +97,16 @@ impl<K: SpanSink> SpanSink for Option<K> {
         self.as_ref().map(SpanSink::is_enabled).unwrap_or(false)
     }
 
+    #[inline]
+    fn sampler(&self) -> Option<&Sampler> {
+        self.as_ref().and_then(SpanSink::sampler)
+    }
+
+    #[inline]
+    fn baggage(&self) -> Option<&BaggageConfig> {
+        self.as_ref().and_then(SpanSink::baggage)
+    }
+
     #[inline]
     fn try_send(&mut self, span: Span) -> Result<(), Error> {
         self.as_mut().expect("Must be enabled").try_send(span)
@@ -80,9 +117,17 @@ impl<K: SpanSink> SpanSink for Option<K> {
 
 impl Id {
     fn new_span_id<R: Rng>(rng: &mut R) -> Self {
+        Self::random(rng, SPAN_ID_LEN)
+    }
+
+    fn new_trace_id<R: Rng>(rng: &mut R) -> Self {
+        Self::random(rng, TRACE_ID_LEN)
+    }
+
+    fn random<R: Rng>(rng: &mut R, len: usize) -> Self {
         use rand::RngExt;
 
-        let mut bytes = vec![0; SPAN_ID_LEN];
+        let mut bytes = vec![0; len];
         rng.fill(bytes.as_mut_slice());
         Self(bytes)
     }
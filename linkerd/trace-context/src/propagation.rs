@@ -5,8 +5,11 @@ use thiserror::Error;
 use tracing::debug;
 
 mod b3;
+mod baggage;
 mod w3c;
 
+pub use self::baggage::{apply_baggage, BaggageConfig};
+
 #[derive(Debug)]
 pub enum Propagation {
     B3Http,
@@ -56,6 +59,12 @@ pub fn increment_span_id<B>(request: &mut http::Request<B>, context: &TraceConte
     }
 }
 
+/// Updates the sampled flag of a trace context that the proxy started. The
+/// proxy only starts w3c trace contexts.
+pub fn set_sampled<B>(request: &mut http::Request<B>, sampled: bool) {
+    w3c::set_http_sampled(request, sampled)
+}
+
 // === Header parse utils ===
 
 fn get_header_str<'a, B>(
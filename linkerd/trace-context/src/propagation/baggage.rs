@@ -0,0 +1,247 @@
+use http::header::HeaderName;
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, CONTROLS};
+use tracing::debug;
+
+static HTTP_BAGGAGE: HeaderName = HeaderName::from_static("baggage");
+
+/// Characters that must be percent-encoded in baggage values.
+///
+/// See https://www.w3.org/TR/baggage/#value
+const VALUE_ENCODE_SET: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b',')
+    .add(b';')
+    .add(b'\\')
+    .add(b'%');
+
+/// Configures how the proxy handles W3C Baggage.
+///
+/// Baggage is forwarded unchanged unless the proxy is configured to set its
+/// own entries.
+#[derive(Clone, Debug)]
+pub struct BaggageConfig {
+    /// Baggage keys whose values are recorded as span attributes.
+    pub span_attributes: Vec<String>,
+
+    /// Entries that the proxy sets on each request's baggage, replacing any
+    /// entries with the same key.
+    pub entries: Vec<(String, String)>,
+
+    /// The largest baggage header that the proxy parses or produces.
+    pub max_bytes: usize,
+}
+
+// === impl BaggageConfig ===
+
+impl BaggageConfig {
+    /// The minimum size that the W3C Baggage specification requires
+    /// implementations to propagate.
+    pub const DEFAULT_MAX_BYTES: usize = 8192;
+}
+
+impl Default for BaggageConfig {
+    fn default() -> Self {
+        Self {
+            span_attributes: Vec::new(),
+            entries: Vec::new(),
+            max_bytes: Self::DEFAULT_MAX_BYTES,
+        }
+    }
+}
+
+/// Sets the proxy's baggage entries on the request and returns the values of
+/// the baggage keys that are recorded as span attributes.
+///
+/// Baggage larger than `max_bytes` is neither parsed nor modified.
+pub fn apply_baggage<B>(
+    config: &BaggageConfig,
+    request: &mut http::Request<B>,
+) -> Vec<(String, String)> {
+    if config.span_attributes.is_empty() && config.entries.is_empty() {
+        return Vec::new();
+    }
+
+    // A request may carry multiple baggage headers, which are combined as a
+    // single list.
+    let mut header = String::new();
+    for value in request.headers().get_all(&HTTP_BAGGAGE) {
+        let Ok(value) = value.to_str() else {
+            debug!(header = %HTTP_BAGGAGE, "Invalid non-ASCII or control character in header value");
+            return Vec::new();
+        };
+        if !header.is_empty() {
+            header.push(',');
+        }
+        header.push_str(value);
+        if header.len() > config.max_bytes {
+            debug!(max_bytes = config.max_bytes, "Ignoring oversized baggage");
+            return Vec::new();
+        }
+    }
+
+    let members = parse_members(&header);
+    let attributes = members
+        .iter()
+        .filter(|m| config.span_attributes.iter().any(|k| k == m.key))
+        .filter_map(|m| Some((m.key.to_owned(), m.value()?)))
+        .collect();
+
+    if !config.entries.is_empty() {
+        let mut baggage = members
+            .iter()
+            .filter(|m| !config.entries.iter().any(|(k, _)| k == m.key))
+            .map(|m| m.raw.to_owned())
+            .collect::<Vec<_>>();
+        baggage.extend(
+            config
+                .entries
+                .iter()
+                .map(|(k, v)| format!("{k}={}", utf8_percent_encode(v, VALUE_ENCODE_SET))),
+        );
+        let baggage = baggage.join(",");
+        if baggage.len() > config.max_bytes {
+            debug!(
+                max_bytes = config.max_bytes,
+                "Not setting baggage entries that exceed the size limit"
+            );
+        } else if let Ok(value) = http::HeaderValue::from_str(&baggage) {
+            request.headers_mut().insert(&HTTP_BAGGAGE, value);
+        }
+    }
+
+    attributes
+}
+
+/// A single `key=value;properties` baggage list member.
+#[derive(Debug, PartialEq)]
+struct Member<'a> {
+    raw: &'a str,
+    key: &'a str,
+    value: &'a str,
+}
+
+impl Member<'_> {
+    /// Returns the member's decoded value.
+    fn value(&self) -> Option<String> {
+        percent_decode_str(self.value)
+            .decode_utf8()
+            .ok()
+            .map(Into::into)
+    }
+}
+
+/// Parses a baggage header value, skipping invalid members.
+fn parse_members(header: &str) -> Vec<Member<'_>> {
+    header
+        .split(',')
+        .filter_map(|raw| {
+            let raw = raw.trim();
+            let (entry, _properties) = raw.split_once(';').unwrap_or((raw, ""));
+            let (key, value) = entry.split_once('=')?;
+            let key = key.trim();
+            if key.is_empty() {
+                return None;
+            }
+            Some(Member {
+                raw,
+                key,
+                value: value.trim(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(baggage: &[&str]) -> http::Request<()> {
+        let mut req = http::Request::new(());
+        for value in baggage {
+            req.headers_mut()
+                .append(&HTTP_BAGGAGE, value.parse().unwrap());
+        }
+        req
+    }
+
+    fn baggage(req: &http::Request<()>) -> Vec<&str> {
+        req.headers()
+            .get_all(&HTTP_BAGGAGE)
+            .iter()
+            .map(|v| v.to_str().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn parses_members() {
+        assert_eq!(
+            parse_members(" tenant = acme ;ttl=3, ,invalid,=x,tier=gold%20plus"),
+            vec![
+                Member {
+                    raw: "tenant = acme ;ttl=3",
+                    key: "tenant",
+                    value: "acme",
+                },
+                Member {
+                    raw: "tier=gold%20plus",
+                    key: "tier",
+                    value: "gold%20plus",
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn promotes_attributes() {
+        let config = BaggageConfig {
+            span_attributes: vec!["tier".to_owned(), "missing".to_owned()],
+            ..Default::default()
+        };
+        let mut req = request(&["tenant=acme", "tier=gold%20plus;ttl=3"]);
+        let attributes = apply_baggage(&config, &mut req);
+        assert_eq!(
+            attributes,
+            vec![("tier".to_owned(), "gold plus".to_owned())]
+        );
+        // Baggage is forwarded unchanged.
+        assert_eq!(baggage(&req), vec!["tenant=acme", "tier=gold%20plus;ttl=3"]);
+    }
+
+    #[test]
+    fn sets_entries() {
+        let config = BaggageConfig {
+            entries: vec![("workload".to_owned(), "web, prod".to_owned())],
+            ..Default::default()
+        };
+        let mut req = request(&["tenant=acme,workload=old"]);
+        apply_baggage(&config, &mut req);
+        assert_eq!(baggage(&req), vec!["tenant=acme,workload=web%2C%20prod"]);
+
+        let mut req = request(&[]);
+        apply_baggage(&config, &mut req);
+        assert_eq!(baggage(&req), vec!["workload=web%2C%20prod"]);
+    }
+
+    #[test]
+    fn limits_size() {
+        let config = BaggageConfig {
+            span_attributes: vec!["tenant".to_owned()],
+            entries: vec![("workload".to_owned(), "web".to_owned())],
+            max_bytes: 16,
+        };
+
+        // Oversized baggage is neither parsed nor modified.
+        let mut req = request(&["tenant=acme", "other=0123456789"]);
+        assert!(apply_baggage(&config, &mut req).is_empty());
+        assert_eq!(baggage(&req), vec!["tenant=acme", "other=0123456789"]);
+
+        // Entries are not set when they would exceed the limit.
+        let mut req = request(&["tenant=acme"]);
+        assert_eq!(
+            apply_baggage(&config, &mut req),
+            vec![("tenant".to_owned(), "acme".to_owned())]
+        );
+        assert_eq!(baggage(&req), vec!["tenant=acme"]);
+    }
+}
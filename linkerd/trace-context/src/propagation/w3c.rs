@@ -19,15 +19,31 @@ pub fn increment_http_span_id<B>(request: &mut http::Request<B>, context: &Trace
 
     trace!(%span_id, "Incremented span id");
 
+    set_traceparent(request, &context.trace_id, &span_id, &context.flags);
+
+    span_id
+}
+
+/// Updates the sampled flag of the request's w3c trace context, e.g. when the
+/// proxy changes its sampling decision for a trace that it started.
+pub fn set_http_sampled<B>(request: &mut http::Request<B>, sampled: bool) {
+    let Some(context) = unpack_w3c_trace_context(request) else {
+        return;
+    };
+    let flags = Flags((context.flags.0 & !1) | sampled as u8);
+    set_traceparent(request, &context.trace_id, &context.parent_id, &flags);
+}
+
+fn set_traceparent<B>(request: &mut http::Request<B>, trace_id: &Id, span_id: &Id, flags: &Flags) {
     let new_header = {
         let mut buf = String::with_capacity(60);
         buf.push_str(VERSION_00);
         buf.push('-');
-        buf.push_str(&hex::encode(context.trace_id.as_ref()));
+        buf.push_str(&hex::encode(trace_id.as_ref()));
         buf.push('-');
         buf.push_str(&hex::encode(span_id.as_ref()));
         buf.push('-');
-        buf.push_str(&hex::encode(vec![context.flags.0]));
+        buf.push_str(&hex::encode(vec![flags.0]));
         buf
     };
 
@@ -36,8 +52,6 @@ pub fn increment_http_span_id<B>(request: &mut http::Request<B>, context: &Trace
     } else {
         debug!(header = %HTTP_TRACEPARENT, header_value = %new_header, "Invalid non-ASCII or control character in header value");
     }
-
-    span_id
 }
 
 /// Parse a given header value as a w3c TraceContext value.
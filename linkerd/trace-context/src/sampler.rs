@@ -0,0 +1,329 @@
+use crate::{
+    propagation::{self, Propagation, TraceContext},
+    Flags, Id, SampledTrace,
+};
+use parking_lot::Mutex;
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::time::Instant;
+
+/// Configures the sampling decisions that the proxy makes.
+///
+/// By default, the proxy only traces requests whose trace context indicates
+/// that they are sampled.
+#[derive(Clone, Debug, Default)]
+pub struct SamplerConfig {
+    /// The probability with which requests without a trace context are
+    /// sampled.
+    pub head_rate: f64,
+
+    /// Overrides `head_rate` for requests that are routed by a policy route.
+    /// Routes are named as `<kind>/<name>`, e.g. `httproute/checkout`.
+    pub route_rates: Vec<(String, f64)>,
+
+    /// The maximum number of traces that the proxy starts each second.
+    pub max_traces_per_second: Option<u32>,
+
+    /// When set, spans are recorded for requests that are not sampled and are
+    /// exported if the request fails or is slow.
+    pub tail: Option<TailConfig>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct TailConfig {
+    /// Responses with at least this status are exported.
+    pub min_status: Option<http::StatusCode>,
+
+    /// Responses that take at least this long are exported.
+    pub min_latency: Option<Duration>,
+}
+
+/// Makes the proxy's sampling decisions.
+#[derive(Clone, Debug, Default)]
+pub struct Sampler {
+    config: Arc<SamplerConfig>,
+    limit: Option<Arc<Mutex<RateLimit>>>,
+}
+
+/// A request extension that lets the request's policy route override the
+/// sampling decision for a trace that the proxy started.
+///
+/// The proxy starts traces before requests are routed, so routes apply their
+/// sampling rates by calling [`sample_route`].
+#[derive(Clone, Debug)]
+pub(crate) struct RouteSampling(Arc<RouteSamplingInner>);
+
+#[derive(Debug)]
+struct RouteSamplingInner {
+    sampler: Sampler,
+    trace_id: Id,
+    sampled: AtomicBool,
+}
+
+/// Limits the number of traces started in each one-second window.
+#[derive(Debug)]
+struct RateLimit {
+    max: u32,
+    window: Instant,
+    started: u32,
+}
+
+// === impl Sampler ===
+
+impl Sampler {
+    pub fn new(config: SamplerConfig) -> Self {
+        let limit = config.max_traces_per_second.map(|max| {
+            Arc::new(Mutex::new(RateLimit {
+                max,
+                window: Instant::now(),
+                started: 0,
+            }))
+        });
+        Self {
+            config: Arc::new(config),
+            limit,
+        }
+    }
+
+    /// Starts a new trace for a request that has no trace context. Returns
+    /// `None` if the proxy does not make sampling decisions.
+    ///
+    /// The returned context is sampled if the request was selected by the
+    /// head sampling rate and the rate limit permits another trace. The
+    /// request's route may later override this decision (see
+    /// [`sample_route`]).
+    pub(crate) fn start_trace(&self) -> Option<TraceContext> {
+        if !self.is_active() {
+            return None;
+        }
+
+        let sampled = draw(self.config.head_rate) && self.acquire();
+
+        Some(TraceContext {
+            propagation: Propagation::W3CHttp,
+            trace_id: Id::new_trace_id(&mut rand::rng()),
+            parent_id: Id::default(),
+            flags: Flags(sampled as u8),
+        })
+    }
+
+    pub(crate) fn tail(&self) -> Option<&TailConfig> {
+        self.config.tail.as_ref()
+    }
+
+    /// Returns an extension that lets the request's route override the
+    /// sampling decision for a trace that the proxy started, if any routes
+    /// have sampling rates.
+    pub(crate) fn route_sampling(&self, context: &TraceContext) -> Option<RouteSampling> {
+        if self.config.route_rates.is_empty() {
+            return None;
+        }
+        Some(RouteSampling(Arc::new(RouteSamplingInner {
+            sampler: self.clone(),
+            trace_id: context.trace_id.clone(),
+            sampled: AtomicBool::new(context.is_sampled()),
+        })))
+    }
+
+    fn route_rate(&self, kind: &str, name: &str) -> Option<f64> {
+        self.config.route_rates.iter().find_map(|(route, rate)| {
+            let (k, n) = route.split_once('/')?;
+            (k.eq_ignore_ascii_case(kind) && n == name).then_some(*rate)
+        })
+    }
+
+    fn is_active(&self) -> bool {
+        self.config.head_rate > 0.0
+            || !self.config.route_rates.is_empty()
+            || self.config.tail.is_some()
+    }
+
+    fn acquire(&self) -> bool {
+        let Some(limit) = self.limit.as_ref() else {
+            return true;
+        };
+        let mut limit = limit.lock();
+        let now = Instant::now();
+        if now.saturating_duration_since(limit.window) >= Duration::from_secs(1) {
+            limit.window = now;
+            limit.started = 0;
+        }
+        if limit.started < limit.max {
+            limit.started += 1;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+// === impl RouteSampling ===
+
+impl RouteSampling {
+    /// Indicates whether the trace is sampled after the request was routed.
+    pub(crate) fn is_sampled(&self) -> bool {
+        self.0.sampled.load(Ordering::Acquire)
+    }
+}
+
+/// Applies the sampling rate of the request's policy route to a trace that the
+/// proxy started for the request.
+///
+/// Only the first route that handles a request may change its sampling
+/// decision, so that a gateway's inbound and outbound routes do not both draw.
+pub fn sample_route<B>(req: &mut http::Request<B>, kind: &str, name: &str) {
+    let Some(RouteSampling(sampling)) = req.extensions_mut().remove::<RouteSampling>() else {
+        return;
+    };
+    let Some(rate) = sampling.sampler.route_rate(kind, name) else {
+        return;
+    };
+
+    // A trace that was already started holds a rate limit permit.
+    let was_sampled = sampling.sampled.load(Ordering::Acquire);
+    let sampled = draw(rate) && (was_sampled || sampling.sampler.acquire());
+    if sampled == was_sampled {
+        return;
+    }
+
+    tracing::debug!(route.kind = %kind, route.name = %name, sampled, "Route overrides sampling");
+    sampling.sampled.store(sampled, Ordering::Release);
+    propagation::set_sampled(req, sampled);
+    if sampled {
+        req.extensions_mut().insert(SampledTrace {
+            trace_id: sampling.trace_id.clone(),
+        });
+    } else {
+        req.extensions_mut().remove::<SampledTrace>();
+    }
+}
+
+fn draw(rate: f64) -> bool {
+    match rate {
+        r if r >= 1.0 => true,
+        r if r <= 0.0 => false,
+        r => rand::random_bool(r),
+    }
+}
+
+// === impl TailConfig ===
+
+impl TailConfig {
+    /// Returns the reason that an unsampled span should be exported, if any.
+    pub(crate) fn export_reason(
+        &self,
+        status: http::StatusCode,
+        latency: Duration,
+    ) -> Option<&'static str> {
+        if self.min_status.is_some_and(|min| status >= min) {
+            return Some("error");
+        }
+        if self.min_latency.is_some_and(|min| latency >= min) {
+            return Some("latency");
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn head_sampling() {
+        let sampler = Sampler::default();
+        assert!(sampler.start_trace().is_none());
+
+        let sampler = Sampler::new(SamplerConfig {
+            head_rate: 1.0,
+            ..Default::default()
+        });
+        let ctx = sampler.start_trace().expect("must trace");
+        assert!(ctx.is_sampled());
+        assert_eq!(ctx.trace_id.as_ref().len(), 16);
+        assert!(ctx.parent_id.as_ref().is_empty());
+        assert!(sampler.route_sampling(&ctx).is_none());
+    }
+
+    #[test]
+    fn route_sampling() {
+        let sampler = Sampler::new(SamplerConfig {
+            head_rate: 1.0,
+            route_rates: vec![
+                ("httproute/health".to_owned(), 0.0),
+                ("GRPCRoute/checkout".to_owned(), 1.0),
+            ],
+            ..Default::default()
+        });
+
+        let routed = |kind: &str, name: &str, head_sampled: bool| {
+            let mut ctx = sampler.start_trace().expect("must trace");
+            ctx.flags = Flags(head_sampled as u8);
+            let sampling = sampler.route_sampling(&ctx).expect("must sample routes");
+            let mut req = http::Request::new(());
+            crate::propagation::increment_span_id(&mut req, &ctx);
+            if head_sampled {
+                req.extensions_mut().insert(SampledTrace {
+                    trace_id: ctx.trace_id.clone(),
+                });
+            }
+            req.extensions_mut().insert(sampling.clone());
+
+            sample_route(&mut req, kind, name);
+            // Only the first route may override the decision.
+            sample_route(&mut req, "httproute", "health");
+
+            let header = crate::propagation::unpack_trace_context(&req).unwrap();
+            assert_eq!(header.is_sampled(), sampling.is_sampled());
+            assert_eq!(
+                req.extensions().get::<SampledTrace>().is_some(),
+                sampling.is_sampled()
+            );
+            sampling.is_sampled()
+        };
+
+        assert!(!routed("HTTPRoute", "health", true));
+        assert!(routed("grpcroute", "checkout", false));
+        assert!(routed("httproute", "books", true));
+        assert!(!routed("httproute", "books", false));
+    }
+
+    #[tokio::test(flavor = "current_thread", start_paused = true)]
+    async fn rate_limit() {
+        let sampler = Sampler::new(SamplerConfig {
+            head_rate: 1.0,
+            max_traces_per_second: Some(2),
+            ..Default::default()
+        });
+        let sampled = || sampler.start_trace().unwrap().is_sampled();
+        assert!(sampled());
+        assert!(sampled());
+        assert!(!sampled());
+
+        tokio::time::advance(Duration::from_secs(1)).await;
+        assert!(sampled());
+    }
+
+    #[test]
+    fn tail_reasons() {
+        let tail = TailConfig {
+            min_status: Some(http::StatusCode::INTERNAL_SERVER_ERROR),
+            min_latency: Some(Duration::from_secs(1)),
+        };
+        let fast = Duration::from_millis(10);
+        assert_eq!(tail.export_reason(http::StatusCode::OK, fast), None);
+        assert_eq!(
+            tail.export_reason(http::StatusCode::BAD_GATEWAY, fast),
+            Some("error")
+        );
+        assert_eq!(
+            tail.export_reason(http::StatusCode::OK, Duration::from_secs(2)),
+            Some("latency")
+        );
+    }
+}
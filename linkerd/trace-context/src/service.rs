@@ -1,4 +1,4 @@
-use crate::{propagation, Span, SpanSink};
+use crate::{propagation, SampledTrace, Span, SpanSink};
 use futures::{future::Either, prelude::*};
 use http::Uri;
 use linkerd_stack::layer;
@@ -12,15 +12,21 @@ use std::{
 };
 use tracing::{debug, info, trace};
 
+/// The span label that describes why the proxy exported a span that was not
+/// sampled by its caller.
+const SAMPLING_REASON: &str = "linkerd.sampling.reason";
+
 /// A layer that adds distributed tracing instrumentation.
 ///
 /// This layer reads the `traceparent` HTTP header from the request. If this
-/// header is absent, the request is fowarded unmodified.  If the header is
+/// header is absent, the request is fowarded unmodified unless the sink's
+/// [`Sampler`](crate::Sampler) starts a new trace.  If the header is
 /// present, a new span will be started in the current trace by creating a new
 /// random span id setting it into the `traceparent` header before forwarding
 /// the request. If the sampled bit of the header was set, we emit metadata
 /// about the span to the given SpanSink when the span is complete, i.e. when
-/// we receive the response.
+/// we receive the response. Otherwise, if the sampler captures failed and slow
+/// requests, the span is only emitted if its response crosses a threshold.
 #[derive(Clone, Debug)]
 pub struct TraceContext<K, S> {
     inner: S,
@@ -132,6 +138,15 @@ impl<K: Clone, S> TraceContext<K, S> {
         );
         labels
     }
+
+    /// Describes a request that failed without a response. The error's type
+    /// is not known, so it is described by the conventional `_OTHER` value.
+    fn add_error_labels(
+        mut labels: HashMap<&'static str, String>,
+    ) -> HashMap<&'static str, String> {
+        labels.insert(semconv::trace::ERROR_TYPE, "_OTHER".to_string());
+        labels
+    }
 }
 
 struct UrlLabel<'a>(&'a Uri);
@@ -188,36 +203,99 @@ where
 
     fn call(&mut self, mut req: http::Request<ReqB>) -> Self::Future {
         if self.sink.is_enabled() {
-            if let Some(context) = propagation::unpack_trace_context(&req) {
+            let baggage = self
+                .sink
+                .baggage()
+                .map(|config| propagation::apply_baggage(config, &mut req))
+                .unwrap_or_default();
+
+            // Continue the request's trace or, if it has none, let the proxy
+            // decide whether to start one.
+            let context = match propagation::unpack_trace_context(&req) {
+                Some(context) => Some((context, None)),
+                None => self
+                    .sink
+                    .sampler()
+                    .and_then(|s| s.start_trace())
+                    .map(|context| (context, Some("head"))),
+            };
+
+            if let Some((context, head_reason)) = context {
                 // Update the trace ID if the request set one and the proxy is configured to emit
                 // spans.
                 let span_id = propagation::increment_span_id(&mut req, &context);
                 debug!(?span_id, sampled = context.is_sampled());
-
                 if context.is_sampled() {
-                    // If the request has been marked for sampling, record its metadata.
-                    let start = SystemTime::now();
-                    let req_labels = Self::request_labels(&req);
-                    let mut sink = self.sink.clone();
-                    let span_name = req.uri().path().to_owned();
-                    return Either::Right(Box::pin(self.inner.call(req).map_ok(move |rsp| {
-                        // Emit the completed span with the response metadata.
-                        let span = Span {
-                            span_id,
-                            trace_id: context.trace_id,
-                            parent_id: context.parent_id,
-                            span_name,
-                            start,
-                            end: SystemTime::now(),
-                            labels: Self::add_response_labels(req_labels, &rsp),
-                        };
-                        trace!(?span);
-                        if let Err(error) = sink.try_send(span) {
-                            info!(%error, "Span dropped");
-                        }
-                        rsp
-                    })));
+                    req.extensions_mut().insert(SampledTrace {
+                        trace_id: context.trace_id.clone(),
+                    });
+                }
+
+                // The request's route may change the sampling decision for a
+                // trace that the proxy started.
+                let route_sampling = head_reason
+                    .and(self.sink.sampler())
+                    .and_then(|s| s.route_sampling(&context));
+                if let Some(sampling) = route_sampling.clone() {
+                    req.extensions_mut().insert(sampling);
+                }
+
+                // If the request has been marked for sampling, record its
+                // metadata. Otherwise, record it in case it fails or is slow.
+                let tail = self.sink.sampler().and_then(|s| s.tail()).cloned();
+                if tail.is_none() && route_sampling.is_none() && !context.is_sampled() {
+                    return Either::Left(self.inner.call(req));
                 }
+
+                let start = SystemTime::now();
+                let mut req_labels = Self::request_labels(&req);
+                let mut sink = self.sink.clone();
+                let span_name = req.uri().path().to_owned();
+                return Either::Right(Box::pin(self.inner.call(req).map(move |res| {
+                    let end = SystemTime::now();
+                    let sampled = route_sampling
+                        .as_ref()
+                        .map_or(context.is_sampled(), |s| s.is_sampled());
+                    let reason = if sampled {
+                        head_reason
+                    } else {
+                        let latency = end.duration_since(start).unwrap_or_default();
+                        let reason = tail.as_ref().and_then(|tail| match &res {
+                            // Requests that fail without a response are
+                            // always exported.
+                            Err(_) => Some("error"),
+                            Ok(rsp) => tail.export_reason(rsp.status(), latency),
+                        });
+                        match reason {
+                            Some(reason) => Some(reason),
+                            None => return res,
+                        }
+                    };
+                    if let Some(reason) = reason {
+                        req_labels.insert(SAMPLING_REASON, reason.to_owned());
+                    }
+
+                    // Emit the completed span with the response metadata.
+                    let labels = match &res {
+                        Ok(rsp) => Self::add_response_labels(req_labels, rsp),
+                        Err(_) => Self::add_error_labels(req_labels),
+                    };
+                    let span = Span {
+                        span_id,
+                        trace_id: context.trace_id,
+                        parent_id: context.parent_id,
+                        span_name,
+                        start,
+                        end,
+                        labels,
+                        baggage,
+                    };
+                    trace!(?span);
+                    if let Err(error) = sink.try_send(span) {
+                        info!(%error, "Span dropped");
+                    }
+                    res
+                })));
             }
         }
 
@@ -229,7 +307,7 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::Id;
+    use crate::{Id, Sampler, SamplerConfig, TailConfig};
     use bytes::Bytes;
     use http::HeaderMap;
     use linkerd_error::Error;
@@ -361,39 +439,225 @@ mod tests {
         )
     }
 
+    #[tokio::test(flavor = "current_thread")]
+    async fn proxy_sampling() {
+        let _trace = linkerd_tracing::test::trace_init();
+
+        // Requests without a trace context are traced when head sampled.
+        let head = Sampler::new(SamplerConfig {
+            head_rate: 1.0,
+            ..Default::default()
+        });
+        let (req_headers, span) = send_with_sampler(
+            http::Request::new(BoxBody::empty()),
+            head,
+            http::StatusCode::OK,
+        )
+        .await;
+        let traceparent = req_headers
+            .get(W3C_TRACEPARENT_HEADER)
+            .expect("must start a trace")
+            .to_str()
+            .unwrap();
+        assert!(traceparent.ends_with("-01"), "{traceparent}");
+        let span = span.expect("must export span");
+        assert!(span.parent_id.as_ref().is_empty());
+        assert_eq!(span.labels[SAMPLING_REASON], "head");
+
+        // Unsampled requests are only exported when they fail.
+        let tail = Sampler::new(SamplerConfig {
+            tail: Some(TailConfig {
+                min_status: Some(http::StatusCode::INTERNAL_SERVER_ERROR),
+                min_latency: None,
+            }),
+            ..Default::default()
+        });
+        let unsampled = || {
+            http::Request::builder()
+                .header(
+                    W3C_TRACEPARENT_HEADER,
+                    "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-00",
+                )
+                .body(BoxBody::empty())
+                .expect("request")
+        };
+        let (_, span) = send_with_sampler(unsampled(), tail.clone(), http::StatusCode::OK).await;
+        assert!(span.is_none());
+        let (_, span) =
+            send_with_sampler(unsampled(), tail, http::StatusCode::SERVICE_UNAVAILABLE).await;
+        let span = span.expect("must export failed span");
+        assert_eq!(span.labels[SAMPLING_REASON], "error");
+        assert_eq!(
+            span.parent_id,
+            Id::from(Bytes::from(
+                hex::decode("00f067aa0ba902b7").expect("decode")
+            )),
+        );
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn tail_sampling_exports_errors() {
+        let _trace = linkerd_tracing::test::trace_init();
+
+        let tail = Sampler::new(SamplerConfig {
+            tail: Some(TailConfig {
+                min_status: Some(http::StatusCode::INTERNAL_SERVER_ERROR),
+                min_latency: None,
+            }),
+            ..Default::default()
+        });
+        let req = http::Request::builder()
+            .header(
+                W3C_TRACEPARENT_HEADER,
+                "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-00",
+            )
+            .body(BoxBody::empty())
+            .expect("request");
+        let (_, span) = send(req, tail, |_| Err("connection refused".into())).await;
+        let span = span.expect("must export failed span");
+        assert_eq!(span.labels[SAMPLING_REASON], "error");
+        assert_eq!(span.labels[semconv::trace::ERROR_TYPE], "_OTHER");
+        assert!(!span
+            .labels
+            .contains_key(semconv::trace::HTTP_RESPONSE_STATUS_CODE));
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn route_sampling() {
+        let _trace = linkerd_tracing::test::trace_init();
+
+        let sampler = Sampler::new(SamplerConfig {
+            head_rate: 0.0,
+            route_rates: vec![("httproute/checkout".to_owned(), 1.0)],
+            ..Default::default()
+        });
+        let routed = |route: &'static str| {
+            send(
+                http::Request::new(BoxBody::empty()),
+                sampler.clone(),
+                move |req| {
+                    crate::sample_route(req, "HTTPRoute", route);
+                    Ok(http::Response::default())
+                },
+            )
+        };
+
+        // The route's rate overrides the head sampling rate.
+        let (req_headers, span) = routed("checkout").await;
+        let traceparent = req_headers[W3C_TRACEPARENT_HEADER].to_str().unwrap();
+        assert!(traceparent.ends_with("-01"), "{traceparent}");
+        let span = span.expect("must export span");
+        assert_eq!(span.labels[SAMPLING_REASON], "head");
+
+        let (req_headers, span) = routed("books").await;
+        let traceparent = req_headers[W3C_TRACEPARENT_HEADER].to_str().unwrap();
+        assert!(traceparent.ends_with("-00"), "{traceparent}");
+        assert!(span.is_none());
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn sampled_trace_extension() {
+        let _trace = linkerd_tracing::test::trace_init();
+
+        let (span_tx, _span_rx) = mpsc::channel(2);
+        let (inner, mut handle) =
+            tower_test::mock::pair::<http::Request<BoxBody>, http::Response<BoxBody>>();
+        let mut stack =
+            TraceContext::<TestSink, _>::layer(TestSink(span_tx, Sampler::default())).layer(inner);
+        handle.allow(2);
+
+        for (traceparent, sampled) in [
+            (
+                "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+                true,
+            ),
+            (
+                "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-00",
+                false,
+            ),
+        ] {
+            let req = http::Request::builder()
+                .header(W3C_TRACEPARENT_HEADER, traceparent)
+                .body(BoxBody::empty())
+                .expect("request");
+            let stack = stack.ready().await.expect("ready");
+            let (_, trace): (http::Response<BoxBody>, _) = tokio::join! {
+                stack.call(req).map(|res| res.expect("must not fail")),
+                handle.next_request().map(|req| {
+                    let (req, tx) = req.expect("request");
+                    tx.send_response(http::Response::default());
+                    req.extensions().get::<SampledTrace>().cloned()
+                }),
+            };
+            assert_eq!(
+                trace.map(|t| t.trace_id.to_string()),
+                sampled.then(|| "4bf92f3577b34da6a3ce929d0e0e4736".to_string()),
+            );
+        }
+    }
+
     async fn send_mock_request(req: http::Request<BoxBody>) -> (HeaderMap, Span) {
+        let (req_headers, span) =
+            send_with_sampler(req, Sampler::default(), http::StatusCode::OK).await;
+        (req_headers, span.expect("must have exported span"))
+    }
+
+    async fn send_with_sampler(
+        req: http::Request<BoxBody>,
+        sampler: Sampler,
+        status: http::StatusCode,
+    ) -> (HeaderMap, Option<Span>) {
+        send(req, sampler, |_| {
+            let mut rsp = http::Response::default();
+            *rsp.status_mut() = status;
+            Ok(rsp)
+        })
+        .await
+    }
+
+    /// Sends a request through the tracing layer to an inner service that
+    /// handles it with `respond`.
+    async fn send(
+        req: http::Request<BoxBody>,
+        sampler: Sampler,
+        respond: impl FnOnce(&mut http::Request<BoxBody>) -> Result<http::Response<BoxBody>, Error>,
+    ) -> (HeaderMap, Option<Span>) {
         let (span_tx, mut span_rx) = mpsc::channel(1);
 
         let (inner, mut handle) =
             tower_test::mock::pair::<http::Request<BoxBody>, http::Response<BoxBody>>();
-        let mut stack = TraceContext::<TestSink, _>::layer(TestSink(span_tx)).layer(inner);
+        let mut stack = TraceContext::<TestSink, _>::layer(TestSink(span_tx, sampler)).layer(inner);
         handle.allow(1);
 
         let stack = stack.ready().await.expect("ready");
 
-        let (_, req_headers): (http::Response<BoxBody>, _) = tokio::join! {
-            stack.call(req).map(|res| res.expect("must not fail")),
+        let (_, req_headers) = tokio::join! {
+            stack.call(req),
             handle.next_request().map(|req| {
-                let (req, tx) = req.expect("request");
-                tx.send_response(http::Response::default());
+                let (mut req, tx) = req.expect("request");
+                match respond(&mut req) {
+                    Ok(rsp) => tx.send_response(rsp),
+                    Err(error) => tx.send_error(error),
+                }
                 req.headers().clone()
             }),
         };
 
-        (
-            req_headers,
-            span_rx.try_recv().expect("must have exported span"),
-        )
+        (req_headers, span_rx.try_recv().ok())
     }
 
     #[derive(Clone)]
-    struct TestSink(mpsc::Sender<Span>);
+    struct TestSink(mpsc::Sender<Span>, Sampler);
 
     impl SpanSink for TestSink {
         fn is_enabled(&self) -> bool {
             true
         }
 
+        fn sampler(&self) -> Option<&Sampler> {
+            Some(&self.1)
+        }
+
         fn try_send(&mut self, span: Span) -> Result<(), Error> {
             self.0.try_send(span)?;
             Ok(())
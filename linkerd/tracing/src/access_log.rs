@@ -1,9 +1,17 @@
-use std::fmt;
+use std::{
+    fmt,
+    fs::{self, File, OpenOptions},
+    io::{self, BufWriter, Write},
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+};
 use tracing::{field, span, Id, Level, Metadata, Subscriber};
 use tracing_subscriber::{
-    field::RecordFields,
     filter::{FilterFn, Filtered},
-    fmt::{format, FormatFields, FormattedFields},
     layer::{Context, Layer},
     registry::LookupSpan,
 };
@@ -13,14 +21,29 @@ pub const TRACE_TARGET: &str = "_access_log";
 pub(super) type AccessLogLayer<S> =
     Filtered<Box<dyn Layer<S> + Send + Sync + 'static>, FilterFn, S>;
 
-#[derive(Default)]
-pub(super) struct Writer<F = ApacheCommon> {
-    formatter: F,
-}
+/// Configures how access logs are formatted, sampled, and written.
+#[derive(Clone, Debug)]
+pub(super) struct Config {
+    pub(super) format: Format,
+
+    /// The fields to include in each access log. When unset, the format's
+    /// default fields are written.
+    pub(super) fields: Option<Arc<[String]>>,
+
+    /// Rules that determine the probability with which each request is
+    /// logged. The first matching rule applies; requests that match no rule
+    /// are always logged.
+    pub(super) sample: Arc<[SampleRule]>,
 
-#[derive(Default)]
-pub(super) struct ApacheCommon {
-    _p: (),
+    /// A file to which access logs are written instead of stderr.
+    pub(super) path: Option<PathBuf>,
+
+    /// The size at which the access log file is rotated. Files are not rotated
+    /// when this is zero.
+    pub(super) max_bytes: u64,
+
+    /// The number of rotated files to retain.
+    pub(super) max_files: usize,
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -29,19 +52,85 @@ pub(super) enum Format {
     Json,
 }
 
-struct ApacheCommonVisitor<'writer> {
-    res: fmt::Result,
-    writer: format::Writer<'writer>,
+#[derive(Clone, Debug, PartialEq)]
+pub(super) struct SampleRule {
+    matches: SampleMatch,
+    probability: f64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum SampleMatch {
+    Any,
+    /// Matches responses with statuses in the class, e.g. `5` for `5xx`.
+    StatusClass(u16),
+    Route(String),
+}
+
+struct Writer {
+    config: Config,
+    sink: Sink,
+}
+
+/// Where formatted access logs are written.
+///
+/// Access logs are not yet exported as OTLP log records; that requires
+/// threading records from this layer to an exporter that shares the trace
+/// collector's control-plane client.
+enum Sink {
+    Stderr,
+    File(FileSink),
+}
+
+/// Sends access log lines to a dedicated thread that writes them to a file,
+/// so that writing access logs never blocks the proxy's runtime.
+struct FileSink {
+    tx: mpsc::SyncSender<String>,
+    /// The number of lines dropped because the writer fell behind.
+    dropped: Arc<AtomicU64>,
+}
+
+struct RotatingFile {
+    path: PathBuf,
+    max_bytes: u64,
+    max_files: usize,
+    file: BufWriter<File>,
+    written: u64,
 }
 
-pub(super) fn build<S>(format: Format) -> AccessLogLayer<S>
+/// The number of access log lines that may be buffered before lines are
+/// dropped.
+const FILE_SINK_CAPACITY: usize = 10_000;
+
+/// The fields recorded on an access log span, in the order they were recorded.
+#[derive(Debug, Default)]
+struct Fields(Vec<(&'static str, Value)>);
+
+#[derive(Debug)]
+enum Value {
+    Str(String),
+    U64(u64),
+    I64(i64),
+    F64(f64),
+    Bool(bool),
+}
+
+pub(super) fn build<S>(config: Config) -> AccessLogLayer<S>
 where
     S: Subscriber + for<'span> LookupSpan<'span>,
 {
-    let writer: Box<dyn Layer<S> + Send + Sync + 'static> = match format {
-        Format::Apache => Box::<Writer<ApacheCommon>>::default(),
-        Format::Json => Box::<Writer<format::JsonFields>>::default(),
+    let sink = match config.path.as_ref() {
+        None => Sink::Stderr,
+        Some(path) => match RotatingFile::open(path.clone(), config.max_bytes, config.max_files)
+            .and_then(FileSink::spawn)
+        {
+            Ok((file, _)) => Sink::File(file),
+            Err(error) => {
+                eprintln!("Failed to open access log {}: {error}", path.display());
+                Sink::Stderr
+            }
+        },
     };
+    let writer: Box<dyn Layer<S> + Send + Sync + 'static> = Box::new(Writer { config, sink });
 
     writer.with_filter(
         FilterFn::new(
@@ -52,58 +141,65 @@ where
     )
 }
 
+// === impl Config ===
+
+impl Config {
+    pub(super) fn new(format: Format) -> Self {
+        Self {
+            format,
+            fields: None,
+            sample: Arc::new([]),
+            path: None,
+            max_bytes: 0,
+            max_files: 0,
+        }
+    }
+}
+
 // === impl Writer ===
 
-impl<S, F> Layer<S> for Writer<F>
+impl<S> Layer<S> for Writer
 where
     S: Subscriber + for<'span> LookupSpan<'span>,
-    F: for<'writer> FormatFields<'writer> + 'static,
 {
     fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
         let span = ctx.span(id).expect("Span not found, this is a bug");
         let mut extensions = span.extensions_mut();
-
-        if extensions.get_mut::<FormattedFields<F>>().is_none() {
-            let mut fields = FormattedFields::<F>::new(String::new());
-            if self
-                .formatter
-                .format_fields(fields.as_writer(), attrs)
-                .is_ok()
-            {
-                extensions.insert(fields);
-            }
+        if extensions.get_mut::<Fields>().is_none() {
+            let mut fields = Fields::default();
+            attrs.record(&mut fields);
+            extensions.insert(fields);
         }
     }
 
     fn on_record(&self, id: &Id, values: &span::Record<'_>, ctx: Context<'_, S>) {
         let span = ctx.span(id).expect("Span not found, this is a bug");
         let mut extensions = span.extensions_mut();
-        if let Some(fields) = extensions.get_mut::<FormattedFields<F>>() {
-            let _ = self.formatter.add_fields(fields, values);
-            return;
-        }
-
-        let mut fields = FormattedFields::<F>::new(String::new());
-        if self
-            .formatter
-            .format_fields(fields.as_writer(), values)
-            .is_ok()
-        {
-            extensions.insert(fields);
+        match extensions.get_mut::<Fields>() {
+            Some(fields) => values.record(fields),
+            None => {
+                let mut fields = Fields::default();
+                values.record(&mut fields);
+                extensions.insert(fields);
+            }
         }
     }
 
     fn on_close(&self, id: Id, ctx: Context<'_, S>) {
         if let Some(span) = ctx.span(&id) {
-            if let Some(fields) = span.extensions().get::<FormattedFields<F>>() {
-                eprintln!("{}", fields.fields);
+            if let Some(fields) = span.extensions().get::<Fields>() {
+                if self.is_sampled(fields) {
+                    self.sink.write(&self.format(fields));
+                }
             }
         }
     }
 }
 
-impl ApacheCommon {
-    const SKIPPED_FIELDS: &'static [&'static str] = &[
+impl Writer {
+    /// Fields that are not written in the Apache common format unless they
+    /// are explicitly configured.
+    const APACHE_SKIPPED_FIELDS: &'static [&'static str] = &[
         "trace_id",
         "request_bytes",
         "total_ns",
@@ -111,43 +207,293 @@ impl ApacheCommon {
         "response_bytes",
         "user_agent",
         "host",
+        "server",
+        "route",
+        "backend",
+        "endpoint",
+        "retries",
+        "response_flags",
     ];
+
+    fn is_sampled(&self, fields: &Fields) -> bool {
+        let Some(rule) = self.config.sample.iter().find(|r| r.matches(fields)) else {
+            return true;
+        };
+        match rule.probability {
+            p if p >= 1.0 => true,
+            p if p <= 0.0 => false,
+            p => rand::random_bool(p),
+        }
+    }
+
+    fn selected<'f>(
+        &'f self,
+        fields: &'f Fields,
+    ) -> impl Iterator<Item = (&'static str, &'f Value)> {
+        fields.0.iter().filter_map(move |(name, value)| {
+            let selected = match self.config.fields.as_ref() {
+                Some(selected) => selected.iter().any(|f| f == name),
+                None => {
+                    !matches!(self.config.format, Format::Apache)
+                        || !Self::APACHE_SKIPPED_FIELDS.contains(name)
+                }
+            };
+            selected.then_some((*name, value))
+        })
+    }
+
+    fn format(&self, fields: &Fields) -> String {
+        use fmt::Write;
+
+        let mut line = String::new();
+        match self.config.format {
+            Format::Apache => {
+                for (name, val) in self.selected(fields) {
+                    let _ = match name {
+                        "timestamp" => write!(&mut line, " [{val}]"),
+                        "client.addr" => write!(&mut line, "{val}"),
+                        "client.id" => write!(&mut line, " {val} -"),
+                        "method" => write!(&mut line, " \"{val}"),
+                        "version" => write!(&mut line, " {val}\""),
+                        _ => write!(&mut line, " {val}"),
+                    };
+                }
+            }
+            Format::Json => {
+                line.push('{');
+                for (i, (name, val)) in self.selected(fields).enumerate() {
+                    if i > 0 {
+                        line.push(',');
+                    }
+                    let val = match val {
+                        Value::Str(s) => serde_json::Value::from(s.as_str()),
+                        Value::U64(n) => serde_json::Value::from(*n),
+                        Value::I64(n) => serde_json::Value::from(*n),
+                        Value::F64(n) => serde_json::Value::from(*n),
+                        Value::Bool(b) => serde_json::Value::from(*b),
+                    };
+                    let _ = write!(&mut line, "{}:{val}", serde_json::Value::from(name));
+                }
+                line.push('}');
+            }
+        }
+        line
+    }
+}
+
+// === impl Sink ===
+
+impl Sink {
+    fn write(&self, line: &str) {
+        match self {
+            Self::Stderr => eprintln!("{line}"),
+            Self::File(file) => file.send(line),
+        }
+    }
+}
+
+// === impl FileSink ===
+
+impl FileSink {
+    fn spawn(mut file: RotatingFile) -> io::Result<(Self, thread::JoinHandle<()>)> {
+        let (tx, rx) = mpsc::sync_channel::<String>(FILE_SINK_CAPACITY);
+        let dropped = Arc::new(AtomicU64::new(0));
+        let handle = thread::Builder::new().name("access-log".into()).spawn({
+            let dropped = dropped.clone();
+            move || {
+                while let Ok(line) = rx.recv() {
+                    // Write all pending lines before flushing so that bursts
+                    // are written together.
+                    for line in std::iter::once(line).chain(rx.try_iter()) {
+                        if let Err(error) = file.write_line(&line) {
+                            eprintln!("Failed to write access log: {error}");
+                        }
+                    }
+                    if let Err(error) = file.flush() {
+                        eprintln!("Failed to write access log: {error}");
+                    }
+                    let dropped = dropped.swap(0, Ordering::Relaxed);
+                    if dropped > 0 {
+                        eprintln!("Dropped {dropped} access logs");
+                    }
+                }
+                let _ = file.flush();
+            }
+        })?;
+        Ok((Self { tx, dropped }, handle))
+    }
+
+    fn send(&self, line: &str) {
+        match self.tx.try_send(line.to_owned()) {
+            Ok(()) => {}
+            Err(mpsc::TrySendError::Full(_)) => {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+            }
+            Err(mpsc::TrySendError::Disconnected(_)) => {
+                eprintln!("Failed to write access log: writer stopped");
+            }
+        }
+    }
 }
 
-impl FormatFields<'_> for ApacheCommon {
-    fn format_fields<R: RecordFields>(&self, writer: format::Writer<'_>, fields: R) -> fmt::Result {
-        let mut visitor = ApacheCommonVisitor {
-            writer,
-            res: Ok(()),
+// === impl RotatingFile ===
+
+impl RotatingFile {
+    fn open(path: PathBuf, max_bytes: u64, max_files: usize) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let written = file.metadata()?.len();
+        Ok(Self {
+            path,
+            max_bytes,
+            max_files,
+            file: BufWriter::new(file),
+            written,
+        })
+    }
+
+    fn write_line(&mut self, line: &str) -> io::Result<()> {
+        let sz = line.len() as u64 + 1;
+        if self.max_bytes > 0 && self.written > 0 && self.written + sz > self.max_bytes {
+            self.rotate()?;
+        }
+        writeln!(&mut self.file, "{line}")?;
+        self.written += sz;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+
+    /// Moves the current file to `<path>.1`, shifting older files up to
+    /// `<path>.<max_files>`, and starts a new file.
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        let rotated = |n: usize| {
+            let mut path = self.path.clone().into_os_string();
+            path.push(format!(".{n}"));
+            PathBuf::from(path)
         };
-        fields.record(&mut visitor);
-        visitor.res
+        if self.max_files == 0 {
+            fs::remove_file(&self.path)?;
+        } else {
+            for n in (1..self.max_files).rev() {
+                // Older files may not exist yet.
+                let _ = fs::rename(rotated(n), rotated(n + 1));
+            }
+            fs::rename(&self.path, rotated(1))?;
+        }
+        self.file = BufWriter::new(File::create(&self.path)?);
+        self.written = 0;
+        Ok(())
     }
+}
 
-    #[inline]
-    fn add_fields(
-        &self,
-        current: &mut FormattedFields<Self>,
-        fields: &span::Record<'_>,
-    ) -> fmt::Result {
-        self.format_fields(current.as_writer(), fields)
+// === impl Fields ===
+
+impl Fields {
+    fn get(&self, name: &str) -> Option<&Value> {
+        self.0
+            .iter()
+            .rev()
+            .find(|(n, _)| *n == name)
+            .map(|(_, v)| v)
+    }
+
+    fn insert(&mut self, field: &field::Field, value: Value) {
+        match self.0.iter_mut().find(|(n, _)| *n == field.name()) {
+            Some((_, v)) => *v = value,
+            None => self.0.push((field.name(), value)),
+        }
     }
 }
 
-impl field::Visit for ApacheCommonVisitor<'_> {
+impl field::Visit for Fields {
     fn record_str(&mut self, field: &field::Field, val: &str) {
-        self.record_debug(field, &format_args!("{val}"))
+        self.insert(field, Value::Str(val.to_owned()))
     }
 
     fn record_debug(&mut self, field: &field::Field, val: &dyn fmt::Debug) {
-        self.res = match field.name() {
-            n if ApacheCommon::SKIPPED_FIELDS.contains(&n) => return,
-            "timestamp" => write!(&mut self.writer, " [{val:?}]"),
-            "client.addr" => write!(&mut self.writer, "{val:?}"),
-            "client.id" => write!(&mut self.writer, " {val:?} -"),
-            "method" => write!(&mut self.writer, " \"{val:?}"),
-            "version" => write!(&mut self.writer, " {val:?}\""),
-            _ => write!(&mut self.writer, " {val:?}"),
+        self.insert(field, Value::Str(format!("{val:?}")))
+    }
+
+    fn record_u64(&mut self, field: &field::Field, val: u64) {
+        self.insert(field, Value::U64(val))
+    }
+
+    fn record_i64(&mut self, field: &field::Field, val: i64) {
+        self.insert(field, Value::I64(val))
+    }
+
+    fn record_f64(&mut self, field: &field::Field, val: f64) {
+        self.insert(field, Value::F64(val))
+    }
+
+    fn record_bool(&mut self, field: &field::Field, val: bool) {
+        self.insert(field, Value::Bool(val))
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Str(s) => s.fmt(f),
+            Self::U64(n) => n.fmt(f),
+            Self::I64(n) => n.fmt(f),
+            Self::F64(n) => n.fmt(f),
+            Self::Bool(b) => b.fmt(f),
+        }
+    }
+}
+
+// === impl SampleRule ===
+
+impl SampleRule {
+    /// Parses a comma-separated list of `<match>=<probability>` rules, where
+    /// matches are status classes (`5xx`), routes (`route:<name>`), or `*`.
+    pub(super) fn parse_list(s: &str) -> Result<Vec<Self>, String> {
+        s.split(',')
+            .map(str::trim)
+            .filter(|rule| !rule.is_empty())
+            .map(|rule| {
+                let (matches, probability) = rule
+                    .rsplit_once('=')
+                    .ok_or_else(|| format!("expected <match>=<probability>: {rule}"))?;
+                let probability = probability
+                    .trim()
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|p| (0.0..=1.0).contains(p))
+                    .ok_or_else(|| format!("invalid probability: {probability}"))?;
+                let matches = match matches.trim() {
+                    "*" => SampleMatch::Any,
+                    m => match m.strip_prefix("route:") {
+                        Some(route) => SampleMatch::Route(route.to_owned()),
+                        None => m
+                            .strip_suffix("xx")
+                            .and_then(|c| c.parse::<u16>().ok())
+                            .filter(|c| (1..=5).contains(c))
+                            .map(SampleMatch::StatusClass)
+                            .ok_or_else(|| format!("invalid sample match: {m}"))?,
+                    },
+                };
+                Ok(Self {
+                    matches,
+                    probability,
+                })
+            })
+            .collect()
+    }
+
+    fn matches(&self, fields: &Fields) -> bool {
+        match &self.matches {
+            SampleMatch::Any => true,
+            SampleMatch::StatusClass(class) => {
+                matches!(fields.get("status"), Some(Value::U64(s)) if s / 100 == u64::from(*class))
+            }
+            SampleMatch::Route(route) => {
+                matches!(fields.get("route"), Some(Value::Str(r)) if r == route)
+            }
         }
     }
 }
@@ -164,3 +510,104 @@ impl std::str::FromStr for Format {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(status: u64) -> Fields {
+        Fields(vec![
+            ("client.addr", Value::Str("192.0.2.2:40000".to_owned())),
+            ("client.id", Value::Str("-".to_owned())),
+            ("method", Value::Str("GET".to_owned())),
+            ("uri", Value::Str("/ready".to_owned())),
+            ("version", Value::Str("HTTP/1.1".to_owned())),
+            ("route", Value::Str("httproute/ready".to_owned())),
+            ("status", Value::U64(status)),
+        ])
+    }
+
+    fn writer(config: Config) -> Writer {
+        Writer {
+            config,
+            sink: Sink::Stderr,
+        }
+    }
+
+    #[test]
+    fn formats_selected_fields() {
+        let apache = writer(Config::new(Format::Apache));
+        assert_eq!(
+            apache.format(&fields(503)),
+            "192.0.2.2:40000 - - \"GET /ready HTTP/1.1\" 503"
+        );
+
+        let json = writer(Config {
+            fields: Some(Arc::from(["status".to_owned(), "route".to_owned()])),
+            ..Config::new(Format::Json)
+        });
+        assert_eq!(
+            json.format(&fields(503)),
+            r#"{"route":"httproute/ready","status":503}"#
+        );
+    }
+
+    #[test]
+    fn samples() {
+        let rules = SampleRule::parse_list("5xx=1, route:httproute/ready=0, *=0").unwrap();
+        let sampled = writer(Config {
+            sample: rules.into(),
+            ..Config::new(Format::Json)
+        });
+        assert!(sampled.is_sampled(&fields(503)));
+        assert!(!sampled.is_sampled(&fields(200)));
+        assert!(writer(Config::new(Format::Json)).is_sampled(&fields(200)));
+
+        for invalid in ["5xx", "6xx=1", "2xx=2", "route=1"] {
+            assert!(SampleRule::parse_list(invalid).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn rotates_files() {
+        let dir = std::env::temp_dir().join(format!("access-log-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("access.log");
+
+        let mut file = RotatingFile::open(path.clone(), 8, 2).unwrap();
+        for line in ["aaaa", "bbbb", "cccc", "dddd"] {
+            file.write_line(line).unwrap();
+        }
+        file.flush().unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "dddd\n");
+        assert_eq!(
+            fs::read_to_string(dir.join("access.log.1")).unwrap(),
+            "cccc\n"
+        );
+        assert_eq!(
+            fs::read_to_string(dir.join("access.log.2")).unwrap(),
+            "bbbb\n"
+        );
+        assert!(!dir.join("access.log.3").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn writes_files_from_thread() {
+        let dir = std::env::temp_dir().join(format!("access-log-sink-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("access.log");
+
+        let file = RotatingFile::open(path.clone(), 0, 0).unwrap();
+        let (sink, handle) = FileSink::spawn(file).unwrap();
+        let sink = Sink::File(sink);
+        sink.write("aaaa");
+        sink.write("bbbb");
+        drop(sink);
+        handle.join().unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "aaaa\nbbbb\n");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
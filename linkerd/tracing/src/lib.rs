@@ -25,9 +25,16 @@ pub use tracing_subscriber::{registry, EnvFilter};
 const ENV_LOG_LEVEL: &str = "LINKERD2_PROXY_LOG";
 const ENV_LOG_FORMAT: &str = "LINKERD2_PROXY_LOG_FORMAT";
 const ENV_ACCESS_LOG: &str = "LINKERD2_PROXY_ACCESS_LOG";
+const ENV_ACCESS_LOG_FIELDS: &str = "LINKERD2_PROXY_ACCESS_LOG_FIELDS";
+const ENV_ACCESS_LOG_SAMPLE: &str = "LINKERD2_PROXY_ACCESS_LOG_SAMPLE";
+const ENV_ACCESS_LOG_PATH: &str = "LINKERD2_PROXY_ACCESS_LOG_PATH";
+const ENV_ACCESS_LOG_MAX_BYTES: &str = "LINKERD2_PROXY_ACCESS_LOG_MAX_BYTES";
+const ENV_ACCESS_LOG_MAX_FILES: &str = "LINKERD2_PROXY_ACCESS_LOG_MAX_FILES";
 
 const DEFAULT_LOG_LEVEL: &str = "warn,linkerd=info,hickory=error";
 const DEFAULT_LOG_FORMAT: &str = "PLAIN";
+const DEFAULT_ACCESS_LOG_MAX_BYTES: u64 = 100 * 1024 * 1024;
+const DEFAULT_ACCESS_LOG_MAX_FILES: usize = 5;
 
 #[derive(Debug, Default)]
 #[must_use]
@@ -35,7 +42,7 @@ pub struct Settings {
     filter: String,
     format: String,
     start_time: Option<time::Instant>,
-    access_log: Option<access_log::Format>,
+    access_log: Option<access_log::Config>,
     is_test: bool,
 }
 
@@ -71,7 +78,7 @@ impl Settings {
             format: std::env::var(ENV_LOG_FORMAT)
                 .ok()
                 .unwrap_or_else(|| DEFAULT_LOG_FORMAT.to_string()),
-            access_log: Self::access_log_format(),
+            access_log: Self::access_log_config(),
             start_time: Some(now),
             is_test: false,
         }
@@ -82,17 +89,52 @@ impl Settings {
             filter,
             format,
             start_time: None,
-            access_log: Self::access_log_format(),
+            access_log: Self::access_log_config(),
             is_test: true,
         }
     }
 
-    fn access_log_format() -> Option<access_log::Format> {
-        let env = std::env::var(ENV_ACCESS_LOG).ok()?;
-        match env.parse() {
-            Ok(format) => Some(format),
+    fn access_log_config() -> Option<access_log::Config> {
+        let format = Self::access_log_env(ENV_ACCESS_LOG, str::parse)?;
+        let mut config = access_log::Config::new(format);
+
+        if let Ok(fields) = std::env::var(ENV_ACCESS_LOG_FIELDS) {
+            let fields = fields
+                .split(',')
+                .map(str::trim)
+                .filter(|f| !f.is_empty())
+                .map(ToOwned::to_owned)
+                .collect::<Vec<_>>();
+            config.fields = Some(fields.into());
+        }
+        if let Some(rules) =
+            Self::access_log_env(ENV_ACCESS_LOG_SAMPLE, access_log::SampleRule::parse_list)
+        {
+            config.sample = rules.into();
+        }
+
+        if let Some(path) = std::env::var_os(ENV_ACCESS_LOG_PATH).filter(|p| !p.is_empty()) {
+            config.path = Some(path.into());
+            config.max_bytes = Self::access_log_env(ENV_ACCESS_LOG_MAX_BYTES, str::parse)
+                .unwrap_or(DEFAULT_ACCESS_LOG_MAX_BYTES);
+            config.max_files = Self::access_log_env(ENV_ACCESS_LOG_MAX_FILES, str::parse)
+                .unwrap_or(DEFAULT_ACCESS_LOG_MAX_FILES);
+        }
+
+        Some(config)
+    }
+
+    /// Parses an access log environment variable. Invalid values are reported
+    /// on stderr and ignored.
+    fn access_log_env<T, E: std::fmt::Display>(
+        name: &str,
+        parse: impl FnOnce(&str) -> Result<T, E>,
+    ) -> Option<T> {
+        let env = std::env::var(name).ok()?;
+        match parse(&env) {
+            Ok(value) => Some(value),
             Err(err) => {
-                eprintln!("Invalid {ENV_ACCESS_LOG}={env:?}: {err}");
+                eprintln!("Invalid {name}={env:?}: {err}");
                 None
             }
         }
@@ -186,7 +228,7 @@ impl Settings {
     /// The log dispatcher handles:
     ///
     /// - process diagnostic logging to stdout;
-    /// - optional access logging to stderr;
+    /// - optional access logging to stderr or a file;
     /// - if the `stream` feature is enabled, on-demand log streaming via the
     ///   returned `Handle`
     pub fn build(self) -> (Dispatch, Handle) {
@@ -0,0 +1,136 @@
+use futures::{ready, TryFuture};
+use linkerd_io as io;
+use linkerd_metrics::prom::{
+    self,
+    encoding::{EncodeLabelSet, EncodeLabelValue, LabelValueEncoder},
+};
+use linkerd_stack::{layer, MakeConnection, Service};
+use pin_project::pin_project;
+use std::{
+    future::Future,
+    net::SocketAddr,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// Counts established client connections by the address family of the server.
+///
+/// This describes which family was chosen when a connection may be made to
+/// any of several addresses, as with `HappyEyeballs`.
+#[derive(Clone, Debug, Default)]
+pub struct AddrFamilyMetrics {
+    connects: prom::Family<AddrFamilyLabels, prom::Counter>,
+}
+
+#[derive(Clone, Debug)]
+pub struct AddrFamilyClient<S> {
+    inner: S,
+    metrics: AddrFamilyMetrics,
+}
+
+#[pin_project]
+pub struct ConnectFuture<F> {
+    #[pin]
+    inner: F,
+    metrics: AddrFamilyMetrics,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+struct AddrFamilyLabels {
+    address_family: AddrFamily,
+}
+
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+enum AddrFamily {
+    Ipv4,
+    Ipv6,
+}
+
+// === impl AddrFamilyMetrics ===
+
+impl AddrFamilyMetrics {
+    pub fn register(registry: &mut prom::Registry) -> Self {
+        let connects = prom::Family::default();
+        registry.register(
+            "tcp_connect",
+            "Total count of established client connections, by address family",
+            connects.clone(),
+        );
+        Self { connects }
+    }
+
+    fn record(&self, server: SocketAddr) {
+        let address_family = if server.is_ipv6() {
+            AddrFamily::Ipv6
+        } else {
+            AddrFamily::Ipv4
+        };
+        self.connects
+            .get_or_create(&AddrFamilyLabels { address_family })
+            .inc();
+    }
+}
+
+// === impl AddrFamilyClient ===
+
+impl<S> AddrFamilyClient<S> {
+    pub fn layer(metrics: AddrFamilyMetrics) -> impl layer::Layer<S, Service = Self> + Clone {
+        layer::mk(move |inner| Self {
+            inner,
+            metrics: metrics.clone(),
+        })
+    }
+}
+
+impl<T, S> Service<T> for AddrFamilyClient<S>
+where
+    S: MakeConnection<T>,
+    S::Connection: io::PeerAddr,
+{
+    type Response = (S::Connection, S::Metadata);
+    type Error = S::Error;
+    type Future = ConnectFuture<S::Future>;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, target: T) -> Self::Future {
+        ConnectFuture {
+            inner: self.inner.connect(target),
+            metrics: self.metrics.clone(),
+        }
+    }
+}
+
+// === impl ConnectFuture ===
+
+impl<I, M, F> Future for ConnectFuture<F>
+where
+    F: TryFuture<Ok = (I, M)>,
+    I: io::PeerAddr,
+{
+    type Output = Result<(I, M), F::Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let (io, meta) = ready!(this.inner.try_poll(cx))?;
+        if let Ok(server) = io.peer_addr() {
+            this.metrics.record(server);
+        }
+        Poll::Ready(Ok((io, meta)))
+    }
+}
+
+// === impl AddrFamily ===
+
+impl EncodeLabelValue for AddrFamily {
+    fn encode(&self, enc: &mut LabelValueEncoder<'_>) -> std::fmt::Result {
+        use std::fmt::Write;
+        match self {
+            Self::Ipv4 => enc.write_str("ipv4"),
+            Self::Ipv6 => enc.write_str("ipv6"),
+        }
+    }
+}
@@ -2,6 +2,7 @@
 #![forbid(unsafe_code)]
 
 mod client;
+pub mod family;
 mod report;
 mod sensor;
 mod server;
@@ -4,7 +4,7 @@
 #![forbid(unsafe_code)]
 #![recursion_limit = "256"]
 
-use linkerd_app::{trace, BindTcp, Config, BUILD_INFO};
+use linkerd_app::{trace, BindTcp, BindWithProxyProtocol, Config, BUILD_INFO};
 use linkerd_signal as signal;
 use tokio::{sync::mpsc, time};
 use tracing::{debug, info, warn};
@@ -58,8 +58,8 @@ fn main() {
         let (shutdown_tx, mut shutdown_rx) = mpsc::unbounded_channel();
         let shutdown_grace_period = config.shutdown_grace_period;
 
-        let bind_in = BindTcp::with_orig_dst();
-        let bind_out = BindTcp::dual_with_orig_dst();
+        let bind_in = BindWithProxyProtocol::from(BindTcp::with_orig_dst());
+        let bind_out = BindWithProxyProtocol::from(BindTcp::dual_with_orig_dst());
         let app = match config
             .build(
                 bind_in,